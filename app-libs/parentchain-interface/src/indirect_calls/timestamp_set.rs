@@ -24,6 +24,7 @@ use itc_parentchain_indirect_calls_executor::{
 	IndirectDispatch,
 };
 use itp_stf_primitives::{traits::IndirectExecutor, types::TrustedOperation};
+use itp_time_utils::observe_parentchain_timestamp;
 use itp_types::{parentchain::ParentchainId, Moment};
 use log::info;
 
@@ -38,6 +39,8 @@ impl<Executor: IndirectExecutor<TrustedCallSigned, Error>, I: ParentchainInstanc
 {
 	fn dispatch(&self, executor: &Executor) -> Result<()> {
 		info!("Found TimestampSet extrinsic in block: now = {:?}", self.now);
+		// The block has been verified by the light client, so its timestamp bounds the host time.
+		observe_parentchain_timestamp(self.now.0);
 		let enclave_account_id = executor.get_enclave_account()?;
 		let parentchain_id = if TypeId::of::<I>() == TypeId::of::<Integritee>() {
 			ParentchainId::Integritee
@@ -21,7 +21,8 @@ use itp_api_client_types::Events;
 
 use itp_types::{
 	parentchain::{
-		BalanceTransfer, ExtrinsicFailed, ExtrinsicStatus, ExtrinsicSuccess, FilterEvents,
+		BalanceTransfer, ExchangeRateUpdated, ExtrinsicFailed, ExtrinsicStatus, ExtrinsicSuccess,
+		FilterEvents,
 	},
 	H256,
 };
@@ -82,4 +83,21 @@ impl FilterEvents for FilterableEvents {
 			})
 			.collect())
 	}
+
+	fn get_exchange_rate_updated_events(
+		&self,
+	) -> core::result::Result<Vec<ExchangeRateUpdated>, Self::Error> {
+		Ok(self
+			.to_events()
+			.iter()
+			.flatten() // flatten filters out the nones
+			.filter_map(|ev| match ev.as_event::<ExchangeRateUpdated>() {
+				Ok(maybe_event) => maybe_event,
+				Err(e) => {
+					log::error!("Could not decode event: {:?}", e);
+					None
+				},
+			})
+			.collect())
+	}
 }
@@ -22,7 +22,8 @@ use ita_stf::{Getter, TrustedCall, TrustedCallSigned};
 use itc_parentchain_indirect_calls_executor::error::Error;
 use itp_stf_primitives::{traits::IndirectExecutor, types::TrustedOperation};
 use itp_types::parentchain::{
-	AccountId, FilterEvents, HandleParentchainEvents, ParentchainError, ParentchainId,
+	AccountId, ExchangeRateUpdated, FilterEvents, HandleParentchainEvents, ParentchainError,
+	ParentchainId,
 };
 use itp_utils::hex::hex_encode;
 use log::*;
@@ -53,6 +54,27 @@ impl ParentchainEventHandler {
 
 		Ok(())
 	}
+
+	/// Mirrors the exchange rate of the teeracle into the shard state.
+	fn set_exchange_rate<Executor: IndirectExecutor<TrustedCallSigned, Error>>(
+		executor: &Executor,
+		event: &ExchangeRateUpdated,
+	) -> Result<(), Error> {
+		let shard = executor.get_default_shard();
+		let trusted_call = TrustedCall::teeracle_set_exchange_rate(
+			executor.get_enclave_account()?,
+			event.trading_pair.clone().into(),
+			event.exchange_rate.to_bits(),
+		);
+		let signed_trusted_call = executor.sign_call_with_self(&trusted_call, &shard)?;
+		let trusted_operation =
+			TrustedOperation::<TrustedCallSigned, Getter>::indirect_call(signed_trusted_call);
+
+		let encrypted_trusted_call = executor.encrypt(&trusted_operation.encode())?;
+		executor.submit_trusted_call(shard, encrypted_trusted_call);
+
+		Ok(())
+	}
 }
 
 impl<Executor> HandleParentchainEvents<Executor, TrustedCallSigned, Error>
@@ -82,6 +104,13 @@ where
 				})
 				.map_err(|_| ParentchainError::ShieldFundsFailure)?;
 		}
+
+		if let Ok(events) = events.get_exchange_rate_updated_events() {
+			events.iter().try_for_each(|event| {
+				info!("found exchange_rate_updated_event: {}", event);
+				Self::set_exchange_rate(executor, event)
+			})?;
+		}
 		Ok(())
 	}
 }
@@ -3,14 +3,35 @@
 // Import types from the crate root including the ones generated by the `construct_runtime!` macro.
 use crate::{Balances, Runtime, RuntimeEvent, Timestamp, NORMAL_DISPATCH_RATIO};
 use frame_support::{
-	pallet_prelude::Weight, parameter_types, weights::constants::WEIGHT_REF_TIME_PER_SECOND,
+	pallet_prelude::Weight,
+	parameter_types,
+	storage::types::{OptionQuery, StorageValue},
+	traits::{Get, StorageInstance},
+	weights::constants::WEIGHT_REF_TIME_PER_SECOND,
 };
 use sp_core::{H160, U256};
 use sp_runtime::traits::BlakeTwo256;
 
+pub use fees::{
+	evm_base_fee, gas_target, next_base_fee, record_evm_gas_used, BaseFeeGasPrice, BaseFeeInfo,
+	DealWithEvmFees, EvmBaseFeeInfo, EvmFeeAdapter, EvmFeeRecipient,
+	BASE_FEE_MAX_CHANGE_DENOMINATOR, ELASTICITY_MULTIPLIER, MIN_BASE_FEE,
+};
+pub use precompiles::{
+	EvmEnabledPrecompiles, EvmPrivateData, EvmPrivateDataCount, EvmStateReaders, PrivateData,
+	SgxPrecompiles, ENCLAVE_PRECOMPILES, EXCHANGE_RATES_PRECOMPILE, EXCHANGE_RATES_READ_GAS,
+	MAX_PRIVATE_DATA_LEN, PARENTCHAIN_PRECOMPILE, PARENTCHAIN_READ_GAS, PRIVATE_DATA_BASE_GAS,
+	PRIVATE_DATA_GAS_PER_WORD, PRIVATE_DATA_PRECOMPILE, RANDOMNESS_GAS, RANDOMNESS_PRECOMPILE,
+	STATE_READERS_PRECOMPILE, STATE_READERS_READ_GAS, STATE_READERS_WRITE_GAS,
+};
+
+mod fees;
+mod precompiles;
+
 pub use pallet_evm::{
-	AddressMapping, Call as EvmCall, EnsureAddressTruncated, FeeCalculator, GasWeightMapping,
-	HashedAddressMapping as GenericHashedAddressMapping, SubstrateBlockHashMapping,
+	AddressMapping, Call as EvmCall, EnsureAddressOrigin, EnsureAddressTruncated, FeeCalculator,
+	GasWeightMapping, HashedAddressMapping as GenericHashedAddressMapping,
+	SubstrateBlockHashMapping,
 };
 
 pub type HashedAddressMapping = GenericHashedAddressMapping<BlakeTwo256>;
@@ -19,14 +40,6 @@ pub type HashedAddressMapping = GenericHashedAddressMapping<BlakeTwo256>;
 pub const MAXIMUM_BLOCK_WEIGHT: Weight =
 	Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND.saturating_div(2), u64::MAX);
 
-// FIXME: For now just a random value.
-pub struct FixedGasPrice;
-impl FeeCalculator for FixedGasPrice {
-	fn min_gas_price() -> (U256, Weight) {
-		(1.into(), Weight::from_parts(1, 0u64))
-	}
-}
-
 /// Current approximation of the gas/s consumption considering
 /// EVM execution over compiled WASM (on 4.4Ghz CPU).
 /// Given the 500ms Weight, from which 75% only are used for transactions,
@@ -59,14 +72,36 @@ impl<T: From<H160>> AddressMapping<T> for IntoAddressMapping {
 	}
 }
 
+/// Chain id of the EVM, until the STF has set the one of the shard.
+pub const DEFAULT_CHAIN_ID: u64 = 42;
+
+pub struct EvmChainIdPrefix;
+impl StorageInstance for EvmChainIdPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmChain"
+	}
+	const STORAGE_PREFIX: &'static str = "ChainId";
+}
+
+/// Chain id of the EVM of the shard. The STF sets it, when it executes an Ethereum transaction
+/// (whose chain id has been checked against the shard) or simulates a call.
+pub type EvmChainId = StorageValue<EvmChainIdPrefix, u64, OptionQuery>;
+
+/// Chain id of the EVM, returned by the `CHAINID` opcode.
+pub struct ChainId;
+impl Get<u64> for ChainId {
+	fn get() -> u64 {
+		EvmChainId::get().unwrap_or(DEFAULT_CHAIN_ID)
+	}
+}
+
 parameter_types! {
-	pub const ChainId: u64 = 42;
 	pub BlockGasLimit: U256 = U256::from(NORMAL_DISPATCH_RATIO * MAXIMUM_BLOCK_WEIGHT.ref_time() / WEIGHT_PER_GAS);
-	//pub PrecompilesValue: FrontierPrecompiles<Runtime> = FrontierPrecompiles::<_>::new();
+	pub PrecompilesValue: SgxPrecompiles = SgxPrecompiles;
 }
 
 impl pallet_evm::Config for Runtime {
-	type FeeCalculator = FixedGasPrice;
+	type FeeCalculator = BaseFeeGasPrice;
 	type GasWeightMapping = FixedGasWeightMapping;
 	type BlockHashMapping = SubstrateBlockHashMapping<Self>;
 	type CallOrigin = EnsureAddressTruncated;
@@ -75,12 +110,12 @@ impl pallet_evm::Config for Runtime {
 	type Currency = Balances;
 	type RuntimeEvent = RuntimeEvent;
 	type Runner = pallet_evm::runner::stack::Runner<Self>;
-	type PrecompilesType = ();
-	type PrecompilesValue = ();
+	type PrecompilesType = SgxPrecompiles;
+	type PrecompilesValue = PrecompilesValue;
 	type ChainId = ChainId;
-	type OnChargeTransaction = ();
+	type OnChargeTransaction = EvmFeeAdapter;
 	type BlockGasLimit = BlockGasLimit;
-	type FindAuthor = (); // Currently not available. Would need some more thoughts how prioritisation fees could be handled.
+	type FindAuthor = (); // Currently not available. Prioritisation fees go to the `EvmFeeRecipient`.
 	type WeightPerGas = ();
 	type OnCreate = ();
 	type Timestamp = Timestamp;
@@ -0,0 +1,217 @@
+//! EIP-1559 like base fee of the EVM, and where the paid fees go.
+//!
+//! The base fee is adjusted once per sidechain block, depending on how full the previous block
+//! was compared to half of the `BlockGasLimit`, which is derived from `MAXIMUM_BLOCK_WEIGHT`.
+//! Sidechain blocks are produced without any hook into the runtime, so the adjustment is done
+//! lazily: the gas used is accounted to the block it was used in, and the base fee of a later
+//! block is derived from it (and from the empty blocks in between) when it is first needed.
+
+use crate::{AccountId, Balances, BlockGasLimit, BlockNumber, Runtime, System};
+use codec::{Decode, Encode};
+use frame_support::{
+	pallet_prelude::Weight,
+	storage::types::{OptionQuery, StorageValue, ValueQuery},
+	traits::{Currency, OnUnbalanced, StorageInstance},
+};
+use pallet_evm::{EVMCurrencyAdapter, Error as EvmError, FeeCalculator, OnChargeEVMTransaction};
+use sp_core::{H160, U256};
+
+/// The base fee never drops below this value.
+pub const MIN_BASE_FEE: u64 = 1;
+
+/// Bounds the change of the base fee from one block to the next to 1/8 (12.5%), as in EIP-1559.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Ratio of the block gas limit to the gas target of a block, as in EIP-1559.
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
+
+type NegativeImbalance = pallet_balances::NegativeImbalance<Runtime>;
+type FeeCurrencyAdapter = EVMCurrencyAdapter<Balances, DealWithEvmFees>;
+
+/// The base fee of a block and the gas used in it so far.
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseFeeInfo {
+	pub block_number: BlockNumber,
+	pub base_fee: U256,
+	pub gas_used: U256,
+}
+
+impl Default for BaseFeeInfo {
+	fn default() -> Self {
+		Self { block_number: 0, base_fee: MIN_BASE_FEE.into(), gas_used: U256::zero() }
+	}
+}
+
+impl BaseFeeInfo {
+	/// The info of `block_number`, derived from this one.
+	///
+	/// Every block in between is assumed to be empty, which lowers the base fee.
+	pub fn at(self, block_number: BlockNumber) -> Self {
+		if block_number <= self.block_number {
+			return self
+		}
+		let mut base_fee = next_base_fee(self.base_fee, self.gas_used, gas_target());
+		for _ in self.block_number + 1..block_number {
+			let lower_base_fee = next_base_fee(base_fee, U256::zero(), gas_target());
+			// Stop early, the base fee won't change anymore.
+			if lower_base_fee == base_fee {
+				break
+			}
+			base_fee = lower_base_fee;
+		}
+		Self { block_number, base_fee, gas_used: U256::zero() }
+	}
+}
+
+pub struct EvmBaseFeeInfoPrefix;
+impl StorageInstance for EvmBaseFeeInfoPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmFees"
+	}
+	const STORAGE_PREFIX: &'static str = "BaseFeeInfo";
+}
+
+/// The base fee info of the last block, in which the EVM was used.
+pub type EvmBaseFeeInfo = StorageValue<EvmBaseFeeInfoPrefix, BaseFeeInfo, ValueQuery>;
+
+pub struct EvmFeeRecipientPrefix;
+impl StorageInstance for EvmFeeRecipientPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmFees"
+	}
+	const STORAGE_PREFIX: &'static str = "FeeRecipient";
+}
+
+/// The account, which receives the fees paid for EVM transactions. Without one, they are burnt.
+pub type EvmFeeRecipient = StorageValue<EvmFeeRecipientPrefix, AccountId, OptionQuery>;
+
+/// The gas a block should use, the base fee rises when more is used and falls when less is.
+pub fn gas_target() -> U256 {
+	BlockGasLimit::get() / ELASTICITY_MULTIPLIER
+}
+
+/// The base fee of the block following a block with `base_fee`, in which `gas_used` was used.
+pub fn next_base_fee(base_fee: U256, gas_used: U256, gas_target: U256) -> U256 {
+	if gas_target.is_zero() || gas_used == gas_target {
+		return base_fee
+	}
+	let max_change = |gas_delta: U256| {
+		base_fee.saturating_mul(gas_delta) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR
+	};
+	let next_base_fee = if gas_used > gas_target {
+		base_fee.saturating_add(max_change(gas_used - gas_target).max(U256::one()))
+	} else {
+		base_fee.saturating_sub(max_change(gas_target - gas_used))
+	};
+	next_base_fee.max(MIN_BASE_FEE.into())
+}
+
+/// The base fee of the current block.
+pub fn evm_base_fee() -> U256 {
+	EvmBaseFeeInfo::get().at(System::block_number()).base_fee
+}
+
+/// Accounts `gas_used` to the current block.
+pub fn record_evm_gas_used(gas_used: U256) {
+	let mut info = EvmBaseFeeInfo::get().at(System::block_number());
+	info.gas_used = info.gas_used.saturating_add(gas_used);
+	EvmBaseFeeInfo::put(info);
+}
+
+/// Fee calculator of the EVM, which demands the base fee of the current block.
+pub struct BaseFeeGasPrice;
+impl FeeCalculator for BaseFeeGasPrice {
+	fn min_gas_price() -> (U256, Weight) {
+		(evm_base_fee(), Weight::from_parts(1, 0u64))
+	}
+}
+
+/// Deposits the fees into the account of the `EvmFeeRecipient`.
+pub struct DealWithEvmFees;
+impl OnUnbalanced<NegativeImbalance> for DealWithEvmFees {
+	fn on_nonzero_unbalanced(fees: NegativeImbalance) {
+		if let Some(recipient) = EvmFeeRecipient::get() {
+			Balances::resolve_creating(&recipient, fees);
+		}
+	}
+}
+
+/// Charges the fees from the (shielded) balance of the account the source is mapped to.
+///
+/// Unlike the `EVMCurrencyAdapter`, the priority fee goes to the `EvmFeeRecipient` too, as there
+/// is no block author in the EVM.
+pub struct EvmFeeAdapter;
+impl OnChargeEVMTransaction<Runtime> for EvmFeeAdapter {
+	type LiquidityInfo = Option<NegativeImbalance>;
+
+	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, EvmError<Runtime>> {
+		<FeeCurrencyAdapter as OnChargeEVMTransaction<Runtime>>::withdraw_fee(who, fee)
+	}
+
+	fn correct_and_deposit_fee(
+		who: &H160,
+		corrected_fee: U256,
+		base_fee: U256,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Self::LiquidityInfo {
+		<FeeCurrencyAdapter as OnChargeEVMTransaction<Runtime>>::correct_and_deposit_fee(
+			who,
+			corrected_fee,
+			base_fee,
+			already_withdrawn,
+		)
+	}
+
+	fn pay_priority_fee(tip: Self::LiquidityInfo) {
+		if let Some(tip) = tip {
+			DealWithEvmFees::on_unbalanced(tip);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TARGET: u64 = 1_000_000;
+
+	#[test]
+	fn base_fee_is_unchanged_if_gas_target_is_met() {
+		assert_eq!(next_base_fee(800.into(), TARGET.into(), TARGET.into()), 800.into());
+	}
+
+	#[test]
+	fn base_fee_rises_by_at_most_an_eighth() {
+		assert_eq!(next_base_fee(800.into(), (2 * TARGET).into(), TARGET.into()), 900.into());
+		assert_eq!(next_base_fee(800.into(), (3 * TARGET / 2).into(), TARGET.into()), 850.into());
+		// Rises even if the change rounds to zero.
+		assert_eq!(next_base_fee(1.into(), (TARGET + 1).into(), TARGET.into()), 2.into());
+	}
+
+	#[test]
+	fn base_fee_falls_by_at_most_an_eighth() {
+		assert_eq!(next_base_fee(800.into(), U256::zero(), TARGET.into()), 700.into());
+		assert_eq!(next_base_fee(800.into(), (TARGET / 2).into(), TARGET.into()), 750.into());
+	}
+
+	#[test]
+	fn base_fee_does_not_fall_below_minimum() {
+		assert_eq!(
+			next_base_fee(MIN_BASE_FEE.into(), U256::zero(), TARGET.into()),
+			MIN_BASE_FEE.into()
+		);
+	}
+
+	#[test]
+	fn base_fee_falls_for_each_empty_block_in_between() {
+		let target = gas_target();
+		let info = BaseFeeInfo { block_number: 5, base_fee: 800.into(), gas_used: target };
+
+		assert_eq!(info.at(5), info);
+		assert_eq!(info.at(6).base_fee, 800.into());
+		assert_eq!(info.at(7).base_fee, 700.into());
+		assert_eq!(info.at(8).base_fee, 613.into());
+		assert_eq!(info.at(8).gas_used, U256::zero());
+		assert_eq!(info.at(u32::MAX).base_fee, 7.into());
+	}
+}
@@ -0,0 +1,38 @@
+//! Precompile, which reveals the exchange rates of the teeracle.
+
+use super::{output, revert, selector_and_arguments, word_argument};
+use crate::TeeracleExchangeRates;
+use fp_evm::{PrecompileHandle, PrecompileResult};
+use sp_core::{H160, H256};
+
+/// Address of the precompile, which reveals the latest exchange rates of the teeracle.
+///
+/// Solidity interface:
+/// ```solidity
+/// interface ExchangeRates {
+///     // The rate of e.g. `bytes32("DOT/USD")` as fixed point number with 32 fractional bits.
+///     function exchangeRate(bytes32 tradingPair) external view returns (uint256);
+/// }
+/// ```
+pub const EXCHANGE_RATES_PRECOMPILE: H160 =
+	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x04, 0x02]);
+
+/// Gas charged for looking up an exchange rate, like reading a cold storage slot.
+pub const EXCHANGE_RATES_READ_GAS: u64 = 2_100;
+
+const EXCHANGE_RATE_SELECTOR: [u8; 4] = [0x0f, 0x57, 0xe8, 0x48];
+
+pub(super) fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+	let (selector, arguments) = selector_and_arguments(handle)?;
+	if selector != EXCHANGE_RATE_SELECTOR {
+		return Err(revert("Unknown function selector"))
+	}
+	let trading_pair = word_argument(&arguments, 0)?;
+	handle.record_cost(EXCHANGE_RATES_READ_GAS)?;
+
+	// The trading pair is left aligned and padded with zeros.
+	let length = trading_pair.iter().rposition(|byte| *byte != 0).map_or(0, |last| last + 1);
+	let rate = TeeracleExchangeRates::get(trading_pair[..length].to_vec())
+		.ok_or_else(|| revert("Unknown trading pair"))?;
+	Ok(output(H256::from_low_u64_be(rate).as_bytes().to_vec()))
+}
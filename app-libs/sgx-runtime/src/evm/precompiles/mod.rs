@@ -0,0 +1,193 @@
+//! Precompiles of the sgx-runtime, which expose features of the sidechain and the enclave to EVM
+//! contracts.
+//!
+//! The precompiles of enclave capabilities are disabled unless root enables them for the shard,
+//! as they reveal data (or spend resources) of the enclave. Addresses of disabled precompiles are
+//! left to the EVM, like any other address.
+
+use fp_evm::{
+	ExitRevert, ExitSucceed, IsPrecompileResult, PrecompileFailure, PrecompileHandle,
+	PrecompileOutput, PrecompileResult, PrecompileSet,
+};
+use frame_support::{
+	storage::types::{OptionQuery, StorageMap},
+	traits::StorageInstance,
+	Blake2_128Concat,
+};
+use sp_core::{H160, U256};
+use sp_std::vec::Vec;
+
+pub use exchange_rates::{EXCHANGE_RATES_PRECOMPILE, EXCHANGE_RATES_READ_GAS};
+pub use parentchain::{PARENTCHAIN_PRECOMPILE, PARENTCHAIN_READ_GAS};
+pub use private_data::{
+	EvmPrivateData, EvmPrivateDataCount, PrivateData, MAX_PRIVATE_DATA_LEN, PRIVATE_DATA_BASE_GAS,
+	PRIVATE_DATA_GAS_PER_WORD, PRIVATE_DATA_PRECOMPILE,
+};
+pub use randomness::{RANDOMNESS_GAS, RANDOMNESS_PRECOMPILE};
+pub use state_readers::{
+	EvmStateReaders, STATE_READERS_PRECOMPILE, STATE_READERS_READ_GAS, STATE_READERS_WRITE_GAS,
+};
+
+mod exchange_rates;
+mod parentchain;
+mod private_data;
+mod randomness;
+mod state_readers;
+
+/// The precompiles of enclave capabilities, which have to be enabled by root.
+pub const ENCLAVE_PRECOMPILES: [H160; 4] = [
+	PARENTCHAIN_PRECOMPILE,
+	EXCHANGE_RATES_PRECOMPILE,
+	RANDOMNESS_PRECOMPILE,
+	PRIVATE_DATA_PRECOMPILE,
+];
+
+pub struct EvmEnabledPrecompilesPrefix;
+impl StorageInstance for EvmEnabledPrecompilesPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmPrecompiles"
+	}
+	const STORAGE_PREFIX: &'static str = "Enabled";
+}
+
+/// The `ENCLAVE_PRECOMPILES` enabled by root.
+pub type EvmEnabledPrecompiles =
+	StorageMap<EvmEnabledPrecompilesPrefix, Blake2_128Concat, H160, (), OptionQuery>;
+
+/// The precompiles of the sgx-runtime. Addresses without a precompile are left to the EVM.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SgxPrecompiles;
+
+impl SgxPrecompiles {
+	fn is_enabled(address: H160) -> bool {
+		address == STATE_READERS_PRECOMPILE
+			|| (ENCLAVE_PRECOMPILES.contains(&address)
+				&& EvmEnabledPrecompiles::contains_key(address))
+	}
+}
+
+impl PrecompileSet for SgxPrecompiles {
+	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+		let address = handle.code_address();
+		if !Self::is_enabled(address) {
+			return None
+		}
+		match address {
+			a if a == STATE_READERS_PRECOMPILE => Some(state_readers::execute(handle)),
+			a if a == PARENTCHAIN_PRECOMPILE => Some(parentchain::execute(handle)),
+			a if a == EXCHANGE_RATES_PRECOMPILE => Some(exchange_rates::execute(handle)),
+			a if a == RANDOMNESS_PRECOMPILE => Some(randomness::execute(handle)),
+			a if a == PRIVATE_DATA_PRECOMPILE => Some(private_data::execute(handle)),
+			_ => None,
+		}
+	}
+
+	fn is_precompile(&self, address: H160, _remaining_gas: u64) -> IsPrecompileResult {
+		IsPrecompileResult::Answer { is_precompile: Self::is_enabled(address), extra_cost: 0 }
+	}
+}
+
+/// Splits the input into the function selector and the ABI encoded arguments.
+fn selector_and_arguments(
+	handle: &impl PrecompileHandle,
+) -> Result<([u8; 4], Vec<u8>), PrecompileFailure> {
+	let input = handle.input();
+	if input.len() < 4 {
+		return Err(revert("Missing function selector"))
+	}
+	let mut selector = [0u8; 4];
+	selector.copy_from_slice(&input[..4]);
+	Ok((selector, input[4..].to_vec()))
+}
+
+/// The ABI encoded word at `position` of `arguments`.
+fn word_argument(arguments: &[u8], position: usize) -> Result<&[u8], PrecompileFailure> {
+	arguments
+		.get(position * 32..(position + 1) * 32)
+		.ok_or_else(|| revert("Missing argument"))
+}
+
+/// Decodes the ABI encoded address at `position` of `arguments`.
+fn address_argument(arguments: &[u8], position: usize) -> Result<H160, PrecompileFailure> {
+	let word = word_argument(arguments, position)?;
+	if word[..12].iter().any(|byte| *byte != 0) {
+		return Err(revert("Invalid address argument"))
+	}
+	Ok(H160::from_slice(&word[12..]))
+}
+
+/// Decodes the ABI encoded `bytes` at `position` of `arguments`.
+fn bytes_argument(arguments: &[u8], position: usize) -> Result<Vec<u8>, PrecompileFailure> {
+	let offset = usize_argument(word_argument(arguments, position)?)?;
+	let length = usize_argument(
+		arguments
+			.get(offset..offset.saturating_add(32))
+			.ok_or_else(|| revert("Missing bytes"))?,
+	)?;
+	let start = offset.saturating_add(32);
+	arguments
+		.get(start..start.saturating_add(length))
+		.map(|bytes| bytes.to_vec())
+		.ok_or_else(|| revert("Missing bytes"))
+}
+
+fn usize_argument(word: &[u8]) -> Result<usize, PrecompileFailure> {
+	let value = U256::from_big_endian(word);
+	if value > U256::from(u32::MAX) {
+		return Err(revert("Invalid offset or length"))
+	}
+	Ok(value.as_usize())
+}
+
+fn output(output: Vec<u8>) -> PrecompileOutput {
+	PrecompileOutput { exit_status: ExitSucceed::Returned, output }
+}
+
+fn revert(reason: &str) -> PrecompileFailure {
+	PrecompileFailure::Revert { exit_status: ExitRevert::Reverted, output: reason.into() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn word(value: u64) -> Vec<u8> {
+		let mut word = [0u8; 32];
+		U256::from(value).to_big_endian(&mut word);
+		word.to_vec()
+	}
+
+	#[test]
+	fn address_argument_is_decoded() {
+		let mut arguments = word(0);
+		arguments.extend_from_slice(&[0u8; 12]);
+		arguments.extend_from_slice(H160::repeat_byte(0x11).as_bytes());
+
+		assert_eq!(address_argument(&arguments, 1).unwrap(), H160::repeat_byte(0x11));
+		assert!(address_argument(&arguments, 2).is_err());
+		assert!(address_argument(&word(u64::MAX), 0).is_err());
+	}
+
+	#[test]
+	fn bytes_argument_is_decoded() {
+		// (address, bytes) with the bytes 0x0102 after the head.
+		let mut arguments = word(0);
+		arguments.extend(word(64));
+		arguments.extend(word(2));
+		arguments.extend_from_slice(&[1, 2]);
+		arguments.extend_from_slice(&[0u8; 30]);
+
+		assert_eq!(bytes_argument(&arguments, 1).unwrap(), vec![1, 2]);
+	}
+
+	#[test]
+	fn bytes_argument_out_of_bounds_is_rejected() {
+		let mut arguments = word(0);
+		arguments.extend(word(64));
+		arguments.extend(word(33));
+		arguments.extend_from_slice(&[0u8; 32]);
+
+		assert!(bytes_argument(&arguments, 1).is_err());
+		assert!(bytes_argument(&word(u64::MAX), 0).is_err());
+	}
+}
@@ -0,0 +1,43 @@
+//! Precompile, which reveals the latest Integritee parentchain block verified by the light client
+//! of the enclave.
+
+use super::{output, revert, selector_and_arguments};
+use crate::ParentchainIntegritee;
+use fp_evm::{PrecompileHandle, PrecompileResult};
+use sp_core::{H160, H256};
+
+/// Address of the precompile, which reveals the latest verified Integritee parentchain block.
+///
+/// Solidity interface:
+/// ```solidity
+/// interface Parentchain {
+///     function blockNumber() external view returns (uint256);
+///     function blockHash() external view returns (bytes32);
+///     // Milliseconds since the unix epoch.
+///     function timestamp() external view returns (uint256);
+/// }
+/// ```
+pub const PARENTCHAIN_PRECOMPILE: H160 =
+	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x04, 0x01]);
+
+/// Gas charged for reading the parentchain block, like reading a cold storage slot.
+pub const PARENTCHAIN_READ_GAS: u64 = 2_100;
+
+const BLOCK_NUMBER_SELECTOR: [u8; 4] = [0x57, 0xe8, 0x71, 0xe7];
+const BLOCK_HASH_SELECTOR: [u8; 4] = [0xf2, 0x2a, 0x19, 0x5e];
+const TIMESTAMP_SELECTOR: [u8; 4] = [0xb8, 0x07, 0x77, 0xea];
+
+pub(super) fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+	let (selector, _) = selector_and_arguments(handle)?;
+	handle.record_cost(PARENTCHAIN_READ_GAS)?;
+
+	let word = match selector {
+		BLOCK_NUMBER_SELECTOR =>
+			ParentchainIntegritee::block_number().map(|number| H256::from_low_u64_be(number.into())),
+		BLOCK_HASH_SELECTOR => ParentchainIntegritee::block_hash(),
+		TIMESTAMP_SELECTOR => ParentchainIntegritee::now().map(H256::from_low_u64_be),
+		_ => return Err(revert("Unknown function selector")),
+	}
+	.ok_or_else(|| revert("No parentchain block has been imported yet"))?;
+	Ok(output(word.as_bytes().to_vec()))
+}
@@ -0,0 +1,101 @@
+//! Precompile, with which contracts store data that only a given account can read.
+
+use super::{address_argument, bytes_argument, output, revert, selector_and_arguments};
+use codec::{Decode, Encode};
+use fp_evm::{PrecompileHandle, PrecompileResult};
+use frame_support::{
+	storage::types::{OptionQuery, StorageMap, StorageValue, ValueQuery},
+	traits::StorageInstance,
+	Blake2_128Concat,
+};
+use sp_core::{H160, H256};
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+/// Address of the precompile, with which contracts store data that only a given account can read.
+///
+/// The data is NOT encrypted to the recipient: it is stored in plaintext in the shard state, like
+/// the rest of the state, which is only encrypted as a whole when the enclave persists or shares
+/// it. Privacy relies on access control: the data is kept apart from the storage of the contract,
+/// so other contracts and the EVM state getters cannot read it, and the `evm_private_data` trusted
+/// getter returns it to the recipient only.
+///
+/// Solidity interface:
+/// ```solidity
+/// interface PrivateData {
+///     // Returns the id, with which the recipient gets the data.
+///     function store(address recipient, bytes calldata data) external returns (bytes32);
+/// }
+/// ```
+pub const PRIVATE_DATA_PRECOMPILE: H160 =
+	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x04, 0x04]);
+
+/// Gas charged for storing data, like writing a storage slot.
+pub const PRIVATE_DATA_BASE_GAS: u64 = 20_000;
+/// Gas charged for every started word of 32 bytes of the data, like writing a storage slot.
+pub const PRIVATE_DATA_GAS_PER_WORD: u64 = 20_000;
+/// Maximum length of the data stored by a single call, in bytes.
+pub const MAX_PRIVATE_DATA_LEN: usize = 4 * 1024;
+
+const STORE_SELECTOR: [u8; 4] = [0x15, 0x1c, 0x3b, 0x12];
+
+/// Data stored for `recipient` by `contract`, in plaintext.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateData {
+	pub contract: H160,
+	pub recipient: H160,
+	pub data: Vec<u8>,
+}
+
+pub struct EvmPrivateDataPrefix;
+impl StorageInstance for EvmPrivateDataPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmPrivateData"
+	}
+	const STORAGE_PREFIX: &'static str = "PrivateData";
+}
+
+/// The private data by its id.
+pub type EvmPrivateData =
+	StorageMap<EvmPrivateDataPrefix, Blake2_128Concat, H256, PrivateData, OptionQuery>;
+
+pub struct EvmPrivateDataCountPrefix;
+impl StorageInstance for EvmPrivateDataCountPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmPrivateData"
+	}
+	const STORAGE_PREFIX: &'static str = "PrivateDataCount";
+}
+
+/// How much data has been stored so far, which makes the ids unique.
+pub type EvmPrivateDataCount = StorageValue<EvmPrivateDataCountPrefix, u64, ValueQuery>;
+
+pub(super) fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+	// With a delegate call, the caller would not be the contract storing the data.
+	if handle.context().address != handle.code_address() {
+		return Err(revert("Cannot be called with a delegate call"))
+	}
+	let (selector, arguments) = selector_and_arguments(handle)?;
+	if selector != STORE_SELECTOR {
+		return Err(revert("Unknown function selector"))
+	}
+	if handle.is_static() {
+		return Err(revert("Cannot store data in a static call"))
+	}
+	let recipient = address_argument(&arguments, 0)?;
+	let data = bytes_argument(&arguments, 1)?;
+	if data.len() > MAX_PRIVATE_DATA_LEN {
+		return Err(revert("Data is too large to be stored"))
+	}
+	let words = (data.len() as u64).saturating_add(31) / 32;
+	handle.record_cost(
+		PRIVATE_DATA_BASE_GAS.saturating_add(words.saturating_mul(PRIVATE_DATA_GAS_PER_WORD)),
+	)?;
+
+	let contract = handle.context().caller;
+	let count = EvmPrivateDataCount::get();
+	let id = H256::from(blake2_256(&(contract, recipient, count).encode()));
+	EvmPrivateData::insert(id, PrivateData { contract, recipient, data });
+	EvmPrivateDataCount::put(count.saturating_add(1));
+	Ok(output(id.as_bytes().to_vec()))
+}
@@ -0,0 +1,54 @@
+//! Precompile, which provides randomness of the enclave to contracts.
+
+use super::{output, revert, selector_and_arguments};
+use fp_evm::{ExitError, PrecompileFailure, PrecompileHandle, PrecompileResult};
+use sp_core::H160;
+
+/// Address of the precompile, which provides secure randomness.
+///
+/// Unlike randomness derived from block data, it cannot be predicted or influenced by anyone, as
+/// it is drawn inside the enclave for every call. It is not reproducible either, other validateers
+/// import the resulting state instead of executing the calls again. Outside of an enclave (without
+/// the `sgx` feature), calls fail.
+///
+/// Solidity interface:
+/// ```solidity
+/// interface Randomness {
+///     function randomBytes32() external returns (bytes32);
+/// }
+/// ```
+pub const RANDOMNESS_PRECOMPILE: H160 =
+	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x04, 0x03]);
+
+/// Gas charged for drawing 32 random bytes.
+pub const RANDOMNESS_GAS: u64 = 5_000;
+
+const RANDOM_BYTES_32_SELECTOR: [u8; 4] = [0xf6, 0x98, 0x2e, 0xc6];
+
+pub(super) fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+	let (selector, _) = selector_and_arguments(handle)?;
+	if selector != RANDOM_BYTES_32_SELECTOR {
+		return Err(revert("Unknown function selector"))
+	}
+	handle.record_cost(RANDOMNESS_GAS)?;
+	Ok(output(random_bytes_32()?.to_vec()))
+}
+
+#[cfg(feature = "sgx")]
+fn random_bytes_32() -> Result<[u8; 32], PrecompileFailure> {
+	use sgx_rand::{os::SgxRng, Rng};
+	let mut bytes = [0u8; 32];
+	SgxRng::new()
+		.map_err(|_| unavailable("Cannot access the random number generator of the enclave"))?
+		.fill_bytes(&mut bytes);
+	Ok(bytes)
+}
+
+#[cfg(not(feature = "sgx"))]
+fn random_bytes_32() -> Result<[u8; 32], PrecompileFailure> {
+	Err(unavailable("Randomness is only available inside the enclave"))
+}
+
+fn unavailable(reason: &'static str) -> PrecompileFailure {
+	PrecompileFailure::Error { exit_status: ExitError::Other(reason.into()) }
+}
@@ -0,0 +1,90 @@
+//! Precompile, with which contracts manage the readers of their state.
+
+use super::{address_argument, output, revert};
+use fp_evm::{PrecompileHandle, PrecompileResult};
+use frame_support::{
+	storage::types::{OptionQuery, StorageDoubleMap},
+	traits::StorageInstance,
+	Blake2_128Concat,
+};
+use sp_core::{H160, H256};
+use sp_std::vec::Vec;
+
+/// Address of the precompile, with which contracts manage the readers of their state.
+///
+/// Solidity interface:
+/// ```solidity
+/// interface StateReaders {
+///     function addReader(address reader) external;
+///     function removeReader(address reader) external;
+///     function isReader(address contract_, address reader) external view returns (bool);
+/// }
+/// ```
+pub const STATE_READERS_PRECOMPILE: H160 =
+	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x04, 0x00]);
+
+/// Gas charged for adding or removing a reader, like writing a storage slot.
+pub const STATE_READERS_WRITE_GAS: u64 = 20_000;
+/// Gas charged for looking up a reader, like reading a cold storage slot.
+pub const STATE_READERS_READ_GAS: u64 = 2_100;
+
+const ADD_READER_SELECTOR: [u8; 4] = [0xaf, 0xd8, 0xb1, 0xd1];
+const REMOVE_READER_SELECTOR: [u8; 4] = [0x38, 0xd6, 0x5d, 0x11];
+const IS_READER_SELECTOR: [u8; 4] = [0x4c, 0x40, 0x1b, 0x6f];
+
+pub struct EvmStateReadersPrefix;
+impl StorageInstance for EvmStateReadersPrefix {
+	fn pallet_prefix() -> &'static str {
+		"EvmStateAccess"
+	}
+	const STORAGE_PREFIX: &'static str = "Readers";
+}
+
+/// Accounts that may read the state of a contract (contract, reader), managed by the contract
+/// itself through the `STATE_READERS_PRECOMPILE`.
+pub type EvmStateReaders = StorageDoubleMap<
+	EvmStateReadersPrefix,
+	Blake2_128Concat,
+	H160,
+	Blake2_128Concat,
+	H160,
+	(),
+	OptionQuery,
+>;
+
+pub(super) fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+	// With a delegate call, the caller would not be the contract whose readers are managed.
+	if handle.context().address != handle.code_address() {
+		return Err(revert("Cannot be called with a delegate call"))
+	}
+	let input = handle.input().to_vec();
+	if input.len() < 4 {
+		return Err(revert("Missing function selector"))
+	}
+	let (selector, arguments) = input.split_at(4);
+	let contract = handle.context().caller;
+
+	match selector {
+		s if s == ADD_READER_SELECTOR || s == REMOVE_READER_SELECTOR => {
+			if handle.is_static() {
+				return Err(revert("Cannot modify the readers in a static call"))
+			}
+			let reader = address_argument(arguments, 0)?;
+			handle.record_cost(STATE_READERS_WRITE_GAS)?;
+			if s == ADD_READER_SELECTOR {
+				EvmStateReaders::insert(contract, reader, ());
+			} else {
+				EvmStateReaders::remove(contract, reader);
+			}
+			Ok(output(Vec::new()))
+		},
+		s if s == IS_READER_SELECTOR => {
+			let contract = address_argument(arguments, 0)?;
+			let reader = address_argument(arguments, 1)?;
+			handle.record_cost(STATE_READERS_READ_GAS)?;
+			let is_reader = EvmStateReaders::contains_key(contract, reader);
+			Ok(output(H256::from_low_u64_be(is_reader as u64).as_bytes().to_vec()))
+		},
+		_ => Err(revert("Unknown function selector")),
+	}
+}
@@ -33,11 +33,24 @@ mod evm;
 
 #[cfg(feature = "evm")]
 pub use evm::{
-	AddressMapping, EnsureAddressTruncated, EvmCall, FeeCalculator, FixedGasPrice,
-	FixedGasWeightMapping, GasWeightMapping, HashedAddressMapping, IntoAddressMapping,
-	SubstrateBlockHashMapping, GAS_PER_SECOND, MAXIMUM_BLOCK_WEIGHT, WEIGHT_PER_GAS,
+	evm_base_fee, gas_target, next_base_fee, record_evm_gas_used, AddressMapping, BaseFeeGasPrice,
+	BaseFeeInfo, BlockGasLimit, ChainId, DealWithEvmFees, EnsureAddressOrigin,
+	EnsureAddressTruncated, EvmBaseFeeInfo, EvmCall, EvmChainId, EvmEnabledPrecompiles,
+	EvmFeeAdapter, EvmFeeRecipient, EvmPrivateData, EvmPrivateDataCount, EvmStateReaders,
+	FeeCalculator, FixedGasWeightMapping, GasWeightMapping, HashedAddressMapping,
+	IntoAddressMapping, PrivateData, SgxPrecompiles, SubstrateBlockHashMapping,
+	BASE_FEE_MAX_CHANGE_DENOMINATOR, ELASTICITY_MULTIPLIER, ENCLAVE_PRECOMPILES,
+	EXCHANGE_RATES_PRECOMPILE, EXCHANGE_RATES_READ_GAS, GAS_PER_SECOND, MAXIMUM_BLOCK_WEIGHT,
+	MAX_PRIVATE_DATA_LEN, MIN_BASE_FEE, PARENTCHAIN_PRECOMPILE, PARENTCHAIN_READ_GAS,
+	PRIVATE_DATA_BASE_GAS, PRIVATE_DATA_GAS_PER_WORD, PRIVATE_DATA_PRECOMPILE, RANDOMNESS_GAS,
+	RANDOMNESS_PRECOMPILE, STATE_READERS_PRECOMPILE, STATE_READERS_READ_GAS,
+	STATE_READERS_WRITE_GAS, WEIGHT_PER_GAS,
 };
 
+mod teeracle;
+
+pub use teeracle::{ExchangeRateBits, TeeracleExchangeRates};
+
 use core::convert::{TryFrom, TryInto};
 use frame_support::{traits::ConstU32, weights::ConstantMultiplier};
 use pallet_transaction_payment::CurrencyAdapter;
@@ -74,6 +87,7 @@ use itp_sgx_runtime_primitives::types::Moment;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_parentchain::Call as ParentchainPalletCall;
 pub use pallet_timestamp::Call as TimestampCall;
+pub use pallet_transaction_payment::Event as TransactionPaymentEvent;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
@@ -101,6 +115,10 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, RuntimeCall, SignedExtra>;
+/// Migrations that run on the state of a shard when it was last upgraded by a runtime with a lower
+/// `spec_version`. Add new `OnRuntimeUpgrade` implementations to the tuple, oldest first, and
+/// bump `VERSION.spec_version` with them.
+pub type Migrations = ();
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -108,6 +126,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
@@ -0,0 +1,29 @@
+//! Exchange rates of the teeracle, mirrored into the shard state from the `ExchangeRateUpdated`
+//! events of the Integritee parentchain.
+
+use frame_support::{
+	storage::types::{OptionQuery, StorageMap},
+	traits::StorageInstance,
+	Blake2_128Concat,
+};
+use sp_std::vec::Vec;
+
+/// Bits of a `U32F32` exchange rate, a fixed point number with 32 fractional bits.
+pub type ExchangeRateBits = u64;
+
+pub struct TeeracleExchangeRatesPrefix;
+impl StorageInstance for TeeracleExchangeRatesPrefix {
+	fn pallet_prefix() -> &'static str {
+		"Teeracle"
+	}
+	const STORAGE_PREFIX: &'static str = "ExchangeRates";
+}
+
+/// The latest exchange rate of a trading pair (e.g. `DOT/USD`), regardless of its data source.
+pub type TeeracleExchangeRates = StorageMap<
+	TeeracleExchangeRatesPrefix,
+	Blake2_128Concat,
+	Vec<u8>,
+	ExchangeRateBits,
+	OptionQuery,
+>;
@@ -36,7 +36,9 @@ pub fn get_evm_account_storages(evm_account: &H160, index: &H256) -> Option<H256
 	)
 }
 
-// FIXME: Once events are available, these addresses should be read from events.
+/// Predicts the address of a contract created by `caller` with its account `nonce`.
+///
+/// The actual address of an executed creation is in its `EvmReceipt`.
 pub fn evm_create_address(caller: H160, nonce: Nonce) -> H160 {
 	let mut stream = rlp::RlpStream::new_list(2);
 	stream.append(&caller);
@@ -44,7 +46,9 @@ pub fn evm_create_address(caller: H160, nonce: Nonce) -> H160 {
 	H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into()
 }
 
-// FIXME: Once events are available, these addresses should be read from events.
+/// Predicts the address of a contract created by `caller` with `salt` (`CREATE2`).
+///
+/// The actual address of an executed creation is in its `EvmReceipt`.
 pub fn evm_create2_address(caller: H160, salt: H256, code_hash: H256) -> H160 {
 	let mut hasher = Keccak256::new();
 	hasher.update([0xff]);
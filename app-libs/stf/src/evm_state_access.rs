@@ -0,0 +1,99 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Who may read the state (code and storage) of an EVM account through the getters.
+//!
+//! Unlike on Ethereum, the EVM state of a shard is confidential. The policy of the shard is set
+//! by root and decides whether the state is only revealed to the owner of a contract (the EVM
+//! account that created it), additionally to the readers a contract adds with the
+//! `STATE_READERS_PRECOMPILE`, or to anyone.
+
+use crate::{
+	evm_helpers::get_evm_account,
+	helpers::{get_storage_map, get_storage_value},
+};
+use codec::{Decode, Encode};
+use ita_sgx_runtime::EvmStateReaders;
+use itp_stf_primitives::types::AccountId;
+use itp_storage::{storage_map_key, storage_value_key, StorageHasher};
+use sp_core::H160;
+
+pub const EVM_STATE_ACCESS_STORAGE_PREFIX: &str = "EvmStateAccess";
+pub const EVM_STATE_ACCESS_POLICY_STORAGE_NAME: &str = "Policy";
+pub const EVM_CONTRACT_OWNERS_STORAGE_NAME: &str = "ContractOwners";
+
+#[derive(Encode, Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvmStateAccessPolicy {
+	/// Only the owner of a contract may read its state.
+	#[default]
+	Owner,
+	/// The owner and the readers added by the contract may read its state.
+	Allowlist,
+	/// Anyone may read the EVM state, like on Ethereum.
+	Public,
+}
+
+pub fn get_evm_state_access_policy() -> EvmStateAccessPolicy {
+	get_storage_value(EVM_STATE_ACCESS_STORAGE_PREFIX, EVM_STATE_ACCESS_POLICY_STORAGE_NAME)
+		.unwrap_or_default()
+}
+
+pub fn set_evm_state_access_policy(policy: EvmStateAccessPolicy) {
+	sp_io::storage::set(
+		&storage_value_key(EVM_STATE_ACCESS_STORAGE_PREFIX, EVM_STATE_ACCESS_POLICY_STORAGE_NAME),
+		&policy.encode(),
+	);
+}
+
+/// The EVM account that created `contract`.
+///
+/// Contracts created by other contracts have no owner.
+pub fn get_contract_owner(contract: &H160) -> Option<H160> {
+	get_storage_map(
+		EVM_STATE_ACCESS_STORAGE_PREFIX,
+		EVM_CONTRACT_OWNERS_STORAGE_NAME,
+		contract,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+pub fn store_contract_owner(contract: &H160, owner: &H160) {
+	sp_io::storage::set(
+		&storage_map_key(
+			EVM_STATE_ACCESS_STORAGE_PREFIX,
+			EVM_CONTRACT_OWNERS_STORAGE_NAME,
+			contract,
+			&StorageHasher::Blake2_128Concat,
+		),
+		&owner.encode(),
+	);
+}
+
+/// Whether `who` may read the code and storage of `evm_account`, according to the policy.
+///
+/// The EVM account of `who` may always read its own state.
+pub fn may_read_evm_state(who: &AccountId, evm_account: &H160) -> bool {
+	let reader = get_evm_account(who);
+	let policy = get_evm_state_access_policy();
+	if policy == EvmStateAccessPolicy::Public || reader == *evm_account {
+		return true
+	}
+	if get_contract_owner(evm_account) == Some(reader) {
+		return true
+	}
+	policy == EvmStateAccessPolicy::Allowlist && EvmStateReaders::contains_key(evm_account, reader)
+}
@@ -0,0 +1,694 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Ethereum transactions, as submitted by standard Ethereum tooling (`eth_sendRawTransaction`).
+//!
+//! Ethereum transactions are signed with the secp256k1 key of the Ethereum account instead of
+//! a `MultiSignature` of a substrate account. They are executed by the EVM with the recovered
+//! sender as source, and leave a receipt per transaction hash. The EVM executions of trusted
+//! calls leave a receipt as well, under the hash of the signed trusted call. EVM receipts are
+//! kept as long as the receipt of their trusted call is in the bounded log of the sender. Read-only
+//! EVM calls (`eth_call`, `eth_estimateGas`) are simulated here as well.
+
+use crate::{
+	evm_helpers::get_evm_account_codes, evm_state_access::store_contract_owner,
+	helpers::get_storage_map, receipts::get_receipts,
+};
+use codec::{Decode, Encode};
+use ethereum::{
+	AccessListItem, EIP1559TransactionMessage, EIP2930TransactionMessage, EnvelopedDecodable,
+	LegacyTransactionMessage, TransactionAction, TransactionV2,
+};
+use ita_sgx_runtime::{
+	record_evm_gas_used, AddressMapping, BlockGasLimit, BlockNumber, EvmChainId, FeeCalculator,
+	HashedAddressMapping, Runtime, System,
+};
+use itp_stf_primitives::{
+	error::StfError,
+	types::{AccountId, ShardIdentifier},
+};
+use itp_storage::{storage_map_key, StorageHasher};
+use log::*;
+use pallet_evm::Runner;
+use sp_core::{H160, H256, U256};
+use sp_io::{
+	crypto::secp256k1_ecdsa_recover,
+	hashing::{blake2_256, keccak_256},
+};
+use std::{format, prelude::v1::*};
+
+pub use ethereum::Log as EvmLog;
+
+type EvmRunner = <Runtime as pallet_evm::Config>::Runner;
+
+pub const EVM_RECEIPTS_STORAGE_PREFIX: &str = "EvmReceipts";
+pub const EVM_RECEIPTS_STORAGE_NAME: &str = "Receipts";
+pub const EVM_TRANSACTION_HASHES_STORAGE_NAME: &str = "TransactionHashes";
+
+/// Chain id of the EVM of `shard`, which Ethereum transactions have to be signed for.
+///
+/// It is derived from the shard, so that a transaction cannot be replayed on another shard. It
+/// fits into 32 bits, like the chain ids Ethereum tooling expects.
+pub fn evm_chain_id(shard: &ShardIdentifier) -> u64 {
+	let hash = blake2_256(&(b"evm_chain_id", shard).encode());
+	u64::from(u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]).max(1))
+}
+
+/// A signed Ethereum transaction, whose sender has been recovered from the signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EthereumTransaction {
+	/// Keccak-256 hash of the raw transaction, under which Ethereum tooling knows it.
+	pub hash: H256,
+	/// EIP-2718 type: 0 for legacy, 1 for EIP-2930 and 2 for EIP-1559 transactions.
+	pub transaction_type: u8,
+	pub source: H160,
+	/// `None` for legacy transactions without replay protection (pre EIP-155).
+	pub chain_id: Option<u64>,
+	pub nonce: U256,
+	pub action: TransactionAction,
+	pub input: Vec<u8>,
+	pub value: U256,
+	pub gas_limit: U256,
+	/// Gas price of legacy and EIP-2930 transactions.
+	pub max_fee_per_gas: U256,
+	pub max_priority_fee_per_gas: Option<U256>,
+	pub access_list: Vec<(H160, Vec<H256>)>,
+}
+
+impl EthereumTransaction {
+	/// Decodes an (EIP-2718 enveloped) signed transaction and recovers its sender.
+	pub fn decode_signed(raw: &[u8]) -> Result<Self, StfError> {
+		let transaction = <TransactionV2 as EnvelopedDecodable>::decode(raw).map_err(|e| {
+			StfError::Dispatch(format!("Could not decode Ethereum transaction: {:?}", e))
+		})?;
+		let source = recover_signer(&transaction).ok_or_else(|| {
+			StfError::Dispatch("Invalid signature of Ethereum transaction".into())
+		})?;
+		let hash = transaction.hash();
+
+		Ok(match transaction {
+			TransactionV2::Legacy(t) => EthereumTransaction {
+				hash,
+				transaction_type: 0,
+				source,
+				chain_id: t.signature.chain_id(),
+				nonce: t.nonce,
+				action: t.action,
+				input: t.input,
+				value: t.value,
+				gas_limit: t.gas_limit,
+				max_fee_per_gas: t.gas_price,
+				max_priority_fee_per_gas: None,
+				access_list: Vec::new(),
+			},
+			TransactionV2::EIP2930(t) => EthereumTransaction {
+				hash,
+				transaction_type: 1,
+				source,
+				chain_id: Some(t.chain_id),
+				nonce: t.nonce,
+				action: t.action,
+				input: t.input,
+				value: t.value,
+				gas_limit: t.gas_limit,
+				max_fee_per_gas: t.gas_price,
+				max_priority_fee_per_gas: None,
+				access_list: into_access_list(t.access_list),
+			},
+			TransactionV2::EIP1559(t) => EthereumTransaction {
+				hash,
+				transaction_type: 2,
+				source,
+				chain_id: Some(t.chain_id),
+				nonce: t.nonce,
+				action: t.action,
+				input: t.input,
+				value: t.value,
+				gas_limit: t.gas_limit,
+				max_fee_per_gas: t.max_fee_per_gas,
+				max_priority_fee_per_gas: Some(t.max_priority_fee_per_gas),
+				access_list: into_access_list(t.access_list),
+			},
+		})
+	}
+
+	/// Account of the sender in the sidechain runtime, holding its balance and nonce.
+	pub fn sender_account(&self) -> AccountId {
+		HashedAddressMapping::into_account_id(self.source)
+	}
+}
+
+/// Call to be simulated with `eth_call` or `eth_estimateGas`.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvmCallRequest {
+	pub from: Option<H160>,
+	/// `None` simulates the creation of a contract with `data` as init code.
+	pub to: Option<H160>,
+	pub data: Vec<u8>,
+	pub value: U256,
+	/// Defaults to, and is capped at, the gas limit of the simulation, see [`simulate_evm_call`].
+	pub gas_limit: Option<u64>,
+	/// Chain id of the shard (see [`evm_chain_id`]), which the `CHAINID` opcode returns.
+	pub chain_id: Option<u64>,
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct EvmCallResult {
+	pub succeeded: bool,
+	/// Return data of the call (the revert reason if it reverted), or the code of the created
+	/// contract.
+	pub value: Vec<u8>,
+	pub gas_used: U256,
+}
+
+/// What an EVM execution does with its input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvmAction {
+	Call(H160),
+	Create,
+	/// Create at an address derived from the salt instead of the nonce of the source.
+	Create2(H256),
+}
+
+/// An EVM execution, which is validated and paid for by its source like a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvmExecution {
+	/// Identifies the execution in its receipt.
+	pub transaction_hash: H256,
+	/// EIP-2718 type of Ethereum transactions, `None` for trusted calls.
+	pub transaction_type: Option<u8>,
+	pub source: H160,
+	pub action: EvmAction,
+	pub input: Vec<u8>,
+	pub value: U256,
+	pub gas_limit: u64,
+	pub max_fee_per_gas: U256,
+	pub max_priority_fee_per_gas: Option<U256>,
+	pub nonce: Option<U256>,
+	pub access_list: Vec<(H160, Vec<H256>)>,
+}
+
+/// Outcome of an EVM execution, stored per Ethereum transaction or trusted call.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct EvmReceipt {
+	/// Keccak-256 hash of an Ethereum transaction, or the Blake2-256 hash of the signed trusted
+	/// call (see `TrustedCallReceipt::call_hash`).
+	pub transaction_hash: H256,
+	/// EIP-2718 type of Ethereum transactions, `None` for trusted calls.
+	pub transaction_type: Option<u8>,
+	/// Sidechain block number the transaction was executed in.
+	pub block_number: BlockNumber,
+	pub from: H160,
+	pub to: Option<H160>,
+	/// Address of the contract created by the transaction.
+	pub contract_address: Option<H160>,
+	pub succeeded: bool,
+	pub gas_used: U256,
+	pub effective_gas_price: U256,
+	pub logs: Vec<EvmLog>,
+}
+
+/// Selects logs by their emitting contract and topics.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvmLogFilter {
+	/// `None` matches logs of any contract.
+	pub address: Option<H160>,
+	/// Positional topics as in `eth_getLogs`, `None` matches any topic at its position.
+	pub topics: Vec<Option<H256>>,
+}
+
+impl EvmLogFilter {
+	pub fn matches(&self, log: &EvmLog) -> bool {
+		if self.address.map_or(false, |address| address != log.address) {
+			return false
+		}
+		self.topics.iter().enumerate().all(|(position, topic)| match topic {
+			Some(topic) => log.topics.get(position) == Some(topic),
+			None => true,
+		})
+	}
+}
+
+/// A log together with the transaction that emitted it.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct EvmLogRecord {
+	pub transaction_hash: H256,
+	pub block_number: BlockNumber,
+	pub log: EvmLog,
+}
+
+/// Executes the transaction, wrapped in the trusted call with `call_hash`, in the EVM and
+/// stores its receipt.
+///
+/// Fails without a receipt if the EVM rejects the transaction (e.g. the sender cannot pay for
+/// the gas). As on Ethereum, the nonce of the sender is only incremented by an execution.
+pub fn execute_ethereum_transaction(
+	transaction: EthereumTransaction,
+	call_hash: H256,
+) -> Result<EvmReceipt, StfError> {
+	// The chain id has been checked against the one of the shard with the signature.
+	if let Some(chain_id) = transaction.chain_id {
+		EvmChainId::put(chain_id);
+	}
+	let action = match transaction.action {
+		TransactionAction::Call(target) => EvmAction::Call(target),
+		TransactionAction::Create => EvmAction::Create,
+	};
+	let transaction_hash = transaction.hash;
+	let receipt = execute_evm(EvmExecution {
+		transaction_hash,
+		transaction_type: Some(transaction.transaction_type),
+		source: transaction.source,
+		action,
+		input: transaction.input,
+		value: transaction.value,
+		gas_limit: gas_limit_as_u64(transaction.gas_limit)?,
+		max_fee_per_gas: transaction.max_fee_per_gas,
+		max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+		nonce: Some(transaction.nonce),
+		access_list: transaction.access_list,
+	})?;
+	sp_io::storage::set(&evm_transaction_hash_key(&call_hash), &transaction_hash.encode());
+	Ok(receipt)
+}
+
+/// Executes `execution` in the EVM, deposits the same events as `pallet-evm` and stores the
+/// receipt. The source of a created contract is recorded as its owner and the gas used is
+/// accounted to the current block, which determines the base fee of the next one.
+///
+/// Fails without a receipt if the EVM rejects the execution (e.g. the source cannot pay for
+/// the gas or the nonce does not match). A reverted execution is not an error, its receipt
+/// tells that it did not succeed.
+pub fn execute_evm(execution: EvmExecution) -> Result<EvmReceipt, StfError> {
+	let effective_gas_price = effective_gas_price(
+		execution.max_fee_per_gas,
+		execution.max_priority_fee_per_gas,
+		<Runtime as pallet_evm::Config>::FeeCalculator::min_gas_price().0,
+	);
+	let config = <Runtime as pallet_evm::Config>::config();
+
+	let (to, succeeded, gas_used, contract_address, logs) = match execution.action {
+		EvmAction::Call(target) => {
+			let info = EvmRunner::call(
+				execution.source,
+				target,
+				execution.input,
+				execution.value,
+				execution.gas_limit,
+				Some(execution.max_fee_per_gas),
+				execution.max_priority_fee_per_gas,
+				execution.nonce,
+				execution.access_list,
+				true,
+				true,
+				config,
+			)
+			.map_err(|e| StfError::Dispatch(format!("Evm Call error: {:?}", e.error)))?;
+			let succeeded = info.exit_reason.is_succeed();
+			System::deposit_event(match succeeded {
+				true => pallet_evm::Event::<Runtime>::Executed { address: target },
+				false => pallet_evm::Event::<Runtime>::ExecutedFailed { address: target },
+			});
+			(Some(target), succeeded, info.used_gas, None, info.logs)
+		},
+		EvmAction::Create => {
+			let info = EvmRunner::create(
+				execution.source,
+				execution.input,
+				execution.value,
+				execution.gas_limit,
+				Some(execution.max_fee_per_gas),
+				execution.max_priority_fee_per_gas,
+				execution.nonce,
+				execution.access_list,
+				true,
+				true,
+				config,
+			)
+			.map_err(|e| StfError::Dispatch(format!("Evm Create error: {:?}", e.error)))?;
+			let succeeded = deposit_create_event(info.exit_reason.is_succeed(), info.value);
+			(None, succeeded, info.used_gas, succeeded.then_some(info.value), info.logs)
+		},
+		EvmAction::Create2(salt) => {
+			let info = EvmRunner::create2(
+				execution.source,
+				execution.input,
+				salt,
+				execution.value,
+				execution.gas_limit,
+				Some(execution.max_fee_per_gas),
+				execution.max_priority_fee_per_gas,
+				execution.nonce,
+				execution.access_list,
+				true,
+				true,
+				config,
+			)
+			.map_err(|e| StfError::Dispatch(format!("Evm Create2 error: {:?}", e.error)))?;
+			let succeeded = deposit_create_event(info.exit_reason.is_succeed(), info.value);
+			(None, succeeded, info.used_gas, succeeded.then_some(info.value), info.logs)
+		},
+	};
+	info!(
+		"Executed EVM transaction {:?}, succeeded: {}, gas used: {}",
+		execution.transaction_hash, succeeded, gas_used
+	);
+
+	if let Some(contract) = contract_address {
+		store_contract_owner(&contract, &execution.source);
+	}
+	record_evm_gas_used(gas_used);
+
+	let receipt = EvmReceipt {
+		transaction_hash: execution.transaction_hash,
+		transaction_type: execution.transaction_type,
+		block_number: System::block_number(),
+		from: execution.source,
+		to,
+		contract_address,
+		succeeded,
+		gas_used,
+		effective_gas_price,
+		logs,
+	};
+	store_evm_receipt(&receipt);
+	Ok(receipt)
+}
+
+/// Executes `request` without charging fees or checking the nonce.
+///
+/// The EVM writes to the state like for any other call, it is up to the caller to discard
+/// these changes.
+pub fn simulate_evm_call(
+	request: EvmCallRequest,
+	max_gas_limit: u64,
+) -> Result<EvmCallResult, StfError> {
+	if let Some(chain_id) = request.chain_id {
+		EvmChainId::put(chain_id);
+	}
+	let source = request.from.unwrap_or_default();
+	let gas_limit = capped_gas_limit(request.gas_limit, max_gas_limit);
+	let config = <Runtime as pallet_evm::Config>::config();
+
+	match request.to {
+		Some(target) => EvmRunner::call(
+			source,
+			target,
+			request.data,
+			request.value,
+			gas_limit,
+			None,
+			None,
+			None,
+			Vec::new(),
+			false,
+			false,
+			config,
+		)
+		.map(|info| EvmCallResult {
+			succeeded: info.exit_reason.is_succeed(),
+			value: info.value,
+			gas_used: info.used_gas,
+		})
+		.map_err(|e| StfError::Dispatch(format!("Evm Call error: {:?}", e.error))),
+		None => EvmRunner::create(
+			source,
+			request.data,
+			request.value,
+			gas_limit,
+			None,
+			None,
+			None,
+			Vec::new(),
+			false,
+			false,
+			config,
+		)
+		.map(|info| EvmCallResult {
+			succeeded: info.exit_reason.is_succeed(),
+			value: get_evm_account_codes(&info.value).unwrap_or_default(),
+			gas_used: info.used_gas,
+		})
+		.map_err(|e| StfError::Dispatch(format!("Evm Create error: {:?}", e.error))),
+	}
+}
+
+pub fn evm_receipt_key(transaction_hash: &H256) -> Vec<u8> {
+	storage_map_key(
+		EVM_RECEIPTS_STORAGE_PREFIX,
+		EVM_RECEIPTS_STORAGE_NAME,
+		transaction_hash,
+		&StorageHasher::Identity,
+	)
+}
+
+pub fn get_evm_receipt(transaction_hash: &H256) -> Option<EvmReceipt> {
+	get_storage_map(
+		EVM_RECEIPTS_STORAGE_PREFIX,
+		EVM_RECEIPTS_STORAGE_NAME,
+		transaction_hash,
+		&StorageHasher::Identity,
+	)
+}
+
+/// Hash of the Ethereum transaction wrapped in the trusted call with `call_hash`.
+fn evm_transaction_hash_key(call_hash: &H256) -> Vec<u8> {
+	storage_map_key(
+		EVM_RECEIPTS_STORAGE_PREFIX,
+		EVM_TRANSACTION_HASHES_STORAGE_NAME,
+		call_hash,
+		&StorageHasher::Identity,
+	)
+}
+
+fn get_evm_transaction_hash(call_hash: &H256) -> Option<H256> {
+	get_storage_map(
+		EVM_RECEIPTS_STORAGE_PREFIX,
+		EVM_TRANSACTION_HASHES_STORAGE_NAME,
+		call_hash,
+		&StorageHasher::Identity,
+	)
+}
+
+/// The EVM receipt of the trusted call with `call_hash` (see `TrustedCallReceipt::call_hash`),
+/// which is stored under the hash of the Ethereum transaction, if it wraps one.
+pub fn get_evm_receipt_of_call(call_hash: &H256) -> Option<EvmReceipt> {
+	get_evm_receipt(&get_evm_transaction_hash(call_hash).unwrap_or(*call_hash))
+}
+
+/// Removes the EVM receipt of the trusted call with `call_hash`, once its receipt has been
+/// dropped from the log of the sender.
+pub fn remove_evm_receipt_of_call(call_hash: &H256) {
+	let transaction_hash = get_evm_transaction_hash(call_hash);
+	if transaction_hash.is_some() {
+		sp_io::storage::clear(&evm_transaction_hash_key(call_hash));
+	}
+	sp_io::storage::clear(&evm_receipt_key(&transaction_hash.unwrap_or(*call_hash)));
+}
+
+/// Logs matching `filter`, emitted by the trusted calls of `account` that are still in its
+/// receipt log, oldest first.
+pub fn get_evm_logs(account: &AccountId, filter: &EvmLogFilter) -> Vec<EvmLogRecord> {
+	get_receipts(account)
+		.iter()
+		.filter_map(|receipt| get_evm_receipt_of_call(&receipt.call_hash))
+		.flat_map(|receipt| {
+			let (transaction_hash, block_number) = (receipt.transaction_hash, receipt.block_number);
+			receipt
+				.logs
+				.into_iter()
+				.filter(|log| filter.matches(log))
+				.map(move |log| EvmLogRecord { transaction_hash, block_number, log })
+		})
+		.collect()
+}
+
+fn store_evm_receipt(receipt: &EvmReceipt) {
+	sp_io::storage::set(&evm_receipt_key(&receipt.transaction_hash), &receipt.encode());
+}
+
+/// Gas price paid by the source, given the current `base_fee`.
+fn effective_gas_price(
+	max_fee_per_gas: U256,
+	max_priority_fee_per_gas: Option<U256>,
+	base_fee: U256,
+) -> U256 {
+	match max_priority_fee_per_gas {
+		Some(priority_fee) => max_fee_per_gas.min(base_fee.saturating_add(priority_fee)),
+		None => max_fee_per_gas,
+	}
+}
+
+/// Deposits the event `pallet-evm` deposits for a creation, returns whether it succeeded.
+fn deposit_create_event(succeeded: bool, address: H160) -> bool {
+	System::deposit_event(match succeeded {
+		true => pallet_evm::Event::<Runtime>::Created { address },
+		false => pallet_evm::Event::<Runtime>::CreatedFailed { address },
+	});
+	succeeded
+}
+
+/// Recovers the sender like `pallet-ethereum` does.
+fn recover_signer(transaction: &TransactionV2) -> Option<H160> {
+	let mut sig = [0u8; 65];
+	let mut msg = [0u8; 32];
+	match transaction {
+		TransactionV2::Legacy(t) => {
+			sig[0..32].copy_from_slice(&t.signature.r()[..]);
+			sig[32..64].copy_from_slice(&t.signature.s()[..]);
+			sig[64] = t.signature.standard_v();
+			msg.copy_from_slice(&LegacyTransactionMessage::from(t.clone()).hash()[..]);
+		},
+		TransactionV2::EIP2930(t) => {
+			sig[0..32].copy_from_slice(&t.r[..]);
+			sig[32..64].copy_from_slice(&t.s[..]);
+			sig[64] = t.odd_y_parity as u8;
+			msg.copy_from_slice(&EIP2930TransactionMessage::from(t.clone()).hash()[..]);
+		},
+		TransactionV2::EIP1559(t) => {
+			sig[0..32].copy_from_slice(&t.r[..]);
+			sig[32..64].copy_from_slice(&t.s[..]);
+			sig[64] = t.odd_y_parity as u8;
+			msg.copy_from_slice(&EIP1559TransactionMessage::from(t.clone()).hash()[..]);
+		},
+	}
+	let pubkey = secp256k1_ecdsa_recover(&sig, &msg).ok()?;
+	Some(H160::from(H256::from(keccak_256(&pubkey))))
+}
+
+fn into_access_list(access_list: Vec<AccessListItem>) -> Vec<(H160, Vec<H256>)> {
+	access_list.into_iter().map(|item| (item.address, item.storage_keys)).collect()
+}
+
+/// Gas limit of the simulations of the public `evm_call` getter. Anyone can request them, so they
+/// are limited well below the block gas limit, which applies to the signed `evm_call` getter.
+pub const PUBLIC_EVM_CALL_GAS_LIMIT: u64 = 5_000_000;
+
+/// The block gas limit, as a gas limit of a simulated call.
+pub fn block_gas_limit() -> Result<u64, StfError> {
+	gas_limit_as_u64(BlockGasLimit::get())
+}
+
+fn capped_gas_limit(requested_gas_limit: Option<u64>, max_gas_limit: u64) -> u64 {
+	requested_gas_limit.map_or(max_gas_limit, |gas_limit| gas_limit.min(max_gas_limit))
+}
+
+fn gas_limit_as_u64(gas_limit: U256) -> Result<u64, StfError> {
+	if gas_limit > U256::from(u64::MAX) {
+		return Err(StfError::Dispatch(format!("Gas limit {} is too high", gas_limit)))
+	}
+	Ok(gas_limit.low_u64())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_utils::hex::decode_hex;
+
+	// Example transaction of EIP-155, signed with the private key 0x4646..46.
+	const EIP155_EXAMPLE_TRANSACTION: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+	fn eip155_example_transaction() -> Vec<u8> {
+		decode_hex(EIP155_EXAMPLE_TRANSACTION).unwrap()
+	}
+
+	#[test]
+	fn requested_gas_limit_is_capped() {
+		assert_eq!(capped_gas_limit(None, 1_000), 1_000);
+		assert_eq!(capped_gas_limit(Some(500), 1_000), 500);
+		assert_eq!(capped_gas_limit(Some(u64::MAX), 1_000), 1_000);
+	}
+
+	#[test]
+	fn decoding_signed_transaction_recovers_sender() {
+		let transaction =
+			EthereumTransaction::decode_signed(&eip155_example_transaction()).unwrap();
+
+		assert_eq!(
+			transaction.source,
+			H160::from_slice(&decode_hex("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap())
+		);
+		assert_eq!(transaction.transaction_type, 0);
+		assert_eq!(transaction.chain_id, Some(1));
+		assert_eq!(transaction.nonce, U256::from(9));
+		assert_eq!(transaction.action, TransactionAction::Call(H160::repeat_byte(0x35)));
+		assert_eq!(transaction.value, U256::exp10(18));
+		assert_eq!(transaction.gas_limit, U256::from(21_000));
+		assert_eq!(transaction.hash, H256::from(keccak_256(&eip155_example_transaction())));
+	}
+
+	#[test]
+	fn tampered_transaction_has_another_sender() {
+		let original = EthereumTransaction::decode_signed(&eip155_example_transaction()).unwrap();
+		let mut tampered = eip155_example_transaction();
+		// Increment the nonce, which is the first field after the two bytes of the list header.
+		tampered[2] += 1;
+
+		let tampered = EthereumTransaction::decode_signed(&tampered).unwrap();
+
+		assert_eq!(tampered.nonce, U256::from(10));
+		assert_ne!(tampered.source, original.source);
+	}
+
+	#[test]
+	fn decoding_garbage_fails() {
+		assert!(EthereumTransaction::decode_signed(&[0xde, 0xad, 0xbe, 0xef]).is_err());
+	}
+
+	#[test]
+	fn shards_have_distinct_chain_ids() {
+		let shard = ShardIdentifier::repeat_byte(1);
+
+		assert_eq!(evm_chain_id(&shard), evm_chain_id(&shard));
+		assert_ne!(evm_chain_id(&shard), evm_chain_id(&ShardIdentifier::repeat_byte(2)));
+		assert!(evm_chain_id(&shard) <= u64::from(u32::MAX));
+	}
+
+	fn log(address: u8, topics: &[u8]) -> EvmLog {
+		EvmLog {
+			address: H160::repeat_byte(address),
+			topics: topics.iter().map(|topic| H256::repeat_byte(*topic)).collect(),
+			data: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn empty_log_filter_matches_all_logs() {
+		let filter = EvmLogFilter::default();
+
+		assert!(filter.matches(&log(1, &[])));
+		assert!(filter.matches(&log(2, &[3, 4])));
+	}
+
+	#[test]
+	fn log_filter_matches_address() {
+		let filter = EvmLogFilter { address: Some(H160::repeat_byte(1)), topics: Vec::new() };
+
+		assert!(filter.matches(&log(1, &[3])));
+		assert!(!filter.matches(&log(2, &[3])));
+	}
+
+	#[test]
+	fn log_filter_matches_topics_by_position() {
+		let filter = EvmLogFilter { address: None, topics: vec![None, Some(H256::repeat_byte(4))] };
+
+		assert!(filter.matches(&log(1, &[3, 4])));
+		assert!(filter.matches(&log(1, &[5, 4, 6])));
+		assert!(!filter.matches(&log(1, &[4, 3])));
+		assert!(!filter.matches(&log(1, &[3])));
+	}
+}
@@ -15,6 +15,7 @@
 
 */
 
+use crate::receipts::get_receipts_page;
 use codec::{Decode, Encode};
 use ita_sgx_runtime::System;
 use itp_stf_interface::ExecuteGetter;
@@ -26,13 +27,23 @@ use itp_utils::stringify::account_id_to_string;
 use log::*;
 use sp_runtime::traits::Verify;
 use sp_std::vec;
+#[cfg(feature = "evm")]
+use std::format;
 use std::prelude::v1::*;
 
 #[cfg(feature = "evm")]
-use ita_sgx_runtime::{AddressMapping, HashedAddressMapping};
+use ita_sgx_runtime::{evm_base_fee, AddressMapping, EvmPrivateData, HashedAddressMapping};
 
 #[cfg(feature = "evm")]
-use crate::evm_helpers::{get_evm_account, get_evm_account_codes, get_evm_account_storages};
+use crate::{
+	evm_helpers::{get_evm_account, get_evm_account_codes, get_evm_account_storages},
+	evm_state_access::{get_evm_state_access_policy, may_read_evm_state, EvmStateAccessPolicy},
+	evm_transaction::{
+		block_gas_limit, get_evm_logs, get_evm_receipt, get_evm_receipt_of_call, simulate_evm_call,
+		EvmCallRequest, EvmLogFilter, PUBLIC_EVM_CALL_GAS_LIMIT,
+	},
+	receipts::get_receipts,
+};
 
 use itp_stf_primitives::traits::PoolTransactionValidation;
 #[cfg(feature = "evm")]
@@ -94,6 +105,25 @@ impl PoolTransactionValidation for Getter {
 #[allow(non_camel_case_types)]
 pub enum PublicGetter {
 	some_value,
+	block_number,
+	// The public EVM getters back the `eth_*` RPC methods and are accessible to anyone. The
+	// EVM state is only revealed by them if the `EvmStateAccessPolicy` is public.
+	#[cfg(feature = "evm")]
+	evm_balance(H160),
+	#[cfg(feature = "evm")]
+	evm_transaction_count(H160),
+	#[cfg(feature = "evm")]
+	evm_code(H160),
+	#[cfg(feature = "evm")]
+	evm_storage(H160, H256),
+	#[cfg(feature = "evm")]
+	evm_call(EvmCallRequest),
+	#[cfg(feature = "evm")]
+	evm_transaction_receipt(H256),
+	#[cfg(feature = "evm")]
+	evm_state_access_policy,
+	#[cfg(feature = "evm")]
+	evm_base_fee,
 }
 
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
@@ -102,12 +132,21 @@ pub enum TrustedGetter {
 	free_balance(AccountId),
 	reserved_balance(AccountId),
 	nonce(AccountId),
+	trusted_call_history(AccountId, u32, u32), // (Account, page, page size)
 	#[cfg(feature = "evm")]
 	evm_nonce(AccountId),
 	#[cfg(feature = "evm")]
 	evm_account_codes(AccountId, H160),
 	#[cfg(feature = "evm")]
 	evm_account_storages(AccountId, H160, H256),
+	#[cfg(feature = "evm")]
+	evm_receipt(AccountId, H256), // (Account, hash of one of its signed trusted calls)
+	#[cfg(feature = "evm")]
+	evm_logs(AccountId, EvmLogFilter),
+	#[cfg(feature = "evm")]
+	evm_private_data(AccountId, H256), // (Recipient, id returned by the private data precompile)
+	#[cfg(feature = "evm")]
+	evm_call(AccountId, EvmCallRequest), // (Account, call simulated from its EVM account)
 }
 
 impl TrustedGetter {
@@ -116,12 +155,21 @@ impl TrustedGetter {
 			TrustedGetter::free_balance(sender_account) => sender_account,
 			TrustedGetter::reserved_balance(sender_account) => sender_account,
 			TrustedGetter::nonce(sender_account) => sender_account,
+			TrustedGetter::trusted_call_history(sender_account, ..) => sender_account,
 			#[cfg(feature = "evm")]
 			TrustedGetter::evm_nonce(sender_account) => sender_account,
 			#[cfg(feature = "evm")]
 			TrustedGetter::evm_account_codes(sender_account, _) => sender_account,
 			#[cfg(feature = "evm")]
 			TrustedGetter::evm_account_storages(sender_account, ..) => sender_account,
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_receipt(sender_account, _) => sender_account,
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_logs(sender_account, _) => sender_account,
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_private_data(sender_account, _) => sender_account,
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_call(sender_account, _) => sender_account,
 		}
 	}
 
@@ -162,6 +210,21 @@ impl ExecuteGetter for Getter {
 			Getter::public(g) => g.get_storage_hashes_to_update(),
 		}
 	}
+
+	fn mutates_state(&self) -> bool {
+		#[cfg(feature = "evm")]
+		if let Getter::public(PublicGetter::evm_call(_)) = self {
+			return true
+		}
+		#[cfg(feature = "evm")]
+		if let Getter::trusted(TrustedGetterSigned {
+			getter: TrustedGetter::evm_call(..), ..
+		}) = self
+		{
+			return true
+		}
+		false
+	}
 }
 
 impl ExecuteGetter for TrustedGetterSigned {
@@ -187,6 +250,12 @@ impl ExecuteGetter for TrustedGetterSigned {
 				debug!("Account nonce is {}", nonce);
 				Some(nonce.encode())
 			},
+			TrustedGetter::trusted_call_history(who, page, page_size) => {
+				let receipts = get_receipts_page(&who, page, page_size);
+				debug!("TrustedGetter trusted_call_history");
+				debug!("Returning {} receipts of page {}", receipts.len(), page);
+				Some(receipts.encode())
+			},
 			#[cfg(feature = "evm")]
 			TrustedGetter::evm_nonce(who) => {
 				let evm_account = get_evm_account(&who);
@@ -197,9 +266,11 @@ impl ExecuteGetter for TrustedGetterSigned {
 				Some(nonce.encode())
 			},
 			#[cfg(feature = "evm")]
-			TrustedGetter::evm_account_codes(_who, evm_account) =>
-			// TODO: This probably needs some security check if who == evm_account (or assosciated)
-				if let Some(info) = get_evm_account_codes(&evm_account) {
+			TrustedGetter::evm_account_codes(who, evm_account) =>
+				if !may_read_evm_state(&who, &evm_account) {
+					warn!("TrustedGetter evm_account_codes: access to {:?} denied", evm_account);
+					None
+				} else if let Some(info) = get_evm_account_codes(&evm_account) {
 					debug!("TrustedGetter Evm Account Codes");
 					debug!("AccountCodes for {} is {:?}", evm_account, info);
 					Some(info) // TOOD: encoded?
@@ -207,15 +278,60 @@ impl ExecuteGetter for TrustedGetterSigned {
 					None
 				},
 			#[cfg(feature = "evm")]
-			TrustedGetter::evm_account_storages(_who, evm_account, index) =>
-			// TODO: This probably needs some security check if who == evm_account (or assosciated)
-				if let Some(value) = get_evm_account_storages(&evm_account, &index) {
+			TrustedGetter::evm_account_storages(who, evm_account, index) =>
+				if !may_read_evm_state(&who, &evm_account) {
+					warn!("TrustedGetter evm_account_storages: access to {:?} denied", evm_account);
+					None
+				} else if let Some(value) = get_evm_account_storages(&evm_account, &index) {
 					debug!("TrustedGetter Evm Account Storages");
 					debug!("AccountStorages for {} is {:?}", evm_account, value);
 					Some(value.encode())
 				} else {
 					None
 				},
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_receipt(who, call_hash) => {
+				debug!("TrustedGetter evm_receipt");
+				// Only receipts of the calls of `who` are revealed to `who`.
+				let receipt = get_receipts(&who)
+					.iter()
+					.any(|receipt| receipt.call_hash == call_hash)
+					.then(|| get_evm_receipt_of_call(&call_hash))
+					.flatten();
+				Some(receipt.encode())
+			},
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_logs(who, filter) => {
+				let logs = get_evm_logs(&who, &filter);
+				debug!("TrustedGetter evm_logs");
+				debug!("Returning {} logs matching {:?}", logs.len(), filter);
+				Some(logs.encode())
+			},
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_call(who, request) => {
+				debug!("TrustedGetter evm_call to {:?}", request.to);
+				// A contract may only be called by those who may read its state.
+				if request.to.map_or(false, |to| !may_read_evm_state(&who, &to)) {
+					warn!("TrustedGetter evm_call: access to {:?} denied", request.to);
+					return None
+				}
+				let request = EvmCallRequest { from: Some(get_evm_account(&who)), ..request };
+				Some(
+					block_gas_limit()
+						.and_then(|gas_limit| simulate_evm_call(request, gas_limit))
+						.map_err(|e| format!("{:?}", e))
+						.encode(),
+				)
+			},
+			#[cfg(feature = "evm")]
+			TrustedGetter::evm_private_data(who, id) => {
+				debug!("TrustedGetter evm_private_data");
+				// Private data is only revealed to its recipient.
+				let data = EvmPrivateData::get(id)
+					.filter(|private| private.recipient == get_evm_account(&who))
+					.map(|private| private.data);
+				Some(data.encode())
+			},
 		}
 	}
 
@@ -228,6 +344,47 @@ impl ExecuteGetter for PublicGetter {
 	fn execute(self) -> Option<Vec<u8>> {
 		match self {
 			PublicGetter::some_value => Some(42u32.encode()),
+			PublicGetter::block_number => Some(System::block_number().encode()),
+			// Without a signature, the EVM state is only revealed if it is public.
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_balance(evm_account) => is_evm_state_public().then(|| {
+				let account = HashedAddressMapping::into_account_id(evm_account);
+				System::account(&account).data.free.encode()
+			}),
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_transaction_count(evm_account) => is_evm_state_public().then(|| {
+				let account = HashedAddressMapping::into_account_id(evm_account);
+				System::account_nonce(&account).encode()
+			}),
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_code(evm_account) => is_evm_state_public()
+				.then(|| get_evm_account_codes(&evm_account).unwrap_or_default().encode()),
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_storage(evm_account, index) => is_evm_state_public().then(|| {
+				get_evm_account_storages(&evm_account, &index).unwrap_or_default().encode()
+			}),
+			// Otherwise, the `evm_call` trusted getter simulates calls from the signer only.
+			// Public simulations are unauthenticated, so their gas is capped. Like any request,
+			// they are charged to the rate limits of the trusted RPC server.
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_call(request) => is_evm_state_public().then(|| {
+				debug!("PublicGetter evm_call to {:?}", request.to);
+				simulate_evm_call(request, PUBLIC_EVM_CALL_GAS_LIMIT)
+					.map_err(|e| format!("{:?}", e))
+					.encode()
+			}),
+			// Otherwise, the `evm_receipt` trusted getter reveals receipts to their senders only,
+			// like it does for the receipts of trusted calls in any case.
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_transaction_receipt(transaction_hash) => is_evm_state_public().then(|| {
+				get_evm_receipt(&transaction_hash)
+					.filter(|receipt| receipt.transaction_type.is_some())
+					.encode()
+			}),
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_state_access_policy => Some(get_evm_state_access_policy().encode()),
+			#[cfg(feature = "evm")]
+			PublicGetter::evm_base_fee => Some(evm_base_fee().encode()),
 		}
 	}
 
@@ -235,3 +392,8 @@ impl ExecuteGetter for PublicGetter {
 		Vec::new()
 	}
 }
+
+#[cfg(feature = "evm")]
+fn is_evm_state_public() -> bool {
+	get_evm_state_access_policy() == EvmStateAccessPolicy::Public
+}
@@ -32,13 +32,19 @@ pub use trusted_call::*;
 
 #[cfg(feature = "evm")]
 pub mod evm_helpers;
+#[cfg(feature = "evm")]
+pub mod evm_state_access;
+#[cfg(feature = "evm")]
+pub mod evm_transaction;
 pub mod getter;
 pub mod hash;
 pub mod helpers;
+pub mod receipts;
 pub mod stf_sgx;
 pub mod stf_sgx_primitives;
 #[cfg(all(feature = "test", feature = "sgx"))]
 pub mod stf_sgx_tests;
+pub mod subscriptions;
 #[cfg(all(feature = "test", feature = "sgx"))]
 pub mod test_genesis;
 pub mod trusted_call;
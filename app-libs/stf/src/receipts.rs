@@ -0,0 +1,114 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Bounded per-account log of executed trusted calls.
+//!
+//! Each executed trusted call (successful or not) leaves a receipt in the state of the
+//! sender, so that clients can look up the outcome after their watch connection is closed.
+
+use crate::helpers::get_storage_map;
+use codec::{Decode, Encode};
+use ita_sgx_runtime::{BlockNumber, Index, RuntimeEvent, System};
+use itp_stf_primitives::types::AccountId;
+use itp_storage::{storage_map_key, StorageHasher};
+use sp_core::H256;
+use std::prelude::v1::*;
+
+pub const RECEIPTS_STORAGE_PREFIX: &str = "TrustedCallReceipts";
+pub const RECEIPTS_STORAGE_NAME: &str = "Receipts";
+
+/// Number of receipts kept per account. Older receipts are dropped first.
+pub const MAX_RECEIPTS_PER_ACCOUNT: usize = 64;
+
+/// Maximum number of receipts returned by a single history getter.
+pub const MAX_RECEIPTS_PAGE_SIZE: u32 = 32;
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum TrustedCallOutcome {
+	Success,
+	Failure(String),
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct TrustedCallReceipt {
+	/// Blake2-256 hash of the encoded `TrustedCallSigned`.
+	pub call_hash: H256,
+	pub nonce: Index,
+	/// Sidechain block number the call was executed in.
+	pub block_number: BlockNumber,
+	pub outcome: TrustedCallOutcome,
+	/// Events emitted by the runtime while executing the call.
+	pub events: Vec<RuntimeEvent>,
+}
+
+pub fn receipts_key(account: &AccountId) -> Vec<u8> {
+	storage_map_key(
+		RECEIPTS_STORAGE_PREFIX,
+		RECEIPTS_STORAGE_NAME,
+		account,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+/// All stored receipts of `account`, oldest first.
+pub fn get_receipts(account: &AccountId) -> Vec<TrustedCallReceipt> {
+	get_storage_map(
+		RECEIPTS_STORAGE_PREFIX,
+		RECEIPTS_STORAGE_NAME,
+		account,
+		&StorageHasher::Blake2_128Concat,
+	)
+	.unwrap_or_default()
+}
+
+/// Page through the receipts of `account`, newest first.
+///
+/// `page_size` is capped at `MAX_RECEIPTS_PAGE_SIZE`.
+pub fn get_receipts_page(
+	account: &AccountId,
+	page: u32,
+	page_size: u32,
+) -> Vec<TrustedCallReceipt> {
+	let page_size = page_size.min(MAX_RECEIPTS_PAGE_SIZE) as usize;
+	get_receipts(account)
+		.into_iter()
+		.rev()
+		.skip((page as usize).saturating_mul(page_size))
+		.take(page_size)
+		.collect()
+}
+
+/// Appends a receipt to the log of `account`, dropping the oldest ones above
+/// `MAX_RECEIPTS_PER_ACCOUNT`.
+///
+/// Returns the dropped receipts, so that data kept along with them can be pruned as well.
+pub fn store_receipt(account: &AccountId, receipt: TrustedCallReceipt) -> Vec<TrustedCallReceipt> {
+	let mut receipts = get_receipts(account);
+	receipts.push(receipt);
+	let excess = receipts.len().saturating_sub(MAX_RECEIPTS_PER_ACCOUNT);
+	let dropped_receipts = receipts.drain(..excess).collect();
+	sp_io::storage::set(&receipts_key(account), &receipts.encode());
+	dropped_receipts
+}
+
+/// Events deposited since the event count was `event_count_before`.
+pub fn events_since(event_count_before: u32) -> Vec<RuntimeEvent> {
+	System::read_events_no_consensus()
+		.skip(event_count_before as usize)
+		.map(|record| record.event)
+		.collect()
+}
@@ -22,9 +22,11 @@ use crate::{
 	Stf, ENCLAVE_ACCOUNT_KEY,
 };
 use codec::{Decode, Encode};
-use frame_support::traits::{OriginTrait, UnfilteredDispatchable};
+use frame_support::traits::{Get, OriginTrait, UnfilteredDispatchable};
+use frame_system::LastRuntimeUpgradeInfo;
 use ita_sgx_runtime::{
-	ParentchainInstanceIntegritee, ParentchainInstanceTargetA, ParentchainInstanceTargetB,
+	Executive, ParentchainInstanceIntegritee, ParentchainInstanceTargetA,
+	ParentchainInstanceTargetB, VERSION,
 };
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
 use itp_sgx_externalities::SgxExternalitiesTrait;
@@ -32,8 +34,8 @@ use itp_stf_interface::{
 	parentchain_pallet::ParentchainPalletInstancesInterface,
 	sudo_pallet::SudoPalletInterface,
 	system_pallet::{SystemPalletAccountInterface, SystemPalletEventInterface},
-	ExecuteCall, ExecuteGetter, InitState, ShardCreationInfo, ShardCreationQuery, ShardVaultQuery,
-	StateCallInterface, StateGetterInterface, UpdateState,
+	ExecuteCall, ExecuteGetter, InitState, RuntimeUpgradeInterface, ShardCreationInfo,
+	ShardCreationQuery, ShardVaultQuery, StateCallInterface, StateGetterInterface, UpdateState,
 };
 use itp_stf_primitives::{error::StfError, traits::TrustedCallVerification};
 use itp_storage::storage_value_key;
@@ -75,6 +77,12 @@ where
 				&storage_value_key("Balances", "ExistentialDeposit"),
 				&1u128.encode(),
 			);
+
+			// A new state is up to date, migrations only have to run for states written by
+			// an older runtime.
+			frame_system::LastRuntimeUpgrade::<Runtime>::put(LastRuntimeUpgradeInfo::from(
+				<Runtime as frame_system::Config>::Version::get(),
+			));
 		});
 
 		#[cfg(feature = "test")]
@@ -96,6 +104,32 @@ where
 	}
 }
 
+impl<TCS, G, State> RuntimeUpgradeInterface<State> for Stf<TCS, G, State, ita_sgx_runtime::Runtime>
+where
+	State: SgxExternalitiesTrait,
+{
+	fn state_spec_version(state: &mut State) -> Option<u32> {
+		state.execute_with(|| {
+			frame_system::LastRuntimeUpgrade::<ita_sgx_runtime::Runtime>::get()
+				.map(|info| info.spec_version.0)
+		})
+	}
+
+	fn runtime_spec_version() -> u32 {
+		VERSION.spec_version
+	}
+
+	fn on_runtime_upgrade(state: &mut State) {
+		state.execute_with(|| {
+			let weight = Executive::execute_on_runtime_upgrade();
+			debug!("runtime upgrade migrations consumed {:?}", weight);
+			frame_system::LastRuntimeUpgrade::<ita_sgx_runtime::Runtime>::put(
+				LastRuntimeUpgradeInfo::from(VERSION),
+			);
+		})
+	}
+}
+
 impl<TCS, G, State, Runtime>
 	UpdateState<State, <State as SgxExternalitiesTrait>::SgxExternalitiesDiffType>
 	for Stf<TCS, G, State, Runtime>
@@ -160,9 +194,12 @@ where
 impl<TCS, G, State, Runtime> StateGetterInterface<G, State> for Stf<TCS, G, State, Runtime>
 where
 	G: PartialEq + ExecuteGetter,
-	State: SgxExternalitiesTrait + Debug,
+	State: SgxExternalitiesTrait + Clone + Debug,
 {
 	fn execute_getter(state: &mut State, getter: G) -> Option<Vec<u8>> {
+		if getter.mutates_state() {
+			return state.clone().execute_with(|| getter.execute())
+		}
 		state.execute_with(|| getter.execute())
 	}
 }
@@ -15,20 +15,29 @@
 
 */
 
-use crate::{Getter, State, Stf, TrustedCall, TrustedCallSigned};
-use ita_sgx_runtime::Runtime;
+use crate::{
+	helpers::set_block_number,
+	receipts::{get_receipts, get_receipts_page, TrustedCallOutcome},
+	subscriptions::{
+		events_in_state, events_of_account, AcceptedSubscriptions, AccountEventsSubscription,
+		MAX_SUBSCRIPTION_VALIDITY_MILLIS,
+	},
+	Getter, State, Stf, TrustedCall, TrustedCallSigned,
+};
+use ita_sgx_runtime::{Runtime, RuntimeEvent, System, TransactionPaymentEvent};
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_interface::{
 	parentchain_pallet::ParentchainPalletInstancesInterface, sudo_pallet::SudoPalletInterface,
 	system_pallet::SystemPalletAccountInterface, InitState, StateCallInterface,
 };
-use itp_stf_primitives::types::{AccountId, Signature};
+use itp_stf_primitives::types::{AccountId, KeyPair, Signature};
 use itp_types::parentchain::ParentchainId;
 use sp_core::{
 	ed25519::{Pair as Ed25519Pair, Signature as Ed25519Signature},
 	Pair,
 };
-use std::{sync::Arc, vec::Vec};
+use std::{boxed::Box, sync::Arc, vec, vec::Vec};
 
 pub type StfState = Stf<TrustedCallSigned, Getter, State, Runtime>;
 
@@ -74,3 +83,147 @@ pub fn test_root_account_exists_after_initialization() {
 	let account_data = StfState::get_account_data(&mut state, &root_account);
 	assert!(account_data.free > 0);
 }
+
+pub fn executed_call_stores_receipt_for_sender() {
+	let sender = AccountId::new([3u8; 32]);
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+
+	StfState::execute_call(&mut state, noop_call(&sender, 0), &mut Vec::new(), repo).unwrap();
+
+	let receipts = state.execute_with(|| get_receipts(&sender));
+	assert_eq!(1, receipts.len());
+	assert_eq!(0, receipts[0].nonce);
+	assert_eq!(TrustedCallOutcome::Success, receipts[0].outcome);
+}
+
+pub fn failed_call_stores_failure_receipt() {
+	let enclave_call_signer = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let enclave_signer_account_id: AccountId = enclave_call_signer.public().into();
+	let mut state = StfState::init_state(enclave_signer_account_id.clone());
+
+	// Shielding fails because no shard vault has been initialized.
+	let shield_funds_call = TrustedCallSigned::new(
+		TrustedCall::balance_shield(
+			enclave_call_signer.public().into(),
+			AccountId::new([1u8; 32]),
+			500u128,
+			ParentchainId::Integritee,
+		),
+		0,
+		Signature::Ed25519(Ed25519Signature([0u8; 64])),
+	);
+
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+	assert!(StfState::execute_call(&mut state, shield_funds_call, &mut Vec::new(), repo).is_err());
+
+	let receipts = state.execute_with(|| get_receipts(&enclave_signer_account_id));
+	assert_eq!(1, receipts.len());
+	assert!(matches!(receipts[0].outcome, TrustedCallOutcome::Failure(_)));
+}
+
+pub fn receipts_are_paged_newest_first() {
+	let sender = AccountId::new([3u8; 32]);
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+
+	for nonce in 0..3 {
+		StfState::execute_call(
+			&mut state,
+			noop_call(&sender, nonce),
+			&mut Vec::new(),
+			repo.clone(),
+		)
+		.unwrap();
+	}
+
+	let first_page = state.execute_with(|| get_receipts_page(&sender, 0, 2));
+	let second_page = state.execute_with(|| get_receipts_page(&sender, 1, 2));
+	assert_eq!(vec![2, 1], first_page.iter().map(|r| r.nonce).collect::<Vec<_>>());
+	assert_eq!(vec![0], second_page.iter().map(|r| r.nonce).collect::<Vec<_>>());
+}
+
+pub fn signed_account_events_subscription_is_verified() {
+	let pair = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let account: AccountId = pair.public().into();
+	let mrenclave = [1u8; 32];
+	let subscription = AccountEventsSubscription::new(account, Default::default(), 0, 1_000);
+
+	let mut signed = subscription.sign(&KeyPair::Ed25519(Box::new(pair)), &mrenclave);
+	assert!(signed.verify_signature(&mrenclave));
+	assert!(!signed.verify_signature(&[2u8; 32]));
+
+	signed.subscription.shard = [3u8; 32].into();
+	assert!(!signed.verify_signature(&mrenclave));
+}
+
+pub fn account_events_subscription_is_accepted_once_until_it_expires() {
+	let account = AccountId::new([3u8; 32]);
+	let subscription =
+		AccountEventsSubscription::new(account.clone(), Default::default(), 0, 1_000);
+	let mut accepted = AcceptedSubscriptions::default();
+
+	assert!(accepted.accept(&subscription, 1_001).is_err());
+	assert!(accepted
+		.accept(
+			&AccountEventsSubscription {
+				expires_at: 1_000 + MAX_SUBSCRIPTION_VALIDITY_MILLIS,
+				..subscription.clone()
+			},
+			999
+		)
+		.is_err());
+
+	assert!(accepted.accept(&subscription, 500).is_ok());
+	assert!(accepted.accept(&subscription, 600).is_err());
+	assert!(accepted
+		.accept(&AccountEventsSubscription { nonce: 1, ..subscription.clone() }, 600)
+		.is_ok());
+
+	// Once the first subscription has expired, its nonce may be used by a new one.
+	let renewed = AccountEventsSubscription { expires_at: 2_000, ..subscription };
+	assert!(accepted.accept(&renewed, 1_500).is_ok());
+}
+
+pub fn transfer_events_concern_sender_and_receiver_only() {
+	let from = AccountId::new([3u8; 32]);
+	let to = AccountId::new([4u8; 32]);
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+
+	let transfer = RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+		from: from.clone(),
+		to: to.clone(),
+		amount: 10,
+	});
+
+	state.execute_with(|| {
+		set_block_number(1);
+		System::deposit_event(transfer.clone());
+	});
+
+	let events = events_in_state(&state);
+	assert_eq!(vec![transfer.clone()], events_of_account(&events, &from));
+	assert_eq!(vec![transfer], events_of_account(&events, &to));
+	assert!(events_of_account(&events, &AccountId::new([5u8; 32])).is_empty());
+}
+
+pub fn fee_payment_events_concern_the_payer() {
+	let payer = AccountId::new([3u8; 32]);
+	let fee_paid = RuntimeEvent::TransactionPayment(TransactionPaymentEvent::TransactionFeePaid {
+		who: payer.clone(),
+		actual_fee: 10,
+		tip: 0,
+	});
+
+	let events = vec![fee_paid.clone()];
+	assert_eq!(vec![fee_paid], events_of_account(&events, &payer));
+	assert!(events_of_account(&events, &AccountId::new([4u8; 32])).is_empty());
+}
+
+fn noop_call(sender: &AccountId, nonce: u32) -> TrustedCallSigned {
+	TrustedCallSigned::new(
+		TrustedCall::noop(sender.clone()),
+		nonce,
+		Signature::Ed25519(Ed25519Signature([0u8; 64])),
+	)
+}
@@ -0,0 +1,199 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Subscriptions of incognito accounts to the sidechain events concerning them.
+//!
+//! Events are only sent to a client that signed the subscription with the account key. Like a
+//! trusted call, the signature is bound to the enclave and the shard, and each subscription is
+//! accepted only once before it expires.
+
+#[cfg(feature = "evm")]
+use crate::evm_helpers::get_evm_account;
+use crate::State;
+use codec::{Decode, Encode};
+use frame_system::EventRecord;
+use ita_sgx_runtime::RuntimeEvent;
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_primitives::types::{AccountId, KeyPair, ShardIdentifier, Signature};
+use itp_storage::storage_value_key;
+use sp_core::H256;
+use sp_runtime::traits::Verify;
+use std::{
+	collections::{btree_map::Entry, BTreeMap},
+	format,
+	prelude::v1::*,
+};
+
+#[cfg(feature = "evm")]
+use sp_core::H160;
+
+/// Payload of an account events notification.
+pub type AccountEvents = Vec<RuntimeEvent>;
+
+/// How far in the future a subscription may expire. Bounds the time window in which an
+/// intercepted subscription could be replayed, and how long the enclave remembers its nonce.
+pub const MAX_SUBSCRIPTION_VALIDITY_MILLIS: u64 = 60_000;
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AccountEventsSubscription {
+	pub account: AccountId,
+	pub shard: ShardIdentifier,
+	/// Chosen by the client, a nonce is accepted only once per account until it expires.
+	pub nonce: u64,
+	/// Unix time in millis after which the subscription is rejected.
+	pub expires_at: u64,
+}
+
+impl AccountEventsSubscription {
+	pub fn new(account: AccountId, shard: ShardIdentifier, nonce: u64, expires_at: u64) -> Self {
+		AccountEventsSubscription { account, shard, nonce, expires_at }
+	}
+
+	pub fn sign(&self, pair: &KeyPair, mrenclave: &[u8; 32]) -> AccountEventsSubscriptionSigned {
+		let signature = pair.sign(self.signature_payload(mrenclave).as_slice());
+		AccountEventsSubscriptionSigned { subscription: self.clone(), signature }
+	}
+
+	fn signature_payload(&self, mrenclave: &[u8; 32]) -> Vec<u8> {
+		let mut payload = self.encode();
+		payload.append(&mut mrenclave.encode());
+		payload.append(&mut self.shard.encode());
+		payload
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AccountEventsSubscriptionSigned {
+	pub subscription: AccountEventsSubscription,
+	pub signature: Signature,
+}
+
+impl AccountEventsSubscriptionSigned {
+	pub fn verify_signature(&self, mrenclave: &[u8; 32]) -> bool {
+		self.signature.verify(
+			self.subscription.signature_payload(mrenclave).as_slice(),
+			&self.subscription.account,
+		)
+	}
+}
+
+/// The subscriptions accepted by the enclave that have not yet expired.
+#[derive(Default, Debug)]
+pub struct AcceptedSubscriptions {
+	expiries: BTreeMap<(AccountId, u64), u64>,
+}
+
+impl AcceptedSubscriptions {
+	/// Accepts the (already verified) `subscription` at `now` (unix time in millis), unless it has
+	/// expired, expires too far in the future, or its nonce has been accepted before.
+	pub fn accept(
+		&mut self,
+		subscription: &AccountEventsSubscription,
+		now: u64,
+	) -> Result<(), String> {
+		self.expiries.retain(|_, expires_at| *expires_at >= now);
+
+		if subscription.expires_at < now {
+			return Err("Account events subscription has expired".into())
+		}
+		if subscription.expires_at > now.saturating_add(MAX_SUBSCRIPTION_VALIDITY_MILLIS) {
+			return Err(format!(
+				"Account events subscription must expire within {} ms",
+				MAX_SUBSCRIPTION_VALIDITY_MILLIS
+			))
+		}
+
+		match self.expiries.entry((subscription.account.clone(), subscription.nonce)) {
+			Entry::Occupied(_) => Err("Account events subscription has already been used".into()),
+			Entry::Vacant(entry) => {
+				entry.insert(subscription.expires_at);
+				Ok(())
+			},
+		}
+	}
+}
+
+/// Whether `account` is a party of the `event`.
+pub fn event_concerns_account(event: &RuntimeEvent, account: &AccountId) -> bool {
+	use frame_system::Event as SystemEvent;
+	use ita_sgx_runtime::TransactionPaymentEvent;
+	use pallet_balances::Event as BalancesEvent;
+	use pallet_sudo::Event as SudoEvent;
+
+	match event {
+		RuntimeEvent::System(SystemEvent::NewAccount { account: who })
+		| RuntimeEvent::System(SystemEvent::KilledAccount { account: who }) => who == account,
+		RuntimeEvent::Balances(balances_event) => match balances_event {
+			BalancesEvent::Endowed { account: who, .. }
+			| BalancesEvent::DustLost { account: who, .. }
+			| BalancesEvent::BalanceSet { who, .. }
+			| BalancesEvent::Reserved { who, .. }
+			| BalancesEvent::Unreserved { who, .. }
+			| BalancesEvent::Deposit { who, .. }
+			| BalancesEvent::Withdraw { who, .. }
+			| BalancesEvent::Slashed { who, .. } => who == account,
+			BalancesEvent::Transfer { from, to, .. }
+			| BalancesEvent::ReserveRepatriated { from, to, .. } => from == account || to == account,
+			_ => false,
+		},
+		RuntimeEvent::TransactionPayment(TransactionPaymentEvent::TransactionFeePaid {
+			who,
+			..
+		}) => who == account,
+		RuntimeEvent::Sudo(SudoEvent::KeyChanged { old_sudoer }) =>
+			old_sudoer.as_ref() == Some(account),
+		#[cfg(feature = "evm")]
+		RuntimeEvent::Evm(event) => evm_event_concerns_address(event, get_evm_account(account)),
+		_ => false,
+	}
+}
+
+/// Whether `address` is the contract of the `event`, or, for logs, one of the indexed topics,
+/// e.g. the sender or receiver of an ERC-20 transfer.
+#[cfg(feature = "evm")]
+fn evm_event_concerns_address(
+	event: &pallet_evm::Event<ita_sgx_runtime::Runtime>,
+	address: H160,
+) -> bool {
+	use pallet_evm::Event as EvmEvent;
+
+	match event {
+		EvmEvent::Log { log } =>
+			log.address == address || log.topics.contains(&H256::from(address)),
+		EvmEvent::Created { address: contract }
+		| EvmEvent::CreationFailed { address: contract }
+		| EvmEvent::Executed { address: contract }
+		| EvmEvent::ExecutedFailed { address: contract } => *contract == address,
+		_ => false,
+	}
+}
+
+/// The subset of `events` that concerns `account`.
+pub fn events_of_account(events: &[RuntimeEvent], account: &AccountId) -> AccountEvents {
+	events.iter().filter(|e| event_concerns_account(e, account)).cloned().collect()
+}
+
+/// The events deposited in `state`, i.e. the events of the last imported block.
+pub fn events_in_state(state: &State) -> AccountEvents {
+	state
+		.get(&storage_value_key("System", "Events"))
+		.and_then(|encoded| {
+			Vec::<EventRecord<RuntimeEvent, H256>>::decode(&mut encoded.as_slice()).ok()
+		})
+		.map(|records| records.into_iter().map(|record| record.event).collect())
+		.unwrap_or_default()
+}
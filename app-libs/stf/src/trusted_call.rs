@@ -22,19 +22,29 @@ use sp_core::{H160, H256, U256};
 use std::vec::Vec;
 
 #[cfg(feature = "evm")]
-use crate::evm_helpers::{create_code_hash, evm_create2_address, evm_create_address};
+use crate::{
+	evm_state_access::{set_evm_state_access_policy, EvmStateAccessPolicy},
+	evm_transaction::{
+		evm_chain_id, execute_ethereum_transaction, execute_evm, remove_evm_receipt_of_call,
+		EthereumTransaction, EvmAction, EvmExecution,
+	},
+};
 use crate::{
 	helpers::{enclave_signer_account, ensure_enclave_signer_account, shard_vault},
+	receipts::{events_since, store_receipt, TrustedCallOutcome, TrustedCallReceipt},
 	Getter,
 };
 use codec::{Compact, Decode, Encode};
 use frame_support::{ensure, traits::UnfilteredDispatchable};
-#[cfg(feature = "evm")]
-use ita_sgx_runtime::{AddressMapping, HashedAddressMapping};
 pub use ita_sgx_runtime::{Balance, Index};
+#[cfg(feature = "evm")]
+use ita_sgx_runtime::{
+	EnsureAddressOrigin, EnsureAddressTruncated, EvmEnabledPrecompiles, EvmFeeRecipient,
+	ENCLAVE_PRECOMPILES,
+};
 use ita_sgx_runtime::{
-	ParentchainInstanceIntegritee, ParentchainInstanceTargetA, ParentchainInstanceTargetB,
-	ParentchainIntegritee, Runtime, System,
+	ExchangeRateBits, ParentchainInstanceIntegritee, ParentchainInstanceTargetA,
+	ParentchainInstanceTargetB, ParentchainIntegritee, Runtime, System, TeeracleExchangeRates,
 };
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
 use itp_node_api_metadata::{
@@ -70,6 +80,7 @@ pub enum TrustedCall {
 	balance_unshield(AccountId, AccountId, Balance, ShardIdentifier), // (AccountIncognito, BeneficiaryPublicAccount, Amount, Shard)
 	balance_shield(AccountId, AccountId, Balance, ParentchainId), // (Root, AccountIncognito, Amount, origin parentchain)
 	timestamp_set(AccountId, Moment, ParentchainId),              // (Root, now)
+	teeracle_set_exchange_rate(AccountId, Vec<u8>, ExchangeRateBits), // (Enclave, trading pair, rate)
 	#[cfg(feature = "evm")]
 	evm_withdraw(AccountId, H160, Balance), // (Origin, Address EVM Account, Value)
 	// (Origin, Source, Target, Input, Value, Gas limit, Max fee per gas, Max priority fee per gas, Nonce, Access list)
@@ -113,6 +124,15 @@ pub enum TrustedCall {
 		Option<U256>,
 		Vec<(H160, Vec<H256>)>,
 	),
+	// (Account of the Ethereum sender, signed Ethereum transaction)
+	#[cfg(feature = "evm")]
+	evm_ethereum_transaction(AccountId, Vec<u8>),
+	#[cfg(feature = "evm")]
+	evm_set_state_access_policy(AccountId, EvmStateAccessPolicy), // (Root, policy)
+	#[cfg(feature = "evm")]
+	evm_set_fee_recipient(AccountId, Option<AccountId>), // (Root, recipient of the EVM fees)
+	#[cfg(feature = "evm")]
+	evm_set_precompile_enabled(AccountId, H160, bool), // (Root, one of the enclave precompiles, enabled)
 }
 
 impl TrustedCall {
@@ -124,6 +144,7 @@ impl TrustedCall {
 			Self::balance_unshield(sender_account, ..) => sender_account,
 			Self::balance_shield(sender_account, ..) => sender_account,
 			Self::timestamp_set(sender_account, ..) => sender_account,
+			Self::teeracle_set_exchange_rate(sender_account, ..) => sender_account,
 			#[cfg(feature = "evm")]
 			Self::evm_withdraw(sender_account, ..) => sender_account,
 			#[cfg(feature = "evm")]
@@ -132,7 +153,53 @@ impl TrustedCall {
 			Self::evm_create(sender_account, ..) => sender_account,
 			#[cfg(feature = "evm")]
 			Self::evm_create2(sender_account, ..) => sender_account,
+			#[cfg(feature = "evm")]
+			Self::evm_ethereum_transaction(sender_account, ..) => sender_account,
+			#[cfg(feature = "evm")]
+			Self::evm_set_state_access_policy(sender_account, ..) => sender_account,
+			#[cfg(feature = "evm")]
+			Self::evm_set_fee_recipient(sender_account, ..) => sender_account,
+			#[cfg(feature = "evm")]
+			Self::evm_set_precompile_enabled(sender_account, ..) => sender_account,
+		}
+	}
+
+	pub fn call_type(&self) -> &'static str {
+		match self {
+			Self::noop(..) => "noop",
+			Self::balance_set_balance(..) => "balance_set_balance",
+			Self::balance_transfer(..) => "balance_transfer",
+			Self::balance_unshield(..) => "balance_unshield",
+			Self::balance_shield(..) => "balance_shield",
+			Self::timestamp_set(..) => "timestamp_set",
+			Self::teeracle_set_exchange_rate(..) => "teeracle_set_exchange_rate",
+			#[cfg(feature = "evm")]
+			Self::evm_withdraw(..) => "evm_withdraw",
+			#[cfg(feature = "evm")]
+			Self::evm_call(..) => "evm_call",
+			#[cfg(feature = "evm")]
+			Self::evm_create(..) => "evm_create",
+			#[cfg(feature = "evm")]
+			Self::evm_create2(..) => "evm_create2",
+			#[cfg(feature = "evm")]
+			Self::evm_ethereum_transaction(..) => "evm_ethereum_transaction",
+			#[cfg(feature = "evm")]
+			Self::evm_set_state_access_policy(..) => "evm_set_state_access_policy",
+			#[cfg(feature = "evm")]
+			Self::evm_set_fee_recipient(..) => "evm_set_fee_recipient",
+			#[cfg(feature = "evm")]
+			Self::evm_set_precompile_enabled(..) => "evm_set_precompile_enabled",
+		}
+	}
+
+	/// Ethereum transactions increment the nonce of their sender when they are executed by the
+	/// EVM, like on Ethereum.
+	fn is_nonce_incremented_by_evm(&self) -> bool {
+		#[cfg(feature = "evm")]
+		if let Self::evm_ethereum_transaction(..) = self {
+			return true
 		}
+		false
 	}
 }
 
@@ -165,6 +232,25 @@ impl TrustedCallSigned {
 		TrustedCallSigned { call, nonce, signature }
 	}
 
+	/// Wraps a signed Ethereum transaction. It carries its own signature, which is verified
+	/// instead of `signature`.
+	#[cfg(feature = "evm")]
+	pub fn from_ethereum_transaction(
+		transaction: &EthereumTransaction,
+		raw_transaction: Vec<u8>,
+	) -> Result<Self, StfError> {
+		let nonce = Index::try_from(transaction.nonce)
+			.map_err(|_| StfError::Dispatch(format!("Nonce {} is too high", transaction.nonce)))?;
+		Ok(TrustedCallSigned {
+			call: TrustedCall::evm_ethereum_transaction(
+				transaction.sender_account(),
+				raw_transaction,
+			),
+			nonce,
+			..Default::default()
+		})
+	}
+
 	pub fn into_trusted_operation(
 		self,
 		direct: bool,
@@ -174,74 +260,26 @@ impl TrustedCallSigned {
 			false => TrustedOperation::indirect_call(self),
 		}
 	}
-}
-
-impl Default for TrustedCallSigned {
-	fn default() -> Self {
-		Self {
-			call: TrustedCall::noop(AccountId32::unchecked_from([0u8; 32].into())),
-			nonce: 0,
-			signature: MultiSignature::Ed25519(ed25519::Signature::unchecked_from([0u8; 64])),
-		}
-	}
-}
-impl TrustedCallVerification for TrustedCallSigned {
-	fn sender_account(&self) -> &AccountId {
-		self.call.sender_account()
-	}
 
-	fn nonce(&self) -> Index {
-		self.nonce
-	}
-
-	fn verify_signature(&self, mrenclave: &[u8; 32], shard: &ShardIdentifier) -> bool {
-		let mut payload = self.call.encode();
-		payload.append(&mut self.nonce.encode());
-		payload.append(&mut mrenclave.encode());
-		payload.append(&mut shard.encode());
-		self.signature.verify(payload.as_slice(), self.call.sender_account())
-	}
-}
-
-// TODO: #91 signed return value
-/*
-pub struct TrustedReturnValue<T> {
-	pub value: T,
-	pub signer: AccountId
-}
-
-impl TrustedReturnValue
-*/
-
-impl<NodeMetadataRepository> ExecuteCall<NodeMetadataRepository> for TrustedCallSigned
-where
-	NodeMetadataRepository: AccessNodeMetadata,
-	NodeMetadataRepository::MetadataType: NodeMetadataTrait,
-{
-	type Error = StfError;
-
-	fn execute(
+	/// Dispatches the call itself, the nonce and the receipt are handled by `execute`.
+	fn dispatch<NodeMetadataRepository>(
 		self,
+		call_hash: [u8; 32],
+		signed_call_hash: [u8; 32],
 		calls: &mut Vec<ParentchainCall>,
 		node_metadata_repo: Arc<NodeMetadataRepository>,
-	) -> Result<(), Self::Error> {
-		let sender = self.call.sender_account().clone();
-		let call_hash = blake2_256(&self.call.encode());
-		let system_nonce = System::account_nonce(&sender);
-		ensure!(self.nonce == system_nonce, Self::Error::InvalidNonce(self.nonce, system_nonce));
-
-		// increment the nonce, no matter if the call succeeds or fails.
-		// The call must have entered the transaction pool already,
-		// so it should be considered as valid
-		System::inc_account_nonce(&sender);
-
+	) -> Result<(), StfError>
+	where
+		NodeMetadataRepository: AccessNodeMetadata,
+		NodeMetadataRepository::MetadataType: NodeMetadataTrait,
+	{
 		match self.call {
 			TrustedCall::noop(who) => {
 				debug!("noop called by {}", account_id_to_string(&who),);
-				Ok::<(), Self::Error>(())
+				Ok::<(), StfError>(())
 			},
 			TrustedCall::balance_set_balance(root, who, free_balance, reserved_balance) => {
-				ensure!(is_root::<Runtime, AccountId>(&root), Self::Error::MissingPrivileges(root));
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
 				debug!(
 					"balance_set_balance({}, {}, {})",
 					account_id_to_string(&who),
@@ -254,7 +292,7 @@ where
 				}
 				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
 				.map_err(|e| {
-					Self::Error::Dispatch(format!("Balance Set Balance error: {:?}", e.error))
+					StfError::Dispatch(format!("Balance Set Balance error: {:?}", e.error))
 				})?;
 				// This explicit Error type is somehow still needed, otherwise the compiler complains
 				// 	multiple `impl`s satisfying `StfError: std::convert::From<_>`
@@ -263,7 +301,7 @@ where
 				//
 				// Alternatively, removing the customised "impl From<..> for StfError" and use map_err directly
 				// would also work
-				Ok::<(), Self::Error>(())
+				Ok::<(), StfError>(())
 			},
 			TrustedCall::balance_transfer(from, to, value) => {
 				let origin = ita_sgx_runtime::RuntimeOrigin::signed(from.clone());
@@ -285,7 +323,7 @@ where
 				}
 				.dispatch_bypass_filter(origin.clone())
 				.map_err(|e| {
-					Self::Error::Dispatch(format!("Balance Transfer error: {:?}", e.error))
+					StfError::Dispatch(format!("Balance Transfer error: {:?}", e.error))
 				})?;
 				ita_sgx_runtime::BalancesCall::<Runtime>::transfer {
 					dest: MultiAddress::Id(to),
@@ -293,7 +331,7 @@ where
 				}
 				.dispatch_bypass_filter(origin)
 				.map_err(|e| {
-					Self::Error::Dispatch(format!("Balance Transfer error: {:?}", e.error))
+					StfError::Dispatch(format!("Balance Transfer error: {:?}", e.error))
 				})?;
 				Ok(())
 			},
@@ -324,7 +362,7 @@ where
 				}
 				.dispatch_bypass_filter(origin)
 				.map_err(|e| {
-					Self::Error::Dispatch(format!("Balance Unshielding error: {:?}", e.error))
+					StfError::Dispatch(format!("Balance Unshielding error: {:?}", e.error))
 				})?;
 				burn_funds(account_incognito, value)?;
 
@@ -404,7 +442,7 @@ where
 							}
 							.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
 							.map_err(|e| {
-								Self::Error::Dispatch(format!("Timestamp Set error: {:?}", e.error))
+								StfError::Dispatch(format!("Timestamp Set error: {:?}", e.error))
 							})?;
 						};
 						ita_sgx_runtime::ParentchainPalletCall::<
@@ -415,7 +453,7 @@ where
 						}
 						.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
 						.map_err(|e| {
-							Self::Error::Dispatch(format!("Timestamp Set error: {:?}", e.error))
+							StfError::Dispatch(format!("Timestamp Set error: {:?}", e.error))
 						})?
 					},
 					ParentchainId::TargetA => ita_sgx_runtime::ParentchainPalletCall::<
@@ -426,7 +464,7 @@ where
 					}
 					.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
 					.map_err(|e| {
-						Self::Error::Dispatch(format!("Timestamp Set error: {:?}", e.error))
+						StfError::Dispatch(format!("Timestamp Set error: {:?}", e.error))
 					})?,
 					ParentchainId::TargetB => ita_sgx_runtime::ParentchainPalletCall::<
 						Runtime,
@@ -436,11 +474,21 @@ where
 					}
 					.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
 					.map_err(|e| {
-						Self::Error::Dispatch(format!("Timestamp Set error: {:?}", e.error))
+						StfError::Dispatch(format!("Timestamp Set error: {:?}", e.error))
 					})?,
 				};
 				Ok(())
 			},
+			TrustedCall::teeracle_set_exchange_rate(enclave_account, trading_pair, rate) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				debug!(
+					"teeracle_set_exchange_rate({}, {})",
+					String::from_utf8_lossy(&trading_pair),
+					rate
+				);
+				TeeracleExchangeRates::insert(trading_pair, rate);
+				Ok(())
+			},
 
 			#[cfg(feature = "evm")]
 			TrustedCall::evm_withdraw(from, address, value) => {
@@ -448,7 +496,7 @@ where
 				ita_sgx_runtime::EvmCall::<Runtime>::withdraw { address, value }
 					.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
 					.map_err(|e| {
-						Self::Error::Dispatch(format!("Evm Withdraw error: {:?}", e.error))
+						StfError::Dispatch(format!("Evm Withdraw error: {:?}", e.error))
 					})?;
 				Ok(())
 			},
@@ -471,9 +519,12 @@ where
 					source,
 					target
 				);
-				ita_sgx_runtime::EvmCall::<Runtime>::call {
+				ensure_evm_origin(from, source)?;
+				execute_evm(EvmExecution {
+					transaction_hash: signed_call_hash.into(),
+					transaction_type: None,
 					source,
-					target,
+					action: EvmAction::Call(target),
 					input,
 					value,
 					gas_limit,
@@ -481,9 +532,7 @@ where
 					max_priority_fee_per_gas,
 					nonce,
 					access_list,
-				}
-				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
-				.map_err(|e| Self::Error::Dispatch(format!("Evm Call error: {:?}", e.error)))?;
+				})?;
 				Ok(())
 			},
 			#[cfg(feature = "evm")]
@@ -504,22 +553,21 @@ where
 					source,
 					value
 				);
-				let nonce_evm_account =
-					System::account_nonce(&HashedAddressMapping::into_account_id(source));
-				ita_sgx_runtime::EvmCall::<Runtime>::create {
+				ensure_evm_origin(from, source)?;
+				let receipt = execute_evm(EvmExecution {
+					transaction_hash: signed_call_hash.into(),
+					transaction_type: None,
 					source,
-					init,
+					action: EvmAction::Create,
+					input: init,
 					value,
 					gas_limit,
 					max_fee_per_gas,
 					max_priority_fee_per_gas,
 					nonce,
 					access_list,
-				}
-				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
-				.map_err(|e| Self::Error::Dispatch(format!("Evm Create error: {:?}", e.error)))?;
-				let contract_address = evm_create_address(source, nonce_evm_account);
-				info!("Trying to create evm contract with address {:?}", contract_address);
+				})?;
+				info!("Created evm contract at address {:?}", receipt.contract_address);
 				Ok(())
 			},
 			#[cfg(feature = "evm")]
@@ -541,26 +589,173 @@ where
 					source,
 					value
 				);
-				let code_hash = create_code_hash(&init);
-				ita_sgx_runtime::EvmCall::<Runtime>::create2 {
+				ensure_evm_origin(from, source)?;
+				let receipt = execute_evm(EvmExecution {
+					transaction_hash: signed_call_hash.into(),
+					transaction_type: None,
 					source,
-					init,
-					salt,
+					action: EvmAction::Create2(salt),
+					input: init,
 					value,
 					gas_limit,
 					max_fee_per_gas,
 					max_priority_fee_per_gas,
 					nonce,
 					access_list,
+				})?;
+				info!("Created evm contract at address {:?}", receipt.contract_address);
+				Ok(())
+			},
+			#[cfg(feature = "evm")]
+			TrustedCall::evm_ethereum_transaction(sender_account, raw_transaction) => {
+				let transaction = EthereumTransaction::decode_signed(&raw_transaction)?;
+				debug!(
+					"evm_ethereum_transaction(sender: {}, source: {}, hash: {:?})",
+					account_id_to_string(&sender_account),
+					transaction.source,
+					transaction.hash
+				);
+				execute_ethereum_transaction(transaction, signed_call_hash.into()).map(|_| ())
+			},
+			#[cfg(feature = "evm")]
+			TrustedCall::evm_set_state_access_policy(root, policy) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				debug!("evm_set_state_access_policy({:?})", policy);
+				set_evm_state_access_policy(policy);
+				Ok(())
+			},
+			#[cfg(feature = "evm")]
+			TrustedCall::evm_set_fee_recipient(root, recipient) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				debug!("evm_set_fee_recipient({:?})", recipient.as_ref().map(account_id_to_string));
+				EvmFeeRecipient::set(recipient);
+				Ok(())
+			},
+			#[cfg(feature = "evm")]
+			TrustedCall::evm_set_precompile_enabled(root, precompile, enabled) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				ensure!(
+					ENCLAVE_PRECOMPILES.contains(&precompile),
+					StfError::Dispatch(format!("{:?} is no enclave precompile", precompile))
+				);
+				debug!("evm_set_precompile_enabled({:?}, {})", precompile, enabled);
+				if enabled {
+					EvmEnabledPrecompiles::insert(precompile, ());
+				} else {
+					EvmEnabledPrecompiles::remove(precompile);
 				}
-				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
-				.map_err(|e| Self::Error::Dispatch(format!("Evm Create2 error: {:?}", e.error)))?;
-				let contract_address = evm_create2_address(source, salt, code_hash);
-				info!("Trying to create evm contract with address {:?}", contract_address);
 				Ok(())
 			},
-		}?;
-		Ok(())
+		}
+	}
+}
+
+impl Default for TrustedCallSigned {
+	fn default() -> Self {
+		Self {
+			call: TrustedCall::noop(AccountId32::unchecked_from([0u8; 32].into())),
+			nonce: 0,
+			signature: MultiSignature::Ed25519(ed25519::Signature::unchecked_from([0u8; 64])),
+		}
+	}
+}
+impl TrustedCallVerification for TrustedCallSigned {
+	fn sender_account(&self) -> &AccountId {
+		self.call.sender_account()
+	}
+
+	fn nonce(&self) -> Index {
+		self.nonce
+	}
+
+	fn verify_signature(&self, mrenclave: &[u8; 32], shard: &ShardIdentifier) -> bool {
+		// Ethereum transactions are bound to the shard by its chain id, not to the enclave.
+		#[cfg(feature = "evm")]
+		if let TrustedCall::evm_ethereum_transaction(sender_account, raw_transaction) = &self.call {
+			return EthereumTransaction::decode_signed(raw_transaction).map_or(
+				false,
+				|transaction| {
+					&transaction.sender_account() == sender_account
+						&& transaction.chain_id == Some(evm_chain_id(shard))
+						&& transaction.nonce == U256::from(self.nonce)
+				},
+			)
+		}
+
+		let mut payload = self.call.encode();
+		payload.append(&mut self.nonce.encode());
+		payload.append(&mut mrenclave.encode());
+		payload.append(&mut shard.encode());
+		self.signature.verify(payload.as_slice(), self.call.sender_account())
+	}
+
+	fn call_type(&self) -> &'static str {
+		self.call.call_type()
+	}
+}
+
+// TODO: #91 signed return value
+/*
+pub struct TrustedReturnValue<T> {
+	pub value: T,
+	pub signer: AccountId
+}
+
+impl TrustedReturnValue
+*/
+
+impl<NodeMetadataRepository> ExecuteCall<NodeMetadataRepository> for TrustedCallSigned
+where
+	NodeMetadataRepository: AccessNodeMetadata,
+	NodeMetadataRepository::MetadataType: NodeMetadataTrait,
+{
+	type Error = StfError;
+
+	fn execute(
+		self,
+		calls: &mut Vec<ParentchainCall>,
+		node_metadata_repo: Arc<NodeMetadataRepository>,
+	) -> Result<(), Self::Error> {
+		let sender = self.call.sender_account().clone();
+		let call_hash = blake2_256(&self.call.encode());
+		let signed_call_hash = blake2_256(&self.encode());
+		let nonce = self.nonce;
+		let system_nonce = System::account_nonce(&sender);
+		ensure!(self.nonce == system_nonce, Self::Error::InvalidNonce(self.nonce, system_nonce));
+
+		// increment the nonce, no matter if the call succeeds or fails.
+		// The call must have entered the transaction pool already,
+		// so it should be considered as valid
+		if !self.call.is_nonce_incremented_by_evm() {
+			System::inc_account_nonce(&sender);
+		}
+
+		let event_count_before = System::event_count();
+
+		// Errors are caught here instead of returned right away, so that a receipt is
+		// stored for failed calls as well (the nonce has been incremented anyway).
+		let result = self.dispatch(call_hash, signed_call_hash, calls, node_metadata_repo);
+
+		#[cfg_attr(not(feature = "evm"), allow(unused_variables))]
+		let dropped_receipts = store_receipt(
+			&sender,
+			TrustedCallReceipt {
+				call_hash: signed_call_hash.into(),
+				nonce,
+				block_number: System::block_number(),
+				outcome: match &result {
+					Ok(()) => TrustedCallOutcome::Success,
+					Err(e) => TrustedCallOutcome::Failure(format!("{:?}", e)),
+				},
+				events: events_since(event_count_before),
+			},
+		);
+		// EVM receipts are only kept as long as the receipts of their calls.
+		#[cfg(feature = "evm")]
+		for dropped_receipt in dropped_receipts.iter() {
+			remove_evm_receipt_of_call(&dropped_receipt.call_hash);
+		}
+		result
 	}
 
 	fn get_storage_hashes_to_update(self) -> Vec<Vec<u8>> {
@@ -572,6 +767,7 @@ where
 			TrustedCall::balance_unshield(..) => debug!("No storage updates needed..."),
 			TrustedCall::balance_shield(..) => debug!("No storage updates needed..."),
 			TrustedCall::timestamp_set(..) => debug!("No storage updates needed..."),
+			TrustedCall::teeracle_set_exchange_rate(..) => debug!("No storage updates needed..."),
 			#[cfg(feature = "evm")]
 			_ => debug!("No storage updates needed..."),
 		};
@@ -579,6 +775,17 @@ where
 	}
 }
 
+/// `from` may act as the EVM account `source`, as checked by the `CallOrigin` of `pallet-evm`.
+#[cfg(feature = "evm")]
+fn ensure_evm_origin(from: AccountId, source: H160) -> Result<(), StfError> {
+	EnsureAddressTruncated::ensure_address_origin(
+		&source,
+		ita_sgx_runtime::RuntimeOrigin::signed(from),
+	)
+	.map(|_| ())
+	.map_err(|e| StfError::Dispatch(format!("Evm origin error: {:?}", e)))
+}
+
 fn burn_funds(account: AccountId, amount: u128) -> Result<(), StfError> {
 	let account_info = System::account(&account);
 	if account_info.data.free < amount {
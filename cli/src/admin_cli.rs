@@ -0,0 +1,131 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{cli_println, Cli, CliError, CliResult, CliResultOk};
+use log::*;
+use serde_json::{json, Value};
+use std::fs;
+
+/// Operate a worker running on this machine through its admin rpc.
+#[derive(Args)]
+pub struct AdminCli {
+	/// port of the admin rpc server of the worker
+	#[clap(long, default_value_t = String::from("4646"))]
+	pub(crate) admin_rpc_port: String,
+
+	/// file with the admin rpc token, it is created in the data dir of the worker
+	#[clap(long, default_value_t = String::from("admin_rpc.token"))]
+	pub(crate) token_file: String,
+
+	#[clap(subcommand)]
+	pub(crate) command: AdminCommand,
+}
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+	/// List the shards the worker operates on
+	ListShards,
+
+	/// Start operating on a shard
+	AddShard {
+		/// shard identifier, base58 encoded
+		shard: String,
+	},
+
+	/// Stop operating on a shard, its state is kept
+	RemoveShard {
+		/// shard identifier, base58 encoded
+		shard: String,
+	},
+
+	/// Pause sidechain block production
+	PauseBlockProduction,
+
+	/// Resume sidechain block production
+	ResumeBlockProduction,
+
+	/// Query whether sidechain block production is paused
+	IsBlockProductionPaused,
+
+	/// Fetch the peers of a shard from the parentchain again
+	ResyncPeers {
+		/// shard identifier, base58 encoded
+		shard: String,
+	},
+
+	/// List the hex encoded trusted operations pending in the TOP pool of a shard
+	PendingTrustedOperations {
+		/// shard identifier, base58 encoded
+		shard: String,
+	},
+
+	/// Create a new snapshot of the state of a shard
+	CreateStateSnapshot {
+		/// shard identifier, base58 encoded
+		shard: String,
+	},
+
+	/// Replace the enclave shielding key with a new one
+	RotateShieldingKey,
+}
+
+impl AdminCommand {
+	fn method_and_params(&self) -> (&'static str, Vec<String>) {
+		match self {
+			AdminCommand::ListShards => ("admin_listShards", vec![]),
+			AdminCommand::AddShard { shard } => ("admin_addShard", vec![shard.clone()]),
+			AdminCommand::RemoveShard { shard } => ("admin_removeShard", vec![shard.clone()]),
+			AdminCommand::PauseBlockProduction => ("admin_pauseBlockProduction", vec![]),
+			AdminCommand::ResumeBlockProduction => ("admin_resumeBlockProduction", vec![]),
+			AdminCommand::IsBlockProductionPaused => ("admin_isBlockProductionPaused", vec![]),
+			AdminCommand::ResyncPeers { shard } => ("admin_resyncPeers", vec![shard.clone()]),
+			AdminCommand::PendingTrustedOperations { shard } =>
+				("admin_pendingTrustedOperations", vec![shard.clone()]),
+			AdminCommand::CreateStateSnapshot { shard } =>
+				("admin_createStateSnapshot", vec![shard.clone()]),
+			AdminCommand::RotateShieldingKey => ("admin_rotateShieldingKey", vec![]),
+		}
+	}
+}
+
+impl AdminCli {
+	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
+		let token = fs::read_to_string(&self.token_file).map_err(|e| CliError::AdminRpc {
+			msg: format!("could not read admin rpc token file {}: {}", self.token_file, e),
+		})?;
+		let (method, params) = self.command.method_and_params();
+		let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+		let url = format!("http://127.0.0.1:{}", self.admin_rpc_port);
+		debug!("sending admin rpc request to {}: {}", url, request);
+
+		let response: Value = reqwest::blocking::Client::new()
+			.post(url)
+			.bearer_auth(token.trim())
+			.json(&request)
+			.send()
+			.and_then(|response| response.json())
+			.map_err(|e| CliError::AdminRpc { msg: format!("admin rpc request failed: {}", e) })?;
+
+		if let Some(error) = response.get("error") {
+			let msg = error["message"].as_str().unwrap_or("unknown error").to_string();
+			return Err(CliError::AdminRpc { msg })
+		}
+		let result = response.get("result").cloned().unwrap_or(Value::Null);
+		cli_println!(cli, "{}", serde_json::to_string_pretty(&result).unwrap_or_default());
+		Ok(CliResultOk::AdminRpc { result })
+	}
+}
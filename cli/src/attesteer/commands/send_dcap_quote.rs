@@ -15,11 +15,14 @@
 
 */
 
-use crate::{command_utils::get_worker_api_direct, Cli};
+use crate::{
+	cli_println, command_utils::get_worker_api_direct,
+	trusted_command_utils::decode_rpc_return_value, Cli, CliError, CliResult, CliResultOk,
+};
 use itc_rpc_client::direct_client::DirectApi;
-use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_rpc::RpcRequest;
 use itp_types::DirectRequestStatus;
-use itp_utils::FromHexPrefixed;
+use itp_utils::hex::hex_encode;
 use log::*;
 use std::fs::read_to_string;
 
@@ -31,31 +34,32 @@ pub struct SendDcapQuoteCmd {
 }
 
 impl SendDcapQuoteCmd {
-	pub fn run(&self, cli: &Cli) {
-		let direct_api = get_worker_api_direct(cli);
-		let hex_encoded_quote = match read_to_string(&self.quote) {
-			Ok(hex_encoded_quote) => hex_encoded_quote,
-			Err(e) => panic!("Opening hex encoded DCAP quote file failed: {:?}", e),
-		};
+	pub fn run(&self, cli: &Cli) -> CliResult {
+		let direct_api = get_worker_api_direct(cli)?;
+		let hex_encoded_quote = read_to_string(&self.quote).map_err(|e| CliError::Io {
+			msg: format!("opening hex encoded DCAP quote file failed: {:?}", e),
+		})?;
 
 		let rpc_method = "attesteer_forwardDcapQuote".to_owned();
 		let jsonrpc_call: String =
-			RpcRequest::compose_jsonrpc_call(rpc_method, vec![hex_encoded_quote]).unwrap();
+			RpcRequest::compose_jsonrpc_call(rpc_method, vec![hex_encoded_quote])
+				.map_err(|e| CliError::WorkerRpcApi { msg: e.to_string() })?;
 
-		let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
-
-		// Decode RPC response.
-		let Ok(rpc_response) = serde_json::from_str::<RpcResponse>(&rpc_response_str) else {
-			panic!("Can't parse RPC response: '{rpc_response_str}'");
-		};
-		let rpc_return_value = match RpcReturnValue::from_hex(&rpc_response.result) {
-			Ok(rpc_return_value) => rpc_return_value,
-			Err(e) => panic!("Failed to decode RpcReturnValue: {:?}", e),
-		};
+		let rpc_response_str = direct_api.get(&jsonrpc_call)?;
+		let rpc_return_value = decode_rpc_return_value(&rpc_response_str)?;
 
 		match rpc_return_value.status {
-			DirectRequestStatus::Ok => println!("DCAP quote verification succeded."),
-			_ => error!("DCAP quote verification failed"),
+			DirectRequestStatus::Ok => {
+				cli_println!(cli, "DCAP quote verification succeded.");
+				// The value is the encoded extrinsic the worker sent to the parentchain.
+				Ok(CliResultOk::ForwardedExtrinsic {
+					extrinsic: hex_encode(&rpc_return_value.value),
+				})
+			},
+			_ => {
+				error!("DCAP quote verification failed");
+				Err(CliError::WorkerRpcApi { msg: "DCAP quote verification failed".to_string() })
+			},
 		}
 	}
 }
@@ -15,7 +15,7 @@
 
 */
 
-use crate::Cli;
+use crate::{Cli, CliResult};
 
 use self::commands::{SendDcapQuoteCmd, SendIasAttestationReportCmd};
 
@@ -32,7 +32,7 @@ pub enum AttesteerCommand {
 }
 
 impl AttesteerCommand {
-	pub fn run(&self, cli: &Cli) {
+	pub fn run(&self, cli: &Cli) -> CliResult {
 		match self {
 			AttesteerCommand::SendDCAPQuote(cmd) => cmd.run(cli),
 			AttesteerCommand::SendIASAttestationReport(cmd) => cmd.run(cli),
@@ -16,8 +16,9 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_accountid_from_str, get_chain_api},
-	Cli, CliResult, CliResultOk,
+	Cli, CliError, CliResult, CliResultOk,
 };
 use substrate_api_client::GetAccountInformation;
 
@@ -29,11 +30,13 @@ pub struct BalanceCommand {
 
 impl BalanceCommand {
 	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
-		let api = get_chain_api(cli);
-		let accountid = get_accountid_from_str(&self.account);
-		let balance =
-			if let Some(data) = api.get_account_data(&accountid).unwrap() { data.free } else { 0 };
-		println!("{}", balance);
+		let api = get_chain_api(cli)?;
+		let accountid = get_accountid_from_str(&self.account)?;
+		let balance = api
+			.get_account_data(&accountid)
+			.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?
+			.map_or(0, |data| data.free);
+		cli_println!(cli, "{}", balance);
 		Ok(CliResultOk::Balance { balance })
 	}
 }
@@ -16,8 +16,9 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_accountid_from_str, get_chain_api},
-	Cli, CliResult, CliResultOk,
+	Cli, CliError, CliResult, CliResultOk,
 };
 use codec::Compact;
 use itp_types::{parentchain::AccountId, OpaqueCall};
@@ -40,11 +41,16 @@ pub struct FaucetCommand {
 
 impl FaucetCommand {
 	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
-		let mut api = get_chain_api(cli);
+		let mut api = get_chain_api(cli)?;
 		api.set_signer(AccountKeyring::Alice.pair().into());
-		let mut nonce = api.get_nonce().unwrap();
+		let mut nonce =
+			api.get_nonce().map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
+		let signer = api.signer().cloned().ok_or_else(|| CliError::Extrinsic {
+			msg: "no signer set for the parentchain api".into(),
+		})?;
+		let mut hashes = Vec::with_capacity(self.accounts.len());
 		for account in &self.accounts {
-			let to = get_accountid_from_str(account);
+			let to = get_accountid_from_str(account)?;
 			let call = OpaqueCall::from_tuple(&compose_call!(
 				api.metadata(),
 				"Balances",
@@ -53,13 +59,16 @@ impl FaucetCommand {
 				Compact(PREFUNDING_AMOUNT)
 			));
 			#[allow(clippy::redundant_clone)]
-			let xt = compose_extrinsic_offline!(api.signer().unwrap(), call, api.extrinsic_params(nonce));
+			let xt = compose_extrinsic_offline!(signer.clone(), call, api.extrinsic_params(nonce));
 			// send and watch extrinsic until finalized
-			println!("Faucet drips to {} (Alice's nonce={})", to, nonce);
-			let _blockh = api.submit_extrinsic(xt).unwrap();
+			cli_println!(cli, "Faucet drips to {} (Alice's nonce={})", to, nonce);
+			let hash = api
+				.submit_extrinsic(xt)
+				.map_err(|e| CliError::Extrinsic { msg: format!("{:?}", e) })?;
+			hashes.push(hash);
 			nonce += 1;
 		}
 
-		Ok(CliResultOk::None)
+		Ok(CliResultOk::ExtrinsicHashes { hashes })
 	}
 }
@@ -15,7 +15,9 @@
 
 */
 
-use crate::{command_utils::get_chain_api, Cli, CliResult, CliResultOk};
+use crate::{
+	cli_println, command_utils::get_chain_api, Cli, CliError, CliResult, CliResultOk, EventInfo,
+};
 
 use itp_types::parentchain::{AddedSgxEnclave, BalanceTransfer};
 use log::*;
@@ -34,54 +36,73 @@ pub struct ListenCommand {
 
 impl ListenCommand {
 	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
-		println!("{:?} {:?}", self.events, self.blocks);
-		let api = get_chain_api(cli);
+		cli_println!(cli, "{:?} {:?}", self.events, self.blocks);
+		let api = get_chain_api(cli)?;
 		info!("Subscribing to events (solo or para)");
 		let mut count = 0u32;
 		let mut blocks = 0u32;
-		let mut subscription = api.subscribe_events().unwrap();
+		let mut collected = Vec::new();
+		let mut subscription = api
+			.subscribe_events()
+			.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
 		loop {
 			if let Some(e) = self.events {
 				if count >= e {
-					return Ok(CliResultOk::None)
+					return Ok(CliResultOk::Events { events: collected })
 				}
 			};
 			if let Some(b) = self.blocks {
 				if blocks >= b {
-					return Ok(CliResultOk::None)
+					return Ok(CliResultOk::Events { events: collected })
 				}
 			};
-
-			let events = subscription.next_events_from_metadata().unwrap().unwrap();
+			let events = subscription
+				.next_events_from_metadata()
+				.ok_or_else(|| CliError::ChainRpcApi { msg: "event subscription ended".into() })?
+				.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
 			blocks += 1;
-			let header = api.get_header(None).unwrap().unwrap();
-			println!("block number (HEAD): {}", header.number);
+			let header = api
+				.get_header(None)
+				.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?
+				.ok_or_else(|| CliError::ChainRpcApi { msg: "no header of the head".into() })?;
+			cli_println!(cli, "block number (HEAD): {}", header.number);
 			for event in events.iter() {
-				let event = event.unwrap();
+				let event = event.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
 				count += 1;
-				match event.pallet_name() {
+				let details = match event.pallet_name() {
 					"System" => continue,
 					"TransactionPayment" => continue,
 					"Treasury" => continue,
 					"Balances" => match event.variant_name() {
 						"Deposit" => continue,
 						"Withdraw" => continue,
-						"Transfer" =>
-							if let Ok(Some(ev)) = event.as_event::<BalanceTransfer>() {
-								println!("{:?}", ev);
-							},
-						_ => println!("{}::{}", event.pallet_name(), event.variant_name()),
+						"Transfer" => event
+							.as_event::<BalanceTransfer>()
+							.ok()
+							.flatten()
+							.map(|ev| format!("{:?}", ev)),
+						_ => None,
 					},
 					"Teerex" => match event.variant_name() {
-						"AddedSgxEnclave" => {
-							if let Ok(Some(ev)) = event.as_event::<AddedSgxEnclave>() {
-								println!("Teerex::{:?}", ev);
-							}
-						},
-						_ => println!("{}::{}", event.pallet_name(), event.variant_name()),
+						"AddedSgxEnclave" => event
+							.as_event::<AddedSgxEnclave>()
+							.ok()
+							.flatten()
+							.map(|ev| format!("Teerex::{:?}", ev)),
+						_ => None,
 					},
-					_ => println!("{}::{}", event.pallet_name(), event.variant_name()),
+					_ => None,
+				};
+				match &details {
+					Some(details) => cli_println!(cli, "{}", details),
+					None => cli_println!(cli, "{}::{}", event.pallet_name(), event.variant_name()),
 				}
+				collected.push(EventInfo {
+					block_number: Some(header.number),
+					pallet: event.pallet_name().into(),
+					variant: event.variant_name().into(),
+					details,
+				});
 			}
 		}
 	}
@@ -16,11 +16,12 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_chain_api, *},
-	Cli, CliResult, CliResultOk,
+	Cli, CliError, CliResult, CliResultOk, TcbInfoRegistration,
 };
 use itp_node_api::api_client::TEEREX;
-use itp_types::{parentchain::Hash, OpaqueCall};
+use itp_types::OpaqueCall;
 use itp_utils::ToHexPrefixed;
 use log::*;
 use regex::Regex;
@@ -52,31 +53,43 @@ pub struct RegisterTcbInfoCommand {
 
 impl RegisterTcbInfoCommand {
 	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
-		let mut chain_api = get_chain_api(cli);
+		let mut chain_api = get_chain_api(cli)?;
 
 		// Get the sender.
-		let from = get_pair_from_str(&self.sender);
+		let from = get_pair_from_str(&self.sender)?;
 		chain_api.set_signer(from.into());
+		let signer = chain_api.signer().cloned().ok_or_else(|| CliError::Extrinsic {
+			msg: "no signer set for the parentchain api".into(),
+		})?;
 
 		let fmspcs = if self.all {
 			trace!("fetching all fmspc's from api.trustedservices.intel.com");
-			let fmspcs = reqwest::blocking::get(
+			let fmspcs: Vec<Platform> = reqwest::blocking::get(
 				"https://api.trustedservices.intel.com/sgx/certification/v4/fmspcs",
 			)
-			.unwrap();
-			let fmspcs: Vec<Platform> = fmspcs.json().expect("Error parsing JSON");
-			println!("{:?}", fmspcs);
+			.and_then(|response| response.json())
+			.map_err(intel_api_error)?;
+			cli_println!(cli, "{:?}", fmspcs);
 			fmspcs.into_iter().map(|f| f.fmspc).collect()
 		} else if let Some(fmspc) = self.fmspc.clone() {
 			vec![fmspc]
 		} else {
-			panic!("must specify either '--all' or '--fmspc'");
+			return Err(CliError::InvalidArgument {
+				msg: "must specify either '--all' or '--fmspc'".into(),
+			})
 		};
-		let mut nonce = chain_api.get_nonce().unwrap();
-		let xt_hashes: Vec<(String, Option<Hash>)> = fmspcs
+		let tcb_info_regex =
+			Regex::new(r#"tcbInfo\"\s?:(\{.*\}),\s?\"signature"#).map_err(intel_api_error)?;
+		let signature_regex =
+			Regex::new(r#"\"signature\"\s?:\s?\"(.*)\"\}"#).map_err(intel_api_error)?;
+		let mut nonce = chain_api
+			.get_nonce()
+			.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
+		let registrations = fmspcs
 			.into_iter()
 			.map(|fmspc| {
-				println!(
+				cli_println!(
+					cli,
 					"fetching tcb info for fmspc {} from api.trustedservices.intel.com",
 					fmspc
 				);
@@ -84,25 +97,34 @@ impl RegisterTcbInfoCommand {
 					"https://api.trustedservices.intel.com/sgx/certification/v4/tcb?fmspc={}",
 					fmspc
 				))
-				.unwrap();
+				.map_err(intel_api_error)?;
 				//extract certificate chain from header
-				let certificate_chain = urlencoding::decode(
-					response.headers().get("TCB-Info-Issuer-Chain").unwrap().to_str().unwrap(),
-				)
-				.unwrap()
-				.to_string();
+				let issuer_chain_header = response
+					.headers()
+					.get("TCB-Info-Issuer-Chain")
+					.ok_or_else(|| intel_api_error("no TCB-Info-Issuer-Chain header"))?
+					.to_str()
+					.map_err(intel_api_error)?;
+				let certificate_chain =
+					urlencoding::decode(issuer_chain_header).map_err(intel_api_error)?.to_string();
 				trace!("certificate chain: \n{}", certificate_chain);
 
-				let body = response.text().unwrap();
+				let body = response.text().map_err(intel_api_error)?;
 				trace!("raw json: \n{}", body);
-				let re = Regex::new(r#"tcbInfo\"\s?:(\{.*\}),\s?\"signature"#).unwrap();
-				let tcb_info = &re.captures(&body).unwrap()[1];
-				let re = Regex::new(r#"\"signature\"\s?:\s?\"(.*)\"\}"#).unwrap();
-				let intel_signature_hex = &re.captures(&body).unwrap()[1];
+				let tcb_info = tcb_info_regex
+					.captures(&body)
+					.and_then(|captures| captures.get(1))
+					.ok_or_else(|| intel_api_error("no tcbInfo in the response"))?
+					.as_str();
+				let intel_signature_hex = signature_regex
+					.captures(&body)
+					.and_then(|captures| captures.get(1))
+					.ok_or_else(|| intel_api_error("no signature in the response"))?
+					.as_str();
 				trace!("TCB info: {}", tcb_info);
 				trace!("signature: {}", intel_signature_hex);
 
-				let intel_signature = hex::decode(intel_signature_hex).unwrap();
+				let intel_signature = hex::decode(intel_signature_hex).map_err(intel_api_error)?;
 
 				let call = OpaqueCall::from_tuple(&compose_call!(
 					chain_api.metadata(),
@@ -120,27 +142,35 @@ impl RegisterTcbInfoCommand {
 				);
 
 				let xt = compose_extrinsic_offline!(
-					chain_api.clone().signer().unwrap(),
+					signer.clone(),
 					call,
 					chain_api.extrinsic_params(nonce)
 				);
 				nonce += 1;
-				match chain_api.submit_and_watch_extrinsic_until(xt, XtStatus::InBlock) {
-					Ok(xt_report) => {
-						println!(
-							"[+] register_tcb_info. extrinsic hash: {:?} / status: {:?}",
-							xt_report.extrinsic_hash, xt_report.status,
-						);
-						(fmspc, Some(xt_report.extrinsic_hash))
-					},
-					Err(e) => {
-						error!("register_tcb_info extrinsic failed {:?}", e);
-						(fmspc, None)
-					},
-				}
+				let extrinsic_hash =
+					match chain_api.submit_and_watch_extrinsic_until(xt, XtStatus::InBlock) {
+						Ok(xt_report) => {
+							cli_println!(
+								cli,
+								"[+] register_tcb_info. extrinsic hash: {:?} / status: {:?}",
+								xt_report.extrinsic_hash,
+								xt_report.status,
+							);
+							Some(xt_report.extrinsic_hash)
+						},
+						Err(e) => {
+							error!("register_tcb_info extrinsic failed {:?}", e);
+							None
+						},
+					};
+				Ok(TcbInfoRegistration { fmspc, extrinsic_hash })
 			})
-			.collect();
-		println!("{:?}", xt_hashes);
-		Ok(CliResultOk::None)
+			.collect::<Result<Vec<_>, CliError>>()?;
+		cli_println!(cli, "{:?}", registrations);
+		Ok(CliResultOk::TcbInfoRegistrations { registrations })
 	}
 }
+
+fn intel_api_error(e: impl std::fmt::Debug) -> CliError {
+	CliError::Io { msg: format!("intel api: {:?}", e) }
+}
@@ -16,6 +16,7 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_accountid_from_str, get_chain_api, *},
 	Cli, CliError, CliResult, CliResultOk,
 };
@@ -43,27 +44,28 @@ pub struct ShieldFundsCommand {
 
 impl ShieldFundsCommand {
 	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
-		let mut chain_api = get_chain_api(cli);
+		let mut chain_api = get_chain_api(cli)?;
 
-		let shard_opt = match self.shard.from_base58() {
-			Ok(s) => ShardIdentifier::decode(&mut &s[..]),
-			_ => panic!("shard argument must be base58 encoded"),
-		};
-
-		let shard = match shard_opt {
-			Ok(shard) => shard,
-			Err(e) => panic!("{}", e),
-		};
+		let shard = self
+			.shard
+			.from_base58()
+			.ok()
+			.and_then(|s| ShardIdentifier::decode(&mut s.as_slice()).ok())
+			.ok_or_else(|| CliError::InvalidArgument {
+				msg: "shard argument must be 32 bytes, base58 encoded".into(),
+			})?;
 
 		// Get the sender.
-		let from = get_pair_from_str(&self.from);
+		let from = get_pair_from_str(&self.from)?;
 		chain_api.set_signer(sr25519_core::Pair::from(from).into());
 
 		// Get the recipient.
-		let to = get_accountid_from_str(&self.to);
+		let to = get_accountid_from_str(&self.to)?;
 
-		let encryption_key = get_shielding_key(cli).unwrap();
-		let encrypted_recevier = encryption_key.encrypt(&to.encode()).unwrap();
+		let encryption_key = get_shielding_key(cli)?;
+		let encrypted_recevier = encryption_key.encrypt(&to.encode()).map_err(|e| {
+			CliError::Extrinsic { msg: format!("could not encrypt the recipient: {:?}", e) }
+		})?;
 
 		// Compose the extrinsic.
 		let xt = compose_extrinsic!(
@@ -77,11 +79,15 @@ impl ShieldFundsCommand {
 
 		match chain_api.submit_and_watch_extrinsic_until(xt, XtStatus::Finalized) {
 			Ok(xt_report) => {
-				println!(
+				let block_hash = xt_report.block_hash.ok_or_else(|| CliError::Extrinsic {
+					msg: "no block hash in the extrinsic report".into(),
+				})?;
+				cli_println!(
+					cli,
 					"[+] shield funds success. extrinsic hash: {:?} / status: {:?} / block hash: {:?}",
-					xt_report.extrinsic_hash, xt_report.status, xt_report.block_hash.unwrap()
+					xt_report.extrinsic_hash, xt_report.status, block_hash
 				);
-				Ok(CliResultOk::H256 { hash: xt_report.block_hash.unwrap() })
+				Ok(CliResultOk::H256 { hash: block_hash })
 			},
 			Err(e) => {
 				error!("shield_funds extrinsic failed {:?}", e);
@@ -16,8 +16,9 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_accountid_from_str, get_chain_api, *},
-	Cli, CliResult, CliResultOk,
+	Cli, CliError, CliResult, CliResultOk,
 };
 use ita_parentchain_interface::integritee::Balance;
 use log::*;
@@ -40,21 +41,27 @@ pub struct TransferCommand {
 
 impl TransferCommand {
 	pub(crate) fn run(&self, cli: &Cli) -> CliResult {
-		let from_account = get_pair_from_str(&self.from);
-		let to_account = get_accountid_from_str(&self.to);
+		let from_account = get_pair_from_str(&self.from)?;
+		let to_account = get_accountid_from_str(&self.to)?;
 		info!("from ss58 is {}", from_account.public().to_ss58check());
 		info!("to ss58 is {}", to_account.to_ss58check());
-		let mut api = get_chain_api(cli);
+		let mut api = get_chain_api(cli)?;
 		api.set_signer(from_account.into());
 		let xt = api.balance_transfer_allow_death(to_account.clone().into(), self.amount);
-		let tx_report = api.submit_and_watch_extrinsic_until(xt, XtStatus::InBlock).unwrap();
-		println!(
+		let tx_report = api
+			.submit_and_watch_extrinsic_until(xt, XtStatus::InBlock)
+			.map_err(|e| CliError::Extrinsic { msg: format!("{:?}", e) })?;
+		cli_println!(
+			cli,
 			"[+] L1 extrinsic success. extrinsic hash: {:?} / status: {:?}",
-			tx_report.extrinsic_hash, tx_report.status
+			tx_report.extrinsic_hash,
+			tx_report.status
 		);
-		let result = api.get_account_data(&to_account).unwrap().unwrap();
-		let balance = result.free;
-		println!("balance for {} is now {}", to_account, balance);
+		let balance = api
+			.get_account_data(&to_account)
+			.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?
+			.map_or(0, |data| data.free);
+		cli_println!(cli, "balance for {} is now {}", to_account, balance);
 
 		Ok(CliResultOk::Balance { balance })
 	}
@@ -21,12 +21,12 @@ use crate::{
 		register_tcb_info::RegisterTcbInfoCommand, shield_funds::ShieldFundsCommand,
 		transfer::TransferCommand,
 	},
+	cli_println,
 	command_utils::*,
-	Cli, CliResult, CliResultOk, ED25519_KEY_TYPE, SR25519_KEY_TYPE,
+	Cli, CliError, CliResult, CliResultOk, WorkerInfo, ED25519_KEY_TYPE, SR25519_KEY_TYPE,
 };
 use base58::ToBase58;
 use clap::Subcommand;
-use codec::Encode;
 use itc_rpc_client::direct_client::DirectApi;
 use itp_node_api::api_client::PalletTeerexApi;
 use sp_core::crypto::Ss58Codec;
@@ -76,8 +76,8 @@ impl BaseCommand {
 	pub fn run(&self, cli: &Cli) -> CliResult {
 		match self {
 			BaseCommand::Balance(cmd) => cmd.run(cli),
-			BaseCommand::NewAccount => new_account(),
-			BaseCommand::ListAccounts => list_accounts(),
+			BaseCommand::NewAccount => new_account(cli),
+			BaseCommand::ListAccounts => list_accounts(cli),
 			BaseCommand::PrintMetadata => print_metadata(cli),
 			BaseCommand::PrintSgxMetadata => print_sgx_metadata(cli),
 			BaseCommand::Faucet(cmd) => cmd.run(cli),
@@ -90,32 +90,35 @@ impl BaseCommand {
 	}
 }
 
-fn new_account() -> CliResult {
-	let store = LocalKeystore::open(PathBuf::from(&KEYSTORE_PATH), None).unwrap();
-	let key = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None).unwrap();
+fn new_account(cli: &Cli) -> CliResult {
+	let store = LocalKeystore::open(PathBuf::from(&KEYSTORE_PATH), None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+	let key = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
 	let key_base58 = key.to_ss58check();
 	drop(store);
-	println!("{}", key_base58);
+	cli_println!(cli, "{}", key_base58);
 	Ok(CliResultOk::PubKeysBase58 {
 		pubkeys_sr25519: Some(vec![key_base58]),
 		pubkeys_ed25519: None,
 	})
 }
 
-fn list_accounts() -> CliResult {
-	let store = LocalKeystore::open(PathBuf::from(&KEYSTORE_PATH), None).unwrap();
-	println!("sr25519 keys:");
+fn list_accounts(cli: &Cli) -> CliResult {
+	let store = LocalKeystore::open(PathBuf::from(&KEYSTORE_PATH), None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+	cli_println!(cli, "sr25519 keys:");
 	let mut keys_sr25519 = vec![];
 	for pubkey in store.sr25519_public_keys(SR25519_KEY_TYPE).into_iter() {
 		let key_ss58 = pubkey.to_ss58check();
-		println!("{}", key_ss58);
+		cli_println!(cli, "{}", key_ss58);
 		keys_sr25519.push(key_ss58);
 	}
-	println!("ed25519 keys:");
+	cli_println!(cli, "ed25519 keys:");
 	let mut keys_ed25519 = vec![];
 	for pubkey in store.ed25519_public_keys(ED25519_KEY_TYPE).into_iter() {
 		let key_ss58 = pubkey.to_ss58check();
-		println!("{}", key_ss58);
+		cli_println!(cli, "{}", key_ss58);
 		keys_ed25519.push(key_ss58);
 	}
 	drop(store);
@@ -127,36 +130,46 @@ fn list_accounts() -> CliResult {
 }
 
 fn print_metadata(cli: &Cli) -> CliResult {
-	let api = get_chain_api(cli);
+	let api = get_chain_api(cli)?;
 	let meta = api.metadata();
-	println!("Metadata:\n {}", &meta.pretty_format().unwrap());
+	let pretty = meta
+		.pretty_format()
+		.ok_or_else(|| CliError::ChainRpcApi { msg: "failed to format metadata".into() })?;
+	cli_println!(cli, "Metadata:\n {}", pretty);
 	Ok(CliResultOk::Metadata { metadata: meta.clone() })
 }
 fn print_sgx_metadata(cli: &Cli) -> CliResult {
-	let worker_api_direct = get_worker_api_direct(cli);
-	let metadata = worker_api_direct.get_state_metadata().unwrap();
-	println!("Metadata:\n {}", metadata.pretty_format().unwrap());
+	let worker_api_direct = get_worker_api_direct(cli)?;
+	let metadata = worker_api_direct.get_state_metadata()?;
+	let pretty = metadata
+		.pretty_format()
+		.ok_or_else(|| CliError::WorkerRpcApi { msg: "failed to format metadata".into() })?;
+	cli_println!(cli, "Metadata:\n {}", pretty);
 	Ok(CliResultOk::Metadata { metadata })
 }
 
 fn list_workers(cli: &Cli) -> CliResult {
-	let api = get_chain_api(cli);
-	let enclaves = api.all_enclaves(None).unwrap();
-	println!("number of enclaves registered: {}", enclaves.len());
-	let fingerprints = enclaves
+	let api = get_chain_api(cli)?;
+	let enclaves = api
+		.all_enclaves(None)
+		.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
+	cli_println!(cli, "number of enclaves registered: {}", enclaves.len());
+	let workers = enclaves
 		.iter()
 		.map(|enclave| {
-			println!("Enclave");
-			println!("   signer: {:?}", enclave.instance_signer());
-			println!("   MRENCLAVE: {}", enclave.fingerprint().0.to_base58());
-			println!("   RA timestamp: {}", enclave.attestation_timestamp());
-			println!(
-				"   URL: {}",
-				String::from_utf8(enclave.instance_url().unwrap_or_else(|| "none".encode()))
-					.unwrap()
-			);
-			enclave.fingerprint().0.to_base58()
+			let url = enclave.instance_url().map(|url| String::from_utf8_lossy(&url).into_owned());
+			cli_println!(cli, "Enclave");
+			cli_println!(cli, "   signer: {:?}", enclave.instance_signer());
+			cli_println!(cli, "   MRENCLAVE: {}", enclave.fingerprint().0.to_base58());
+			cli_println!(cli, "   RA timestamp: {}", enclave.attestation_timestamp());
+			cli_println!(cli, "   URL: {}", url.as_deref().unwrap_or("none"));
+			WorkerInfo {
+				signer: format!("{:?}", enclave.instance_signer()),
+				mr_enclave: enclave.fingerprint().0.to_base58(),
+				attestation_timestamp: enclave.attestation_timestamp(),
+				url,
+			}
 		})
 		.collect();
-	Ok(CliResultOk::MrEnclaveBase58 { mr_enclaves: fingerprints })
+	Ok(CliResultOk::Workers { workers })
 }
@@ -16,12 +16,15 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::get_worker_api_direct,
 	get_layer_two_nonce,
 	trusted_cli::TrustedCli,
-	trusted_command_utils::{get_identifiers, get_keystore_path, get_pair_from_str},
+	trusted_command_utils::{
+		ensure_runtime_version_matches, get_identifiers, get_keystore_path, get_pair_from_str,
+	},
 	trusted_operation::{get_json_request, get_state, perform_trusted_operation, wait_until},
-	Cli, CliResult, CliResultOk, SR25519_KEY_TYPE,
+	BenchmarkStats, Cli, CliError, CliResult, CliResultOk, SR25519_KEY_TYPE,
 };
 use codec::Decode;
 use hdrhistogram::Histogram;
@@ -96,14 +99,14 @@ impl BenchmarkClient {
 		initial_balance: u128,
 		initial_request: String,
 		cli: &Cli,
-	) -> Self {
+	) -> Result<Self, CliError> {
 		debug!("get direct api");
-		let client_api = get_worker_api_direct(cli);
+		let client_api = get_worker_api_direct(cli)?;
 
 		debug!("setup sender and receiver");
 		let (sender, receiver) = channel();
 		client_api.watch(initial_request, sender);
-		BenchmarkClient { account, current_balance: initial_balance, client_api, receiver }
+		Ok(BenchmarkClient { account, current_balance: initial_balance, client_api, receiver })
 	}
 }
 
@@ -120,31 +123,36 @@ impl BenchmarkCommand {
 			self.random_wait_before_transaction_min_ms,
 			self.random_wait_before_transaction_max_ms,
 		);
-		let store = LocalKeystore::open(get_keystore_path(trusted_args), None).unwrap();
-		let funding_account_keys = get_pair_from_str(trusted_args, &self.funding_account);
+		let store = LocalKeystore::open(get_keystore_path(trusted_args)?, None)
+			.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+		let funding_account_keys = get_pair_from_str(trusted_args, &self.funding_account)?;
 
-		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		ensure_runtime_version_matches(cli, trusted_args)?;
 
 		// Get shielding pubkey.
-		let worker_api_direct = get_worker_api_direct(cli);
-		let shielding_pubkey: Rsa3072PubKey = match worker_api_direct.get_rsa_pubkey() {
-			Ok(key) => key,
-			Err(err_msg) => panic!("{}", err_msg.to_string()),
-		};
+		let worker_api_direct = get_worker_api_direct(cli)?;
+		let shielding_pubkey: Rsa3072PubKey = worker_api_direct.get_rsa_pubkey()?;
 
 		let nonce_start = get_layer_two_nonce!(funding_account_keys, cli, trusted_args);
-		println!("Nonce for account {}: {}", self.funding_account, nonce_start);
+		cli_println!(cli, "Nonce for account {}: {}", self.funding_account, nonce_start);
 
 		let mut accounts = Vec::new();
 		let initial_balance = (self.number_iterations + 1) * (STF_TX_FEE + EXISTENTIAL_DEPOSIT);
 		// Setup new accounts and initialize them with money from Alice.
 		for i in 0..self.number_clients {
 			let nonce = i + nonce_start;
-			println!("Initializing account {} with initial amount {:?}", i, initial_balance);
+			cli_println!(
+				cli,
+				"Initializing account {} with initial amount {:?}",
+				i,
+				initial_balance
+			);
 
 			// Create new account to use.
-			let a = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None).unwrap();
-			let account = get_pair_from_str(trusted_args, a.to_string().as_str());
+			let a = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None)
+				.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+			let account = get_pair_from_str(trusted_args, a.to_string().as_str())?;
 
 			// Transfer amount from Alice to new account.
 			let top: TrustedOperation<TrustedCallSigned, Getter> = TrustedCall::balance_transfer(
@@ -162,44 +170,44 @@ impl BenchmarkCommand {
 
 			// For the last account we wait for confirmation in order to ensure all accounts were setup correctly
 			let wait_for_confirmation = i == self.number_clients - 1;
-			let account_funding_request = get_json_request(shard, &top, shielding_pubkey);
+			let account_funding_request = get_json_request(shard, &top, shielding_pubkey)?;
 
 			let client =
-				BenchmarkClient::new(account, initial_balance, account_funding_request, cli);
-			let _result = wait_for_top_confirmation(wait_for_confirmation, &client);
+				BenchmarkClient::new(account, initial_balance, account_funding_request, cli)?;
+			let _result = wait_for_top_confirmation(cli, wait_for_confirmation, &client)?;
 			accounts.push(client);
 		}
 
 		rayon::ThreadPoolBuilder::new()
 			.num_threads(self.number_clients as usize)
 			.build_global()
-			.unwrap();
+			.map_err(|e| CliError::Io { msg: format!("could not build the thread pool: {}", e) })?;
 
 		let overall_start = Instant::now();
 
 		// Run actual benchmark logic, in parallel, for each account initialized above.
 		let outputs: Vec<Vec<BenchmarkTransaction>> = accounts
 			.into_par_iter()
-			.map(move |mut client| {
+			.map(move |mut client| -> Result<Vec<BenchmarkTransaction>, CliError> {
 				let mut output: Vec<BenchmarkTransaction> = Vec::new();
 
 				for i in 0..self.number_iterations {
-					println!("Iteration: {}", i);
+					cli_println!(cli, "Iteration: {}", i);
 
 					if random_wait_before_transaction_ms.1 > 0 {
-						random_wait(random_wait_before_transaction_ms);
+						random_wait(cli, random_wait_before_transaction_ms);
 					}
 
 					// Create new account.
-					let account_keys = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None).unwrap();
+					let account_keys = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None)
+						.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
 
 					let new_account =
-						get_pair_from_str(trusted_args, account_keys.to_string().as_str());
+						get_pair_from_str(trusted_args, account_keys.to_string().as_str())?;
 
-
-					println!("  Transfer amount: {}", EXISTENTIAL_DEPOSIT);
-					println!("  From: {:?}", client.account.public());
-					println!("  To:   {:?}", new_account.public());
+					cli_println!(cli, "  Transfer amount: {}", EXISTENTIAL_DEPOSIT);
+					cli_println!(cli, "  From: {:?}", client.account.public());
+					cli_println!(cli, "  To:   {:?}", new_account.public());
 
 					// Get nonce of account.
 					let nonce = get_nonce(client.account.clone(), shard, &client.client_api);
@@ -214,18 +222,29 @@ impl BenchmarkCommand {
 					.into_trusted_operation(trusted_args.direct);
 
 					let last_iteration = i == self.number_iterations - 1;
-					let jsonrpc_call = get_json_request(shard, &top, shielding_pubkey);
-					client.client_api.send(&jsonrpc_call).unwrap();
+					let jsonrpc_call = get_json_request(shard, &top, shielding_pubkey)?;
+					client.client_api.send(&jsonrpc_call)?;
 					let result = wait_for_top_confirmation(
+						cli,
 						self.wait_for_confirmation || last_iteration,
 						&client,
-					);
+					)?;
 
 					client.current_balance -= EXISTENTIAL_DEPOSIT;
 
-					let balance = get_balance(client.account.clone(), shard, &client.client_api);
-					println!("Balance: {}", balance.unwrap_or_default());
-					assert_eq!(client.current_balance, balance.unwrap_or_default());
+					let balance =
+						get_balance(client.account.clone(), shard, &client.client_api).unwrap_or_default();
+					cli_println!(cli, "Balance: {}", balance);
+					if client.current_balance != balance {
+						return Err(CliError::TrustedOp {
+							msg: format!(
+								"unexpected balance of {:?}: expected {}, got {}",
+								client.account.public(),
+								client.current_balance,
+								balance
+							),
+						})
+					}
 
 					output.push(result);
 
@@ -236,22 +255,33 @@ impl BenchmarkCommand {
 					}
 				}
 
-				client.client_api.close().unwrap();
+				client.client_api.close()?;
 
-				output
+				Ok(output)
 			})
-			.collect();
+			.collect::<Result<_, CliError>>()?;
 
-		println!(
+		let duration_ms = overall_start.elapsed().as_millis();
+		cli_println!(
+			cli,
 			"Finished benchmark with {} clients and {} transactions in {} ms",
 			self.number_clients,
 			self.number_iterations,
-			overall_start.elapsed().as_millis()
+			duration_ms
 		);
 
-		print_benchmark_statistic(outputs, self.wait_for_confirmation);
-
-		Ok(CliResultOk::None)
+		let (percentiles, missing_measurements) =
+			benchmark_statistic(cli, outputs, self.wait_for_confirmation);
+
+		Ok(CliResultOk::BenchmarkStats {
+			stats: BenchmarkStats {
+				number_clients: self.number_clients,
+				number_iterations: self.number_iterations,
+				duration_ms,
+				missing_measurements,
+				percentiles,
+			},
+		})
 	}
 }
 
@@ -293,8 +323,15 @@ fn get_nonce(
 	nonce
 }
 
-fn print_benchmark_statistic(outputs: Vec<Vec<BenchmarkTransaction>>, wait_for_confirmation: bool) {
-	let mut hist = Histogram::<u64>::new(1).unwrap();
+/// Returns the latency in ms per percentile and the number of missing measurements.
+fn benchmark_statistic(
+	cli: &Cli,
+	outputs: Vec<Vec<BenchmarkTransaction>>,
+	wait_for_confirmation: bool,
+) -> (Vec<(u32, u64)>, u32) {
+	// One significant digit is within the supported range of 0 to 5, so this can't fail.
+	let mut hist = Histogram::<u64>::new(1).expect("1 significant digit is valid");
+	let mut missing_measurements = 0u32;
 	for output in outputs {
 		for t in output {
 			let benchmarked_timestamp =
@@ -302,62 +339,58 @@ fn print_benchmark_statistic(outputs: Vec<Vec<BenchmarkTransaction>>, wait_for_c
 			if let Some(confirmed) = benchmarked_timestamp {
 				hist += confirmed.duration_since(t.started).as_millis() as u64;
 			} else {
-				println!("Missing measurement data");
+				missing_measurements += 1;
+				cli_println!(cli, "Missing measurement data");
 			}
 		}
 	}
 
-	for i in (5..=100).step_by(5) {
-		let text = format!(
-			"{} percent are done within {} ms",
-			i,
-			hist.value_at_quantile(i as f64 / 100.0)
-		);
-		println!("{}", text);
-	}
+	let percentiles = (5..=100)
+		.step_by(5)
+		.map(|i| {
+			let latency = hist.value_at_quantile(i as f64 / 100.0);
+			cli_println!(cli, "{} percent are done within {} ms", i, latency);
+			(i, latency)
+		})
+		.collect();
+	(percentiles, missing_measurements)
 }
 
-fn random_wait(random_wait_before_transaction_ms: (u32, u32)) {
+fn random_wait(cli: &Cli, random_wait_before_transaction_ms: (u32, u32)) {
 	let mut rng = rand::thread_rng();
 	let sleep_time = time::Duration::from_millis(
 		rng.gen_range(random_wait_before_transaction_ms.0..=random_wait_before_transaction_ms.1)
 			.into(),
 	);
-	println!("Sleep for: {}ms", sleep_time.as_millis());
+	cli_println!(cli, "Sleep for: {}ms", sleep_time.as_millis());
 	thread::sleep(sleep_time);
 }
 
 fn wait_for_top_confirmation(
+	cli: &Cli,
 	wait_for_sidechain_block: bool,
 	client: &BenchmarkClient,
-) -> BenchmarkTransaction {
+) -> Result<BenchmarkTransaction, CliError> {
 	let started = Instant::now();
 
-	let submitted = wait_until(&client.receiver, is_submitted);
+	let (submitted_hash, submitted) = wait_until(cli, &client.receiver, is_submitted)
+		.ok_or_else(|| CliError::TrustedOp { msg: "trusted operation was not submitted".into() })?;
 
 	let confirmed = if wait_for_sidechain_block {
 		// We wait for the transaction hash that actually matches the submitted hash
 		loop {
-			let transaction_information = wait_until(&client.receiver, is_sidechain_block);
-			if let Some((hash, _)) = transaction_information {
-				if hash == submitted.unwrap().0 {
-					break transaction_information
-				}
+			match wait_until(cli, &client.receiver, is_sidechain_block) {
+				Some((hash, confirmed)) if hash == submitted_hash => break Some(confirmed),
+				Some(_) => continue,
+				// The connection was closed before the trusted operation got into a block.
+				None => break None,
 			}
 		}
 	} else {
 		None
 	};
-	if let (Some(s), Some(c)) = (submitted, confirmed) {
-		// Assert the two hashes are identical
-		assert_eq!(s.0, c.0);
-	}
 
-	BenchmarkTransaction {
-		started,
-		submitted: submitted.unwrap().1,
-		confirmed: confirmed.map(|v| v.1),
-	}
+	Ok(BenchmarkTransaction { started, submitted, confirmed })
 }
 
 fn is_submitted(s: TrustedOperationStatus) -> bool {
@@ -15,7 +15,7 @@
 
 */
 
-use crate::Cli;
+use crate::{Cli, CliError};
 use base58::FromBase58;
 use ita_parentchain_interface::integritee::{AccountId, Signature};
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
@@ -25,63 +25,96 @@ use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_application_crypto::sr25519;
 use sp_core::{crypto::Ss58Codec, Pair};
 use sp_runtime::traits::{IdentifyAccount, Verify};
-use std::path::PathBuf;
+use std::{
+	net::{TcpStream, ToSocketAddrs},
+	path::PathBuf,
+	time::Duration,
+};
 use substrate_client_keystore::LocalKeystore;
 
 type AccountPublic = <Signature as Verify>::Signer;
 pub(crate) const KEYSTORE_PATH: &str = "my_keystore";
+const WORKER_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Retrieves the public shielding key via the enclave websocket server.
-pub(crate) fn get_shielding_key(cli: &Cli) -> Result<Rsa3072PubKey, String> {
-	let worker_api_direct = get_worker_api_direct(cli);
-	worker_api_direct.get_rsa_pubkey().map_err(|e| e.to_string())
+pub(crate) fn get_shielding_key(cli: &Cli) -> Result<Rsa3072PubKey, CliError> {
+	let worker_api_direct = get_worker_api_direct(cli)?;
+	worker_api_direct
+		.get_rsa_pubkey()
+		.map_err(|e| CliError::WorkerRpcApi { msg: e.to_string() })
 }
 
-pub(crate) fn get_chain_api(cli: &Cli) -> ParentchainApi {
+pub(crate) fn get_chain_api(cli: &Cli) -> Result<ParentchainApi, CliError> {
 	let url = format!("{}:{}", cli.node_url, cli.node_port);
 	info!("connecting to {}", url);
-	ParentchainApi::new(TungsteniteRpcClient::new(&url, 5).unwrap()).unwrap()
+	let client = TungsteniteRpcClient::new(&url, 5)
+		.map_err(|e| CliError::ChainRpcApi { msg: format!("{} unreachable: {:?}", url, e) })?;
+	ParentchainApi::new(client).map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })
 }
 
-pub(crate) fn get_accountid_from_str(account: &str) -> AccountId {
-	match &account[..2] {
-		"//" => AccountPublic::from(sr25519::Pair::from_string(account, None).unwrap().public())
-			.into_account(),
-		_ => AccountPublic::from(sr25519::Public::from_ss58check(account).unwrap()).into_account(),
-	}
+pub(crate) fn get_accountid_from_str(account: &str) -> Result<AccountId, CliError> {
+	let public = if account.starts_with("//") {
+		sr25519::Pair::from_string(account, None)
+			.map(|pair| pair.public())
+			.map_err(|e| CliError::InvalidArgument {
+				msg: format!("invalid account {}: {:?}", account, e),
+			})?
+	} else {
+		sr25519::Public::from_ss58check(account).map_err(|e| CliError::InvalidArgument {
+			msg: format!("invalid account {}: {:?}", account, e),
+		})?
+	};
+	Ok(AccountPublic::from(public).into_account())
 }
 
-pub(crate) fn get_worker_api_direct(cli: &Cli) -> DirectWorkerApi {
+/// The direct client connects per request, so we check here that the worker is reachable at
+/// all, to report an unreachable worker as an error instead of failing in the middle of a command.
+pub(crate) fn get_worker_api_direct(cli: &Cli) -> Result<DirectWorkerApi, CliError> {
 	let url = format!("{}:{}", cli.worker_url, cli.trusted_worker_port);
 	info!("Connecting to integritee-service-direct-port on '{}'", url);
-	DirectWorkerApi::new(url)
+	let host = cli.worker_url.rsplit("://").next().unwrap_or(&cli.worker_url);
+	let unreachable =
+		|e: std::io::Error| CliError::WorkerRpcApi { msg: format!("{} unreachable: {}", url, e) };
+	let port = cli.trusted_worker_port.parse::<u16>().map_err(|e| CliError::WorkerRpcApi {
+		msg: format!("invalid trusted worker port {}: {}", cli.trusted_worker_port, e),
+	})?;
+	let address =
+		(host, port).to_socket_addrs().map_err(unreachable)?.next().ok_or_else(|| {
+			CliError::WorkerRpcApi { msg: format!("{} can not be resolved", url) }
+		})?;
+	TcpStream::connect_timeout(&address, WORKER_CONNECT_TIMEOUT).map_err(unreachable)?;
+	Ok(DirectWorkerApi::new(url))
 }
 
 /// get a pair either form keyring (well known keys) or from the store
-pub(crate) fn get_pair_from_str(account: &str) -> sr25519::AppPair {
+pub(crate) fn get_pair_from_str(account: &str) -> Result<sr25519::AppPair, CliError> {
 	info!("getting pair for {}", account);
-	match &account[..2] {
-		"//" => sr25519::AppPair::from_string(account, None).unwrap(),
-		_ => {
-			info!("fetching from keystore at {}", &KEYSTORE_PATH);
-			// open store without password protection
-			let store = LocalKeystore::open(PathBuf::from(&KEYSTORE_PATH), None)
-				.expect("store should exist");
-			info!("store opened");
-			let _pair = store
-				.key_pair::<sr25519::AppPair>(
-					&sr25519::Public::from_ss58check(account).unwrap().into(),
-				)
-				.unwrap()
-				.unwrap();
-			drop(store);
-			_pair
-		},
+	if account.starts_with("//") {
+		return sr25519::AppPair::from_string(account, None).map_err(|e| CliError::InvalidArgument {
+			msg: format!("invalid account {}: {:?}", account, e),
+		})
 	}
+
+	info!("fetching from keystore at {}", &KEYSTORE_PATH);
+	let public = sr25519::Public::from_ss58check(account).map_err(|e| {
+		CliError::InvalidArgument { msg: format!("invalid account {}: {:?}", account, e) }
+	})?;
+	// open store without password protection
+	let store = LocalKeystore::open(PathBuf::from(&KEYSTORE_PATH), None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+	info!("store opened");
+	store
+		.key_pair::<sr25519::AppPair>(&public.into())
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?
+		.ok_or_else(|| CliError::Keystore {
+			msg: format!("no key for {} in {}", account, KEYSTORE_PATH),
+		})
 }
 
-pub(crate) fn mrenclave_from_base58(src: &str) -> [u8; 32] {
-	let mut mrenclave = [0u8; 32];
-	mrenclave.copy_from_slice(&src.from_base58().expect("mrenclave has to be base58 encoded"));
-	mrenclave
+pub(crate) fn mrenclave_from_base58(src: &str) -> Result<[u8; 32], CliError> {
+	let invalid = || CliError::InvalidArgument {
+		msg: format!("mrenclave {} has to be 32 bytes, base58 encoded", src),
+	};
+	let decoded = src.from_base58().map_err(|_| invalid())?;
+	<[u8; 32]>::try_from(decoded.as_slice()).map_err(|_| invalid())
 }
@@ -16,7 +16,7 @@
 */
 
 extern crate chrono;
-use crate::{base_cli::BaseCommand, trusted_cli::TrustedCli, Cli, CliResult, CliResultOk};
+use crate::{admin_cli::AdminCli, base_cli::BaseCommand, trusted_cli::TrustedCli, Cli, CliResult};
 use clap::Subcommand;
 
 #[cfg(feature = "teeracle")]
@@ -41,6 +41,9 @@ pub enum Commands {
 	/// Subcommand for the attesteer.
 	#[clap(subcommand)]
 	Attesteer(AttesteerCommand),
+
+	/// operate a worker running on this machine through its admin rpc
+	Admin(AdminCli),
 }
 
 pub fn match_command(cli: &Cli) -> CliResult {
@@ -48,13 +51,8 @@ pub fn match_command(cli: &Cli) -> CliResult {
 		Commands::Base(cmd) => cmd.run(cli),
 		Commands::Trusted(trusted_cli) => trusted_cli.run(cli),
 		#[cfg(feature = "teeracle")]
-		Commands::Oracle(cmd) => {
-			cmd.run(cli);
-			Ok(CliResultOk::None)
-		},
-		Commands::Attesteer(cmd) => {
-			cmd.run(cli);
-			Ok(CliResultOk::None)
-		},
+		Commands::Oracle(cmd) => cmd.run(cli),
+		Commands::Attesteer(cmd) => cmd.run(cli),
+		Commands::Admin(admin_cli) => admin_cli.run(cli),
 	}
 }
@@ -16,12 +16,18 @@
 */
 
 use crate::{
+	cli_println,
+	evm::commands::evm_command_utils::{
+		decode_evm_address_arg, decode_hex_arg, get_evm_base_fee, get_evm_receipt,
+		print_evm_receipt,
+	},
 	get_layer_two_evm_nonce, get_layer_two_nonce,
 	trusted_cli::TrustedCli,
-	trusted_command_utils::{get_identifiers, get_pair_from_str},
+	trusted_command_utils::{ensure_runtime_version_matches, get_identifiers, get_pair_from_str},
 	trusted_operation::perform_trusted_operation,
 	Cli, CliResult, CliResultOk,
 };
+use codec::Encode;
 use ita_stf::{Index, TrustedCall, TrustedGetter};
 use itp_stf_primitives::{
 	traits::TrustedCallSigning,
@@ -29,7 +35,7 @@ use itp_stf_primitives::{
 };
 use itp_types::AccountId;
 use log::*;
-use sp_core::{crypto::Ss58Codec, Pair, H160, U256};
+use sp_core::{crypto::Ss58Codec, hashing::blake2_256, Pair, H160, H256, U256};
 use std::{boxed::Box, vec::Vec};
 #[derive(Parser)]
 pub struct EvmCallCommands {
@@ -45,43 +51,50 @@ pub struct EvmCallCommands {
 
 impl EvmCallCommands {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let sender = get_pair_from_str(trusted_args, &self.from);
+		let sender = get_pair_from_str(trusted_args, &self.from)?;
 		let sender_acc: AccountId = sender.public().into();
 
 		info!("senders ss58 is {}", sender.public().to_ss58check());
 
 		let mut sender_evm_acc_slice: [u8; 20] = [0; 20];
-		sender_evm_acc_slice
-			.copy_from_slice((<[u8; 32]>::from(sender_acc.clone())).get(0..20).unwrap());
+		sender_evm_acc_slice.copy_from_slice(&<[u8; 32]>::from(sender_acc.clone())[..20]);
 		let sender_evm_acc: H160 = sender_evm_acc_slice.into();
 
 		info!("senders evm account is {}", sender_evm_acc);
 
-		let execution_address =
-			H160::from_slice(&array_bytes::hex2bytes(&self.execution_address).unwrap());
+		let execution_address = decode_evm_address_arg(&self.execution_address)?;
 
-		let function_hash = array_bytes::hex2bytes(&self.function).unwrap();
+		let function_hash = decode_hex_arg(&self.function)?;
 
-		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		ensure_runtime_version_matches(cli, trusted_args)?;
+		let max_fee_per_gas = get_evm_base_fee(cli, trusted_args)?;
 		let nonce = get_layer_two_nonce!(sender, cli, trusted_args);
 		let evm_nonce = get_layer_two_evm_nonce!(sender, cli, trusted_args);
 
-		println!("calling smart contract function");
-		let function_call = TrustedCall::evm_call(
+		cli_println!(cli, "calling smart contract function");
+		let signed_call = TrustedCall::evm_call(
 			sender_acc,
 			sender_evm_acc,
 			execution_address,
 			function_hash,
 			U256::from(0),
-			10_000_000,    // gas limit
-			U256::from(1), // max_fee_per_gas !>= min_gas_price defined in runtime
+			10_000_000, // gas limit
+			max_fee_per_gas,
 			None,
 			Some(U256::from(evm_nonce)),
 			Vec::new(),
 		)
-		.sign(&KeyPair::Sr25519(Box::new(sender)), nonce, &mrenclave, &shard)
-		.into_trusted_operation(trusted_args.direct);
-		Ok(perform_trusted_operation::<()>(cli, trusted_args, &function_call)
-			.map(|_| CliResultOk::None)?)
+		.sign(&KeyPair::Sr25519(Box::new(sender.clone())), nonce, &mrenclave, &shard);
+		let call_hash = H256::from(blake2_256(&signed_call.encode()));
+		let function_call = signed_call.into_trusted_operation(trusted_args.direct);
+		perform_trusted_operation::<()>(cli, trusted_args, &function_call)?;
+
+		let receipt = get_evm_receipt(cli, trusted_args, &sender, call_hash);
+		match receipt.as_ref() {
+			Some(receipt) => print_evm_receipt(cli, receipt),
+			None => cli_println!(cli, "No EVM receipt found for the trusted call {:?}", call_hash),
+		}
+		Ok(CliResultOk::EvmReceipt { receipt: receipt.map(Into::into) })
 	}
 }
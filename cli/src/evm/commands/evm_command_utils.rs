@@ -15,6 +15,19 @@
 
 */
 
+use crate::{
+	cli_println, trusted_cli::TrustedCli, trusted_operation::perform_trusted_operation, Cli,
+	CliError, EvmLogInfo, EvmReceiptInfo,
+};
+use ita_stf::{
+	evm_transaction::{EvmLog, EvmReceipt},
+	Getter, PublicGetter, TrustedCallSigned, TrustedGetter,
+};
+use itp_stf_primitives::types::{KeyPair, TrustedOperation};
+use itp_utils::hex::hex_encode;
+use log::*;
+use sp_core::{sr25519, Pair, H160, H256, U256};
+
 #[macro_export]
 macro_rules! get_layer_two_evm_nonce {
 	($signer_pair:ident, $cli:ident, $trusted_args:ident ) => {{
@@ -30,3 +43,103 @@ macro_rules! get_layer_two_evm_nonce {
 		nonce
 	}};
 }
+
+/// Decodes a hex encoded command line argument.
+pub(crate) fn decode_hex_arg(arg: &str) -> Result<Vec<u8>, CliError> {
+	array_bytes::hex2bytes(arg)
+		.map_err(|e| CliError::InvalidArgument { msg: format!("invalid hex {}: {:?}", arg, e) })
+}
+
+/// Decodes a hex encoded 20 byte EVM address.
+pub(crate) fn decode_evm_address_arg(arg: &str) -> Result<H160, CliError> {
+	<[u8; 20]>::try_from(decode_hex_arg(arg)?.as_slice())
+		.map(H160::from)
+		.map_err(|_| CliError::InvalidArgument {
+			msg: format!("{} is not a 20 byte EVM address", arg),
+		})
+}
+
+/// Decodes a hex encoded 32 byte EVM log topic.
+pub(crate) fn decode_evm_topic_arg(arg: &str) -> Result<H256, CliError> {
+	<[u8; 32]>::try_from(decode_hex_arg(arg)?.as_slice())
+		.map(H256::from)
+		.map_err(|_| CliError::InvalidArgument {
+			msg: format!("{} is not a 32 byte EVM log topic", arg),
+		})
+}
+
+/// Queries the base fee of the current sidechain block, the minimum max fee per gas of an EVM
+/// execution.
+pub(crate) fn get_evm_base_fee(cli: &Cli, trusted_args: &TrustedCli) -> Result<U256, CliError> {
+	let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::public(
+		PublicGetter::evm_base_fee,
+	));
+	let base_fee = perform_trusted_operation::<U256>(cli, trusted_args, &top).map_err(|e| {
+		CliError::TrustedOp { msg: format!("Could not get the EVM base fee: {:?}", e) }
+	})?;
+	debug!("got evm base fee: {}", base_fee);
+	Ok(base_fee)
+}
+
+/// Queries the EVM receipt of the signed trusted call with `call_hash`, sent by `signer`.
+pub(crate) fn get_evm_receipt(
+	cli: &Cli,
+	trusted_args: &TrustedCli,
+	signer: &sr25519::Pair,
+	call_hash: H256,
+) -> Option<EvmReceipt> {
+	let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
+		TrustedGetter::evm_receipt(signer.public().into(), call_hash)
+			.sign(&KeyPair::Sr25519(Box::new(signer.clone()))),
+	));
+	perform_trusted_operation::<Option<EvmReceipt>>(cli, trusted_args, &top)
+		.map_err(|e| error!("Could not get the EVM receipt of {:?}: {:?}", call_hash, e))
+		.ok()
+		.flatten()
+}
+
+pub(crate) fn print_evm_receipt(cli: &Cli, receipt: &EvmReceipt) {
+	cli_println!(
+		cli,
+		"EVM execution {:?} in sidechain block {}: {}, gas used: {}",
+		receipt.transaction_hash,
+		receipt.block_number,
+		if receipt.succeeded { "success" } else { "failed" },
+		receipt.gas_used
+	);
+	if let Some(contract_address) = receipt.contract_address {
+		cli_println!(cli, "   created contract {:?}", contract_address);
+	}
+	for log in &receipt.logs {
+		print_evm_log(cli, log);
+	}
+}
+
+pub(crate) fn print_evm_log(cli: &Cli, log: &EvmLog) {
+	cli_println!(
+		cli,
+		"   log of {:?}, topics: {:?}, data: {}",
+		log.address,
+		log.topics,
+		hex_encode(&log.data)
+	);
+}
+
+impl From<EvmLog> for EvmLogInfo {
+	fn from(log: EvmLog) -> Self {
+		EvmLogInfo { address: log.address, topics: log.topics, data: hex_encode(&log.data) }
+	}
+}
+
+impl From<EvmReceipt> for EvmReceiptInfo {
+	fn from(receipt: EvmReceipt) -> Self {
+		EvmReceiptInfo {
+			transaction_hash: receipt.transaction_hash,
+			block_number: receipt.block_number,
+			success: receipt.succeeded,
+			gas_used: receipt.gas_used,
+			contract_address: receipt.contract_address,
+			logs: receipt.logs.into_iter().map(EvmLogInfo::from).collect(),
+		}
+	}
+}
@@ -16,22 +16,25 @@
 */
 
 use crate::{
-	get_layer_two_evm_nonce, get_layer_two_nonce,
+	cli_println,
+	evm::commands::evm_command_utils::{
+		decode_hex_arg, get_evm_base_fee, get_evm_receipt, print_evm_receipt,
+	},
+	get_layer_two_nonce,
 	trusted_cli::TrustedCli,
-	trusted_command_utils::{get_identifiers, get_pair_from_str},
+	trusted_command_utils::{ensure_runtime_version_matches, get_identifiers, get_pair_from_str},
 	trusted_operation::perform_trusted_operation,
-	Cli, CliResult, CliResultOk,
+	Cli, CliError, CliResult, CliResultOk,
 };
-use ita_stf::{evm_helpers::evm_create_address, Index, TrustedCall, TrustedGetter};
+use codec::Encode;
+use ita_stf::{Index, TrustedCall};
 use itp_stf_primitives::{
 	traits::TrustedCallSigning,
 	types::{KeyPair, TrustedOperation},
 };
 use itp_types::AccountId;
 use log::*;
-use pallet_evm::{AddressMapping, HashedAddressMapping};
-use sp_core::{crypto::Ss58Codec, Pair, H160, U256};
-use sp_runtime::traits::BlakeTwo256;
+use sp_core::{crypto::Ss58Codec, hashing::blake2_256, Pair, H160, H256, U256};
 use std::vec::Vec;
 #[derive(Parser)]
 pub struct EvmCreateCommands {
@@ -44,46 +47,54 @@ pub struct EvmCreateCommands {
 
 impl EvmCreateCommands {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let from = get_pair_from_str(trusted_args, &self.from);
+		let from = get_pair_from_str(trusted_args, &self.from)?;
 		let from_acc: AccountId = from.public().into();
-		println!("from ss58 is {}", from.public().to_ss58check());
+		cli_println!(cli, "from ss58 is {}", from.public().to_ss58check());
 
 		let mut sender_evm_acc_slice: [u8; 20] = [0; 20];
-		sender_evm_acc_slice
-			.copy_from_slice((<[u8; 32]>::from(from_acc.clone())).get(0..20).unwrap());
+		sender_evm_acc_slice.copy_from_slice(&<[u8; 32]>::from(from_acc.clone())[..20]);
 		let sender_evm_acc: H160 = sender_evm_acc_slice.into();
 
-		let (mrenclave, shard) = get_identifiers(trusted_args);
-
-		let sender_evm_substrate_addr =
-			HashedAddressMapping::<BlakeTwo256>::into_account_id(sender_evm_acc);
-		println!(
-			"Trying to get nonce of evm account {:?}",
-			sender_evm_substrate_addr.to_ss58check()
-		);
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		ensure_runtime_version_matches(cli, trusted_args)?;
+		let max_fee_per_gas = get_evm_base_fee(cli, trusted_args)?;
 
 		let nonce = get_layer_two_nonce!(from, cli, trusted_args);
-		let evm_account_nonce = get_layer_two_evm_nonce!(from, cli, trusted_args);
 
-		let top = TrustedCall::evm_create(
+		let signed_call = TrustedCall::evm_create(
 			from_acc,
 			sender_evm_acc,
-			array_bytes::hex2bytes(&self.smart_contract).unwrap().to_vec(),
+			decode_hex_arg(&self.smart_contract)?,
 			U256::from(0),
-			967295,        // gas limit
-			U256::from(1), // max_fee_per_gas !>= min_gas_price defined in runtime
+			967295, // gas limit
+			max_fee_per_gas,
 			None,
 			None,
 			Vec::new(),
 		)
-		.sign(&from.into(), nonce, &mrenclave, &shard)
-		.into_trusted_operation(trusted_args.direct);
+		.sign(&from.clone().into(), nonce, &mrenclave, &shard);
+		let call_hash = H256::from(blake2_256(&signed_call.encode()));
+		let top = signed_call.into_trusted_operation(trusted_args.direct);
 
 		perform_trusted_operation::<()>(cli, trusted_args, &top)?;
-
-		let execution_address = evm_create_address(sender_evm_acc, evm_account_nonce);
 		info!("trusted call evm_create executed");
-		println!("Created the smart contract with address {:?}", execution_address);
-		Ok(CliResultOk::H160 { hash: execution_address })
+
+		let receipt = get_evm_receipt(cli, trusted_args, &from, call_hash);
+		if let Some(receipt) = receipt.as_ref() {
+			print_evm_receipt(cli, receipt);
+		}
+		match receipt.and_then(|receipt| receipt.contract_address) {
+			Some(execution_address) => {
+				cli_println!(
+					cli,
+					"Created the smart contract with address {:?}",
+					execution_address
+				);
+				Ok(CliResultOk::H160 { hash: execution_address })
+			},
+			None => Err(CliError::TrustedOp {
+				msg: format!("No contract has been created by the trusted call {:?}", call_hash),
+			}),
+		}
 	}
 }
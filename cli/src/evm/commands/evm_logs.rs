@@ -0,0 +1,90 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	cli_println,
+	evm::commands::evm_command_utils::{
+		decode_evm_address_arg, decode_evm_topic_arg, print_evm_log,
+	},
+	trusted_cli::TrustedCli,
+	trusted_command_utils::get_pair_from_str,
+	trusted_operation::perform_trusted_operation,
+	Cli, CliError, CliResult, CliResultOk, EvmLogRecordInfo,
+};
+use ita_stf::{
+	evm_transaction::{EvmLogFilter, EvmLogRecord},
+	Getter, TrustedCallSigned, TrustedGetter,
+};
+use itp_stf_primitives::types::{KeyPair, TrustedOperation};
+use sp_core::Pair;
+
+#[derive(Parser)]
+pub struct EvmLogsCommands {
+	/// Sender's incognito AccountId in ss58check format
+	from: String,
+
+	/// Only logs emitted by this contract
+	#[clap(long)]
+	address: Option<String>,
+
+	/// Topics by position, `_` matches any topic (e.g. `--topics 0xddf2..,_,0x00..`)
+	#[clap(long, value_delimiter = ',')]
+	topics: Vec<String>,
+}
+
+impl EvmLogsCommands {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let sender = get_pair_from_str(trusted_args, &self.from)?;
+		let filter = EvmLogFilter {
+			address: self.address.as_deref().map(decode_evm_address_arg).transpose()?,
+			topics: self
+				.topics
+				.iter()
+				.map(|topic| match topic.as_str() {
+					"_" => Ok(None),
+					topic => decode_evm_topic_arg(topic).map(Some),
+				})
+				.collect::<Result<_, CliError>>()?,
+		};
+
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
+			TrustedGetter::evm_logs(sender.public().into(), filter)
+				.sign(&KeyPair::Sr25519(Box::new(sender))),
+		));
+		let records = perform_trusted_operation::<Vec<EvmLogRecord>>(cli, trusted_args, &top)
+			.map_err(|e| CliError::TrustedOp { msg: e.to_string() })?;
+
+		for record in &records {
+			cli_println!(
+				cli,
+				"Trusted call {:?} in sidechain block {}:",
+				record.transaction_hash,
+				record.block_number
+			);
+			print_evm_log(cli, &record.log);
+		}
+		let logs = records
+			.into_iter()
+			.map(|record| EvmLogRecordInfo {
+				transaction_hash: record.transaction_hash,
+				block_number: record.block_number,
+				log: record.log.into(),
+			})
+			.collect();
+		Ok(CliResultOk::EvmLogs { logs })
+	}
+}
@@ -16,8 +16,9 @@
 */
 
 use crate::{
-	trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
-	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
+	cli_println, evm::commands::evm_command_utils::decode_evm_address_arg, trusted_cli::TrustedCli,
+	trusted_command_utils::get_pair_from_str, trusted_operation::perform_trusted_operation, Cli,
+	CliError, CliResult, CliResultOk,
 };
 use ita_stf::{Getter, TrustedCallSigned, TrustedGetter};
 use itp_stf_primitives::types::{KeyPair, TrustedOperation};
@@ -36,20 +37,18 @@ pub struct EvmReadCommands {
 
 impl EvmReadCommands {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let sender = get_pair_from_str(trusted_args, &self.from);
+		let sender = get_pair_from_str(trusted_args, &self.from)?;
 		let sender_acc: AccountId = sender.public().into();
 
 		info!("senders ss58 is {}", sender.public().to_ss58check());
 
 		let mut sender_evm_acc_slice: [u8; 20] = [0; 20];
-		sender_evm_acc_slice
-			.copy_from_slice((<[u8; 32]>::from(sender_acc.clone())).get(0..20).unwrap());
+		sender_evm_acc_slice.copy_from_slice(&<[u8; 32]>::from(sender_acc.clone())[..20]);
 		let sender_evm_acc: H160 = sender_evm_acc_slice.into();
 
 		info!("senders evm account is {}", sender_evm_acc);
 
-		let execution_address =
-			H160::from_slice(&array_bytes::hex2bytes(&self.execution_address).unwrap());
+		let execution_address = decode_evm_address_arg(&self.execution_address)?;
 
 		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
 			TrustedGetter::evm_account_storages(sender_acc, execution_address, H256::zero())
@@ -57,7 +56,7 @@ impl EvmReadCommands {
 		));
 		match perform_trusted_operation::<H256>(cli, trusted_args, &top) {
 			Ok(hash) => {
-				println!("{:?}", hash);
+				cli_println!(cli, "{:?}", hash);
 				Ok(CliResultOk::H256 { hash })
 			},
 			Err(e) => {
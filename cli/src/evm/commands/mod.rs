@@ -1,6 +1,7 @@
 pub mod evm_call;
 pub mod evm_command_utils;
 pub mod evm_create;
+pub mod evm_logs;
 pub mod evm_read;
 
 pub use crate::get_layer_two_evm_nonce;
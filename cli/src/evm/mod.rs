@@ -17,7 +17,8 @@
 
 use crate::{
 	evm::commands::{
-		evm_call::EvmCallCommands, evm_create::EvmCreateCommands, evm_read::EvmReadCommands,
+		evm_call::EvmCallCommands, evm_create::EvmCreateCommands, evm_logs::EvmLogsCommands,
+		evm_read::EvmReadCommands,
 	},
 	trusted_cli::TrustedCli,
 	Cli, CliResult,
@@ -36,6 +37,9 @@ pub enum EvmCommand {
 
 	/// Create smart contract
 	EvmCall(EvmCallCommands),
+
+	/// List the logs emitted by the own trusted calls, filtered by contract and topics
+	EvmLogs(EvmLogsCommands),
 }
 
 impl EvmCommand {
@@ -44,6 +48,7 @@ impl EvmCommand {
 			EvmCommand::EvmCreate(cmd) => cmd.run(cli, trusted_args),
 			EvmCommand::EvmRead(cmd) => cmd.run(cli, trusted_args),
 			EvmCommand::EvmCall(cmd) => cmd.run(cli, trusted_args),
+			EvmCommand::EvmLogs(cmd) => cmd.run(cli, trusted_args),
 		}
 	}
 }
@@ -28,6 +28,7 @@ extern crate chrono;
 extern crate env_logger;
 extern crate log;
 
+mod admin_cli;
 mod attesteer;
 mod base_cli;
 mod benchmark;
@@ -44,10 +45,12 @@ mod trusted_operation;
 pub mod commands;
 
 use crate::commands::Commands;
-use clap::Parser;
+use clap::{ArgEnum, Parser};
+use ita_stf::Index;
 use itp_node_api::api_client::Metadata;
+use serde::{Serialize, Serializer};
 use sp_application_crypto::KeyTypeId;
-use sp_core::{H160, H256};
+use sp_core::{H160, H256, U256};
 use thiserror::Error;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -80,10 +83,45 @@ pub struct Cli {
 	#[clap(short = 'P', long, default_value_t = String::from("2000"))]
 	trusted_worker_port: String,
 
+	/// output format: human readable text or a single json document on stdout
+	#[clap(long, arg_enum, default_value = "plain")]
+	output: OutputFormat,
+
 	#[clap(subcommand)]
 	command: Commands,
 }
 
+impl Cli {
+	pub fn output_format(&self) -> OutputFormat {
+		self.output
+	}
+
+	pub fn is_json_output(&self) -> bool {
+		self.output == OutputFormat::Json
+	}
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+	Plain,
+	Json,
+}
+
+/// Prints human readable command output.
+///
+/// Suppressed with `--output json`, in which case the `CliResult` returned
+/// by the command is the only thing written to stdout.
+#[macro_export]
+macro_rules! cli_println {
+	($cli:expr, $($arg:tt)*) => {
+		if !$cli.is_json_output() {
+			println!($($arg)*);
+		}
+	};
+}
+
+#[derive(Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
 pub enum CliResultOk {
 	PubKeysBase58 {
 		pubkeys_sr25519: Option<Vec<String>>,
@@ -92,25 +130,152 @@ pub enum CliResultOk {
 	Balance {
 		balance: u128,
 	},
+	Nonce {
+		nonce: Index,
+	},
 	MrEnclaveBase58 {
 		mr_enclaves: Vec<String>,
 	},
+	/// Result of "ListWorkers"
+	Workers {
+		workers: Vec<WorkerInfo>,
+	},
 	Metadata {
+		#[serde(serialize_with = "serialize_metadata")]
 		metadata: Metadata,
 	},
+	/// Result of "ShieldFundsCommand", "GetShardCommand", "EvmReadCommands", the oracle whitelist
+	/// and of trusted calls: the trusted call hash if sent directly, the hash of the parentchain
+	/// block including it otherwise
 	H256 {
 		hash: H256,
 	},
+	/// Result of "FaucetCommand"
+	ExtrinsicHashes {
+		hashes: Vec<H256>,
+	},
+	/// Result of "RegisterTcbInfoCommand"
+	TcbInfoRegistrations {
+		registrations: Vec<TcbInfoRegistration>,
+	},
+	/// Result of the attesteer commands: the hex encoded extrinsic sent by the worker
+	ForwardedExtrinsic {
+		extrinsic: String,
+	},
 	/// Result of "EvmCreateCommands": execution_address
 	H160 {
 		hash: H160,
 	},
-	// TODO should ideally be removed; or at least drastically less used
-	// We WANT all commands exposed by the cli to return something useful for the caller(ie instead of printing)
-	None,
+	/// Result of "ListenCommand" and the oracle listeners
+	Events {
+		events: Vec<EventInfo>,
+	},
+	/// Result of "EvmCallCommands"
+	EvmReceipt {
+		receipt: Option<EvmReceiptInfo>,
+	},
+	/// Result of "EvmLogsCommands"
+	EvmLogs {
+		logs: Vec<EvmLogRecordInfo>,
+	},
+	/// Result of "HistoryCommand"
+	TrustedCallHistory {
+		receipts: Vec<ReceiptInfo>,
+	},
+	/// Result of "BenchmarkCommand"
+	BenchmarkStats {
+		stats: BenchmarkStats,
+	},
+	/// Result of the admin commands, as returned by the admin rpc of the worker
+	AdminRpc {
+		result: serde_json::Value,
+	},
+}
+
+#[derive(Serialize)]
+pub struct WorkerInfo {
+	pub signer: String,
+	pub mr_enclave: String,
+	pub attestation_timestamp: u64,
+	pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TcbInfoRegistration {
+	pub fmspc: String,
+	/// `None` if the registration extrinsic failed.
+	pub extrinsic_hash: Option<H256>,
+}
+
+#[derive(Serialize)]
+pub struct EventInfo {
+	pub block_number: Option<u32>,
+	pub pallet: String,
+	pub variant: String,
+	pub details: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReceiptInfo {
+	pub call_hash: H256,
+	pub nonce: Index,
+	pub block_number: u32,
+	pub success: bool,
+	pub error: Option<String>,
+	pub events: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct EvmReceiptInfo {
+	/// Hash of the signed trusted call.
+	pub transaction_hash: H256,
+	pub block_number: u32,
+	pub success: bool,
+	pub gas_used: U256,
+	pub contract_address: Option<H160>,
+	pub logs: Vec<EvmLogInfo>,
 }
 
-#[derive(Debug, Error)]
+#[derive(Serialize)]
+pub struct EvmLogInfo {
+	pub address: H160,
+	pub topics: Vec<H256>,
+	/// Hex encoded.
+	pub data: String,
+}
+
+#[derive(Serialize)]
+pub struct EvmLogRecordInfo {
+	/// Hash of the signed trusted call, which emitted the log.
+	pub transaction_hash: H256,
+	pub block_number: u32,
+	pub log: EvmLogInfo,
+}
+
+#[derive(Serialize)]
+pub struct BenchmarkStats {
+	pub number_clients: u32,
+	pub number_iterations: u128,
+	pub duration_ms: u128,
+	pub missing_measurements: u32,
+	/// (percentile, latency in ms) pairs.
+	pub percentiles: Vec<(u32, u64)>,
+}
+
+fn serialize_metadata<S: Serializer>(
+	metadata: &Metadata,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	let pretty = metadata
+		.pretty_format()
+		.ok_or_else(|| serde::ser::Error::custom("failed to format metadata"))?;
+	let value: serde_json::Value =
+		serde_json::from_str(&pretty).map_err(serde::ser::Error::custom)?;
+	value.serialize(serializer)
+}
+
+#[derive(Debug, Error, Serialize)]
+#[serde(tag = "error", rename_all = "snake_case")]
 pub enum CliError {
 	#[error("extrinsic error: {:?}", msg)]
 	Extrinsic { msg: String },
@@ -120,12 +285,31 @@ pub enum CliError {
 	EvmRead { msg: String },
 	#[error("worker rpc api error: {:?}", msg)]
 	WorkerRpcApi { msg: String },
+	#[error("parentchain rpc api error: {:?}", msg)]
+	ChainRpcApi { msg: String },
+	#[error("runtime version mismatch: {:?}", msg)]
+	RuntimeVersion { msg: String },
+	#[error("admin rpc error: {:?}", msg)]
+	AdminRpc { msg: String },
+	#[error("invalid argument: {:?}", msg)]
+	InvalidArgument { msg: String },
+	#[error("keystore error: {:?}", msg)]
+	Keystore { msg: String },
+	#[error("io error: {:?}", msg)]
+	Io { msg: String },
 }
 
 pub type CliResult = Result<CliResultOk, CliError>;
 
+/// Renders a `CliResult` as the json document printed with `--output json`.
+pub fn cli_result_to_json(result: &CliResult) -> serde_json::Result<String> {
+	match result {
+		Ok(ok) => serde_json::to_string(ok),
+		Err(err) => serde_json::to_string(err),
+	}
+}
+
 /// This is used for the commands that directly call `perform_trusted_operation`
-/// which typically return `CliResultOk::None`
 ///
 /// eg: `SetBalanceCommand`,`TransferCommand`,`UnshieldFundsCommand`
 impl From<trusted_operation::TrustedOperationError> for CliError {
@@ -133,3 +317,9 @@ impl From<trusted_operation::TrustedOperationError> for CliError {
 		CliError::TrustedOp { msg: value.to_string() }
 	}
 }
+
+impl From<itc_rpc_client::direct_client::Error> for CliError {
+	fn from(value: itc_rpc_client::direct_client::Error) -> Self {
+		CliError::WorkerRpcApi { msg: value.to_string() }
+	}
+}
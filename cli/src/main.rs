@@ -16,7 +16,7 @@
 */
 
 use clap::Parser;
-use integritee_cli::{commands, Cli};
+use integritee_cli::{cli_result_to_json, commands, Cli, OutputFormat};
 
 fn main() {
 	env_logger::builder()
@@ -25,5 +25,24 @@ fn main() {
 
 	let cli = Cli::parse();
 
-	commands::match_command(&cli).unwrap();
+	let result = commands::match_command(&cli);
+	match cli.output_format() {
+		OutputFormat::Plain =>
+			if let Err(e) = result {
+				eprintln!("{}", e);
+				std::process::exit(1);
+			},
+		OutputFormat::Json => {
+			match cli_result_to_json(&result) {
+				Ok(json) => println!("{}", json),
+				Err(e) => {
+					eprintln!("could not serialize the result: {}", e);
+					std::process::exit(1);
+				},
+			}
+			if result.is_err() {
+				std::process::exit(1);
+			}
+		},
+	}
 }
@@ -16,8 +16,9 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_chain_api, get_pair_from_str, mrenclave_from_base58},
-	Cli,
+	Cli, CliError, CliResult, CliResultOk,
 };
 use itp_node_api::api_client::{ADD_TO_WHITELIST, TEERACLE};
 use substrate_api_client::{
@@ -41,10 +42,10 @@ pub struct AddToWhitelistCmd {
 }
 
 impl AddToWhitelistCmd {
-	pub fn run(&self, cli: &Cli) {
-		let mut api = get_chain_api(cli);
-		let mrenclave = mrenclave_from_base58(&self.mrenclave);
-		let from = get_pair_from_str(&self.from);
+	pub fn run(&self, cli: &Cli) -> CliResult {
+		let mut api = get_chain_api(cli)?;
+		let mrenclave = mrenclave_from_base58(&self.mrenclave)?;
+		let from = get_pair_from_str(&self.from)?;
 
 		let market_data_source = self.source.clone();
 
@@ -61,7 +62,14 @@ impl AddToWhitelistCmd {
 		// compose the extrinsic
 		let xt = compose_extrinsic!(api, "Sudo", "sudo", call);
 
-		let report = api.submit_and_watch_extrinsic_until(xt, XtStatus::Finalized).unwrap();
-		println!("[+] Add to whitelist got finalized. Hash: {:?}\n", report.extrinsic_hash);
+		let report = api
+			.submit_and_watch_extrinsic_until(xt, XtStatus::Finalized)
+			.map_err(|e| CliError::Extrinsic { msg: format!("{:?}", e) })?;
+		cli_println!(
+			cli,
+			"[+] Add to whitelist got finalized. Hash: {:?}\n",
+			report.extrinsic_hash
+		);
+		Ok(CliResultOk::H256 { hash: report.extrinsic_hash })
 	}
 }
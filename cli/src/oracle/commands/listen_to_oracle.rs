@@ -15,7 +15,9 @@
 
 */
 
-use crate::{command_utils::get_chain_api, Cli};
+use crate::{
+	cli_println, command_utils::get_chain_api, Cli, CliError, CliResult, CliResultOk, EventInfo,
+};
 use itp_node_api::api_client::ParentchainApi;
 use itp_time_utils::{duration_now, remaining_time};
 use itp_types::parentchain::OracleUpdated;
@@ -30,36 +32,52 @@ pub struct ListenToOracleEventsCmd {
 	duration: u64,
 }
 
-type EventCount = u32;
 impl ListenToOracleEventsCmd {
-	pub fn run(&self, cli: &Cli) {
-		let api = get_chain_api(cli);
+	pub fn run(&self, cli: &Cli) -> CliResult {
+		let api = get_chain_api(cli)?;
 		let duration = Duration::from_secs(self.duration);
-		let count = count_oracle_update_events(&api, duration);
-		println!("Number of Oracle events received : ");
-		println!("   EVENTS_COUNT: {}", count);
+		let events = collect_oracle_update_events(cli, &api, duration)?;
+		let count = events.len();
+		cli_println!(cli, "Number of Oracle events received : ");
+		cli_println!(cli, "   EVENTS_COUNT: {}", count);
+		Ok(CliResultOk::Events { events })
 	}
 }
 
-fn count_oracle_update_events(api: &ParentchainApi, duration: Duration) -> EventCount {
+fn collect_oracle_update_events(
+	cli: &Cli,
+	api: &ParentchainApi,
+	duration: Duration,
+) -> Result<Vec<EventInfo>, CliError> {
 	let stop = duration_now() + duration;
 
 	//subscribe to events
-	let mut subscription = api.subscribe_events().unwrap();
-	let mut count = 0;
+	let mut subscription = api
+		.subscribe_events()
+		.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
+	let mut collected = Vec::new();
 	while remaining_time(stop).unwrap_or_default() > Duration::ZERO {
-		let events = subscription.next_events_from_metadata().unwrap().unwrap();
+		let events = subscription
+			.next_events_from_metadata()
+			.ok_or_else(|| CliError::ChainRpcApi { msg: "event subscription ended".into() })?
+			.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
 		for event in events.iter() {
-			let event = event.unwrap();
+			let event = event.map_err(|e| CliError::ChainRpcApi { msg: format!("{:?}", e) })?;
 			match event.pallet_name() {
 				"Teeracle" => match event.variant_name() {
 					"OracleUpdated" =>
 						if let Ok(Some(ev)) = event.as_event::<OracleUpdated>() {
-							count += 1;
-							println!(
-								"OracleUpdated: ORACLE_NAME : {}, SRC : {}",
+							let details = format!(
+								"ORACLE_NAME : {}, SRC : {}",
 								ev.oracle_data_name, ev.data_source
 							);
+							cli_println!(cli, "OracleUpdated: {}", details);
+							collected.push(EventInfo {
+								block_number: None,
+								pallet: event.pallet_name().into(),
+								variant: event.variant_name().into(),
+								details: Some(details),
+							});
 						},
 					_ => continue,
 				},
@@ -67,6 +85,6 @@ fn count_oracle_update_events(api: &ParentchainApi, duration: Duration) -> Event
 			}
 		}
 	}
-	debug!("Received {} ExchangeRateUpdated event(s) in total", count);
-	count
+	debug!("Received {} OracleUpdated event(s) in total", collected.len());
+	Ok(collected)
 }
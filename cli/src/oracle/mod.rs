@@ -20,7 +20,7 @@
 //! Todo: This shall be a standalone crate in app-libs/oracle. However, this needs:
 //! https://github.com/integritee-network/worker/issues/852
 
-use crate::Cli;
+use crate::{Cli, CliResult};
 use commands::{AddToWhitelistCmd, ListenToExchangeRateEventsCmd, ListenToOracleEventsCmd};
 
 mod commands;
@@ -39,7 +39,7 @@ pub enum OracleCommand {
 }
 
 impl OracleCommand {
-	pub fn run(&self, cli: &Cli) {
+	pub fn run(&self, cli: &Cli) -> CliResult {
 		match self {
 			OracleCommand::AddToWhitelist(cmd) => cmd.run(cli),
 			OracleCommand::ListenToExchangeRateEvents(cmd) => cmd.run(cli),
@@ -16,7 +16,8 @@
 */
 
 use crate::{
-	trusted_cli::TrustedCli, trusted_command_utils::get_balance, Cli, CliResult, CliResultOk,
+	cli_println, trusted_cli::TrustedCli, trusted_command_utils::get_balance, Cli, CliResult,
+	CliResultOk,
 };
 
 #[derive(Parser)]
@@ -27,8 +28,8 @@ pub struct BalanceCommand {
 
 impl BalanceCommand {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let balance = get_balance(cli, trusted_args, &self.account).unwrap_or_default();
-		println!("{}", balance);
+		let balance = get_balance(cli, trusted_args, &self.account)?;
+		cli_println!(cli, "{}", balance);
 		Ok(CliResultOk::Balance { balance })
 	}
 }
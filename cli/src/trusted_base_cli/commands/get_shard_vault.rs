@@ -16,8 +16,8 @@
 */
 
 use crate::{
-	command_utils::get_worker_api_direct, trusted_cli::TrustedCli, Cli, CliError, CliResult,
-	CliResultOk,
+	cli_println, command_utils::get_worker_api_direct, trusted_cli::TrustedCli, Cli, CliError,
+	CliResult, CliResultOk,
 };
 
 use codec::Decode;
@@ -36,10 +36,11 @@ pub struct GetShardVaultCommand {}
 
 impl GetShardVaultCommand {
 	pub(crate) fn run(&self, cli: &Cli, _trusted_args: &TrustedCli) -> CliResult {
-		let direct_api = get_worker_api_direct(cli);
+		let direct_api = get_worker_api_direct(cli)?;
 		let rpc_method = "author_getShardVault".to_owned();
-		let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(rpc_method, vec![]).unwrap();
-		let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
+		let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(rpc_method, vec![])
+			.map_err(|e| CliError::WorkerRpcApi { msg: e.to_string() })?;
+		let rpc_response_str = direct_api.get(&jsonrpc_call)?;
 		// Decode RPC response.
 		let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str)
 			.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
@@ -51,7 +52,10 @@ impl GetShardVaultCommand {
 			})?;
 
 		if rpc_return_value.status == DirectRequestStatus::Error {
-			error!("{}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap());
+			error!(
+				"{}",
+				String::decode(&mut rpc_return_value.value.as_slice()).unwrap_or_default()
+			);
 			return Err(CliError::WorkerRpcApi { msg: "rpc error".to_string() })
 		}
 
@@ -62,7 +66,7 @@ impl GetShardVaultCommand {
 				CliError::WorkerRpcApi { msg: err.to_string() }
 			})?;
 		let vault_ss58 = vault.to_ss58check();
-		println!("{}", vault_ss58);
+		cli_println!(cli, "{}", vault_ss58);
 		Ok(CliResultOk::PubKeysBase58 {
 			pubkeys_sr25519: None,
 			pubkeys_ed25519: Some(vec![vault_ss58]),
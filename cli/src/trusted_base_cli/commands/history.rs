@@ -0,0 +1,88 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	cli_println, trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
+	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
+	ReceiptInfo,
+};
+use ita_stf::{
+	receipts::{TrustedCallOutcome, TrustedCallReceipt},
+	Getter, TrustedCallSigned, TrustedGetter,
+};
+use itp_stf_primitives::types::{KeyPair, TrustedOperation};
+use sp_core::Pair;
+
+#[derive(Parser)]
+pub struct HistoryCommand {
+	/// AccountId in ss58check format
+	account: String,
+
+	/// page to query, 0 being the most recent trusted calls
+	#[clap(long, default_value_t = 0)]
+	page: u32,
+
+	/// number of receipts per page
+	#[clap(long, default_value_t = 10)]
+	page_size: u32,
+}
+
+impl HistoryCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let who = get_pair_from_str(trusted_args, &self.account)?;
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
+			TrustedGetter::trusted_call_history(who.public().into(), self.page, self.page_size)
+				.sign(&KeyPair::Sr25519(Box::new(who))),
+		));
+		let receipts =
+			perform_trusted_operation::<Vec<TrustedCallReceipt>>(cli, trusted_args, &top)
+				.map_err(|e| CliError::TrustedOp { msg: e.to_string() })?;
+
+		let receipts: Vec<ReceiptInfo> = receipts
+			.into_iter()
+			.map(|receipt| {
+				let (success, error) = match receipt.outcome {
+					TrustedCallOutcome::Success => (true, None),
+					TrustedCallOutcome::Failure(e) => (false, Some(e)),
+				};
+				ReceiptInfo {
+					call_hash: receipt.call_hash,
+					nonce: receipt.nonce,
+					block_number: receipt.block_number,
+					success,
+					error,
+					events: receipt.events.iter().map(|e| format!("{:?}", e)).collect(),
+				}
+			})
+			.collect();
+
+		for receipt in &receipts {
+			cli_println!(
+				cli,
+				"Trusted call {:?} (nonce {}) in sidechain block {}: {}",
+				receipt.call_hash,
+				receipt.nonce,
+				receipt.block_number,
+				receipt.error.as_deref().unwrap_or("success")
+			);
+			for event in &receipt.events {
+				cli_println!(cli, "   {}", event);
+			}
+		}
+		Ok(CliResultOk::TrustedCallHistory { receipts })
+	}
+}
@@ -1,7 +1,9 @@
 pub mod balance;
 pub mod get_shard;
 pub mod get_shard_vault;
+pub mod history;
 pub mod nonce;
 pub mod set_balance;
+pub mod subscribe_events;
 pub mod transfer;
 pub mod unshield_funds;
@@ -16,8 +16,9 @@
 */
 
 use crate::{
-	get_layer_two_nonce, trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
-	trusted_operation::perform_trusted_operation, Cli, CliResult, CliResultOk,
+	cli_println, get_layer_two_nonce, trusted_cli::TrustedCli,
+	trusted_command_utils::get_pair_from_str, trusted_operation::perform_trusted_operation, Cli,
+	CliResult, CliResultOk,
 };
 use ita_stf::Index;
 use itp_stf_primitives::types::{KeyPair, TrustedOperation};
@@ -32,8 +33,9 @@ pub struct NonceCommand {
 
 impl NonceCommand {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let who = get_pair_from_str(trusted_args, &self.account);
-		println!("{}", get_layer_two_nonce!(who, cli, trusted_args));
-		Ok(CliResultOk::None)
+		let who = get_pair_from_str(trusted_args, &self.account)?;
+		let nonce = get_layer_two_nonce!(who, cli, trusted_args);
+		cli_println!(cli, "{}", nonce);
+		Ok(CliResultOk::Nonce { nonce })
 	}
 }
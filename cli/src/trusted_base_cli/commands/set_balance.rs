@@ -16,9 +16,9 @@
 */
 
 use crate::{
-	get_layer_two_nonce,
+	cli_println, get_layer_two_nonce,
 	trusted_cli::TrustedCli,
-	trusted_command_utils::{get_identifiers, get_pair_from_str},
+	trusted_command_utils::{ensure_runtime_version_matches, get_identifiers, get_pair_from_str},
 	trusted_operation::perform_trusted_operation,
 	Cli, CliResult, CliResultOk,
 };
@@ -29,7 +29,7 @@ use itp_stf_primitives::{
 	types::{KeyPair, TrustedOperation},
 };
 use log::*;
-use sp_core::{crypto::Ss58Codec, Pair};
+use sp_core::{crypto::Ss58Codec, Pair, H256};
 use std::boxed::Box;
 
 #[derive(Parser)]
@@ -43,13 +43,14 @@ pub struct SetBalanceCommand {
 
 impl SetBalanceCommand {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let who = get_pair_from_str(trusted_args, &self.account);
-		let signer = get_pair_from_str(trusted_args, "//Alice");
+		let who = get_pair_from_str(trusted_args, &self.account)?;
+		let signer = get_pair_from_str(trusted_args, "//Alice")?;
 		info!("account ss58 is {}", who.public().to_ss58check());
 
-		println!("send trusted call set-balance({}, {})", who.public(), self.amount);
+		cli_println!(cli, "send trusted call set-balance({}, {})", who.public(), self.amount);
 
-		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		ensure_runtime_version_matches(cli, trusted_args)?;
 		let nonce = get_layer_two_nonce!(signer, cli, trusted_args);
 		let top: TrustedOperation<TrustedCallSigned, Getter> = TrustedCall::balance_set_balance(
 			signer.public().into(),
@@ -59,6 +60,7 @@ impl SetBalanceCommand {
 		)
 		.sign(&KeyPair::Sr25519(Box::new(signer)), nonce, &mrenclave, &shard)
 		.into_trusted_operation(trusted_args.direct);
-		Ok(perform_trusted_operation::<()>(cli, trusted_args, &top).map(|_| CliResultOk::None)?)
+		let hash = perform_trusted_operation::<H256>(cli, trusted_args, &top)?;
+		Ok(CliResultOk::H256 { hash })
 	}
 }
@@ -0,0 +1,130 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	cli_println,
+	command_utils::get_worker_api_direct,
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_identifiers, get_pair_from_str},
+	Cli, CliError, CliResult, CliResultOk, EventInfo,
+};
+use codec::Decode;
+use ita_stf::subscriptions::{
+	AccountEvents, AccountEventsSubscription, MAX_SUBSCRIPTION_VALIDITY_MILLIS,
+};
+use itc_rpc_client::direct_client::DirectApi;
+use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_stf_primitives::types::KeyPair;
+use itp_time_utils::now_as_millis;
+use itp_types::{DirectRequestStatus, SubscriptionTopic};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use log::*;
+use rand::Rng;
+use sp_core::Pair;
+use std::sync::mpsc::channel;
+
+#[derive(Parser)]
+pub struct SubscribeEventsCommand {
+	/// AccountId in ss58check format
+	account: String,
+
+	/// stop after receiving this number of event notifications
+	#[clap(long)]
+	count: Option<u32>,
+}
+
+impl SubscribeEventsCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let who = get_pair_from_str(trusted_args, &self.account)?;
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		// The subscription only needs to be valid until the worker has registered it.
+		let signed_subscription = AccountEventsSubscription::new(
+			who.public().into(),
+			shard,
+			rand::thread_rng().gen(),
+			now_as_millis() + MAX_SUBSCRIPTION_VALIDITY_MILLIS / 2,
+		)
+		.sign(&KeyPair::Sr25519(Box::new(who)), &mrenclave);
+
+		let jsonrpc_call = RpcRequest::compose_jsonrpc_call(
+			"author_subscribeAccountEvents".to_string(),
+			vec![signed_subscription.to_hex()],
+		)
+		.map_err(|e| CliError::WorkerRpcApi { msg: e.to_string() })?;
+
+		let direct_api = get_worker_api_direct(cli)?;
+		let (sender, receiver) = channel();
+		direct_api.watch(jsonrpc_call, sender);
+
+		let mut collected = Vec::new();
+		let mut confirmed = false;
+		let mut notifications = 0u32;
+		while self.count.map_or(true, |count| notifications < count) {
+			let response = receiver.recv().map_err(|e| CliError::TrustedOp {
+				msg: format!("Failed to receive rpc response: {:?}", e),
+			})?;
+			let return_value = decode_return_value(&response)?;
+
+			if return_value.status == DirectRequestStatus::Error {
+				let msg = String::decode(&mut return_value.value.as_slice()).unwrap_or_default();
+				direct_api.close()?;
+				return Err(CliError::TrustedOp { msg })
+			}
+
+			// The first response confirms the subscription, all following ones carry events.
+			if !confirmed {
+				let topic = SubscriptionTopic::decode(&mut return_value.value.as_slice());
+				debug!("Subscription confirmed: {:?}", topic);
+				confirmed = true;
+				continue
+			}
+
+			let events =
+				AccountEvents::decode(&mut return_value.value.as_slice()).map_err(|e| {
+					CliError::TrustedOp { msg: format!("Could not decode events: {:?}", e) }
+				})?;
+			notifications += 1;
+			for event in events {
+				cli_println!(cli, "{:?}", event);
+				collected.push(event_info(&event));
+			}
+		}
+
+		direct_api.close()?;
+		Ok(CliResultOk::Events { events: collected })
+	}
+}
+
+fn decode_return_value(response: &str) -> Result<RpcReturnValue, CliError> {
+	let response: RpcResponse = serde_json::from_str(response)
+		.map_err(|e| CliError::TrustedOp { msg: format!("Could not parse response: {:?}", e) })?;
+	RpcReturnValue::from_hex(&response.result)
+		.map_err(|e| CliError::TrustedOp { msg: format!("Could not decode response: {:?}", e) })
+}
+
+/// Splits the debug representation of an event, e.g. `Balances(Transfer { .. })`.
+fn event_info(event: &impl std::fmt::Debug) -> EventInfo {
+	let debug = format!("{:?}", event);
+	let (pallet, rest) = debug.split_once('(').unwrap_or((debug.as_str(), ""));
+	let variant = rest.split(|c: char| !c.is_alphanumeric()).next().unwrap_or_default();
+	EventInfo {
+		block_number: None,
+		pallet: pallet.into(),
+		variant: variant.into(),
+		details: Some(debug.clone()),
+	}
+}
@@ -16,9 +16,11 @@
 */
 
 use crate::{
-	get_layer_two_nonce,
+	cli_println, get_layer_two_nonce,
 	trusted_cli::TrustedCli,
-	trusted_command_utils::{get_accountid_from_str, get_identifiers, get_pair_from_str},
+	trusted_command_utils::{
+		ensure_runtime_version_matches, get_accountid_from_str, get_identifiers, get_pair_from_str,
+	},
 	trusted_operation::perform_trusted_operation,
 	Cli, CliResult, CliResultOk,
 };
@@ -29,7 +31,7 @@ use itp_stf_primitives::{
 	types::{KeyPair, TrustedOperation},
 };
 use log::*;
-use sp_core::{crypto::Ss58Codec, Pair};
+use sp_core::{crypto::Ss58Codec, Pair, H256};
 use std::boxed::Box;
 
 #[derive(Parser)]
@@ -46,14 +48,16 @@ pub struct TransferCommand {
 
 impl TransferCommand {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let from = get_pair_from_str(trusted_args, &self.from);
-		let to = get_accountid_from_str(&self.to);
+		let from = get_pair_from_str(trusted_args, &self.from)?;
+		let to = get_accountid_from_str(&self.to)?;
 		info!("from ss58 is {}", from.public().to_ss58check());
 		info!("to ss58 is {}", to.to_ss58check());
 
-		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		ensure_runtime_version_matches(cli, trusted_args)?;
 		let nonce = get_layer_two_nonce!(from, cli, trusted_args);
-		println!(
+		cli_println!(
+			cli,
 			"send trusted call transfer from {} to {}: {}, nonce: {}",
 			from.public(),
 			to,
@@ -64,9 +68,8 @@ impl TransferCommand {
 			TrustedCall::balance_transfer(from.public().into(), to, self.amount)
 				.sign(&KeyPair::Sr25519(Box::new(from)), nonce, &mrenclave, &shard)
 				.into_trusted_operation(trusted_args.direct);
-		let res =
-			perform_trusted_operation::<()>(cli, trusted_args, &top).map(|_| CliResultOk::None)?;
+		let hash = perform_trusted_operation::<H256>(cli, trusted_args, &top)?;
 		info!("trusted call transfer executed");
-		Ok(res)
+		Ok(CliResultOk::H256 { hash })
 	}
 }
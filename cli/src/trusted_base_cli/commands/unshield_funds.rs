@@ -16,9 +16,11 @@
 */
 
 use crate::{
-	get_layer_two_nonce,
+	cli_println, get_layer_two_nonce,
 	trusted_cli::TrustedCli,
-	trusted_command_utils::{get_accountid_from_str, get_identifiers, get_pair_from_str},
+	trusted_command_utils::{
+		ensure_runtime_version_matches, get_accountid_from_str, get_identifiers, get_pair_from_str,
+	},
 	trusted_operation::perform_trusted_operation,
 	Cli, CliResult, CliResultOk,
 };
@@ -29,7 +31,7 @@ use itp_stf_primitives::{
 	types::{KeyPair, TrustedOperation},
 };
 use log::*;
-use sp_core::{crypto::Ss58Codec, Pair};
+use sp_core::{crypto::Ss58Codec, Pair, H256};
 use std::boxed::Box;
 #[derive(Parser)]
 pub struct UnshieldFundsCommand {
@@ -45,24 +47,27 @@ pub struct UnshieldFundsCommand {
 
 impl UnshieldFundsCommand {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
-		let from = get_pair_from_str(trusted_args, &self.from);
-		let to = get_accountid_from_str(&self.to);
-		println!("from ss58 is {}", from.public().to_ss58check());
-		println!("to   ss58 is {}", to.to_ss58check());
+		let from = get_pair_from_str(trusted_args, &self.from)?;
+		let to = get_accountid_from_str(&self.to)?;
+		cli_println!(cli, "from ss58 is {}", from.public().to_ss58check());
+		cli_println!(cli, "to   ss58 is {}", to.to_ss58check());
 
-		println!(
+		cli_println!(
+			cli,
 			"send trusted call unshield_funds from {} to {}: {}",
 			from.public(),
 			to,
 			self.amount
 		);
 
-		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let (mrenclave, shard) = get_identifiers(trusted_args)?;
+		ensure_runtime_version_matches(cli, trusted_args)?;
 		let nonce = get_layer_two_nonce!(from, cli, trusted_args);
 		let top: TrustedOperation<TrustedCallSigned, Getter> =
 			TrustedCall::balance_unshield(from.public().into(), to, self.amount, shard)
 				.sign(&KeyPair::Sr25519(Box::new(from)), nonce, &mrenclave, &shard)
 				.into_trusted_operation(trusted_args.direct);
-		Ok(perform_trusted_operation::<()>(cli, trusted_args, &top).map(|_| CliResultOk::None)?)
+		let hash = perform_trusted_operation::<H256>(cli, trusted_args, &top)?;
+		Ok(CliResultOk::H256 { hash })
 	}
 }
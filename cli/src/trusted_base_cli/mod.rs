@@ -16,14 +16,16 @@
 */
 
 use crate::{
+	cli_println,
 	trusted_base_cli::commands::{
 		balance::BalanceCommand, get_shard::GetShardCommand, get_shard_vault::GetShardVaultCommand,
-		nonce::NonceCommand, set_balance::SetBalanceCommand, transfer::TransferCommand,
+		history::HistoryCommand, nonce::NonceCommand, set_balance::SetBalanceCommand,
+		subscribe_events::SubscribeEventsCommand, transfer::TransferCommand,
 		unshield_funds::UnshieldFundsCommand,
 	},
 	trusted_cli::TrustedCli,
 	trusted_command_utils::get_keystore_path,
-	Cli, CliResult, CliResultOk, ED25519_KEY_TYPE, SR25519_KEY_TYPE,
+	Cli, CliError, CliResult, CliResultOk, ED25519_KEY_TYPE, SR25519_KEY_TYPE,
 };
 use log::*;
 use sp_core::crypto::Ss58Codec;
@@ -61,13 +63,19 @@ pub enum TrustedBaseCommand {
 
 	/// get shard vault for shielding (if defined for this worker)
 	GetShardVault(GetShardVaultCommand),
+
+	/// list receipts of the most recent trusted calls of an incognito account
+	History(HistoryCommand),
+
+	/// print the sidechain events of an incognito account as they happen
+	SubscribeEvents(SubscribeEventsCommand),
 }
 
 impl TrustedBaseCommand {
 	pub fn run(&self, cli: &Cli, trusted_cli: &TrustedCli) -> CliResult {
 		match self {
-			TrustedBaseCommand::NewAccount => new_account(trusted_cli),
-			TrustedBaseCommand::ListAccounts => list_accounts(trusted_cli),
+			TrustedBaseCommand::NewAccount => new_account(cli, trusted_cli),
+			TrustedBaseCommand::ListAccounts => list_accounts(cli, trusted_cli),
 			TrustedBaseCommand::Transfer(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::SetBalance(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::Balance(cmd) => cmd.run(cli, trusted_cli),
@@ -75,26 +83,36 @@ impl TrustedBaseCommand {
 			TrustedBaseCommand::Nonce(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::GetShard(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::GetShardVault(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::History(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SubscribeEvents(cmd) => cmd.run(cli, trusted_cli),
 		}
 	}
 }
 
-fn new_account(trusted_args: &TrustedCli) -> CliResult {
-	let store = LocalKeystore::open(get_keystore_path(trusted_args), None).unwrap();
-	let key = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None).unwrap();
+fn new_account(cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+	let store = LocalKeystore::open(get_keystore_path(trusted_args)?, None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+	let key = LocalKeystore::sr25519_generate_new(&store, SR25519_KEY_TYPE, None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
 	drop(store);
 	info!("new account {}", key.to_ss58check());
 	let key_str = key.to_ss58check();
-	println!("{}", key_str);
+	cli_println!(cli, "{}", key_str);
 
 	Ok(CliResultOk::PubKeysBase58 { pubkeys_sr25519: Some(vec![key_str]), pubkeys_ed25519: None })
 }
 
-fn list_accounts(trusted_args: &TrustedCli) -> CliResult {
-	let store = LocalKeystore::open(get_keystore_path(trusted_args), None).unwrap();
+fn list_accounts(cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+	let store = LocalKeystore::open(get_keystore_path(trusted_args)?, None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
 	info!("sr25519 keys:");
-	for pubkey in store.sr25519_public_keys(SR25519_KEY_TYPE).into_iter() {
-		println!("{}", pubkey.to_ss58check());
+	let sr25519_pubkeys: Vec<String> = store
+		.sr25519_public_keys(SR25519_KEY_TYPE)
+		.into_iter()
+		.map(|pubkey| pubkey.to_ss58check())
+		.collect();
+	for pubkey in &sr25519_pubkeys {
+		cli_println!(cli, "{}", pubkey);
 	}
 	info!("ed25519 keys:");
 	let pubkeys: Vec<String> = store
@@ -103,9 +121,12 @@ fn list_accounts(trusted_args: &TrustedCli) -> CliResult {
 		.map(|pubkey| pubkey.to_ss58check())
 		.collect();
 	for pubkey in &pubkeys {
-		println!("{}", pubkey);
+		cli_println!(cli, "{}", pubkey);
 	}
 	drop(store);
 
-	Ok(CliResultOk::PubKeysBase58 { pubkeys_sr25519: None, pubkeys_ed25519: Some(pubkeys) })
+	Ok(CliResultOk::PubKeysBase58 {
+		pubkeys_sr25519: Some(sr25519_pubkeys),
+		pubkeys_ed25519: Some(pubkeys),
+	})
 }
@@ -39,6 +39,10 @@ pub struct TrustedCli {
 	#[clap(short, long)]
 	pub(crate) direct: bool,
 
+	/// only warn instead of refusing to sign trusted calls if the worker runs another runtime version
+	#[clap(long)]
+	pub(crate) ignore_runtime_version: bool,
+
 	#[clap(subcommand)]
 	pub(crate) command: TrustedCommand,
 }
@@ -19,11 +19,12 @@ use crate::{
 	command_utils::{get_worker_api_direct, mrenclave_from_base58},
 	trusted_cli::TrustedCli,
 	trusted_operation::{perform_trusted_operation, read_shard},
-	Cli,
+	Cli, CliError,
 };
-use base58::{FromBase58, ToBase58};
+use base58::ToBase58;
 use codec::{Decode, Encode};
 use ita_parentchain_interface::integritee::Balance;
+use ita_sgx_runtime::VERSION;
 use ita_stf::{Getter, TrustedCallSigned, TrustedGetter};
 use itc_rpc_client::direct_client::DirectApi;
 use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
@@ -46,14 +47,15 @@ macro_rules! get_layer_two_nonce {
 			TrustedGetter::nonce($signer_pair.public().into())
 				.sign(&KeyPair::Sr25519(Box::new($signer_pair.clone()))),
 		));
-		// final nonce = current system nonce + pending tx count, panic early
-		let nonce = perform_trusted_operation::<Index>($cli, $trusted_args, &top)
-			.ok()
-			.unwrap_or_default();
+		// final nonce = current system nonce + pending tx count, fail early
+		let nonce = perform_trusted_operation::<Index>($cli, $trusted_args, &top)?;
 		debug!("got system nonce: {:?}", nonce);
 		let pending_tx_count =
-			get_pending_trusted_calls_for($cli, $trusted_args, &$signer_pair.public().into()).len();
-		let pending_tx_count = Index::try_from(pending_tx_count).unwrap();
+			get_pending_trusted_calls_for($cli, $trusted_args, &$signer_pair.public().into())?
+				.len();
+		let pending_tx_count = Index::try_from(pending_tx_count).map_err(|e| {
+			$crate::CliError::TrustedOp { msg: format!("too many pending trusted calls: {}", e) }
+		})?;
 		debug!("got pending tx count: {:?}", pending_tx_count);
 		nonce + pending_tx_count
 	}};
@@ -61,67 +63,115 @@ macro_rules! get_layer_two_nonce {
 
 const TRUSTED_KEYSTORE_PATH: &str = "my_trusted_keystore";
 
-pub(crate) fn get_balance(cli: &Cli, trusted_args: &TrustedCli, arg_who: &str) -> Option<u128> {
+pub(crate) fn get_balance(
+	cli: &Cli,
+	trusted_args: &TrustedCli,
+	arg_who: &str,
+) -> Result<Balance, CliError> {
 	debug!("arg_who = {:?}", arg_who);
-	let who = get_pair_from_str(trusted_args, arg_who);
+	let who = get_pair_from_str(trusted_args, arg_who)?;
 	let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
 		TrustedGetter::free_balance(who.public().into()).sign(&KeyPair::Sr25519(Box::new(who))),
 	));
-	perform_trusted_operation::<Balance>(cli, trusted_args, &top).ok()
+	Ok(perform_trusted_operation::<Balance>(cli, trusted_args, &top)?)
 }
 
-pub(crate) fn get_keystore_path(trusted_args: &TrustedCli) -> PathBuf {
-	let (_mrenclave, shard) = get_identifiers(trusted_args);
-	PathBuf::from(&format!("{}/{}", TRUSTED_KEYSTORE_PATH, shard.encode().to_base58()))
+pub(crate) fn get_keystore_path(trusted_args: &TrustedCli) -> Result<PathBuf, CliError> {
+	let shard = read_shard(trusted_args)?;
+	Ok(PathBuf::from(&format!("{}/{}", TRUSTED_KEYSTORE_PATH, shard.encode().to_base58())))
 }
 
-pub(crate) fn get_identifiers(trusted_args: &TrustedCli) -> ([u8; 32], ShardIdentifier) {
-	let mrenclave = mrenclave_from_base58(
-		trusted_args
-			.mrenclave
-			.as_ref()
-			.expect("argument '--mrenclave' must be provided for this command"),
-	);
+pub(crate) fn get_identifiers(
+	trusted_args: &TrustedCli,
+) -> Result<([u8; 32], ShardIdentifier), CliError> {
+	let mrenclave = mrenclave_from_base58(trusted_args.mrenclave.as_ref().ok_or_else(|| {
+		CliError::InvalidArgument {
+			msg: "argument '--mrenclave' must be provided for this command".into(),
+		}
+	})?)?;
 	let shard = match &trusted_args.shard {
-		Some(val) =>
-			ShardIdentifier::from_slice(&val.from_base58().expect("shard has to be base58 encoded")),
+		Some(_) => read_shard(trusted_args)?,
 		None => ShardIdentifier::from_slice(&mrenclave),
 	};
-	(mrenclave, shard)
+	Ok((mrenclave, shard))
 }
 
-// TODO this function is redundant with client::main
-pub(crate) fn get_accountid_from_str(account: &str) -> AccountId {
-	match &account[..2] {
-		"//" => sr25519::Pair::from_string(account, None)
-			.unwrap()
-			.public()
-			.into_account()
-			.into(),
-		_ => sr25519::Public::from_ss58check(account).unwrap().into_account().into(),
+/// Makes sure the worker runs the runtime version this cli was built against before a trusted
+/// call is signed for it. A mismatch in `spec_version` or `transaction_version` means the call
+/// could be encoded differently from what the enclave expects, so we refuse to sign, unless
+/// `--ignore-runtime-version` was given, in which case we only warn.
+pub(crate) fn ensure_runtime_version_matches(
+	cli: &Cli,
+	trusted_args: &TrustedCli,
+) -> Result<(), CliError> {
+	let worker_version = get_worker_api_direct(cli)?
+		.get_state_runtime_version()
+		.map_err(|e| CliError::WorkerRpcApi { msg: format!("{:?}", e) })?;
+
+	if worker_version.spec_version == VERSION.spec_version
+		&& worker_version.transaction_version == VERSION.transaction_version
+	{
+		return Ok(())
+	}
+
+	let msg = format!(
+		"worker runtime version (spec_version: {}, transaction_version: {}) does not match the \
+		 cli's (spec_version: {}, transaction_version: {})",
+		worker_version.spec_version,
+		worker_version.transaction_version,
+		VERSION.spec_version,
+		VERSION.transaction_version
+	);
+	if trusted_args.ignore_runtime_version {
+		warn!("{}", msg);
+		Ok(())
+	} else {
+		Err(CliError::RuntimeVersion { msg })
 	}
 }
 
+// TODO this function is redundant with client::main
+pub(crate) fn get_accountid_from_str(account: &str) -> Result<AccountId, CliError> {
+	let invalid =
+		|e| CliError::InvalidArgument { msg: format!("invalid account {}: {:?}", account, e) };
+	let public = if account.starts_with("//") {
+		sr25519::Pair::from_string(account, None).map_err(invalid)?.public()
+	} else {
+		sr25519::Public::from_ss58check(account).map_err(invalid)?
+	};
+	Ok(public.into_account().into())
+}
+
 // TODO this function is ALMOST redundant with client::main
 // get a pair either form keyring (well known keys) or from the store
-pub(crate) fn get_pair_from_str(trusted_args: &TrustedCli, account: &str) -> sr25519_core::Pair {
+pub(crate) fn get_pair_from_str(
+	trusted_args: &TrustedCli,
+	account: &str,
+) -> Result<sr25519_core::Pair, CliError> {
 	info!("getting pair for {}", account);
-	match &account[..2] {
-		"//" => sr25519_core::Pair::from_string(account, None).unwrap(),
-		_ => {
-			info!("fetching from keystore at {}", &TRUSTED_KEYSTORE_PATH);
-			// open store without password protection
-			let store = LocalKeystore::open(get_keystore_path(trusted_args), None)
-				.expect("store should exist");
-			info!("store opened");
-			let public_key = &sr25519::AppPublic::from_ss58check(account).unwrap();
-			info!("public_key: {:?}", &public_key);
-			let _pair = store.key_pair::<sr25519::AppPair>(public_key).unwrap().unwrap();
-			info!("key pair fetched");
-			drop(store);
-			_pair.into()
-		},
+	if account.starts_with("//") {
+		return sr25519_core::Pair::from_string(account, None).map_err(|e| {
+			CliError::InvalidArgument { msg: format!("invalid account {}: {:?}", account, e) }
+		})
 	}
+
+	info!("fetching from keystore at {}", &TRUSTED_KEYSTORE_PATH);
+	let public_key = sr25519::AppPublic::from_ss58check(account).map_err(|e| {
+		CliError::InvalidArgument { msg: format!("invalid account {}: {:?}", account, e) }
+	})?;
+	info!("public_key: {:?}", &public_key);
+	// open store without password protection
+	let store = LocalKeystore::open(get_keystore_path(trusted_args)?, None)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?;
+	info!("store opened");
+	let pair = store
+		.key_pair::<sr25519::AppPair>(&public_key)
+		.map_err(|e| CliError::Keystore { msg: format!("{:?}", e) })?
+		.ok_or_else(|| CliError::Keystore {
+			msg: format!("no key for {} in the keystore", account),
+		})?;
+	info!("key pair fetched");
+	Ok(pair.into())
 }
 
 // helper method to get the pending trusted calls for a given account via direct RPC
@@ -129,26 +179,35 @@ pub(crate) fn get_pending_trusted_calls_for(
 	cli: &Cli,
 	trusted_args: &TrustedCli,
 	who: &AccountId,
-) -> Vec<TrustedOperation<TrustedCallSigned, Getter>> {
-	let shard = read_shard(trusted_args).unwrap();
-	let direct_api = get_worker_api_direct(cli);
+) -> Result<Vec<TrustedOperation<TrustedCallSigned, Getter>>, CliError> {
+	let shard = read_shard(trusted_args)?;
+	let direct_api = get_worker_api_direct(cli)?;
 	let rpc_method = "author_pendingTrustedCallsFor".to_owned();
 	let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(
 		rpc_method,
 		vec![shard.encode().to_base58(), who.to_hex()],
 	)
-	.unwrap();
+	.map_err(|e| CliError::WorkerRpcApi { msg: e.to_string() })?;
 
-	let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
-	let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str).unwrap();
-	let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result).unwrap();
+	let rpc_response_str = direct_api.get(&jsonrpc_call)?;
+	let rpc_return_value = decode_rpc_return_value(&rpc_response_str)?;
+	direct_api.close()?;
 
 	if rpc_return_value.status == DirectRequestStatus::Error {
-		error!("{}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap());
-		direct_api.close().unwrap();
-		return vec![]
+		error!("{}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap_or_default());
+		return Ok(vec![])
 	}
 
-	direct_api.close().unwrap();
-	Decode::decode(&mut rpc_return_value.value.as_slice()).unwrap_or_default()
+	Ok(Decode::decode(&mut rpc_return_value.value.as_slice()).unwrap_or_default())
+}
+
+/// Decodes the `RpcReturnValue` of the response to a `DirectApi::get` request.
+pub(crate) fn decode_rpc_return_value(rpc_response_str: &str) -> Result<RpcReturnValue, CliError> {
+	let rpc_response: RpcResponse =
+		serde_json::from_str(rpc_response_str).map_err(|e| CliError::WorkerRpcApi {
+			msg: format!("can't parse rpc response {}: {}", rpc_response_str, e),
+		})?;
+	RpcReturnValue::from_hex(&rpc_response.result).map_err(|e| CliError::WorkerRpcApi {
+		msg: format!("failed to decode rpc return value: {:?}", e),
+	})
 }
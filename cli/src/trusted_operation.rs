@@ -16,15 +16,16 @@
 */
 
 use crate::{
+	cli_println,
 	command_utils::{get_chain_api, get_pair_from_str, get_shielding_key, get_worker_api_direct},
 	trusted_cli::TrustedCli,
-	Cli,
+	Cli, CliError,
 };
 use base58::{FromBase58, ToBase58};
 use codec::{Decode, Encode, Input};
 use enclave_bridge_primitives::Request;
 use ita_stf::{Getter, TrustedCallSigned};
-use itc_rpc_client::direct_client::{DirectApi, DirectClient};
+use itc_rpc_client::direct_client::{DirectApi, DirectClient, Error as DirectClientError};
 use itp_node_api::api_client::{ApiClientError, ENCLAVE_BRIDGE};
 use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
 use itp_sgx_crypto::ShieldingCryptoEncrypt;
@@ -68,6 +69,8 @@ pub(crate) enum TrustedOperationError {
 	ConfirmedBlockHashDoesNotMatchExpected(BlockHash, BlockHash),
 	#[error("invocation extrinsic L1 error: {msg:?}")]
 	IndirectInvocationFailed { msg: String },
+	#[error("worker rpc error: {0}")]
+	WorkerRpc(DirectClientError),
 	#[error("default error: {msg:?}")]
 	Default { msg: String },
 }
@@ -78,6 +81,18 @@ impl From<ApiClientError> for TrustedOperationError {
 	}
 }
 
+impl From<DirectClientError> for TrustedOperationError {
+	fn from(error: DirectClientError) -> Self {
+		Self::WorkerRpc(error)
+	}
+}
+
+impl From<CliError> for TrustedOperationError {
+	fn from(error: CliError) -> Self {
+		Self::Default { msg: error.to_string() }
+	}
+}
+
 pub(crate) type TrustedOpResult<T> = StdResult<T, TrustedOperationError>;
 
 pub(crate) fn perform_trusted_operation<T: Decode + Debug>(
@@ -98,8 +113,8 @@ fn execute_getter_from_cli_args<T: Decode + Debug>(
 	trusted_args: &TrustedCli,
 	getter: &Getter,
 ) -> TrustedOpResult<T> {
-	let shard = read_shard(trusted_args).unwrap();
-	let direct_api = get_worker_api_direct(cli);
+	let shard = read_shard(trusted_args)?;
+	let direct_api = get_worker_api_direct(cli)?;
 	get_state(&direct_api, shard, getter)
 }
 
@@ -111,10 +126,10 @@ pub(crate) fn get_state<T: Decode + Debug>(
 	// Compose jsonrpc call.
 	let data = Request { shard, cyphertext: getter.encode() };
 	let rpc_method = "state_executeGetter".to_owned();
-	let jsonrpc_call: String =
-		RpcRequest::compose_jsonrpc_call(rpc_method, vec![data.to_hex()]).unwrap();
+	let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(rpc_method, vec![data.to_hex()])
+		.map_err(|err| TrustedOperationError::Default { msg: err.to_string() })?;
 
-	let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
+	let rpc_response_str = direct_api.get(&jsonrpc_call)?;
 
 	// Decode RPC response.
 	let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str)
@@ -127,7 +142,7 @@ pub(crate) fn get_state<T: Decode + Debug>(
 		})?;
 
 	if rpc_return_value.status == DirectRequestStatus::Error {
-		error!("{}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap());
+		error!("{}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap_or_default());
 		return Err(TrustedOperationError::Default {
 			msg: "[Error] DirectRequestStatus::Error".to_string(),
 		})
@@ -154,18 +169,20 @@ fn send_indirect_request<T: Decode + Debug>(
 	trusted_args: &TrustedCli,
 	trusted_operation: &TrustedOperation<TrustedCallSigned, Getter>,
 ) -> TrustedOpResult<T> {
-	let mut chain_api = get_chain_api(cli);
-	let encryption_key = get_shielding_key(cli).unwrap();
-	let call_encrypted = encryption_key.encrypt(&trusted_operation.encode()).unwrap();
+	let mut chain_api = get_chain_api(cli)?;
+	let encryption_key = get_shielding_key(cli)?;
+	let call_encrypted = encryption_key.encrypt(&trusted_operation.encode()).map_err(|e| {
+		TrustedOperationError::Default { msg: format!("Could not encrypt the call: {:?}", e) }
+	})?;
 
-	let shard = read_shard(trusted_args).unwrap();
+	let shard = read_shard(trusted_args)?;
 	debug!(
 		"invoke indirect send_request: trusted operation: {:?},  shard: {}",
 		trusted_operation,
 		shard.encode().to_base58()
 	);
 	let arg_signer = &trusted_args.xt_signer;
-	let signer = get_pair_from_str(arg_signer);
+	let signer = get_pair_from_str(arg_signer)?;
 	chain_api.set_signer(signer.into());
 
 	let request = Request { shard, cyphertext: call_encrypted };
@@ -175,11 +192,13 @@ fn send_indirect_request<T: Decode + Debug>(
 		.submit_and_watch_extrinsic_until(xt, XtStatus::InBlock)
 	{
 		Ok(xt_report) => {
-			println!(
+			let block_hash = xt_report.block_hash.ok_or(TrustedOperationError::MissingBlock)?;
+			cli_println!(
+				cli,
 				"[+] invoke TrustedOperation extrinsic success. extrinsic hash: {:?} / status: {:?} / block hash: {:?}",
-				xt_report.extrinsic_hash, xt_report.status, xt_report.block_hash.unwrap()
+				xt_report.extrinsic_hash, xt_report.status, block_hash
 			);
-			xt_report.block_hash.unwrap()
+			block_hash
 		},
 		Err(e) => {
 			error!("invoke TrustedOperation extrinsic failed {:?}", e);
@@ -196,20 +215,24 @@ fn send_indirect_request<T: Decode + Debug>(
 	);
 	info!("Waiting for execution confirmation from enclave...");
 	let mut blocks = 0u32;
-	let mut subscription = chain_api.subscribe_events().unwrap();
+	let mut subscription = chain_api.subscribe_events()?;
 	loop {
-		let events = subscription.next_events_from_metadata().unwrap().unwrap();
+		let events = subscription.next_events_from_metadata().ok_or_else(|| {
+			TrustedOperationError::Default { msg: "Event subscription has ended".to_string() }
+		})??;
 		blocks += 1;
 		if blocks > TIMEOUT_BLOCKS {
 			return Err(TrustedOperationError::ConfirmationTimedOut(blocks))
 		}
 		for event in events.iter() {
-			let event = event.unwrap();
+			let event = event.map_err(|e| TrustedOperationError::Default {
+				msg: format!("Could not decode event: {:?}", e),
+			})?;
 			match event.pallet_name() {
 				"EnclaveBridge" => match event.variant_name() {
 					"ProcessedParentchainBlock" => {
 						if let Ok(Some(ev)) = event.as_event::<ProcessedParentchainBlock>() {
-							println!("EnclaveBridge::{:?}", ev);
+							cli_println!(cli, "EnclaveBridge::{:?}", ev);
 							debug!(
 								"Invocation block Number we're waiting for: {:?}",
 								invocation_block_number
@@ -249,22 +272,20 @@ fn send_indirect_request<T: Decode + Debug>(
 	}
 }
 
-pub fn read_shard(trusted_args: &TrustedCli) -> StdResult<ShardIdentifier, codec::Error> {
-	match &trusted_args.shard {
-		Some(s) => match s.from_base58() {
-			Ok(s) => ShardIdentifier::decode(&mut &s[..]),
-			_ => panic!("shard argument must be base58 encoded"),
-		},
-		None => match trusted_args
-			.mrenclave
-			.as_ref()
-			.expect("at least argument '--mrenclave' must be provided for this command")
-			.from_base58()
-		{
-			Ok(s) => ShardIdentifier::decode(&mut &s[..]),
-			_ => panic!("mrenclave argument must be base58 encoded"),
-		},
-	}
+pub fn read_shard(trusted_args: &TrustedCli) -> StdResult<ShardIdentifier, CliError> {
+	let (name, value) = match (&trusted_args.shard, &trusted_args.mrenclave) {
+		(Some(shard), _) => ("shard", shard),
+		(None, Some(mrenclave)) => ("mrenclave", mrenclave),
+		(None, None) =>
+			return Err(CliError::InvalidArgument {
+				msg: "at least argument '--mrenclave' must be provided for this command".into(),
+			}),
+	};
+	let invalid = || CliError::InvalidArgument {
+		msg: format!("{} argument must be 32 bytes, base58 encoded", name),
+	};
+	let decoded = value.from_base58().map_err(|_| invalid())?;
+	ShardIdentifier::decode(&mut decoded.as_slice()).map_err(|_| invalid())
 }
 
 /// sends a rpc watch request to the worker api server
@@ -273,15 +294,15 @@ fn send_direct_request<T: Decode + Debug>(
 	trusted_args: &TrustedCli,
 	operation_call: &TrustedOperation<TrustedCallSigned, Getter>,
 ) -> TrustedOpResult<T> {
-	let encryption_key = get_shielding_key(cli).unwrap();
-	let shard = read_shard(trusted_args).unwrap();
-	let jsonrpc_call: String = get_json_request(shard, operation_call, encryption_key);
+	let encryption_key = get_shielding_key(cli)?;
+	let shard = read_shard(trusted_args)?;
+	let jsonrpc_call: String = get_json_request(shard, operation_call, encryption_key)?;
 	debug!(
 		"send_direct_request: trusted operation: {:?},  shard: {}",
 		operation_call,
 		shard.encode().to_base58()
 	);
-	let direct_api = get_worker_api_direct(cli);
+	let direct_api = get_worker_api_direct(cli)?;
 
 	debug!("setup sender and receiver");
 	let (sender, receiver) = channel();
@@ -292,7 +313,8 @@ fn send_direct_request<T: Decode + Debug>(
 		match receiver.recv() {
 			Ok(response) => {
 				debug!("received response");
-				let response: RpcResponse = serde_json::from_str(&response).unwrap();
+				let response: RpcResponse = serde_json::from_str(&response)
+					.map_err(|err| TrustedOperationError::Default { msg: err.to_string() })?;
 				if let Ok(return_value) = RpcReturnValue::from_hex(&response.result) {
 					debug!("successfully decoded rpc response: {:?}", return_value);
 					match return_value.status {
@@ -301,7 +323,7 @@ fn send_direct_request<T: Decode + Debug>(
 							if let Ok(value) = String::decode(&mut return_value.value.as_slice()) {
 								error!("{}", value);
 							}
-							direct_api.close().unwrap();
+							direct_api.close()?;
 							return Err(TrustedOperationError::Default {
 								msg: "[Error] DirectRequestStatus::Error".to_string(),
 							})
@@ -309,10 +331,10 @@ fn send_direct_request<T: Decode + Debug>(
 						DirectRequestStatus::TrustedOperationStatus(status) => {
 							debug!("request status is: {:?}", status);
 							if let Ok(value) = Hash::decode(&mut return_value.value.as_slice()) {
-								println!("Trusted call {:?} is {:?}", value, status);
+								cli_println!(cli, "Trusted call {:?} is {:?}", value, status);
 							}
 							if connection_can_be_closed(status) {
-								direct_api.close().unwrap();
+								direct_api.close()?;
 								let value =
 									decode_response_value(&mut return_value.value.as_slice())?;
 								return Ok(value)
@@ -320,14 +342,14 @@ fn send_direct_request<T: Decode + Debug>(
 						},
 						DirectRequestStatus::Ok => {
 							debug!("request status is ignored");
-							direct_api.close().unwrap();
+							direct_api.close()?;
 							let value = decode_response_value(&mut return_value.value.as_slice())?;
 							return Ok(value)
 						},
 					}
 					if !return_value.do_watch {
 						debug!("do watch is false, closing connection");
-						direct_api.close().unwrap();
+						direct_api.close()?;
 						let value = decode_response_value(&mut return_value.value.as_slice())?;
 						return Ok(value)
 					}
@@ -335,7 +357,7 @@ fn send_direct_request<T: Decode + Debug>(
 			},
 			Err(e) => {
 				error!("failed to receive rpc response: {:?}", e);
-				direct_api.close().unwrap();
+				direct_api.close()?;
 				return Err(TrustedOperationError::Default {
 					msg: "failed to receive rpc response".to_string(),
 				})
@@ -356,8 +378,11 @@ pub(crate) fn get_json_request(
 	shard: ShardIdentifier,
 	operation_call: &TrustedOperation<TrustedCallSigned, Getter>,
 	shielding_pubkey: sgx_crypto_helper::rsa3072::Rsa3072PubKey,
-) -> String {
-	let operation_call_encrypted = shielding_pubkey.encrypt(&operation_call.encode()).unwrap();
+) -> TrustedOpResult<String> {
+	let operation_call_encrypted =
+		shielding_pubkey.encrypt(&operation_call.encode()).map_err(|e| {
+			TrustedOperationError::Default { msg: format!("Could not encrypt the call: {:?}", e) }
+		})?;
 
 	// compose jsonrpc call
 	let request = Request { shard, cyphertext: operation_call_encrypted };
@@ -365,10 +390,11 @@ pub(crate) fn get_json_request(
 		"author_submitAndWatchExtrinsic".to_string(),
 		vec![request.to_hex()],
 	)
-	.unwrap()
+	.map_err(|err| TrustedOperationError::Default { msg: err.to_string() })
 }
 
 pub(crate) fn wait_until(
+	cli: &Cli,
 	receiver: &Receiver<String>,
 	until: impl Fn(TrustedOperationStatus) -> bool,
 ) -> Option<(H256, Instant)> {
@@ -395,7 +421,7 @@ pub(crate) fn wait_until(
 								debug!("request status is: {:?}", status);
 								if let Ok(value) = Hash::decode(&mut return_value.value.as_slice())
 								{
-									println!("Trusted call {:?} is {:?}", value, status);
+									cli_println!(cli, "Trusted call {:?} is {:?}", value, status);
 									if until(status.clone()) {
 										return Some((value, Instant::now()))
 									} else if status == TrustedOperationStatus::Invalid {
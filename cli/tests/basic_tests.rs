@@ -1,5 +1,6 @@
 use clap::Parser;
-use integritee_cli::Cli;
+use integritee_cli::{cli_result_to_json, Cli, CliError, CliResult, CliResultOk, OutputFormat};
+use sp_core::H256;
 
 fn init() {
 	let _ = env_logger::try_init();
@@ -22,3 +23,73 @@ fn test_help() {
 
 	assert!(matches!(res, Err(clap::Error { kind: clap::ErrorKind::DisplayHelp, .. })));
 }
+
+#[test]
+fn test_output_format_defaults_to_plain() {
+	init();
+
+	let cli = Cli::try_parse_from(vec!["placeholder_cli_path", "list-accounts"]).unwrap();
+
+	assert_eq!(cli.output_format(), OutputFormat::Plain);
+	assert!(!cli.is_json_output());
+}
+
+#[test]
+fn test_output_format_json() {
+	init();
+
+	let cli =
+		Cli::try_parse_from(vec!["placeholder_cli_path", "--output", "json", "list-accounts"])
+			.unwrap();
+
+	assert_eq!(cli.output_format(), OutputFormat::Json);
+}
+
+#[test]
+fn test_cli_error_is_rendered_as_json() {
+	init();
+
+	let result: CliResult = Err(CliError::TrustedOp { msg: "failed".to_string() });
+
+	assert_eq!(cli_result_to_json(&result).unwrap(), r#"{"error":"trusted_op","msg":"failed"}"#);
+}
+
+#[test]
+fn test_invalid_argument_is_rendered_as_json() {
+	init();
+
+	let result: CliResult = Err(CliError::InvalidArgument { msg: "bad shard".to_string() });
+
+	assert_eq!(
+		cli_result_to_json(&result).unwrap(),
+		r#"{"error":"invalid_argument","msg":"bad shard"}"#
+	);
+}
+
+#[test]
+fn test_extrinsic_hashes_are_rendered_as_json() {
+	init();
+
+	let result: CliResult = Ok(CliResultOk::ExtrinsicHashes { hashes: vec![H256::zero()] });
+
+	assert_eq!(
+		cli_result_to_json(&result).unwrap(),
+		format!(r#"{{"result":"extrinsic_hashes","hashes":["0x{}"]}}"#, "0".repeat(64))
+	);
+}
+
+#[test]
+fn test_admin_command_parses() {
+	init();
+
+	let res = Cli::try_parse_from(vec![
+		"placeholder_cli_path",
+		"admin",
+		"--admin-rpc-port",
+		"5000",
+		"pending-trusted-operations",
+		"5wePd1LYa5M49ghwgZXs55cepKbJKhj5xfzQGfPeMS7c",
+	]);
+
+	assert!(res.is_ok());
+}
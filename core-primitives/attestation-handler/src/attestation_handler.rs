@@ -30,9 +30,9 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use crate::{cert, Error as EnclaveError, Error, Result as EnclaveResult};
+use crate::{cert, dcap, Error as EnclaveError, Error, Result as EnclaveResult};
 use codec::Encode;
-use core::{convert::TryInto, default::Default};
+use core::default::Default;
 use itertools::Itertools;
 use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_settings::{
@@ -41,7 +41,6 @@ use itp_settings::{
 };
 use itp_sgx_crypto::key_repository::AccessKey;
 use itp_sgx_io as io;
-use itp_time_utils::now_as_secs;
 use log::*;
 use sgx_rand::{os, Rng};
 use sgx_tcrypto::{rsgx_sha256_slice, SgxEccHandle};
@@ -285,6 +284,7 @@ where
 		let qe_quote = if !skip_ra {
 			let qe_quote = match self.retrieve_qe_dcap_quote(
 				&chain_signer.public().0,
+				&dcap::certificate_key_hash(&certificate_key_bytes(&pub_k)),
 				quoting_enclave_target_info.unwrap(),
 				*quote_size.unwrap(),
 			) {
@@ -682,95 +682,18 @@ where
 
 	/// Returns Ok if the verification of the quote by the quote verification enclave (QVE) was successful
 	pub fn ecdsa_quote_verification(&self, quote: Vec<u8>) -> SgxResult<()> {
-		let mut app_enclave_target_info: sgx_target_info_t = unsafe { std::mem::zeroed() };
-		let quote_collateral: sgx_ql_qve_collateral_t = unsafe { std::mem::zeroed() };
-		let mut qve_report_info: sgx_ql_qe_report_info_t = unsafe { std::mem::zeroed() };
-		let supplemental_data_size = std::mem::size_of::<sgx_ql_qv_supplemental_t>() as u32;
-
-		// Get target info of the app enclave. QvE will target the generated report to this enclave.
-		let ret_val =
-			unsafe { sgx_self_target(&mut app_enclave_target_info as *mut sgx_target_info_t) };
-		if ret_val != sgx_status_t::SGX_SUCCESS {
-			error!("sgx_self_target returned: {:?}", ret_val);
-			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
-		}
-
-		// Set current time, which is needed to check against the expiration date of the certificate.
-		let current_time: i64 = now_as_secs().try_into().unwrap_or_else(|e| {
-			panic!("Could not convert SystemTime from u64 into i64: {:?}", e);
-		});
-
-		// Set random nonce.
-		let mut rand_nonce = vec![0u8; 16];
-		let ret_val = unsafe { sgx_read_rand(rand_nonce.as_mut_ptr(), rand_nonce.len()) };
-		if ret_val != sgx_status_t::SGX_SUCCESS {
-			error!("sgx_read_rand returned: {:?}", ret_val);
-			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
-		}
-		debug!("Retrieved random nonce {:?}", rand_nonce);
-		qve_report_info.nonce.rand.copy_from_slice(rand_nonce.as_slice());
-		qve_report_info.app_enclave_target_info = app_enclave_target_info;
-
-		// Ocall to call Quote verification Enclave (QvE), which verifies the generated quote.
-		let (
-			collateral_expiration_status,
+		let (quote_verification_result, collateral_expiration_status) =
+			dcap::ecdsa_quote_verification(&quote, self.ocall_api.as_ref())?;
+		dcap::check_quote_verification_result(
 			quote_verification_result,
-			qve_report_info_return_value,
-			supplemental_data,
-		) = self.ocall_api.get_qve_report_on_quote(
-			quote.clone(),
-			current_time,
-			quote_collateral,
-			qve_report_info,
-			supplemental_data_size,
-		)?;
-
-		// Check nonce of qve report to protect against replay attacks, as the qve report
-		// is coming from the untrusted side.
-		if qve_report_info_return_value.nonce.rand != qve_report_info.nonce.rand {
-			error!(
-				"Nonce of input value and return value are not matching. Input: {:?}, Output: {:?}",
-				qve_report_info.nonce.rand, qve_report_info_return_value.nonce.rand
-			);
-			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
-		}
-
-		// Set the threshold of QvE ISV SVN. The ISV SVN of QvE used to verify quote must be greater or equal to this threshold
-		// e.g. You can check latest QvE ISVSVN from QvE configuration file on Github
-		// https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/master/QuoteVerification/QvE/Enclave/linux/config.xml#L4
-		// or you can get latest QvE ISVSVN in QvE Identity JSON file from
-		// https://api.trustedservices.intel.com/sgx/certification/v3/qve/identity
-		// Make sure you are using trusted & latest QvE ISV SVN as threshold
-		// Warning: The function may return erroneous result if QvE ISV SVN has been modified maliciously.
-		let qve_isvsvn_threshold: sgx_isv_svn_t = 6;
-
-		// Verify the qve report to validate that it is coming from a legit quoting verification enclave
-		// and has not been tampered with.
-		let ret_val = unsafe {
-			sgx_tvl_verify_qve_report_and_identity(
-				quote.as_ptr(),
-				quote.len() as u32,
-				&qve_report_info_return_value as *const sgx_ql_qe_report_info_t,
-				current_time,
-				collateral_expiration_status,
-				quote_verification_result,
-				supplemental_data.as_ptr(),
-				supplemental_data_size,
-				qve_isvsvn_threshold,
-			)
-		};
-
-		if ret_val != sgx_quote3_error_t::SGX_QL_SUCCESS {
-			error!("sgx_tvl_verify_qve_report_and_identity returned: {:?}", ret_val);
-			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
-		}
-
-		Ok(())
+			collateral_expiration_status,
+		)
 	}
 
 	pub fn retrieve_qe_dcap_quote(
 		&self,
 		pub_k: &[u8; 32],
+		certificate_key_hash: &[u8; 32],
 		quoting_enclave_target_info: &sgx_target_info_t,
 		quote_size: u32,
 	) -> SgxResult<Vec<u8>> {
@@ -778,8 +701,10 @@ where
 		// The quote will be generated on top of this report and validate that the
 		// report as well as the public key inside it are coming from a legit
 		// intel sgx enclave.
+		// The hash of the certificate key binds the quote to the TLS session of the mutual RA.
 		let mut report_data: sgx_report_data_t = sgx_report_data_t::default();
 		report_data.d[..32].clone_from_slice(&pub_k[..]);
+		report_data.d[32..].clone_from_slice(&certificate_key_hash[..]);
 
 		let app_report = match rsgx_create_report(quoting_enclave_target_info, &report_data) {
 			Ok(report) => {
@@ -816,6 +741,15 @@ where
 	}
 }
 
+/// Public key as it is contained in the certificate (x || y, big endian).
+fn certificate_key_bytes(pub_k: &sgx_ec256_public_t) -> Vec<u8> {
+	let mut gx = pub_k.gx;
+	gx.reverse();
+	let mut gy = pub_k.gy;
+	gy.reverse();
+	[gx, gy].concat()
+}
+
 fn decode_spid(hex_encoded_string: &str) -> SgxResult<sgx_spid_t> {
 	let mut spid = sgx_spid_t::default();
 	let hex = hex_encoded_string.trim();
@@ -18,7 +18,7 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use crate::{Error as EnclaveError, Result as EnclaveResult};
+use crate::{dcap, Error as EnclaveError, Result as EnclaveResult};
 use arrayvec::ArrayVec;
 use chrono::DateTime;
 use itertools::Itertools;
@@ -371,8 +371,7 @@ where
 		verify_attn_report(attn_report_raw, pub_k, attestation_ocall)
 	} else {
 		// TODO Refactor state provisioning to not use MURA #1385
-		// TODO DCAP is currently just passed through! SECURITY!!!
-		Ok(())
+		dcap::verify_dcap_quote(&payload, &pub_k, attestation_ocall)
 	}
 }
 
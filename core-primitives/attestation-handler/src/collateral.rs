@@ -15,9 +15,15 @@
 
 */
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
-use crate::sgx_reexport_prelude::serde_json;
-use sgx_types::sgx_ql_qve_collateral_t;
-use std::{io::Write, string::String, vec::Vec};
+use crate::sgx_reexport_prelude::{chrono, serde_json};
+use chrono::DateTime;
+use sgx_types::{c_char, sgx_ql_qve_collateral_t};
+use std::{fs, io::Write, path::Path, string::String, vec::Vec};
+
+/// Collateral version, where the PCK certificate chain is contained in the quote.
+pub const COLLATERAL_VERSION: u32 = 1;
+/// TEE type of the collateral for SGX (as opposed to TDX).
+pub const TEE_TYPE_SGX: u32 = 0;
 
 /// This is a rust-ified version of the type sgx_ql_qve_collateral_t.
 /// See Appendix A.3 in the document
@@ -80,6 +86,65 @@ impl SgxQlQveCollateral {
 		}
 	}
 
+	/// Returns the C type pointing into `self`, which must therefore outlive the returned value.
+	pub fn as_c_type(&self) -> sgx_ql_qve_collateral_t {
+		sgx_ql_qve_collateral_t {
+			version: self.version,
+			tee_type: self.tee_type,
+			pck_crl_issuer_chain: self.pck_crl_issuer_chain.as_ptr() as *mut c_char,
+			pck_crl_issuer_chain_size: self.pck_crl_issuer_chain.len() as u32,
+			root_ca_crl: self.root_ca_crl.as_ptr() as *mut c_char,
+			root_ca_crl_size: self.root_ca_crl.len() as u32,
+			pck_crl: self.pck_crl.as_ptr() as *mut c_char,
+			pck_crl_size: self.pck_crl.len() as u32,
+			tcb_info_issuer_chain: self.tcb_info_issuer_chain.as_ptr() as *mut c_char,
+			tcb_info_issuer_chain_size: self.tcb_info_issuer_chain.len() as u32,
+			tcb_info: self.tcb_info.as_ptr() as *mut c_char,
+			tcb_info_size: self.tcb_info.len() as u32,
+			qe_identity_issuer_chain: self.qe_identity_issuer_chain.as_ptr() as *mut c_char,
+			qe_identity_issuer_chain_size: self.qe_identity_issuer_chain.len() as u32,
+			qe_identity: self.qe_identity.as_ptr() as *mut c_char,
+			qe_identity_size: self.qe_identity.len() as u32,
+		}
+	}
+
+	/// Reads the collateral from the files written by [`Self::dump_to_disk`] in `dir`.
+	///
+	/// The collateral is not trusted, the quote verification enclave checks its signatures.
+	pub fn read_from_dir(dir: &Path) -> std::io::Result<Self> {
+		let read = |filename: &str| -> std::io::Result<Vec<u8>> {
+			let mut data = fs::read(dir.join(filename))?;
+			// The QvE expects C strings, including the null terminator.
+			if data.last() != Some(&0) {
+				data.push(0);
+			}
+			Ok(data)
+		};
+		Ok(SgxQlQveCollateral {
+			version: COLLATERAL_VERSION,
+			tee_type: TEE_TYPE_SGX,
+			pck_crl_issuer_chain: read("pck_crl_issuer_chain")?,
+			root_ca_crl: read("root_ca_crl")?,
+			pck_crl: read("pck_crl")?,
+			tcb_info_issuer_chain: read("tcb_info_issuer_chain")?,
+			tcb_info: read("tcb_info")?,
+			qe_identity_issuer_chain: read("qe_identity_issuer_chain")?,
+			qe_identity: read("qe_identity")?,
+		})
+	}
+
+	/// Writes the collateral to `dir`, in the layout read by [`Self::read_from_dir`].
+	pub fn write_to_dir(&self, dir: &Path) -> std::io::Result<()> {
+		fs::create_dir_all(dir)?;
+		fs::write(dir.join("pck_crl_issuer_chain"), &self.pck_crl_issuer_chain)?;
+		fs::write(dir.join("root_ca_crl"), &self.root_ca_crl)?;
+		fs::write(dir.join("pck_crl"), &self.pck_crl)?;
+		fs::write(dir.join("tcb_info_issuer_chain"), &self.tcb_info_issuer_chain)?;
+		fs::write(dir.join("tcb_info"), &self.tcb_info)?;
+		fs::write(dir.join("qe_identity_issuer_chain"), &self.qe_identity_issuer_chain)?;
+		fs::write(dir.join("qe_identity"), &self.qe_identity)
+	}
+
 	pub fn dump_to_disk(&self) {
 		Self::write_data_to_disk("pck_crl_issuer_chain", &self.pck_crl_issuer_chain);
 		Self::write_data_to_disk("root_ca_crl", &self.root_ca_crl);
@@ -110,6 +175,23 @@ impl SgxQlQveCollateral {
 		}
 	}
 
+	/// Unix timestamp in seconds, at which Intel issues new TCB info or QE identity at the latest.
+	///
+	/// After that, the collateral is considered expired by the quote verification.
+	pub fn next_update(&self) -> Option<i64> {
+		let tcb_info_next_update = Self::next_update_of("tcbInfo", &self.tcb_info)?;
+		let qe_identity_next_update = Self::next_update_of("enclaveIdentity", &self.qe_identity)?;
+		Some(tcb_info_next_update.min(qe_identity_next_update))
+	}
+
+	fn next_update_of(data_name: &str, data: &[u8]) -> Option<i64> {
+		let json = String::from_utf8_lossy(data);
+		let value: serde_json::Value =
+			serde_json::from_str(json.trim_matches(char::from(0))).ok()?;
+		let next_update = value[data_name]["nextUpdate"].as_str()?;
+		DateTime::parse_from_rfc3339(next_update).ok().map(|date| date.timestamp())
+	}
+
 	/// Separates the actual data part from the signature for an Intel collateral in JSON format
 	/// Returns the data part and signature as a pair
 	fn separate_json_data_and_signature(data_name: &str, data: &[u8]) -> Option<(String, String)> {
@@ -136,6 +218,7 @@ impl SgxQlQveCollateral {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use itp_sgx_temp_dir::TempDir;
 
 	#[test]
 	fn separate_json_data_and_signature() {
@@ -155,4 +238,91 @@ mod tests {
 		let json = br#"{"tcbInfo":{"id":"SGX"},"signature":""#;
 		assert!(SgxQlQveCollateral::separate_json_data_and_signature("tcbInfo", json).is_none());
 	}
+
+	#[test]
+	fn read_from_dir_works() {
+		let temp_dir = TempDir::with_prefix("read_collateral_from_dir_works").unwrap();
+		let dir = temp_dir.path();
+		for filename in [
+			"pck_crl_issuer_chain",
+			"root_ca_crl",
+			"pck_crl",
+			"tcb_info_issuer_chain",
+			"qe_identity_issuer_chain",
+			"qe_identity",
+		] {
+			fs::write(dir.join(filename), filename).unwrap();
+		}
+		fs::write(dir.join("tcb_info"), b"tcb_info\0").unwrap();
+
+		let collateral = SgxQlQveCollateral::read_from_dir(dir).unwrap();
+
+		assert_eq!(collateral.version, COLLATERAL_VERSION);
+		assert_eq!(collateral.pck_crl, b"pck_crl\0".to_vec());
+		assert_eq!(collateral.tcb_info, b"tcb_info\0".to_vec());
+		let c_collateral = collateral.as_c_type();
+		assert_eq!(c_collateral.qe_identity_size, 12);
+	}
+
+	#[test]
+	fn write_to_dir_and_read_from_dir_roundtrip() {
+		let temp_dir = TempDir::with_prefix("write_collateral_to_dir_works").unwrap();
+		let dir = temp_dir.path().join("collateral");
+		let collateral =
+			collateral_with_next_updates("2023-01-17T12:45:32Z", "2023-01-18T12:45:32Z");
+
+		collateral.write_to_dir(&dir).unwrap();
+		let read_collateral = SgxQlQveCollateral::read_from_dir(&dir).unwrap();
+
+		assert_eq!(read_collateral.tcb_info, collateral.tcb_info);
+		assert_eq!(read_collateral.qe_identity, collateral.qe_identity);
+	}
+
+	#[test]
+	fn next_update_is_the_earliest_of_tcb_info_and_qe_identity() {
+		let collateral =
+			collateral_with_next_updates("2023-01-17T12:45:32Z", "2023-01-16T00:00:00Z");
+		assert_eq!(collateral.next_update(), Some(1673827200));
+
+		let collateral =
+			collateral_with_next_updates("2023-01-17T12:45:32Z", "2023-01-18T00:00:00Z");
+		assert_eq!(collateral.next_update(), Some(1673959532));
+	}
+
+	#[test]
+	fn next_update_is_none_for_invalid_collateral() {
+		let mut collateral =
+			collateral_with_next_updates("2023-01-17T12:45:32Z", "2023-01-18T00:00:00Z");
+		collateral.tcb_info = br#"{"tcbInfo":{"id":"SGX"},"signature":"71746f2"}"#.to_vec();
+
+		assert_eq!(collateral.next_update(), None);
+	}
+
+	fn collateral_with_next_updates(tcb_info: &str, qe_identity: &str) -> SgxQlQveCollateral {
+		SgxQlQveCollateral {
+			version: COLLATERAL_VERSION,
+			tee_type: TEE_TYPE_SGX,
+			pck_crl_issuer_chain: b"pck_crl_issuer_chain\0".to_vec(),
+			root_ca_crl: b"root_ca_crl\0".to_vec(),
+			pck_crl: b"pck_crl\0".to_vec(),
+			tcb_info_issuer_chain: b"tcb_info_issuer_chain\0".to_vec(),
+			tcb_info: format!(
+				r#"{{"tcbInfo":{{"id":"SGX","nextUpdate":"{}"}},"signature":"71746f2"}}"#,
+				tcb_info
+			)
+			.into_bytes(),
+			qe_identity_issuer_chain: b"qe_identity_issuer_chain\0".to_vec(),
+			qe_identity: format!(
+				r#"{{"enclaveIdentity":{{"id":"QE","nextUpdate":"{}"}},"signature":"71746f2"}}"#,
+				qe_identity
+			)
+			.into_bytes(),
+		}
+	}
+
+	#[test]
+	fn read_from_dir_fails_for_missing_files() {
+		let temp_dir = TempDir::with_prefix("read_collateral_from_dir_fails").unwrap();
+		assert!(SgxQlQveCollateral::read_from_dir(temp_dir.path()).is_err());
+	}
 }
@@ -0,0 +1,471 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Verification of the DCAP quote, which a peer presents in its mutual RA certificate.
+//!
+//! The quote is parsed and checked inside the enclave: it must be an ECDSA quote of our own
+//! MRENCLAVE, bind the key of the TLS certificate and its QE report must bind the attestation key.
+//! The signatures, the PCK certificate chain, the TCB info and the QE identity are then verified
+//! by the quote verification enclave (QvE) against the collateral, which the untrusted side
+//! supplies either from local files or from the PCCS. The result of the QvE is authenticated
+//! inside the enclave.
+
+use itp_ocall_api::EnclaveAttestationOCallApi;
+use log::*;
+use sgx_types::{sgx_ql_qv_result_t, sgx_status_t, SgxResult};
+use sp_core::hashing::sha2_256;
+use std::vec::Vec;
+
+/// The only quote version, which the QE3 generates.
+pub const QUOTE_VERSION: u16 = 3;
+/// Attestation key type ECDSA-256-with-P-256 curve.
+pub const ATTESTATION_KEY_TYPE_ECDSA_P256: u16 = 2;
+/// Certification data type containing the concatenated PCK certificate chain (PEM).
+pub const CERTIFICATION_DATA_TYPE_PCK_CERT_CHAIN: u16 = 5;
+
+const HEADER_SIZE: usize = 48;
+const REPORT_BODY_SIZE: usize = 384;
+const MR_ENCLAVE_OFFSET: usize = 64;
+const MR_SIGNER_OFFSET: usize = 128;
+const REPORT_DATA_OFFSET: usize = 320;
+const ECDSA_SIGNATURE_SIZE: usize = 64;
+const ECDSA_PUBLIC_KEY_SIZE: usize = 64;
+const SIGNATURE_DATA_OFFSET: usize = HEADER_SIZE + REPORT_BODY_SIZE + 4;
+
+/// The parts of a DCAP quote (version 3), which are checked inside the enclave.
+///
+/// Layout according to "Intel® SGX ECDSA Quote Library API", Appendix A.4.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DcapQuote {
+	pub version: u16,
+	pub attestation_key_type: u16,
+	pub mr_enclave: [u8; 32],
+	pub mr_signer: [u8; 32],
+	pub report_data: [u8; 64],
+	pub attestation_key: [u8; ECDSA_PUBLIC_KEY_SIZE],
+	pub qe_report_data: [u8; 64],
+	pub qe_auth_data: Vec<u8>,
+	pub certification_data_type: u16,
+	pub certification_data: Vec<u8>,
+}
+
+impl DcapQuote {
+	pub fn parse(quote: &[u8]) -> SgxResult<Self> {
+		let mut reader = QuoteReader { quote, offset: 0 };
+
+		let version = reader.read_u16()?;
+		let attestation_key_type = reader.read_u16()?;
+		if version != QUOTE_VERSION || attestation_key_type != ATTESTATION_KEY_TYPE_ECDSA_P256 {
+			error!(
+				"Unsupported quote version {} or attestation key type {}",
+				version, attestation_key_type
+			);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+
+		reader.offset = HEADER_SIZE;
+		let report_body = reader.read(REPORT_BODY_SIZE)?;
+		let signature_data_len = reader.read_u32()? as usize;
+		if quote.len() != SIGNATURE_DATA_OFFSET + signature_data_len {
+			error!(
+				"Quote length {} does not match the signature data length {}",
+				quote.len(),
+				signature_data_len
+			);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+
+		let _isv_report_signature = reader.read(ECDSA_SIGNATURE_SIZE)?;
+		let attestation_key = reader.read(ECDSA_PUBLIC_KEY_SIZE)?;
+		let qe_report_body = reader.read(REPORT_BODY_SIZE)?;
+		let _qe_report_signature = reader.read(ECDSA_SIGNATURE_SIZE)?;
+		let qe_auth_data_len = reader.read_u16()? as usize;
+		let qe_auth_data = reader.read(qe_auth_data_len)?.to_vec();
+		let certification_data_type = reader.read_u16()?;
+		let certification_data_len = reader.read_u32()? as usize;
+		let certification_data = reader.read(certification_data_len)?.to_vec();
+
+		Ok(DcapQuote {
+			version,
+			attestation_key_type,
+			mr_enclave: array_at(report_body, MR_ENCLAVE_OFFSET),
+			mr_signer: array_at(report_body, MR_SIGNER_OFFSET),
+			report_data: array_at(report_body, REPORT_DATA_OFFSET),
+			attestation_key: array_at(attestation_key, 0),
+			qe_report_data: array_at(qe_report_body, REPORT_DATA_OFFSET),
+			qe_auth_data,
+			certification_data_type,
+			certification_data,
+		})
+	}
+
+	/// The QE binds the attestation key (and its authentication data) with its report data:
+	/// SHA256(attestation key || QE authentication data) || 32 zero bytes.
+	pub fn verify_attestation_key_binding(&self) -> SgxResult<()> {
+		let mut data = self.attestation_key.to_vec();
+		data.extend(&self.qe_auth_data);
+		if self.qe_report_data[..32] != sha2_256(&data) || self.qe_report_data[32..] != [0u8; 32] {
+			error!("The QE report does not bind the attestation key of the quote");
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+		if self.certification_data_type != CERTIFICATION_DATA_TYPE_PCK_CERT_CHAIN {
+			error!(
+				"Quote contains certification data of type {}, expected the PCK certificate chain",
+				self.certification_data_type
+			);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+		Ok(())
+	}
+
+	/// The enclave puts the hash of its TLS certificate key into the second half of the report data.
+	pub fn verify_certificate_key_binding(&self, certificate_key: &[u8]) -> SgxResult<()> {
+		if self.report_data[32..] != certificate_key_hash(certificate_key) {
+			error!("The quote does not bind the key of the certificate it has been presented with");
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+		Ok(())
+	}
+}
+
+/// Hash of the public key (x || y, big endian) of a mutual RA certificate, as it is included in
+/// the report data of the DCAP quote.
+pub fn certificate_key_hash(certificate_key: &[u8]) -> [u8; 32] {
+	sha2_256(certificate_key)
+}
+
+/// Our acceptance policy of a quote verification result. The same as for EPID, an outdated
+/// platform results in `SGX_ERROR_UPDATE_NEEDED`, which can be overridden with `outdated_ok`.
+///
+/// A verification against expired collateral is always rejected, because the collateral may not
+/// reflect revocations since then. This can not be overridden with `outdated_ok`.
+pub fn check_quote_verification_result(
+	quote_verification_result: sgx_ql_qv_result_t,
+	collateral_expiration_status: u32,
+) -> SgxResult<()> {
+	if collateral_expiration_status != 0 {
+		error!("The collateral used to verify the quote has expired, refresh it from the PCCS");
+		return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+	}
+	match quote_verification_result {
+		sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK => Ok(()),
+		sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED => {
+			info!(
+				"Quote verification result is SW_HARDENING_NEEDED, which is considered acceptable."
+			);
+			Ok(())
+		},
+		sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_NEEDED
+		| sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE
+		| sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE_CONFIG_NEEDED
+		| sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_AND_SW_HARDENING_NEEDED => {
+			warn!("Quote verification result is {:?}", quote_verification_result);
+			Err(sgx_status_t::SGX_ERROR_UPDATE_NEEDED)
+		},
+		result => {
+			error!("Quote verification failed with terminal result {:?}", result);
+			Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		},
+	}
+}
+
+/// Verifies the DCAP `quote` presented in a mutual RA certificate with public key `certificate_key`.
+pub fn verify_dcap_quote<A>(
+	quote: &[u8],
+	certificate_key: &[u8],
+	attestation_ocall: &A,
+) -> SgxResult<()>
+where
+	A: EnclaveAttestationOCallApi,
+{
+	let dcap_quote = DcapQuote::parse(quote)?;
+
+	let self_mr_enclave = attestation_ocall.get_mrenclave_of_self()?;
+	if dcap_quote.mr_enclave != self_mr_enclave.m {
+		error!(
+			"mr_enclave is not equal to self {:?} != {:?}",
+			dcap_quote.mr_enclave, self_mr_enclave.m
+		);
+		return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+	}
+	dcap_quote.verify_certificate_key_binding(certificate_key)?;
+	dcap_quote.verify_attestation_key_binding()?;
+
+	verify_quote_with_qve(quote, attestation_ocall)
+}
+
+#[cfg(feature = "sgx")]
+fn verify_quote_with_qve<A: EnclaveAttestationOCallApi>(
+	quote: &[u8],
+	attestation_ocall: &A,
+) -> SgxResult<()> {
+	let (quote_verification_result, collateral_expiration_status) =
+		sgx::ecdsa_quote_verification(quote, attestation_ocall)?;
+	check_quote_verification_result(quote_verification_result, collateral_expiration_status)
+}
+
+#[cfg(not(feature = "sgx"))]
+fn verify_quote_with_qve<A: EnclaveAttestationOCallApi>(
+	_quote: &[u8],
+	_attestation_ocall: &A,
+) -> SgxResult<()> {
+	error!("The quote verification enclave can only be used inside an enclave");
+	Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+}
+
+#[cfg(feature = "sgx")]
+pub use sgx::*;
+
+#[cfg(feature = "sgx")]
+pub mod sgx {
+	use super::*;
+	use core::convert::TryInto;
+	use itp_time_utils::now_as_secs;
+	use sgx_types::*;
+
+	/// Verifies the quote with the QvE. Returns the quote verification result and the collateral
+	/// expiration status, after having authenticated them inside the enclave.
+	///
+	/// If the untrusted side has no local collateral, the QvE fetches it from the PCCS.
+	pub fn ecdsa_quote_verification<A: EnclaveAttestationOCallApi>(
+		quote: &[u8],
+		ocall_api: &A,
+	) -> SgxResult<(sgx_ql_qv_result_t, u32)> {
+		let mut app_enclave_target_info: sgx_target_info_t = unsafe { std::mem::zeroed() };
+		let quote_collateral: sgx_ql_qve_collateral_t = unsafe { std::mem::zeroed() };
+		let mut qve_report_info: sgx_ql_qe_report_info_t = unsafe { std::mem::zeroed() };
+		let supplemental_data_size = std::mem::size_of::<sgx_ql_qv_supplemental_t>() as u32;
+
+		// Get target info of the app enclave. QvE will target the generated report to this enclave.
+		let ret_val =
+			unsafe { sgx_self_target(&mut app_enclave_target_info as *mut sgx_target_info_t) };
+		if ret_val != sgx_status_t::SGX_SUCCESS {
+			error!("sgx_self_target returned: {:?}", ret_val);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+
+		// Set current time, which is needed to check against the expiration date of the certificate.
+		let current_time: i64 = now_as_secs().try_into().unwrap_or_else(|e| {
+			panic!("Could not convert SystemTime from u64 into i64: {:?}", e);
+		});
+
+		// Set random nonce.
+		let mut rand_nonce = vec![0u8; 16];
+		let ret_val = unsafe { sgx_read_rand(rand_nonce.as_mut_ptr(), rand_nonce.len()) };
+		if ret_val != sgx_status_t::SGX_SUCCESS {
+			error!("sgx_read_rand returned: {:?}", ret_val);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+		debug!("Retrieved random nonce {:?}", rand_nonce);
+		qve_report_info.nonce.rand.copy_from_slice(rand_nonce.as_slice());
+		qve_report_info.app_enclave_target_info = app_enclave_target_info;
+
+		// Ocall to call Quote verification Enclave (QvE), which verifies the generated quote.
+		let (
+			collateral_expiration_status,
+			quote_verification_result,
+			qve_report_info_return_value,
+			supplemental_data,
+		) = ocall_api.get_qve_report_on_quote(
+			quote.to_vec(),
+			current_time,
+			quote_collateral,
+			qve_report_info,
+			supplemental_data_size,
+		)?;
+
+		// Check nonce of qve report to protect against replay attacks, as the qve report
+		// is coming from the untrusted side.
+		if qve_report_info_return_value.nonce.rand != qve_report_info.nonce.rand {
+			error!(
+				"Nonce of input value and return value are not matching. Input: {:?}, Output: {:?}",
+				qve_report_info.nonce.rand, qve_report_info_return_value.nonce.rand
+			);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+
+		// Set the threshold of QvE ISV SVN. The ISV SVN of QvE used to verify quote must be greater or equal to this threshold
+		// e.g. You can check latest QvE ISVSVN from QvE configuration file on Github
+		// https://github.com/intel/SGXDataCenterAttestationPrimitives/blob/master/QuoteVerification/QvE/Enclave/linux/config.xml#L4
+		// or you can get latest QvE ISVSVN in QvE Identity JSON file from
+		// https://api.trustedservices.intel.com/sgx/certification/v3/qve/identity
+		// Make sure you are using trusted & latest QvE ISV SVN as threshold
+		// Warning: The function may return erroneous result if QvE ISV SVN has been modified maliciously.
+		let qve_isvsvn_threshold: sgx_isv_svn_t = 6;
+
+		// Verify the qve report to validate that it is coming from a legit quoting verification enclave
+		// and has not been tampered with. This also authenticates the quote verification result,
+		// the collateral expiration status and the supplemental data.
+		let ret_val = unsafe {
+			sgx_tvl_verify_qve_report_and_identity(
+				quote.as_ptr(),
+				quote.len() as u32,
+				&qve_report_info_return_value as *const sgx_ql_qe_report_info_t,
+				current_time,
+				collateral_expiration_status,
+				quote_verification_result,
+				supplemental_data.as_ptr(),
+				supplemental_data_size,
+				qve_isvsvn_threshold,
+			)
+		};
+
+		if ret_val != sgx_quote3_error_t::SGX_QL_SUCCESS {
+			error!("sgx_tvl_verify_qve_report_and_identity returned: {:?}", ret_val);
+			return Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		}
+
+		Ok((quote_verification_result, collateral_expiration_status))
+	}
+}
+
+struct QuoteReader<'a> {
+	quote: &'a [u8],
+	offset: usize,
+}
+
+impl<'a> QuoteReader<'a> {
+	fn read(&mut self, len: usize) -> SgxResult<&'a [u8]> {
+		let end = self.offset.checked_add(len).filter(|end| *end <= self.quote.len()).ok_or_else(
+			|| {
+				error!("Quote is too short, expected at least {} bytes", self.offset + len);
+				sgx_status_t::SGX_ERROR_UNEXPECTED
+			},
+		)?;
+		let bytes = &self.quote[self.offset..end];
+		self.offset = end;
+		Ok(bytes)
+	}
+
+	fn read_u16(&mut self) -> SgxResult<u16> {
+		Ok(u16::from_le_bytes(array_at(self.read(2)?, 0)))
+	}
+
+	fn read_u32(&mut self) -> SgxResult<u32> {
+		Ok(u32::from_le_bytes(array_at(self.read(4)?, 0)))
+	}
+}
+
+/// Copies `N` bytes at `offset`. The caller ensures that `bytes` is long enough.
+fn array_at<const N: usize>(bytes: &[u8], offset: usize) -> [u8; N] {
+	let mut array = [0u8; N];
+	array.copy_from_slice(&bytes[offset..offset + N]);
+	array
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Quote in the layout of the QE3, generated with test keys. The report data contains an
+	/// ed25519 signer and the hash of `TEST_CERTIFICATE_KEY`.
+	const TEST_QUOTE: &[u8] = include_bytes!("fixtures/dcap_quote_v3.bin");
+	const TEST_CERTIFICATE_KEY: &str = "eadd3a39a6b98d2e3545ecbc88ce805abd44d2da1eb95860006579b10b227cd7e1e6288dedfe96dacae538b38527ba509c7bde059a48f2e3072d8427c339cc02";
+
+	fn certificate_key() -> Vec<u8> {
+		hex::decode(TEST_CERTIFICATE_KEY).unwrap()
+	}
+
+	#[test]
+	fn parse_quote_works() {
+		let quote = DcapQuote::parse(TEST_QUOTE).unwrap();
+
+		assert_eq!(quote.version, QUOTE_VERSION);
+		assert_eq!(quote.attestation_key_type, ATTESTATION_KEY_TYPE_ECDSA_P256);
+		assert_eq!(quote.mr_enclave, [0x11; 32]);
+		assert_eq!(quote.mr_signer, [0x22; 32]);
+		assert_eq!(quote.qe_auth_data, (0u8..32).collect::<Vec<_>>());
+		assert_eq!(quote.certification_data_type, CERTIFICATION_DATA_TYPE_PCK_CERT_CHAIN);
+		assert!(quote.certification_data.starts_with(b"-----BEGIN CERTIFICATE-----"));
+	}
+
+	#[test]
+	fn parse_quote_fails_for_truncated_quote() {
+		assert!(DcapQuote::parse(&TEST_QUOTE[..TEST_QUOTE.len() - 1]).is_err());
+		assert!(DcapQuote::parse(&TEST_QUOTE[..HEADER_SIZE + 10]).is_err());
+		assert!(DcapQuote::parse(&[]).is_err());
+	}
+
+	#[test]
+	fn parse_quote_fails_for_other_version() {
+		let mut quote = TEST_QUOTE.to_vec();
+		quote[0] = 4;
+		assert!(DcapQuote::parse(&quote).is_err());
+	}
+
+	#[test]
+	fn attestation_key_binding_is_verified() {
+		let quote = DcapQuote::parse(TEST_QUOTE).unwrap();
+		assert!(quote.verify_attestation_key_binding().is_ok());
+
+		let mut tampered = quote.clone();
+		tampered.attestation_key[0] ^= 1;
+		assert!(tampered.verify_attestation_key_binding().is_err());
+
+		let mut tampered = quote;
+		tampered.qe_auth_data[0] ^= 1;
+		assert!(tampered.verify_attestation_key_binding().is_err());
+	}
+
+	#[test]
+	fn certificate_key_binding_is_verified() {
+		let quote = DcapQuote::parse(TEST_QUOTE).unwrap();
+		let mut other_key = certificate_key();
+		other_key[0] ^= 1;
+
+		assert!(quote.verify_certificate_key_binding(&certificate_key()).is_ok());
+		assert!(quote.verify_certificate_key_binding(&other_key).is_err());
+	}
+
+	#[test]
+	fn quote_verification_result_policy_works() {
+		use sgx_ql_qv_result_t::*;
+
+		assert!(check_quote_verification_result(SGX_QL_QV_RESULT_OK, 0).is_ok());
+		assert!(check_quote_verification_result(SGX_QL_QV_RESULT_SW_HARDENING_NEEDED, 0).is_ok());
+		assert_eq!(
+			check_quote_verification_result(SGX_QL_QV_RESULT_OUT_OF_DATE, 0),
+			Err(sgx_status_t::SGX_ERROR_UPDATE_NEEDED)
+		);
+		assert_eq!(
+			check_quote_verification_result(SGX_QL_QV_RESULT_REVOKED, 0),
+			Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		);
+		assert_eq!(
+			check_quote_verification_result(SGX_QL_QV_RESULT_INVALID_SIGNATURE, 0),
+			Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		);
+	}
+
+	#[test]
+	fn quote_verification_result_is_rejected_for_expired_collateral() {
+		use sgx_ql_qv_result_t::*;
+
+		assert_eq!(
+			check_quote_verification_result(SGX_QL_QV_RESULT_OK, 1),
+			Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		);
+		assert_eq!(
+			check_quote_verification_result(SGX_QL_QV_RESULT_SW_HARDENING_NEEDED, 1),
+			Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		);
+		assert_eq!(
+			check_quote_verification_result(SGX_QL_QV_RESULT_OUT_OF_DATE, 1),
+			Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+		);
+	}
+}
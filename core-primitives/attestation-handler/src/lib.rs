@@ -43,6 +43,8 @@ pub mod collateral;
 
 pub mod cert;
 
+pub mod dcap;
+
 pub mod error;
 
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
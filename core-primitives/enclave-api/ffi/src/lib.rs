@@ -28,6 +28,7 @@ extern "C" {
 		untrusted_worker_addr_size: u32,
 		encoded_base_dir_str: *const u8,
 		encoded_base_dir_size: u32,
+		state_migration_dry_run: c_int,
 	) -> sgx_status_t;
 
 	pub fn init_enclave_sidechain_components(
@@ -35,11 +36,15 @@ extern "C" {
 		retval: *mut sgx_status_t,
 	) -> sgx_status_t;
 
+	pub fn enable_tracing(eid: sgx_enclave_id_t, retval: *mut sgx_status_t) -> sgx_status_t;
+
 	pub fn init_direct_invocation_server(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
 		server_addr: *const u8,
 		server_addr_size: u32,
+		limits: *const u8,
+		limits_size: u32,
 	) -> sgx_status_t;
 
 	pub fn init_parentchain_components(
@@ -58,6 +63,46 @@ extern "C" {
 		shard_size: u32,
 	) -> sgx_status_t;
 
+	pub fn load_shard(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+	) -> sgx_status_t;
+
+	pub fn unload_shard(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+	) -> sgx_status_t;
+
+	pub fn get_pending_trusted_operations(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		operations: *mut u8,
+		operations_max_size: u32,
+		operations_size: *mut u32,
+	) -> sgx_status_t;
+
+	pub fn create_state_snapshot(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		state_hash: *mut u8,
+		state_hash_size: u32,
+	) -> sgx_status_t;
+
+	pub fn rotate_shielding_key(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		pubkey: *mut u8,
+		pubkey_size: u32,
+	) -> sgx_status_t;
+
 	pub fn init_proxied_shard_vault(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
@@ -89,7 +134,11 @@ extern "C" {
 		creation_size: u32,
 	) -> sgx_status_t;
 
-	pub fn execute_trusted_calls(eid: sgx_enclave_id_t, retval: *mut sgx_status_t) -> sgx_status_t;
+	pub fn execute_trusted_calls(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		produce_blocks: c_int,
+	) -> sgx_status_t;
 
 	pub fn sync_parentchain(
 		eid: sgx_enclave_id_t,
@@ -277,6 +326,7 @@ extern "C" {
 		shard: *const u8,
 		shard_size: u32,
 		skip_ra: c_int,
+		shielding_key_only: c_int,
 	) -> sgx_status_t;
 
 }
@@ -0,0 +1,1357 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Implementation of the enclave API that runs in the untrusted process, without SGX.
+//!
+//! The `MockEnclave` uses the same STF and state handling components as the enclave, but keeps
+//! its keys unsealed on disk and its states in memory. Remote attestation is faked: the enclave
+//! can only be registered on the parentchain with `skip-ra`, and its fingerprint is derived
+//! from the signing key instead of the MRENCLAVE.
+//!
+//! The mock only supports the offchain-worker mode. Trusted operations submitted to the direct
+//! invocation server enter the same TOP pool as in the enclave. Like in the enclave's offchain
+//! worker, they are executed by the STF executor whenever parentchain blocks have been synced,
+//! and the resulting parentchain calls are sent with the worker's `send_to_parentchain` OCALL.
+//! There is no sidechain, neither block production nor the import of peer blocks is supported.
+//!
+//! Parentchain blocks are only checked to extend the last synced block, there is no light client
+//! verifying their finality. Indirect calls (e.g. shielding funds) are NOT imported from them.
+//!
+//! This allows running the worker on machines without SGX hardware or SDK, e.g. for
+//! integration tests. It provides NO confidentiality or integrity guarantees whatsoever.
+
+use crate::rpc::{
+	public_api_rpc_handler, sidechain_io_handler, MockRpcConnectionRegistry,
+	MockRpcResponseChannel, MockRpcSubscriptionRegistry, MockRpcWsHandler,
+};
+use codec::{Decode, Encode};
+use core::fmt::Debug;
+use ita_sgx_runtime::Runtime;
+use ita_stf::{Getter, State as StfState, StateType as StfStateType, Stf, TrustedCallSigned};
+use itc_direct_rpc_server::{create_determine_watch, rpc_responder::RpcResponder};
+use itc_parentchain::primitives::{
+	ParentchainInitParams,
+	ParentchainInitParams::{Parachain, Solochain},
+};
+use itc_tls_websocket_server::{
+	certificate_generation::ed25519_self_signed_certificate,
+	create_ws_server,
+	limits::{ObserveRejections, WebSocketRejection},
+	WebSocketServer,
+};
+use itp_attestation_handler::SgxQlQveCollateral;
+use itp_enclave_api::{
+	admin_api::AdminApi,
+	direct_request::DirectRequest,
+	enclave_base::EnclaveBase,
+	error::Error,
+	remote_attestation::{
+		QveReport, RemoteAttestation, RemoteAttestationCallBacks, TlsRemoteAttestation,
+	},
+	sidechain::Sidechain,
+	teeracle_api::TeeracleApi,
+	EnclaveResult,
+};
+use itp_extrinsics_factory::{CreateExtrinsics, ExtrinsicsFactory};
+use itp_node_api::{
+	api_client::{PairSignature, StaticExtrinsicSigner},
+	metadata::{
+		pallet_teerex::TeerexCallIndexes,
+		provider::{AccessNodeMetadata, NodeMetadataRepository},
+		NodeMetadata,
+	},
+};
+use itp_nonce_cache::{MutateNonce, Nonce, NonceCache};
+use itp_ocall_api::{
+	EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi,
+	Error as OCallApiError, Result as OCallApiResult,
+};
+use itp_settings::enclave::SHIELDING_KEY_ROTATION_GRACE_PERIOD;
+use itp_sgx_crypto::{
+	key_repository::{AccessKey, FallbackKeyRepository, MutateKey, RotatingKeyRepository},
+	mocks::KeyRepositoryMock,
+};
+use itp_stf_executor::{
+	executor::StfExecutor,
+	getter_executor::GetterExecutor,
+	state_getter::StfStateGetter,
+	traits::{StateUpdateProposer, StfUpdateState},
+	ExecutedOperation,
+};
+use itp_stf_interface::{
+	parentchain_pallet::ParentchainPalletInstancesInterface,
+	system_pallet::SystemPalletEventInterface, ShardCreationInfo, ShardCreationQuery,
+	ShardVaultQuery,
+};
+use itp_stf_primitives::types::{Hash, TrustedOperation, TrustedOperationOrHash};
+use itp_stf_state_handler::{
+	handle_state::HandleState,
+	in_memory_state_file_io::{create_sgx_externalities_in_memory_state_io, InMemoryStateFileIo},
+	query_shard_state::QueryShardState,
+	state_initializer::StateInitializer,
+	state_migrator::StateMigrator,
+	state_snapshot_repository::StateSnapshotRepository,
+	StateHandler,
+};
+use itp_stf_state_observer::state_observer::StateObserver;
+use itp_storage::StorageProof;
+use itp_top_pool::{basic_pool::BasicPool, pool::Options as PoolOptions};
+use itp_top_pool_author::{
+	api::SidechainApi, author::Author, top_filter::CallsOnlyFilter, traits::AuthorApi,
+};
+use itp_types::{
+	parentchain::{Balance, Header, ParentchainCall, ParentchainId},
+	storage::StorageEntryVerified,
+	Block as ParentchainBlock, OpaqueCall, ShardIdentifier, SignedBlock as SignedParentchainBlock,
+	TrustedRpcServerLimits, WorkerRequest, WorkerResponse, H256,
+};
+use log::*;
+use sgx_crypto_helper::{
+	rsa3072::{Rsa3072KeyPair, Rsa3072PubKey},
+	RsaKeyPair,
+};
+use sgx_types::*;
+use sp_core::{
+	blake2_256,
+	crypto::{DeriveJunction, Pair},
+	ed25519,
+};
+use sp_runtime::{generic::SignedBlock, traits::Header as HeaderT, OpaqueExtrinsic};
+use std::{
+	fs,
+	os::raw::c_int,
+	path::{Path, PathBuf},
+	sync::{Arc, RwLock},
+	time::Duration,
+};
+use teerex_primitives::{EnclaveFingerprint, Fmspc, SgxAttestationMethod};
+
+/// File in the base directory holding the (unsealed!) seed of the enclave signing key.
+pub const MOCK_SIGNING_KEY_SEED_FILE: &str = "mock_enclave_signing_key_seed.bin";
+/// File in the base directory holding the (unsealed!) enclave shielding key pair.
+pub const MOCK_SHIELDING_KEY_FILE: &str = "mock_enclave_shielding_key.json";
+
+mod rpc;
+
+const STATE_SNAPSHOT_CACHE_SIZE: usize = 3;
+/// Same limit as the enclave's offchain-worker executor uses per shard.
+const MAX_TRUSTED_CALLS_EXECUTION_DURATION: Duration = Duration::from_secs(5);
+
+pub type MockEnclaveStf = Stf<TrustedCallSigned, Getter, StfState, Runtime>;
+type MockShieldingKeyRepository =
+	RotatingKeyRepository<Rsa3072KeyPair, KeyRepositoryMock<Rsa3072KeyPair>>;
+type MockShieldingKeyWithFallbackRepository =
+	FallbackKeyRepository<Rsa3072KeyPair, KeyRepositoryMock<Rsa3072KeyPair>>;
+type MockStateFileIo = InMemoryStateFileIo<StfStateType, StfState>;
+type MockStateHandler = StateHandler<
+	StateSnapshotRepository<MockStateFileIo>,
+	StateObserver<StfState>,
+	StateInitializer<StfState, MockEnclaveStf, MockShieldingKeyRepository>,
+	StateMigrator<StfState, MockEnclaveStf>,
+>;
+type MockTopPool = BasicPool<
+	SidechainApi<ParentchainBlock, TrustedCallSigned>,
+	ParentchainBlock,
+	RpcResponder<MockRpcConnectionRegistry, Hash, MockRpcResponseChannel>,
+	TrustedOperation<TrustedCallSigned, Getter>,
+>;
+type MockTopPoolAuthor = Author<
+	MockTopPool,
+	CallsOnlyFilter<TrustedCallSigned, Getter>,
+	MockStateHandler,
+	MockShieldingKeyWithFallbackRepository,
+	MockOCallApi,
+	TrustedCallSigned,
+	Getter,
+>;
+type MockStfExecutor = StfExecutor<
+	MockOCallApi,
+	MockStateHandler,
+	NodeMetadataRepository<NodeMetadata>,
+	MockEnclaveStf,
+	TrustedCallSigned,
+	Getter,
+>;
+type MockExtrinsicsFactory = ExtrinsicsFactory<
+	StaticExtrinsicSigner<ed25519::Pair, PairSignature>,
+	NonceCache,
+	NodeMetadataRepository<NodeMetadata>,
+>;
+
+/// The untrusted side of the `send_to_parentchain` OCALL, provided by the worker.
+pub trait SendToParentchain: Send + Sync {
+	fn send_to_parentchain(
+		&self,
+		extrinsics: Vec<OpaqueExtrinsic>,
+		parentchain_id: &ParentchainId,
+		await_each_inclusion: bool,
+	) -> EnclaveResult<()>;
+}
+
+/// Takes the place of the OCALLs the in-process enclave components make.
+///
+/// There is no enclave boundary to cross: extrinsics are handed to the worker directly, metrics
+/// and rejections are only logged. Without a light client, parentchain storage can't be read
+/// verified, neither can quotes be generated.
+#[derive(Clone)]
+pub struct MockOCallApi {
+	fingerprint: EnclaveFingerprint,
+	parentchain_sender: Arc<dyn SendToParentchain>,
+}
+
+impl EnclaveAttestationOCallApi for MockOCallApi {
+	fn sgx_init_quote(&self) -> SgxResult<(sgx_target_info_t, sgx_epid_group_id_t)> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_ias_socket(&self) -> SgxResult<i32> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_quote(
+		&self,
+		_sig_rl: Vec<u8>,
+		_report: sgx_report_t,
+		_sign_type: sgx_quote_sign_type_t,
+		_spid: sgx_spid_t,
+		_quote_nonce: sgx_quote_nonce_t,
+	) -> SgxResult<(sgx_report_t, Vec<u8>)> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_dcap_quote(&self, _report: sgx_report_t, _quote_size: u32) -> SgxResult<Vec<u8>> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_qve_report_on_quote(
+		&self,
+		_quote: Vec<u8>,
+		_current_time: i64,
+		_quote_collateral: sgx_ql_qve_collateral_t,
+		_qve_report_info: sgx_ql_qe_report_info_t,
+		_supplemental_data_size: u32,
+	) -> SgxResult<(u32, sgx_ql_qv_result_t, sgx_ql_qe_report_info_t, Vec<u8>)> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_update_info(
+		&self,
+		_platform_info: sgx_platform_info_t,
+		_enclave_trusted: i32,
+	) -> SgxResult<sgx_update_info_bit_t> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	/// Trusted calls are signed for the fingerprint, which takes the place of the MRENCLAVE.
+	fn get_mrenclave_of_self(&self) -> SgxResult<sgx_measurement_t> {
+		Ok(sgx_measurement_t { m: self.fingerprint.0 })
+	}
+}
+
+impl EnclaveOnChainOCallApi for MockOCallApi {
+	fn send_to_parentchain(
+		&self,
+		extrinsics: Vec<OpaqueExtrinsic>,
+		parentchain_id: &ParentchainId,
+		await_each_inclusion: bool,
+	) -> SgxResult<()> {
+		self.parentchain_sender
+			.send_to_parentchain(extrinsics, parentchain_id, await_each_inclusion)
+			.map_err(|e| {
+				error!("[MockEnclave] Failed to send extrinsics to {:?}: {:?}", parentchain_id, e);
+				sgx_status_t::SGX_ERROR_UNEXPECTED
+			})
+	}
+
+	fn worker_request<V: Encode + Decode>(
+		&self,
+		_req: Vec<WorkerRequest>,
+		_parentchain_id: &ParentchainId,
+	) -> SgxResult<Vec<WorkerResponse<V>>> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_storage_verified<H: HeaderT<Hash = H256>, V: Decode>(
+		&self,
+		_storage_hash: Vec<u8>,
+		_header: &H,
+		_parentchain_id: &ParentchainId,
+	) -> OCallApiResult<StorageEntryVerified<V>> {
+		Err(OCallApiError::Sgx(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED))
+	}
+
+	fn get_multiple_storages_verified<H: HeaderT<Hash = H256>, V: Decode>(
+		&self,
+		storage_hashes: Vec<Vec<u8>>,
+		_header: &H,
+		_parentchain_id: &ParentchainId,
+	) -> OCallApiResult<Vec<StorageEntryVerified<V>>> {
+		// The STF does not request any parentchain storage on block import.
+		if storage_hashes.is_empty() {
+			return Ok(Vec::new())
+		}
+		Err(OCallApiError::Sgx(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED))
+	}
+}
+
+impl EnclaveMetricsOCallApi for MockOCallApi {
+	fn update_metric<Metric: Encode>(&self, _metric: Metric) -> SgxResult<()> {
+		Ok(())
+	}
+}
+
+impl ObserveRejections for MockOCallApi {
+	fn on_rejection(&self, rejection: WebSocketRejection) {
+		debug!("[MockEnclave] Direct invocation server rejected: {}", rejection.as_str());
+	}
+}
+
+/// Parentchain related components the enclave keeps per parentchain.
+#[derive(Default)]
+struct MockParentchain {
+	genesis_hash: RwLock<Option<H256>>,
+	last_synced_header: RwLock<Option<Header>>,
+	nonce_cache: Arc<NonceCache>,
+	node_metadata_repository: Arc<NodeMetadataRepository<NodeMetadata>>,
+}
+
+/// Enclave API implementation that runs entirely in the untrusted process.
+#[derive(Clone)]
+pub struct MockEnclave {
+	base_dir: PathBuf,
+	signer: ed25519::Pair,
+	shielding_key_repository: Arc<MockShieldingKeyRepository>,
+	state_handler: Arc<MockStateHandler>,
+	top_pool_author: Arc<MockTopPoolAuthor>,
+	stf_executor: Arc<MockStfExecutor>,
+	rpc_handler: Arc<MockRpcWsHandler>,
+	rpc_response_channel: Arc<MockRpcResponseChannel>,
+	ocall_api: Arc<MockOCallApi>,
+	integritee_parentchain: Arc<MockParentchain>,
+	target_a_parentchain: Arc<MockParentchain>,
+	target_b_parentchain: Arc<MockParentchain>,
+}
+
+impl MockEnclave {
+	/// Creates a new mock enclave, loading its keys from `base_dir`, or generating them
+	/// if they don't exist yet.
+	///
+	/// States are kept in memory only, they do not survive a restart. Extrinsics the enclave
+	/// wants to send to a parentchain are handed to `parentchain_sender`.
+	pub fn new(
+		base_dir: &Path,
+		parentchain_sender: Arc<dyn SendToParentchain>,
+	) -> EnclaveResult<Self> {
+		let signer = load_or_create_signer(base_dir)?;
+		let shielding_key = load_or_create_shielding_key(base_dir)?;
+		let shielding_key_repository = Arc::new(MockShieldingKeyRepository::new(
+			Arc::new(KeyRepositoryMock::new(shielding_key)),
+			SHIELDING_KEY_ROTATION_GRACE_PERIOD,
+		));
+
+		let state_snapshot_repository = StateSnapshotRepository::empty(
+			create_sgx_externalities_in_memory_state_io(),
+			STATE_SNAPSHOT_CACHE_SIZE,
+		)
+		.map_err(|e| Error::Other(e.into()))?;
+		let state_observer = Arc::new(StateObserver::default());
+		let state_handler = Arc::new(MockStateHandler::new(
+			state_snapshot_repository,
+			state_observer.clone(),
+			Arc::new(StateInitializer::new(shielding_key_repository.clone())),
+			Arc::new(StateMigrator::new(false)),
+		));
+
+		let ocall_api =
+			Arc::new(MockOCallApi { fingerprint: fingerprint(&signer), parentchain_sender });
+		let integritee_parentchain = Arc::new(MockParentchain::default());
+		let stf_executor = Arc::new(MockStfExecutor::new(
+			ocall_api.clone(),
+			state_handler.clone(),
+			integritee_parentchain.node_metadata_repository.clone(),
+		));
+		let connection_registry = Arc::new(MockRpcConnectionRegistry::new());
+		let rpc_response_channel = Arc::new(MockRpcResponseChannel::default());
+		let rpc_responder =
+			Arc::new(RpcResponder::new(connection_registry.clone(), rpc_response_channel.clone()));
+		let top_pool = Arc::new(MockTopPool::create(
+			PoolOptions::default(),
+			Arc::new(SidechainApi::new()),
+			rpc_responder,
+		));
+		let top_pool_author = Arc::new(MockTopPoolAuthor::new(
+			top_pool,
+			CallsOnlyFilter::new(),
+			state_handler.clone(),
+			Arc::new(MockShieldingKeyWithFallbackRepository::new(shielding_key_repository.clone())),
+			ocall_api.clone(),
+		));
+
+		let io_handler = public_api_rpc_handler(
+			top_pool_author.clone(),
+			Arc::new(GetterExecutor::<_, StfStateGetter<MockEnclaveStf>, Getter>::new(
+				state_observer,
+			)),
+			shielding_key_repository.clone(),
+		);
+		let rpc_handler = Arc::new(MockRpcWsHandler::new(
+			io_handler,
+			Arc::new(create_determine_watch::<Hash>()),
+			connection_registry,
+			Arc::new(MockRpcSubscriptionRegistry::new()),
+		));
+
+		Ok(MockEnclave {
+			base_dir: base_dir.to_path_buf(),
+			signer,
+			shielding_key_repository,
+			state_handler,
+			top_pool_author,
+			stf_executor,
+			rpc_handler,
+			rpc_response_channel,
+			ocall_api,
+			integritee_parentchain,
+			target_a_parentchain: Default::default(),
+			target_b_parentchain: Default::default(),
+		})
+	}
+
+	fn parentchain(&self, parentchain_id: &ParentchainId) -> &MockParentchain {
+		match parentchain_id {
+			ParentchainId::Integritee => &self.integritee_parentchain,
+			ParentchainId::TargetA => &self.target_a_parentchain,
+			ParentchainId::TargetB => &self.target_b_parentchain,
+		}
+	}
+
+	fn ensure_shard_exists(&self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+		if !self.state_handler.shard_exists(shard).map_err(|e| Error::Other(e.into()))? {
+			return Err(Error::Other("shard not initialized".into()))
+		}
+		Ok(())
+	}
+
+	fn extrinsics_factory(
+		&self,
+		parentchain_id: &ParentchainId,
+	) -> EnclaveResult<MockExtrinsicsFactory> {
+		let parentchain = self.parentchain(parentchain_id);
+		let genesis_hash = parentchain
+			.genesis_hash
+			.read()
+			.expect("Lock poisoning")
+			.ok_or_else(|| Error::Other("parentchain components are not initialized".into()))?;
+		Ok(MockExtrinsicsFactory::new(
+			genesis_hash,
+			StaticExtrinsicSigner::new(self.signer.clone()),
+			parentchain.nonce_cache.clone(),
+			parentchain.node_metadata_repository.clone(),
+		))
+	}
+
+	/// Executes the trusted calls pending in the TOP pool of every shard with the STF executor,
+	/// like the enclave's offchain-worker executor does after a parentchain block import.
+	fn execute_pending_trusted_calls(
+		&self,
+		latest_parentchain_header: &Header,
+	) -> EnclaveResult<()> {
+		let mut parentchain_effects = Vec::<ParentchainCall>::new();
+		let shards = self.state_handler.list_shards().map_err(|e| Error::Other(e.into()))?;
+		for shard in shards {
+			let trusted_calls = self.top_pool_author.get_pending_trusted_calls(shard);
+			if trusted_calls.is_empty() {
+				continue
+			}
+			let batch_execution_result = self
+				.stf_executor
+				.propose_state_update(
+					&trusted_calls,
+					latest_parentchain_header,
+					&shard,
+					MAX_TRUSTED_CALLS_EXECUTION_DURATION,
+					|mut state| {
+						MockEnclaveStf::reset_events(&mut state);
+						state
+					},
+				)
+				.map_err(|e| Error::Other(e.into()))?;
+
+			parentchain_effects.append(&mut batch_execution_result.get_extrinsic_callbacks());
+			let failed_operations = batch_execution_result.get_failed_operations();
+			let successful_operations: Vec<ExecutedOperation<TrustedCallSigned, Getter>> =
+				batch_execution_result
+					.get_executed_operation_hashes()
+					.into_iter()
+					.map(|h| {
+						ExecutedOperation::success(h, TrustedOperationOrHash::Hash(h), Vec::new())
+					})
+					.collect();
+			info!(
+				"[MockEnclave] Executed {} of {} trusted calls on shard {:?}",
+				successful_operations.len(),
+				trusted_calls.len(),
+				shard
+			);
+
+			self.remove_calls_from_pool(&shard, failed_operations);
+			self.state_handler
+				.reset(batch_execution_result.state_after_execution, &shard)
+				.map_err(|e| Error::Other(e.into()))?;
+			self.remove_calls_from_pool(&shard, successful_operations);
+		}
+
+		self.send_parentchain_effects(&parentchain_effects)
+	}
+
+	fn remove_calls_from_pool(
+		&self,
+		shard: &ShardIdentifier,
+		executed_calls: Vec<ExecutedOperation<TrustedCallSigned, Getter>>,
+	) {
+		let executed_calls = executed_calls
+			.into_iter()
+			.map(|e| {
+				let is_success = e.is_success();
+				(e.trusted_operation_or_hash, is_success)
+			})
+			.collect();
+		let failed_to_remove = self.top_pool_author.remove_calls_from_pool(*shard, executed_calls);
+		if !failed_to_remove.is_empty() {
+			warn!(
+				"[MockEnclave] Failed to remove {} trusted calls from the pool",
+				failed_to_remove.len()
+			);
+		}
+	}
+
+	/// Sends the parentchain calls resulting from trusted calls to their parentchains.
+	fn send_parentchain_effects(
+		&self,
+		parentchain_effects: &[ParentchainCall],
+	) -> EnclaveResult<()> {
+		let calls_per_parentchain: [(ParentchainId, Vec<OpaqueCall>); 3] = [
+			(
+				ParentchainId::Integritee,
+				parentchain_effects.iter().filter_map(|c| c.as_integritee()).collect(),
+			),
+			(
+				ParentchainId::TargetA,
+				parentchain_effects.iter().filter_map(|c| c.as_target_a()).collect(),
+			),
+			(
+				ParentchainId::TargetB,
+				parentchain_effects.iter().filter_map(|c| c.as_target_b()).collect(),
+			),
+		];
+		for (parentchain_id, calls) in calls_per_parentchain {
+			if calls.is_empty() {
+				continue
+			}
+			debug!("[MockEnclave] Sending {} calls to {:?}", calls.len(), parentchain_id);
+			let extrinsics = self
+				.extrinsics_factory(&parentchain_id)?
+				.create_extrinsics(&calls, None)
+				.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+			self.ocall_api
+				.send_to_parentchain(extrinsics, &parentchain_id, false)
+				.map_err(Error::Sgx)?;
+		}
+		Ok(())
+	}
+
+	/// Composes the extrinsic registering this enclave on the integritee parentchain.
+	///
+	/// Like the real enclave in `skip-ra` mode, the fingerprint is used as attestation proof.
+	fn register_enclave_xt(&self, url: &str, skip_ra: bool) -> EnclaveResult<Vec<u8>> {
+		if !skip_ra {
+			return Err(Error::Other("the mock enclave can only be registered with skip-ra".into()))
+		}
+		let extrinsics_factory = self.extrinsics_factory(&ParentchainId::Integritee)?;
+		let call_ids = extrinsics_factory
+			.node_metadata_repository
+			.get_from_metadata(|m| m.register_sgx_enclave_call_indexes())
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		let call = OpaqueCall::from_tuple(&(
+			call_ids,
+			self.get_fingerprint()?.encode(),
+			Some(url.to_string()),
+			SgxAttestationMethod::Skip { proxied: false },
+		));
+		let extrinsics = extrinsics_factory
+			.create_extrinsics(&[call], None)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		Ok(extrinsics[0].encode())
+	}
+}
+
+fn load_or_create_signer(base_dir: &Path) -> EnclaveResult<ed25519::Pair> {
+	let seed_file = base_dir.join(MOCK_SIGNING_KEY_SEED_FILE);
+	if seed_file.exists() {
+		let seed = fs::read(&seed_file).map_err(|e| Error::Other(e.into()))?;
+		return ed25519::Pair::from_seed_slice(&seed)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))
+	}
+	info!("[MockEnclave] Generating new signing key: {}", seed_file.display());
+	let (signer, seed) = ed25519::Pair::generate();
+	write_key_file(&seed_file, &seed)?;
+	Ok(signer)
+}
+
+fn load_or_create_shielding_key(base_dir: &Path) -> EnclaveResult<Rsa3072KeyPair> {
+	let key_file = base_dir.join(MOCK_SHIELDING_KEY_FILE);
+	if key_file.exists() {
+		let key_json = fs::read(&key_file).map_err(|e| Error::Other(e.into()))?;
+		return serde_json::from_slice(&key_json).map_err(|e| Error::Other(e.into()))
+	}
+	info!("[MockEnclave] Generating new shielding key: {}", key_file.display());
+	create_shielding_key(base_dir)
+}
+
+fn create_shielding_key(base_dir: &Path) -> EnclaveResult<Rsa3072KeyPair> {
+	let shielding_key = Rsa3072KeyPair::new().map_err(Error::Sgx)?;
+	let key_json = serde_json::to_vec(&shielding_key).map_err(|e| Error::Other(e.into()))?;
+	write_key_file(&base_dir.join(MOCK_SHIELDING_KEY_FILE), &key_json)?;
+	Ok(shielding_key)
+}
+
+fn write_key_file(path: &Path, key: &[u8]) -> EnclaveResult<()> {
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(|e| Error::Other(e.into()))?;
+	}
+	fs::write(path, key).map_err(|e| Error::Other(e.into()))
+}
+
+fn fingerprint(signer: &ed25519::Pair) -> EnclaveFingerprint {
+	blake2_256(signer.public().as_ref()).into()
+}
+
+/// Parentchain blocks are synced one after the other, each has to extend the previous one.
+fn ensure_extends(header: &Header, last_synced_header: &Header) -> EnclaveResult<()> {
+	if header.parent_hash != last_synced_header.hash()
+		|| header.number != last_synced_header.number + 1
+	{
+		return Err(Error::Other(
+			format!(
+				"parentchain block {} does not extend the last synced block {}",
+				header.number, last_synced_header.number
+			)
+			.into(),
+		))
+	}
+	Ok(())
+}
+
+fn not_supported<T>(what: &str) -> EnclaveResult<T> {
+	Err(Error::Other(format!("{} is not supported by the mock enclave", what).into()))
+}
+
+impl EnclaveBase for MockEnclave {
+	fn init(
+		&self,
+		mu_ra_addr: &str,
+		untrusted_worker_addr: &str,
+		base_dir: &str,
+		state_migration_dry_run: bool,
+	) -> EnclaveResult<()> {
+		warn!("[MockEnclave] The worker does NOT run in a TEE, use for testing only!");
+		info!(
+			"[MockEnclave] Initialized with mu-ra url {}, untrusted worker url {}, base dir {}",
+			mu_ra_addr, untrusted_worker_addr, base_dir
+		);
+		if state_migration_dry_run {
+			info!("[MockEnclave] States are kept in memory, there is nothing to migrate");
+		}
+		Ok(())
+	}
+
+	fn init_enclave_sidechain_components(&self) -> EnclaveResult<()> {
+		not_supported("The sidechain")
+	}
+
+	fn enable_tracing(&self) -> EnclaveResult<()> {
+		info!("[MockEnclave] Tracing spans are not recorded by the mock enclave");
+		Ok(())
+	}
+
+	fn init_direct_invocation_server(
+		&self,
+		rpc_server_addr: String,
+		limits: TrustedRpcServerLimits,
+	) -> EnclaveResult<()> {
+		let cert = ed25519_self_signed_certificate(self.signer.clone(), "Enclave")
+			.map_err(|e| Error::Other(e.into()))?;
+		let pem_serialized = cert.serialize_pem().map_err(|e| Error::Other(e.into()))?;
+		let private_key = cert.serialize_private_key_pem();
+		if let Some(max_batch_size) = limits.max_batch_size {
+			self.rpc_handler.set_max_batch_size(max_batch_size as usize);
+		}
+
+		let web_socket_server = create_ws_server(
+			rpc_server_addr.as_str(),
+			&private_key,
+			&pem_serialized,
+			self.rpc_handler.clone(),
+			limits.into(),
+			self.ocall_api.clone(),
+		);
+		self.rpc_response_channel.set_web_socket_server(web_socket_server.clone());
+
+		if let Err(e) = web_socket_server.run() {
+			error!("[MockEnclave] Web socket server encountered an unexpected error: {:?}", e)
+		}
+		Ok(())
+	}
+
+	fn init_parentchain_components<Header: Decode + Debug>(
+		&self,
+		params: ParentchainInitParams,
+	) -> EnclaveResult<Header> {
+		let (parentchain_id, genesis_header) = match params {
+			Solochain { id, params, .. } => (id, params.genesis_header),
+			Parachain { id, params, .. } => (id, params.genesis_header),
+		};
+		warn!(
+			"[MockEnclave] {:?} parentchain blocks are NOT validated by a light client and indirect calls are NOT imported",
+			parentchain_id
+		);
+		let parentchain = self.parentchain(&parentchain_id);
+		*parentchain.genesis_hash.write().expect("Lock poisoning") = Some(genesis_header.hash());
+
+		let latest_header = parentchain
+			.last_synced_header
+			.write()
+			.expect("Lock poisoning")
+			.get_or_insert(genesis_header)
+			.clone();
+		Ok(Header::decode(&mut latest_header.encode().as_slice())?)
+	}
+
+	fn init_shard(&self, shard: Vec<u8>) -> EnclaveResult<()> {
+		let shard = ShardIdentifier::decode(&mut shard.as_slice())?;
+		self.state_handler.initialize_shard(shard).map_err(|e| Error::Other(e.into()))?;
+		Ok(())
+	}
+
+	fn load_shard(&self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+		self.state_handler.load_shard(shard).map_err(|e| Error::Other(e.into()))?;
+		Ok(())
+	}
+
+	fn unload_shard(&self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+		self.state_handler.unload_shard(shard).map_err(|e| Error::Other(e.into()))
+	}
+
+	fn init_proxied_shard_vault(
+		&self,
+		shard: &ShardIdentifier,
+		parentchain_id: &ParentchainId,
+		_funding_balance: Balance,
+	) -> EnclaveResult<()> {
+		self.ensure_shard_exists(shard)?;
+		let vault = self
+			.signer
+			.derive(vec![DeriveJunction::hard(shard.encode())].into_iter(), None)
+			.map_err(|_| Error::Other("failed to derive shard vault keypair".into()))?
+			.0;
+		info!("[MockEnclave] Shard vault account derived pubkey: {:?}", vault.public());
+
+		let (state_lock, mut state) = self
+			.state_handler
+			.load_for_mutation(shard)
+			.map_err(|e| Error::Other(e.into()))?;
+		MockEnclaveStf::init_shard_vault_account(
+			&mut state,
+			vault.public().into(),
+			*parentchain_id,
+		)
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		self.state_handler
+			.write_after_mutation(state, state_lock, shard)
+			.map_err(|e| Error::Other(e.into()))?;
+
+		warn!("[MockEnclave] Shard vault is not funded and no proxy is registered for it");
+		Ok(())
+	}
+
+	fn init_shard_creation_parentchain_header(
+		&self,
+		shard: &ShardIdentifier,
+		parentchain_id: &ParentchainId,
+		header: &Header,
+	) -> EnclaveResult<()> {
+		if let Some(creation_block) =
+			self.get_shard_creation_info(shard)?.for_parentchain(*parentchain_id)
+		{
+			error!(
+				"[MockEnclave] First relevant parentchain header has been previously initialized to {:?}: {:?}",
+				creation_block.number, parentchain_id
+			);
+			return Err(Error::Other(
+				"first relevant parentchain header has been previously initialized. cannot change"
+					.into(),
+			))
+		}
+
+		let (state_lock, mut state) = self
+			.state_handler
+			.load_for_mutation(shard)
+			.map_err(|e| Error::Other(e.into()))?;
+		MockEnclaveStf::set_creation_block(&mut state, header.clone(), *parentchain_id)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		self.state_handler
+			.write_after_mutation(state, state_lock, shard)
+			.map_err(|e| Error::Other(e.into()))?;
+		Ok(())
+	}
+
+	fn get_shard_creation_info(&self, shard: &ShardIdentifier) -> EnclaveResult<ShardCreationInfo> {
+		self.ensure_shard_exists(shard)?;
+		let (_state_lock, mut state) = self
+			.state_handler
+			.load_for_mutation(shard)
+			.map_err(|e| Error::Other(e.into()))?;
+		Ok(MockEnclaveStf::get_shard_creation_info(&mut state))
+	}
+
+	fn set_nonce(&self, nonce: u32, parentchain_id: ParentchainId) -> EnclaveResult<()> {
+		info!(
+			"[MockEnclave] Setting the nonce to: {} for parentchain: {:?}",
+			nonce, parentchain_id
+		);
+		let mut nonce_guard = self
+			.parentchain(&parentchain_id)
+			.nonce_cache
+			.load_for_mutation()
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		*nonce_guard = Nonce(nonce);
+		Ok(())
+	}
+
+	fn set_node_metadata(
+		&self,
+		metadata: Vec<u8>,
+		parentchain_id: ParentchainId,
+	) -> EnclaveResult<()> {
+		let metadata = NodeMetadata::decode(&mut metadata.as_slice())?;
+		self.parentchain(&parentchain_id)
+			.node_metadata_repository
+			.set_metadata(metadata);
+		Ok(())
+	}
+
+	fn get_rsa_shielding_pubkey(&self) -> EnclaveResult<Rsa3072PubKey> {
+		let shielding_key = self
+			.shielding_key_repository
+			.retrieve_key()
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		shielding_key.export_pubkey().map_err(Error::Sgx)
+	}
+
+	fn get_ecc_signing_pubkey(&self) -> EnclaveResult<ed25519::Public> {
+		Ok(self.signer.public())
+	}
+
+	fn get_ecc_vault_pubkey(&self, shard: &ShardIdentifier) -> EnclaveResult<ed25519::Public> {
+		self.ensure_shard_exists(shard)?;
+		let (_state_lock, mut state) = self
+			.state_handler
+			.load_for_mutation(shard)
+			.map_err(|e| Error::Other(e.into()))?;
+		let (vault, _parentchain_id) = MockEnclaveStf::get_vault(&mut state).ok_or_else(|| {
+			Error::Other("failed to fetch shard vault account. has it been initialized?".into())
+		})?;
+		Ok(ed25519::Public::from_raw(vault.into()))
+	}
+
+	fn get_fingerprint(&self) -> EnclaveResult<EnclaveFingerprint> {
+		Ok(fingerprint(&self.signer))
+	}
+}
+
+impl Sidechain for MockEnclave {
+	fn sync_parentchain<ParentchainBlock: Encode>(
+		&self,
+		blocks: &[SignedBlock<ParentchainBlock>],
+		_events: &[Vec<u8>],
+		_events_proofs: &[StorageProof],
+		parentchain_id: &ParentchainId,
+		_immediate_import: bool,
+	) -> EnclaveResult<()> {
+		let parentchain = self.parentchain(parentchain_id);
+		let mut latest_header = None;
+		for block in blocks {
+			let header =
+				SignedParentchainBlock::decode(&mut block.encode().as_slice())?.block.header;
+			let mut last_synced_header =
+				parentchain.last_synced_header.write().expect("Lock poisoning");
+			if let Some(last_synced_header) = last_synced_header.as_ref() {
+				ensure_extends(&header, last_synced_header)?;
+			}
+			self.stf_executor
+				.update_states(&header, parentchain_id)
+				.map_err(|e| Error::Other(e.into()))?;
+			*last_synced_header = Some(header.clone());
+			latest_header = Some(header);
+		}
+
+		match latest_header {
+			Some(latest_header) => {
+				trace!(
+					"[MockEnclave] Synced {:?} parentchain up to block {}",
+					parentchain_id,
+					latest_header.number
+				);
+				self.execute_pending_trusted_calls(&latest_header)
+			},
+			None => Ok(()),
+		}
+	}
+
+	fn execute_trusted_calls(&self, _produce_blocks: bool) -> EnclaveResult<()> {
+		not_supported("Sidechain block production")
+	}
+}
+
+impl DirectRequest for MockEnclave {
+	fn rpc(&self, request: Vec<u8>) -> EnclaveResult<Vec<u8>> {
+		let request = String::from_utf8(request).map_err(|e| Error::Other(e.into()))?;
+		let response = sidechain_io_handler()
+			.handle_request_sync(&request)
+			.unwrap_or_else(|| format!("Empty rpc response for request: {}", request));
+		Ok(response.into_bytes())
+	}
+}
+
+impl RemoteAttestation for MockEnclave {
+	fn generate_ias_ra_extrinsic(&self, w_url: &str, skip_ra: bool) -> EnclaveResult<Vec<u8>> {
+		self.register_enclave_xt(w_url, skip_ra)
+	}
+
+	fn generate_dcap_ra_extrinsic(&self, w_url: &str, skip_ra: bool) -> EnclaveResult<Vec<u8>> {
+		self.register_enclave_xt(w_url, skip_ra)
+	}
+
+	fn generate_dcap_ra_extrinsic_from_quote(
+		&self,
+		_url: String,
+		_quote: &[u8],
+	) -> EnclaveResult<Vec<u8>> {
+		not_supported("DCAP attestation")
+	}
+
+	fn generate_dcap_ra_quote(&self, skip_ra: bool) -> EnclaveResult<Vec<u8>> {
+		if !skip_ra {
+			return not_supported("DCAP attestation")
+		}
+		Ok(self.get_fingerprint()?.encode())
+	}
+
+	fn generate_register_quoting_enclave_extrinsic(
+		&self,
+		_collateral: &SgxQlQveCollateral,
+	) -> EnclaveResult<Vec<u8>> {
+		not_supported("Registering the quoting enclave")
+	}
+
+	fn generate_register_tcb_info_extrinsic(
+		&self,
+		_collateral: &SgxQlQveCollateral,
+	) -> EnclaveResult<Vec<u8>> {
+		not_supported("Registering TCB info")
+	}
+
+	fn dump_ias_ra_cert_to_disk(&self) -> EnclaveResult<()> {
+		not_supported("IAS attestation")
+	}
+
+	fn dump_dcap_ra_cert_to_disk(&self) -> EnclaveResult<()> {
+		not_supported("DCAP attestation")
+	}
+
+	fn dump_dcap_collateral_to_disk(&self, _fmspc: Fmspc) -> EnclaveResult<()> {
+		not_supported("DCAP attestation")
+	}
+
+	fn set_ql_qe_enclave_paths(&self) -> EnclaveResult<()> {
+		Ok(())
+	}
+
+	fn set_sgx_qpl_logging(&self) -> EnclaveResult<()> {
+		Ok(())
+	}
+
+	fn qe_get_target_info(&self) -> EnclaveResult<sgx_target_info_t> {
+		not_supported("DCAP attestation")
+	}
+
+	fn qe_get_quote_size(&self) -> EnclaveResult<u32> {
+		not_supported("DCAP attestation")
+	}
+
+	fn get_dcap_collateral(&self, _fmspc: Fmspc) -> EnclaveResult<*const sgx_ql_qve_collateral_t> {
+		not_supported("DCAP attestation")
+	}
+
+	fn fetch_dcap_collateral(&self, _fmspc: Fmspc) -> EnclaveResult<SgxQlQveCollateral> {
+		not_supported("DCAP attestation")
+	}
+}
+
+impl RemoteAttestationCallBacks for MockEnclave {
+	fn init_quote(&self) -> EnclaveResult<(sgx_target_info_t, sgx_epid_group_id_t)> {
+		not_supported("IAS attestation")
+	}
+
+	fn calc_quote_size(&self, _revocation_list: Vec<u8>) -> EnclaveResult<u32> {
+		not_supported("IAS attestation")
+	}
+
+	fn get_quote(
+		&self,
+		_revocation_list: Vec<u8>,
+		_report: sgx_report_t,
+		_quote_type: sgx_quote_sign_type_t,
+		_spid: sgx_spid_t,
+		_quote_nonce: sgx_quote_nonce_t,
+		_quote_length: u32,
+	) -> EnclaveResult<(sgx_report_t, Vec<u8>)> {
+		not_supported("IAS attestation")
+	}
+
+	fn get_dcap_quote(&self, _report: sgx_report_t, _quote_size: u32) -> EnclaveResult<Vec<u8>> {
+		not_supported("DCAP attestation")
+	}
+
+	fn get_qve_report_on_quote(
+		&self,
+		_quote: Vec<u8>,
+		_current_time: i64,
+		_quote_collateral: &sgx_ql_qve_collateral_t,
+		_qve_report_info: sgx_ql_qe_report_info_t,
+		_supplemental_data_size: u32,
+	) -> EnclaveResult<QveReport> {
+		not_supported("DCAP attestation")
+	}
+
+	fn get_update_info(
+		&self,
+		_platform_blob: sgx_platform_info_t,
+		_enclave_trusted: i32,
+	) -> EnclaveResult<sgx_update_info_bit_t> {
+		not_supported("IAS attestation")
+	}
+}
+
+impl TlsRemoteAttestation for MockEnclave {
+	fn run_state_provisioning_server(
+		&self,
+		_socket_fd: c_int,
+		_sign_type: sgx_quote_sign_type_t,
+		_quoting_enclave_target_info: Option<&sgx_target_info_t>,
+		_quote_size: Option<&u32>,
+		_skip_ra: bool,
+	) -> EnclaveResult<()> {
+		not_supported("State provisioning")
+	}
+
+	fn request_state_provisioning(
+		&self,
+		_socket_fd: c_int,
+		_sign_type: sgx_quote_sign_type_t,
+		_quoting_enclave_target_info: Option<&sgx_target_info_t>,
+		_quote_size: Option<&u32>,
+		_shard: &ShardIdentifier,
+		_skip_ra: bool,
+		_shielding_key_only: bool,
+	) -> EnclaveResult<()> {
+		not_supported("State provisioning")
+	}
+}
+
+impl TeeracleApi for MockEnclave {
+	fn update_market_data_xt(
+		&self,
+		_crypto_currency: &str,
+		_fiat_currency: &str,
+	) -> EnclaveResult<Vec<u8>> {
+		not_supported("Teeracle")
+	}
+
+	fn update_weather_data_xt(&self, _longitude: &str, _latitude: &str) -> EnclaveResult<Vec<u8>> {
+		not_supported("Teeracle")
+	}
+}
+
+impl AdminApi for MockEnclave {
+	fn get_pending_trusted_operations(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<Vec<Vec<u8>>> {
+		self.ensure_shard_exists(shard)?;
+		self.top_pool_author
+			.pending_tops(*shard)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))
+	}
+
+	fn create_state_snapshot(&self, shard: &ShardIdentifier) -> EnclaveResult<H256> {
+		let (state_lock, state) = self
+			.state_handler
+			.load_for_mutation(shard)
+			.map_err(|e| Error::Other(e.into()))?;
+		self.state_handler
+			.write_after_mutation(state, state_lock, shard)
+			.map_err(|e| Error::Other(e.into()))
+	}
+
+	fn rotate_shielding_key(&self) -> EnclaveResult<Rsa3072PubKey> {
+		info!("[MockEnclave] Rotating shielding key");
+		let shielding_key = create_shielding_key(&self.base_dir)?;
+		self.shielding_key_repository
+			.update_key(shielding_key)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		self.get_rsa_shielding_pubkey()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ita_stf::TrustedCall;
+	use itc_tls_websocket_server::{ConnectionToken, WebSocketMessageHandler};
+	use itp_rpc::RpcRequest;
+	use itp_sgx_crypto::ShieldingCryptoEncrypt;
+	use itp_sgx_temp_dir::TempDir;
+	use itp_stf_interface::system_pallet::SystemPalletAccountInterface;
+	use itp_stf_primitives::traits::TrustedCallSigning;
+	use itp_types::{parentchain::BlockNumber, AccountId, Request};
+	use itp_utils::ToHexPrefixed;
+	use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+
+	struct ParentchainSenderMock;
+
+	impl SendToParentchain for ParentchainSenderMock {
+		fn send_to_parentchain(
+			&self,
+			_extrinsics: Vec<OpaqueExtrinsic>,
+			_parentchain_id: &ParentchainId,
+			_await_each_inclusion: bool,
+		) -> EnclaveResult<()> {
+			Ok(())
+		}
+	}
+
+	fn mock_enclave(base_dir: &Path) -> MockEnclave {
+		MockEnclave::new(base_dir, Arc::new(ParentchainSenderMock)).unwrap()
+	}
+
+	fn header(number: BlockNumber) -> Header {
+		Header::new(
+			number,
+			Default::default(),
+			Default::default(),
+			[1; 32].into(),
+			Default::default(),
+		)
+	}
+
+	fn child_header(parent: &Header) -> Header {
+		Header::new(
+			parent.number + 1,
+			Default::default(),
+			Default::default(),
+			parent.hash(),
+			Default::default(),
+		)
+	}
+
+	fn sync_integritee_block(enclave: &MockEnclave, header: Header) -> EnclaveResult<()> {
+		let block = SignedParentchainBlock {
+			block: ParentchainBlock { header, extrinsics: vec![] },
+			justifications: None,
+		};
+		enclave.sync_parentchain(&[block], &[], &[], &ParentchainId::Integritee, true)
+	}
+
+	#[test]
+	fn keys_are_persisted_in_base_dir() {
+		let temp_dir = TempDir::with_prefix("keys_are_persisted_in_base_dir").unwrap();
+
+		let enclave = mock_enclave(temp_dir.path());
+		let reloaded_enclave = mock_enclave(temp_dir.path());
+
+		assert_eq!(
+			enclave.get_ecc_signing_pubkey().unwrap(),
+			reloaded_enclave.get_ecc_signing_pubkey().unwrap()
+		);
+		assert_eq!(enclave.get_fingerprint().unwrap(), reloaded_enclave.get_fingerprint().unwrap());
+	}
+
+	#[test]
+	fn shard_creation_header_can_only_be_set_once() {
+		let temp_dir = TempDir::with_prefix("shard_creation_header_can_only_be_set_once").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let shard = ShardIdentifier::from_slice(&[3u8; 32]);
+
+		enclave.init_shard(shard.encode()).unwrap();
+		assert!(enclave.get_shard_creation_info(&shard).unwrap().integritee.is_none());
+
+		enclave
+			.init_shard_creation_parentchain_header(&shard, &ParentchainId::Integritee, &header(5))
+			.unwrap();
+		let creation_info = enclave.get_shard_creation_info(&shard).unwrap();
+		assert_eq!(creation_info.integritee.unwrap().number, 5);
+		assert!(creation_info.target_a.is_none());
+
+		assert!(enclave
+			.init_shard_creation_parentchain_header(&shard, &ParentchainId::Integritee, &header(6))
+			.is_err());
+	}
+
+	#[test]
+	fn shard_vault_is_derived_from_signer() {
+		let temp_dir = TempDir::with_prefix("shard_vault_is_derived_from_signer").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let shard = ShardIdentifier::from_slice(&[4u8; 32]);
+
+		assert!(enclave.init_proxied_shard_vault(&shard, &ParentchainId::Integritee, 0).is_err());
+
+		enclave.init_shard(shard.encode()).unwrap();
+		enclave.init_proxied_shard_vault(&shard, &ParentchainId::Integritee, 0).unwrap();
+
+		let vault = enclave.get_ecc_vault_pubkey(&shard).unwrap();
+		assert_ne!(vault, enclave.get_ecc_signing_pubkey().unwrap());
+	}
+
+	#[test]
+	fn rotated_shielding_key_is_persisted() {
+		let temp_dir = TempDir::with_prefix("rotated_shielding_key_is_persisted").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let pubkey_json = |pubkey: Rsa3072PubKey| serde_json::to_string(&pubkey).unwrap();
+
+		let initial_pubkey = pubkey_json(enclave.get_rsa_shielding_pubkey().unwrap());
+		let rotated_pubkey = pubkey_json(enclave.rotate_shielding_key().unwrap());
+		assert_ne!(initial_pubkey, rotated_pubkey);
+
+		let reloaded_enclave = mock_enclave(temp_dir.path());
+		assert_eq!(
+			pubkey_json(reloaded_enclave.get_rsa_shielding_pubkey().unwrap()),
+			rotated_pubkey
+		);
+	}
+
+	#[test]
+	fn state_snapshot_requires_existing_shard() {
+		let temp_dir = TempDir::with_prefix("state_snapshot_requires_existing_shard").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let shard = ShardIdentifier::from_slice(&[5u8; 32]);
+
+		assert!(enclave.create_state_snapshot(&shard).is_err());
+
+		enclave.init_shard(shard.encode()).unwrap();
+		enclave.create_state_snapshot(&shard).unwrap();
+		assert!(enclave.get_pending_trusted_operations(&shard).unwrap().is_empty());
+	}
+
+	#[test]
+	fn submitted_trusted_call_is_executed_on_shard_state() {
+		let temp_dir =
+			TempDir::with_prefix("submitted_trusted_call_is_executed_on_shard_state").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let shard = ShardIdentifier::from_slice(&[6u8; 32]);
+		enclave.init_shard(shard.encode()).unwrap();
+
+		let sender = ed25519::Pair::from_seed(&[7u8; 32]);
+		let sender_account: AccountId = sender.public().into();
+		let trusted_operation = TrustedCall::noop(sender_account.clone())
+			.sign(&sender.into(), 0, &enclave.get_fingerprint().unwrap().0, &shard)
+			.into_trusted_operation(true);
+		let request = Request {
+			shard,
+			cyphertext: enclave
+				.get_rsa_shielding_pubkey()
+				.unwrap()
+				.encrypt(&trusted_operation.encode())
+				.unwrap(),
+		};
+		let rpc_request = RpcRequest::compose_jsonrpc_call(
+			"author_submitExtrinsic".to_string(),
+			vec![request.to_hex()],
+		)
+		.unwrap();
+
+		enclave.rpc_handler.handle_message(ConnectionToken(1), rpc_request).unwrap();
+		assert_eq!(enclave.get_pending_trusted_operations(&shard).unwrap().len(), 1);
+
+		sync_integritee_block(&enclave, header(1)).unwrap();
+
+		assert!(enclave.get_pending_trusted_operations(&shard).unwrap().is_empty());
+		let (_state_lock, mut state) = enclave.state_handler.load_for_mutation(&shard).unwrap();
+		assert_eq!(MockEnclaveStf::get_account_nonce(&mut state, &sender_account), 1);
+	}
+
+	#[test]
+	fn parentchain_blocks_must_extend_last_synced_block() {
+		let temp_dir =
+			TempDir::with_prefix("parentchain_blocks_must_extend_last_synced_block").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let first_header = header(1);
+		sync_integritee_block(&enclave, first_header.clone()).unwrap();
+
+		assert!(sync_integritee_block(&enclave, header(2)).is_err());
+		assert!(
+			sync_integritee_block(&enclave, child_header(&child_header(&first_header))).is_err()
+		);
+
+		sync_integritee_block(&enclave, child_header(&first_header)).unwrap();
+	}
+
+	#[test]
+	fn sidechain_block_production_is_refused() {
+		let temp_dir = TempDir::with_prefix("sidechain_block_production_is_refused").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+
+		assert!(enclave.init_enclave_sidechain_components().is_err());
+		assert!(enclave.execute_trusted_calls(true).is_err());
+	}
+
+	#[test]
+	fn sidechain_block_import_is_refused() {
+		let temp_dir = TempDir::with_prefix("sidechain_block_import_is_refused").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+		let rpc_request = RpcRequest::compose_jsonrpc_call(
+			RPC_METHOD_NAME_IMPORT_BLOCKS.to_string(),
+			vec![Vec::<u8>::new().to_hex()],
+		)
+		.unwrap();
+
+		let response = String::from_utf8(enclave.rpc(rpc_request.into_bytes()).unwrap()).unwrap();
+
+		assert!(response.contains("does not import sidechain blocks"));
+	}
+
+	#[test]
+	fn registering_enclave_requires_skip_ra() {
+		let temp_dir = TempDir::with_prefix("registering_enclave_requires_skip_ra").unwrap();
+		let enclave = mock_enclave(temp_dir.path());
+
+		assert!(enclave.generate_ias_ra_extrinsic("ws://localhost:2000", false).is_err());
+		// Parentchain components are not initialized yet.
+		assert!(enclave.generate_ias_ra_extrinsic("ws://localhost:2000", true).is_err());
+	}
+}
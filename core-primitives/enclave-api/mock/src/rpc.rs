@@ -0,0 +1,186 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! RPC of the mock enclave: the direct invocation API and the (unsupported) sidechain block import.
+
+use codec::Encode;
+use ita_stf::{Getter, TrustedCallSigned};
+use itc_direct_rpc_server::{
+	response_channel::ResponseChannel, rpc_connection_registry::ConnectionRegistry,
+	rpc_subscription_registry::SubscriptionRegistry, rpc_watch_extractor::RpcWatchExtractor,
+	rpc_ws_handler::RpcWsHandler, DirectRpcError,
+};
+use itc_tls_websocket_server::{
+	config_provider::FromFileConfigProvider, ws_server::TungsteniteWsServer, ConnectionToken,
+	WebSocketResponder,
+};
+use itp_rpc::RpcReturnValue;
+use itp_sgx_crypto::key_repository::AccessKey;
+use itp_stf_executor::getter_executor::ExecuteGetter;
+use itp_stf_primitives::types::Hash;
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{DirectRequestStatus, Request, H256};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use its_rpc_handler::{constants::RPC_METHOD_NAME_IMPORT_BLOCKS, direct_top_pool_api};
+use jsonrpc_core::{serde_json::json, Error as RpcError, ErrorCode, IoHandler, Params, Value};
+use log::*;
+use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
+use std::sync::{Arc, RwLock};
+
+pub type MockRpcConnectionRegistry = ConnectionRegistry<Hash, ConnectionToken>;
+pub type MockRpcSubscriptionRegistry = SubscriptionRegistry<ConnectionToken>;
+pub type MockRpcWsHandler = RpcWsHandler<
+	RpcWatchExtractor<Hash>,
+	MockRpcConnectionRegistry,
+	MockRpcSubscriptionRegistry,
+	Hash,
+>;
+pub type MockWebSocketServer = TungsteniteWsServer<MockRpcWsHandler, FromFileConfigProvider>;
+
+/// RPC response channel, using the direct invocation server once it has been started.
+///
+/// Responses are discarded with an error as long as no server is running.
+#[derive(Default)]
+pub struct MockRpcResponseChannel {
+	web_socket_server: RwLock<Option<Arc<MockWebSocketServer>>>,
+}
+
+impl MockRpcResponseChannel {
+	pub fn set_web_socket_server(&self, web_socket_server: Arc<MockWebSocketServer>) {
+		*self.web_socket_server.write().expect("Lock poisoning") = Some(web_socket_server);
+	}
+}
+
+impl ResponseChannel<ConnectionToken> for MockRpcResponseChannel {
+	type Error = DirectRpcError;
+
+	fn respond(&self, token: ConnectionToken, message: String) -> Result<(), Self::Error> {
+		let web_socket_server = self
+			.web_socket_server
+			.read()
+			.map_err(|_| DirectRpcError::Other("lock poisoning".into()))?
+			.clone()
+			.ok_or_else(|| {
+				DirectRpcError::Other("direct invocation server is not running".into())
+			})?;
+		web_socket_server.send_message(token, message).map_err(|e| e.into())
+	}
+}
+
+fn compute_hex_encoded_return_error(error_msg: &str) -> String {
+	RpcReturnValue::from_error_message(error_msg).to_hex()
+}
+
+/// Creates the direct invocation API of the mock enclave.
+///
+/// It is the subset of the enclave's public API which is backed by the mock: submitting trusted
+/// operations to the TOP pool, executing getters and retrieving the shielding key.
+pub fn public_api_rpc_handler<Author, GetterExecutor, ShieldingKeyRepository>(
+	top_pool_author: Arc<Author>,
+	getter_executor: Arc<GetterExecutor>,
+	shielding_key_repository: Arc<ShieldingKeyRepository>,
+) -> IoHandler
+where
+	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter> + Send + Sync + 'static,
+	GetterExecutor: ExecuteGetter + Send + Sync + 'static,
+	ShieldingKeyRepository: AccessKey<KeyType = Rsa3072KeyPair> + Send + Sync + 'static,
+{
+	let mut io =
+		direct_top_pool_api::add_top_pool_direct_rpc_methods(top_pool_author, IoHandler::new());
+
+	io.add_sync_method("author_getShieldingKey", move |_: Params| {
+		debug!("[MockEnclave] rpc was called: author_getShieldingKey");
+		let rsa_pubkey = match shielding_key_repository
+			.retrieve_key()
+			.map_err(|e| format!("{:?}", e))
+			.and_then(|key| key.export_pubkey().map_err(|e| format!("{:?}", e)))
+		{
+			Ok(key) => key,
+			Err(e) => {
+				let error_msg = format!("Could not get rsa pubkey due to: {}", e);
+				return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+			},
+		};
+		let rsa_pubkey_json = match serde_json::to_string(&rsa_pubkey) {
+			Ok(k) => k,
+			Err(e) => {
+				let error_msg = format!("Could not serialize rsa pubkey: {}", e);
+				return Ok(json!(compute_hex_encoded_return_error(error_msg.as_str())))
+			},
+		};
+		let json_value =
+			RpcReturnValue::new(rsa_pubkey_json.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
+	});
+
+	io.add_sync_method("state_executeGetter", move |params: Params| {
+		debug!("[MockEnclave] rpc was called: state_executeGetter");
+		let json_value = match execute_getter_inner(getter_executor.as_ref(), params) {
+			Ok(state_getter_value) => RpcReturnValue {
+				do_watch: false,
+				value: state_getter_value.encode(),
+				status: DirectRequestStatus::Ok,
+			}
+			.to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
+	let rpc_methods_string = format!(
+		"methods: [{}]",
+		io.iter().map(|(name, _)| name.to_owned()).collect::<Vec<String>>().join(", ")
+	);
+	io.add_sync_method("rpc_methods", move |_: Params| {
+		debug!("[MockEnclave] rpc was called: rpc_methods");
+		Ok(Value::String(rpc_methods_string.to_owned()))
+	});
+
+	io
+}
+
+fn execute_getter_inner<GE: ExecuteGetter>(
+	getter_executor: &GE,
+	params: Params,
+) -> Result<Option<Vec<u8>>, String> {
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| "Missing request parameter".to_string())?;
+	let request = Request::from_hex(hex_encoded_request).map_err(|e| format!("{:?}", e))?;
+
+	getter_executor
+		.execute_getter(&request.shard, request.cyphertext)
+		.map_err(|e| format!("{:?}", e))
+}
+
+/// Creates the handler for the RPC requests peers send to our untrusted RPC server.
+///
+/// The mock enclave has no sidechain, so the block import is refused with a JSON-RPC error
+/// instead of silently dropping the blocks.
+pub fn sidechain_io_handler() -> IoHandler {
+	let mut io = IoHandler::new();
+	io.add_sync_method(RPC_METHOD_NAME_IMPORT_BLOCKS, |_: Params| {
+		warn!("[MockEnclave] Refusing to import sidechain blocks, there is no sidechain");
+		Err::<Value, _>(RpcError {
+			code: ErrorCode::MethodNotFound,
+			message: "The mock enclave does not import sidechain blocks".to_string(),
+			data: None,
+		})
+	});
+	io
+}
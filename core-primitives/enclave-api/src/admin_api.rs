@@ -0,0 +1,122 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::EnclaveResult;
+use itp_types::{ShardIdentifier, H256};
+use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+
+/// Operational calls into the enclave, used by the admin rpc of a running worker.
+pub trait AdminApi: Send + Sync + 'static {
+	/// Encoded trusted operations that are currently pending in the TOP pool of a shard.
+	fn get_pending_trusted_operations(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<Vec<Vec<u8>>>;
+
+	/// Write the current state of a shard to a new snapshot and return the state hash.
+	fn create_state_snapshot(&self, shard: &ShardIdentifier) -> EnclaveResult<H256>;
+
+	/// Replace the shielding key with a newly generated one and return its public key.
+	fn rotate_shielding_key(&self) -> EnclaveResult<Rsa3072PubKey>;
+}
+
+#[cfg(feature = "implement-ffi")]
+mod impl_ffi {
+	use super::AdminApi;
+	use crate::{error::Error, Enclave, EnclaveResult};
+	use codec::{Decode, Encode};
+	use frame_support::ensure;
+	use itp_enclave_api_ffi as ffi;
+	use itp_settings::worker::{PENDING_TRUSTED_OPERATIONS_MAX_SIZE, SHIELDING_KEY_SIZE};
+	use itp_types::{ShardIdentifier, H256};
+	use log::*;
+	use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+	use sgx_types::*;
+
+	impl AdminApi for Enclave {
+		fn get_pending_trusted_operations(
+			&self,
+			shard: &ShardIdentifier,
+		) -> EnclaveResult<Vec<Vec<u8>>> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let mut operations = vec![0u8; PENDING_TRUSTED_OPERATIONS_MAX_SIZE];
+			let mut operations_len: u32 = 0;
+			let shard_bytes = shard.encode();
+
+			let result = unsafe {
+				ffi::get_pending_trusted_operations(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					operations.as_mut_ptr(),
+					operations.len() as u32,
+					&mut operations_len as *mut u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut &operations[..operations_len as usize])?)
+		}
+
+		fn create_state_snapshot(&self, shard: &ShardIdentifier) -> EnclaveResult<H256> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let mut state_hash = H256::default();
+			let shard_bytes = shard.encode();
+
+			let result = unsafe {
+				ffi::create_state_snapshot(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					state_hash.as_mut_ptr(),
+					state_hash.as_bytes().len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(state_hash)
+		}
+
+		fn rotate_shielding_key(&self) -> EnclaveResult<Rsa3072PubKey> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let mut pubkey = vec![0u8; SHIELDING_KEY_SIZE];
+
+			let result = unsafe {
+				ffi::rotate_shielding_key(
+					self.eid,
+					&mut retval,
+					pubkey.as_mut_ptr(),
+					pubkey.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			let rsa_pubkey: Rsa3072PubKey =
+				serde_json::from_slice(pubkey.as_slice()).map_err(|e| Error::Other(e.into()))?;
+			info!("Rotated shielding key, new RSA pubkey {:?}", rsa_pubkey);
+			Ok(rsa_pubkey)
+		}
+	}
+}
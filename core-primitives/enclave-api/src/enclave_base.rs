@@ -21,7 +21,7 @@ use codec::Decode;
 use core::fmt::Debug;
 use itc_parentchain::primitives::{ParentchainId, ParentchainInitParams};
 use itp_stf_interface::ShardCreationInfo;
-use itp_types::{parentchain::Header, Balance, ShardIdentifier};
+use itp_types::{parentchain::Header, Balance, ShardIdentifier, TrustedRpcServerLimits};
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
 use teerex_primitives::EnclaveFingerprint;
@@ -29,18 +29,29 @@ use teerex_primitives::EnclaveFingerprint;
 /// Trait for base/common Enclave API functions
 pub trait EnclaveBase: Send + Sync + 'static {
 	/// Initialize the enclave (needs to be called once at application startup).
+	///
+	/// With `state_migration_dry_run`, the migrations of states written by an older runtime
+	/// version are only reported, but not applied.
 	fn init(
 		&self,
 		mu_ra_addr: &str,
 		untrusted_worker_addr: &str,
 		base_dir: &str,
+		state_migration_dry_run: bool,
 	) -> EnclaveResult<()>;
 
 	/// Initialize the enclave sidechain components.
 	fn init_enclave_sidechain_components(&self) -> EnclaveResult<()>;
 
+	/// Record tracing spans in the enclave and export them with the metrics ocall.
+	fn enable_tracing(&self) -> EnclaveResult<()>;
+
 	/// Initialize the direct invocation RPC server.
-	fn init_direct_invocation_server(&self, rpc_server_addr: String) -> EnclaveResult<()>;
+	fn init_direct_invocation_server(
+		&self,
+		rpc_server_addr: String,
+		limits: TrustedRpcServerLimits,
+	) -> EnclaveResult<()>;
 
 	/// Initialize the light client (needs to be called once at application startup).
 	fn init_parentchain_components<Header: Decode + Debug>(
@@ -51,6 +62,12 @@ pub trait EnclaveBase: Send + Sync + 'static {
 	/// Initialize a new shard.
 	fn init_shard(&self, shard: Vec<u8>) -> EnclaveResult<()>;
 
+	/// Load the state of an existing shard, so the enclave handles it (again).
+	fn load_shard(&self, shard: &ShardIdentifier) -> EnclaveResult<()>;
+
+	/// Stop handling a shard. Its state is kept and can be loaded again.
+	fn unload_shard(&self, shard: &ShardIdentifier) -> EnclaveResult<()>;
+
 	/// Initialize a new shard vault account and register enclave signer as its proxy.
 	fn init_proxied_shard_vault(
 		&self,
@@ -117,6 +134,7 @@ mod impl_ffi {
 			mu_ra_addr: &str,
 			untrusted_worker_addr: &str,
 			base_dir: &str,
+			state_migration_dry_run: bool,
 		) -> EnclaveResult<()> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 
@@ -134,6 +152,7 @@ mod impl_ffi {
 					encoded_untrusted_worker_addr.len() as u32,
 					encoded_base_dir.as_ptr(),
 					encoded_base_dir.len() as u32,
+					state_migration_dry_run.into(),
 				)
 			};
 
@@ -154,10 +173,26 @@ mod impl_ffi {
 			Ok(())
 		}
 
-		fn init_direct_invocation_server(&self, rpc_server_addr: String) -> EnclaveResult<()> {
+		fn enable_tracing(&self) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+
+			let result = unsafe { ffi::enable_tracing(self.eid, &mut retval) };
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn init_direct_invocation_server(
+			&self,
+			rpc_server_addr: String,
+			limits: TrustedRpcServerLimits,
+		) -> EnclaveResult<()> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 
 			let encoded_rpc_server_addr = rpc_server_addr.encode();
+			let encoded_limits = limits.encode();
 
 			let result = unsafe {
 				ffi::init_direct_invocation_server(
@@ -165,6 +200,8 @@ mod impl_ffi {
 					&mut retval,
 					encoded_rpc_server_addr.as_ptr(),
 					encoded_rpc_server_addr.len() as u32,
+					encoded_limits.as_ptr(),
+					encoded_limits.len() as u32,
 				)
 			};
 
@@ -199,6 +236,44 @@ mod impl_ffi {
 			Ok(())
 		}
 
+		fn load_shard(&self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+
+			let result = unsafe {
+				ffi::load_shard(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn unload_shard(&self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+
+			let result = unsafe {
+				ffi::unload_shard(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
 		fn init_proxied_shard_vault(
 			&self,
 			shard: &ShardIdentifier,
@@ -13,6 +13,7 @@
 
 use crate::error::Error;
 
+pub mod admin_api;
 pub mod direct_request;
 pub mod enclave_base;
 pub mod enclave_test;
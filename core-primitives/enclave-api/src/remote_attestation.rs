@@ -17,6 +17,7 @@
 */
 
 use crate::EnclaveResult;
+use itp_attestation_handler::SgxQlQveCollateral;
 use itp_types::ShardIdentifier;
 use sgx_types::*;
 use teerex_primitives::Fmspc;
@@ -41,9 +42,15 @@ pub trait RemoteAttestation {
 	) -> EnclaveResult<Vec<u8>>;
 	fn generate_dcap_ra_quote(&self, skip_ra: bool) -> EnclaveResult<Vec<u8>>;
 
-	fn generate_register_quoting_enclave_extrinsic(&self, fmspc: Fmspc) -> EnclaveResult<Vec<u8>>;
+	fn generate_register_quoting_enclave_extrinsic(
+		&self,
+		collateral: &SgxQlQveCollateral,
+	) -> EnclaveResult<Vec<u8>>;
 
-	fn generate_register_tcb_info_extrinsic(&self, fmspc: Fmspc) -> EnclaveResult<Vec<u8>>;
+	fn generate_register_tcb_info_extrinsic(
+		&self,
+		collateral: &SgxQlQveCollateral,
+	) -> EnclaveResult<Vec<u8>>;
 
 	fn dump_ias_ra_cert_to_disk(&self) -> EnclaveResult<()>;
 
@@ -60,6 +67,9 @@ pub trait RemoteAttestation {
 	fn qe_get_quote_size(&self) -> EnclaveResult<u32>;
 
 	fn get_dcap_collateral(&self, fmspc: Fmspc) -> EnclaveResult<*const sgx_ql_qve_collateral_t>;
+
+	/// Fetches the collateral for `fmspc` from the PCCS and copies it out of the quote library.
+	fn fetch_dcap_collateral(&self, fmspc: Fmspc) -> EnclaveResult<SgxQlQveCollateral>;
 }
 
 /// call-backs that are made from inside the enclave (using o-call), to e-calls again inside the enclave
@@ -107,6 +117,9 @@ pub trait TlsRemoteAttestation {
 		skip_ra: bool,
 	) -> EnclaveResult<()>;
 
+	/// Request the keys and state of a shard from a peer. With `shielding_key_only`, only the
+	/// shielding key is requested, e.g. to follow a key rotation of the peer.
+	#[allow(clippy::too_many_arguments)]
 	fn request_state_provisioning(
 		&self,
 		socket_fd: c_int,
@@ -115,6 +128,7 @@ pub trait TlsRemoteAttestation {
 		quote_size: Option<&u32>,
 		shard: &ShardIdentifier,
 		skip_ra: bool,
+		shielding_key_only: bool,
 	) -> EnclaveResult<()>;
 }
 
@@ -124,6 +138,7 @@ mod impl_ffi {
 	use crate::{error::Error, utils, Enclave, EnclaveResult};
 	use codec::Encode;
 	use frame_support::ensure;
+	use itp_attestation_handler::SgxQlQveCollateral;
 	use itp_enclave_api_ffi as ffi;
 	use itp_settings::worker::EXTRINSIC_MAX_SIZE;
 	use itp_types::ShardIdentifier;
@@ -289,7 +304,7 @@ mod impl_ffi {
 
 		fn generate_register_quoting_enclave_extrinsic(
 			&self,
-			fmspc: Fmspc,
+			collateral: &SgxQlQveCollateral,
 		) -> EnclaveResult<Vec<u8>> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 			let mut unchecked_extrinsic: Vec<u8> = vec![0u8; EXTRINSIC_MAX_SIZE];
@@ -297,25 +312,20 @@ mod impl_ffi {
 
 			trace!("Generating register quoting enclave");
 
-			let collateral_ptr = self.get_dcap_collateral(fmspc)?;
+			let c_collateral = collateral.as_c_type();
 
 			let result = unsafe {
 				ffi::generate_register_quoting_enclave_extrinsic(
 					self.eid,
 					&mut retval,
-					collateral_ptr,
+					&c_collateral,
 					unchecked_extrinsic.as_mut_ptr(),
 					unchecked_extrinsic.len() as u32,
 					&mut unchecked_extrinsic_size as *mut u32,
 				)
 			};
-			let free_status = unsafe { sgx_ql_free_quote_verification_collateral(collateral_ptr) };
 			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
 			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
-			ensure!(
-				free_status == sgx_quote3_error_t::SGX_QL_SUCCESS,
-				Error::SgxQuote(free_status)
-			);
 			ensure!(
 				(unchecked_extrinsic_size as usize) < unchecked_extrinsic.len(),
 				Error::Sgx(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
@@ -323,32 +333,30 @@ mod impl_ffi {
 			Ok(Vec::from(&unchecked_extrinsic[..unchecked_extrinsic_size as usize]))
 		}
 
-		fn generate_register_tcb_info_extrinsic(&self, fmspc: Fmspc) -> EnclaveResult<Vec<u8>> {
+		fn generate_register_tcb_info_extrinsic(
+			&self,
+			collateral: &SgxQlQveCollateral,
+		) -> EnclaveResult<Vec<u8>> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 			let mut unchecked_extrinsic: Vec<u8> = vec![0u8; EXTRINSIC_MAX_SIZE];
 			let mut unchecked_extrinsic_size: u32 = 0;
 
 			trace!("Generating tcb_info registration");
 
-			let collateral_ptr = self.get_dcap_collateral(fmspc)?;
+			let c_collateral = collateral.as_c_type();
 
 			let result = unsafe {
 				ffi::generate_register_tcb_info_extrinsic(
 					self.eid,
 					&mut retval,
-					collateral_ptr,
+					&c_collateral,
 					unchecked_extrinsic.as_mut_ptr(),
 					unchecked_extrinsic.len() as u32,
 					&mut unchecked_extrinsic_size as *mut u32,
 				)
 			};
-			let free_status = unsafe { sgx_ql_free_quote_verification_collateral(collateral_ptr) };
 			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
 			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
-			ensure!(
-				free_status == sgx_quote3_error_t::SGX_QL_SUCCESS,
-				Error::SgxQuote(free_status)
-			);
 			ensure!(
 				(unchecked_extrinsic_size as usize) < unchecked_extrinsic.len(),
 				Error::Sgx(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
@@ -516,6 +524,18 @@ mod impl_ffi {
 			ensure!(sgx_status == sgx_quote3_error_t::SGX_QL_SUCCESS, Error::SgxQuote(sgx_status));
 			Ok(collateral_ptr)
 		}
+
+		fn fetch_dcap_collateral(&self, fmspc: Fmspc) -> EnclaveResult<SgxQlQveCollateral> {
+			let collateral_ptr = self.get_dcap_collateral(fmspc)?;
+			// SAFETY: `get_dcap_collateral` only returns initialized, non-null collateral.
+			let collateral = unsafe { SgxQlQveCollateral::from_c_type(&*collateral_ptr) };
+			let free_status = unsafe { sgx_ql_free_quote_verification_collateral(collateral_ptr) };
+			ensure!(
+				free_status == sgx_quote3_error_t::SGX_QL_SUCCESS,
+				Error::SgxQuote(free_status)
+			);
+			Ok(collateral)
+		}
 	}
 
 	#[cfg(feature = "implement-ffi")]
@@ -780,6 +800,7 @@ mod impl_ffi {
 			quote_size: Option<&u32>,
 			shard: &ShardIdentifier,
 			skip_ra: bool,
+			shielding_key_only: bool,
 		) -> EnclaveResult<()> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 
@@ -796,6 +817,7 @@ mod impl_ffi {
 					encoded_shard.as_ptr(),
 					encoded_shard.len() as u32,
 					skip_ra.into(),
+					shielding_key_only.into(),
 				)
 			};
 
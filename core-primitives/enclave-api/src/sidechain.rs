@@ -35,7 +35,9 @@ pub trait Sidechain: Send + Sync + 'static {
 		immediate_import: bool,
 	) -> EnclaveResult<()>;
 
-	fn execute_trusted_calls(&self) -> EnclaveResult<()>;
+	/// Import the queued sidechain blocks and, if `produce_blocks` is set, execute the pending
+	/// trusted calls in our slot.
+	fn execute_trusted_calls(&self, produce_blocks: bool) -> EnclaveResult<()>;
 }
 
 #[cfg(feature = "implement-ffi")]
@@ -87,10 +89,11 @@ mod impl_ffi {
 			Ok(())
 		}
 
-		fn execute_trusted_calls(&self) -> EnclaveResult<()> {
+		fn execute_trusted_calls(&self, produce_blocks: bool) -> EnclaveResult<()> {
 			let mut retval = sgx_status_t::SGX_SUCCESS;
 
-			let result = unsafe { ffi::execute_trusted_calls(self.eid, &mut retval) };
+			let result =
+				unsafe { ffi::execute_trusted_calls(self.eid, &mut retval, produce_blocks.into()) };
 
 			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
 			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
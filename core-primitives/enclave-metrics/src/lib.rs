@@ -23,9 +23,16 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
+pub mod rpc_rejections;
+pub mod trace;
+pub mod trusted_calls;
+
 use codec::{Decode, Encode};
-use std::string::String;
+use core::time::Duration;
+use std::{string::String, vec::Vec};
 use substrate_fixed::types::U32F32;
+use trace::TraceBatch;
+use trusted_calls::TrustedCallCount;
 
 // FIXME: Copied from ita-oracle because of cyclic deps. Should be removed after integritee-network/pallets#71
 pub type ExchangeRate = U32F32;
@@ -38,6 +45,27 @@ pub enum EnclaveMetric {
 	TopPoolSizeDecrement,
 	ExchangeRateOracle(ExchangeRateOracleMetric),
 	// OracleMetric(OracleMetric<MetricsInfo>),
+	/// A sidechain slot has been claimed by this validateer.
+	SidechainSlotClaimed,
+	/// A claimed sidechain slot did not result in a proposed block.
+	SidechainSlotMissed,
+	/// Time it took to propose a sidechain block.
+	SidechainBlockProposalDuration(Duration),
+	/// Time it took to import a sidechain block.
+	SidechainBlockImportDuration(Duration),
+	/// Number of trusted calls executed in a batch. (CallType, Count)
+	TrustedCallsExecuted(Vec<(String, TrustedCallCount)>),
+	/// Size of the encoded state in bytes, after a sidechain block has been imported.
+	StateSnapshotSize(u64),
+	/// Number of indirect calls processed in a parentchain block. (ParentchainId, Count)
+	IndirectCallsProcessed(String, u64),
+	/// Spans recorded while processing trusted operations, to be exported by the tracer.
+	TraceSpans(TraceBatch),
+	/// Drift of the host time from the trusted time in millis, positive if the host clock is ahead.
+	HostTimeDrift(i64),
+	/// Connections and requests rejected by the trusted RPC server since the last export.
+	/// (Reason, Count)
+	RpcRequestsRejected(Vec<(String, u64)>),
 }
 
 #[derive(Encode, Decode, Debug)]
@@ -0,0 +1,80 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Rejections of the trusted RPC server, counted inside the enclave.
+//!
+//! Rejections pile up when the server is flooded, so reporting each of them with an ocall would
+//! add to the load. Instead, they are counted in the [`GLOBAL_RPC_REJECTION_COUNTS`] and exported
+//! periodically, together with the trace spans.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+lazy_static! {
+	/// Global counts of the rejections of the trusted RPC server since the last export.
+	pub static ref GLOBAL_RPC_REJECTION_COUNTS: RpcRejectionCounts = Default::default();
+}
+
+/// Counts the rejections of the trusted RPC server per reason, until they are exported.
+#[derive(Default)]
+pub struct RpcRejectionCounts {
+	counts: Mutex<BTreeMap<String, u64>>,
+}
+
+impl RpcRejectionCounts {
+	pub fn increment(&self, reason: &str) {
+		if let Ok(mut counts) = self.counts.lock() {
+			let count = counts.entry(reason.into()).or_default();
+			*count = count.saturating_add(1);
+		}
+	}
+
+	/// Takes the counts per reason, returns `None` if nothing has been rejected.
+	pub fn take(&self) -> Option<Vec<(String, u64)>> {
+		let counts = core::mem::take(&mut *self.counts.lock().ok()?);
+		if counts.is_empty() {
+			None
+		} else {
+			Some(counts.into_iter().collect())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn counts_are_taken_per_reason_and_reset() {
+		let counts = RpcRejectionCounts::default();
+		counts.increment("ip_rate_limited");
+		counts.increment("idle_timeout");
+		counts.increment("ip_rate_limited");
+
+		assert_eq!(
+			counts.take(),
+			Some(vec![("idle_timeout".into(), 1), ("ip_rate_limited".into(), 2)])
+		);
+		assert_eq!(counts.take(), None);
+	}
+}
@@ -0,0 +1,329 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Tracing spans recorded inside the enclave.
+//!
+//! All spans that belong to the same trusted operation share a trace id, which is derived from
+//! the operation hash. Hence, every component that knows the hash can contribute to the trace,
+//! without having to pass a trace context along. The client gets the hash as a response to
+//! `author_submitExtrinsic` and can use it to look up the trace.
+//!
+//! A client can also continue its own trace, by adding a W3C `traceparent` to the request. The
+//! derived trace id is then mapped to the client's trace id by the exporter, see [`TraceBatch`].
+//!
+//! Spans are collected in the [`GLOBAL_TRACE_SPAN_BUFFER`] and exported in batches, nothing is
+//! recorded unless tracing has been enabled.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use codec::{Decode, Encode};
+use core::{
+	sync::atomic::{AtomicBool, Ordering},
+	time::Duration,
+};
+use lazy_static::lazy_static;
+use std::{string::String, vec::Vec};
+
+pub type TraceId = [u8; 16];
+pub type SpanId = [u8; 8];
+
+/// Name of the root span, which covers the submission of a trusted operation.
+pub const SUBMIT_OPERATION_SPAN: &str = "author_submit_operation";
+
+/// Name of the span, which covers the handling of a request by the trusted RPC server.
+pub const RPC_REQUEST_SPAN: &str = "rpc_request";
+
+/// Maximum number of spans that are buffered until the next export, further spans are dropped.
+pub const MAX_BUFFERED_TRACE_SPANS: usize = 10_000;
+
+lazy_static! {
+	/// Global buffer of the spans recorded in the enclave.
+	pub static ref GLOBAL_TRACE_SPAN_BUFFER: TraceSpanBuffer = Default::default();
+}
+
+environmental::environmental!(request_trace_context: TraceContext);
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct TraceSpan {
+	pub trace_id: TraceId,
+	/// Assigned by the exporter if `None`.
+	pub span_id: Option<SpanId>,
+	pub parent_span_id: Option<SpanId>,
+	pub name: String,
+	/// Start of the span, since unix epoch.
+	pub start: Duration,
+	/// End of the span, since unix epoch.
+	pub end: Duration,
+	pub attributes: Vec<(String, String)>,
+}
+
+impl TraceSpan {
+	/// Root span of the trace belonging to the operation with `operation_hash`.
+	pub fn root(operation_hash: &[u8; 32], name: &str, start: Duration, end: Duration) -> Self {
+		TraceSpan {
+			trace_id: trace_id_of(operation_hash),
+			span_id: Some(root_span_id_of(operation_hash)),
+			parent_span_id: None,
+			name: name.into(),
+			start,
+			end,
+			attributes: Vec::new(),
+		}
+	}
+
+	/// Span that is a child of the root span of the operation with `operation_hash`.
+	pub fn child(operation_hash: &[u8; 32], name: &str, start: Duration, end: Duration) -> Self {
+		TraceSpan {
+			trace_id: trace_id_of(operation_hash),
+			span_id: None,
+			parent_span_id: Some(root_span_id_of(operation_hash)),
+			name: name.into(),
+			start,
+			end,
+			attributes: Vec::new(),
+		}
+	}
+
+	pub fn with_attribute(mut self, key: &str, value: String) -> Self {
+		self.attributes.push((key.into(), value));
+		self
+	}
+}
+
+/// Trace context of a client request, given as W3C `traceparent`.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+	pub trace_id: TraceId,
+	/// Span of the client (or the RPC server) the request is handled in.
+	pub parent_span_id: SpanId,
+}
+
+impl TraceContext {
+	/// Parses a `traceparent` of the form `00-<trace id>-<parent span id>-<trace flags>`.
+	///
+	/// Returns `None` for unknown versions and for the invalid all-zero ids.
+	pub fn from_traceparent(traceparent: &str) -> Option<Self> {
+		let mut parts = traceparent.trim().split('-');
+		let (version, trace_id, parent_span_id, flags) =
+			(parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+		if version != "00" || parts.next().is_some() || decode_hex::<1>(flags).is_none() {
+			return None
+		}
+
+		let trace_id = decode_hex::<16>(trace_id)?;
+		let parent_span_id = decode_hex::<8>(parent_span_id)?;
+		if trace_id == TraceId::default() || parent_span_id == SpanId::default() {
+			return None
+		}
+		Some(TraceContext { trace_id, parent_span_id })
+	}
+}
+
+/// Runs `f` with `context` as trace context of the request that is being handled.
+pub fn with_request_trace_context<R>(context: Option<TraceContext>, f: impl FnOnce() -> R) -> R {
+	match context {
+		Some(mut context) => request_trace_context::using(&mut context, f),
+		None => f(),
+	}
+}
+
+/// Trace context of the request that is being handled by the current thread, if any.
+pub fn current_request_trace_context() -> Option<TraceContext> {
+	request_trace_context::with(|context| *context)
+}
+
+/// Spans and trace contexts that are exported together.
+#[derive(Encode, Decode, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceBatch {
+	pub spans: Vec<TraceSpan>,
+	/// Client trace contexts of operations, by the trace id derived from the operation hash.
+	pub contexts: Vec<(TraceId, TraceContext)>,
+}
+
+impl TraceBatch {
+	pub fn is_empty(&self) -> bool {
+		self.spans.is_empty() && self.contexts.is_empty()
+	}
+}
+
+/// Collects the recorded spans until they are exported in a batch.
+///
+/// Recording does nothing while tracing is disabled, which it is by default.
+#[derive(Default)]
+pub struct TraceSpanBuffer {
+	enabled: AtomicBool,
+	batch: Mutex<TraceBatch>,
+}
+
+impl TraceSpanBuffer {
+	pub fn set_enabled(&self, enabled: bool) {
+		self.enabled.store(enabled, Ordering::Relaxed);
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.enabled.load(Ordering::Relaxed)
+	}
+
+	pub fn record(&self, span: TraceSpan) {
+		if !self.is_enabled() {
+			return
+		}
+		if let Ok(mut batch) = self.batch.lock() {
+			if batch.spans.len() < MAX_BUFFERED_TRACE_SPANS {
+				batch.spans.push(span);
+			}
+		}
+	}
+
+	/// Records that the operation with `operation_hash` belongs to the client trace `context`.
+	pub fn record_context(&self, operation_hash: &[u8; 32], context: TraceContext) {
+		if !self.is_enabled() {
+			return
+		}
+		if let Ok(mut batch) = self.batch.lock() {
+			if batch.contexts.len() < MAX_BUFFERED_TRACE_SPANS {
+				batch.contexts.push((trace_id_of(operation_hash), context));
+			}
+		}
+	}
+
+	/// Takes the recorded spans and contexts, returns `None` if there are none.
+	pub fn take(&self) -> Option<TraceBatch> {
+		let batch = core::mem::take(&mut *self.batch.lock().ok()?);
+		if batch.is_empty() {
+			None
+		} else {
+			Some(batch)
+		}
+	}
+}
+
+fn decode_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+	if hex.len() != 2 * N || !hex.is_ascii() {
+		return None
+	}
+	let mut bytes = [0u8; N];
+	for (i, byte) in bytes.iter_mut().enumerate() {
+		*byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+	}
+	Some(bytes)
+}
+
+/// Trace id of the operation with `operation_hash`.
+pub fn trace_id_of(operation_hash: &[u8; 32]) -> TraceId {
+	let mut trace_id = TraceId::default();
+	trace_id.copy_from_slice(&operation_hash[..16]);
+	trace_id
+}
+
+/// Span id of the root span of the operation with `operation_hash`.
+pub fn root_span_id_of(operation_hash: &[u8; 32]) -> SpanId {
+	let mut span_id = SpanId::default();
+	span_id.copy_from_slice(&operation_hash[16..24]);
+	span_id
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn child_span_has_root_span_as_parent() {
+		let hash = [7u8; 32];
+		let root = TraceSpan::root(&hash, SUBMIT_OPERATION_SPAN, Duration::ZERO, Duration::ZERO);
+		let child = TraceSpan::child(&hash, "stf_execute", Duration::ZERO, Duration::ZERO);
+
+		assert_eq!(root.trace_id, child.trace_id);
+		assert_eq!(root.span_id, child.parent_span_id);
+		assert_eq!(child.span_id, None);
+	}
+
+	#[test]
+	fn trace_id_is_derived_from_operation_hash() {
+		let mut hash = [0u8; 32];
+		hash[0] = 1;
+		hash[16] = 2;
+
+		assert_eq!(trace_id_of(&hash)[0], 1);
+		assert_eq!(root_span_id_of(&hash)[0], 2);
+	}
+
+	#[test]
+	fn traceparent_is_parsed() {
+		let context = TraceContext::from_traceparent(
+			"00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+		)
+		.unwrap();
+
+		assert_eq!(context.trace_id[0], 0x0a);
+		assert_eq!(context.trace_id[15], 0x9c);
+		assert_eq!(context.parent_span_id, [0xb7, 0xad, 0x6b, 0x71, 0x69, 0x20, 0x33, 0x31]);
+	}
+
+	#[test]
+	fn invalid_traceparent_is_rejected() {
+		for traceparent in [
+			"",
+			"01-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+			"00-00000000000000000000000000000000-b7ad6b7169203331-01",
+			"00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+			"00-0af7651916cd43dd8448eb211c80319c-b7ad6b71692033-01",
+			"00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-00",
+			"00-0af7651916cd43dd8448eb211c80319x-b7ad6b7169203331-01",
+		] {
+			assert_eq!(TraceContext::from_traceparent(traceparent), None, "{}", traceparent);
+		}
+	}
+
+	#[test]
+	fn request_trace_context_is_scoped() {
+		let context = TraceContext { trace_id: [1u8; 16], parent_span_id: [2u8; 8] };
+
+		assert_eq!(current_request_trace_context(), None);
+		with_request_trace_context(Some(context), || {
+			assert_eq!(current_request_trace_context(), Some(context));
+		});
+		assert_eq!(current_request_trace_context(), None);
+	}
+
+	#[test]
+	fn disabled_buffer_records_nothing() {
+		let buffer = TraceSpanBuffer::default();
+		buffer.record(TraceSpan::child(&[1u8; 32], "span", Duration::ZERO, Duration::ZERO));
+
+		assert_eq!(buffer.take(), None);
+	}
+
+	#[test]
+	fn enabled_buffer_is_emptied_by_take() {
+		let buffer = TraceSpanBuffer::default();
+		buffer.set_enabled(true);
+		let context = TraceContext { trace_id: [1u8; 16], parent_span_id: [2u8; 8] };
+		let span = TraceSpan::child(&[3u8; 32], "span", Duration::ZERO, Duration::ZERO);
+		buffer.record(span.clone());
+		buffer.record_context(&[3u8; 32], context);
+
+		let batch = buffer.take().unwrap();
+		assert_eq!(batch.spans, vec![span]);
+		assert_eq!(batch.contexts, vec![(trace_id_of(&[3u8; 32]), context)]);
+		assert_eq!(buffer.take(), None);
+	}
+}
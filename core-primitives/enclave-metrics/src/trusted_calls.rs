@@ -0,0 +1,88 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Trusted calls executed in a batch, counted inside the enclave.
+//!
+//! Trusted calls are executed while a sidechain block is proposed, so they are counted per batch
+//! and reported with a single ocall, instead of one ocall per call.
+
+use crate::EnclaveMetric;
+use codec::{Decode, Encode};
+use std::{collections::BTreeMap, string::String};
+
+/// Counts the successful and failed trusted calls of a batch per call type.
+#[derive(Default, Debug)]
+pub struct TrustedCallCounts {
+	counts: BTreeMap<String, TrustedCallCount>,
+}
+
+/// Number of successful and failed trusted calls of a call type.
+#[derive(Encode, Decode, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedCallCount {
+	pub successful: u64,
+	pub failed: u64,
+}
+
+impl TrustedCallCounts {
+	pub fn record(&mut self, call_type: String, is_success: bool) {
+		let count = self.counts.entry(call_type).or_default();
+		if is_success {
+			count.successful = count.successful.saturating_add(1);
+		} else {
+			count.failed = count.failed.saturating_add(1);
+		}
+	}
+
+	/// The metric reporting the counts, `None` if no trusted call has been executed.
+	pub fn into_metric(self) -> Option<EnclaveMetric> {
+		if self.counts.is_empty() {
+			None
+		} else {
+			Some(EnclaveMetric::TrustedCallsExecuted(self.counts.into_iter().collect()))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn calls_are_counted_per_call_type() {
+		let mut counts = TrustedCallCounts::default();
+		counts.record("transfer".into(), true);
+		counts.record("evm_call".into(), false);
+		counts.record("transfer".into(), false);
+		counts.record("transfer".into(), true);
+
+		match counts.into_metric() {
+			Some(EnclaveMetric::TrustedCallsExecuted(counts)) => assert_eq!(
+				counts,
+				vec![
+					("evm_call".into(), TrustedCallCount { successful: 0, failed: 1 }),
+					("transfer".into(), TrustedCallCount { successful: 2, failed: 1 }),
+				]
+			),
+			metric => panic!("Unexpected metric: {:?}", metric),
+		}
+	}
+
+	#[test]
+	fn no_metric_without_calls() {
+		assert!(TrustedCallCounts::default().into_metric().is_none());
+	}
+}
@@ -21,11 +21,13 @@ pub use substrate_api_client::{api::Error as ApiClientError, rpc::TungsteniteRpc
 
 pub mod account;
 pub mod chain;
+pub mod pallet_sidechain;
 pub mod pallet_teeracle;
 pub mod pallet_teerex;
 
 pub use account::*;
 pub use chain::*;
+pub use pallet_sidechain::*;
 pub use pallet_teeracle::*;
 pub use pallet_teerex::*;
 
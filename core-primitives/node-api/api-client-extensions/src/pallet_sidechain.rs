@@ -0,0 +1,50 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::ApiResult;
+use itp_api_client_types::{traits::GetStorage, Api, Config, Request};
+use itp_types::{parentchain::SidechainBlockConfirmation, ShardIdentifier};
+
+pub const SIDECHAIN: &str = "Sidechain";
+
+/// ApiClient extension that enables communication with the `sidechain` pallet.
+pub trait PalletSidechainApi {
+	type Hash;
+
+	/// The last sidechain block of a shard, which has been confirmed on the parentchain.
+	fn latest_sidechain_block_confirmation(
+		&self,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<SidechainBlockConfirmation>>;
+}
+
+impl<RuntimeConfig, Client> PalletSidechainApi for Api<RuntimeConfig, Client>
+where
+	RuntimeConfig: Config,
+	Client: Request,
+{
+	type Hash = RuntimeConfig::Hash;
+
+	fn latest_sidechain_block_confirmation(
+		&self,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<SidechainBlockConfirmation>> {
+		self.get_storage_map(SIDECHAIN, "LatestSidechainBlockConfirmation", shard, at_block)
+	}
+}
@@ -36,10 +36,16 @@ pub mod files {
 	pub static ENCLAVE_FILE: &str = "enclave.signed.so";
 	pub static SHIELDING_KEY_FILE: &str = "enclave-shielding-pubkey.json";
 	pub static SIGNING_KEY_FILE: &str = "enclave-signing-pubkey.bin";
+	/// bearer token required by the admin rpc, stored in the data dir
+	pub static ADMIN_RPC_TOKEN_FILE: &str = "admin_rpc.token";
 	/// sidechain database path
 	pub static SIDECHAIN_STORAGE_PATH: &str = "sidechain_db";
 	pub static SIDECHAIN_PURGE_INTERVAL: u64 = 7200; // purge sidechain every .. s
 	pub static SIDECHAIN_PURGE_LIMIT: u64 = 100; // keep the last.. sidechainblocks when purging
+	/// DCAP collateral (TCB info, QE identity, CRLs) to verify quotes with, in the data dir
+	pub static DCAP_COLLATERAL_PATH: &str = "dcap_collateral";
+	/// Cache of the DCAP collateral fetched from the PCCS, per FMSPC, in the data dir
+	pub static DCAP_COLLATERAL_CACHE_PATH: &str = "dcap_collateral_cache";
 
 	// used by enclave
 	/// Path to the light-client db for the Integritee parentchain.
@@ -82,6 +88,8 @@ pub mod worker {
 	pub const SIGNING_KEY_SIZE: usize = 32;
 	// size of the MR enclave
 	pub const MR_ENCLAVE_SIZE: usize = 32;
+	// maximum size of the encoded pending trusted operations of a shard
+	pub const PENDING_TRUSTED_OPERATIONS_MAX_SIZE: usize = 1_048_576;
 
 	// Should be set to a value that ensures that the enclave can register itself
 	// and that the worker can start.
@@ -89,6 +97,9 @@ pub mod worker {
 	// Should be set to a value that ensures that at least 2 sidechain blocks are finalized per
 	// parentchain block.
 	pub const BLOCK_NUMBER_FINALIZATION_DIFF: u64 = 20;
+	// How often a worker checks whether the primary worker of its shard rotated the shielding key.
+	pub static SHIELDING_KEY_SYNC_INTERVAL: core::time::Duration =
+		core::time::Duration::from_secs(60);
 }
 
 pub mod sidechain {
@@ -98,7 +109,15 @@ pub mod sidechain {
 }
 
 /// Settings concerning the enclave
-pub mod enclave {}
+pub mod enclave {
+	use core::time::Duration;
+
+	/// How long the replaced shielding key can still be used for decryption after a key rotation.
+	///
+	/// Must be well above `worker::SHIELDING_KEY_SYNC_INTERVAL`, so that the peers have fetched the
+	/// new key before the old one is no longer accepted.
+	pub static SHIELDING_KEY_ROTATION_GRACE_PERIOD: Duration = Duration::from_secs(600);
+}
 
 /// Settings for the Teeracle
 pub mod teeracle {
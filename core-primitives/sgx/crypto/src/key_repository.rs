@@ -23,10 +23,12 @@ use std::sync::RwLock;
 
 use crate::{
 	error::{Error, Result},
-	ToPubkey,
+	ShieldingCryptoDecrypt, ShieldingCryptoEncrypt, ToPubkey,
 };
 use itp_sgx_io::SealedIO;
-use std::sync::Arc;
+use itp_time_utils::trusted_duration_now;
+use log::*;
+use std::{sync::Arc, time::Duration, vec::Vec};
 
 /// Access a cryptographic key.
 pub trait AccessKey {
@@ -99,10 +101,164 @@ where
 	}
 }
 
+/// Key repository that keeps the replaced key usable for a grace period after a key update.
+///
+/// Clients that encrypted with the previous public key before they learned about the update
+/// can still be served through [`FallbackKeyRepository`]. Everything else only ever sees the
+/// current key. The previous key is kept in memory only, so the grace period ends with an
+/// enclave restart.
+pub struct RotatingKeyRepository<KeyType, Repository> {
+	key_repository: Arc<Repository>,
+	previous_key_lock: RwLock<Option<(KeyType, Duration)>>,
+	grace_period: Duration,
+}
+
+impl<KeyType, Repository> RotatingKeyRepository<KeyType, Repository> {
+	pub fn new(key_repository: Arc<Repository>, grace_period: Duration) -> Self {
+		RotatingKeyRepository { key_repository, previous_key_lock: RwLock::new(None), grace_period }
+	}
+}
+
+impl<KeyType, Repository> RotatingKeyRepository<KeyType, Repository>
+where
+	KeyType: Clone,
+	Repository: AccessKey<KeyType = KeyType>,
+{
+	/// Retrieve the current key, together with the previous key if its grace period has not
+	/// ended yet.
+	pub fn retrieve_key_with_fallback(&self) -> Result<KeyWithFallback<KeyType>> {
+		let current = self.key_repository.retrieve_key()?;
+		let previous_key_lock = self.previous_key_lock.read().map_err(|_| Error::LockPoisoning)?;
+		let fallback = match previous_key_lock.as_ref() {
+			Some((previous_key, valid_until)) => match trusted_duration_now() {
+				Ok(now) if now < *valid_until => Some(previous_key.clone()),
+				Ok(_) => None,
+				Err(e) => {
+					warn!("Not using the previous key, as the current time is unknown: {}", e);
+					None
+				},
+			},
+			None => None,
+		};
+		Ok(KeyWithFallback { current, fallback })
+	}
+}
+
+impl<KeyType, Repository> AccessKey for RotatingKeyRepository<KeyType, Repository>
+where
+	Repository: AccessKey<KeyType = KeyType>,
+{
+	type KeyType = KeyType;
+
+	fn retrieve_key(&self) -> Result<Self::KeyType> {
+		self.key_repository.retrieve_key()
+	}
+}
+
+impl<KeyType, Repository> AccessPubkey for RotatingKeyRepository<KeyType, Repository>
+where
+	Repository: AccessPubkey,
+{
+	type KeyType = Repository::KeyType;
+
+	fn retrieve_pubkey(&self) -> Result<Self::KeyType> {
+		self.key_repository.retrieve_pubkey()
+	}
+}
+
+impl<KeyType, Repository> MutateKey<KeyType> for RotatingKeyRepository<KeyType, Repository>
+where
+	Repository: AccessKey<KeyType = KeyType> + MutateKey<KeyType>,
+{
+	fn update_key(&self, key: KeyType) -> Result<()> {
+		// Hold the lock across the update, so no one sees the new key without the previous one.
+		let mut previous_key_lock =
+			self.previous_key_lock.write().map_err(|_| Error::LockPoisoning)?;
+		let previous_key = self.key_repository.retrieve_key()?;
+		self.key_repository.update_key(key)?;
+
+		*previous_key_lock = match trusted_duration_now() {
+			Ok(now) => Some((previous_key, now + self.grace_period)),
+			Err(e) => {
+				warn!("Dropping the previous key, as the current time is unknown: {}", e);
+				None
+			},
+		};
+		Ok(())
+	}
+}
+
+/// View on a [`RotatingKeyRepository`] that hands out the current key together with the previous
+/// key during its grace period, see [`KeyWithFallback`].
+pub struct FallbackKeyRepository<KeyType, Repository> {
+	rotating_key_repository: Arc<RotatingKeyRepository<KeyType, Repository>>,
+}
+
+impl<KeyType, Repository> FallbackKeyRepository<KeyType, Repository> {
+	pub fn new(rotating_key_repository: Arc<RotatingKeyRepository<KeyType, Repository>>) -> Self {
+		FallbackKeyRepository { rotating_key_repository }
+	}
+}
+
+impl<KeyType, Repository> AccessKey for FallbackKeyRepository<KeyType, Repository>
+where
+	KeyType: Clone,
+	Repository: AccessKey<KeyType = KeyType>,
+{
+	type KeyType = KeyWithFallback<KeyType>;
+
+	fn retrieve_key(&self) -> Result<Self::KeyType> {
+		self.rotating_key_repository.retrieve_key_with_fallback()
+	}
+}
+
+/// Current key and, during its grace period, the key it replaced.
+///
+/// Encrypts with the current key only. Decryption falls back to the previous key if the current
+/// key fails to decrypt.
+#[derive(Clone, Debug)]
+pub struct KeyWithFallback<KeyType> {
+	pub current: KeyType,
+	pub fallback: Option<KeyType>,
+}
+
+impl<KeyType> ShieldingCryptoEncrypt for KeyWithFallback<KeyType>
+where
+	KeyType: ShieldingCryptoEncrypt<Error = Error>,
+{
+	type Error = Error;
+
+	fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+		self.current.encrypt(data)
+	}
+}
+
+impl<KeyType> ShieldingCryptoDecrypt for KeyWithFallback<KeyType>
+where
+	KeyType: ShieldingCryptoDecrypt<Error = Error>,
+{
+	type Error = Error;
+
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+		match (self.current.decrypt(data), self.fallback.as_ref()) {
+			(Ok(decrypted), _) => Ok(decrypted),
+			(Err(e), None) => Err(e),
+			(Err(e), Some(fallback)) => fallback.decrypt(data).map_err(|_| e).map(|decrypted| {
+				debug!("Decrypted with the previous key, it is in its grace period");
+				decrypted
+			}),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::{aes::Aes, mocks::AesSealMock};
+	use crate::{
+		aes::Aes,
+		mocks::{AesSealMock, KeyRepositoryMock},
+	};
+	use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
 
 	type TestKeyRepository = KeyRepository<Aes, AesSealMock>;
 
@@ -119,4 +275,37 @@ mod tests {
 		assert_eq!(updated_key, key_repository.retrieve_key().unwrap());
 		assert_eq!(updated_key, seal_mock.unseal().unwrap());
 	}
+
+	fn rotating_key_repository(
+		grace_period: Duration,
+	) -> RotatingKeyRepository<Rsa3072KeyPair, KeyRepositoryMock<Rsa3072KeyPair>> {
+		let key_repository = Arc::new(KeyRepositoryMock::new(Rsa3072KeyPair::new().unwrap()));
+		RotatingKeyRepository::new(key_repository, grace_period)
+	}
+
+	#[test]
+	fn previous_key_decrypts_during_grace_period() {
+		let key_repository = Arc::new(rotating_key_repository(Duration::from_secs(600)));
+		let previous_key = key_repository.retrieve_key().unwrap();
+		let ciphertext = previous_key.encrypt(&[1u8, 2u8, 3u8]).unwrap();
+
+		key_repository.update_key(Rsa3072KeyPair::new().unwrap()).unwrap();
+
+		let key = FallbackKeyRepository::new(key_repository.clone()).retrieve_key().unwrap();
+		assert!(key.current.decrypt(&ciphertext).is_err());
+		assert_eq!(key.decrypt(&ciphertext).unwrap(), vec![1u8, 2u8, 3u8]);
+	}
+
+	#[test]
+	fn previous_key_is_not_used_after_grace_period() {
+		let key_repository = Arc::new(rotating_key_repository(Duration::ZERO));
+		let previous_key = key_repository.retrieve_key().unwrap();
+		let ciphertext = previous_key.encrypt(&[1u8, 2u8, 3u8]).unwrap();
+
+		key_repository.update_key(Rsa3072KeyPair::new().unwrap()).unwrap();
+
+		let key = FallbackKeyRepository::new(key_repository).retrieve_key().unwrap();
+		assert!(key.fallback.is_none());
+		assert!(key.decrypt(&ciphertext).is_err());
+	}
 }
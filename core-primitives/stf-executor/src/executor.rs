@@ -21,8 +21,12 @@ use crate::{
 	BatchExecutionResult, ExecutedOperation,
 };
 use codec::{Decode, Encode};
+use itp_enclave_metrics::{
+	trace::{TraceSpan, GLOBAL_TRACE_SPAN_BUFFER},
+	trusted_calls::TrustedCallCounts,
+};
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
-use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi};
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_interface::{
 	parentchain_pallet::ParentchainPalletInstancesInterface, StateCallInterface, UpdateState,
@@ -41,8 +45,8 @@ use itp_types::{
 use log::*;
 use sp_runtime::traits::Header as HeaderTrait;
 use std::{
-	collections::BTreeMap, fmt::Debug, marker::PhantomData, sync::Arc, time::Duration, vec,
-	vec::Vec,
+	collections::BTreeMap, fmt::Debug, marker::PhantomData, string::ToString, sync::Arc,
+	time::Duration, vec, vec::Vec,
 };
 
 pub struct StfExecutor<OCallApi, StateHandler, NodeMetadataRepository, Stf, TCS, G>
@@ -95,6 +99,7 @@ where
 		_header: &PH,
 		shard: &ShardIdentifier,
 		post_processing: StatePostProcessing,
+		call_counts: &mut TrustedCallCounts,
 	) -> Result<ExecutedOperation<TCS, G>>
 	where
 		PH: HeaderTrait<Hash = H256>,
@@ -102,6 +107,8 @@ where
 		debug!("query mrenclave of self");
 		let mrenclave = self.ocall_api.get_mrenclave_of_self()?;
 
+		let execution_start = duration_now();
+		let operation_hash = trusted_operation.hash();
 		let top_or_hash = TrustedOperationOrHash::from_top(trusted_operation.clone());
 
 		let trusted_call = match trusted_operation.to_call().ok_or(Error::InvalidTrustedCallType) {
@@ -114,6 +121,13 @@ where
 
 		if let false = trusted_call.verify_signature(&mrenclave.m, &shard) {
 			error!("TrustedCallSigned: bad signature");
+			report_trusted_call_execution(
+				call_counts,
+				&operation_hash,
+				&trusted_call,
+				false,
+				execution_start,
+			);
 			return Ok(ExecutedOperation::failed(top_or_hash))
 		}
 
@@ -126,10 +140,23 @@ where
 			self.node_metadata_repo.clone(),
 		) {
 			error!("Stf execute failed: {:?}", e);
+			report_trusted_call_execution(
+				call_counts,
+				&operation_hash,
+				&trusted_call,
+				false,
+				execution_start,
+			);
 			return Ok(ExecutedOperation::failed(top_or_hash))
 		}
+		report_trusted_call_execution(
+			call_counts,
+			&operation_hash,
+			&trusted_call,
+			true,
+			execution_start,
+		);
 
-		let operation_hash = trusted_operation.hash();
 		debug!("Operation hash {:?}", operation_hash);
 
 		if let StatePostProcessing::Prune = post_processing {
@@ -278,7 +305,7 @@ where
 impl<OCallApi, StateHandler, NodeMetadataRepository, Stf, TCS, G> StateUpdateProposer<TCS, G>
 	for StfExecutor<OCallApi, StateHandler, NodeMetadataRepository, Stf, TCS, G>
 where
-	OCallApi: EnclaveAttestationOCallApi + EnclaveOnChainOCallApi,
+	OCallApi: EnclaveAttestationOCallApi + EnclaveOnChainOCallApi + EnclaveMetricsOCallApi,
 	StateHandler: HandleState<HashType = H256>,
 	StateHandler::StateT: SgxExternalitiesTrait + Encode + StateHash,
 	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesType: Encode,
@@ -317,6 +344,7 @@ where
 		// Execute any pre-processing steps.
 		let mut state = prepare_state_function(state);
 		let mut executed_and_failed_calls = Vec::<ExecutedOperation<TCS, G>>::new();
+		let mut call_counts = TrustedCallCounts::default();
 
 		// Iterate through all calls until time is over.
 		for trusted_call_signed in trusted_calls.into_iter() {
@@ -332,6 +360,7 @@ where
 				header,
 				shard,
 				StatePostProcessing::None,
+				&mut call_counts,
 			) {
 				Ok(executed_or_failed_call) => {
 					executed_and_failed_calls.push(executed_or_failed_call);
@@ -342,6 +371,12 @@ where
 			};
 		}
 
+		if let Some(metric) = call_counts.into_metric() {
+			if let Err(e) = self.ocall_api.update_metric(metric) {
+				warn!("Failed to update trusted call metric: {:?}", e);
+			}
+		}
+
 		Ok(BatchExecutionResult {
 			executed_operations: executed_and_failed_calls,
 			state_hash_before_execution,
@@ -350,6 +385,31 @@ where
 	}
 }
 
+/// Counts the execution of a trusted call for the metrics of its batch and records its trace span.
+fn report_trusted_call_execution<TCS: TrustedCallVerification>(
+	call_counts: &mut TrustedCallCounts,
+	operation_hash: &H256,
+	trusted_call: &TCS,
+	is_success: bool,
+	execution_start: Duration,
+) {
+	let call_type = trusted_call.call_type();
+	call_counts.record(call_type.into(), is_success);
+
+	if GLOBAL_TRACE_SPAN_BUFFER.is_enabled() {
+		GLOBAL_TRACE_SPAN_BUFFER.record(
+			TraceSpan::child(
+				operation_hash.as_fixed_bytes(),
+				"stf_execute_trusted_call",
+				execution_start,
+				duration_now(),
+			)
+			.with_attribute("call_type", call_type.into())
+			.with_attribute("success", is_success.to_string()),
+		);
+	}
+}
+
 fn into_map(
 	storage_entries: Vec<StorageEntryVerified<Vec<u8>>>,
 ) -> BTreeMap<Vec<u8>, Option<Vec<u8>>> {
@@ -42,7 +42,7 @@ pub mod getter_executor;
 pub mod state_getter;
 pub mod traits;
 
-#[cfg(feature = "sgx")]
+#[cfg(any(feature = "std", feature = "sgx"))]
 pub mod executor;
 
 #[cfg(feature = "sgx")]
@@ -47,6 +47,18 @@ pub trait InitState<State, AccountId> {
 	fn init_state(enclave_account: AccountId) -> State;
 }
 
+/// Interface to bring a state written by an older runtime version up to date.
+pub trait RuntimeUpgradeInterface<State> {
+	/// Spec version of the runtime that last upgraded the state, `None` if it was never recorded.
+	fn state_spec_version(state: &mut State) -> Option<u32>;
+
+	/// Spec version of the runtime the STF is built with.
+	fn runtime_spec_version() -> u32;
+
+	/// Run all registered migrations on the state and record the current runtime version in it.
+	fn on_runtime_upgrade(state: &mut State);
+}
+
 /// Interface to query shard vault account for shard
 pub trait ShardVaultQuery<S> {
 	fn get_vault(state: &mut S) -> Option<(AccountId, ParentchainId)>;
@@ -114,6 +126,11 @@ pub trait ExecuteGetter {
 	fn execute(self) -> Option<Vec<u8>>;
 	/// Get storages hashes that should be updated for a specific getter.
 	fn get_storage_hashes_to_update(self) -> Vec<Vec<u8>>;
+	/// Whether the getter writes to the state, e.g. to simulate a call. Such getters are executed
+	/// on a copy of the state, whose changes are discarded.
+	fn mutates_state(&self) -> bool {
+		false
+	}
 }
 
 #[derive(Debug, Clone, Copy, Encode, Decode)]
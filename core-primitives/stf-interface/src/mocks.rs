@@ -20,7 +20,7 @@
 extern crate alloc;
 use crate::{
 	system_pallet::SystemPalletAccountInterface, ExecuteCall, ExecuteGetter, InitState,
-	StateCallInterface, StateGetterInterface, UpdateState,
+	RuntimeUpgradeInterface, StateCallInterface, StateGetterInterface, UpdateState,
 };
 use alloc::{string::String, sync::Arc, vec::Vec};
 use codec::{Decode, Encode};
@@ -46,6 +46,20 @@ impl<State, StateDiff, AccountId> InitState<State, AccountId>
 	}
 }
 
+impl<State, StateDiff> RuntimeUpgradeInterface<State> for StateInterfaceMock<State, StateDiff> {
+	fn state_spec_version(_state: &mut State) -> Option<u32> {
+		unimplemented!()
+	}
+
+	fn runtime_spec_version() -> u32 {
+		unimplemented!()
+	}
+
+	fn on_runtime_upgrade(_state: &mut State) {
+		unimplemented!()
+	}
+}
+
 impl<State, StateDiff> UpdateState<State, StateDiff> for StateInterfaceMock<State, StateDiff> {
 	fn apply_state_diff(_state: &mut State, _state_diff: StateDiff) {
 		unimplemented!()
@@ -43,6 +43,9 @@ pub trait TrustedCallVerification {
 	fn nonce(&self) -> Index;
 
 	fn verify_signature(&self, mrenclave: &[u8; 32], shard: &ShardIdentifier) -> bool;
+
+	/// Name of the call, e.g. to label metrics.
+	fn call_type(&self) -> &'static str;
 }
 
 /// validation for top pool
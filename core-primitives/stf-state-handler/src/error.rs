@@ -43,6 +43,8 @@ pub enum Error {
 	StateNotFoundInRepository(String),
 	#[error("State observer error: {0}")]
 	StateObserver(#[from] itp_stf_state_observer::error::Error),
+	#[error("State of shard {shard} was upgraded to runtime spec version {state_spec_version}, this enclave runs the older {runtime_spec_version}")]
+	RuntimeDowngrade { shard: ShardIdentifier, state_spec_version: u32, runtime_spec_version: u32 },
 	#[error("Cache size for registry is zero")]
 	ZeroCacheSize,
 	#[error("Could not acquire lock, lock is poisoned")]
@@ -35,6 +35,16 @@ pub trait HandleState {
 	/// Initializes a default state for the shard and returns its hash.
 	fn initialize_shard(&self, shard: ShardIdentifier) -> Result<Self::HashType>;
 
+	/// Load the latest state of an existing shard, so that it is handled again.
+	///
+	/// Does nothing if the shard is already loaded. Returns the hash of the loaded state.
+	fn load_shard(&self, shard: &ShardIdentifier) -> Result<Self::HashType>;
+
+	/// Stop handling a shard, by removing its state from memory.
+	///
+	/// The state snapshots are kept, so the shard can be loaded again later on.
+	fn unload_shard(&self, shard: &ShardIdentifier) -> Result<()>;
+
 	/// Execute a function that acts (immutably) on the current state.
 	///
 	/// This allows access to the state, without any cloning.
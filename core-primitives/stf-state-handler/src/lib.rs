@@ -38,6 +38,7 @@ pub mod in_memory_state_file_io;
 pub mod query_shard_state;
 pub mod state_handler;
 pub mod state_initializer;
+pub mod state_migrator;
 mod state_snapshot_primitives;
 pub mod state_snapshot_repository;
 pub mod state_snapshot_repository_loader;
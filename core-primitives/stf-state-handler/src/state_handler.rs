@@ -26,6 +26,7 @@ use crate::{
 	handle_state::HandleState,
 	query_shard_state::QueryShardState,
 	state_initializer::InitializeState,
+	state_migrator::{MigrateState, MigrationOutcome},
 	state_snapshot_repository::VersionedStateAccess,
 };
 use core::fmt::Debug;
@@ -42,7 +43,9 @@ type StatesMap<State, Hash> = HashMap<ShardIdentifier, (State, Hash)>;
 ///
 /// Responsible for handling any state instances. Holds a map with all the latest states for each shard.
 /// In addition, uses the snapshot repository to save file snapshots of a state.
-pub struct StateHandler<Repository, StateObserver, StateInitializer>
+/// Every state entering the handler is migrated to the current runtime version first:
+/// states loaded from the repository, as well as initialized and provisioned (reset) states.
+pub struct StateHandler<Repository, StateObserver, StateInitializer, StateMigrator>
 where
 	Repository: VersionedStateAccess,
 {
@@ -50,15 +53,18 @@ where
 	states_map_lock: RwLock<StatesMap<Repository::StateType, Repository::HashType>>,
 	state_observer: Arc<StateObserver>,
 	state_initializer: Arc<StateInitializer>,
+	state_migrator: Arc<StateMigrator>,
 }
 
-impl<Repository, StateObserver, StateInitializer>
-	StateHandler<Repository, StateObserver, StateInitializer>
+impl<Repository, StateObserver, StateInitializer, StateMigrator>
+	StateHandler<Repository, StateObserver, StateInitializer, StateMigrator>
 where
 	Repository: VersionedStateAccess,
-	Repository::StateType: Hash<Repository::HashType>,
+	Repository::StateType: SgxExternalitiesTrait + Hash<Repository::HashType>,
+	Repository::HashType: Copy,
 	StateObserver: UpdateState<Repository::StateType>,
 	StateInitializer: InitializeState<StateType = Repository::StateType>,
+	StateMigrator: MigrateState<StateType = Repository::StateType>,
 {
 	/// Creates a new instance WITHOUT loading any state from the repository.
 	/// Results in an empty states map.
@@ -66,27 +72,41 @@ where
 		state_snapshot_repository: Repository,
 		state_observer: Arc<StateObserver>,
 		state_initializer: Arc<StateInitializer>,
+		state_migrator: Arc<StateMigrator>,
 	) -> Self {
 		Self::new_with_states_map(
 			state_snapshot_repository,
 			state_observer,
 			state_initializer,
+			state_migrator,
 			Default::default(),
 		)
 	}
 
 	/// Create a new state handler and initialize its state map with the
 	/// states that are available in the snapshot repository.
+	///
+	/// States that were written by an older runtime version are migrated, and saved
+	/// as a new snapshot, before the handler is returned. This way, no block is ever
+	/// produced on top of a state that has not been migrated.
 	pub fn load_from_repository(
-		state_snapshot_repository: Repository,
+		mut state_snapshot_repository: Repository,
 		state_observer: Arc<StateObserver>,
 		state_initializer: Arc<StateInitializer>,
+		state_migrator: Arc<StateMigrator>,
 	) -> Result<Self> {
-		let states_map = Self::load_all_latest_snapshots(&state_snapshot_repository)?;
+		let mut states_map = Self::load_all_latest_snapshots(&state_snapshot_repository)?;
+		Self::migrate_all_states(
+			&mut state_snapshot_repository,
+			state_observer.as_ref(),
+			state_migrator.as_ref(),
+			&mut states_map,
+		)?;
 		Ok(Self::new_with_states_map(
 			state_snapshot_repository,
 			state_observer,
 			state_initializer,
+			state_migrator,
 			states_map,
 		))
 	}
@@ -95,6 +115,7 @@ where
 		state_snapshot_repository: Repository,
 		state_observer: Arc<StateObserver>,
 		state_initializer: Arc<StateInitializer>,
+		state_migrator: Arc<StateMigrator>,
 		states_map: StatesMap<Repository::StateType, Repository::HashType>,
 	) -> Self {
 		StateHandler {
@@ -102,6 +123,7 @@ where
 			states_map_lock: RwLock::new(states_map),
 			state_observer,
 			state_initializer,
+			state_migrator,
 		}
 	}
 
@@ -131,6 +153,39 @@ where
 		Ok(r)
 	}
 
+	fn migrate_all_states(
+		state_snapshot_repository: &mut Repository,
+		state_observer: &StateObserver,
+		state_migrator: &StateMigrator,
+		states_map: &mut StatesMap<Repository::StateType, Repository::HashType>,
+	) -> Result<()> {
+		for (shard, (state, state_hash)) in states_map.iter_mut() {
+			match state_migrator.migrate(shard, state)? {
+				MigrationOutcome::Migrated { .. } => {
+					state.prune_state_diff();
+					*state_hash = state.hash();
+					state_snapshot_repository.update(shard, state, *state_hash)?;
+					// The observer was initialized from the repository before the migration.
+					state_observer.queue_state_update(*shard, state.clone())?;
+				},
+				MigrationOutcome::UpToDate | MigrationOutcome::DryRun { .. } => {},
+			}
+		}
+		Ok(())
+	}
+
+	/// Migrates a single state, returns whether it has been changed.
+	fn migrate_state(
+		&self,
+		shard: &ShardIdentifier,
+		state: &mut Repository::StateType,
+	) -> Result<bool> {
+		match self.state_migrator.migrate(shard, state)? {
+			MigrationOutcome::Migrated { .. } => Ok(true),
+			MigrationOutcome::UpToDate | MigrationOutcome::DryRun { .. } => Ok(false),
+		}
+	}
+
 	fn update_state_snapshot(
 		&self,
 		shard: &ShardIdentifier,
@@ -144,14 +199,15 @@ where
 	}
 }
 
-impl<Repository, StateObserver, StateInitializer> HandleState
-	for StateHandler<Repository, StateObserver, StateInitializer>
+impl<Repository, StateObserver, StateInitializer, StateMigrator> HandleState
+	for StateHandler<Repository, StateObserver, StateInitializer, StateMigrator>
 where
 	Repository: VersionedStateAccess,
 	Repository::StateType: SgxExternalitiesTrait + Hash<Repository::HashType> + Debug,
 	Repository::HashType: Copy,
 	StateObserver: UpdateState<Repository::StateType>,
 	StateInitializer: InitializeState<StateType = Repository::StateType>,
+	StateMigrator: MigrateState<StateType = Repository::StateType>,
 {
 	type WriteLockPayload = StatesMap<Repository::StateType, Repository::HashType>;
 	type StateT = Repository::StateType;
@@ -162,6 +218,35 @@ where
 		self.reset(initialized_state, &shard)
 	}
 
+	fn load_shard(&self, shard: &ShardIdentifier) -> Result<Self::HashType> {
+		let mut states_map_lock = self.states_map_lock.write().map_err(|_| Error::LockPoisoning)?;
+		if let Some((_, state_hash)) = states_map_lock.get(shard) {
+			return Ok(*state_hash)
+		}
+
+		let mut state = self
+			.state_snapshot_repository
+			.read()
+			.map_err(|_| Error::LockPoisoning)?
+			.load_latest(shard)?;
+		if self.migrate_state(shard, &mut state)? {
+			// Persist the migrated state, so the snapshot matches the state we serve.
+			return self.write_after_mutation(state, states_map_lock, shard)
+		}
+		let state_hash = state.hash();
+		states_map_lock.insert(*shard, (state, state_hash));
+		Ok(state_hash)
+	}
+
+	fn unload_shard(&self, shard: &ShardIdentifier) -> Result<()> {
+		self.states_map_lock
+			.write()
+			.map_err(|_| Error::LockPoisoning)?
+			.remove(shard)
+			.map(|_| ())
+			.ok_or_else(|| Error::InvalidShard(*shard))
+	}
+
 	fn execute_on_current<E, R>(&self, shard: &ShardIdentifier, executing_function: E) -> Result<R>
 	where
 		E: FnOnce(&Self::StateT, Self::HashType) -> R,
@@ -221,16 +306,18 @@ where
 		Ok(state_hash)
 	}
 
-	fn reset(&self, state: Self::StateT, shard: &ShardIdentifier) -> Result<Self::HashType> {
+	fn reset(&self, mut state: Self::StateT, shard: &ShardIdentifier) -> Result<Self::HashType> {
 		debug!("Resetting state");
 		trace!("Resetting state: {:?}", state);
+		// Initialized and provisioned states may have been produced by another runtime version.
+		self.migrate_state(shard, &mut state)?;
 		let state_write_lock = self.states_map_lock.write().map_err(|_| Error::LockPoisoning)?;
 		self.write_after_mutation(state, state_write_lock, shard)
 	}
 }
 
-impl<Repository, StateObserver, StateInitializer> QueryShardState
-	for StateHandler<Repository, StateObserver, StateInitializer>
+impl<Repository, StateObserver, StateInitializer, StateMigrator> QueryShardState
+	for StateHandler<Repository, StateObserver, StateInitializer, StateMigrator>
 where
 	Repository: VersionedStateAccess,
 	Repository::StateType: Hash<Repository::HashType>,
@@ -252,7 +339,7 @@ where
 mod tests {
 	use super::*;
 	use crate::test::mocks::{
-		initialize_state_mock::InitializeStateMock,
+		initialize_state_mock::InitializeStateMock, migrate_state_mock::MigrateStateMock,
 		versioned_state_access_mock::VersionedStateAccessMock,
 	};
 	use codec::Encode;
@@ -266,8 +353,13 @@ mod tests {
 	type TestStateRepository = VersionedStateAccessMock<TestState, TestHash>;
 	type TestStateObserver = UpdateStateMock<TestState>;
 	type TestStateInitializer = InitializeStateMock<TestState>;
-	type TestStateHandler =
-		StateHandler<TestStateRepository, TestStateObserver, TestStateInitializer>;
+	type TestStateMigrator = MigrateStateMock<TestState>;
+	type TestStateHandler = StateHandler<
+		TestStateRepository,
+		TestStateObserver,
+		TestStateInitializer,
+		TestStateMigrator,
+	>;
 
 	fn create_state(content: u64) -> TestState {
 		let mut state = TestState::new(SgxExternalitiesType::default());
@@ -314,6 +406,7 @@ mod tests {
 			default_repository(),
 			state_observer.clone(),
 			state_initializer,
+			Arc::new(TestStateMigrator::default()),
 		));
 		state_handler.initialize_shard(shard_id).unwrap();
 
@@ -356,6 +449,26 @@ mod tests {
 		assert!(!state_handler.shard_exists(&ShardIdentifier::random()).unwrap());
 	}
 
+	#[test]
+	fn unloaded_shard_can_be_loaded_again() {
+		let shard_id = ShardIdentifier::random();
+		let state_handler = default_state_handler();
+		let state_hash = state_handler.initialize_shard(shard_id).unwrap();
+
+		state_handler.unload_shard(&shard_id).unwrap();
+		assert!(!state_handler.shard_exists(&shard_id).unwrap());
+		assert!(state_handler.unload_shard(&shard_id).is_err());
+
+		assert_eq!(state_hash, state_handler.load_shard(&shard_id).unwrap());
+		assert!(state_handler.shard_exists(&shard_id).unwrap());
+	}
+
+	#[test]
+	fn load_shard_fails_for_unknown_shard() {
+		let state_handler = default_state_handler();
+		assert!(state_handler.load_shard(&ShardIdentifier::random()).is_err());
+	}
+
 	#[test]
 	fn load_from_repository_works() {
 		let state_observer = Arc::new(TestStateObserver::default());
@@ -373,9 +486,13 @@ mod tests {
 		assert_eq!(3, repository.list_shards().unwrap().len());
 		assert!(repository.load_latest(&ShardIdentifier::from([3u8; 32])).is_err());
 
-		let state_handler =
-			TestStateHandler::load_from_repository(repository, state_observer, state_initializer)
-				.unwrap();
+		let state_handler = TestStateHandler::load_from_repository(
+			repository,
+			state_observer,
+			state_initializer,
+			Arc::new(TestStateMigrator::default()),
+		)
+		.unwrap();
 
 		assert_eq!(
 			2,
@@ -384,6 +501,85 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn load_from_repository_persists_migrated_states() {
+		let shard_id = ShardIdentifier::from([1u8; 32]);
+		let state_observer = Arc::new(TestStateObserver::default());
+		let state_initializer = Arc::new(TestStateInitializer::new(Default::default()));
+		let repository = TestStateRepository::new(HashMap::from([(
+			shard_id,
+			VecDeque::from([create_state(1)]),
+		)]));
+		let migrated_state = create_state_without_diff(2);
+
+		let state_handler = TestStateHandler::load_from_repository(
+			repository,
+			state_observer.clone(),
+			state_initializer,
+			Arc::new(TestStateMigrator::new(Some(migrated_state.clone()))),
+		)
+		.unwrap();
+
+		let (loaded_state, _) = state_handler.load_cloned(&shard_id).unwrap();
+		assert_eq!(migrated_state, loaded_state);
+		assert_eq!(
+			migrated_state,
+			state_handler
+				.state_snapshot_repository
+				.read()
+				.unwrap()
+				.load_latest(&shard_id)
+				.unwrap()
+		);
+		assert_eq!(
+			vec![(shard_id, migrated_state)],
+			state_observer.queued_updates.read().unwrap().clone()
+		);
+	}
+
+	#[test]
+	fn initialized_and_reset_states_are_migrated() {
+		let shard_id = ShardIdentifier::random();
+		let migrated_state = create_state_without_diff(7);
+		let state_handler =
+			state_handler_with_migrator(TestStateMigrator::new(Some(migrated_state.clone())));
+
+		state_handler.initialize_shard(shard_id).unwrap();
+		assert_eq!(migrated_state, state_handler.load_cloned(&shard_id).unwrap().0);
+
+		state_handler.reset(create_state(3), &shard_id).unwrap();
+		assert_eq!(migrated_state, state_handler.load_cloned(&shard_id).unwrap().0);
+	}
+
+	#[test]
+	fn load_shard_migrates_and_persists_state() {
+		let shard_id = ShardIdentifier::from([1u8; 32]);
+		let migrated_state = create_state_without_diff(2);
+		let repository = TestStateRepository::new(HashMap::from([(
+			shard_id,
+			VecDeque::from([create_state(1)]),
+		)]));
+		let state_handler = TestStateHandler::new(
+			repository,
+			Arc::new(TestStateObserver::default()),
+			Arc::new(TestStateInitializer::new(Default::default())),
+			Arc::new(TestStateMigrator::new(Some(migrated_state.clone()))),
+		);
+
+		state_handler.load_shard(&shard_id).unwrap();
+
+		assert_eq!(migrated_state, state_handler.load_cloned(&shard_id).unwrap().0);
+		assert_eq!(
+			migrated_state,
+			state_handler
+				.state_snapshot_repository
+				.read()
+				.unwrap()
+				.load_latest(&shard_id)
+				.unwrap()
+		);
+	}
+
 	#[test]
 	fn ensure_state_diff_is_discarded() {
 		let shard_id = ShardIdentifier::random();
@@ -403,9 +599,18 @@ mod tests {
 	}
 
 	fn default_state_handler() -> Arc<TestStateHandler> {
+		state_handler_with_migrator(TestStateMigrator::default())
+	}
+
+	fn state_handler_with_migrator(state_migrator: TestStateMigrator) -> Arc<TestStateHandler> {
 		let state_observer = Arc::new(TestStateObserver::default());
 		let state_initializer = Arc::new(TestStateInitializer::new(Default::default()));
-		Arc::new(TestStateHandler::new(default_repository(), state_observer, state_initializer))
+		Arc::new(TestStateHandler::new(
+			default_repository(),
+			state_observer,
+			state_initializer,
+			Arc::new(state_migrator),
+		))
 	}
 
 	fn default_repository() -> TestStateRepository {
@@ -0,0 +1,266 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	error::{Error, Result},
+	state_snapshot_repository::VersionedStateAccess,
+};
+use core::marker::PhantomData;
+use itp_stf_interface::RuntimeUpgradeInterface;
+use itp_types::ShardIdentifier;
+use log::*;
+use std::vec::Vec;
+
+/// What migrating the state of a shard amounted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationOutcome {
+	/// The state was already upgraded to the current runtime version.
+	UpToDate,
+	/// The migrations ran and the state is now at `to_spec_version`.
+	Migrated { from_spec_version: Option<u32>, to_spec_version: u32 },
+	/// The migrations ran on a copy of the state, the state itself was left untouched.
+	DryRun { from_spec_version: Option<u32>, to_spec_version: u32 },
+}
+
+/// Bring a state that was written by an older runtime version up to date.
+pub trait MigrateState {
+	type StateType;
+
+	fn migrate(
+		&self,
+		shard: &ShardIdentifier,
+		state: &mut Self::StateType,
+	) -> Result<MigrationOutcome>;
+}
+
+/// Runs the runtime upgrade migrations of the STF on states whose recorded
+/// runtime spec version is older than the one of the STF.
+///
+/// In dry-run mode, the migrations run on a copy of the state and are only reported.
+pub struct StateMigrator<State, Stf> {
+	dry_run: bool,
+	_phantom: PhantomData<(State, Stf)>,
+}
+
+impl<State, Stf> StateMigrator<State, Stf>
+where
+	Stf: RuntimeUpgradeInterface<State>,
+{
+	pub fn new(dry_run: bool) -> Self {
+		Self { dry_run, _phantom: Default::default() }
+	}
+}
+
+impl<State, Stf> MigrateState for StateMigrator<State, Stf>
+where
+	State: Clone,
+	Stf: RuntimeUpgradeInterface<State>,
+{
+	type StateType = State;
+
+	fn migrate(
+		&self,
+		shard: &ShardIdentifier,
+		state: &mut Self::StateType,
+	) -> Result<MigrationOutcome> {
+		let from_spec_version = Stf::state_spec_version(state);
+		let to_spec_version = Stf::runtime_spec_version();
+
+		match from_spec_version {
+			Some(version) if version == to_spec_version => return Ok(MigrationOutcome::UpToDate),
+			Some(version) if version > to_spec_version =>
+				return Err(Error::RuntimeDowngrade {
+					shard: *shard,
+					state_spec_version: version,
+					runtime_spec_version: to_spec_version,
+				}),
+			_ => {},
+		}
+
+		if self.dry_run {
+			let mut state_copy = state.clone();
+			Stf::on_runtime_upgrade(&mut state_copy);
+			info!(
+				"[dry-run] State of shard {:?} would be migrated from spec version {:?} to {}",
+				shard, from_spec_version, to_spec_version
+			);
+			return Ok(MigrationOutcome::DryRun { from_spec_version, to_spec_version })
+		}
+
+		info!(
+			"Migrating state of shard {:?} from spec version {:?} to {}",
+			shard, from_spec_version, to_spec_version
+		);
+		Stf::on_runtime_upgrade(state);
+		Ok(MigrationOutcome::Migrated { from_spec_version, to_spec_version })
+	}
+}
+
+/// Test harness: runs the migrations on the latest snapshot of every shard in the repository.
+///
+/// Pass a dry-run migrator to check what a new runtime would do with stored states
+/// without touching them.
+pub fn migrate_latest_snapshots<Repository, Migrator>(
+	repository: &Repository,
+	migrator: &Migrator,
+) -> Result<Vec<(ShardIdentifier, MigrationOutcome)>>
+where
+	Repository: VersionedStateAccess,
+	Migrator: MigrateState<StateType = Repository::StateType>,
+{
+	repository
+		.list_shards()?
+		.into_iter()
+		.map(|shard| {
+			let mut state = repository.load_latest(&shard)?;
+			migrator.migrate(&shard, &mut state).map(|outcome| (shard, outcome))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test::mocks::versioned_state_access_mock::VersionedStateAccessMock;
+	use codec::{Decode, Encode};
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use itp_types::H256;
+	use std::{
+		assert_matches::assert_matches,
+		collections::{HashMap, VecDeque},
+	};
+
+	const SPEC_VERSION_KEY: &[u8] = b"spec_version";
+	const MIGRATED_KEY: &[u8] = b"migrated";
+
+	type TestState = SgxExternalities;
+
+	/// STF at spec version 3, whose single migration leaves a marker in the state.
+	struct TestStf;
+
+	impl RuntimeUpgradeInterface<TestState> for TestStf {
+		fn state_spec_version(state: &mut TestState) -> Option<u32> {
+			state.get(SPEC_VERSION_KEY).map(|v| u32::decode(&mut v.as_slice()).unwrap())
+		}
+
+		fn runtime_spec_version() -> u32 {
+			3
+		}
+
+		fn on_runtime_upgrade(state: &mut TestState) {
+			state.insert(MIGRATED_KEY.to_vec(), true.encode());
+			state.insert(SPEC_VERSION_KEY.to_vec(), Self::runtime_spec_version().encode());
+		}
+	}
+
+	type TestMigrator = StateMigrator<TestState, TestStf>;
+
+	fn state_at(spec_version: Option<u32>) -> TestState {
+		let mut state = TestState::default();
+		if let Some(version) = spec_version {
+			state.insert(SPEC_VERSION_KEY.to_vec(), version.encode());
+		}
+		state
+	}
+
+	#[test]
+	fn state_at_current_version_is_not_migrated() {
+		let mut state = state_at(Some(3));
+
+		let outcome = TestMigrator::new(false).migrate(&shard(), &mut state).unwrap();
+
+		assert_eq!(outcome, MigrationOutcome::UpToDate);
+		assert!(!state.contains_key(MIGRATED_KEY));
+	}
+
+	#[test]
+	fn state_at_older_version_is_migrated() {
+		let mut state = state_at(Some(2));
+
+		let outcome = TestMigrator::new(false).migrate(&shard(), &mut state).unwrap();
+
+		assert_eq!(
+			outcome,
+			MigrationOutcome::Migrated { from_spec_version: Some(2), to_spec_version: 3 }
+		);
+		assert!(state.contains_key(MIGRATED_KEY));
+		assert_eq!(TestStf::state_spec_version(&mut state), Some(3));
+	}
+
+	#[test]
+	fn state_without_recorded_version_is_migrated() {
+		let mut state = state_at(None);
+
+		let outcome = TestMigrator::new(false).migrate(&shard(), &mut state).unwrap();
+
+		assert_eq!(
+			outcome,
+			MigrationOutcome::Migrated { from_spec_version: None, to_spec_version: 3 }
+		);
+	}
+
+	#[test]
+	fn dry_run_leaves_state_untouched() {
+		let mut state = state_at(Some(1));
+		let state_before = state.clone();
+
+		let outcome = TestMigrator::new(true).migrate(&shard(), &mut state).unwrap();
+
+		assert_eq!(
+			outcome,
+			MigrationOutcome::DryRun { from_spec_version: Some(1), to_spec_version: 3 }
+		);
+		assert_eq!(state, state_before);
+	}
+
+	#[test]
+	fn state_of_newer_runtime_is_refused() {
+		let mut state = state_at(Some(4));
+
+		let result = TestMigrator::new(false).migrate(&shard(), &mut state);
+
+		assert_matches!(result, Err(Error::RuntimeDowngrade { state_spec_version: 4, .. }));
+	}
+
+	#[test]
+	fn migrate_latest_snapshots_reports_every_shard() {
+		let outdated_shard = ShardIdentifier::from([1u8; 32]);
+		let current_shard = ShardIdentifier::from([2u8; 32]);
+		let repository = VersionedStateAccessMock::<TestState, H256>::new(HashMap::from([
+			(outdated_shard, VecDeque::from([state_at(Some(2)), state_at(Some(1))])),
+			(current_shard, VecDeque::from([state_at(Some(3))])),
+		]));
+
+		let mut outcomes = migrate_latest_snapshots(&repository, &TestMigrator::new(true)).unwrap();
+		outcomes.sort_by_key(|(shard, _)| *shard);
+
+		assert_eq!(
+			outcomes,
+			vec![
+				(
+					outdated_shard,
+					MigrationOutcome::DryRun { from_spec_version: Some(2), to_spec_version: 3 }
+				),
+				(current_shard, MigrationOutcome::UpToDate),
+			]
+		);
+	}
+
+	fn shard() -> ShardIdentifier {
+		ShardIdentifier::default()
+	}
+}
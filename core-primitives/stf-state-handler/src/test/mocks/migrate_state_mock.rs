@@ -0,0 +1,63 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	error::Result,
+	state_migrator::{MigrateState, MigrationOutcome},
+};
+use itp_types::ShardIdentifier;
+
+/// Migrate state mock.
+///
+/// Replaces every state it is given with `migrated_state`, if one is set.
+/// Leaves states untouched otherwise.
+pub struct MigrateStateMock<State> {
+	migrated_state: Option<State>,
+}
+
+impl<State> MigrateStateMock<State> {
+	pub fn new(migrated_state: Option<State>) -> Self {
+		Self { migrated_state }
+	}
+}
+
+impl<State> Default for MigrateStateMock<State> {
+	fn default() -> Self {
+		Self::new(None)
+	}
+}
+
+impl<State> MigrateState for MigrateStateMock<State>
+where
+	State: Clone,
+{
+	type StateType = State;
+
+	fn migrate(
+		&self,
+		_shard: &ShardIdentifier,
+		state: &mut Self::StateType,
+	) -> Result<MigrationOutcome> {
+		match &self.migrated_state {
+			Some(migrated_state) => {
+				*state = migrated_state.clone();
+				Ok(MigrationOutcome::Migrated { from_spec_version: None, to_spec_version: 1 })
+			},
+			None => Ok(MigrationOutcome::UpToDate),
+		}
+	}
+}
@@ -16,5 +16,6 @@
 */
 
 pub mod initialize_state_mock;
+pub mod migrate_state_mock;
 pub mod state_key_repository_mock;
 pub mod versioned_state_access_mock;
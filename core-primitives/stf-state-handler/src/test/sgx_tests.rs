@@ -23,7 +23,9 @@ use crate::{
 	state_handler::StateHandler,
 	state_snapshot_repository::{StateSnapshotRepository, VersionedStateAccess},
 	state_snapshot_repository_loader::StateSnapshotRepositoryLoader,
-	test::mocks::initialize_state_mock::InitializeStateMock,
+	test::mocks::{
+		initialize_state_mock::InitializeStateMock, migrate_state_mock::MigrateStateMock,
+	},
 };
 use codec::{Decode, Encode};
 use itp_hashing::Hash;
@@ -43,12 +45,14 @@ const STATE_SNAPSHOTS_CACHE_SIZE: usize = 3;
 
 type StateKeyRepository = KeyRepository<Aes, AesSeal>;
 type TestStateInitializer = InitializeStateMock<SgxExternalities>;
+type TestStateMigrator = MigrateStateMock<SgxExternalities>;
 type TestStateFileIo = SgxStateFileIo<StateKeyRepository, SgxExternalities>;
 type TestStateRepository = StateSnapshotRepository<TestStateFileIo>;
 type TestStateRepositoryLoader =
 	StateSnapshotRepositoryLoader<TestStateFileIo, TestStateInitializer>;
 type TestStateObserver = StateObserver<SgxExternalities>;
-type TestStateHandler = StateHandler<TestStateRepository, TestStateObserver, TestStateInitializer>;
+type TestStateHandler =
+	StateHandler<TestStateRepository, TestStateObserver, TestStateInitializer, TestStateMigrator>;
 
 // Fixme: Move this test to sgx-runtime:
 //
@@ -327,6 +331,7 @@ fn initialize_state_handler(
 			state_snapshot_repository,
 			state_observer,
 			state_initializer,
+			Arc::new(TestStateMigrator::default()),
 		)
 		.unwrap(),
 	)
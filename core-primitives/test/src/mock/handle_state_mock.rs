@@ -56,6 +56,19 @@ impl HandleState for HandleStateMock {
 		self.reset(StfState::default(), &shard)
 	}
 
+	fn load_shard(&self, shard: &ShardIdentifier) -> Result<Self::HashType> {
+		self.load_cloned(shard).map(|(_, state_hash)| state_hash)
+	}
+
+	fn unload_shard(&self, shard: &ShardIdentifier) -> Result<()> {
+		self.state_map
+			.write()
+			.unwrap()
+			.remove(shard)
+			.map(|_| ())
+			.ok_or_else(|| Error::Other(format!("shard is not initialized {:?}", shard).into()))
+	}
+
 	fn execute_on_current<E, R>(&self, shard: &ShardIdentifier, executing_function: E) -> Result<R>
 	where
 		E: FnOnce(&Self::StateT, Self::HashType) -> R,
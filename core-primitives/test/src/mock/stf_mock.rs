@@ -106,6 +106,14 @@ impl TrustedCallMock {
 			Self::waste_time_ms(sender_account, ..) => sender_account,
 		}
 	}
+
+	pub fn call_type(&self) -> &'static str {
+		match self {
+			Self::noop(..) => "noop",
+			Self::balance_transfer(..) => "balance_transfer",
+			Self::waste_time_ms(..) => "waste_time_ms",
+		}
+	}
 }
 
 impl TrustedCallSigning<TrustedCallSignedMock> for TrustedCallMock {
@@ -199,6 +207,10 @@ impl TrustedCallVerification for TrustedCallSignedMock {
 	fn verify_signature(&self, _mrenclave: &[u8; 32], _shard: &ShardIdentifier) -> bool {
 		true
 	}
+
+	fn call_type(&self) -> &'static str {
+		self.call.call_type()
+	}
 }
 
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
@@ -24,6 +24,13 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
+pub mod trusted_time;
+
+pub use trusted_time::{
+	host_time_drift_millis, observe_parentchain_timestamp, trusted_duration_now,
+	trusted_now_as_millis, HostTimeDriftError,
+};
+
 use std::time::{Duration, SystemTime};
 
 /// Returns the current timestamp based on the unix epoch in seconds.
@@ -32,6 +39,8 @@ pub fn now_as_secs() -> u64 {
 }
 
 /// Returns current duration since unix epoch in millis as u64.
+///
+/// This is the host time, use `trusted_now_as_millis` where the host must not control the time.
 pub fn now_as_millis() -> u64 {
 	duration_now().as_millis() as u64
 }
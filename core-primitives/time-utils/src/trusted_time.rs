@@ -0,0 +1,223 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Time source, which bounds the host time by verified parentchain timestamps.
+//!
+//! Inside the enclave, the system time is provided by the untrusted host. The timestamps of the
+//! `pallet_timestamp` of imported parentchain blocks are verified by the light client, so the host
+//! time must not be earlier than the latest of them, nor much later. Readings slightly outside of
+//! these bounds are clamped to them, readings further off are rejected.
+
+use crate::duration_now;
+use core::{
+	fmt,
+	sync::atomic::{AtomicI64, AtomicU64, Ordering},
+};
+use std::time::Duration;
+
+/// How far the trusted time may be ahead of the latest verified parentchain timestamp.
+///
+/// Parentchain blocks are imported before every sidechain slot, so this only becomes relevant if
+/// the host withholds parentchain blocks, or the parentchain stalls.
+pub const MAX_TIME_AHEAD_OF_PARENTCHAIN: Duration = Duration::from_secs(60);
+
+/// How far the host time may be outside of the bounds to be clamped instead of rejected.
+pub const MAX_CLAMPED_HOST_TIME_DRIFT: Duration = Duration::from_secs(30);
+
+static TRUSTED_TIME_ORACLE: TrustedTimeOracle = TrustedTimeOracle::new();
+
+/// Records the timestamp (millis since the unix epoch) of a verified parentchain block.
+pub fn observe_parentchain_timestamp(timestamp: u64) {
+	TRUSTED_TIME_ORACLE.observe_parentchain_timestamp(timestamp)
+}
+
+/// Returns the current duration since unix epoch, bounded by the verified parentchain timestamps.
+pub fn trusted_duration_now() -> Result<Duration, HostTimeDriftError> {
+	TRUSTED_TIME_ORACLE.trusted_time(duration_now())
+}
+
+/// Returns the current trusted duration since unix epoch in millis as u64.
+pub fn trusted_now_as_millis() -> Result<u64, HostTimeDriftError> {
+	trusted_duration_now().map(|now| now.as_millis() as u64)
+}
+
+/// Drift of the host time from the trusted time in millis at the last reading. Positive if the host
+/// clock is ahead.
+pub fn host_time_drift_millis() -> i64 {
+	TRUSTED_TIME_ORACLE.host_time_drift_millis()
+}
+
+/// The host time is too far off the verified parentchain timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostTimeDriftError {
+	/// The host time in millis since the unix epoch.
+	pub host_time: u64,
+	/// Drift of the host time from the closest trusted time in millis.
+	pub drift: i64,
+}
+
+impl fmt::Display for HostTimeDriftError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"Host time {} ms drifted by {} ms from the verified parentchain timestamps",
+			self.host_time, self.drift
+		)
+	}
+}
+
+impl std::error::Error for HostTimeDriftError {}
+
+/// Bounds the host time by verified parentchain timestamps and keeps it monotonic.
+#[derive(Debug, Default)]
+pub struct TrustedTimeOracle {
+	/// Latest verified parentchain timestamp in millis, zero if there is none yet.
+	latest_parentchain_timestamp: AtomicU64,
+	/// Latest trusted time handed out in millis.
+	latest_trusted_time: AtomicU64,
+	/// Drift of the host time at the last reading in millis.
+	host_time_drift: AtomicI64,
+}
+
+impl TrustedTimeOracle {
+	pub const fn new() -> Self {
+		Self {
+			latest_parentchain_timestamp: AtomicU64::new(0),
+			latest_trusted_time: AtomicU64::new(0),
+			host_time_drift: AtomicI64::new(0),
+		}
+	}
+
+	/// Records the timestamp of a verified parentchain block. Earlier timestamps are ignored.
+	pub fn observe_parentchain_timestamp(&self, timestamp: u64) {
+		self.latest_parentchain_timestamp.fetch_max(timestamp, Ordering::SeqCst);
+	}
+
+	/// Bounds `host_time` by the latest verified parentchain timestamp and by the time handed out
+	/// before.
+	///
+	/// Without a verified parentchain timestamp, the host time is only kept monotonic.
+	pub fn trusted_time(&self, host_time: Duration) -> Result<Duration, HostTimeDriftError> {
+		let host_time = host_time.as_millis() as u64;
+		let latest_trusted_time = self.latest_trusted_time.load(Ordering::SeqCst);
+		let (lower_bound, upper_bound) =
+			match self.latest_parentchain_timestamp.load(Ordering::SeqCst) {
+				0 => (latest_trusted_time, u64::MAX),
+				parentchain_timestamp => (
+					parentchain_timestamp.max(latest_trusted_time),
+					parentchain_timestamp
+						.saturating_add(MAX_TIME_AHEAD_OF_PARENTCHAIN.as_millis() as u64),
+				),
+			};
+
+		let trusted_time = host_time.clamp(lower_bound, upper_bound.max(lower_bound));
+		let drift = host_time as i64 - trusted_time as i64;
+		self.host_time_drift.store(drift, Ordering::SeqCst);
+
+		if drift.unsigned_abs() > MAX_CLAMPED_HOST_TIME_DRIFT.as_millis() as u64 {
+			return Err(HostTimeDriftError { host_time, drift })
+		}
+		self.latest_trusted_time.fetch_max(trusted_time, Ordering::SeqCst);
+		Ok(Duration::from_millis(trusted_time))
+	}
+
+	pub fn host_time_drift_millis(&self) -> i64 {
+		self.host_time_drift.load(Ordering::SeqCst)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PARENTCHAIN_TIMESTAMP: u64 = 1_700_000_000_000;
+
+	fn millis(millis: u64) -> Duration {
+		Duration::from_millis(millis)
+	}
+
+	fn oracle() -> TrustedTimeOracle {
+		let oracle = TrustedTimeOracle::new();
+		oracle.observe_parentchain_timestamp(PARENTCHAIN_TIMESTAMP);
+		oracle
+	}
+
+	#[test]
+	fn host_time_within_bounds_is_trusted() {
+		let oracle = oracle();
+		let host_time = PARENTCHAIN_TIMESTAMP + 6_000;
+
+		assert_eq!(oracle.trusted_time(millis(host_time)).unwrap(), millis(host_time));
+		assert_eq!(oracle.host_time_drift_millis(), 0);
+	}
+
+	#[test]
+	fn host_time_without_parentchain_timestamp_is_only_kept_monotonic() {
+		let oracle = TrustedTimeOracle::new();
+
+		assert_eq!(oracle.trusted_time(millis(10_000)).unwrap(), millis(10_000));
+		assert_eq!(oracle.trusted_time(millis(9_000)).unwrap(), millis(10_000));
+		assert_eq!(oracle.host_time_drift_millis(), -1_000);
+	}
+
+	#[test]
+	fn host_time_slightly_behind_parentchain_is_clamped() {
+		let oracle = oracle();
+
+		assert_eq!(
+			oracle.trusted_time(millis(PARENTCHAIN_TIMESTAMP - 5_000)).unwrap(),
+			millis(PARENTCHAIN_TIMESTAMP)
+		);
+		assert_eq!(oracle.host_time_drift_millis(), -5_000);
+	}
+
+	#[test]
+	fn host_time_slightly_ahead_of_parentchain_is_clamped() {
+		let oracle = oracle();
+		let upper_bound = PARENTCHAIN_TIMESTAMP + MAX_TIME_AHEAD_OF_PARENTCHAIN.as_millis() as u64;
+
+		assert_eq!(oracle.trusted_time(millis(upper_bound + 5_000)).unwrap(), millis(upper_bound));
+		assert_eq!(oracle.host_time_drift_millis(), 5_000);
+	}
+
+	#[test]
+	fn host_time_far_off_is_rejected() {
+		let oracle = oracle();
+		let max_drift = MAX_CLAMPED_HOST_TIME_DRIFT.as_millis() as u64;
+		let upper_bound = PARENTCHAIN_TIMESTAMP + MAX_TIME_AHEAD_OF_PARENTCHAIN.as_millis() as u64;
+
+		assert_eq!(
+			oracle.trusted_time(millis(PARENTCHAIN_TIMESTAMP - max_drift - 1)),
+			Err(HostTimeDriftError {
+				host_time: PARENTCHAIN_TIMESTAMP - max_drift - 1,
+				drift: -(max_drift as i64) - 1
+			})
+		);
+		assert!(oracle.trusted_time(millis(upper_bound + max_drift + 1)).is_err());
+		assert_eq!(oracle.host_time_drift_millis(), max_drift as i64 + 1);
+	}
+
+	#[test]
+	fn earlier_parentchain_timestamps_are_ignored() {
+		let oracle = oracle();
+		oracle.observe_parentchain_timestamp(PARENTCHAIN_TIMESTAMP - 60_000);
+
+		assert_eq!(
+			oracle.trusted_time(millis(PARENTCHAIN_TIMESTAMP - 1_000)).unwrap(),
+			millis(PARENTCHAIN_TIMESTAMP)
+		);
+	}
+}
@@ -26,7 +26,12 @@ use crate::{
 	traits::{AuthorApi, OnBlockImported},
 };
 use codec::{Decode, Encode};
-use itp_enclave_metrics::EnclaveMetric;
+use itp_enclave_metrics::{
+	trace::{
+		current_request_trace_context, TraceSpan, GLOBAL_TRACE_SPAN_BUFFER, SUBMIT_OPERATION_SPAN,
+	},
+	EnclaveMetric,
+};
 use itp_ocall_api::EnclaveMetricsOCallApi;
 use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt};
 use itp_stf_primitives::{
@@ -34,6 +39,7 @@ use itp_stf_primitives::{
 	types::{AccountId, TrustedOperation as StfTrustedOperation, TrustedOperationOrHash},
 };
 use itp_stf_state_handler::query_shard_state::QueryShardState;
+use itp_time_utils::duration_now;
 use itp_top_pool::{
 	error::{Error as PoolError, IntoPoolError},
 	primitives::{
@@ -48,7 +54,7 @@ use jsonrpc_core::{
 };
 use log::*;
 use sp_runtime::generic;
-use std::{boxed::Box, sync::Arc, vec::Vec};
+use std::{boxed::Box, format, sync::Arc, vec::Vec};
 
 /// Define type of TOP filter that is used in the Author
 #[cfg(feature = "sidechain")]
@@ -151,6 +157,8 @@ where
 		shard: ShardIdentifier,
 		submission_mode: TopSubmissionMode,
 	) -> PoolFuture<TxHash, RpcError> {
+		let submission_start = duration_now();
+
 		// check if shard exists
 		match self.state_facade.shard_exists(&shard) {
 			Err(_) => return Box::pin(ready(Err(ClientError::InvalidShard.into()))),
@@ -194,17 +202,31 @@ where
 			warn!("Failed to update metric for top pool size: {:?}", e);
 		}
 
+		let operation_hash = self.hash_of(&trusted_operation);
 		if let Some(trusted_call_signed) = trusted_operation.to_call() {
 			debug!(
 				"Submitting trusted call to TOP pool: {:?}, TOP hash: {:?}",
-				trusted_call_signed,
-				self.hash_of(&trusted_operation)
+				trusted_call_signed, operation_hash
 			);
 		} else if let StfTrustedOperation::<TCS, G>::get(ref getter) = trusted_operation {
 			debug!(
 				"Submitting trusted or public getter to TOP pool: {:?}, TOP hash: {:?}",
-				getter,
-				self.hash_of(&trusted_operation)
+				getter, operation_hash
+			);
+		}
+
+		if GLOBAL_TRACE_SPAN_BUFFER.is_enabled() {
+			if let Some(context) = current_request_trace_context() {
+				GLOBAL_TRACE_SPAN_BUFFER.record_context(operation_hash.as_fixed_bytes(), context);
+			}
+			GLOBAL_TRACE_SPAN_BUFFER.record(
+				TraceSpan::root(
+					operation_hash.as_fixed_bytes(),
+					SUBMIT_OPERATION_SPAN,
+					submission_start,
+					duration_now(),
+				)
+				.with_attribute("shard", format!("{:?}", shard)),
 			);
 		}
 
@@ -110,6 +110,32 @@ pub enum TrustedOperationStatus {
 	Invalid,
 }
 
+/// Topic of a direct RPC subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Encode, Decode)]
+pub enum SubscriptionTopic {
+	/// Headers of newly imported sidechain blocks.
+	NewHeads,
+	/// Events of the given account on the given shard.
+	AccountEvents(ShardIdentifier, AccountId),
+}
+
+/// Limits of the trusted RPC server, as configured by the operator of the worker.
+///
+/// Limits that are not set fall back to the defaults of the enclave.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrustedRpcServerLimits {
+	pub max_connections: Option<u32>,
+	pub max_requests_per_second_per_ip: Option<u32>,
+	pub max_requests_per_second_per_connection: Option<u32>,
+	/// In bytes.
+	pub max_message_size: Option<u32>,
+	/// In seconds.
+	pub idle_timeout: Option<u64>,
+	pub max_outbound_queue: Option<u32>,
+	/// Maximum number of requests in a JSON-RPC batch.
+	pub max_batch_size: Option<u32>,
+}
+
 #[derive(Encode, Decode, Clone, Debug, PartialEq)]
 pub enum WorkerRequest {
 	ChainStorage(Vec<u8>, Option<BlockHash>), // (storage_key, at_block)
@@ -91,6 +91,10 @@ pub trait FilterEvents {
 	fn get_extrinsic_statuses(&self) -> core::result::Result<Vec<ExtrinsicStatus>, Self::Error>;
 
 	fn get_transfer_events(&self) -> core::result::Result<Vec<BalanceTransfer>, Self::Error>;
+
+	fn get_exchange_rate_updated_events(
+		&self,
+	) -> core::result::Result<Vec<ExchangeRateUpdated>, Self::Error>;
 }
 
 #[derive(Encode, Decode, Debug)]
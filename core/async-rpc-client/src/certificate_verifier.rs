@@ -0,0 +1,169 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Verifies the certificate of the worker against the pinned one.
+//!
+//! The certificate chain is not validated against any root, the pin replaces it. The TLS
+//! handshake still proves that the worker holds the private key of the presented certificate.
+
+use crate::config::ServerCertificatePin;
+use log::*;
+use rustls::{
+	Certificate, ClientConfig as TlsClientConfig, RootCertStore, ServerCertVerified,
+	ServerCertVerifier, TLSError,
+};
+use std::sync::Arc;
+use webpki::DNSNameRef;
+use yasna::Tag;
+
+/// DER encoding of an ed25519 `SubjectPublicKeyInfo` up to the public key, see RFC 8410.
+const ED25519_SUBJECT_PUBLIC_KEY_INFO_PREFIX: [u8; 12] =
+	[0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+/// TLS client config, which only accepts the pinned certificate.
+pub(crate) fn tls_client_config(pin: &ServerCertificatePin) -> TlsClientConfig {
+	let mut config = TlsClientConfig::new();
+	// The worker is identified by the pin, not by its name.
+	config.enable_sni = false;
+	config
+		.dangerous()
+		.set_certificate_verifier(Arc::new(PinnedCertificateVerifier { pin: pin.clone() }));
+	config
+}
+
+struct PinnedCertificateVerifier {
+	pin: ServerCertificatePin,
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+	fn verify_server_cert(
+		&self,
+		_roots: &RootCertStore,
+		presented_certs: &[Certificate],
+		_dns_name: DNSNameRef<'_>,
+		_ocsp_response: &[u8],
+	) -> Result<ServerCertVerified, TLSError> {
+		let end_entity = presented_certs.first().ok_or(TLSError::NoCertificatesPresented)?;
+
+		if matches_pin(&self.pin, &end_entity.0) {
+			Ok(ServerCertVerified::assertion())
+		} else {
+			warn!("Certificate of the worker does not match the pinned one");
+			Err(TLSError::General("Certificate does not match the pinned one".into()))
+		}
+	}
+}
+
+fn matches_pin(pin: &ServerCertificatePin, certificate: &[u8]) -> bool {
+	match pin {
+		ServerCertificatePin::EnclaveSigningKey(public_key) => {
+			let pinned_subject_public_key_info =
+				[ED25519_SUBJECT_PUBLIC_KEY_INFO_PREFIX.as_slice(), public_key.as_slice()].concat();
+			subject_public_key_info(certificate).as_deref()
+				== Some(pinned_subject_public_key_info.as_slice())
+		},
+		ServerCertificatePin::Certificate(pinned_certificate) =>
+			pinned_certificate.as_slice() == certificate,
+		ServerCertificatePin::AcceptAny => true,
+	}
+}
+
+/// The DER encoded `SubjectPublicKeyInfo` of a DER encoded X.509 certificate, see RFC 5280.
+///
+/// `None` if the certificate cannot be parsed.
+fn subject_public_key_info(certificate: &[u8]) -> Option<Vec<u8>> {
+	yasna::parse_der(certificate, |reader| {
+		reader.read_sequence(|certificate| {
+			let subject_public_key_info = certificate.next().read_sequence(|tbs_certificate| {
+				tbs_certificate.read_optional(|version| {
+					version.read_tagged(Tag::context(0), |version| version.read_der())
+				})?;
+				// serialNumber, signature, issuer, validity and subject
+				for _ in 0..5 {
+					tbs_certificate.next().read_der()?;
+				}
+				let subject_public_key_info = tbs_certificate.next().read_der()?;
+				// issuerUniqueID, subjectUniqueID and extensions
+				while tbs_certificate.read_optional(|field| field.read_der())?.is_some() {}
+				Ok(subject_public_key_info)
+			})?;
+			// signatureAlgorithm and signatureValue
+			certificate.next().read_der()?;
+			certificate.next().read_der()?;
+			Ok(subject_public_key_info)
+		})
+	})
+	.ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itc_tls_websocket_server::certificate_generation::ed25519_self_signed_certificate;
+	use sp_core::{ed25519, Pair};
+
+	fn signer(seed: u8) -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[seed; 32])
+	}
+
+	fn certificate(signer: ed25519::Pair, name: &str) -> Vec<u8> {
+		ed25519_self_signed_certificate(signer, name).unwrap().serialize_der().unwrap()
+	}
+
+	fn subject_public_key_info_of(public_key: &[u8; 32]) -> Vec<u8> {
+		[ED25519_SUBJECT_PUBLIC_KEY_INFO_PREFIX.as_slice(), public_key.as_slice()].concat()
+	}
+
+	#[test]
+	fn certificate_of_pinned_signing_key_is_accepted() {
+		let pin = ServerCertificatePin::EnclaveSigningKey(signer(1).public().0);
+
+		assert!(matches_pin(&pin, &certificate(signer(1), "Enclave")));
+		assert!(!matches_pin(&pin, &certificate(signer(2), "Enclave")));
+	}
+
+	#[test]
+	fn pinned_key_embedded_elsewhere_in_certificate_is_not_accepted() {
+		let pinned_key = signer(1).public().0;
+		let pin = ServerCertificatePin::EnclaveSigningKey(pinned_key);
+		// The subject alternative name contains the subject public key info of the pinned key.
+		let name = String::from_utf8(subject_public_key_info_of(&pinned_key)).unwrap();
+		let forged_certificate = certificate(signer(2), &name);
+
+		assert!(forged_certificate
+			.windows(name.len())
+			.any(|window| window == subject_public_key_info_of(&pinned_key).as_slice()));
+		assert!(!matches_pin(&pin, &forged_certificate));
+	}
+
+	#[test]
+	fn key_without_certificate_is_not_accepted() {
+		let public_key = signer(1).public().0;
+		let pin = ServerCertificatePin::EnclaveSigningKey(public_key);
+
+		assert!(!matches_pin(&pin, &subject_public_key_info_of(&public_key)));
+	}
+
+	#[test]
+	fn only_pinned_certificate_is_accepted() {
+		let pinned_certificate = certificate(signer(1), "Enclave");
+		let pin = ServerCertificatePin::Certificate(pinned_certificate.clone());
+
+		assert!(matches_pin(&pin, &pinned_certificate));
+		assert!(!matches_pin(&pin, &certificate(signer(2), "Enclave")));
+	}
+}
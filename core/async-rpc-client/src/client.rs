@@ -0,0 +1,329 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	config::ClientConfig,
+	connection::{run_connection, Command},
+	error::{Error, Result},
+	pending_requests::{RequestId, ResponseSink},
+	subscription::{status_error, Subscription, TrustedOperationWatch},
+};
+use codec::{Decode, Encode};
+use itp_rpc::{Id, RpcRequest, RpcReturnValue};
+use itp_types::{DirectRequestStatus, Request, ShardIdentifier};
+use itp_utils::ToHexPrefixed;
+use log::*;
+use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+use sp_core::H256;
+use std::{
+	sync::{
+		atomic::{AtomicU32, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+use tokio::{
+	sync::{mpsc, oneshot},
+	time::timeout,
+};
+
+/// Asynchronous client of the direct RPC interface of a worker.
+///
+/// Clones share the connection, which is closed once all clones and subscriptions are dropped.
+/// Hence, they share the request rate limit of that connection as well, see the crate docs.
+#[derive(Clone)]
+pub struct AsyncDirectClient {
+	commands: mpsc::UnboundedSender<Command>,
+	next_id: Arc<AtomicU32>,
+	request_timeout: Duration,
+}
+
+impl AsyncDirectClient {
+	/// Creates the client and connects to the worker in the background.
+	///
+	/// Has to be called within a tokio runtime, which then runs the connection. Requests wait
+	/// until the connection is established.
+	pub fn new(config: ClientConfig) -> Self {
+		let (commands, commands_receiver) = mpsc::unbounded_channel();
+		let request_timeout = config.request_timeout;
+		tokio::spawn(run_connection(config, commands_receiver));
+		AsyncDirectClient { commands, next_id: Default::default(), request_timeout }
+	}
+
+	/// Sends a request and waits for its response.
+	pub async fn request(&self, method: &str, params: Vec<String>) -> Result<RpcReturnValue> {
+		let (id, request) = self.compose_request(method, params)?;
+		let (sender, receiver) = oneshot::channel();
+		self.send(Command::Send { id, request, sink: ResponseSink::Call(sender) })?;
+
+		match timeout(self.request_timeout, receiver).await {
+			Ok(response) => response.map_err(|_| Error::ClientClosed)?,
+			Err(_) => {
+				let _ = self.commands.send(Command::Cancel { id });
+				Err(Error::Timeout(self.request_timeout))
+			},
+		}
+	}
+
+	/// Sends a request, which the worker answers with updates until it stops watching it, e.g.
+	/// `author_submitAndWatchExtrinsic`.
+	///
+	/// The watch ends when the connection is lost, because sending the request again could e.g.
+	/// submit a trusted call twice.
+	pub fn watch(&self, method: &str, params: Vec<String>) -> Result<Subscription> {
+		self.open_subscription(method, params, false)
+	}
+
+	/// Subscribes to notifications of the worker, e.g. `author_subscribeAccountEvents`.
+	///
+	/// The subscription is renewed when the connection is re-established.
+	pub fn subscribe(&self, method: &str, params: Vec<String>) -> Result<Subscription> {
+		self.open_subscription(method, params, true)
+	}
+
+	/// Shielding key of the enclave, to encrypt trusted operations with.
+	pub async fn get_rsa_pubkey(&self) -> Result<Rsa3072PubKey> {
+		let shielding_key: String = self.request_ok("author_getShieldingKey", Vec::new()).await?;
+		Ok(serde_json::from_str(&shielding_key)?)
+	}
+
+	pub async fn get_mu_ra_url(&self) -> Result<String> {
+		self.request_ok("author_getMuRaUrl", Vec::new()).await
+	}
+
+	pub async fn get_untrusted_worker_url(&self) -> Result<String> {
+		self.request_ok("author_getUntrustedUrl", Vec::new()).await
+	}
+
+	/// Executes a signed getter on the state of `shard`. `None` if the getter has no value.
+	pub async fn execute_getter<Value: Decode>(
+		&self,
+		shard: ShardIdentifier,
+		getter: &impl Encode,
+	) -> Result<Option<Value>> {
+		let request = Request { shard, cyphertext: getter.encode() };
+		let value: Option<Vec<u8>> =
+			self.request_ok("state_executeGetter", vec![request.to_hex()]).await?;
+		Ok(value.map(|value| Value::decode(&mut value.as_slice())).transpose()?)
+	}
+
+	/// Submits a trusted operation, encrypted with the shielding key, and returns its hash.
+	pub async fn submit_trusted_operation(
+		&self,
+		shard: ShardIdentifier,
+		shielding_key: &Rsa3072PubKey,
+		operation: &impl Encode,
+	) -> Result<H256> {
+		let request = encrypted_request(shard, shielding_key, operation)?;
+		let return_value = self.request("author_submitExtrinsic", vec![request]).await?;
+		match return_value.status {
+			DirectRequestStatus::TrustedOperationStatus(_) =>
+				Ok(H256::decode(&mut return_value.value.as_slice())?),
+			_ => Err(status_error(&return_value)),
+		}
+	}
+
+	/// Submits a trusted operation, encrypted with the shielding key, and watches its status.
+	pub async fn submit_and_watch_trusted_operation(
+		&self,
+		shard: ShardIdentifier,
+		shielding_key: &Rsa3072PubKey,
+		operation: &impl Encode,
+	) -> Result<TrustedOperationWatch> {
+		let request = encrypted_request(shard, shielding_key, operation)?;
+		let subscription = self.watch("author_submitAndWatchExtrinsic", vec![request])?;
+		timeout(self.request_timeout, TrustedOperationWatch::confirm(subscription))
+			.await
+			.map_err(|_| Error::Timeout(self.request_timeout))?
+	}
+
+	/// Sends a request and decodes its value, if the worker answered with `Ok`.
+	async fn request_ok<Value: Decode>(&self, method: &str, params: Vec<String>) -> Result<Value> {
+		let return_value = self.request(method, params).await?;
+		match return_value.status {
+			DirectRequestStatus::Ok => Ok(Value::decode(&mut return_value.value.as_slice())?),
+			_ => Err(status_error(&return_value)),
+		}
+	}
+
+	fn open_subscription(
+		&self,
+		method: &str,
+		params: Vec<String>,
+		renew_on_reconnect: bool,
+	) -> Result<Subscription> {
+		let (id, request) = self.compose_request(method, params)?;
+		let (sender, receiver) = mpsc::unbounded_channel();
+		let sink = if renew_on_reconnect {
+			ResponseSink::Subscription { sender, request: request.clone() }
+		} else {
+			ResponseSink::Watch(sender)
+		};
+		self.send(Command::Send { id, request, sink })?;
+		Ok(Subscription::new(id, receiver, self.commands.clone()))
+	}
+
+	/// Composes a request with an id, that is unique on the connection.
+	fn compose_request(&self, method: &str, params: Vec<String>) -> Result<(RequestId, String)> {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		let request = serde_json::to_string(&RpcRequest {
+			jsonrpc: "2.0".into(),
+			method: method.into(),
+			params,
+			id: Id::Number(id),
+		})?;
+		trace!("Composed request {}: {}", id, method);
+		Ok((id, request))
+	}
+
+	fn send(&self, command: Command) -> Result<()> {
+		self.commands.send(command).map_err(|_| Error::ClientClosed)
+	}
+}
+
+fn encrypted_request(
+	shard: ShardIdentifier,
+	shielding_key: &Rsa3072PubKey,
+	operation: &impl Encode,
+) -> Result<String> {
+	let mut cyphertext = Vec::new();
+	shielding_key
+		.encrypt_buffer(&operation.encode(), &mut cyphertext)
+		.map_err(|e| {
+			Error::Custom(format!("Failed to encrypt trusted operation: {:?}", e).into())
+		})?;
+	Ok(Request { shard, cyphertext }.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::ServerCertificatePin;
+	use itc_tls_websocket_server::{
+		error::WebSocketResult,
+		test::fixtures::{
+			test_cert::get_test_certificate_chain,
+			test_server_config_provider::TestServerConfigProvider,
+		},
+		ws_server::TungsteniteWsServer,
+		ConnectionToken, WebSocketMessageHandler, WebSocketServer,
+	};
+	use itp_networking_utils::ports::get_available_port_in_range;
+	use itp_rpc::RpcResponse;
+	use std::thread;
+
+	type TestServer = TungsteniteWsServer<MethodEchoHandler, TestServerConfigProvider>;
+
+	/// Answers every request with its method name.
+	struct MethodEchoHandler;
+
+	impl WebSocketMessageHandler for MethodEchoHandler {
+		fn handle_message(
+			&self,
+			_connection_token: ConnectionToken,
+			message: String,
+		) -> WebSocketResult<Option<String>> {
+			let request: RpcRequest = serde_json::from_str(&message).unwrap();
+			let result =
+				RpcReturnValue::new(request.method.encode(), false, DirectRequestStatus::Ok)
+					.to_hex();
+			let response = RpcResponse { jsonrpc: "2.0".into(), result, id: request.id };
+			Ok(Some(serde_json::to_string(&response).unwrap()))
+		}
+	}
+
+	fn start_server(port: u16) -> (Arc<TestServer>, thread::JoinHandle<()>) {
+		let server = Arc::new(TungsteniteWsServer::new(
+			format!("127.0.0.1:{}", port),
+			Arc::new(TestServerConfigProvider {}),
+			Arc::new(MethodEchoHandler),
+		));
+		let server_clone = server.clone();
+		let server_join_handle = thread::spawn(move || {
+			if let Err(e) = server_clone.run() {
+				error!("Web-socket server failed: {:?}", e);
+			}
+		});
+		// Wait until server is up.
+		while !server.is_running().unwrap() {
+			thread::sleep(Duration::from_millis(50));
+		}
+		(server, server_join_handle)
+	}
+
+	fn config(port: u16, pin: ServerCertificatePin) -> ClientConfig {
+		ClientConfig::new(format!("wss://127.0.0.1:{}", port), pin)
+	}
+
+	fn test_certificate_pin() -> ServerCertificatePin {
+		ServerCertificatePin::Certificate(get_test_certificate_chain()[0].0.clone())
+	}
+
+	#[tokio::test]
+	async fn concurrent_requests_get_their_own_responses() {
+		let _ = env_logger::builder().is_test(true).try_init();
+		let port = get_available_port_in_range(22001..22500).unwrap();
+		let (server, server_join_handle) = start_server(port);
+
+		let client = AsyncDirectClient::new(config(port, test_certificate_pin()));
+		let (first, second) = tokio::join!(
+			client.request_ok::<String>("first_method", Vec::new()),
+			client.request_ok::<String>("second_method", Vec::new())
+		);
+
+		assert_eq!(first.unwrap(), "first_method");
+		assert_eq!(second.unwrap(), "second_method");
+
+		server.shut_down().unwrap();
+		server_join_handle.join().unwrap();
+	}
+
+	#[tokio::test]
+	async fn requests_wait_until_worker_is_reachable() {
+		let _ = env_logger::builder().is_test(true).try_init();
+		let port = get_available_port_in_range(22501..23000).unwrap();
+
+		let client = AsyncDirectClient::new(config(port, test_certificate_pin()));
+		let response = tokio::spawn({
+			let client = client.clone();
+			async move { client.request_ok::<String>("method", Vec::new()).await }
+		});
+		tokio::time::sleep(Duration::from_millis(300)).await;
+		let (server, server_join_handle) = start_server(port);
+
+		assert_eq!(response.await.unwrap().unwrap(), "method");
+
+		server.shut_down().unwrap();
+		server_join_handle.join().unwrap();
+	}
+
+	#[tokio::test]
+	async fn worker_with_other_certificate_is_not_trusted() {
+		let _ = env_logger::builder().is_test(true).try_init();
+		let port = get_available_port_in_range(23001..23500).unwrap();
+		let (server, server_join_handle) = start_server(port);
+
+		let mut config = config(port, ServerCertificatePin::EnclaveSigningKey([1u8; 32]));
+		config.request_timeout = Duration::from_millis(500);
+		let client = AsyncDirectClient::new(config);
+
+		assert!(matches!(client.request("method", Vec::new()).await, Err(Error::Timeout(_))));
+
+		server.shut_down().unwrap();
+		server_join_handle.join().unwrap();
+	}
+}
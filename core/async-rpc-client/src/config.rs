@@ -0,0 +1,62 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use std::time::Duration;
+
+/// Configuration of the [`AsyncDirectClient`](crate::AsyncDirectClient).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+	/// Url of the trusted web-socket of the worker, e.g. `wss://localhost:2000`.
+	pub url: String,
+	/// Certificate the worker has to present.
+	pub server_certificate_pin: ServerCertificatePin,
+	/// Time to wait for the response to a request.
+	pub request_timeout: Duration,
+	/// Delay before reconnecting, doubled after every failed attempt up to `max_reconnect_delay`.
+	pub initial_reconnect_delay: Duration,
+	pub max_reconnect_delay: Duration,
+	/// Interval of the pings, which keep the connection from being closed as idle by the worker.
+	pub keep_alive_interval: Duration,
+}
+
+impl ClientConfig {
+	pub fn new(url: String, server_certificate_pin: ServerCertificatePin) -> Self {
+		ClientConfig {
+			url,
+			server_certificate_pin,
+			request_timeout: Duration::from_secs(30),
+			initial_reconnect_delay: Duration::from_millis(100),
+			max_reconnect_delay: Duration::from_secs(10),
+			keep_alive_interval: Duration::from_secs(60),
+		}
+	}
+}
+
+/// Certificate, which the worker has to present on the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerCertificatePin {
+	/// Accept any certificate of this ed25519 public key.
+	///
+	/// The enclave creates the certificate of its trusted web-socket with its signing key, which
+	/// is registered on the parentchain together with its remote attestation. So this is the
+	/// account of the enclave in the enclave registry.
+	EnclaveSigningKey([u8; 32]),
+	/// Accept only this DER encoded certificate.
+	Certificate(Vec<u8>),
+	/// Accept any certificate. The worker is not authenticated, only use this for development.
+	AcceptAny,
+}
@@ -0,0 +1,207 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Task, which owns the web-socket connection to the worker.
+
+use crate::{
+	certificate_verifier::tls_client_config,
+	config::ClientConfig,
+	error::{Error, Result},
+	pending_requests::{PendingRequests, RequestId, ResponseSink},
+};
+use futures::{SinkExt, StreamExt};
+use log::*;
+use std::{collections::VecDeque, mem, sync::Arc};
+use tokio::{
+	net::TcpStream,
+	sync::mpsc,
+	time::{interval, sleep},
+};
+use tokio_rustls::{client::TlsStream, TlsConnector};
+use tokio_tungstenite::{client_async, tungstenite::Message, WebSocketStream};
+use url::Url;
+use webpki::DNSNameRef;
+
+type WebSocket = WebSocketStream<TlsStream<TcpStream>>;
+
+/// Commands of the client to the connection task.
+pub(crate) enum Command {
+	/// Sends a request and passes its responses to the sink.
+	Send { id: RequestId, request: String, sink: ResponseSink },
+	/// Drops the sink of a request, its further responses are ignored.
+	Cancel { id: RequestId },
+}
+
+/// Why the connection has been left.
+enum Disconnect {
+	ConnectionLost,
+	ClientDropped,
+}
+
+/// Connects to the worker and serves the commands of the client, until the client is dropped.
+///
+/// Commands received while disconnected are queued until the connection is re-established.
+pub(crate) async fn run_connection(
+	config: ClientConfig,
+	mut commands: mpsc::UnboundedReceiver<Command>,
+) {
+	let mut pending_requests = PendingRequests::default();
+	let mut queued_commands = VecDeque::new();
+	let mut renewals = Vec::new();
+	let mut reconnect_delay = config.initial_reconnect_delay;
+
+	loop {
+		let mut web_socket = match connect(&config).await {
+			Ok(web_socket) => {
+				debug!("Connected to worker {}", config.url);
+				reconnect_delay = config.initial_reconnect_delay;
+				web_socket
+			},
+			Err(e) => {
+				warn!(
+					"Failed to connect to worker {}: {:?}, retrying in {:?}",
+					config.url, e, reconnect_delay
+				);
+				let wait = sleep(reconnect_delay);
+				tokio::pin!(wait);
+				loop {
+					tokio::select! {
+						_ = &mut wait => break,
+						command = commands.recv() => match command {
+							Some(command) => queued_commands.push_back(command),
+							None => return,
+						},
+					}
+				}
+				reconnect_delay = (reconnect_delay * 2).min(config.max_reconnect_delay);
+				continue
+			},
+		};
+
+		let disconnect = serve(
+			&config,
+			&mut web_socket,
+			&mut commands,
+			&mut pending_requests,
+			&mut queued_commands,
+			mem::take(&mut renewals),
+		)
+		.await;
+
+		match disconnect {
+			Disconnect::ClientDropped => {
+				let _ = web_socket.close(None).await;
+				return
+			},
+			Disconnect::ConnectionLost => {
+				warn!("Lost connection to worker {}, reconnecting", config.url);
+				renewals = pending_requests.on_connection_lost();
+			},
+		}
+	}
+}
+
+/// Serves the commands of the client and the messages of the worker on a connection.
+async fn serve(
+	config: &ClientConfig,
+	web_socket: &mut WebSocket,
+	commands: &mut mpsc::UnboundedReceiver<Command>,
+	pending_requests: &mut PendingRequests,
+	queued_commands: &mut VecDeque<Command>,
+	renewals: Vec<(RequestId, String)>,
+) -> Disconnect {
+	for (id, request) in renewals {
+		debug!("Renewing subscription {}", id);
+		if let Err(e) = web_socket.send(Message::Text(request)).await {
+			warn!("Failed to renew subscription {}: {:?}", id, e);
+			return Disconnect::ConnectionLost
+		}
+	}
+	while let Some(command) = queued_commands.pop_front() {
+		if let Err(e) = handle_command(command, web_socket, pending_requests).await {
+			warn!("Failed to send request to worker: {:?}", e);
+			return Disconnect::ConnectionLost
+		}
+	}
+
+	let mut keep_alive = interval(config.keep_alive_interval);
+	// The first tick completes immediately.
+	keep_alive.tick().await;
+
+	loop {
+		tokio::select! {
+			command = commands.recv() => match command {
+				Some(command) =>
+					if let Err(e) = handle_command(command, web_socket, pending_requests).await {
+						warn!("Failed to send request to worker: {:?}", e);
+						return Disconnect::ConnectionLost
+					},
+				None => return Disconnect::ClientDropped,
+			},
+			message = web_socket.next() => match message {
+				Some(Ok(Message::Text(message))) => pending_requests.dispatch(&message),
+				Some(Ok(Message::Close(_))) | None => return Disconnect::ConnectionLost,
+				Some(Ok(_)) => {},
+				Some(Err(e)) => {
+					warn!("Failed to read from worker: {:?}", e);
+					return Disconnect::ConnectionLost
+				},
+			},
+			_ = keep_alive.tick() =>
+				if let Err(e) = web_socket.send(Message::Ping(Vec::new())).await {
+					warn!("Failed to ping worker: {:?}", e);
+					return Disconnect::ConnectionLost
+				},
+		}
+	}
+}
+
+async fn handle_command(
+	command: Command,
+	web_socket: &mut WebSocket,
+	pending_requests: &mut PendingRequests,
+) -> Result<()> {
+	match command {
+		Command::Send { id, request, sink } => {
+			// E.g. a request, which timed out while the connection was re-established.
+			if sink.is_closed() {
+				return Ok(())
+			}
+			pending_requests.insert(id, sink);
+			web_socket.send(Message::Text(request)).await?;
+		},
+		Command::Cancel { id } => pending_requests.remove(id),
+	}
+	Ok(())
+}
+
+async fn connect(config: &ClientConfig) -> Result<WebSocket> {
+	let url = Url::parse(&config.url).map_err(|e| Error::InvalidUrl(e.to_string()))?;
+	let host = url.host_str().ok_or_else(|| Error::InvalidUrl(config.url.clone()))?;
+	let port = url
+		.port_or_known_default()
+		.ok_or_else(|| Error::InvalidUrl(config.url.clone()))?;
+
+	let tcp_stream = TcpStream::connect((host, port)).await?;
+	let connector = TlsConnector::from(Arc::new(tls_client_config(&config.server_certificate_pin)));
+	// The worker is identified by the pinned certificate, so its name is not used.
+	let server_name = DNSNameRef::try_from_ascii_str("worker").expect("Valid DNS name; qed");
+	let tls_stream = connector.connect(server_name, tcp_stream).await?;
+
+	let (web_socket, _response) = client_async(config.url.as_str(), tls_stream).await?;
+	Ok(web_socket)
+}
@@ -0,0 +1,52 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use codec::Error as CodecError;
+use serde_json::Error as JsonError;
+use std::time::Duration;
+use thiserror;
+use tokio_tungstenite::tungstenite::Error as WebSocketError;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("{0}")]
+	Codec(#[from] CodecError),
+	#[error("{0}")]
+	SerdeJson(#[from] JsonError),
+	#[error("Validateer returned the following error message: {0}")]
+	Status(String),
+	#[error("JSON-RPC error {code}: {message}")]
+	Rpc { code: i64, message: String },
+	#[error("Websocket error: {0}")]
+	WebSocket(#[from] WebSocketError),
+	#[error("Connection failed: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Invalid worker url: {0}")]
+	InvalidUrl(String),
+	#[error("Connection to the worker was lost before the response arrived")]
+	ConnectionLost,
+	#[error("The client has been shut down")]
+	ClientClosed,
+	#[error("No response within {0:?}")]
+	Timeout(Duration),
+	#[error("Unexpected response: {0}")]
+	UnexpectedResponse(String),
+	#[error("Custom Error: {0}")]
+	Custom(Box<dyn std::error::Error + Sync + Send + 'static>),
+}
@@ -0,0 +1,41 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Asynchronous client of the direct RPC interface (trusted web-socket) of a worker.
+//!
+//! Keeps a single, persistent TLS web-socket connection to the worker, over which all requests
+//! and watches are multiplexed by their JSON-RPC id. A lost connection is re-established in the
+//! background, subscriptions are renewed on the new connection.
+//!
+//! As there is only one connection, all requests of a client count against the per-connection
+//! request rate limit of the worker (20 requests per second by default, see the
+//! `--rpc-max-requests-per-connection` flag of the service). Requests above the limit fail with
+//! [`Error::Rpc`]. Clients that need a higher rate have to open several clients, each with its
+//! own connection, which then only share the per-IP limit of the worker.
+
+pub use client::AsyncDirectClient;
+pub use config::{ClientConfig, ServerCertificatePin};
+pub use error::{Error, Result};
+pub use subscription::{Subscription, TrustedOperationWatch};
+
+mod certificate_verifier;
+mod client;
+pub mod config;
+mod connection;
+pub mod error;
+mod pending_requests;
+mod subscription;
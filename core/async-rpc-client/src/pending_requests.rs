@@ -0,0 +1,225 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::error::{Error, Result};
+use itp_rpc::{RpcResponse, RpcReturnValue};
+use itp_utils::FromHexPrefixed;
+use log::*;
+use serde_json::Value;
+use std::{collections::HashMap, mem};
+use tokio::sync::{mpsc, oneshot};
+
+pub(crate) type RequestId = u32;
+
+/// Receives the responses to a request.
+pub(crate) enum ResponseSink {
+	/// Request with a single response.
+	Call(oneshot::Sender<Result<RpcReturnValue>>),
+	/// Request with a response for every update, until the worker stops watching it.
+	Watch(mpsc::UnboundedSender<Result<RpcReturnValue>>),
+	/// Like `Watch`, but the request is sent again on a new connection.
+	Subscription { sender: mpsc::UnboundedSender<Result<RpcReturnValue>>, request: String },
+}
+
+impl ResponseSink {
+	/// True if nobody waits for the responses anymore.
+	pub(crate) fn is_closed(&self) -> bool {
+		match self {
+			ResponseSink::Call(sender) => sender.is_closed(),
+			ResponseSink::Watch(sender) | ResponseSink::Subscription { sender, .. } =>
+				sender.is_closed(),
+		}
+	}
+}
+
+/// The requests waiting for responses, by their JSON-RPC id.
+#[derive(Default)]
+pub(crate) struct PendingRequests {
+	requests: HashMap<RequestId, ResponseSink>,
+}
+
+impl PendingRequests {
+	pub(crate) fn insert(&mut self, id: RequestId, sink: ResponseSink) {
+		self.requests.insert(id, sink);
+	}
+
+	pub(crate) fn remove(&mut self, id: RequestId) {
+		self.requests.remove(&id);
+	}
+
+	/// Passes a message of the worker on to the request with the same id.
+	pub(crate) fn dispatch(&mut self, message: &str) {
+		let (id, response) = match parse_response(message) {
+			Ok(parsed) => parsed,
+			Err(e) => {
+				warn!("Ignoring message of the worker, which is not a valid response: {:?}", e);
+				return
+			},
+		};
+		let keeps_watching = response.as_ref().map_or(false, |value| value.do_watch);
+
+		match self.requests.remove(&id) {
+			None => debug!("Ignoring response to unknown request {}", id),
+			Some(ResponseSink::Call(sender)) => {
+				let _ = sender.send(response);
+			},
+			Some(ResponseSink::Watch(sender)) =>
+				if sender.send(response).is_ok() && keeps_watching {
+					self.requests.insert(id, ResponseSink::Watch(sender));
+				},
+			Some(ResponseSink::Subscription { sender, request }) =>
+				if sender.send(response).is_ok() && keeps_watching {
+					self.requests.insert(id, ResponseSink::Subscription { sender, request });
+				},
+		}
+	}
+
+	/// Fails the requests, which can't be resumed on a new connection, and returns the requests
+	/// of the subscriptions to send again.
+	pub(crate) fn on_connection_lost(&mut self) -> Vec<(RequestId, String)> {
+		let mut renewals = Vec::new();
+		for (id, sink) in mem::take(&mut self.requests) {
+			match sink {
+				ResponseSink::Call(sender) => {
+					let _ = sender.send(Err(Error::ConnectionLost));
+				},
+				// Sending a watched request again would e.g. submit a trusted call twice.
+				ResponseSink::Watch(sender) => {
+					let _ = sender.send(Err(Error::ConnectionLost));
+				},
+				ResponseSink::Subscription { sender, request } =>
+					if !sender.is_closed() {
+						renewals.push((id, request.clone()));
+						self.requests.insert(id, ResponseSink::Subscription { sender, request });
+					},
+			}
+		}
+		renewals
+	}
+}
+
+/// Parses a response of the worker into its id and its return value, or its error.
+fn parse_response(message: &str) -> Result<(RequestId, Result<RpcReturnValue>)> {
+	let response: Value = serde_json::from_str(message)?;
+	let id = response
+		.get("id")
+		.and_then(Value::as_u64)
+		.and_then(|id| RequestId::try_from(id).ok())
+		.ok_or_else(|| Error::UnexpectedResponse(message.into()))?;
+
+	if let Some(error) = response.get("error") {
+		let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+		let message = error.get("message").and_then(Value::as_str).unwrap_or_default().into();
+		return Ok((id, Err(Error::Rpc { code, message })))
+	}
+
+	let response: RpcResponse = serde_json::from_value(response)?;
+	let return_value = RpcReturnValue::from_hex(&response.result)
+		.map_err(|e| Error::Custom(format!("{:?}", e).into()));
+	Ok((id, return_value))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use itp_rpc::Id;
+	use itp_types::{DirectRequestStatus, TrustedOperationStatus};
+	use itp_utils::ToHexPrefixed;
+
+	fn response(id: RequestId, do_watch: bool, status: DirectRequestStatus) -> String {
+		serde_json::to_string(&RpcResponse {
+			jsonrpc: "2.0".into(),
+			result: RpcReturnValue::new(id.encode(), do_watch, status).to_hex(),
+			id: Id::Number(id),
+		})
+		.unwrap()
+	}
+
+	#[test]
+	fn responses_are_passed_to_request_with_same_id() {
+		let mut pending_requests = PendingRequests::default();
+		let (first_sender, mut first_receiver) = oneshot::channel();
+		let (second_sender, mut second_receiver) = oneshot::channel();
+		pending_requests.insert(1, ResponseSink::Call(first_sender));
+		pending_requests.insert(2, ResponseSink::Call(second_sender));
+
+		pending_requests.dispatch(&response(2, false, DirectRequestStatus::Ok));
+		pending_requests.dispatch(&response(1, false, DirectRequestStatus::Ok));
+
+		assert_eq!(first_receiver.try_recv().unwrap().unwrap().value, 1u32.encode());
+		assert_eq!(second_receiver.try_recv().unwrap().unwrap().value, 2u32.encode());
+		assert!(pending_requests.requests.is_empty());
+	}
+
+	#[test]
+	fn watch_receives_updates_until_worker_stops_watching() {
+		let mut pending_requests = PendingRequests::default();
+		let (sender, mut receiver) = mpsc::unbounded_channel();
+		pending_requests.insert(1, ResponseSink::Watch(sender));
+		let status = |status| DirectRequestStatus::TrustedOperationStatus(status);
+
+		pending_requests.dispatch(&response(1, true, status(TrustedOperationStatus::Submitted)));
+		pending_requests.dispatch(&response(
+			1,
+			false,
+			status(TrustedOperationStatus::InSidechainBlock(Default::default())),
+		));
+		pending_requests.dispatch(&response(1, false, status(TrustedOperationStatus::Finalized)));
+
+		assert!(receiver.try_recv().unwrap().unwrap().do_watch);
+		assert!(!receiver.try_recv().unwrap().unwrap().do_watch);
+		assert!(receiver.try_recv().is_err());
+	}
+
+	#[test]
+	fn error_response_is_passed_to_request() {
+		let mut pending_requests = PendingRequests::default();
+		let (sender, mut receiver) = oneshot::channel();
+		pending_requests.insert(3, ResponseSink::Call(sender));
+
+		pending_requests.dispatch(
+			r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Request rejected"},"id":3}"#,
+		);
+
+		assert!(matches!(
+			receiver.try_recv().unwrap(),
+			Err(Error::Rpc { code: -32005, message }) if message == "Request rejected"
+		));
+	}
+
+	#[test]
+	fn lost_connection_fails_requests_but_keeps_subscriptions() {
+		let mut pending_requests = PendingRequests::default();
+		let (call_sender, mut call_receiver) = oneshot::channel();
+		let (watch_sender, mut watch_receiver) = mpsc::unbounded_channel();
+		let (subscription_sender, _subscription_receiver) = mpsc::unbounded_channel();
+		pending_requests.insert(1, ResponseSink::Call(call_sender));
+		pending_requests.insert(2, ResponseSink::Watch(watch_sender));
+		pending_requests.insert(
+			3,
+			ResponseSink::Subscription { sender: subscription_sender, request: "subscribe".into() },
+		);
+
+		let renewals = pending_requests.on_connection_lost();
+
+		assert!(matches!(call_receiver.try_recv().unwrap(), Err(Error::ConnectionLost)));
+		assert!(matches!(watch_receiver.try_recv().unwrap(), Err(Error::ConnectionLost)));
+		assert_eq!(renewals, vec![(3, "subscribe".to_string())]);
+		assert_eq!(pending_requests.requests.len(), 1);
+	}
+}
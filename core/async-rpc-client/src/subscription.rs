@@ -0,0 +1,102 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	connection::Command,
+	error::{Error, Result},
+	pending_requests::RequestId,
+};
+use codec::Decode;
+use itp_rpc::RpcReturnValue;
+use itp_types::{DirectRequestStatus, TrustedOperationStatus};
+use sp_core::H256;
+use tokio::sync::mpsc;
+
+/// Responses of the worker to a watched request or a subscription.
+///
+/// Dropping it stops forwarding the responses of the worker.
+pub struct Subscription {
+	id: RequestId,
+	responses: mpsc::UnboundedReceiver<Result<RpcReturnValue>>,
+	commands: mpsc::UnboundedSender<Command>,
+}
+
+impl Subscription {
+	pub(crate) fn new(
+		id: RequestId,
+		responses: mpsc::UnboundedReceiver<Result<RpcReturnValue>>,
+		commands: mpsc::UnboundedSender<Command>,
+	) -> Self {
+		Subscription { id, responses, commands }
+	}
+
+	/// The next response, `None` once the worker stopped sending responses.
+	///
+	/// A subscription is renewed after the connection has been re-established, so the worker
+	/// confirms it again. A watched request ends with [`Error::ConnectionLost`] instead.
+	pub async fn next(&mut self) -> Option<Result<RpcReturnValue>> {
+		self.responses.recv().await
+	}
+}
+
+impl Drop for Subscription {
+	fn drop(&mut self) {
+		let _ = self.commands.send(Command::Cancel { id: self.id });
+	}
+}
+
+/// Status updates of a submitted trusted operation.
+pub struct TrustedOperationWatch {
+	hash: H256,
+	subscription: Subscription,
+}
+
+impl TrustedOperationWatch {
+	/// Waits for the worker to confirm the submission of the trusted operation.
+	pub(crate) async fn confirm(mut subscription: Subscription) -> Result<Self> {
+		let confirmation = subscription.next().await.ok_or(Error::ConnectionLost)??;
+		let hash = match confirmation.status {
+			DirectRequestStatus::TrustedOperationStatus(_) =>
+				H256::decode(&mut confirmation.value.as_slice())?,
+			_ => return Err(status_error(&confirmation)),
+		};
+		Ok(TrustedOperationWatch { hash, subscription })
+	}
+
+	/// Hash of the trusted operation.
+	pub fn hash(&self) -> H256 {
+		self.hash
+	}
+
+	/// The next status of the trusted operation, `None` once the worker stopped watching it.
+	pub async fn next_status(&mut self) -> Option<Result<TrustedOperationStatus>> {
+		let update = self.subscription.next().await?;
+		Some(update.and_then(|update| match update.status {
+			DirectRequestStatus::TrustedOperationStatus(status) => Ok(status),
+			_ => Err(status_error(&update)),
+		}))
+	}
+}
+
+/// The error of a return value without the expected status.
+pub(crate) fn status_error(return_value: &RpcReturnValue) -> Error {
+	match return_value.status {
+		DirectRequestStatus::Error =>
+			Error::Status(String::decode(&mut return_value.value.as_slice()).unwrap_or_default()),
+		_ => Error::UnexpectedResponse(format!("{:?}", return_value)),
+	}
+}
@@ -39,7 +39,7 @@ use crate::rpc_watch_extractor::RpcWatchExtractor;
 use codec::{Encode, Error as CodecError};
 use itc_tls_websocket_server::error::WebSocketError;
 use itp_rpc::RpcResponse;
-use itp_types::TrustedOperationStatus;
+use itp_types::{SubscriptionTopic, TrustedOperationStatus};
 use serde_json::error::Error as SerdeJsonError;
 use sp_runtime::traits;
 use std::{boxed::Box, fmt::Debug, vec::Vec};
@@ -53,6 +53,8 @@ mod builders;
 pub mod response_channel;
 pub mod rpc_connection_registry;
 pub mod rpc_responder;
+pub mod rpc_subscription_notifier;
+pub mod rpc_subscription_registry;
 pub mod rpc_watch_extractor;
 pub mod rpc_ws_handler;
 
@@ -80,11 +82,47 @@ impl<T: std::hash::Hash + traits::Member + Encode> RpcHash for T {}
 /// Registry for RPC connections (i.e. connections that are kept alive to send updates).
 pub trait RpcConnectionRegistry: Send + Sync {
 	type Hash: RpcHash;
-	type Connection: Copy + Debug;
+	type Connection: Copy + Debug + PartialEq;
 
 	fn store(&self, hash: Self::Hash, connection: Self::Connection, rpc_response: RpcResponse);
 
 	fn withdraw(&self, hash: &Self::Hash) -> Option<(Self::Connection, RpcResponse)>;
+
+	/// Withdraws all operations watched by `connection`, e.g. because it has been closed.
+	fn withdraw_connection(&self, connection: Self::Connection);
+}
+
+/// Registry for RPC subscriptions (i.e. connections that are kept alive to send notifications).
+pub trait RpcSubscriptionRegistry: Send + Sync {
+	type Connection: Copy + Debug;
+
+	fn subscribe(
+		&self,
+		topic: SubscriptionTopic,
+		connection: Self::Connection,
+		rpc_response: RpcResponse,
+	);
+
+	fn subscribers(&self, topic: &SubscriptionTopic) -> Vec<(Self::Connection, RpcResponse)>;
+
+	fn unsubscribe(&self, topic: &SubscriptionTopic, connection: Self::Connection);
+
+	/// All topics with at least one subscriber.
+	fn topics(&self) -> Vec<SubscriptionTopic>;
+
+	/// Unsubscribes `connection` from all topics, e.g. because it has been closed.
+	fn unsubscribe_connection(&self, connection: Self::Connection) {
+		for topic in self.topics() {
+			self.unsubscribe(&topic, connection);
+		}
+	}
+}
+
+/// Sends a notification to all subscribers of a topic.
+pub trait NotifySubscribers: Send + Sync {
+	fn notify_subscribers(&self, topic: &SubscriptionTopic, value: Vec<u8>) -> DirectRpcResult<()>;
+
+	fn subscribed_topics(&self) -> Vec<SubscriptionTopic>;
 }
 
 /// Sends an RPC response back to the client.
@@ -106,6 +144,12 @@ pub trait DetermineWatch: Send + Sync {
 	type Hash: RpcHash;
 
 	fn must_be_watched(&self, rpc_response: &RpcResponse) -> DirectRpcResult<Option<Self::Hash>>;
+
+	/// Returns the topic in case the RpcResponse confirms a subscription.
+	fn must_be_subscribed(
+		&self,
+		rpc_response: &RpcResponse,
+	) -> DirectRpcResult<Option<SubscriptionTopic>>;
 }
 
 /// Convenience method to create a do_watch extractor.
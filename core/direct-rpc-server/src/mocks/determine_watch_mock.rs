@@ -17,12 +17,14 @@
 
 use crate::{DetermineWatch, DirectRpcResult, RpcHash};
 use itp_rpc::RpcResponse;
+use itp_types::SubscriptionTopic;
 
 pub struct DetermineWatchMock<Hash>
 where
 	Hash: RpcHash,
 {
 	watch_next: Option<Hash>,
+	subscribe_next: Option<SubscriptionTopic>,
 }
 
 impl<Hash> DetermineWatchMock<Hash>
@@ -31,12 +33,17 @@ where
 {
 	#[allow(unused)]
 	pub fn do_watch(hash: Hash) -> Self {
-		DetermineWatchMock { watch_next: Some(hash) }
+		DetermineWatchMock { watch_next: Some(hash), subscribe_next: None }
 	}
 
 	#[allow(unused)]
 	pub fn no_watch() -> Self {
-		DetermineWatchMock { watch_next: None }
+		DetermineWatchMock { watch_next: None, subscribe_next: None }
+	}
+
+	#[allow(unused)]
+	pub fn do_subscribe(topic: SubscriptionTopic) -> Self {
+		DetermineWatchMock { watch_next: None, subscribe_next: Some(topic) }
 	}
 }
 
@@ -49,4 +56,11 @@ where
 	fn must_be_watched(&self, _rpc_response: &RpcResponse) -> DirectRpcResult<Option<Self::Hash>> {
 		Ok(self.watch_next.clone())
 	}
+
+	fn must_be_subscribed(
+		&self,
+		_rpc_response: &RpcResponse,
+	) -> DirectRpcResult<Option<SubscriptionTopic>> {
+		Ok(self.subscribe_next.clone())
+	}
 }
@@ -0,0 +1,50 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{DirectRpcResult, NotifySubscribers};
+use itp_types::SubscriptionTopic;
+use std::vec::Vec;
+
+/// Notify subscribers mock, records all notifications.
+#[derive(Default)]
+pub struct NotifySubscribersMock {
+	pub topics: Vec<SubscriptionTopic>,
+	pub notifications: RwLock<Vec<(SubscriptionTopic, Vec<u8>)>>,
+}
+
+impl NotifySubscribersMock {
+	pub fn with_topics(topics: Vec<SubscriptionTopic>) -> Self {
+		NotifySubscribersMock { topics, notifications: Default::default() }
+	}
+}
+
+impl NotifySubscribers for NotifySubscribersMock {
+	fn notify_subscribers(&self, topic: &SubscriptionTopic, value: Vec<u8>) -> DirectRpcResult<()> {
+		self.notifications.write().unwrap().push((topic.clone(), value));
+		Ok(())
+	}
+
+	fn subscribed_topics(&self) -> Vec<SubscriptionTopic> {
+		self.topics.clone()
+	}
+}
@@ -30,7 +30,7 @@ type HashMapLock<K, V> = RwLock<HashMap<K, V>>;
 pub struct ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	connection_map: HashMapLock<<Self as RpcConnectionRegistry>::Hash, (Token, RpcResponse)>,
 }
@@ -38,7 +38,7 @@ where
 impl<Hash, Token> ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	pub fn new() -> Self {
 		Self::default()
@@ -53,7 +53,7 @@ where
 impl<Hash, Token> Default for ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	fn default() -> Self {
 		ConnectionRegistry { connection_map: RwLock::new(HashMap::default()) }
@@ -63,7 +63,7 @@ where
 impl<Hash, Token> RpcConnectionRegistry for ConnectionRegistry<Hash, Token>
 where
 	Hash: RpcHash,
-	Token: Copy + Send + Sync + Debug,
+	Token: Copy + Send + Sync + Debug + PartialEq,
 {
 	type Hash = Hash;
 	type Connection = Token;
@@ -77,6 +77,11 @@ where
 		let mut map = self.connection_map.write().expect("Lock poisoning");
 		map.remove(hash)
 	}
+
+	fn withdraw_connection(&self, connection: Self::Connection) {
+		let mut map = self.connection_map.write().expect("Lock poisoning");
+		map.retain(|_, (c, _)| *c != connection);
+	}
 }
 
 #[cfg(test)]
@@ -119,6 +124,21 @@ pub mod tests {
 		assert!(registry.is_empty());
 	}
 
+	#[test]
+	pub fn withdrawing_connection_removes_all_its_operations() {
+		let registry = TestRegistry::new();
+
+		registry.store("first".to_string(), 1, dummy_rpc_response());
+		registry.store("second".to_string(), 1, dummy_rpc_response());
+		registry.store("third".to_string(), 2, dummy_rpc_response());
+
+		registry.withdraw_connection(1);
+
+		assert!(registry.withdraw(&"first".to_string()).is_none());
+		assert!(registry.withdraw(&"second".to_string()).is_none());
+		assert_eq!(2, registry.withdraw(&"third".to_string()).unwrap().0);
+	}
+
 	fn dummy_rpc_response() -> RpcResponse {
 		RpcResponse { jsonrpc: String::new(), result: Default::default(), id: Id::Number(1u32) }
 	}
@@ -0,0 +1,130 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	response_channel::ResponseChannel, DirectRpcError, DirectRpcResult, NotifySubscribers,
+	RpcSubscriptionRegistry,
+};
+use itp_rpc::RpcReturnValue;
+use itp_types::{DirectRequestStatus, SubscriptionTopic};
+use itp_utils::ToHexPrefixed;
+use log::*;
+use std::{sync::Arc, vec::Vec};
+
+pub struct RpcSubscriptionNotifier<Registry, ResponseChannelType>
+where
+	Registry: RpcSubscriptionRegistry,
+	ResponseChannelType: ResponseChannel<Registry::Connection>,
+{
+	subscription_registry: Arc<Registry>,
+	response_channel: Arc<ResponseChannelType>,
+}
+
+impl<Registry, ResponseChannelType> RpcSubscriptionNotifier<Registry, ResponseChannelType>
+where
+	Registry: RpcSubscriptionRegistry,
+	ResponseChannelType: ResponseChannel<Registry::Connection>,
+{
+	pub fn new(
+		subscription_registry: Arc<Registry>,
+		response_channel: Arc<ResponseChannelType>,
+	) -> Self {
+		RpcSubscriptionNotifier { subscription_registry, response_channel }
+	}
+}
+
+impl<Registry, ResponseChannelType> NotifySubscribers
+	for RpcSubscriptionNotifier<Registry, ResponseChannelType>
+where
+	Registry: RpcSubscriptionRegistry,
+	ResponseChannelType: ResponseChannel<Registry::Connection>,
+{
+	fn notify_subscribers(&self, topic: &SubscriptionTopic, value: Vec<u8>) -> DirectRpcResult<()> {
+		let subscribers = self.subscription_registry.subscribers(topic);
+		if subscribers.is_empty() {
+			return Ok(())
+		}
+
+		debug!("notifying {} subscriber(s) of {:?}", subscribers.len(), topic);
+
+		let result = RpcReturnValue::new(value, true, DirectRequestStatus::Ok).to_hex();
+
+		for (connection, mut response) in subscribers {
+			response.result = result.clone();
+			let sent = serde_json::to_string(&response)
+				.map_err(DirectRpcError::SerializationError)
+				.and_then(|message| {
+					self.response_channel.respond(connection, message).map_err(|e| e.into())
+				});
+
+			// The connection is most likely closed, so we stop notifying it.
+			if let Err(e) = sent {
+				warn!("Failed to notify subscriber {:?}, unsubscribing: {:?}", connection, e);
+				self.subscription_registry.unsubscribe(topic, connection);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn subscribed_topics(&self) -> Vec<SubscriptionTopic> {
+		self.subscription_registry.topics()
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use crate::{
+		builders::rpc_response_builder::RpcResponseBuilder,
+		mocks::response_channel_mock::ResponseChannelMock,
+		rpc_subscription_registry::SubscriptionRegistry,
+	};
+	use codec::Encode;
+
+	type TestRegistry = SubscriptionRegistry<u64>;
+	type TestNotifier = RpcSubscriptionNotifier<TestRegistry, ResponseChannelMock<u64>>;
+
+	#[test]
+	fn notification_is_sent_to_every_subscriber_of_the_topic() {
+		let registry = Arc::new(TestRegistry::new());
+		let response_channel = Arc::new(ResponseChannelMock::<u64>::default());
+		registry.subscribe(SubscriptionTopic::NewHeads, 1, RpcResponseBuilder::new().build());
+		registry.subscribe(SubscriptionTopic::NewHeads, 2, RpcResponseBuilder::new().build());
+
+		let notifier = TestNotifier::new(registry.clone(), response_channel.clone());
+		notifier
+			.notify_subscribers(&SubscriptionTopic::NewHeads, 42u32.encode())
+			.unwrap();
+
+		assert_eq!(response_channel.number_of_updates(), 2);
+		assert_eq!(registry.subscribers(&SubscriptionTopic::NewHeads).len(), 2);
+	}
+
+	#[test]
+	fn notifying_topic_without_subscribers_sends_nothing() {
+		let registry = Arc::new(TestRegistry::new());
+		let response_channel = Arc::new(ResponseChannelMock::<u64>::default());
+
+		let notifier = TestNotifier::new(registry, response_channel.clone());
+		notifier
+			.notify_subscribers(&SubscriptionTopic::NewHeads, 42u32.encode())
+			.unwrap();
+
+		assert_eq!(response_channel.number_of_updates(), 0);
+	}
+}
@@ -0,0 +1,158 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::RpcSubscriptionRegistry;
+use itp_rpc::RpcResponse;
+use itp_types::SubscriptionTopic;
+use std::{collections::HashMap, fmt::Debug, vec::Vec};
+
+type SubscriptionMapLock<Token> = RwLock<HashMap<SubscriptionTopic, Vec<(Token, RpcResponse)>>>;
+
+pub struct SubscriptionRegistry<Token>
+where
+	Token: Copy + Send + Sync + Debug + PartialEq,
+{
+	subscription_map: SubscriptionMapLock<Token>,
+}
+
+impl<Token> SubscriptionRegistry<Token>
+where
+	Token: Copy + Send + Sync + Debug + PartialEq,
+{
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[cfg(test)]
+	pub fn is_empty(&self) -> bool {
+		self.subscription_map.read().unwrap().is_empty()
+	}
+}
+
+impl<Token> Default for SubscriptionRegistry<Token>
+where
+	Token: Copy + Send + Sync + Debug + PartialEq,
+{
+	fn default() -> Self {
+		SubscriptionRegistry { subscription_map: RwLock::new(HashMap::default()) }
+	}
+}
+
+impl<Token> RpcSubscriptionRegistry for SubscriptionRegistry<Token>
+where
+	Token: Copy + Send + Sync + Debug + PartialEq,
+{
+	type Connection = Token;
+
+	fn subscribe(
+		&self,
+		topic: SubscriptionTopic,
+		connection: Self::Connection,
+		rpc_response: RpcResponse,
+	) {
+		let mut map = self.subscription_map.write().expect("Lock poisoning");
+		let subscribers = map.entry(topic).or_default();
+		// A connection subscribes at most once per topic, a new subscription replaces the old one.
+		subscribers.retain(|(c, _)| *c != connection);
+		subscribers.push((connection, rpc_response));
+	}
+
+	fn subscribers(&self, topic: &SubscriptionTopic) -> Vec<(Self::Connection, RpcResponse)> {
+		let map = self.subscription_map.read().expect("Lock poisoning");
+		map.get(topic).cloned().unwrap_or_default()
+	}
+
+	fn unsubscribe(&self, topic: &SubscriptionTopic, connection: Self::Connection) {
+		let mut map = self.subscription_map.write().expect("Lock poisoning");
+		if let Some(subscribers) = map.get_mut(topic) {
+			subscribers.retain(|(c, _)| *c != connection);
+			if subscribers.is_empty() {
+				map.remove(topic);
+			}
+		}
+	}
+
+	fn topics(&self) -> Vec<SubscriptionTopic> {
+		let map = self.subscription_map.read().expect("Lock poisoning");
+		map.keys().cloned().collect()
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use itp_rpc::Id;
+	use itp_types::AccountId;
+
+	type TestRegistry = SubscriptionRegistry<u64>;
+
+	#[test]
+	pub fn subscribing_twice_with_same_connection_keeps_one_subscription() {
+		let registry = TestRegistry::new();
+
+		registry.subscribe(SubscriptionTopic::NewHeads, 1, dummy_rpc_response());
+		registry.subscribe(SubscriptionTopic::NewHeads, 1, dummy_rpc_response());
+		registry.subscribe(SubscriptionTopic::NewHeads, 2, dummy_rpc_response());
+
+		let connections: Vec<u64> = registry
+			.subscribers(&SubscriptionTopic::NewHeads)
+			.into_iter()
+			.map(|s| s.0)
+			.collect();
+		assert_eq!(connections, vec![1, 2]);
+	}
+
+	#[test]
+	pub fn subscribers_are_separated_by_topic() {
+		let registry = TestRegistry::new();
+		let account_topic =
+			SubscriptionTopic::AccountEvents(Default::default(), AccountId::new([1u8; 32]));
+
+		registry.subscribe(SubscriptionTopic::NewHeads, 1, dummy_rpc_response());
+		registry.subscribe(account_topic.clone(), 2, dummy_rpc_response());
+
+		assert_eq!(registry.subscribers(&SubscriptionTopic::NewHeads).len(), 1);
+		assert_eq!(registry.subscribers(&account_topic)[0].0, 2);
+		assert!(registry
+			.subscribers(&SubscriptionTopic::AccountEvents(
+				Default::default(),
+				AccountId::new([2u8; 32])
+			))
+			.is_empty());
+	}
+
+	#[test]
+	pub fn unsubscribing_last_connection_clears_registry() {
+		let registry = TestRegistry::new();
+
+		registry.subscribe(SubscriptionTopic::NewHeads, 1, dummy_rpc_response());
+		registry.unsubscribe(&SubscriptionTopic::NewHeads, 1);
+
+		assert!(registry.is_empty());
+		assert!(registry.topics().is_empty());
+	}
+
+	fn dummy_rpc_response() -> RpcResponse {
+		RpcResponse { jsonrpc: String::new(), result: Default::default(), id: Id::Number(1u32) }
+	}
+}
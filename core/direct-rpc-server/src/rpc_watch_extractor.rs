@@ -19,7 +19,7 @@ use crate::{DetermineWatch, DirectRpcError, DirectRpcResult, RpcHash};
 use alloc::format;
 use codec::Decode;
 use itp_rpc::{RpcResponse, RpcReturnValue};
-use itp_types::DirectRequestStatus;
+use itp_types::{DirectRequestStatus, SubscriptionTopic};
 use itp_utils::FromHexPrefixed;
 use std::marker::PhantomData;
 
@@ -70,6 +70,23 @@ where
 			_ => Ok(None),
 		}
 	}
+
+	fn must_be_subscribed(
+		&self,
+		rpc_response: &RpcResponse,
+	) -> DirectRpcResult<Option<SubscriptionTopic>> {
+		let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
+			.map_err(|e| DirectRpcError::Other(format!("{:?}", e).into()))?;
+
+		// A confirmed subscription is the only successful response that keeps the connection.
+		if !rpc_return_value.do_watch || rpc_return_value.status != DirectRequestStatus::Ok {
+			return Ok(None)
+		}
+
+		SubscriptionTopic::decode(&mut rpc_return_value.value.as_slice())
+			.map(Some)
+			.map_err(DirectRpcError::EncodingError)
+	}
 }
 
 #[cfg(test)]
@@ -124,4 +141,34 @@ pub mod tests {
 
 		assert_eq!(Some(hash.clone()), do_watch);
 	}
+
+	#[test]
+	fn rpc_response_with_ok_status_and_watch_flag_must_be_subscribed() {
+		let watch_extractor = RpcWatchExtractor::<String>::new();
+		let rpc_return_value = RpcReturnValueBuilder::new()
+			.with_do_watch(true)
+			.with_value(SubscriptionTopic::NewHeads.encode())
+			.with_status(DirectRequestStatus::Ok)
+			.build();
+		let rpc_response = RpcResponseBuilder::new().with_result(rpc_return_value).build();
+
+		assert_eq!(
+			Some(SubscriptionTopic::NewHeads),
+			watch_extractor.must_be_subscribed(&rpc_response).unwrap()
+		);
+		assert_eq!(None, watch_extractor.must_be_watched(&rpc_response).unwrap());
+	}
+
+	#[test]
+	fn trusted_operation_status_response_must_not_be_subscribed() {
+		let watch_extractor = RpcWatchExtractor::<String>::new();
+		let rpc_return_value = RpcReturnValueBuilder::new()
+			.with_do_watch(true)
+			.with_value(String::from("rpc_hash").encode())
+			.with_status(DirectRequestStatus::TrustedOperationStatus(TrustedOperationStatus::Ready))
+			.build();
+		let rpc_response = RpcResponseBuilder::new().with_result(rpc_return_value).build();
+
+		assert_eq!(None, watch_extractor.must_be_subscribed(&rpc_response).unwrap());
+	}
 }
@@ -18,43 +18,108 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use crate::{DetermineWatch, RpcConnectionRegistry, RpcHash};
-use itc_tls_websocket_server::{error::WebSocketResult, ConnectionToken, WebSocketMessageHandler};
-use jsonrpc_core::IoHandler;
+use crate::{DetermineWatch, RpcConnectionRegistry, RpcHash, RpcSubscriptionRegistry};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use itc_tls_websocket_server::{
+	error::WebSocketResult, limits::WebSocketRejection, ConnectionToken, WebSocketMessageHandler,
+};
+use itp_enclave_metrics::trace::{
+	with_request_trace_context, TraceContext, TraceSpan, GLOBAL_TRACE_SPAN_BUFFER, RPC_REQUEST_SPAN,
+};
+use itp_rpc::RpcResponse;
+use itp_time_utils::duration_now;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Failure, Id, IoHandler, Output, Version};
 use log::*;
-use std::{string::String, sync::Arc};
+use serde_json::Value;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use std::{format, string::String, sync::Arc, vec, vec::Vec};
 
-pub struct RpcWsHandler<Watcher, Registry, Hash>
+/// Error code of requests rejected because of a limit, as in EIP-1474.
+pub const LIMIT_EXCEEDED_ERROR_CODE: i64 = -32005;
+
+/// Default maximum number of requests in a JSON-RPC batch.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// Optional request field with a W3C trace context, to continue the trace of the client.
+pub const TRACEPARENT_FIELD: &str = "traceparent";
+
+pub struct RpcWsHandler<Watcher, Registry, SubscriptionRegistry, Hash>
 where
 	Watcher: DetermineWatch<Hash = Hash>,
 	Registry: RpcConnectionRegistry<Hash = Hash>,
+	SubscriptionRegistry: RpcSubscriptionRegistry,
 	Hash: RpcHash,
 {
 	rpc_io_handler: IoHandler,
 	connection_watcher: Arc<Watcher>,
 	connection_registry: Arc<Registry>,
+	subscription_registry: Arc<SubscriptionRegistry>,
+	max_batch_size: AtomicUsize,
 }
 
-impl<Watcher, Registry, Hash> RpcWsHandler<Watcher, Registry, Hash>
+impl<Watcher, Registry, SubscriptionRegistry, Hash>
+	RpcWsHandler<Watcher, Registry, SubscriptionRegistry, Hash>
 where
 	Watcher: DetermineWatch<Hash = Hash>,
 	Registry: RpcConnectionRegistry<Hash = Hash>,
+	SubscriptionRegistry: RpcSubscriptionRegistry,
 	Hash: RpcHash,
 {
 	pub fn new(
 		rpc_io_handler: IoHandler,
 		connection_watcher: Arc<Watcher>,
 		connection_registry: Arc<Registry>,
+		subscription_registry: Arc<SubscriptionRegistry>,
 	) -> Self {
-		RpcWsHandler { rpc_io_handler, connection_watcher, connection_registry }
+		RpcWsHandler {
+			rpc_io_handler,
+			connection_watcher,
+			connection_registry,
+			subscription_registry,
+			max_batch_size: AtomicUsize::new(DEFAULT_MAX_BATCH_SIZE),
+		}
+	}
+
+	/// Sets the maximum number of requests in a JSON-RPC batch, larger batches are rejected.
+	///
+	/// Can be changed after the handler has been shared, as the limit is only known when the
+	/// server is started.
+	pub fn set_max_batch_size(&self, max_batch_size: usize) {
+		self.max_batch_size.store(max_batch_size, Ordering::Relaxed);
+	}
+
+	/// Stores the connection for responses, that have to be watched or subscribed.
+	fn register_watch(&self, connection_token: ConnectionToken, rpc_response: RpcResponse)
+	where
+		Registry::Connection: From<ConnectionToken>,
+		SubscriptionRegistry::Connection: From<ConnectionToken>,
+	{
+		if let Ok(Some(connection_hash)) = self.connection_watcher.must_be_watched(&rpc_response) {
+			self.connection_registry
+				.store(connection_hash, connection_token.into(), rpc_response);
+		} else if let Ok(Some(topic)) = self.connection_watcher.must_be_subscribed(&rpc_response) {
+			self.subscription_registry
+				.subscribe(topic, connection_token.into(), rpc_response);
+		}
+	}
+
+	/// Number of requests in `message`, if it is a JSON-RPC batch.
+	fn batch_size(message: &str) -> Option<usize> {
+		if !message.trim_start().starts_with('[') {
+			return None
+		}
+		serde_json::from_str::<Vec<Value>>(message).ok().map(|batch| batch.len())
 	}
 }
 
-impl<Watcher, Registry, Hash> WebSocketMessageHandler for RpcWsHandler<Watcher, Registry, Hash>
+impl<Watcher, Registry, SubscriptionRegistry, Hash> WebSocketMessageHandler
+	for RpcWsHandler<Watcher, Registry, SubscriptionRegistry, Hash>
 where
 	Watcher: DetermineWatch<Hash = Hash>,
 	Registry: RpcConnectionRegistry<Hash = Hash>,
 	Registry::Connection: From<ConnectionToken>,
+	SubscriptionRegistry: RpcSubscriptionRegistry,
+	SubscriptionRegistry::Connection: From<ConnectionToken>,
 	Hash: RpcHash,
 {
 	fn handle_message(
@@ -62,26 +127,165 @@ where
 		connection_token: ConnectionToken,
 		message: String,
 	) -> WebSocketResult<Option<String>> {
-		let maybe_rpc_response = self.rpc_io_handler.handle_request_sync(message.as_str());
+		let max_batch_size = self.max_batch_size.load(Ordering::Relaxed);
+		match Self::batch_size(message.as_str()) {
+			Some(0) => return Ok(failure_response(RpcError::invalid_request(), Id::Null)),
+			Some(batch_size) if batch_size > max_batch_size => {
+				debug!("Rejecting batch of {} requests", batch_size);
+				let error = RpcError {
+					code: ErrorCode::ServerError(LIMIT_EXCEEDED_ERROR_CODE),
+					message: format!("Batch exceeds the maximum of {} requests", max_batch_size),
+					data: None,
+				};
+				return Ok(failure_response(error, Id::Null))
+			},
+			_ => {},
+		}
+
+		let (message, maybe_trace) = take_trace_context(message);
+
+		// The io-handler answers a batch with an array of the responses, correlated by their id.
+		let maybe_rpc_response = match maybe_trace {
+			Some(trace) if GLOBAL_TRACE_SPAN_BUFFER.is_enabled() => {
+				let request_start = duration_now();
+				// The operations submitted by the request become children of the request span.
+				let request_span_id = request_span_id(&trace.context, &message, request_start);
+				let request_context = TraceContext {
+					trace_id: trace.context.trace_id,
+					parent_span_id: request_span_id,
+				};
+				let maybe_rpc_response = with_request_trace_context(Some(request_context), || {
+					self.rpc_io_handler.handle_request_sync(message.as_str())
+				});
+				GLOBAL_TRACE_SPAN_BUFFER.record(TraceSpan {
+					trace_id: trace.context.trace_id,
+					span_id: Some(request_span_id),
+					parent_span_id: Some(trace.context.parent_span_id),
+					name: RPC_REQUEST_SPAN.into(),
+					start: request_start,
+					end: duration_now(),
+					attributes: vec![("method".into(), trace.methods.join(","))],
+				});
+				maybe_rpc_response
+			},
+			_ => self.rpc_io_handler.handle_request_sync(message.as_str()),
+		};
 
 		debug!("RPC response string: {:?}", maybe_rpc_response);
 
-		if let Ok(rpc_response) =
-			serde_json::from_str(maybe_rpc_response.clone().unwrap_or_default().as_str())
-		{
-			if let Ok(Some(connection_hash)) =
-				self.connection_watcher.must_be_watched(&rpc_response)
-			{
-				self.connection_registry.store(
-					connection_hash,
-					connection_token.into(),
-					rpc_response,
-				);
-			}
+		match serde_json::from_str(maybe_rpc_response.clone().unwrap_or_default().as_str()) {
+			Ok(Value::Array(rpc_responses)) => rpc_responses
+				.into_iter()
+				.filter_map(|rpc_response| serde_json::from_value(rpc_response).ok())
+				.for_each(|rpc_response| self.register_watch(connection_token, rpc_response)),
+			Ok(rpc_response) =>
+				if let Ok(rpc_response) = serde_json::from_value(rpc_response) {
+					self.register_watch(connection_token, rpc_response)
+				},
+			Err(_) => {},
 		}
 
 		Ok(maybe_rpc_response)
 	}
+
+	/// Every request of a batch is charged to the rate limits, an empty batch like a request.
+	fn request_count(&self, message: &str) -> u32 {
+		Self::batch_size(message)
+			.map_or(1, |batch_size| u32::try_from(batch_size).unwrap_or(u32::MAX).max(1))
+	}
+
+	fn reject_message(
+		&self,
+		_connection_token: ConnectionToken,
+		message: Option<&str>,
+		rejection: WebSocketRejection,
+	) -> Option<String> {
+		let error = match rejection {
+			WebSocketRejection::MessageTooLarge => RpcError {
+				code: ErrorCode::InvalidRequest,
+				message: "Request exceeds the maximum message size".into(),
+				data: None,
+			},
+			_ => RpcError {
+				code: ErrorCode::ServerError(LIMIT_EXCEEDED_ERROR_CODE),
+				message: format!("Request rejected: {}", rejection.as_str()),
+				data: None,
+			},
+		};
+		failure_response(error, message.and_then(request_id).unwrap_or(Id::Null))
+	}
+
+	fn on_connection_closed(&self, connection_token: ConnectionToken) {
+		self.connection_registry.withdraw_connection(connection_token.into());
+		self.subscription_registry.unsubscribe_connection(connection_token.into());
+	}
+}
+
+fn failure_response(error: RpcError, id: Id) -> Option<String> {
+	serde_json::to_string(&Output::Failure(Failure { jsonrpc: Some(Version::V2), error, id })).ok()
+}
+
+/// Trace context given with a request and the methods it calls.
+struct RequestTrace {
+	context: TraceContext,
+	methods: Vec<String>,
+}
+
+/// Removes the [`TRACEPARENT_FIELD`] from the request(s) in `message`, because the io-handler
+/// rejects unknown fields. The trace context of a batch is the one of its first request.
+fn take_trace_context(message: String) -> (String, Option<RequestTrace>) {
+	if !message.contains(TRACEPARENT_FIELD) {
+		return (message, None)
+	}
+	let mut request: Value = match serde_json::from_str(message.as_str()) {
+		Ok(request) => request,
+		Err(_) => return (message, None),
+	};
+
+	let mut traceparents = Vec::new();
+	let mut methods = Vec::new();
+	let mut take_traceparent = |request: &mut Value| {
+		if let Some(request) = request.as_object_mut() {
+			if let Some(Value::String(traceparent)) = request.remove(TRACEPARENT_FIELD) {
+				traceparents.push(traceparent);
+			}
+			if let Some(Value::String(method)) = request.get("method") {
+				methods.push(method.clone());
+			}
+		}
+	};
+	match &mut request {
+		Value::Array(batch) => batch.iter_mut().for_each(&mut take_traceparent),
+		request => take_traceparent(request),
+	}
+
+	let maybe_context = traceparents
+		.first()
+		.and_then(|traceparent| TraceContext::from_traceparent(traceparent));
+	match serde_json::to_string(&request) {
+		Ok(message) => (message, maybe_context.map(|context| RequestTrace { context, methods })),
+		Err(_) => (message, None),
+	}
+}
+
+/// Span id of the request span, unique for the request within the trace of the client.
+fn request_span_id(
+	context: &TraceContext,
+	message: &str,
+	request_start: core::time::Duration,
+) -> [u8; 8] {
+	let mut preimage = context.parent_span_id.to_vec();
+	preimage.extend_from_slice(&request_start.as_nanos().to_le_bytes());
+	preimage.extend_from_slice(message.as_bytes());
+	let mut span_id = [0u8; 8];
+	span_id.copy_from_slice(&BlakeTwo256::hash(&preimage)[..8]);
+	span_id
+}
+
+/// The id of a JSON-RPC request, if it has one.
+fn request_id(message: &str) -> Option<Id> {
+	let request: Value = serde_json::from_str(message).ok()?;
+	serde_json::from_value(request.get("id")?.clone()).ok()
 }
 
 #[cfg(test)]
@@ -91,20 +295,28 @@ pub mod tests {
 	use crate::{
 		mocks::determine_watch_mock::DetermineWatchMock,
 		rpc_connection_registry::ConnectionRegistry,
+		rpc_subscription_registry::SubscriptionRegistry,
 	};
 	use codec::Encode;
 	use itc_tls_websocket_server::ConnectionToken;
 	use itp_rpc::RpcReturnValue;
-	use itp_types::DirectRequestStatus;
+	use itp_types::{DirectRequestStatus, SubscriptionTopic};
 	use itp_utils::ToHexPrefixed;
 	use jsonrpc_core::Params;
 	use serde_json::json;
 
 	type TestConnectionRegistry = ConnectionRegistry<String, ConnectionToken>;
+	type TestSubscriptionRegistry = SubscriptionRegistry<ConnectionToken>;
 	type TestConnectionWatcher = DetermineWatchMock<String>;
-	type TestWsHandler = RpcWsHandler<TestConnectionWatcher, TestConnectionRegistry, String>;
+	type TestWsHandler = RpcWsHandler<
+		TestConnectionWatcher,
+		TestConnectionRegistry,
+		TestSubscriptionRegistry,
+		String,
+	>;
 
 	const RPC_METHOD_NAME: &str = "test_call";
+	const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
 
 	#[test]
 	fn valid_rpc_call_without_watch_runs_successfully() {
@@ -136,6 +348,28 @@ pub mod tests {
 		assert!(connection_registry.withdraw(&connection_hash).is_some());
 	}
 
+	#[test]
+	fn valid_rpc_call_with_subscription_stores_subscriber() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+		let (connection_token, message) = create_message_to_handle(RPC_METHOD_NAME);
+
+		let subscription_registry = Arc::new(TestSubscriptionRegistry::new());
+		let ws_handler = TestWsHandler::new(
+			io_handler,
+			Arc::new(TestConnectionWatcher::do_subscribe(SubscriptionTopic::NewHeads)),
+			Arc::new(TestConnectionRegistry::new()),
+			subscription_registry.clone(),
+		);
+
+		let handle_result = ws_handler.handle_message(connection_token, message);
+
+		assert!(handle_result.is_ok());
+		assert_eq!(
+			subscription_registry.subscribers(&SubscriptionTopic::NewHeads)[0].0,
+			connection_token
+		);
+	}
+
 	#[test]
 	fn when_rpc_returns_error_then_return_ok_but_status_is_set_to_error() {
 		let io_handler = create_io_handler_with_error(RPC_METHOD_NAME);
@@ -165,6 +399,159 @@ pub mod tests {
 		assert!(connection_registry.is_empty());
 	}
 
+	#[test]
+	fn batch_is_answered_with_responses_correlated_by_id() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+		let (ws_handler, connection_registry) = create_ws_handler(io_handler, None);
+
+		let batch = create_batch_to_handle(&[RPC_METHOD_NAME, "not_a_valid_method"]);
+		let responses: Vec<Value> = serde_json::from_str(
+			&ws_handler.handle_message(ConnectionToken(23), batch).unwrap().unwrap(),
+		)
+		.unwrap();
+
+		assert_eq!(responses.len(), 2);
+		assert_eq!(responses[0]["id"], json!(1));
+		assert!(responses[0].get("result").is_some());
+		assert_eq!(responses[1]["id"], json!(2));
+		assert_eq!(responses[1]["error"]["code"], json!(-32601));
+		assert!(connection_registry.is_empty());
+	}
+
+	#[test]
+	fn watched_requests_in_batch_store_connection() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+		let connection_hash = String::from("connection_hash");
+		let (ws_handler, connection_registry) =
+			create_ws_handler(io_handler, Some(connection_hash.clone()));
+
+		let batch = create_batch_to_handle(&[RPC_METHOD_NAME, RPC_METHOD_NAME]);
+		ws_handler.handle_message(ConnectionToken(23), batch).unwrap();
+
+		assert_eq!(connection_registry.withdraw(&connection_hash).unwrap().0, ConnectionToken(23));
+	}
+
+	#[test]
+	fn batch_exceeding_maximum_size_is_rejected() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+		let (ws_handler, _) = create_ws_handler(io_handler, None);
+		ws_handler.set_max_batch_size(1);
+
+		let batch = create_batch_to_handle(&[RPC_METHOD_NAME, RPC_METHOD_NAME]);
+		let response = ws_handler.handle_message(ConnectionToken(23), batch).unwrap().unwrap();
+
+		assert_eq!(response, "{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32005,\"message\":\"Batch exceeds the maximum of 1 requests\"},\"id\":null}");
+	}
+
+	#[test]
+	fn every_request_of_batch_is_counted() {
+		let (ws_handler, _) =
+			create_ws_handler(create_io_handler_with_method(RPC_METHOD_NAME), None);
+		let (_, message) = create_message_to_handle(RPC_METHOD_NAME);
+
+		let batch = create_batch_to_handle(&[RPC_METHOD_NAME, RPC_METHOD_NAME, RPC_METHOD_NAME]);
+
+		assert_eq!(ws_handler.request_count(&batch), 3);
+		assert_eq!(ws_handler.request_count(&message), 1);
+		assert_eq!(ws_handler.request_count("[]"), 1);
+	}
+
+	#[test]
+	fn empty_batch_is_answered_with_invalid_request() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+		let (ws_handler, _) = create_ws_handler(io_handler, None);
+
+		let response = ws_handler
+			.handle_message(ConnectionToken(23), "[]".to_string())
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(response, "{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32600,\"message\":\"Invalid request\"},\"id\":null}");
+	}
+
+	#[test]
+	fn rejected_request_is_answered_with_json_error_message() {
+		let (connection_token, message) = create_message_to_handle(RPC_METHOD_NAME);
+		let (ws_handler, _) =
+			create_ws_handler(create_io_handler_with_method(RPC_METHOD_NAME), None);
+
+		let reply = ws_handler.reject_message(
+			connection_token,
+			Some(&message),
+			WebSocketRejection::ConnectionRateLimited,
+		);
+
+		assert_eq!(reply.unwrap(), "{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32005,\"message\":\"Request rejected: connection_rate_limited\"},\"id\":1}");
+
+		let reply =
+			ws_handler.reject_message(connection_token, None, WebSocketRejection::MessageTooLarge);
+
+		assert_eq!(reply.unwrap(), "{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32600,\"message\":\"Request exceeds the maximum message size\"},\"id\":null}");
+	}
+
+	#[test]
+	fn request_with_traceparent_is_handled() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+		let (ws_handler, _) = create_ws_handler(io_handler, None);
+
+		let message = json!({
+			"jsonrpc": "2.0",
+			"method": RPC_METHOD_NAME,
+			"params": {},
+			"id": 1,
+			"traceparent": TRACEPARENT,
+		})
+		.to_string();
+		let response: Value = serde_json::from_str(
+			&ws_handler.handle_message(ConnectionToken(23), message).unwrap().unwrap(),
+		)
+		.unwrap();
+
+		assert!(response.get("result").is_some());
+	}
+
+	#[test]
+	fn trace_context_is_taken_from_first_request_of_batch() {
+		let batch = json!([
+			{"jsonrpc": "2.0", "method": "first", "params": {}, "id": 1, "traceparent": TRACEPARENT},
+			{"jsonrpc": "2.0", "method": "second", "params": {}, "id": 2, "traceparent": "invalid"},
+		])
+		.to_string();
+
+		let (message, trace) = take_trace_context(batch);
+
+		assert!(!message.contains(TRACEPARENT_FIELD));
+		let trace = trace.unwrap();
+		assert_eq!(trace.context, TraceContext::from_traceparent(TRACEPARENT).unwrap());
+		assert_eq!(trace.methods, vec!["first".to_string(), "second".to_string()]);
+	}
+
+	#[test]
+	fn request_without_traceparent_is_not_modified() {
+		let (_, message) = create_message_to_handle(RPC_METHOD_NAME);
+
+		let (taken_message, trace) = take_trace_context(message.clone());
+
+		assert_eq!(taken_message, message);
+		assert!(trace.is_none());
+	}
+
+	#[test]
+	fn closing_connection_withdraws_its_watched_operations() {
+		let io_handler = create_io_handler_with_method(RPC_METHOD_NAME);
+
+		let connection_hash = String::from("connection_hash");
+		let (connection_token, message) = create_message_to_handle(RPC_METHOD_NAME);
+
+		let (ws_handler, connection_registry) =
+			create_ws_handler(io_handler, Some(connection_hash.clone()));
+
+		ws_handler.handle_message(connection_token, message).unwrap();
+		ws_handler.on_connection_closed(connection_token);
+
+		assert!(connection_registry.is_empty());
+	}
+
 	fn create_message_to_handle(method_name: &str) -> (ConnectionToken, String) {
 		let json_rpc_pre_method = r#"{"jsonrpc": "2.0", "method": ""#;
 		let json_rpc_post_method = r#"", "params": {}, "id": 1}"#;
@@ -175,6 +562,17 @@ pub mod tests {
 		(ConnectionToken(23), json_string)
 	}
 
+	fn create_batch_to_handle(method_names: &[&str]) -> String {
+		let requests: Vec<Value> = method_names
+			.iter()
+			.enumerate()
+			.map(|(index, method_name)| {
+				json!({"jsonrpc": "2.0", "method": method_name, "params": {}, "id": index + 1})
+			})
+			.collect();
+		Value::Array(requests).to_string()
+	}
+
 	fn create_ws_handler(
 		io_handler: IoHandler,
 		watch_connection: Option<String>,
@@ -187,7 +585,12 @@ pub mod tests {
 		let connection_registry = Arc::new(TestConnectionRegistry::new());
 
 		(
-			TestWsHandler::new(io_handler, Arc::new(watcher), connection_registry.clone()),
+			TestWsHandler::new(
+				io_handler,
+				Arc::new(watcher),
+				connection_registry.clone(),
+				Arc::new(TestSubscriptionRegistry::new()),
+			),
 			connection_registry,
 		)
 	}
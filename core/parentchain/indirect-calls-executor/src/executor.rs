@@ -28,10 +28,12 @@ use alloc::format;
 use binary_merkle_tree::merkle_root;
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
+use itp_enclave_metrics::EnclaveMetric;
 use itp_node_api::metadata::{
 	pallet_enclave_bridge::EnclaveBridgeCallIndexes, provider::AccessNodeMetadata,
 	NodeMetadataTrait,
 };
+use itp_ocall_api::EnclaveMetricsOCallApi;
 use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
 use itp_stf_executor::traits::{StfEnclaveSigning, StfShardVaultQuery};
 use itp_stf_primitives::{
@@ -53,6 +55,7 @@ pub struct IndirectCallsExecutor<
 	StfEnclaveSigner,
 	TopPoolAuthor,
 	NodeMetadataProvider,
+	OCallApi,
 	IndirectCallsFilter,
 	EventCreator,
 	ParentchainEventHandler,
@@ -63,6 +66,7 @@ pub struct IndirectCallsExecutor<
 	pub stf_enclave_signer: Arc<StfEnclaveSigner>,
 	pub(crate) top_pool_author: Arc<TopPoolAuthor>,
 	pub(crate) node_meta_data_provider: Arc<NodeMetadataProvider>,
+	ocall_api: Arc<OCallApi>,
 	pub parentchain_id: ParentchainId,
 	_phantom: PhantomData<(IndirectCallsFilter, EventCreator, ParentchainEventHandler, TCS, G)>,
 }
@@ -71,6 +75,7 @@ impl<
 		StfEnclaveSigner,
 		TopPoolAuthor,
 		NodeMetadataProvider,
+		OCallApi,
 		IndirectCallsFilter,
 		EventCreator,
 		ParentchainEventHandler,
@@ -82,6 +87,7 @@ impl<
 		StfEnclaveSigner,
 		TopPoolAuthor,
 		NodeMetadataProvider,
+		OCallApi,
 		IndirectCallsFilter,
 		EventCreator,
 		ParentchainEventHandler,
@@ -94,6 +100,7 @@ impl<
 		stf_enclave_signer: Arc<StfEnclaveSigner>,
 		top_pool_author: Arc<TopPoolAuthor>,
 		node_meta_data_provider: Arc<NodeMetadataProvider>,
+		ocall_api: Arc<OCallApi>,
 		parentchain_id: ParentchainId,
 	) -> Self {
 		IndirectCallsExecutor {
@@ -101,6 +108,7 @@ impl<
 			stf_enclave_signer,
 			top_pool_author,
 			node_meta_data_provider,
+			ocall_api,
 			parentchain_id,
 			_phantom: Default::default(),
 		}
@@ -112,6 +120,7 @@ impl<
 		StfEnclaveSigner,
 		TopPoolAuthor,
 		NodeMetadataProvider,
+		OCallApi,
 		FilterIndirectCalls,
 		EventCreator,
 		ParentchainEventHandler,
@@ -123,6 +132,7 @@ impl<
 		StfEnclaveSigner,
 		TopPoolAuthor,
 		NodeMetadataProvider,
+		OCallApi,
 		FilterIndirectCalls,
 		EventCreator,
 		ParentchainEventHandler,
@@ -137,6 +147,7 @@ impl<
 	NodeMetadataProvider: AccessNodeMetadata,
 	FilterIndirectCalls: FilterIntoDataFrom<NodeMetadataProvider::MetadataType>,
 	NodeMetadataProvider::MetadataType: NodeMetadataTrait + Clone,
+	OCallApi: EnclaveMetricsOCallApi,
 	FilterIndirectCalls::Output: IndirectDispatch<Self, TCS> + Encode + Debug,
 	EventCreator: EventsFromMetadata<NodeMetadataProvider::MetadataType>,
 	ParentchainEventHandler: HandleParentchainEvents<Self, TCS, Error>,
@@ -203,6 +214,15 @@ impl<
 			}
 		}
 		debug!("successfully processed {} indirect invocations", executed_calls.len());
+		if !executed_calls.is_empty() {
+			let metric = EnclaveMetric::IndirectCallsProcessed(
+				format!("{:?}", self.parentchain_id),
+				executed_calls.len() as u64,
+			);
+			if let Err(e) = self.ocall_api.update_metric(metric) {
+				warn!("Failed to update indirect calls metric: {:?}", e);
+			}
+		}
 		if self.parentchain_id == ParentchainId::Integritee {
 			// Include a processed parentchain block confirmation for each block.
 			Ok(Some(self.create_processed_parentchain_block_call::<ParentchainBlock>(
@@ -244,6 +264,7 @@ impl<
 		StfEnclaveSigner,
 		TopPoolAuthor,
 		NodeMetadataProvider,
+		OCallApi,
 		FilterIndirectCalls,
 		EventFilter,
 		PrivacySidechain,
@@ -255,6 +276,7 @@ impl<
 		StfEnclaveSigner,
 		TopPoolAuthor,
 		NodeMetadataProvider,
+		OCallApi,
 		FilterIndirectCalls,
 		EventFilter,
 		PrivacySidechain,
@@ -328,6 +350,7 @@ mod test {
 		types::{AccountId, TrustedOperation},
 	};
 	use itp_test::mock::{
+		metrics_ocall_mock::MetricsOCallMock,
 		shielding_crypto_mock::ShieldingCryptoMock,
 		stf_mock::{GetterMock, TrustedCallSignedMock},
 	};
@@ -343,11 +366,13 @@ mod test {
 	type TestStfEnclaveSigner = StfEnclaveSignerMock;
 	type TestTopPoolAuthor = AuthorApiMock<H256, H256, TrustedCallSignedMock, GetterMock>;
 	type TestNodeMetadataRepository = NodeMetadataRepository<NodeMetadataMock>;
+	type TestOCallApi = MetricsOCallMock;
 	type TestIndirectCallExecutor = IndirectCallsExecutor<
 		TestShieldingKeyRepo,
 		TestStfEnclaveSigner,
 		TestTopPoolAuthor,
 		TestNodeMetadataRepository,
+		TestOCallApi,
 		MockExtrinsicFilter<MockParentchainExtrinsicParser>,
 		TestEventCreator,
 		MockParentchainEventHandler,
@@ -363,7 +388,7 @@ mod test {
 	fn indirect_call_can_be_added_to_pool_successfully() {
 		let _ = env_logger::builder().is_test(true).try_init();
 
-		let (indirect_calls_executor, top_pool_author, _) =
+		let (indirect_calls_executor, top_pool_author, _, _) =
 			test_fixtures([0u8; 32], NodeMetadataMock::new());
 
 		let opaque_extrinsic =
@@ -380,12 +405,33 @@ mod test {
 		assert_eq!(1, top_pool_author.pending_tops(shard_id()).unwrap().len());
 	}
 
+	#[test]
+	fn processed_indirect_calls_are_reported_as_metric() {
+		let (indirect_calls_executor, _, _, ocall_api) =
+			test_fixtures([0u8; 32], NodeMetadataMock::new());
+
+		let opaque_extrinsic =
+			OpaqueExtrinsic::from_bytes(invoke_unchecked_extrinsic().encode().as_slice()).unwrap();
+		let parentchain_block = ParentchainBlockBuilder::default()
+			.with_extrinsics(vec![opaque_extrinsic])
+			.build();
+
+		indirect_calls_executor
+			.execute_indirect_calls_in_extrinsics(&parentchain_block, &Vec::new())
+			.unwrap();
+
+		assert_eq!(
+			ocall_api.get_metrics_updates(),
+			vec![EnclaveMetric::IndirectCallsProcessed("Integritee".into(), 1).encode()]
+		);
+	}
+
 	#[test]
 	fn shielding_call_can_be_added_to_pool_successfully() {
 		let _ = env_logger::builder().is_test(true).try_init();
 
 		let mr_enclave = [33u8; 32];
-		let (indirect_calls_executor, top_pool_author, shielding_key_repo) =
+		let (indirect_calls_executor, top_pool_author, shielding_key_repo, _) =
 			test_fixtures(mr_enclave.clone(), NodeMetadataMock::new());
 		let shielding_key = shielding_key_repo.retrieve_key().unwrap();
 
@@ -419,7 +465,7 @@ mod test {
 	fn ensure_empty_extrinsic_vec_triggers_zero_filled_merkle_root() {
 		// given
 		let dummy_metadata = NodeMetadataMock::new();
-		let (indirect_calls_executor, _, _) = test_fixtures([38u8; 32], dummy_metadata.clone());
+		let (indirect_calls_executor, _, _, _) = test_fixtures([38u8; 32], dummy_metadata.clone());
 
 		let block_hash = H256::from([1; 32]);
 		let extrinsics = Vec::new();
@@ -447,7 +493,7 @@ mod test {
 	fn ensure_non_empty_extrinsic_vec_triggers_non_zero_merkle_root() {
 		// given
 		let dummy_metadata = NodeMetadataMock::new();
-		let (indirect_calls_executor, _, _) = test_fixtures([39u8; 32], dummy_metadata.clone());
+		let (indirect_calls_executor, _, _, _) = test_fixtures([39u8; 32], dummy_metadata.clone());
 
 		let block_hash = H256::from([1; 32]);
 		let extrinsics = vec![H256::from([4; 32]), H256::from([9; 32])];
@@ -519,20 +565,27 @@ mod test {
 	fn test_fixtures(
 		mr_enclave: [u8; 32],
 		metadata: NodeMetadataMock,
-	) -> (TestIndirectCallExecutor, Arc<TestTopPoolAuthor>, Arc<TestShieldingKeyRepo>) {
+	) -> (
+		TestIndirectCallExecutor,
+		Arc<TestTopPoolAuthor>,
+		Arc<TestShieldingKeyRepo>,
+		Arc<TestOCallApi>,
+	) {
 		let shielding_key_repo = Arc::new(TestShieldingKeyRepo::default());
 		let stf_enclave_signer = Arc::new(TestStfEnclaveSigner::new(mr_enclave));
 		let top_pool_author = Arc::new(TestTopPoolAuthor::default());
 		let node_metadata_repo = Arc::new(NodeMetadataRepository::new(metadata));
+		let ocall_api = Arc::new(TestOCallApi::default());
 
 		let executor = IndirectCallsExecutor::new(
 			shielding_key_repo.clone(),
 			stf_enclave_signer,
 			top_pool_author.clone(),
 			node_metadata_repo,
+			ocall_api.clone(),
 			ParentchainId::Integritee,
 		);
 
-		(executor, top_pool_author, shielding_key_repo)
+		(executor, top_pool_author, shielding_key_repo, ocall_api)
 	}
 }
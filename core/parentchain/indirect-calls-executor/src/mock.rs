@@ -14,7 +14,10 @@ use itp_sgx_runtime_primitives::types::{AccountId, Balance};
 use itp_stf_primitives::{traits::IndirectExecutor, types::Signature};
 use itp_test::mock::stf_mock::{GetterMock, TrustedCallMock, TrustedCallSignedMock};
 use itp_types::{
-	parentchain::{BalanceTransfer, ExtrinsicStatus, FilterEvents, HandleParentchainEvents},
+	parentchain::{
+		BalanceTransfer, ExchangeRateUpdated, ExtrinsicStatus, FilterEvents,
+		HandleParentchainEvents,
+	},
 	Address, Request, ShardIdentifier, H256,
 };
 use log::*;
@@ -175,6 +178,12 @@ impl FilterEvents for MockEvents {
 		};
 		Ok(Vec::from([transfer]))
 	}
+
+	fn get_exchange_rate_updated_events(
+		&self,
+	) -> core::result::Result<Vec<ExchangeRateUpdated>, Self::Error> {
+		Ok(Vec::new())
+	}
 }
 
 pub struct MockParentchainEventHandler {}
@@ -26,6 +26,7 @@ use itp_types::DirectRequestStatus;
 use itp_utils::FromHexPrefixed;
 use log::*;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+use sp_version::RuntimeVersion;
 use std::{
 	sync::{
 		mpsc::{channel, Sender as MpscSender},
@@ -51,6 +52,8 @@ pub trait DirectApi {
 	fn get_mu_ra_url(&self) -> Result<String>;
 	fn get_untrusted_worker_url(&self) -> Result<String>;
 	fn get_state_metadata(&self) -> Result<Metadata>;
+	/// Runtime version of the STF the worker is running.
+	fn get_state_runtime_version(&self) -> Result<RuntimeVersion>;
 
 	fn send(&self, request: &str) -> Result<()>;
 	/// Close any open websocket connection.
@@ -155,6 +158,25 @@ impl DirectApi for DirectClient {
 		Metadata::try_from(metadata).map_err(|e| e.into())
 	}
 
+	fn get_state_runtime_version(&self) -> Result<RuntimeVersion> {
+		let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(
+			"state_getRuntimeVersion".to_string(),
+			Default::default(),
+		)?;
+
+		// Send json rpc call to ws server.
+		let response_str = self.get(&jsonrpc_call)?;
+
+		// Decode rpc response.
+		let rpc_response: RpcResponse = serde_json::from_str(&response_str)?;
+		let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
+			.map_err(|e| Error::Custom(format!("{:?}", e).into()))?;
+
+		let runtime_version = RuntimeVersion::decode(&mut rpc_return_value.value.as_slice())?;
+		debug!("[+] Got runtime version of enclave runtime: {:?}", runtime_version);
+		Ok(runtime_version)
+	}
+
 	fn send(&self, request: &str) -> Result<()> {
 		self.web_socket_control.send(request)
 	}
@@ -22,6 +22,7 @@ use codec::Decode;
 use frame_metadata::RuntimeMetadataPrefixed;
 use itp_api_client_types::Metadata;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+use sp_version::RuntimeVersion;
 use std::{sync::mpsc::Sender as MpscSender, thread::JoinHandle};
 
 #[derive(Clone, Default)]
@@ -30,6 +31,7 @@ pub struct DirectClientMock {
 	mu_ra_url: String,
 	untrusted_worker_url: String,
 	metadata: Vec<u8>,
+	runtime_version: RuntimeVersion,
 }
 
 impl DirectClientMock {
@@ -39,7 +41,13 @@ impl DirectClientMock {
 		untrusted_worker_url: String,
 		metadata: Vec<u8>,
 	) -> Self {
-		Self { rsa_pubkey, mu_ra_url, untrusted_worker_url, metadata }
+		Self {
+			rsa_pubkey,
+			mu_ra_url,
+			untrusted_worker_url,
+			metadata,
+			runtime_version: Default::default(),
+		}
 	}
 
 	pub fn with_rsa_pubkey(mut self, key: Rsa3072PubKey) -> Self {
@@ -61,6 +69,11 @@ impl DirectClientMock {
 		self.metadata = metadata;
 		self
 	}
+
+	pub fn with_runtime_version(mut self, runtime_version: RuntimeVersion) -> Self {
+		self.runtime_version = runtime_version;
+		self
+	}
 }
 
 impl DirectApi for DirectClientMock {
@@ -89,6 +102,10 @@ impl DirectApi for DirectClientMock {
 		Metadata::try_from(metadata).map_err(|e| e.into())
 	}
 
+	fn get_state_runtime_version(&self) -> Result<RuntimeVersion> {
+		Ok(self.runtime_version.clone())
+	}
+
 	fn send(&self, _request: &str) -> Result<()> {
 		unimplemented!()
 	}
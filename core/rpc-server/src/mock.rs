@@ -57,4 +57,11 @@ impl FetchBlocks<SignedSidechainBlock> for MockSidechainBlockFetcher {
 	) -> its_storage::Result<Vec<SignedBlock>> {
 		Ok(Vec::new())
 	}
+
+	fn last_block_hash_of_shard(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> its_storage::Result<Option<BlockHash>> {
+		Ok(None)
+	}
 }
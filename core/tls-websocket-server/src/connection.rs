@@ -19,8 +19,10 @@
 use crate::sgx_reexport_prelude::*;
 
 use crate::{
-	error::WebSocketError, stream_state::StreamState, WebSocketConnection, WebSocketMessageHandler,
-	WebSocketResult,
+	error::WebSocketError,
+	limits::{ConnectionLimiter, WebSocketRejection},
+	stream_state::StreamState,
+	WebSocketConnection, WebSocketMessageHandler, WebSocketResult,
 };
 use log::*;
 use mio::{event::Event, net::TcpStream, Poll, Ready, Token};
@@ -29,15 +31,17 @@ use std::{
 	format,
 	string::{String, ToString},
 	sync::Arc,
-	time::Instant,
+	time::{Duration, Instant},
 };
-use tungstenite::Message;
+use tungstenite::{protocol::WebSocketConfig, Message};
 
 /// A web-socket connection object.
 pub struct TungsteniteWsConnection<Handler> {
 	stream_state: StreamState,
 	connection_token: Token,
 	connection_handler: Arc<Handler>,
+	limiter: ConnectionLimiter,
+	web_socket_config: WebSocketConfig,
 	is_closed: bool,
 }
 
@@ -50,6 +54,8 @@ where
 		server_session: ServerSession,
 		connection_token: Token,
 		handler: Arc<Handler>,
+		limiter: ConnectionLimiter,
+		web_socket_config: WebSocketConfig,
 	) -> WebSocketResult<Self> {
 		Ok(TungsteniteWsConnection {
 			stream_state: StreamState::from_stream(rustls::StreamOwned::new(
@@ -58,10 +64,21 @@ where
 			)),
 			connection_token,
 			connection_handler: handler,
+			limiter,
+			web_socket_config,
 			is_closed: false,
 		})
 	}
 
+	pub(crate) fn limiter(&self) -> &ConnectionLimiter {
+		&self.limiter
+	}
+
+	/// True if there was no activity on the connection for `idle_timeout`.
+	pub(crate) fn is_idle(&self, idle_timeout: Duration) -> bool {
+		self.limiter.is_idle(idle_timeout)
+	}
+
 	fn do_tls_read(&mut self) -> ConnectionState {
 		let tls_stream = match self.stream_state.internal_stream_mut() {
 			None => return ConnectionState::Closing,
@@ -144,6 +161,16 @@ where
 				Err(e) => match e {
 					tungstenite::Error::ConnectionClosed => return Ok(true),
 					tungstenite::Error::AlreadyClosed => return Ok(true),
+					// The rest of the message is still in the stream, the connection can't
+					// continue.
+					tungstenite::Error::Capacity(_) => {
+						warn!(
+							"Message exceeds the maximum size (connection {}), closing",
+							self.connection_token.0
+						);
+						self.reject(None, WebSocketRejection::MessageTooLarge)?;
+						return Ok(true)
+					},
 					_ => error!(
 						"Failed to read message from web-socket (connection {}): {:?}",
 						self.connection_token.0, e
@@ -153,7 +180,8 @@ where
 			trace!("Read successful for connection {}", self.connection_token.0);
 		} else {
 			trace!("Initialize connection {}", self.connection_token.0);
-			self.stream_state = std::mem::take(&mut self.stream_state).attempt_handshake();
+			self.stream_state =
+				std::mem::take(&mut self.stream_state).attempt_handshake(self.web_socket_config);
 			if self.stream_state.is_invalid() {
 				warn!("Web-socket connection ({:?}) failed, closing", self.connection_token);
 				return Ok(true)
@@ -167,6 +195,15 @@ where
 	fn handle_message(&mut self, message: Message) -> WebSocketResult<()> {
 		match message {
 			Message::Text(string_message) => {
+				let requests = self.connection_handler.request_count(&string_message);
+				if let Some(rejection) = self.limiter.check_request_rate(requests)? {
+					debug!(
+						"Rejecting message (connection {}): {}",
+						self.connection_token.0,
+						rejection.as_str()
+					);
+					return self.reject(Some(&string_message), rejection)
+				}
 				trace!(
 					"Got Message::Text on web-socket (connection {}), calling handler..",
 					self.connection_token.0
@@ -224,6 +261,27 @@ where
 		Ok(())
 	}
 
+	/// Reports a rejected message and replies to it, if the handler has a reply.
+	fn reject(
+		&mut self,
+		message: Option<&str>,
+		rejection: WebSocketRejection,
+	) -> WebSocketResult<()> {
+		self.limiter.report(rejection);
+		match self.connection_handler.reject_message(
+			self.connection_token.into(),
+			message,
+			rejection,
+		) {
+			Some(reply) => self.write_message(reply),
+			None => Ok(()),
+		}
+	}
+
+	/// Writes a message to the connection.
+	///
+	/// If the client does not read its messages and the outbound queue is full, the connection is
+	/// closed.
 	pub(crate) fn write_message(&mut self, message: String) -> WebSocketResult<()> {
 		match &mut self.stream_state {
 			StreamState::EstablishedWebsocket(web_socket) => {
@@ -231,9 +289,19 @@ where
 					return Err(WebSocketError::ConnectionClosed)
 				}
 				debug!("Write message to connection {}: {}", self.connection_token.0, message);
-				web_socket
-					.write_message(Message::Text(message))
-					.map_err(|e| WebSocketError::SocketWriteError(format!("{:?}", e)))
+				match web_socket.write_message(Message::Text(message)) {
+					Ok(()) => Ok(()),
+					Err(tungstenite::Error::SendQueueFull(_)) => {
+						warn!(
+							"Outbound queue of connection {} is full, closing",
+							self.connection_token.0
+						);
+						self.limiter.report(WebSocketRejection::OutboundQueueFull);
+						self.is_closed = true;
+						Err(WebSocketError::OutboundQueueFull)
+					},
+					Err(e) => Err(WebSocketError::SocketWriteError(format!("{:?}", e))),
+				}
 			},
 			_ =>
 				Err(WebSocketError::SocketWriteError("No active web-socket available".to_string())),
@@ -271,6 +339,7 @@ where
 
 	fn on_ready(&mut self, poll: &mut Poll, event: &Event) -> WebSocketResult<()> {
 		let mut is_closing = false;
+		self.limiter.record_activity();
 
 		if event.readiness().is_readable() {
 			trace!("Connection ({:?}) is readable", self.token());
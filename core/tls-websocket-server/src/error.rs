@@ -44,6 +44,8 @@ pub enum WebSocketError {
 	ConnectionNotYetEstablished,
 	#[error("Web-socket write: {0}")]
 	SocketWriteError(String),
+	#[error("Outbound message queue of the web-socket connection is full")]
+	OutboundQueueFull,
 	#[error("Lock poisoning")]
 	LockPoisoning,
 	#[error("Failed to receive server signal message: {0}")]
@@ -41,6 +41,7 @@ use crate::{
 	config_provider::FromFileConfigProvider,
 	connection_id_generator::{ConnectionId, ConnectionIdGenerator},
 	error::{WebSocketError, WebSocketResult},
+	limits::{ObserveRejections, WebSocketRejection, WebSocketServerLimits},
 	ws_server::TungsteniteWsServer,
 };
 use mio::{event::Evented, Token};
@@ -55,6 +56,7 @@ pub mod config_provider;
 mod connection;
 pub mod connection_id_generator;
 pub mod error;
+pub mod limits;
 mod stream_state;
 mod tls_common;
 pub mod ws_server;
@@ -85,6 +87,27 @@ pub trait WebSocketMessageHandler: Send + Sync {
 		connection_token: ConnectionToken,
 		message: String,
 	) -> WebSocketResult<Option<String>>;
+
+	/// Number of requests in `message`, each of which is charged to the request rate limits.
+	fn request_count(&self, _message: &str) -> u32 {
+		1
+	}
+
+	/// Creates the reply to a message, which the server rejected without handling it.
+	///
+	/// `message` is `None` if the message could not be read, e.g. because it was too large.
+	fn reject_message(
+		&self,
+		_connection_token: ConnectionToken,
+		_message: Option<&str>,
+		_rejection: WebSocketRejection,
+	) -> Option<String> {
+		None
+	}
+
+	/// Notifies the handler that a connection has been closed, so it can release anything it
+	/// keeps for the connection.
+	fn on_connection_closed(&self, _connection_token: ConnectionToken) {}
 }
 
 /// Allows to send response messages to a specific connection.
@@ -166,6 +189,8 @@ pub fn create_ws_server<Handler>(
 	private_key: &str,
 	certificate: &str,
 	handler: Arc<Handler>,
+	limits: WebSocketServerLimits,
+	rejection_observer: Arc<dyn ObserveRejections>,
 ) -> Arc<TungsteniteWsServer<Handler, FromFileConfigProvider>>
 where
 	Handler: WebSocketMessageHandler,
@@ -173,5 +198,9 @@ where
 	let config_provider =
 		Arc::new(FromFileConfigProvider::new(private_key.to_string(), certificate.to_string()));
 
-	Arc::new(TungsteniteWsServer::new(addr_plain.to_string(), config_provider, handler))
+	Arc::new(
+		TungsteniteWsServer::new(addr_plain.to_string(), config_provider, handler)
+			.with_limits(limits)
+			.with_rejection_observer(rejection_observer),
+	)
 }
@@ -0,0 +1,285 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Limits of the web-socket server, protecting the enclave from clients flooding it with
+//! connections or requests.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use crate::error::{WebSocketError, WebSocketResult};
+use itp_types::TrustedRpcServerLimits;
+use std::{
+	net::IpAddr,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Limits of the web-socket server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketServerLimits {
+	/// Maximum number of open connections, further connections are dropped right away.
+	pub max_connections: usize,
+	/// Maximum number of requests per second of all connections from the same IP address.
+	pub max_requests_per_second_per_ip: u32,
+	/// Maximum number of requests per second of a single connection.
+	pub max_requests_per_second_per_connection: u32,
+	/// Maximum size of an incoming message in bytes.
+	pub max_message_size: usize,
+	/// Connections without any activity for this long are closed.
+	pub idle_timeout: Duration,
+	/// Maximum number of outgoing messages queued for a connection, that does not read them fast
+	/// enough. The connection is closed when the queue is full.
+	pub max_outbound_queue: usize,
+}
+
+impl Default for WebSocketServerLimits {
+	fn default() -> Self {
+		WebSocketServerLimits {
+			max_connections: 1_000,
+			max_requests_per_second_per_ip: 100,
+			max_requests_per_second_per_connection: 20,
+			max_message_size: 1 << 20,
+			idle_timeout: Duration::from_secs(300),
+			max_outbound_queue: 64,
+		}
+	}
+}
+
+impl From<TrustedRpcServerLimits> for WebSocketServerLimits {
+	/// The limits configured by the operator, falling back to the defaults for unset limits.
+	fn from(limits: TrustedRpcServerLimits) -> Self {
+		let default = WebSocketServerLimits::default();
+		WebSocketServerLimits {
+			max_connections: limits
+				.max_connections
+				.map_or(default.max_connections, |max| max as usize),
+			max_requests_per_second_per_ip: limits
+				.max_requests_per_second_per_ip
+				.unwrap_or(default.max_requests_per_second_per_ip),
+			max_requests_per_second_per_connection: limits
+				.max_requests_per_second_per_connection
+				.unwrap_or(default.max_requests_per_second_per_connection),
+			max_message_size: limits
+				.max_message_size
+				.map_or(default.max_message_size, |max| max as usize),
+			idle_timeout: limits.idle_timeout.map_or(default.idle_timeout, Duration::from_secs),
+			max_outbound_queue: limits
+				.max_outbound_queue
+				.map_or(default.max_outbound_queue, |max| max as usize),
+		}
+	}
+}
+
+/// Reason why the web-socket server rejected a connection or a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WebSocketRejection {
+	/// The maximum number of connections is reached.
+	TooManyConnections,
+	/// The request rate limit of the IP address is exceeded.
+	IpRateLimited,
+	/// The request rate limit of the connection is exceeded.
+	ConnectionRateLimited,
+	/// The message exceeds the maximum message size.
+	MessageTooLarge,
+	/// The outbound queue of the connection is full, the client does not read its messages.
+	OutboundQueueFull,
+	/// The connection has been idle for too long.
+	IdleTimeout,
+}
+
+impl WebSocketRejection {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			WebSocketRejection::TooManyConnections => "too_many_connections",
+			WebSocketRejection::IpRateLimited => "ip_rate_limited",
+			WebSocketRejection::ConnectionRateLimited => "connection_rate_limited",
+			WebSocketRejection::MessageTooLarge => "message_too_large",
+			WebSocketRejection::OutboundQueueFull => "outbound_queue_full",
+			WebSocketRejection::IdleTimeout => "idle_timeout",
+		}
+	}
+}
+
+/// Observes the connections and messages rejected by the web-socket server, e.g. to count them.
+pub trait ObserveRejections: Send + Sync {
+	fn on_rejection(&self, rejection: WebSocketRejection);
+}
+
+/// Limits the number of requests per second, in windows of one second.
+#[derive(Debug)]
+pub struct RequestRateLimiter {
+	max_requests_per_second: u32,
+	window_start: Instant,
+	requests_in_window: u32,
+}
+
+impl RequestRateLimiter {
+	pub fn new(max_requests_per_second: u32) -> Self {
+		RequestRateLimiter {
+			max_requests_per_second,
+			window_start: Instant::now(),
+			requests_in_window: 0,
+		}
+	}
+
+	/// Counts a request at `now`, returns false if it exceeds the limit.
+	pub fn try_acquire(&mut self, now: Instant) -> bool {
+		self.try_acquire_many(now, 1)
+	}
+
+	/// Counts `requests` at `now`, returns false without counting them if they exceed the limit.
+	pub fn try_acquire_many(&mut self, now: Instant, requests: u32) -> bool {
+		if now.saturating_duration_since(self.window_start) >= Duration::from_secs(1) {
+			self.window_start = now;
+			self.requests_in_window = 0;
+		}
+		let requests_in_window = self.requests_in_window.saturating_add(requests);
+		if requests_in_window > self.max_requests_per_second {
+			return false
+		}
+		self.requests_in_window = requests_in_window;
+		true
+	}
+}
+
+/// Rate limiter shared by all connections from the same IP address.
+pub(crate) type SharedRequestRateLimiter = Arc<Mutex<RequestRateLimiter>>;
+
+/// Enforces the limits of a single connection.
+pub(crate) struct ConnectionLimiter {
+	peer_ip: IpAddr,
+	rate_limiter: RequestRateLimiter,
+	ip_rate_limiter: SharedRequestRateLimiter,
+	last_activity: Instant,
+	rejection_observer: Option<Arc<dyn ObserveRejections>>,
+}
+
+impl ConnectionLimiter {
+	pub(crate) fn new(
+		peer_ip: IpAddr,
+		limits: &WebSocketServerLimits,
+		ip_rate_limiter: SharedRequestRateLimiter,
+		rejection_observer: Option<Arc<dyn ObserveRejections>>,
+	) -> Self {
+		ConnectionLimiter {
+			peer_ip,
+			rate_limiter: RequestRateLimiter::new(limits.max_requests_per_second_per_connection),
+			ip_rate_limiter,
+			last_activity: Instant::now(),
+			rejection_observer,
+		}
+	}
+
+	pub(crate) fn peer_ip(&self) -> IpAddr {
+		self.peer_ip
+	}
+
+	/// Counts `requests` against the rate limits of the connection and of its IP address.
+	pub(crate) fn check_request_rate(
+		&mut self,
+		requests: u32,
+	) -> WebSocketResult<Option<WebSocketRejection>> {
+		let now = Instant::now();
+		if !self.rate_limiter.try_acquire_many(now, requests) {
+			return Ok(Some(WebSocketRejection::ConnectionRateLimited))
+		}
+		let mut ip_rate_limiter =
+			self.ip_rate_limiter.lock().map_err(|_| WebSocketError::LockPoisoning)?;
+		if !ip_rate_limiter.try_acquire_many(now, requests) {
+			return Ok(Some(WebSocketRejection::IpRateLimited))
+		}
+		Ok(None)
+	}
+
+	pub(crate) fn record_activity(&mut self) {
+		self.last_activity = Instant::now();
+	}
+
+	pub(crate) fn is_idle(&self, idle_timeout: Duration) -> bool {
+		self.last_activity.elapsed() >= idle_timeout
+	}
+
+	pub(crate) fn report(&self, rejection: WebSocketRejection) {
+		report_rejection(self.rejection_observer.as_deref(), rejection)
+	}
+}
+
+pub(crate) fn report_rejection(
+	rejection_observer: Option<&dyn ObserveRejections>,
+	rejection: WebSocketRejection,
+) {
+	if let Some(observer) = rejection_observer {
+		observer.on_rejection(rejection);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn requests_above_limit_are_rejected_until_next_window() {
+		let mut limiter = RequestRateLimiter::new(2);
+		let start = Instant::now();
+
+		assert!(limiter.try_acquire(start));
+		assert!(limiter.try_acquire(start + Duration::from_millis(100)));
+		assert!(!limiter.try_acquire(start + Duration::from_millis(200)));
+
+		assert!(limiter.try_acquire(start + Duration::from_millis(1_100)));
+	}
+
+	#[test]
+	fn requests_are_counted_together_and_only_if_all_fit_in_limit() {
+		let mut limiter = RequestRateLimiter::new(3);
+		let now = Instant::now();
+
+		assert!(limiter.try_acquire_many(now, 2));
+		assert!(!limiter.try_acquire_many(now, 2));
+		assert!(limiter.try_acquire(now));
+		assert!(!limiter.try_acquire(now));
+	}
+
+	#[test]
+	fn zero_limit_rejects_all_requests() {
+		let mut limiter = RequestRateLimiter::new(0);
+
+		assert!(!limiter.try_acquire(Instant::now()));
+	}
+
+	#[test]
+	fn unset_configured_limits_fall_back_to_defaults() {
+		let limits = WebSocketServerLimits::from(TrustedRpcServerLimits {
+			max_connections: Some(10),
+			idle_timeout: Some(60),
+			..Default::default()
+		});
+
+		assert_eq!(
+			limits,
+			WebSocketServerLimits {
+				max_connections: 10,
+				idle_timeout: Duration::from_secs(60),
+				..Default::default()
+			}
+		);
+	}
+}
@@ -23,8 +23,9 @@ use mio::net::TcpStream;
 use rustls::ServerSession;
 use std::boxed::Box;
 use tungstenite::{
-	accept,
+	accept_with_config,
 	handshake::{server::NoCallback, MidHandshake},
+	protocol::WebSocketConfig,
 	HandshakeError, ServerHandshake, WebSocket,
 };
 
@@ -74,10 +75,11 @@ impl StreamState {
 		}
 	}
 
-	pub(crate) fn attempt_handshake(self) -> Self {
+	pub(crate) fn attempt_handshake(self, config: WebSocketConfig) -> Self {
 		match self {
 			// We have the bare TLS stream only, attempt to do a web-socket handshake.
-			StreamState::TlsStream(tls_stream) => Self::from_handshake_result(accept(*tls_stream)),
+			StreamState::TlsStream(tls_stream) =>
+				Self::from_handshake_result(accept_with_config(*tls_stream, Some(config))),
 			// We already have an on-going handshake, attempt another try.
 			StreamState::WebSocketHandshake(hs) => Self::from_handshake_result(hs.handshake()),
 			_ => self,
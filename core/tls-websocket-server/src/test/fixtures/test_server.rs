@@ -16,9 +16,13 @@
 */
 
 use crate::{
+	limits::WebSocketServerLimits,
 	test::{
 		fixtures::test_server_config_provider::TestServerConfigProvider,
-		mocks::web_socket_handler_mock::WebSocketHandlerMock,
+		mocks::{
+			rejection_observer_mock::RejectionObserverMock,
+			web_socket_handler_mock::WebSocketHandlerMock,
+		},
 	},
 	TungsteniteWsServer,
 };
@@ -39,3 +43,22 @@ pub fn create_server(
 		Arc::new(TungsteniteWsServer::new(server_addr_string, config_provider, handler.clone()));
 	(server, handler)
 }
+
+pub fn create_server_with_limits(
+	handler_responses: Vec<String>,
+	port: u16,
+	limits: WebSocketServerLimits,
+) -> (Arc<TestServer>, Arc<WebSocketHandlerMock>, Arc<RejectionObserverMock>) {
+	let config_provider = Arc::new(TestServerConfigProvider {});
+	let handler = Arc::new(WebSocketHandlerMock::from_response_sequence(handler_responses));
+	let rejection_observer = Arc::new(RejectionObserverMock::default());
+
+	let server_addr_string = format!("127.0.0.1:{}", port);
+
+	let server = Arc::new(
+		TungsteniteWsServer::new(server_addr_string, config_provider, handler.clone())
+			.with_limits(limits)
+			.with_rejection_observer(rejection_observer.clone()),
+	);
+	(server, handler, rejection_observer)
+}
@@ -15,5 +15,6 @@
 
 */
 
+pub mod rejection_observer_mock;
 pub mod web_socket_connection_mock;
 pub mod web_socket_handler_mock;
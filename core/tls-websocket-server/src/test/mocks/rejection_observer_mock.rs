@@ -0,0 +1,42 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::limits::{ObserveRejections, WebSocketRejection};
+use std::vec::Vec;
+
+#[derive(Default)]
+pub struct RejectionObserverMock {
+	rejections: RwLock<Vec<WebSocketRejection>>,
+}
+
+impl RejectionObserverMock {
+	pub fn get_rejections(&self) -> Vec<WebSocketRejection> {
+		self.rejections.read().unwrap().clone()
+	}
+}
+
+impl ObserveRejections for RejectionObserverMock {
+	fn on_rejection(&self, rejection: WebSocketRejection) {
+		self.rejections.write().unwrap().push(rejection);
+	}
+}
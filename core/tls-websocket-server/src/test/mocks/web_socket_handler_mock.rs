@@ -21,14 +21,17 @@ use std::sync::SgxRwLock as RwLock;
 #[cfg(feature = "std")]
 use std::sync::RwLock;
 
-use crate::{ConnectionToken, WebSocketMessageHandler, WebSocketResult};
+use crate::{
+	limits::WebSocketRejection, ConnectionToken, WebSocketMessageHandler, WebSocketResult,
+};
 use log::debug;
-use std::{collections::HashMap, string::String, vec::Vec};
+use std::{collections::HashMap, format, string::String, vec::Vec};
 
 pub struct WebSocketHandlerMock {
 	pub responses: Vec<String>,
 	pub connection_message_indices: RwLock<HashMap<ConnectionToken, usize>>,
 	pub messages_handled: RwLock<Vec<(ConnectionToken, String)>>,
+	pub connections_closed: RwLock<Vec<ConnectionToken>>,
 }
 
 impl WebSocketHandlerMock {
@@ -37,12 +40,21 @@ impl WebSocketHandlerMock {
 			responses,
 			connection_message_indices: RwLock::default(),
 			messages_handled: Default::default(),
+			connections_closed: Default::default(),
 		}
 	}
 
 	pub fn get_handled_messages(&self) -> Vec<(ConnectionToken, String)> {
 		self.messages_handled.read().unwrap().clone()
 	}
+
+	pub fn get_closed_connections(&self) -> Vec<ConnectionToken> {
+		self.connections_closed.read().unwrap().clone()
+	}
+
+	pub fn rejection_reply(rejection: WebSocketRejection) -> String {
+		format!("rejected: {}", rejection.as_str())
+	}
 }
 
 impl WebSocketMessageHandler for WebSocketHandlerMock {
@@ -65,4 +77,17 @@ impl WebSocketMessageHandler for WebSocketHandlerMock {
 		*message_index += 1;
 		Ok(response)
 	}
+
+	fn reject_message(
+		&self,
+		_connection_token: ConnectionToken,
+		_message: Option<&str>,
+		rejection: WebSocketRejection,
+	) -> Option<String> {
+		Some(Self::rejection_reply(rejection))
+	}
+
+	fn on_connection_closed(&self, connection_token: ConnectionToken) {
+		self.connections_closed.write().unwrap().push(connection_token);
+	}
 }
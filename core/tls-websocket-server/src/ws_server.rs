@@ -29,6 +29,10 @@ use crate::{
 	connection::TungsteniteWsConnection,
 	connection_id_generator::GenerateConnectionId,
 	error::{WebSocketError, WebSocketResult},
+	limits::{
+		report_rejection, ConnectionLimiter, ObserveRejections, RequestRateLimiter,
+		SharedRequestRateLimiter, WebSocketRejection, WebSocketServerLimits,
+	},
 	ConnectionIdGenerator, ConnectionToken, WebSocketConnection, WebSocketMessageHandler,
 	WebSocketResponder, WebSocketServer,
 };
@@ -41,12 +45,24 @@ use mio::{
 use mio_extras::channel::{channel, Receiver, Sender};
 use net::SocketAddr;
 use rustls::ServerConfig;
-use std::{collections::HashMap, format, net, string::String, sync::Arc};
+use std::{
+	collections::HashMap,
+	format,
+	net::{self, IpAddr},
+	string::String,
+	sync::Arc,
+	time::{Duration, Instant},
+	vec::Vec,
+};
+use tungstenite::protocol::WebSocketConfig;
 
 // Default tokens for the server.
 pub(crate) const NEW_CONNECTIONS_LISTENER: mio::Token = mio::Token(0);
 pub(crate) const SERVER_SIGNAL_TOKEN: mio::Token = mio::Token(1);
 
+/// Interval in which connections are checked for the idle timeout.
+const IDLE_CONNECTIONS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Secure web-socket server implementation using the Tungstenite library.
 pub struct TungsteniteWsServer<Handler, ConfigProvider> {
 	ws_address: String,
@@ -56,6 +72,9 @@ pub struct TungsteniteWsServer<Handler, ConfigProvider> {
 	connections: RwLock<HashMap<mio::Token, TungsteniteWsConnection<Handler>>>,
 	is_running: RwLock<bool>,
 	signal_sender: Mutex<Option<Sender<ServerSignal>>>,
+	limits: WebSocketServerLimits,
+	rejection_observer: Option<Arc<dyn ObserveRejections>>,
+	ip_rate_limiters: Mutex<HashMap<IpAddr, SharedRequestRateLimiter>>,
 }
 
 impl<Handler, ConfigProvider> TungsteniteWsServer<Handler, ConfigProvider>
@@ -76,6 +95,28 @@ where
 			connections: Default::default(),
 			is_running: Default::default(),
 			signal_sender: Default::default(),
+			limits: Default::default(),
+			rejection_observer: None,
+			ip_rate_limiters: Default::default(),
+		}
+	}
+
+	pub fn with_limits(mut self, limits: WebSocketServerLimits) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	pub fn with_rejection_observer(mut self, observer: Arc<dyn ObserveRejections>) -> Self {
+		self.rejection_observer = Some(observer);
+		self
+	}
+
+	fn web_socket_config(&self) -> WebSocketConfig {
+		WebSocketConfig {
+			max_send_queue: Some(self.limits.max_outbound_queue),
+			max_message_size: Some(self.limits.max_message_size),
+			max_frame_size: Some(self.limits.max_message_size),
+			..Default::default()
 		}
 	}
 
@@ -87,6 +128,18 @@ where
 	) -> WebSocketResult<()> {
 		let (socket, addr) = tcp_listener.accept()?;
 
+		let mut connections_lock =
+			self.connections.write().map_err(|_| WebSocketError::LockPoisoning)?;
+		if connections_lock.len() >= self.limits.max_connections {
+			// Dropping the socket closes the connection.
+			warn!("Maximum number of connections reached, dropping connection from {:?}", addr);
+			report_rejection(
+				self.rejection_observer.as_deref(),
+				WebSocketRejection::TooManyConnections,
+			);
+			return Ok(())
+		}
+
 		debug!("Accepting new connection from {:?}", addr);
 
 		let tls_session = rustls::ServerSession::new(&tls_config);
@@ -94,18 +147,36 @@ where
 		let token = mio::Token(connection_id);
 		trace!("New connection has token {:?}", token);
 
+		let ip_rate_limiter = self
+			.ip_rate_limiters
+			.lock()
+			.map_err(|_| WebSocketError::LockPoisoning)?
+			.entry(addr.ip())
+			.or_insert_with(|| {
+				Arc::new(Mutex::new(RequestRateLimiter::new(
+					self.limits.max_requests_per_second_per_ip,
+				)))
+			})
+			.clone();
+		let limiter = ConnectionLimiter::new(
+			addr.ip(),
+			&self.limits,
+			ip_rate_limiter,
+			self.rejection_observer.clone(),
+		);
+
 		let mut web_socket_connection = TungsteniteWsConnection::new(
 			socket,
 			tls_session,
 			token,
 			self.connection_handler.clone(),
+			limiter,
+			self.web_socket_config(),
 		)?;
 
 		trace!("Web-socket connection created");
 		web_socket_connection.register(poll)?;
 
-		let mut connections_lock =
-			self.connections.write().map_err(|_| WebSocketError::LockPoisoning)?;
 		connections_lock.insert(token, web_socket_connection);
 
 		debug!("Accepted connection, {} active connections", connections_lock.len());
@@ -123,18 +194,58 @@ where
 
 			if connection.is_closed() {
 				trace!("Connection {:?} is closed, removing", token);
-				connections_lock.remove(&token);
-				trace!(
-					"Closed {:?}, {} active connections remaining",
-					token,
-					connections_lock.len()
-				);
+				self.remove_connection(&mut connections_lock, token)?;
 			}
 		}
 
 		Ok(())
 	}
 
+	/// Closes the connections, that have been idle for longer than the idle timeout.
+	fn close_idle_connections(&self) -> WebSocketResult<()> {
+		let mut connections_lock =
+			self.connections.write().map_err(|_| WebSocketError::LockPoisoning)?;
+
+		let idle_connections: Vec<mio::Token> = connections_lock
+			.iter()
+			.filter(|(_, connection)| connection.is_idle(self.limits.idle_timeout))
+			.map(|(token, _)| *token)
+			.collect();
+
+		for token in idle_connections {
+			debug!("Connection {:?} is idle, closing", token);
+			report_rejection(self.rejection_observer.as_deref(), WebSocketRejection::IdleTimeout);
+			self.remove_connection(&mut connections_lock, token)?;
+		}
+		Ok(())
+	}
+
+	/// Removes a connection, which closes it, and releases everything kept for it.
+	fn remove_connection(
+		&self,
+		connections: &mut HashMap<mio::Token, TungsteniteWsConnection<Handler>>,
+		token: mio::Token,
+	) -> WebSocketResult<()> {
+		let connection = match connections.remove(&token) {
+			Some(connection) => connection,
+			None => return Ok(()),
+		};
+		let peer_ip = connection.limiter().peer_ip();
+		drop(connection);
+
+		self.connection_handler.on_connection_closed(token.into());
+
+		// Release the rate limiter of the IP address, unless another connection still uses it.
+		let mut ip_rate_limiters =
+			self.ip_rate_limiters.lock().map_err(|_| WebSocketError::LockPoisoning)?;
+		if ip_rate_limiters.get(&peer_ip).map_or(false, |l| Arc::strong_count(l) == 1) {
+			ip_rate_limiters.remove(&peer_ip);
+		}
+
+		trace!("Closed {:?}, {} active connections remaining", token, connections.len());
+		Ok(())
+	}
+
 	/// Send a message response to a connection.
 	/// Make sure this is called inside the event loop, otherwise dead-locks are possible.
 	fn write_message_to_connection(
@@ -147,7 +258,12 @@ where
 		let connection = connections_lock
 			.get_mut(&connection_token.into())
 			.ok_or_else(|| WebSocketError::InvalidConnection(connection_token.0))?;
-		connection.write_message(message)
+		let write_result = connection.write_message(message);
+
+		if connection.is_closed() {
+			self.remove_connection(&mut connections_lock, connection_token.into())?;
+		}
+		write_result
 	}
 
 	fn handle_server_signal(
@@ -245,11 +361,20 @@ where
 
 		*self.is_running.write().map_err(|_| WebSocketError::LockPoisoning)? = true;
 
+		let mut last_idle_connections_check = Instant::now();
+
 		// Run the event loop.
 		'outer_event_loop: loop {
-			let num_events = poll.poll(&mut events, None)?;
+			let num_events = poll.poll(&mut events, Some(IDLE_CONNECTIONS_CHECK_INTERVAL))?;
 			debug!("Number of readiness events: {}", num_events);
 
+			if last_idle_connections_check.elapsed() >= IDLE_CONNECTIONS_CHECK_INTERVAL {
+				if let Err(e) = self.close_idle_connections() {
+					error!("Failed to close idle web-socket connections: {:?}", e);
+				}
+				last_idle_connections_check = Instant::now();
+			}
+
 			for event in events.iter() {
 				match event.token() {
 					NEW_CONNECTIONS_LISTENER => {
@@ -314,7 +439,10 @@ enum ServerSignal {
 mod tests {
 	use super::*;
 	use crate::test::{
-		fixtures::{no_cert_verifier::NoCertVerifier, test_server::create_server},
+		fixtures::{
+			no_cert_verifier::NoCertVerifier,
+			test_server::{create_server, create_server_with_limits},
+		},
 		mocks::web_socket_handler_mock::WebSocketHandlerMock,
 	};
 	use rustls::ClientConfig;
@@ -476,6 +604,114 @@ mod tests {
 		assert_eq!(1, handler.get_handled_messages().len());
 	}
 
+	#[test]
+	fn server_replies_with_rejection_when_request_rate_is_exceeded() {
+		let _ = env_logger::builder().is_test(true).try_init();
+
+		let expected_answer = "first response".to_string();
+		let port: u16 = 21780;
+		let limits = WebSocketServerLimits {
+			max_requests_per_second_per_connection: 1,
+			..Default::default()
+		};
+		let (server, handler, rejection_observer) =
+			create_server_with_limits(vec![expected_answer.clone()], port, limits);
+
+		let server_clone = server.clone();
+		let server_join_handle = thread::spawn(move || server_clone.run());
+
+		// Wait until server is up.
+		while !server.is_running().unwrap() {
+			thread::sleep(std::time::Duration::from_millis(50));
+		}
+
+		let mut socket = connect_tls_client(get_server_addr(port).as_str());
+		socket.write_message(Message::Text("First request".into())).unwrap();
+		assert_eq!(Message::Text(expected_answer), socket.read_message().unwrap());
+
+		socket.write_message(Message::Text("Second request".into())).unwrap();
+		assert_eq!(
+			Message::Text(WebSocketHandlerMock::rejection_reply(
+				WebSocketRejection::ConnectionRateLimited
+			)),
+			socket.read_message().unwrap()
+		);
+
+		socket.close(None).unwrap();
+		socket.write_pending().unwrap();
+		server.shut_down().unwrap();
+		server_join_handle.join().unwrap().unwrap();
+
+		assert_eq!(1, handler.get_handled_messages().len());
+		assert_eq!(
+			rejection_observer.get_rejections(),
+			vec![WebSocketRejection::ConnectionRateLimited]
+		);
+	}
+
+	#[test]
+	fn server_drops_connections_above_maximum() {
+		let _ = env_logger::builder().is_test(true).try_init();
+
+		let port: u16 = 21781;
+		let limits = WebSocketServerLimits { max_connections: 1, ..Default::default() };
+		let (server, _, rejection_observer) =
+			create_server_with_limits(vec!["response".to_string()], port, limits);
+
+		let server_clone = server.clone();
+		let server_join_handle = thread::spawn(move || server_clone.run());
+
+		// Wait until server is up.
+		while !server.is_running().unwrap() {
+			thread::sleep(std::time::Duration::from_millis(50));
+		}
+
+		let _first_socket = connect_tls_client(get_server_addr(port).as_str());
+		assert!(try_connect_tls_client(get_server_addr(port).as_str()).is_err());
+
+		server.shut_down().unwrap();
+		server_join_handle.join().unwrap().unwrap();
+
+		assert_eq!(
+			rejection_observer.get_rejections(),
+			vec![WebSocketRejection::TooManyConnections]
+		);
+	}
+
+	#[test]
+	fn server_closes_idle_connections() {
+		let _ = env_logger::builder().is_test(true).try_init();
+
+		let port: u16 = 21782;
+		let limits = WebSocketServerLimits {
+			idle_timeout: Duration::from_millis(100),
+			..Default::default()
+		};
+		let (server, handler, rejection_observer) =
+			create_server_with_limits(vec!["response".to_string()], port, limits);
+
+		let server_clone = server.clone();
+		let server_join_handle = thread::spawn(move || server_clone.run());
+
+		// Wait until server is up.
+		while !server.is_running().unwrap() {
+			thread::sleep(std::time::Duration::from_millis(50));
+		}
+
+		let mut socket = connect_tls_client(get_server_addr(port).as_str());
+		socket.write_message(Message::Text("First request".into())).unwrap();
+		assert_eq!(Message::Text("response".to_string()), socket.read_message().unwrap());
+
+		thread::sleep(IDLE_CONNECTIONS_CHECK_INTERVAL * 2);
+		assert!(socket.read_message().is_err());
+
+		server.shut_down().unwrap();
+		server_join_handle.join().unwrap().unwrap();
+
+		assert_eq!(rejection_observer.get_rejections(), vec![WebSocketRejection::IdleTimeout]);
+		assert_eq!(handler.get_closed_connections().len(), 1);
+	}
+
 	// Ignored because it does not directly test any of our own components.
 	// It was used to test the behavior of the tungstenite client configuration with certificates.
 	#[test]
@@ -502,6 +738,12 @@ mod tests {
 	}
 
 	fn connect_tls_client(server_addr: &str) -> WebSocket<MaybeTlsStream<TcpStream>> {
+		try_connect_tls_client(server_addr).expect("Can't connect")
+	}
+
+	fn try_connect_tls_client(
+		server_addr: &str,
+	) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, String> {
 		let ws_server_url = Url::parse(format!("wss://{}", server_addr).as_str()).unwrap();
 
 		let mut config = ClientConfig::new();
@@ -509,10 +751,8 @@ mod tests {
 		let connector = Connector::Rustls(Arc::new(config));
 		let stream = TcpStream::connect(server_addr).unwrap();
 
-		let (socket, _response) =
-			client_tls_with_config(ws_server_url, stream, None, Some(connector))
-				.expect("Can't connect");
-
-		socket
+		client_tls_with_config(ws_server_url, stream, None, Some(connector))
+			.map(|(socket, _response)| socket)
+			.map_err(|e| format!("{:?}", e))
 	}
 }
@@ -0,0 +1,151 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Ecalls to operate a running enclave, used by the admin rpc of the worker.
+
+use crate::{
+	error::{Error, Result as EnclaveResult},
+	initialization::global_components::{
+		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
+		GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
+	},
+};
+use codec::Encode;
+use itp_component_container::ComponentGetter;
+use itp_sgx_crypto::key_repository::{AccessPubkey, MutateKey};
+use itp_stf_state_handler::handle_state::HandleState;
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{ShardIdentifier, H256};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
+use sgx_types::sgx_status_t;
+use std::{slice, string::String, vec::Vec};
+
+#[no_mangle]
+pub unsafe extern "C" fn get_pending_trusted_operations(
+	shard: *const u8,
+	shard_size: u32,
+	operations: *mut u8,
+	operations_max_size: u32,
+	operations_size: *mut u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let encoded_operations = match get_pending_trusted_operations_internal(shard_identifier) {
+		Ok(operations) => operations.encode(),
+		Err(e) => {
+			error!("Failed to get pending trusted operations ({:?}): {:?}", shard_identifier, e);
+			return e.into()
+		},
+	};
+
+	if encoded_operations.len() > operations_max_size as usize {
+		error!(
+			"Pending trusted operations ({} bytes) exceed the maximum size of {} bytes",
+			encoded_operations.len(),
+			operations_max_size
+		);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	let operations_slice = slice::from_raw_parts_mut(operations, operations_max_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(operations_slice, encoded_operations.clone()) {
+		return Error::BufferError(e).into()
+	};
+	*operations_size = encoded_operations.len() as u32;
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn get_pending_trusted_operations_internal(shard: ShardIdentifier) -> EnclaveResult<Vec<Vec<u8>>> {
+	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+	Ok(top_pool_author.pending_tops(shard)?)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn create_state_snapshot(
+	shard: *const u8,
+	shard_size: u32,
+	state_hash: *mut u8,
+	state_hash_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let hash = match create_state_snapshot_internal(shard_identifier) {
+		Ok(hash) => hash,
+		Err(e) => {
+			error!("Failed to create state snapshot ({:?}): {:?}", shard_identifier, e);
+			return e.into()
+		},
+	};
+
+	let state_hash_slice = slice::from_raw_parts_mut(state_hash, state_hash_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(state_hash_slice, hash.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Writes the unchanged state of a shard, which creates a new snapshot of it.
+fn create_state_snapshot_internal(shard: ShardIdentifier) -> EnclaveResult<H256> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	let (state_lock, state) = state_handler.load_for_mutation(&shard)?;
+	let state_hash = state_handler.write_after_mutation(state, state_lock, &shard)?;
+	info!("Created state snapshot for shard {:?}, state hash: {:?}", shard, state_hash);
+	Ok(state_hash)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rotate_shielding_key(pubkey: *mut u8, pubkey_size: u32) -> sgx_status_t {
+	let rsa_pubkey_json = match rotate_shielding_key_internal() {
+		Ok(json) => json,
+		Err(e) => {
+			error!("Failed to rotate the shielding key: {:?}", e);
+			return e.into()
+		},
+	};
+
+	let pubkey_slice = slice::from_raw_parts_mut(pubkey, pubkey_size as usize);
+	if let Err(e) =
+		write_slice_and_whitespace_pad(pubkey_slice, rsa_pubkey_json.as_bytes().to_vec())
+	{
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Generates and seals a new shielding key. Shards created afterwards derive their enclave
+/// account from the new key, existing shards are not affected.
+///
+/// The previous key still decrypts for `SHIELDING_KEY_ROTATION_GRACE_PERIOD`, which gives the
+/// peers time to fetch the new key from us.
+fn rotate_shielding_key_internal() -> EnclaveResult<String> {
+	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
+	let rsa_keypair = Rsa3072KeyPair::new()
+		.map_err(|e| Error::Other(format!("Failed to generate RSA key pair: {:?}", e).into()))?;
+	shielding_key_repository.update_key(rsa_keypair)?;
+
+	let rsa_pubkey = shielding_key_repository.retrieve_pubkey()?;
+	info!("Rotated shielding key, new RSA pubkey: {:?}", rsa_pubkey);
+	serde_json::to_string(&rsa_pubkey)
+		.map_err(|e| Error::Other(format!("Failed to serialize RSA pubkey: {:?}", e).into()))
+}
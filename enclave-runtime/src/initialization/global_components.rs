@@ -27,7 +27,10 @@ use crate::{
 		target_b_parachain::TargetBParachainHandler, target_b_solochain::TargetBSolochainHandler,
 	},
 	ocall::OcallApi,
-	rpc::rpc_response_channel::RpcResponseChannel,
+	rpc::{
+		block_import_notifier::SubscriptionBlockImportNotifier,
+		rpc_response_channel::RpcResponseChannel,
+	},
 	tls_ra::seal_handler::SealHandler,
 };
 use ita_parentchain_interface::{integritee, target_a, target_b};
@@ -35,7 +38,9 @@ use ita_sgx_runtime::Runtime;
 use ita_stf::{Getter, State as StfState, Stf, TrustedCallSigned};
 use itc_direct_rpc_server::{
 	rpc_connection_registry::ConnectionRegistry, rpc_responder::RpcResponder,
-	rpc_watch_extractor::RpcWatchExtractor, rpc_ws_handler::RpcWsHandler,
+	rpc_subscription_notifier::RpcSubscriptionNotifier,
+	rpc_subscription_registry::SubscriptionRegistry, rpc_watch_extractor::RpcWatchExtractor,
+	rpc_ws_handler::RpcWsHandler,
 };
 use itc_parentchain::{
 	block_import_dispatcher::{
@@ -61,7 +66,10 @@ use itp_node_api::{
 	metadata::{provider::NodeMetadataRepository, NodeMetadata},
 };
 use itp_nonce_cache::NonceCache;
-use itp_sgx_crypto::{key_repository::KeyRepository, Aes, AesSeal, Ed25519Seal, Rsa3072Seal};
+use itp_sgx_crypto::{
+	key_repository::{FallbackKeyRepository, KeyRepository, RotatingKeyRepository},
+	Aes, AesSeal, Ed25519Seal, Rsa3072Seal,
+};
 use itp_stf_executor::{
 	enclave_signer::StfEnclaveSigner, executor::StfExecutor, getter_executor::GetterExecutor,
 	state_getter::StfStateGetter,
@@ -69,7 +77,8 @@ use itp_stf_executor::{
 use itp_stf_primitives::types::{Hash, TrustedOperation};
 use itp_stf_state_handler::{
 	file_io::sgx::SgxStateFileIo, state_initializer::StateInitializer,
-	state_snapshot_repository::StateSnapshotRepository, StateHandler,
+	state_migrator::StateMigrator, state_snapshot_repository::StateSnapshotRepository,
+	StateHandler,
 };
 use itp_stf_state_observer::state_observer::StateObserver;
 use itp_top_pool::basic_pool::BasicPool;
@@ -100,15 +109,24 @@ pub type EnclaveGetter = Getter;
 pub type EnclaveTrustedCallSigned = TrustedCallSigned;
 pub type EnclaveStf = Stf<EnclaveTrustedCallSigned, EnclaveGetter, StfState, Runtime>;
 pub type EnclaveStateKeyRepository = KeyRepository<Aes, AesSeal>;
-pub type EnclaveShieldingKeyRepository = KeyRepository<Rsa3072KeyPair, Rsa3072Seal>;
+pub type EnclaveSealedShieldingKeyRepository = KeyRepository<Rsa3072KeyPair, Rsa3072Seal>;
+pub type EnclaveShieldingKeyRepository =
+	RotatingKeyRepository<Rsa3072KeyPair, EnclaveSealedShieldingKeyRepository>;
+pub type EnclaveShieldingKeyWithFallbackRepository =
+	FallbackKeyRepository<Rsa3072KeyPair, EnclaveSealedShieldingKeyRepository>;
 pub type EnclaveSigningKeyRepository = KeyRepository<ed25519::Pair, Ed25519Seal>;
 pub type EnclaveStateFileIo = SgxStateFileIo<EnclaveStateKeyRepository, StfState>;
 pub type EnclaveStateSnapshotRepository = StateSnapshotRepository<EnclaveStateFileIo>;
 pub type EnclaveStateObserver = StateObserver<StfState>;
 pub type EnclaveStateInitializer =
 	StateInitializer<StfState, EnclaveStf, EnclaveShieldingKeyRepository>;
-pub type EnclaveStateHandler =
-	StateHandler<EnclaveStateSnapshotRepository, EnclaveStateObserver, EnclaveStateInitializer>;
+pub type EnclaveStateMigrator = StateMigrator<StfState, EnclaveStf>;
+pub type EnclaveStateHandler = StateHandler<
+	EnclaveStateSnapshotRepository,
+	EnclaveStateObserver,
+	EnclaveStateInitializer,
+	EnclaveStateMigrator,
+>;
 pub type EnclaveGetterExecutor =
 	GetterExecutor<EnclaveStateObserver, StfStateGetter<EnclaveStf>, Getter>;
 pub type EnclaveOCallApi = OcallApi;
@@ -134,10 +152,17 @@ pub type EnclaveAttestationHandler =
 	IntelAttestationHandler<EnclaveOCallApi, EnclaveSigningKeyRepository>;
 
 pub type EnclaveRpcConnectionRegistry = ConnectionRegistry<Hash, ConnectionToken>;
-pub type EnclaveRpcWsHandler =
-	RpcWsHandler<RpcWatchExtractor<Hash>, EnclaveRpcConnectionRegistry, Hash>;
+pub type EnclaveRpcSubscriptionRegistry = SubscriptionRegistry<ConnectionToken>;
+pub type EnclaveRpcWsHandler = RpcWsHandler<
+	RpcWatchExtractor<Hash>,
+	EnclaveRpcConnectionRegistry,
+	EnclaveRpcSubscriptionRegistry,
+	Hash,
+>;
 pub type EnclaveWebSocketServer = TungsteniteWsServer<EnclaveRpcWsHandler, FromFileConfigProvider>;
 pub type EnclaveRpcResponder = RpcResponder<EnclaveRpcConnectionRegistry, Hash, RpcResponseChannel>;
+pub type EnclaveRpcSubscriptionNotifier =
+	RpcSubscriptionNotifier<EnclaveRpcSubscriptionRegistry, RpcResponseChannel>;
 pub type EnclaveSidechainApi = SidechainApi<ParentchainBlock, EnclaveTrustedCallSigned>;
 
 // Parentchain types relevant for all parentchains
@@ -166,10 +191,11 @@ pub type TargetBParentchainEventImportQueue = ImportQueue<Vec<u8>>;
 // Stuff for the integritee parentchain
 
 pub type IntegriteeParentchainIndirectCallsExecutor = IndirectCallsExecutor<
-	EnclaveShieldingKeyRepository,
+	EnclaveShieldingKeyWithFallbackRepository,
 	EnclaveStfEnclaveSigner,
 	EnclaveTopPoolAuthor,
 	EnclaveNodeMetadataRepository,
+	EnclaveOCallApi,
 	integritee::ExtrinsicFilter,
 	EventCreator<integritee::FilterableEvents>,
 	integritee::ParentchainEventHandler,
@@ -208,10 +234,11 @@ pub type IntegriteeParentchainBlockImportDispatcher = BlockImportDispatcher<
 /// Also note that the extrinsic parser must be changed if the signed extra contains the
 /// `AssetTxPayment`.
 pub type TargetAParentchainIndirectCallsExecutor = IndirectCallsExecutor<
-	EnclaveShieldingKeyRepository,
+	EnclaveShieldingKeyWithFallbackRepository,
 	EnclaveStfEnclaveSigner,
 	EnclaveTopPoolAuthor,
 	EnclaveNodeMetadataRepository,
+	EnclaveOCallApi,
 	target_a::ExtrinsicFilter,
 	EventCreator<target_a::FilterableEvents>,
 	target_a::ParentchainEventHandler,
@@ -250,10 +277,11 @@ pub type TargetAParentchainBlockImportDispatcher = BlockImportDispatcher<
 /// Also note that the extrinsic parser must be changed if the signed extra contains the
 /// `AssetTxPayment`.
 pub type TargetBParentchainIndirectCallsExecutor = IndirectCallsExecutor<
-	EnclaveShieldingKeyRepository,
+	EnclaveShieldingKeyWithFallbackRepository,
 	EnclaveStfEnclaveSigner,
 	EnclaveTopPoolAuthor,
 	EnclaveNodeMetadataRepository,
+	EnclaveOCallApi,
 	target_b::ExtrinsicFilter,
 	EventCreator<target_b::FilterableEvents>,
 	target_b::ParentchainEventHandler,
@@ -295,7 +323,7 @@ pub type EnclaveTopPoolAuthor = Author<
 	EnclaveTopPool,
 	AuthorTopFilter<EnclaveTrustedCallSigned, EnclaveGetter>,
 	EnclaveStateHandler,
-	EnclaveShieldingKeyRepository,
+	EnclaveShieldingKeyWithFallbackRepository,
 	EnclaveOCallApi,
 	EnclaveTrustedCallSigned,
 	EnclaveGetter,
@@ -312,9 +340,12 @@ pub type EnclaveSidechainBlockImporter = SidechainBlockImporter<
 	EnclaveTopPoolAuthor,
 	// For now the sidechain does only support one parentchain.
 	IntegriteeParentchainTriggeredBlockImportDispatcher,
+	EnclaveSidechainBlockImportNotifier,
 	EnclaveTrustedCallSigned,
 	EnclaveGetter,
 >;
+pub type EnclaveSidechainBlockImportNotifier =
+	SubscriptionBlockImportNotifier<EnclaveRpcSubscriptionNotifier>;
 pub type EnclaveSidechainBlockImportQueue = ImportQueue<SignedSidechainBlock>;
 pub type EnclaveBlockImportConfirmationHandler = BlockImportConfirmationHandler<
 	ParentchainBlock,
@@ -366,6 +397,11 @@ pub static GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT: ComponentContainer<
 	EnclaveShieldingKeyRepository,
 > = ComponentContainer::new("Shielding key repository");
 
+/// Shielding key repository that still decrypts with the previous key during its grace period
+pub static GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT: ComponentContainer<
+	EnclaveShieldingKeyWithFallbackRepository,
+> = ComponentContainer::new("Shielding key with fallback repository");
+
 /// Signing key repository
 pub static GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT: ComponentContainer<
 	EnclaveSigningKeyRepository,
@@ -456,6 +492,11 @@ pub static GLOBAL_TARGET_B_PARACHAIN_HANDLER_COMPONENT: ComponentContainer<
 pub static GLOBAL_RPC_WS_HANDLER_COMPONENT: ComponentContainer<EnclaveRpcWsHandler> =
 	ComponentContainer::new("rpc_ws_handler");
 
+/// Enclave RPC subscription notifier.
+pub static GLOBAL_RPC_SUBSCRIPTION_NOTIFIER_COMPONENT: ComponentContainer<
+	EnclaveRpcSubscriptionNotifier,
+> = ComponentContainer::new("rpc_subscription_notifier");
+
 /// Sidechain import queue.
 pub static GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT: ComponentContainer<
 	EnclaveSidechainBlockImportQueue,
@@ -22,18 +22,23 @@ use crate::{
 	initialization::global_components::{
 		EnclaveBlockImportConfirmationHandler, EnclaveGetterExecutor, EnclaveLightClientSeal,
 		EnclaveOCallApi, EnclaveRpcConnectionRegistry, EnclaveRpcResponder,
-		EnclaveShieldingKeyRepository, EnclaveSidechainApi, EnclaveSidechainBlockImportQueue,
+		EnclaveRpcSubscriptionNotifier, EnclaveRpcSubscriptionRegistry,
+		EnclaveShieldingKeyRepository, EnclaveShieldingKeyWithFallbackRepository,
+		EnclaveSidechainApi, EnclaveSidechainBlockImportNotifier, EnclaveSidechainBlockImportQueue,
 		EnclaveSidechainBlockImportQueueWorker, EnclaveSidechainBlockImporter,
 		EnclaveSidechainBlockSyncer, EnclaveStateFileIo, EnclaveStateHandler,
-		EnclaveStateInitializer, EnclaveStateObserver, EnclaveStateSnapshotRepository,
-		EnclaveStfEnclaveSigner, EnclaveTopPool, EnclaveTopPoolAuthor,
-		GLOBAL_ATTESTATION_HANDLER_COMPONENT, GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL,
-		GLOBAL_OCALL_API_COMPONENT, GLOBAL_RPC_WS_HANDLER_COMPONENT,
-		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
-		GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT,
-		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
-		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
-		GLOBAL_STATE_OBSERVER_COMPONENT, GLOBAL_TARGET_A_PARENTCHAIN_LIGHT_CLIENT_SEAL,
+		EnclaveStateInitializer, EnclaveStateMigrator, EnclaveStateObserver,
+		EnclaveStateSnapshotRepository, EnclaveStfEnclaveSigner, EnclaveTopPool,
+		EnclaveTopPoolAuthor, GLOBAL_ATTESTATION_HANDLER_COMPONENT,
+		GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL, GLOBAL_OCALL_API_COMPONENT,
+		GLOBAL_RPC_SUBSCRIPTION_NOTIFIER_COMPONENT, GLOBAL_RPC_WS_HANDLER_COMPONENT,
+		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT,
+		GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT,
+		GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT,
+		GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
+		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_OBSERVER_COMPONENT,
+		GLOBAL_TARGET_A_PARENTCHAIN_LIGHT_CLIENT_SEAL,
 		GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
 		GLOBAL_WEB_SOCKET_SERVER_COMPONENT,
 	},
@@ -61,9 +66,12 @@ use itc_tls_websocket_server::{
 use itp_attestation_handler::IntelAttestationHandler;
 use itp_component_container::{ComponentGetter, ComponentInitializer};
 use itp_primitives_cache::GLOBAL_PRIMITIVES_CACHE;
-use itp_settings::files::{
-	INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, STATE_SNAPSHOTS_CACHE_SIZE,
-	TARGET_A_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, TARGET_B_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
+use itp_settings::{
+	enclave::SHIELDING_KEY_ROTATION_GRACE_PERIOD,
+	files::{
+		INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, STATE_SNAPSHOTS_CACHE_SIZE,
+		TARGET_A_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, TARGET_B_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
+	},
 };
 use itp_sgx_crypto::{
 	get_aes_repository, get_ed25519_repository, get_rsa3072_repository, key_repository::AccessKey,
@@ -75,7 +83,7 @@ use itp_stf_state_handler::{
 };
 use itp_top_pool::pool::Options as PoolOptions;
 use itp_top_pool_author::author::AuthorTopFilter;
-use itp_types::{parentchain::ParentchainId, ShardIdentifier};
+use itp_types::{parentchain::ParentchainId, ShardIdentifier, TrustedRpcServerLimits};
 use its_sidechain::block_composer::BlockComposer;
 use log::*;
 use sp_core::crypto::Pair;
@@ -84,14 +92,22 @@ pub(crate) fn init_enclave(
 	mu_ra_url: String,
 	untrusted_worker_url: String,
 	base_dir: PathBuf,
+	state_migration_dry_run: bool,
 ) -> EnclaveResult<()> {
 	let signing_key_repository = Arc::new(get_ed25519_repository(base_dir.clone())?);
 	GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.initialize(signing_key_repository.clone());
 	let signer = signing_key_repository.retrieve_key()?;
 	info!("[Enclave initialized] Ed25519 prim raw : {:?}", signer.public().0);
 
-	let shielding_key_repository = Arc::new(get_rsa3072_repository(base_dir.clone())?);
+	let shielding_key_repository = Arc::new(EnclaveShieldingKeyRepository::new(
+		Arc::new(get_rsa3072_repository(base_dir.clone())?),
+		SHIELDING_KEY_ROTATION_GRACE_PERIOD,
+	));
 	GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.initialize(shielding_key_repository.clone());
+	let shielding_key_with_fallback_repository =
+		Arc::new(EnclaveShieldingKeyWithFallbackRepository::new(shielding_key_repository.clone()));
+	GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT
+		.initialize(shielding_key_with_fallback_repository.clone());
 
 	// Create the aes key that is used for state encryption such that a key is always present in tests.
 	// It will be overwritten anyway if mutual remote attestation is performed with the primary worker.
@@ -130,10 +146,12 @@ pub(crate) fn init_enclave(
 	let state_observer = initialize_state_observer(&state_snapshot_repository)?;
 	GLOBAL_STATE_OBSERVER_COMPONENT.initialize(state_observer.clone());
 
+	let state_migrator = Arc::new(EnclaveStateMigrator::new(state_migration_dry_run));
 	let state_handler = Arc::new(StateHandler::load_from_repository(
 		state_snapshot_repository,
 		state_observer.clone(),
 		state_initializer,
+		state_migrator,
 	)?);
 
 	GLOBAL_STATE_HANDLER_COMPONENT.initialize(state_handler.clone());
@@ -170,14 +188,26 @@ pub(crate) fn init_enclave(
 		connection_registry.clone(),
 		state_handler,
 		ocall_api.clone(),
-		shielding_key_repository.clone(),
+		shielding_key_with_fallback_repository,
 	);
 	GLOBAL_TOP_POOL_AUTHOR_COMPONENT.initialize(top_pool_author.clone());
 
 	let getter_executor = Arc::new(EnclaveGetterExecutor::new(state_observer));
 	let io_handler =
 		public_api_rpc_handler(top_pool_author, getter_executor, shielding_key_repository);
-	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
+	let subscription_registry = Arc::new(EnclaveRpcSubscriptionRegistry::new());
+	let subscription_notifier = Arc::new(EnclaveRpcSubscriptionNotifier::new(
+		subscription_registry.clone(),
+		Arc::new(RpcResponseChannel::default()),
+	));
+	GLOBAL_RPC_SUBSCRIPTION_NOTIFIER_COMPONENT.initialize(subscription_notifier);
+
+	let rpc_handler = Arc::new(RpcWsHandler::new(
+		io_handler,
+		watch_extractor,
+		connection_registry,
+		subscription_registry,
+	));
 	GLOBAL_RPC_WS_HANDLER_COMPONENT.initialize(rpc_handler);
 
 	let sidechain_block_import_queue = Arc::new(EnclaveSidechainBlockImportQueue::default());
@@ -216,12 +246,17 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 
 	let signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
 
+	let block_import_notifier = Arc::new(EnclaveSidechainBlockImportNotifier::new(
+		GLOBAL_RPC_SUBSCRIPTION_NOTIFIER_COMPONENT.get()?,
+	));
+
 	let sidechain_block_importer = Arc::new(EnclaveSidechainBlockImporter::new(
 		state_handler,
 		state_key_repository.clone(),
 		top_pool_author,
 		parentchain_block_import_dispatcher,
 		ocall_api.clone(),
+		block_import_notifier,
 	));
 
 	let sidechain_block_import_queue = GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT.get()?;
@@ -256,8 +291,14 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 	Ok(())
 }
 
-pub(crate) fn init_direct_invocation_server(server_addr: String) -> EnclaveResult<()> {
+pub(crate) fn init_direct_invocation_server(
+	server_addr: String,
+	limits: TrustedRpcServerLimits,
+) -> EnclaveResult<()> {
 	let rpc_handler = GLOBAL_RPC_WS_HANDLER_COMPONENT.get()?;
+	if let Some(max_batch_size) = limits.max_batch_size {
+		rpc_handler.set_max_batch_size(max_batch_size as usize);
+	}
 	let signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
 
 	let cert =
@@ -268,8 +309,14 @@ pub(crate) fn init_direct_invocation_server(server_addr: String) -> EnclaveResul
 	let pem_serialized = cert.serialize_pem().map_err(|e| Error::Other(e.into()))?;
 	let private_key = cert.serialize_private_key_pem();
 
-	let web_socket_server =
-		create_ws_server(server_addr.as_str(), &private_key, &pem_serialized, rpc_handler);
+	let web_socket_server = create_ws_server(
+		server_addr.as_str(),
+		&private_key,
+		&pem_serialized,
+		rpc_handler,
+		limits.into(),
+		GLOBAL_OCALL_API_COMPONENT.get()?,
+	);
 
 	GLOBAL_WEB_SOCKET_SERVER_COMPONENT.initialize(web_socket_server.clone());
 
@@ -289,12 +336,24 @@ pub(crate) fn init_shard(shard: ShardIdentifier) -> EnclaveResult<()> {
 	Ok(())
 }
 
+pub(crate) fn load_shard(shard: ShardIdentifier) -> EnclaveResult<()> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	let _ = state_handler.load_shard(&shard)?;
+	Ok(())
+}
+
+pub(crate) fn unload_shard(shard: ShardIdentifier) -> EnclaveResult<()> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	state_handler.unload_shard(&shard)?;
+	Ok(())
+}
+
 /// Initialize the TOP pool author component.
 pub fn create_top_pool_author(
 	connection_registry: Arc<EnclaveRpcConnectionRegistry>,
 	state_handler: Arc<EnclaveStateHandler>,
 	ocall_api: Arc<EnclaveOCallApi>,
-	shielding_key_repository: Arc<EnclaveShieldingKeyRepository>,
+	shielding_key_repository: Arc<EnclaveShieldingKeyWithFallbackRepository>,
 ) -> Arc<EnclaveTopPoolAuthor> {
 	let response_channel = Arc::new(RpcResponseChannel::default());
 	let rpc_responder = Arc::new(EnclaveRpcResponder::new(connection_registry, response_channel));
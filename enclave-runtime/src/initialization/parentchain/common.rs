@@ -36,9 +36,10 @@ use crate::{
 			TargetBParentchainImmediateBlockImportDispatcher,
 			TargetBParentchainIndirectCallsExecutor,
 			TargetBParentchainTriggeredBlockImportDispatcher, GLOBAL_OCALL_API_COMPONENT,
-			GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
-			GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_STATE_OBSERVER_COMPONENT,
-			GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
+			GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT,
+			GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT,
+			GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
+			GLOBAL_STATE_OBSERVER_COMPONENT, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
 		},
 		EnclaveStfEnclaveSigner,
 	},
@@ -62,19 +63,22 @@ pub(crate) fn create_integritee_parentchain_block_importer(
 	let state_observer = GLOBAL_STATE_OBSERVER_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
 	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
+	let shielding_key_with_fallback_repository =
+		GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT.get()?;
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
 	let stf_enclave_signer = Arc::new(EnclaveStfEnclaveSigner::new(
 		state_observer,
-		ocall_api,
-		shielding_key_repository.clone(),
+		ocall_api.clone(),
+		shielding_key_repository,
 		top_pool_author.clone(),
 	));
 	let indirect_calls_executor = Arc::new(IntegriteeParentchainIndirectCallsExecutor::new(
-		shielding_key_repository,
+		shielding_key_with_fallback_repository,
 		stf_enclave_signer,
 		top_pool_author,
 		node_metadata_repository,
+		ocall_api,
 		ParentchainId::Integritee,
 	));
 	Ok(IntegriteeParentchainBlockImporter::new(
@@ -97,19 +101,22 @@ pub(crate) fn create_target_a_parentchain_block_importer(
 	let state_observer = GLOBAL_STATE_OBSERVER_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
 	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
+	let shielding_key_with_fallback_repository =
+		GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT.get()?;
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
 	let stf_enclave_signer = Arc::new(EnclaveStfEnclaveSigner::new(
 		state_observer,
-		ocall_api,
-		shielding_key_repository.clone(),
+		ocall_api.clone(),
+		shielding_key_repository,
 		top_pool_author.clone(),
 	));
 	let indirect_calls_executor = Arc::new(TargetAParentchainIndirectCallsExecutor::new(
-		shielding_key_repository,
+		shielding_key_with_fallback_repository,
 		stf_enclave_signer,
 		top_pool_author,
 		node_metadata_repository,
+		ocall_api,
 		ParentchainId::TargetA,
 	));
 	Ok(TargetAParentchainBlockImporter::new(
@@ -132,19 +139,22 @@ pub(crate) fn create_target_b_parentchain_block_importer(
 	let state_observer = GLOBAL_STATE_OBSERVER_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
 	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
+	let shielding_key_with_fallback_repository =
+		GLOBAL_SHIELDING_KEY_WITH_FALLBACK_REPOSITORY_COMPONENT.get()?;
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
 	let stf_enclave_signer = Arc::new(EnclaveStfEnclaveSigner::new(
 		state_observer,
-		ocall_api,
-		shielding_key_repository.clone(),
+		ocall_api.clone(),
+		shielding_key_repository,
 		top_pool_author.clone(),
 	));
 	let indirect_calls_executor = Arc::new(TargetBParentchainIndirectCallsExecutor::new(
-		shielding_key_repository,
+		shielding_key_with_fallback_repository,
 		stf_enclave_signer,
 		top_pool_author,
 		node_metadata_repository,
+		ocall_api,
 		ParentchainId::TargetB,
 	));
 	Ok(TargetBParentchainBlockImporter::new(
@@ -180,6 +190,7 @@ pub(crate) fn create_integritee_offchain_immediate_import_dispatcher(
 ) -> Result<Arc<IntegriteeParentchainBlockImportDispatcher>> {
 	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
 	let offchain_worker_executor = Arc::new(EnclaveOffchainWorkerExecutor::new(
 		top_pool_author,
@@ -195,6 +206,7 @@ pub(crate) fn create_integritee_offchain_immediate_import_dispatcher(
 		if let Err(e) = offchain_worker_executor.execute() {
 			error!("Failed to execute trusted calls: {:?}", e);
 		}
+		ocall_api.export_buffered_metrics();
 	});
 
 	Ok(Arc::new(IntegriteeParentchainBlockImportDispatcher::new_immediate_dispatcher(Arc::new(
@@ -210,6 +222,7 @@ pub(crate) fn create_target_a_offchain_immediate_import_dispatcher(
 ) -> Result<Arc<TargetAParentchainBlockImportDispatcher>> {
 	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
 	let offchain_worker_executor = Arc::new(EnclaveOffchainWorkerExecutor::new(
 		top_pool_author,
@@ -225,6 +238,7 @@ pub(crate) fn create_target_a_offchain_immediate_import_dispatcher(
 		if let Err(e) = offchain_worker_executor.execute() {
 			error!("Failed to execute trusted calls: {:?}", e);
 		}
+		ocall_api.export_buffered_metrics();
 	});
 
 	Ok(Arc::new(TargetAParentchainBlockImportDispatcher::new_immediate_dispatcher(Arc::new(
@@ -240,6 +254,7 @@ pub(crate) fn create_target_b_offchain_immediate_import_dispatcher(
 ) -> Result<Arc<TargetBParentchainBlockImportDispatcher>> {
 	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
 	let offchain_worker_executor = Arc::new(EnclaveOffchainWorkerExecutor::new(
 		top_pool_author,
@@ -255,6 +270,7 @@ pub(crate) fn create_target_b_offchain_immediate_import_dispatcher(
 		if let Err(e) = offchain_worker_executor.execute() {
 			error!("Failed to execute trusted calls: {:?}", e);
 		}
+		ocall_api.export_buffered_metrics();
 	});
 
 	Ok(Arc::new(TargetBParentchainBlockImportDispatcher::new_immediate_dispatcher(Arc::new(
@@ -51,6 +51,7 @@ use codec::Decode;
 use core::ffi::c_int;
 use itc_parentchain::{block_import_dispatcher::DispatchBlockImport, primitives::ParentchainId};
 use itp_component_container::ComponentGetter;
+use itp_enclave_metrics::trace::GLOBAL_TRACE_SPAN_BUFFER;
 
 use itp_import_queue::PushToQueue;
 use itp_node_api::metadata::NodeMetadata;
@@ -59,7 +60,7 @@ use itp_nonce_cache::{MutateNonce, Nonce};
 use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider};
 use itp_sgx_crypto::key_repository::AccessPubkey;
 use itp_storage::{StorageProof, StorageProofChecker};
-use itp_types::{ShardIdentifier, SignedBlock};
+use itp_types::{ShardIdentifier, SignedBlock, TrustedRpcServerLimits};
 use itp_utils::write_slice_and_whitespace_pad;
 use log::*;
 use once_cell::sync::OnceCell;
@@ -72,6 +73,7 @@ use std::{
 	vec::Vec,
 };
 
+mod admin;
 mod attestation;
 mod empty_impls;
 mod initialization;
@@ -116,6 +118,7 @@ pub unsafe extern "C" fn init(
 	untrusted_worker_addr_size: u32,
 	encoded_base_dir_str: *const u8,
 	encoded_base_dir_size: u32,
+	state_migration_dry_run: c_int,
 ) -> sgx_status_t {
 	// Initialize the logging environment in the enclave.
 	env_logger::builder()
@@ -154,7 +157,12 @@ pub unsafe extern "C" fn init(
 	let path = PathBuf::from(base_dir);
 	BASE_PATH.set(path.clone()).expect("We only init this once here; qed.");
 
-	match initialization::init_enclave(mu_ra_url, untrusted_worker_url, path) {
+	match initialization::init_enclave(
+		mu_ra_url,
+		untrusted_worker_url,
+		path,
+		state_migration_dry_run == 1,
+	) {
 		Err(e) => e.into(),
 		Ok(()) => sgx_status_t::SGX_SUCCESS,
 	}
@@ -359,6 +367,14 @@ pub unsafe extern "C" fn init_enclave_sidechain_components() -> sgx_status_t {
 	sgx_status_t::SGX_SUCCESS
 }
 
+/// Record tracing spans from now on, they are exported with the metrics ocall.
+#[no_mangle]
+pub unsafe extern "C" fn enable_tracing() -> sgx_status_t {
+	GLOBAL_TRACE_SPAN_BUFFER.set_enabled(true);
+	info!("Enabled the export of tracing spans");
+	sgx_status_t::SGX_SUCCESS
+}
+
 /// Call this once at worker startup to initialize the TOP pool and direct invocation RPC server.
 ///
 /// This function will run the RPC server on the same thread as it is called and will loop there.
@@ -368,6 +384,8 @@ pub unsafe extern "C" fn init_enclave_sidechain_components() -> sgx_status_t {
 pub unsafe extern "C" fn init_direct_invocation_server(
 	server_addr: *const u8,
 	server_addr_size: usize,
+	limits: *const u8,
+	limits_size: usize,
 ) -> sgx_status_t {
 	let mut server_addr_encoded = slice::from_raw_parts(server_addr, server_addr_size);
 
@@ -379,7 +397,17 @@ pub unsafe extern "C" fn init_direct_invocation_server(
 		},
 	};
 
-	if let Err(e) = initialization::init_direct_invocation_server(server_addr) {
+	let mut limits_encoded = slice::from_raw_parts(limits, limits_size);
+
+	let limits = match TrustedRpcServerLimits::decode(&mut limits_encoded) {
+		Ok(limits) => limits,
+		Err(e) => {
+			error!("Decoding RPC server limits failed. Error: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	if let Err(e) = initialization::init_direct_invocation_server(server_addr, limits) {
 		error!("Failed to initialize direct invocation server: {:?}", e);
 		return sgx_status_t::SGX_ERROR_UNEXPECTED
 	}
@@ -428,6 +456,32 @@ pub unsafe extern "C" fn init_shard(shard: *const u8, shard_size: u32) -> sgx_st
 	sgx_status_t::SGX_SUCCESS
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn load_shard(shard: *const u8, shard_size: u32) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	if let Err(e) = initialization::load_shard(shard_identifier) {
+		error!("Failed to load shard ({:?}): {:?}", shard_identifier, e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unload_shard(shard: *const u8, shard_size: u32) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	if let Err(e) = initialization::unload_shard(shard_identifier) {
+		error!("Failed to unload shard ({:?}): {:?}", shard_identifier, e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn sync_parentchain(
 	blocks_to_sync: *const u8,
@@ -18,7 +18,12 @@
 use crate::ocall::{ffi, OcallApi};
 use codec::Encode;
 use frame_support::ensure;
+use itc_tls_websocket_server::limits::{ObserveRejections, WebSocketRejection};
+use itp_enclave_metrics::{
+	rpc_rejections::GLOBAL_RPC_REJECTION_COUNTS, trace::GLOBAL_TRACE_SPAN_BUFFER, EnclaveMetric,
+};
 use itp_ocall_api::EnclaveMetricsOCallApi;
+use log::*;
 use sgx_types::{sgx_status_t, SgxResult};
 
 impl EnclaveMetricsOCallApi for OcallApi {
@@ -40,3 +45,27 @@ impl EnclaveMetricsOCallApi for OcallApi {
 		Ok(())
 	}
 }
+
+impl OcallApi {
+	/// Exports the tracing spans and the RPC rejections recorded since the last export, with an
+	/// ocall each.
+	pub(crate) fn export_buffered_metrics(&self) {
+		if let Some(batch) = GLOBAL_TRACE_SPAN_BUFFER.take() {
+			if let Err(e) = self.update_metric(EnclaveMetric::TraceSpans(batch)) {
+				warn!("Failed to export trace spans: {:?}", e);
+			}
+		}
+		if let Some(rejections) = GLOBAL_RPC_REJECTION_COUNTS.take() {
+			if let Err(e) = self.update_metric(EnclaveMetric::RpcRequestsRejected(rejections)) {
+				warn!("Failed to export RPC rejections: {:?}", e);
+			}
+		}
+	}
+}
+
+/// Rejections are only counted here, see [`OcallApi::export_buffered_metrics`].
+impl ObserveRejections for OcallApi {
+	fn on_rejection(&self, rejection: WebSocketRejection) {
+		GLOBAL_RPC_REJECTION_COUNTS.increment(rejection.as_str());
+	}
+}
@@ -0,0 +1,76 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use codec::Encode;
+use ita_stf::{
+	subscriptions::{events_in_state, events_of_account},
+	State as StfState,
+};
+use itc_direct_rpc_server::NotifySubscribers;
+use itp_types::{SubscriptionTopic, H256};
+use its_primitives::traits::{Block as SidechainBlockTrait, Header as HeaderTrait};
+use its_sidechain::consensus_common::NotifyBlockImported;
+use log::*;
+use std::sync::Arc;
+
+/// Block import notifier that serves the direct RPC subscriptions.
+///
+/// Sends the header of every imported sidechain block to the `NewHeads` subscribers and
+/// the events of the block to the subscribers of the accounts concerned by them.
+pub struct SubscriptionBlockImportNotifier<Notifier> {
+	subscription_notifier: Arc<Notifier>,
+}
+
+impl<Notifier> SubscriptionBlockImportNotifier<Notifier> {
+	pub fn new(subscription_notifier: Arc<Notifier>) -> Self {
+		SubscriptionBlockImportNotifier { subscription_notifier }
+	}
+}
+
+impl<Notifier, SidechainBlock> NotifyBlockImported<SidechainBlock, StfState>
+	for SubscriptionBlockImportNotifier<Notifier>
+where
+	Notifier: NotifySubscribers,
+	SidechainBlock: SidechainBlockTrait,
+	SidechainBlock::HeaderType: HeaderTrait<ShardIdentifier = H256>,
+{
+	fn notify_block_imported(&self, block: &SidechainBlock, state: &StfState) {
+		let shard = block.header().shard_id();
+		let mut maybe_events = None;
+
+		for topic in self.subscription_notifier.subscribed_topics() {
+			let value = match &topic {
+				SubscriptionTopic::NewHeads => block.header().encode(),
+				SubscriptionTopic::AccountEvents(topic_shard, account) => {
+					if *topic_shard != shard {
+						continue
+					}
+					let events = maybe_events.get_or_insert_with(|| events_in_state(state));
+					let account_events = events_of_account(events, account);
+					if account_events.is_empty() {
+						continue
+					}
+					account_events.encode()
+				},
+			};
+
+			if let Err(e) = self.subscription_notifier.notify_subscribers(&topic, value) {
+				warn!("Failed to notify subscribers of {:?}: {:?}", topic, e);
+			}
+		}
+	}
+}
@@ -0,0 +1,446 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Ethereum JSON-RPC façade (`eth_*`) for the EVM of the shard handled by this worker.
+//!
+//! Standard Ethereum tooling can target the shard through the trusted RPC, within the limits of
+//! the sidechain: only the latest state is available (block parameters are ignored), and
+//! receipts refer to sidechain block numbers. The read methods are backed by public getters,
+//! which only reveal the EVM state if the `EvmStateAccessPolicy` of the shard is public.
+//!
+//! Ethereum tooling cannot select a shard, so the façade is only served by a worker handling
+//! exactly one shard. Each shard has its own chain id, to which transactions are bound.
+
+use codec::{Decode, Encode};
+use ita_sgx_runtime::{Balance, BlockNumber, Index};
+use ita_stf::{
+	evm_transaction::{
+		evm_chain_id, EthereumTransaction, EvmCallRequest, EvmCallResult, EvmReceipt,
+	},
+	Getter, PublicGetter, TrustedCallSigned,
+};
+use itp_sgx_crypto::{key_repository::AccessPubkey, ShieldingCryptoEncrypt};
+use itp_stf_executor::getter_executor::ExecuteGetter;
+use itp_stf_primitives::types::TrustedOperation;
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{ShardIdentifier, H256};
+use itp_utils::hex::{decode_hex, hex_encode};
+use jsonrpc_core::{
+	futures::executor,
+	serde_json::{json, Map},
+	Error as RpcError, ErrorCode, IoHandler, Params, Value,
+};
+use log::debug;
+use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+use sp_core::{H160, U256};
+use std::{format, string::String, sync::Arc, vec::Vec};
+
+/// Error code of Ethereum nodes for failed executions.
+const EXECUTION_ERROR: i64 = -32000;
+
+/// Error code of Ethereum nodes for reverted calls, with the revert reason as data.
+const EXECUTION_REVERTED: i64 = 3;
+
+pub fn add_eth_rpc_methods<Author, GetterExecutor, AccessShieldingKey>(
+	io: &mut IoHandler,
+	top_pool_author: Arc<Author>,
+	getter_executor: Arc<GetterExecutor>,
+	shielding_key: Arc<AccessShieldingKey>,
+) where
+	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter> + Send + Sync + 'static,
+	GetterExecutor: ExecuteGetter + Send + Sync + 'static,
+	AccessShieldingKey: AccessPubkey<KeyType = Rsa3072PubKey> + Send + Sync + 'static,
+{
+	let shard_author = top_pool_author.clone();
+	let shard = move || evm_shard(shard_author.as_ref());
+
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_chainId", move |_: Params| {
+		debug!("worker_api_direct rpc was called: eth_chainId");
+		Ok(quantity(evm_chain_id(&local_shard()?)))
+	});
+
+	let local_shard = shard.clone();
+	io.add_sync_method("net_version", move |_: Params| {
+		debug!("worker_api_direct rpc was called: net_version");
+		Ok(Value::String(format!("{}", evm_chain_id(&local_shard()?))))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_gasPrice", move |_: Params| {
+		debug!("worker_api_direct rpc was called: eth_gasPrice");
+		let base_fee: U256 = execute_public_getter(
+			local_getter_executor.as_ref(),
+			&local_shard()?,
+			PublicGetter::evm_base_fee,
+		)?;
+		Ok(quantity(base_fee))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_blockNumber", move |_: Params| {
+		debug!("worker_api_direct rpc was called: eth_blockNumber");
+		let block_number: BlockNumber = execute_public_getter(
+			local_getter_executor.as_ref(),
+			&local_shard()?,
+			PublicGetter::block_number,
+		)?;
+		Ok(quantity(block_number))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_getBalance", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_getBalance");
+		let params = parse_params(params, 1)?;
+		let balance: Balance = execute_public_getter(
+			local_getter_executor.as_ref(),
+			&local_shard()?,
+			PublicGetter::evm_balance(parse_address(&params[0])?),
+		)?;
+		Ok(quantity(balance))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_getTransactionCount", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_getTransactionCount");
+		let params = parse_params(params, 1)?;
+		let nonce: Index = execute_public_getter(
+			local_getter_executor.as_ref(),
+			&local_shard()?,
+			PublicGetter::evm_transaction_count(parse_address(&params[0])?),
+		)?;
+		Ok(quantity(nonce))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_getCode", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_getCode");
+		let params = parse_params(params, 1)?;
+		let code: Vec<u8> = execute_public_getter(
+			local_getter_executor.as_ref(),
+			&local_shard()?,
+			PublicGetter::evm_code(parse_address(&params[0])?),
+		)?;
+		Ok(Value::String(hex_encode(&code)))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_getStorageAt", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_getStorageAt");
+		let params = parse_params(params, 2)?;
+		let mut index = [0u8; 32];
+		parse_quantity(&params[1])?.to_big_endian(&mut index);
+		let value: H256 = execute_public_getter(
+			local_getter_executor.as_ref(),
+			&local_shard()?,
+			PublicGetter::evm_storage(parse_address(&params[0])?, H256::from(index)),
+		)?;
+		Ok(Value::String(hex_encode(value.as_bytes())))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_call", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_call");
+		let params = parse_params(params, 1)?;
+		let result = simulate_call(local_getter_executor.as_ref(), &local_shard()?, &params[0])?;
+		Ok(Value::String(hex_encode(&result.value)))
+	});
+
+	let local_getter_executor = getter_executor.clone();
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_estimateGas", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_estimateGas");
+		let params = parse_params(params, 1)?;
+		let result = simulate_call(local_getter_executor.as_ref(), &local_shard()?, &params[0])?;
+		Ok(quantity(result.gas_used))
+	});
+
+	let local_shard = shard.clone();
+	io.add_sync_method("eth_sendRawTransaction", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_sendRawTransaction");
+		let params = parse_params(params, 1)?;
+		let raw_transaction = parse_bytes(&params[0])?;
+		let transaction_hash = submit_raw_transaction(
+			top_pool_author.as_ref(),
+			shielding_key.as_ref(),
+			local_shard()?,
+			raw_transaction,
+		)?;
+		Ok(Value::String(hex_encode(transaction_hash.as_bytes())))
+	});
+
+	io.add_sync_method("eth_getTransactionReceipt", move |params: Params| {
+		debug!("worker_api_direct rpc was called: eth_getTransactionReceipt");
+		let params = parse_params(params, 1)?;
+		let receipt: Option<EvmReceipt> = execute_public_getter(
+			getter_executor.as_ref(),
+			&shard()?,
+			PublicGetter::evm_transaction_receipt(parse_hash(&params[0])?),
+		)?;
+		Ok(receipt.map_or(Value::Null, receipt_to_json))
+	});
+}
+
+/// The shard, whose EVM is served. Fails unless exactly one shard is handled.
+fn evm_shard<Author>(top_pool_author: &Author) -> Result<ShardIdentifier, RpcError>
+where
+	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter>,
+{
+	match top_pool_author.list_handled_shards().as_slice() {
+		[shard] => Ok(*shard),
+		shards => Err(execution_error(format!(
+			"The eth_* RPC is only served for exactly one shard, but {} are handled",
+			shards.len()
+		))),
+	}
+}
+
+fn execute_public_getter<GE: ExecuteGetter, V: Decode>(
+	getter_executor: &GE,
+	shard: &ShardIdentifier,
+	getter: PublicGetter,
+) -> Result<V, RpcError> {
+	let encoded_value = getter_executor
+		.execute_getter(shard, Getter::public(getter).encode())
+		.map_err(|e| execution_error(format!("Could not execute getter: {:?}", e)))?
+		.ok_or_else(|| {
+			execution_error("Getter returned no value, the EVM state might not be public".into())
+		})?;
+	V::decode(&mut encoded_value.as_slice())
+		.map_err(|e| execution_error(format!("Could not decode getter value: {:?}", e)))
+}
+
+fn simulate_call<GE: ExecuteGetter>(
+	getter_executor: &GE,
+	shard: &ShardIdentifier,
+	call: &Value,
+) -> Result<EvmCallResult, RpcError> {
+	let request =
+		EvmCallRequest { chain_id: Some(evm_chain_id(shard)), ..parse_call_request(call)? };
+	let result: Result<EvmCallResult, String> =
+		execute_public_getter(getter_executor, shard, PublicGetter::evm_call(request))?;
+	let result = result.map_err(execution_error)?;
+	if !result.succeeded {
+		return Err(RpcError {
+			code: ErrorCode::ServerError(EXECUTION_REVERTED),
+			message: "execution reverted".into(),
+			data: Some(Value::String(hex_encode(&result.value))),
+		})
+	}
+	Ok(result)
+}
+
+fn submit_raw_transaction<Author, AccessShieldingKey>(
+	top_pool_author: &Author,
+	shielding_key: &AccessShieldingKey,
+	shard: ShardIdentifier,
+	raw_transaction: Vec<u8>,
+) -> Result<H256, RpcError>
+where
+	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter>,
+	AccessShieldingKey: AccessPubkey<KeyType = Rsa3072PubKey>,
+{
+	let transaction = EthereumTransaction::decode_signed(&raw_transaction)
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+	let chain_id = evm_chain_id(&shard);
+	if transaction.chain_id != Some(chain_id) {
+		return Err(RpcError::invalid_params(format!(
+			"Transaction is not signed for chain id {}",
+			chain_id
+		)))
+	}
+
+	let trusted_call = TrustedCallSigned::from_ethereum_transaction(&transaction, raw_transaction)
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+	let trusted_operation: TrustedOperation<TrustedCallSigned, Getter> =
+		trusted_call.into_trusted_operation(true);
+	// The pool only accepts operations, which are encrypted with the shielding key.
+	let encrypted_operation = shielding_key
+		.retrieve_pubkey()
+		.and_then(|key| key.encrypt(&trusted_operation.encode()))
+		.map_err(|e| execution_error(format!("Could not encrypt trusted operation: {:?}", e)))?;
+
+	executor::block_on(top_pool_author.submit_top(encrypted_operation, shard))?;
+	Ok(transaction.hash)
+}
+
+fn receipt_to_json(receipt: EvmReceipt) -> Value {
+	let transaction_hash = hex_encode(receipt.transaction_hash.as_bytes());
+	let block_number = quantity(receipt.block_number);
+	// Sidechain blocks are not known when the transaction is executed.
+	let block_hash = hex_encode(H256::zero().as_bytes());
+	let logs: Vec<Value> = receipt
+		.logs
+		.iter()
+		.enumerate()
+		.map(|(index, log)| {
+			json!({
+				"address": hex_encode(log.address.as_bytes()),
+				"topics": log.topics.iter().map(|topic| hex_encode(topic.as_bytes())).collect::<Vec<_>>(),
+				"data": hex_encode(&log.data),
+				"blockNumber": block_number.clone(),
+				"blockHash": block_hash.clone(),
+				"transactionHash": transaction_hash.clone(),
+				"transactionIndex": "0x0",
+				"logIndex": quantity(index as u64),
+				"removed": false,
+			})
+		})
+		.collect();
+
+	json!({
+		"transactionHash": transaction_hash,
+		"transactionIndex": "0x0",
+		"type": quantity(receipt.transaction_type.unwrap_or_default()),
+		"blockNumber": block_number,
+		"blockHash": block_hash,
+		"from": hex_encode(receipt.from.as_bytes()),
+		"to": receipt.to.map(|to| hex_encode(to.as_bytes())),
+		"contractAddress": receipt.contract_address.map(|address| hex_encode(address.as_bytes())),
+		"status": if receipt.succeeded { "0x1" } else { "0x0" },
+		"gasUsed": quantity(receipt.gas_used),
+		"cumulativeGasUsed": quantity(receipt.gas_used),
+		"effectiveGasPrice": quantity(receipt.effective_gas_price),
+		// Logs are not indexed by a bloom filter, they have to be taken from the receipt.
+		"logsBloom": hex_encode(&[0u8; 256]),
+		"logs": logs,
+	})
+}
+
+fn parse_params(params: Params, min_len: usize) -> Result<Vec<Value>, RpcError> {
+	let params = match params {
+		Params::None => Vec::new(),
+		params => params.parse::<Vec<Value>>()?,
+	};
+	if params.len() < min_len {
+		return Err(RpcError::invalid_params(format!(
+			"Expected at least {} parameters, got {}",
+			min_len,
+			params.len()
+		)))
+	}
+	Ok(params)
+}
+
+fn parse_call_request(value: &Value) -> Result<EvmCallRequest, RpcError> {
+	let call: &Map<String, Value> = value
+		.as_object()
+		.ok_or_else(|| RpcError::invalid_params("Expected a call object"))?;
+	let field = |name: &str| call.get(name).filter(|value| !value.is_null());
+
+	Ok(EvmCallRequest {
+		from: field("from").map(parse_address).transpose()?,
+		to: field("to").map(parse_address).transpose()?,
+		data: field("input")
+			.or_else(|| field("data"))
+			.map(parse_bytes)
+			.transpose()?
+			.unwrap_or_default(),
+		value: field("value").map(parse_quantity).transpose()?.unwrap_or_default(),
+		gas_limit: field("gas")
+			.map(parse_quantity)
+			.transpose()?
+			.map(|gas| gas.min(U256::from(u64::MAX)).low_u64()),
+		chain_id: None,
+	})
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, RpcError> {
+	let hex = value
+		.as_str()
+		.ok_or_else(|| RpcError::invalid_params("Expected a hex string"))?;
+	decode_hex(hex).map_err(|e| RpcError::invalid_params(format!("Invalid hex string: {:?}", e)))
+}
+
+fn parse_address(value: &Value) -> Result<H160, RpcError> {
+	let bytes = parse_bytes(value)?;
+	if bytes.len() != H160::len_bytes() {
+		return Err(RpcError::invalid_params("Expected an address of 20 bytes"))
+	}
+	Ok(H160::from_slice(&bytes))
+}
+
+fn parse_hash(value: &Value) -> Result<H256, RpcError> {
+	let bytes = parse_bytes(value)?;
+	if bytes.len() != H256::len_bytes() {
+		return Err(RpcError::invalid_params("Expected a hash of 32 bytes"))
+	}
+	Ok(H256::from_slice(&bytes))
+}
+
+fn parse_quantity(value: &Value) -> Result<U256, RpcError> {
+	let hex = value.as_str().ok_or_else(|| RpcError::invalid_params("Expected a quantity"))?;
+	let digits = hex.strip_prefix("0x").unwrap_or(hex);
+	U256::from_str_radix(digits, 16)
+		.map_err(|e| RpcError::invalid_params(format!("Invalid quantity {}: {:?}", hex, e)))
+}
+
+/// Hex encoding of Ethereum quantities, without leading zeros.
+fn quantity<T: Into<U256>>(value: T) -> Value {
+	Value::String(format!("0x{:x}", value.into()))
+}
+
+fn execution_error(message: String) -> RpcError {
+	RpcError { code: ErrorCode::ServerError(EXECUTION_ERROR), message, data: None }
+}
+
+#[cfg(feature = "test")]
+pub mod tests {
+	use super::*;
+	use itp_top_pool_author::mocks::AuthorApiMock;
+
+	pub fn quantities_are_hex_encoded_without_leading_zeros() {
+		assert_eq!(quantity(0u64), Value::String("0x0".into()));
+		assert_eq!(quantity(42u64), Value::String("0x2a".into()));
+		assert_eq!(parse_quantity(&json!("0x2a")).unwrap(), U256::from(42));
+	}
+
+	pub fn call_request_is_parsed_from_call_object() {
+		let call = json!({
+			"from": "0x1111111111111111111111111111111111111111",
+			"to": "0x2222222222222222222222222222222222222222",
+			"data": "0x01ab",
+			"gas": "0x5208",
+		});
+
+		let request = parse_call_request(&call).unwrap();
+
+		assert_eq!(request.from, Some(H160::repeat_byte(0x11)));
+		assert_eq!(request.to, Some(H160::repeat_byte(0x22)));
+		assert_eq!(request.data, vec![0x01, 0xab]);
+		assert_eq!(request.value, U256::zero());
+		assert_eq!(request.gas_limit, Some(21_000));
+	}
+
+	pub fn invalid_address_is_rejected() {
+		assert!(parse_address(&json!("0x1234")).is_err());
+		assert!(parse_address(&json!(42)).is_err());
+	}
+
+	pub fn no_shard_is_served_without_a_handled_shard() {
+		let top_pool_author = AuthorApiMock::<H256, H256, TrustedCallSigned, Getter>::default();
+
+		assert!(evm_shard(&top_pool_author).is_err());
+	}
+}
@@ -15,5 +15,8 @@
 
 */
 
+pub mod block_import_notifier;
+#[cfg(feature = "evm")]
+pub mod eth_api;
 pub mod rpc_response_channel;
 pub mod worker_api_direct;
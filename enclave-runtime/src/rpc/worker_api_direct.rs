@@ -20,6 +20,7 @@ use crate::{
 		generate_dcap_ra_extrinsic_from_quote_internal,
 		generate_ias_ra_extrinsic_from_der_cert_internal,
 	},
+	ocall::OcallApi,
 	utils::{
 		get_stf_enclave_signer_from_solo_or_parachain,
 		get_validator_accessor_from_integritee_solo_or_parachain,
@@ -27,15 +28,20 @@ use crate::{
 };
 use codec::Encode;
 use core::result::Result;
-use ita_sgx_runtime::Runtime;
-use ita_stf::{Getter, TrustedCallSigned};
+use ita_sgx_runtime::{Runtime, VERSION};
+use ita_stf::{
+	subscriptions::{AcceptedSubscriptions, AccountEventsSubscriptionSigned},
+	Getter, TrustedCallSigned,
+};
 use itc_parentchain::light_client::{concurrent_access::ValidatorAccess, ExtrinsicSender};
+use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_primitives_cache::{GetPrimitives, GLOBAL_PRIMITIVES_CACHE};
 use itp_rpc::RpcReturnValue;
 use itp_sgx_crypto::key_repository::AccessPubkey;
 use itp_stf_executor::{getter_executor::ExecuteGetter, traits::StfShardVaultQuery};
+use itp_time_utils::trusted_now_as_millis;
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::{DirectRequestStatus, Request, ShardIdentifier, H256};
+use itp_types::{DirectRequestStatus, Request, ShardIdentifier, SubscriptionTopic, H256};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use its_primitives::types::block::SignedBlock;
 use its_sidechain::rpc_handler::{direct_top_pool_api, import_block_api};
@@ -43,7 +49,17 @@ use jsonrpc_core::{serde_json::json, IoHandler, Params, Value};
 use log::debug;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_runtime::OpaqueExtrinsic;
-use std::{borrow::ToOwned, format, str, string::String, sync::Arc, vec::Vec};
+use std::{
+	borrow::ToOwned,
+	format, str,
+	string::String,
+	sync::{Arc, SgxMutex},
+	vec::Vec,
+};
+
+lazy_static! {
+	static ref ACCEPTED_SUBSCRIPTIONS: SgxMutex<AcceptedSubscriptions> = Default::default();
+}
 
 fn compute_hex_encoded_return_error(error_msg: &str) -> String {
 	RpcReturnValue::from_error_message(error_msg).to_hex()
@@ -74,6 +90,14 @@ where
 		IoHandler::new(),
 	);
 
+	#[cfg(feature = "evm")]
+	crate::rpc::eth_api::add_eth_rpc_methods(
+		&mut io,
+		top_pool_author.clone(),
+		getter_executor.clone(),
+		shielding_key.clone(),
+	);
+
 	io.add_sync_method("author_getShieldingKey", move |_: Params| {
 		debug!("worker_api_direct rpc was called: author_getShieldingKey");
 		let rsa_pubkey = match shielding_key.retrieve_pubkey() {
@@ -155,8 +179,22 @@ where
 
 	io.add_sync_method("chain_subscribeAllHeads", |_: Params| {
 		debug!("worker_api_direct rpc was called: chain_subscribeAllHeads");
-		let parsed = "world";
-		Ok(Value::String(format!("hello, {}", parsed)))
+		let json_value = RpcReturnValue::new(
+			SubscriptionTopic::NewHeads.encode(),
+			true,
+			DirectRequestStatus::Ok,
+		);
+		Ok(json!(json_value.to_hex()))
+	});
+
+	io.add_sync_method("author_subscribeAccountEvents", |params: Params| {
+		debug!("worker_api_direct rpc was called: author_subscribeAccountEvents");
+		let json_value = match subscribe_account_events_inner(params) {
+			Ok(topic) =>
+				RpcReturnValue::new(topic.encode(), true, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
 	});
 
 	io.add_sync_method("state_getMetadata", |_: Params| {
@@ -168,8 +206,8 @@ where
 
 	io.add_sync_method("state_getRuntimeVersion", |_: Params| {
 		debug!("worker_api_direct rpc was called: state_getRuntimeVersion");
-		let parsed = "world";
-		Ok(Value::String(format!("hello, {}", parsed)))
+		let json_value = RpcReturnValue::new(VERSION.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
 	});
 
 	io.add_sync_method("state_executeGetter", move |params: Params| {
@@ -262,6 +300,35 @@ fn execute_getter_inner<GE: ExecuteGetter>(
 	Ok(getter_result)
 }
 
+fn subscribe_account_events_inner(params: Params) -> Result<SubscriptionTopic, String> {
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+
+	if hex_encoded_params.len() != 1 {
+		return Err(format!(
+			"Wrong number of arguments for account events subscription: {}, expected: {}",
+			hex_encoded_params.len(),
+			1
+		))
+	}
+
+	let signed_subscription = AccountEventsSubscriptionSigned::from_hex(&hex_encoded_params[0])
+		.map_err(|e| format!("{:?}", e))?;
+
+	let mrenclave = OcallApi.get_mrenclave_of_self().map_err(|e| format!("{:?}", e))?.m;
+	if !signed_subscription.verify_signature(&mrenclave) {
+		return Err("Invalid signature of account events subscription".into())
+	}
+
+	let subscription = signed_subscription.subscription;
+	let now = trusted_now_as_millis().map_err(|e| format!("{:?}", e))?;
+	ACCEPTED_SUBSCRIPTIONS
+		.lock()
+		.map_err(|_| String::from("Accepted subscriptions lock is poisoned"))?
+		.accept(&subscription, now)?;
+
+	Ok(SubscriptionTopic::AccountEvents(subscription.shard, subscription.account))
+}
+
 fn forward_dcap_quote_inner(params: Params) -> Result<OpaqueExtrinsic, String> {
 	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
 
@@ -16,25 +16,36 @@
 
 */
 
-use crate::{rpc::worker_api_direct::public_api_rpc_handler, Hash};
+use crate::{ocall::OcallApi, rpc::worker_api_direct::public_api_rpc_handler, Hash};
 use codec::{Decode, Encode};
-use ita_stf::{Getter, TrustedGetter, TrustedGetterSigned};
+use ita_sgx_runtime::VERSION;
+use ita_stf::{
+	subscriptions::{AccountEventsSubscription, AccountEventsSubscriptionSigned},
+	Getter, TrustedGetter, TrustedGetterSigned,
+};
 use itc_direct_rpc_server::{
 	create_determine_watch, rpc_connection_registry::ConnectionRegistry,
-	rpc_ws_handler::RpcWsHandler,
+	rpc_subscription_registry::SubscriptionRegistry, rpc_ws_handler::RpcWsHandler,
+	RpcSubscriptionRegistry,
 };
 use itc_tls_websocket_server::{ConnectionToken, WebSocketMessageHandler};
+use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_rpc::{RpcRequest, RpcReturnValue};
 use itp_sgx_crypto::get_rsa3072_repository;
 use itp_sgx_temp_dir::TempDir;
 use itp_stf_executor::{getter_executor::GetterExecutor, mocks::GetStateMock};
+use itp_stf_primitives::types::KeyPair;
 use itp_stf_state_observer::mock::ObserveStateMock;
+use itp_time_utils::now_as_millis;
 use itp_top_pool_author::mocks::AuthorApiMock;
-use itp_types::{AccountId, DirectRequestStatus, Request, ShardIdentifier};
+use itp_types::{AccountId, DirectRequestStatus, Request, ShardIdentifier, SubscriptionTopic};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
-use sp_core::ed25519::Signature;
+use sp_core::{
+	ed25519::{Pair as Ed25519Pair, Signature},
+	Pair,
+};
 use sp_runtime::MultiSignature;
-use std::{string::ToString, sync::Arc, vec::Vec};
+use std::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
 
 pub fn get_state_request_works() {
 	type TestState = u64;
@@ -53,7 +64,12 @@ pub fn get_state_request_works() {
 
 	let io_handler =
 		public_api_rpc_handler(top_pool_author, getter_executor, Arc::new(rsa_repository));
-	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
+	let rpc_handler = Arc::new(RpcWsHandler::new(
+		io_handler,
+		watch_extractor,
+		connection_registry,
+		Arc::new(SubscriptionRegistry::<ConnectionToken>::new()),
+	));
 
 	let getter = Getter::trusted(TrustedGetterSigned::new(
 		TrustedGetter::nonce(AccountId::new([0u8; 32])),
@@ -84,3 +100,186 @@ pub fn get_state_request_works() {
 		Option::decode(&mut rpc_return_value.value.as_slice()).unwrap();
 	assert_eq!(decoded_value, Some(state.encode()));
 }
+
+pub fn get_runtime_version_request_works() {
+	let temp_dir = TempDir::with_prefix("get_runtime_version_request_works").unwrap();
+	let rpc_handler =
+		create_rpc_handler(&temp_dir, Arc::new(SubscriptionRegistry::<ConnectionToken>::new()));
+
+	let request_string =
+		RpcRequest::compose_jsonrpc_call("state_getRuntimeVersion".to_string(), vec![]).unwrap();
+
+	let response_string =
+		rpc_handler.handle_message(ConnectionToken(1), request_string).unwrap().unwrap();
+
+	let expected_return_value =
+		RpcReturnValue::new(VERSION.encode(), false, DirectRequestStatus::Ok).to_hex();
+	assert!(response_string.contains(&expected_return_value));
+}
+
+pub fn subscribe_account_events_request_registers_subscriber() {
+	let temp_dir =
+		TempDir::with_prefix("subscribe_account_events_request_registers_subscriber").unwrap();
+	let subscription_registry = Arc::new(SubscriptionRegistry::<ConnectionToken>::new());
+	let rpc_handler = create_rpc_handler(&temp_dir, subscription_registry.clone());
+
+	let pair = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let account: AccountId = pair.public().into();
+	let shard = ShardIdentifier::default();
+	let signed_subscription = AccountEventsSubscription::new(
+		account.clone(),
+		shard,
+		1,
+		now_as_millis() + SUBSCRIPTION_VALIDITY_MILLIS,
+	)
+	.sign(&KeyPair::Ed25519(Box::new(pair)), &mrenclave());
+
+	subscribe_account_events(rpc_handler.as_ref(), ConnectionToken(1), &signed_subscription);
+
+	let subscribers =
+		subscription_registry.subscribers(&SubscriptionTopic::AccountEvents(shard, account));
+	assert_eq!(1, subscribers.len());
+	assert_eq!(ConnectionToken(1), subscribers[0].0);
+}
+
+pub fn subscribe_account_events_request_with_invalid_signature_is_rejected() {
+	let temp_dir =
+		TempDir::with_prefix("subscribe_account_events_request_with_invalid_signature_is_rejected")
+			.unwrap();
+	let subscription_registry = Arc::new(SubscriptionRegistry::<ConnectionToken>::new());
+	let rpc_handler = create_rpc_handler(&temp_dir, subscription_registry.clone());
+
+	let pair = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let other_account = AccountId::new([1u8; 32]);
+	let shard = ShardIdentifier::default();
+	let mut signed_subscription = AccountEventsSubscription::new(
+		pair.public().into(),
+		shard,
+		2,
+		now_as_millis() + SUBSCRIPTION_VALIDITY_MILLIS,
+	)
+	.sign(&KeyPair::Ed25519(Box::new(pair)), &mrenclave());
+	signed_subscription.subscription.account = other_account.clone();
+
+	subscribe_account_events(rpc_handler.as_ref(), ConnectionToken(1), &signed_subscription);
+
+	assert!(subscription_registry
+		.subscribers(&SubscriptionTopic::AccountEvents(shard, other_account))
+		.is_empty());
+}
+
+pub fn subscribe_account_events_request_for_other_enclave_is_rejected() {
+	let temp_dir =
+		TempDir::with_prefix("subscribe_account_events_request_for_other_enclave_is_rejected")
+			.unwrap();
+	let subscription_registry = Arc::new(SubscriptionRegistry::<ConnectionToken>::new());
+	let rpc_handler = create_rpc_handler(&temp_dir, subscription_registry.clone());
+
+	let pair = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let account: AccountId = pair.public().into();
+	let shard = ShardIdentifier::default();
+	let signed_subscription = AccountEventsSubscription::new(
+		account.clone(),
+		shard,
+		3,
+		now_as_millis() + SUBSCRIPTION_VALIDITY_MILLIS,
+	)
+	.sign(&KeyPair::Ed25519(Box::new(pair)), &[7u8; 32]);
+
+	subscribe_account_events(rpc_handler.as_ref(), ConnectionToken(1), &signed_subscription);
+
+	assert!(subscription_registry
+		.subscribers(&SubscriptionTopic::AccountEvents(shard, account))
+		.is_empty());
+}
+
+pub fn expired_account_events_subscription_is_rejected() {
+	let temp_dir = TempDir::with_prefix("expired_account_events_subscription_is_rejected").unwrap();
+	let subscription_registry = Arc::new(SubscriptionRegistry::<ConnectionToken>::new());
+	let rpc_handler = create_rpc_handler(&temp_dir, subscription_registry.clone());
+
+	let pair = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let account: AccountId = pair.public().into();
+	let shard = ShardIdentifier::default();
+	let signed_subscription = AccountEventsSubscription::new(
+		account.clone(),
+		shard,
+		4,
+		now_as_millis() - SUBSCRIPTION_VALIDITY_MILLIS,
+	)
+	.sign(&KeyPair::Ed25519(Box::new(pair)), &mrenclave());
+
+	subscribe_account_events(rpc_handler.as_ref(), ConnectionToken(1), &signed_subscription);
+
+	assert!(subscription_registry
+		.subscribers(&SubscriptionTopic::AccountEvents(shard, account))
+		.is_empty());
+}
+
+pub fn replayed_account_events_subscription_is_rejected() {
+	let temp_dir =
+		TempDir::with_prefix("replayed_account_events_subscription_is_rejected").unwrap();
+	let subscription_registry = Arc::new(SubscriptionRegistry::<ConnectionToken>::new());
+	let rpc_handler = create_rpc_handler(&temp_dir, subscription_registry.clone());
+
+	let pair = Ed25519Pair::from_seed(b"14672678901234567890123456789012");
+	let account: AccountId = pair.public().into();
+	let shard = ShardIdentifier::default();
+	let signed_subscription = AccountEventsSubscription::new(
+		account.clone(),
+		shard,
+		5,
+		now_as_millis() + SUBSCRIPTION_VALIDITY_MILLIS,
+	)
+	.sign(&KeyPair::Ed25519(Box::new(pair)), &mrenclave());
+
+	subscribe_account_events(rpc_handler.as_ref(), ConnectionToken(1), &signed_subscription);
+	subscribe_account_events(rpc_handler.as_ref(), ConnectionToken(2), &signed_subscription);
+
+	let subscribers =
+		subscription_registry.subscribers(&SubscriptionTopic::AccountEvents(shard, account));
+	assert_eq!(1, subscribers.len());
+	assert_eq!(ConnectionToken(1), subscribers[0].0);
+}
+
+const SUBSCRIPTION_VALIDITY_MILLIS: u64 = 10_000;
+
+fn mrenclave() -> [u8; 32] {
+	OcallApi.get_mrenclave_of_self().unwrap().m
+}
+
+fn subscribe_account_events(
+	rpc_handler: &impl WebSocketMessageHandler,
+	connection: ConnectionToken,
+	signed_subscription: &AccountEventsSubscriptionSigned,
+) {
+	let request_string = RpcRequest::compose_jsonrpc_call(
+		"author_subscribeAccountEvents".to_string(),
+		vec![signed_subscription.to_hex()],
+	)
+	.unwrap();
+
+	rpc_handler.handle_message(connection, request_string).unwrap();
+}
+
+fn create_rpc_handler(
+	temp_dir: &TempDir,
+	subscription_registry: Arc<SubscriptionRegistry<ConnectionToken>>,
+) -> Arc<impl WebSocketMessageHandler> {
+	type TestState = u64;
+
+	let rsa_repository = get_rsa3072_repository(temp_dir.path().to_path_buf()).unwrap();
+	let state_observer = Arc::new(ObserveStateMock::<TestState>::new(0u64));
+	let getter_executor =
+		Arc::new(GetterExecutor::<_, GetStateMock<TestState>, Getter>::new(state_observer));
+	let top_pool_author = Arc::new(AuthorApiMock::default());
+
+	let io_handler =
+		public_api_rpc_handler(top_pool_author, getter_executor, Arc::new(rsa_repository));
+	Arc::new(RpcWsHandler::new(
+		io_handler,
+		Arc::new(create_determine_watch::<Hash>()),
+		Arc::new(ConnectionRegistry::<Hash, ConnectionToken>::new()),
+		subscription_registry,
+	))
+}
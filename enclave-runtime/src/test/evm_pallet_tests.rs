@@ -15,23 +15,29 @@
 */
 
 use crate::test::fixtures::test_setup::{test_setup, TestStf};
+use codec::{Decode, Encode};
 use core::str::FromStr;
-use ita_sgx_runtime::{AddressMapping, HashedAddressMapping, Index, System};
+use ita_sgx_runtime::{
+	record_evm_gas_used, AddressMapping, BlockGasLimit, EvmFeeRecipient, HashedAddressMapping,
+	Index, System, MIN_BASE_FEE,
+};
 use ita_stf::{
 	evm_helpers::{
-		create_code_hash, evm_create2_address, evm_create_address, get_evm_account_codes,
-		get_evm_account_storages,
+		create_code_hash, evm_create2_address, evm_create_address, get_evm_account,
+		get_evm_account_codes, get_evm_account_storages,
 	},
+	evm_transaction::{get_evm_receipt, EvmReceipt},
+	receipts::MAX_RECEIPTS_PER_ACCOUNT,
 	test_genesis::{endow, endowed_account as funded_pair},
-	State, TrustedCall,
+	Getter, PublicGetter, State, TrustedCall, TrustedGetter,
 };
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
 use itp_sgx_externalities::SgxExternalitiesTrait;
-use itp_stf_interface::StateCallInterface;
+use itp_stf_interface::{ExecuteGetter, StateCallInterface};
 use itp_stf_primitives::{traits::TrustedCallSigning, types::KeyPair};
 use itp_types::{parentchain::ParentchainCall, AccountId, ShardIdentifier};
 use primitive_types::H256;
-use sp_core::{crypto::Pair, H160, U256};
+use sp_core::{crypto::Pair, ed25519, hashing::blake2_256, H160, U256};
 use std::{sync::Arc, vec::Vec};
 
 pub fn test_evm_call() {
@@ -278,7 +284,7 @@ pub fn test_evm_create() {
 	let smart_contract = array_bytes::hex2bytes(smart_contract).unwrap();
 
 	let trusted_call = TrustedCall::evm_create(
-		sender_acc,
+		sender_acc.clone(),
 		sender_evm_acc,
 		smart_contract,
 		U256::from(0), // value
@@ -289,6 +295,7 @@ pub fn test_evm_create() {
 		Vec::new(),
 	)
 	.sign(&sender.into(), 0, &mrenclave, &shard);
+	let call_hash = H256::from(blake2_256(&trusted_call.encode()));
 
 	// Should be the first call of the evm account
 	let nonce = state.execute_with(|| System::account_nonce(&sender_evm_substrate_addr));
@@ -305,6 +312,20 @@ pub fn test_evm_create() {
 	);
 	assert!(state.execute_with(|| get_evm_account_codes(&execution_address).is_some()));
 
+	// The receipt tells the actual address.
+	let receipt = state.execute_with(|| get_evm_receipt(&call_hash)).unwrap();
+	assert!(receipt.succeeded);
+	assert_eq!(receipt.contract_address, Some(execution_address));
+	assert_eq!(receipt.from, sender_evm_acc);
+	assert!(receipt.gas_used > U256::zero());
+	let receipt = execute_evm_receipt_getter(&mut state, sender_acc, sender.into(), call_hash);
+	assert_eq!(receipt.and_then(|r| r.contract_address), Some(execution_address));
+
+	// It is not revealed to anyone else.
+	let other = ed25519::Pair::from_seed(b"01234567890123456789012345678901");
+	assert!(execute_evm_receipt_getter(&mut state, other.public().into(), other.into(), call_hash)
+		.is_none());
+
 	// Ensure the nonce of the evm account has been increased by one
 	// Should be the first call of the evm account
 	let nonce = state.execute_with(|| System::account_nonce(&sender_evm_substrate_addr));
@@ -345,6 +366,7 @@ pub fn test_evm_create2() {
 		Vec::new(),
 	)
 	.sign(&sender.into(), 0, &mrenclave, &shard);
+	let call_hash = H256::from(blake2_256(&trusted_call.encode()));
 
 	// when
 	let code_hash = create_code_hash(&smart_contract);
@@ -361,4 +383,150 @@ pub fn test_evm_create2() {
 	);
 
 	assert!(state.execute_with(|| get_evm_account_codes(&execution_address).is_some()));
+	let receipt = state.execute_with(|| get_evm_receipt(&call_hash)).unwrap();
+	assert_eq!(receipt.contract_address, Some(execution_address));
+}
+
+pub fn test_evm_fees_go_to_fee_recipient() {
+	// given
+	let (_, mut state, shard, mrenclave, ..) = test_setup();
+	let sender = funded_pair();
+	let sender_acc: AccountId = sender.public().into();
+	let sender_evm_acc = get_evm_account(&sender_acc);
+	let sender_evm_substrate_addr = HashedAddressMapping::into_account_id(sender_evm_acc);
+	let fee_recipient: AccountId = ed25519::Pair::from_seed(&[7u8; 32]).public().into();
+	endow(
+		&mut state,
+		vec![
+			(sender_evm_substrate_addr.clone(), 51_777_000_000_000),
+			(fee_recipient.clone(), 1_000),
+		],
+	);
+	state.execute_with(|| EvmFeeRecipient::put(fee_recipient.clone()));
+
+	let gas_price: u128 = 1_000;
+	let trusted_call = TrustedCall::evm_call(
+		sender_acc,
+		sender_evm_acc,
+		H160::from_str("1000000000000000000000000000000000000001").unwrap(),
+		Vec::new(),
+		U256::zero(),
+		21_000, // gas limit
+		U256::from(gas_price),
+		None,
+		Some(U256::zero()),
+		Vec::new(),
+	)
+	.sign(&sender.into(), 0, &mrenclave, &shard);
+
+	// when
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	TestStf::execute_call(&mut state, trusted_call, &mut Vec::new(), repo).unwrap();
+
+	// then
+	let fees = 21_000 * gas_price;
+	assert_eq!(
+		state.execute_with(|| System::account(&sender_evm_substrate_addr).data.free),
+		51_777_000_000_000 - fees
+	);
+	assert_eq!(state.execute_with(|| System::account(&fee_recipient).data.free), 1_000 + fees);
+}
+
+pub fn test_evm_base_fee_rises_after_full_block() {
+	// given
+	let (_, mut state, shard, mrenclave, ..) = test_setup();
+	let sender = funded_pair();
+	let sender_acc: AccountId = sender.public().into();
+	let sender_evm_acc = get_evm_account(&sender_acc);
+	endow(
+		&mut state,
+		vec![(HashedAddressMapping::into_account_id(sender_evm_acc), 51_777_000_000_000)],
+	);
+	assert_eq!(execute_evm_base_fee_getter(&mut state), U256::from(MIN_BASE_FEE));
+
+	// when
+	state.execute_with(|| {
+		record_evm_gas_used(BlockGasLimit::get());
+		System::set_block_number(System::block_number() + 1);
+	});
+
+	// then
+	assert_eq!(execute_evm_base_fee_getter(&mut state), U256::from(MIN_BASE_FEE + 1));
+
+	// The former base fee is not enough anymore.
+	let trusted_call = TrustedCall::evm_call(
+		sender_acc,
+		sender_evm_acc,
+		H160::from_str("1000000000000000000000000000000000000001").unwrap(),
+		Vec::new(),
+		U256::zero(),
+		21_000, // gas limit
+		U256::from(MIN_BASE_FEE),
+		None,
+		Some(U256::zero()),
+		Vec::new(),
+	)
+	.sign(&sender.into(), 0, &mrenclave, &shard);
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	assert!(TestStf::execute_call(&mut state, trusted_call, &mut Vec::new(), repo).is_err());
+}
+
+pub fn test_evm_receipts_are_pruned_with_the_receipt_log() {
+	// given
+	let (_, mut state, shard, mrenclave, ..) = test_setup();
+	let sender = funded_pair();
+	let sender_acc: AccountId = sender.public().into();
+	let sender_evm_acc = get_evm_account(&sender_acc);
+	endow(
+		&mut state,
+		vec![(HashedAddressMapping::into_account_id(sender_evm_acc), 51_777_000_000_000)],
+	);
+
+	// when
+	let call_hashes: Vec<H256> = (0..=MAX_RECEIPTS_PER_ACCOUNT as Index)
+		.map(|nonce| {
+			let trusted_call = TrustedCall::evm_call(
+				sender_acc.clone(),
+				sender_evm_acc,
+				H160::from_str("1000000000000000000000000000000000000001").unwrap(),
+				Vec::new(),
+				U256::zero(),
+				21_000, // gas limit
+				U256::from(1_000),
+				None,
+				Some(U256::zero()),
+				Vec::new(),
+			)
+			.sign(&sender.clone().into(), nonce, &mrenclave, &shard);
+			let call_hash = H256::from(blake2_256(&trusted_call.encode()));
+			let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+			TestStf::execute_call(&mut state, trusted_call, &mut Vec::new(), repo).unwrap();
+			call_hash
+		})
+		.collect();
+
+	// then
+	assert!(state.execute_with(|| get_evm_receipt(&call_hashes[0])).is_none());
+	assert!(state.execute_with(|| get_evm_receipt(&call_hashes[1])).is_some());
+	assert!(state
+		.execute_with(|| get_evm_receipt(&call_hashes[MAX_RECEIPTS_PER_ACCOUNT]))
+		.is_some());
+}
+
+fn execute_evm_base_fee_getter(state: &mut State) -> U256 {
+	let encoded = state
+		.execute_with(|| Getter::public(PublicGetter::evm_base_fee).execute())
+		.unwrap();
+	U256::decode(&mut encoded.as_slice()).unwrap()
+}
+
+fn execute_evm_receipt_getter(
+	state: &mut State,
+	who: AccountId,
+	pair: KeyPair,
+	call_hash: H256,
+) -> Option<EvmReceipt> {
+	let getter = TrustedGetter::evm_receipt(who, call_hash).sign(&pair);
+	let encoded = state.execute_with(|| getter.execute()).unwrap();
+	Option::<EvmReceipt>::decode(&mut encoded.as_slice()).unwrap()
 }
@@ -0,0 +1,238 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::test::fixtures::test_setup::{enclave_call_signer, test_setup, TestStf};
+use codec::{Decode, Encode};
+use ita_sgx_runtime::{
+	AddressMapping, EvmEnabledPrecompiles, HashedAddressMapping, EXCHANGE_RATES_PRECOMPILE,
+	MAX_PRIVATE_DATA_LEN, PARENTCHAIN_PRECOMPILE, PARENTCHAIN_READ_GAS, PRIVATE_DATA_PRECOMPILE,
+	RANDOMNESS_PRECOMPILE,
+};
+use ita_stf::{
+	evm_helpers::get_evm_account,
+	evm_transaction::{simulate_evm_call, EvmCallRequest, EvmCallResult},
+	test_genesis::{endow, endowed_account as funded_pair},
+	State, TrustedCall, TrustedGetter,
+};
+use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_interface::{
+	parentchain_pallet::ParentchainPalletInstancesInterface, ExecuteGetter, StateCallInterface,
+};
+use itp_stf_primitives::{error::StfError, traits::TrustedCallSigning, types::KeyPair};
+use itp_types::{AccountId, Header};
+use sp_core::{ed25519, hashing::blake2_256, Pair, H160, H256, U256};
+use sp_runtime::traits::Header as HeaderT;
+use std::{format, sync::Arc, vec::Vec};
+
+const BLOCK_NUMBER_SELECTOR: &str = "57e871e7";
+const BLOCK_HASH_SELECTOR: &str = "f22a195e";
+const EXCHANGE_RATE_SELECTOR: &str = "0f57e848";
+const RANDOM_BYTES_32_SELECTOR: &str = "f6982ec6";
+const STORE_SELECTOR: &str = "151c3b12";
+
+pub fn test_enclave_precompiles_are_disabled_by_default() {
+	let (_, mut state, shard, mrenclave, ..) = test_setup();
+	let owner = funded_pair();
+	let header: Header = HeaderT::new(
+		3,
+		Default::default(),
+		Default::default(),
+		H256::from([1; 32]),
+		Default::default(),
+	);
+	TestStf::update_parentchain_integritee_block(&mut state, header).unwrap();
+
+	// Without a precompile, the address is an empty account.
+	let result =
+		state.execute_with(|| call_precompile(PARENTCHAIN_PRECOMPILE, BLOCK_NUMBER_SELECTOR));
+	assert!(result.succeeded);
+	assert!(result.value.is_empty());
+
+	// Only root may enable a precompile.
+	let call = TrustedCall::evm_set_precompile_enabled(
+		owner.public().into(),
+		PARENTCHAIN_PRECOMPILE,
+		true,
+	)
+	.sign(&owner.into(), 0, &mrenclave, &shard);
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	assert!(matches!(
+		TestStf::execute_call(&mut state, call, &mut Vec::new(), repo),
+		Err(StfError::MissingPrivileges(_))
+	));
+	assert!(!state.execute_with(|| EvmEnabledPrecompiles::contains_key(PARENTCHAIN_PRECOMPILE)));
+}
+
+pub fn test_parentchain_precompile_returns_latest_block() {
+	let (_, mut state, ..) = test_setup();
+	let header: Header = HeaderT::new(
+		3,
+		Default::default(),
+		Default::default(),
+		H256::from([1; 32]),
+		Default::default(),
+	);
+	TestStf::update_parentchain_integritee_block(&mut state, header.clone()).unwrap();
+	enable(&mut state, PARENTCHAIN_PRECOMPILE);
+
+	let result =
+		state.execute_with(|| call_precompile(PARENTCHAIN_PRECOMPILE, BLOCK_NUMBER_SELECTOR));
+	assert!(result.succeeded);
+	assert_eq!(result.value, H256::from_low_u64_be(3).as_bytes().to_vec());
+	// Intrinsic gas, 4 non-zero bytes of call data and the read of the block.
+	assert_eq!(result.gas_used, U256::from(21_000 + 4 * 16 + PARENTCHAIN_READ_GAS));
+
+	let result =
+		state.execute_with(|| call_precompile(PARENTCHAIN_PRECOMPILE, BLOCK_HASH_SELECTOR));
+	assert_eq!(result.value, header.hash().as_bytes().to_vec());
+}
+
+pub fn test_exchange_rates_precompile_returns_rate_set_by_enclave() {
+	let (_, mut state, shard, mrenclave, shielding_key, ..) = test_setup();
+	enable(&mut state, EXCHANGE_RATES_PRECOMPILE);
+	let enclave_signer = enclave_call_signer(&shielding_key);
+	// 1.5 as fixed point number with 32 fractional bits.
+	let rate = 3u64 << 31;
+
+	let call = TrustedCall::teeracle_set_exchange_rate(
+		enclave_signer.public().into(),
+		b"DOT/USD".to_vec(),
+		rate,
+	)
+	.sign(&enclave_signer.into(), 0, &mrenclave, &shard);
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	TestStf::execute_call(&mut state, call, &mut Vec::new(), repo).unwrap();
+
+	let trading_pair = |pair: &[u8]| {
+		let mut word = [0u8; 32];
+		word[..pair.len()].copy_from_slice(pair);
+		format!("{}{}", EXCHANGE_RATE_SELECTOR, hex::encode(word))
+	};
+	let result = state
+		.execute_with(|| call_precompile(EXCHANGE_RATES_PRECOMPILE, &trading_pair(b"DOT/USD")));
+	assert!(result.succeeded);
+	assert_eq!(result.value, H256::from_low_u64_be(rate).as_bytes().to_vec());
+
+	let result = state
+		.execute_with(|| call_precompile(EXCHANGE_RATES_PRECOMPILE, &trading_pair(b"BTC/USD")));
+	assert!(!result.succeeded);
+}
+
+pub fn test_randomness_precompile_returns_fresh_randomness() {
+	let (_, mut state, ..) = test_setup();
+	enable(&mut state, RANDOMNESS_PRECOMPILE);
+
+	let first =
+		state.execute_with(|| call_precompile(RANDOMNESS_PRECOMPILE, RANDOM_BYTES_32_SELECTOR));
+	let second =
+		state.execute_with(|| call_precompile(RANDOMNESS_PRECOMPILE, RANDOM_BYTES_32_SELECTOR));
+
+	assert!(first.succeeded);
+	assert_eq!(first.value.len(), 32);
+	assert_ne!(first.value, second.value);
+}
+
+pub fn test_private_data_is_only_revealed_to_recipient() {
+	let (_, mut state, shard, mrenclave, ..) = test_setup();
+	enable(&mut state, PRIVATE_DATA_PRECOMPILE);
+	let sender = funded_pair();
+	let sender_evm_account = get_evm_account(&sender.public().into());
+	endow(
+		&mut state,
+		vec![(HashedAddressMapping::into_account_id(sender_evm_account), 51_777_000_000_000)],
+	);
+	let recipient = ed25519::Pair::from_seed(b"12345678901234567890123456789012");
+	let recipient_evm_account = get_evm_account(&recipient.public().into());
+
+	// store(recipient, 0x0102)
+	let input = format!(
+		"{}{}{}{}{}",
+		STORE_SELECTOR,
+		hex::encode(H256::from(recipient_evm_account)),
+		hex::encode(H256::from_low_u64_be(64)),
+		hex::encode(H256::from_low_u64_be(2)),
+		hex::encode(H256::from_slice(&[[1u8, 2].as_slice(), &[0u8; 30]].concat()))
+	);
+	let call = TrustedCall::evm_call(
+		sender.public().into(),
+		sender_evm_account,
+		PRIVATE_DATA_PRECOMPILE,
+		array_bytes::hex2bytes(&input).unwrap(),
+		U256::zero(),
+		100_000, // gas limit
+		U256::from(1),
+		None,
+		Some(U256::zero()),
+		Vec::new(),
+	)
+	.sign(&sender.into(), 0, &mrenclave, &shard);
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	TestStf::execute_call(&mut state, call, &mut Vec::new(), repo).unwrap();
+
+	let id = H256::from(blake2_256(&(sender_evm_account, recipient_evm_account, 0u64).encode()));
+	assert_eq!(state.execute_with(|| private_data(recipient, id)), Some(vec![1, 2]));
+	assert_eq!(state.execute_with(|| private_data(sender, id)), None);
+}
+
+pub fn test_oversized_private_data_is_not_stored() {
+	let (_, mut state, ..) = test_setup();
+	enable(&mut state, PRIVATE_DATA_PRECOMPILE);
+	let length = MAX_PRIVATE_DATA_LEN + 1;
+
+	// store(recipient, data), with data of `length` zero bytes
+	let input = format!(
+		"{}{}{}{}{}",
+		STORE_SELECTOR,
+		hex::encode(H256::from_low_u64_be(1)),
+		hex::encode(H256::from_low_u64_be(64)),
+		hex::encode(H256::from_low_u64_be(length as u64)),
+		hex::encode(vec![0u8; (length + 31) / 32 * 32])
+	);
+	let result = state.execute_with(|| call_precompile(PRIVATE_DATA_PRECOMPILE, &input));
+
+	assert!(!result.succeeded);
+	assert_eq!(result.value, b"Data is too large to be stored".to_vec());
+}
+
+fn enable(state: &mut State, precompile: H160) {
+	state.execute_with(|| EvmEnabledPrecompiles::insert(precompile, ()));
+}
+
+fn call_precompile(precompile: H160, input: &str) -> EvmCallResult {
+	simulate_evm_call(
+		EvmCallRequest {
+			from: None,
+			to: Some(precompile),
+			data: array_bytes::hex2bytes(input).unwrap(),
+			value: U256::zero(),
+			gas_limit: None,
+			chain_id: None,
+		},
+		100_000,
+	)
+	.unwrap()
+}
+
+fn private_data(who: ed25519::Pair, id: H256) -> Option<Vec<u8>> {
+	let who_account: AccountId = who.public().into();
+	let encoded = TrustedGetter::evm_private_data(who_account, id)
+		.sign(&KeyPair::Ed25519(Box::new(who)))
+		.execute()
+		.unwrap();
+	Option::<Vec<u8>>::decode(&mut encoded.as_slice()).unwrap()
+}
@@ -0,0 +1,220 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::test::fixtures::test_setup::{test_setup, TestStf};
+use codec::Decode;
+use ita_sgx_runtime::{
+	AddressMapping, EvmStateReaders, HashedAddressMapping, STATE_READERS_PRECOMPILE,
+};
+use ita_stf::{
+	evm_helpers::{evm_create_address, get_evm_account},
+	evm_state_access::{set_evm_state_access_policy, EvmStateAccessPolicy},
+	evm_transaction::EvmCallRequest,
+	test_genesis::{endow, endowed_account as funded_pair},
+	Getter, PublicGetter, State, TrustedCall, TrustedGetter,
+};
+use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_interface::{ExecuteGetter, StateCallInterface};
+use itp_stf_primitives::{
+	error::StfError,
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair},
+};
+use itp_types::ShardIdentifier;
+use sp_core::{ed25519, Pair, H160, H256, U256};
+use std::{format, sync::Arc, vec::Vec};
+
+// Init code of a contract, which stores 42 in slot 0 and has the code `0x00` (STOP).
+const STORE_42_INIT_CODE: &str = "602a600055600060005360016000f3";
+
+pub fn test_evm_state_is_only_readable_by_owner_by_default() {
+	let reader = reader_pair();
+	let (mut state, _, _, owner, contract) = setup_with_contract(&reader);
+
+	assert_eq!(
+		state.execute_with(|| read_slot_0(owner, contract)),
+		Some(H256::from_low_u64_be(42))
+	);
+	assert!(state.execute_with(|| read_code(owner, contract)).is_some());
+	// Readers on the allowlist of the contract are ignored.
+	assert_eq!(state.execute_with(|| read_slot_0(reader, contract)), None);
+	assert_eq!(state.execute_with(|| read_code(reader, contract)), None);
+
+	// Without a signature, nothing is revealed.
+	assert!(state.execute_with(|| public_getter(PublicGetter::evm_code(contract))).is_none());
+	assert!(state
+		.execute_with(|| public_getter(PublicGetter::evm_storage(contract, H256::zero())))
+		.is_none());
+	let owner_evm_account = get_evm_account(&account(owner));
+	assert!(state
+		.execute_with(|| public_getter(PublicGetter::evm_balance(owner_evm_account)))
+		.is_none());
+	assert!(state
+		.execute_with(|| public_getter(PublicGetter::evm_transaction_count(owner_evm_account)))
+		.is_none());
+}
+
+pub fn test_evm_state_is_readable_by_readers_on_allowlist() {
+	let reader = reader_pair();
+	let other_reader = ed25519::Pair::from_seed(b"98765432109876543210987654321098");
+	let (mut state, _, _, owner, contract) = setup_with_contract(&reader);
+	state.execute_with(|| set_evm_state_access_policy(EvmStateAccessPolicy::Allowlist));
+
+	assert!(state.execute_with(|| EvmStateReaders::contains_key(
+		contract,
+		get_evm_account(&account(reader))
+	)));
+	assert_eq!(
+		state.execute_with(|| read_slot_0(owner, contract)),
+		Some(H256::from_low_u64_be(42))
+	);
+	assert_eq!(
+		state.execute_with(|| read_slot_0(reader, contract)),
+		Some(H256::from_low_u64_be(42))
+	);
+	assert_eq!(state.execute_with(|| read_slot_0(other_reader, contract)), None);
+	assert!(state.execute_with(|| public_getter(PublicGetter::evm_code(contract))).is_none());
+}
+
+pub fn test_evm_state_is_readable_by_anyone_if_public() {
+	let reader = reader_pair();
+	let other_reader = ed25519::Pair::from_seed(b"98765432109876543210987654321098");
+	let (mut state, shard, mrenclave, owner, contract) = setup_with_contract(&reader);
+
+	// Only root may change the policy.
+	let call =
+		TrustedCall::evm_set_state_access_policy(account(owner), EvmStateAccessPolicy::Public)
+			.sign(&owner.into(), 1, &mrenclave, &shard);
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	assert!(matches!(
+		TestStf::execute_call(&mut state, call, &mut Vec::new(), repo),
+		Err(StfError::MissingPrivileges(_))
+	));
+	assert_eq!(state.execute_with(|| read_slot_0(other_reader, contract)), None);
+
+	state.execute_with(|| set_evm_state_access_policy(EvmStateAccessPolicy::Public));
+
+	assert_eq!(
+		state.execute_with(|| read_slot_0(other_reader, contract)),
+		Some(H256::from_low_u64_be(42))
+	);
+	let public_storage =
+		state.execute_with(|| public_getter(PublicGetter::evm_storage(contract, H256::zero())));
+	assert_eq!(
+		H256::decode(&mut public_storage.unwrap().as_slice()).unwrap(),
+		H256::from_low_u64_be(42)
+	);
+	let owner_evm_account = get_evm_account(&account(owner));
+	let public_nonce = state
+		.execute_with(|| public_getter(PublicGetter::evm_transaction_count(owner_evm_account)));
+	assert_eq!(u32::decode(&mut public_nonce.unwrap().as_slice()).unwrap(), 1);
+}
+
+pub fn test_evm_calls_are_only_simulated_for_readers() {
+	let reader = reader_pair();
+	let other = ed25519::Pair::from_seed(b"98765432109876543210987654321098");
+	let (mut state, _, _, owner, contract) = setup_with_contract(&reader);
+	let request = EvmCallRequest { to: Some(contract), ..Default::default() };
+
+	// Without a signature, calls are not simulated and receipts are not revealed.
+	assert!(state
+		.execute_with(|| public_getter(PublicGetter::evm_call(request.clone())))
+		.is_none());
+	assert!(state
+		.execute_with(|| public_getter(PublicGetter::evm_transaction_receipt(H256::zero())))
+		.is_none());
+
+	assert!(state.execute_with(|| simulate_call(owner, request.clone())).is_some());
+	assert!(state.execute_with(|| simulate_call(other, request.clone())).is_none());
+
+	state.execute_with(|| set_evm_state_access_policy(EvmStateAccessPolicy::Public));
+
+	assert!(state.execute_with(|| public_getter(PublicGetter::evm_call(request))).is_some());
+}
+
+/// Creates a contract with the funded account, which adds `reader` to its readers with the
+/// `STATE_READERS_PRECOMPILE` and then executes `STORE_42_INIT_CODE`.
+fn setup_with_contract(
+	reader: &ed25519::Pair,
+) -> (State, ShardIdentifier, [u8; 32], ed25519::Pair, H160) {
+	let (_, mut state, shard, mrenclave, ..) = test_setup();
+	let owner = funded_pair();
+	let owner_evm_account = get_evm_account(&account(owner));
+	endow(
+		&mut state,
+		vec![(HashedAddressMapping::into_account_id(owner_evm_account), 51_777_000_000_000)],
+	);
+
+	// MSTORE the call data of `addReader(reader)` at 0, then
+	// CALL(gas, STATE_READERS_PRECOMPILE, 0, 0, 36, 0, 0) and POP the result.
+	let init_code = format!(
+		"7fafd8b1d1{}600052 73{}600452 6000600060246000600061{}5af150 {}",
+		"00".repeat(28),
+		hex::encode(get_evm_account(&account(*reader))),
+		hex::encode(&STATE_READERS_PRECOMPILE[18..]),
+		STORE_42_INIT_CODE
+	)
+	.replace(' ', "");
+	let call = TrustedCall::evm_create(
+		account(owner),
+		owner_evm_account,
+		array_bytes::hex2bytes(&init_code).unwrap(),
+		U256::zero(),
+		1_000_000,
+		U256::from(1),
+		None,
+		Some(U256::zero()),
+		Vec::new(),
+	)
+	.sign(&owner.into(), 0, &mrenclave, &shard);
+	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
+	TestStf::execute_call(&mut state, call, &mut Vec::new(), repo).unwrap();
+
+	(state, shard, mrenclave, owner, evm_create_address(owner_evm_account, 0))
+}
+
+fn read_slot_0(who: ed25519::Pair, evm_account: H160) -> Option<H256> {
+	TrustedGetter::evm_account_storages(account(who), evm_account, H256::zero())
+		.sign(&KeyPair::Ed25519(Box::new(who)))
+		.execute()
+		.map(|value| H256::decode(&mut value.as_slice()).unwrap())
+}
+
+fn read_code(who: ed25519::Pair, evm_account: H160) -> Option<Vec<u8>> {
+	TrustedGetter::evm_account_codes(account(who), evm_account)
+		.sign(&KeyPair::Ed25519(Box::new(who)))
+		.execute()
+}
+
+fn simulate_call(who: ed25519::Pair, request: EvmCallRequest) -> Option<Vec<u8>> {
+	TrustedGetter::evm_call(account(who), request)
+		.sign(&KeyPair::Ed25519(Box::new(who)))
+		.execute()
+}
+
+fn public_getter(getter: PublicGetter) -> Option<Vec<u8>> {
+	Getter::public(getter).execute()
+}
+
+fn reader_pair() -> ed25519::Pair {
+	ed25519::Pair::from_seed(b"12345678901234567890123456789012")
+}
+
+fn account(pair: ed25519::Pair) -> AccountId {
+	pair.public().into()
+}
@@ -19,7 +19,9 @@
 use crate::test::mocks::types::TestBlockImporter;
 use codec::{Decode, Encode};
 use itc_parentchain::primitives::ParentchainId;
-use itp_ocall_api::{EnclaveOnChainOCallApi, EnclaveSidechainOCallApi, Result};
+use itp_ocall_api::{
+	EnclaveMetricsOCallApi, EnclaveOnChainOCallApi, EnclaveSidechainOCallApi, Result,
+};
 use itp_types::{
 	storage::StorageEntryVerified, BlockHash, Header as ParentchainHeader, ShardIdentifier,
 	WorkerRequest, WorkerResponse, H256,
@@ -119,3 +121,9 @@ impl EnclaveSidechainOCallApi for ProposeToImportOCallApi {
 		Ok(Vec::new())
 	}
 }
+
+impl EnclaveMetricsOCallApi for ProposeToImportOCallApi {
+	fn update_metric<Metric: Encode>(&self, _metric: Metric) -> SgxResult<()> {
+		Ok(())
+	}
+}
@@ -18,9 +18,13 @@
 
 //! Type definitions for testing. Includes various mocks.
 
-use crate::test::mocks::rpc_responder_mock::RpcResponderMock;
+use crate::{
+	rpc::block_import_notifier::SubscriptionBlockImportNotifier,
+	test::mocks::rpc_responder_mock::RpcResponderMock,
+};
 use ita_sgx_runtime::Runtime;
 use ita_stf::{Getter, Stf, TrustedCallSigned};
+use itc_direct_rpc_server::mocks::notify_subscribers_mock::NotifySubscribersMock;
 use itc_parentchain::block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
 use itp_sgx_crypto::{mocks::KeyRepositoryMock, Aes};
@@ -101,6 +105,9 @@ pub type TestBlockImporter = BlockImporter<
 	TestStateKeyRepo,
 	TestTopPoolAuthor,
 	TestParentchainBlockImportTrigger,
+	TestBlockImportNotifier,
 	TrustedCallSigned,
 	Getter,
 >;
+
+pub type TestBlockImportNotifier = SubscriptionBlockImportNotifier<NotifySubscribersMock>;
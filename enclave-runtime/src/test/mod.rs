@@ -21,12 +21,17 @@ pub mod direct_rpc_tests;
 pub mod enclave_signer_tests;
 #[cfg(feature = "evm")]
 pub mod evm_pallet_tests;
+#[cfg(feature = "evm")]
+pub mod evm_precompiles_tests;
+#[cfg(feature = "evm")]
+pub mod evm_state_access_tests;
 pub mod fixtures;
 pub mod ipfs_tests;
 pub mod mocks;
 pub mod sidechain_aura_tests;
 pub mod sidechain_event_tests;
 mod state_getter_tests;
+mod state_migration_tests;
 pub mod tests_main;
 pub mod top_pool_tests;
 
@@ -34,6 +34,7 @@ use ita_stf::{
 	test_genesis::{endowed_account, second_endowed_account, unendowed_account},
 	Balance, Getter, TrustedCall, TrustedCallSigned,
 };
+use itc_direct_rpc_server::mocks::notify_subscribers_mock::NotifySubscribersMock;
 use itc_parentchain_test::ParentchainHeaderBuilder;
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
 use itp_ocall_api::EnclaveAttestationOCallApi;
@@ -113,10 +114,15 @@ pub fn produce_sidechain_block_and_import_it() {
 		top_pool_author.clone(),
 		parentchain_block_import_trigger.clone(),
 		ocall_api.clone(),
+		Arc::new(TestBlockImportNotifier::new(Arc::new(NotifySubscribersMock::default()))),
 	));
 	let block_composer = Arc::new(TestBlockComposer::new(signer, state_key_repo));
-	let proposer_environment =
-		ProposerFactory::new(top_pool_author.clone(), stf_executor, block_composer);
+	let proposer_environment = ProposerFactory::new(
+		top_pool_author.clone(),
+		stf_executor,
+		block_composer,
+		Arc::new(TestBlockImportNotifier::new(Arc::new(NotifySubscribersMock::default()))),
+	);
 
 	info!("Create trusted operations..");
 	let sender = endowed_account();
@@ -29,6 +29,7 @@ use crate::{
 };
 use ita_sgx_runtime::Runtime;
 use ita_stf::{helpers::set_block_number, Getter, TrustedCallSigned};
+use itc_direct_rpc_server::mocks::notify_subscribers_mock::NotifySubscribersMock;
 use itc_parentchain_test::ParentchainHeaderBuilder;
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
 use itp_settings::{
@@ -41,7 +42,7 @@ use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::metrics_ocall_mock::MetricsOCallMock;
 use itp_time_utils::duration_now;
 use itp_top_pool_author::top_filter::AllowAllTopsFilter;
-use itp_types::Block as ParentchainBlock;
+use itp_types::{Block as ParentchainBlock, SubscriptionTopic};
 use its_block_verification::slot::slot_from_timestamp_and_duration;
 use its_primitives::types::SignedBlock as SignedSidechainBlock;
 use its_sidechain::{aura::proposer_factory::ProposerFactory, slots::SlotInfo};
@@ -95,15 +96,25 @@ pub fn ensure_events_get_reset_upon_block_proposal() {
 		Arc::new(MetricsOCallMock::default()),
 	));
 	let parentchain_block_import_trigger = Arc::new(TestParentchainBlockImportTrigger::default());
+	let subscription_notifier =
+		Arc::new(NotifySubscribersMock::with_topics(vec![SubscriptionTopic::NewHeads]));
 	let block_importer = Arc::new(TestBlockImporter::new(
 		state_handler.clone(),
 		state_key_repo.clone(),
 		top_pool_author.clone(),
 		parentchain_block_import_trigger.clone(),
 		ocall_api.clone(),
+		Arc::new(TestBlockImportNotifier::new(subscription_notifier.clone())),
 	));
 	let block_composer = Arc::new(TestBlockComposer::new(signer, state_key_repo));
-	let proposer_environment = ProposerFactory::new(top_pool_author, stf_executor, block_composer);
+	let proposal_subscription_notifier =
+		Arc::new(NotifySubscribersMock::with_topics(vec![SubscriptionTopic::NewHeads]));
+	let proposer_environment = ProposerFactory::new(
+		top_pool_author,
+		stf_executor,
+		block_composer,
+		Arc::new(TestBlockImportNotifier::new(proposal_subscription_notifier.clone())),
+	);
 
 	// Add some events to the state.
 	let topic_hash = H256::from([7; 32]);
@@ -152,6 +163,13 @@ pub fn ensure_events_get_reset_upon_block_proposal() {
 		)
 		.unwrap();
 
+	// Ensure the locally produced block has been notified to the subscribers.
+	let proposal_notifications = proposal_subscription_notifier.notifications.read().unwrap();
+	assert_eq!(blocks.len(), 1);
+	assert_eq!(proposal_notifications.len(), 1);
+	assert_eq!(proposal_notifications[0].0, SubscriptionTopic::NewHeads);
+	drop(proposal_notifications);
+
 	info!("Executed AURA successfully. Sending blocks and extrinsics..");
 	let propose_to_block_import_ocall_api =
 		Arc::new(ProposeToImportOCallApi::new(parentchain_header, block_importer));
@@ -168,4 +186,9 @@ pub fn ensure_events_get_reset_upon_block_proposal() {
 	assert_eq!(TestStf::get_event_count(&mut state), 0);
 	assert_eq!(TestStf::get_event_topics(&mut state, &topic_hash).len(), 0);
 	assert_eq!(TestStf::get_events(&mut state).len(), 0);
+
+	// Ensure the imported block has been notified to the subscribers.
+	let notifications = subscription_notifier.notifications.read().unwrap();
+	assert_eq!(notifications.len(), 1);
+	assert_eq!(notifications[0].0, SubscriptionTopic::NewHeads);
 }
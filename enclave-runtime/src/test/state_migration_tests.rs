@@ -0,0 +1,102 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Copyright (C) 2017-2019 Baidu, Inc. All Rights Reserved.
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::test::fixtures::test_setup::TestStf;
+use ita_sgx_runtime::VERSION;
+use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+use itp_stf_interface::{InitState, RuntimeUpgradeInterface};
+use itp_stf_state_handler::{
+	in_memory_state_file_io::create_sgx_externalities_in_memory_state_io,
+	state_migrator::{migrate_latest_snapshots, MigrateState, MigrationOutcome, StateMigrator},
+	state_snapshot_repository::{StateSnapshotRepository, VersionedStateAccess},
+};
+use itp_storage::storage_value_key;
+use itp_types::{AccountId, ShardIdentifier};
+use std::vec;
+
+type TestStateMigrator = StateMigrator<SgxExternalities, TestStf>;
+
+pub fn new_state_does_not_need_migration() {
+	let mut state = new_state();
+
+	let outcome = TestStateMigrator::new(false).migrate(&shard(), &mut state).unwrap();
+
+	assert_eq!(outcome, MigrationOutcome::UpToDate);
+}
+
+pub fn state_without_runtime_version_is_migrated() {
+	let mut state = state_without_runtime_version();
+
+	let outcome = TestStateMigrator::new(false).migrate(&shard(), &mut state).unwrap();
+
+	assert_eq!(
+		outcome,
+		MigrationOutcome::Migrated {
+			from_spec_version: None,
+			to_spec_version: VERSION.spec_version
+		}
+	);
+	assert_eq!(TestStf::state_spec_version(&mut state), Some(VERSION.spec_version));
+}
+
+pub fn migration_dry_run_over_stored_snapshots_leaves_them_untouched() {
+	let outdated_shard = ShardIdentifier::from([1u8; 32]);
+	let current_shard = ShardIdentifier::from([2u8; 32]);
+	let mut repository =
+		StateSnapshotRepository::empty(create_sgx_externalities_in_memory_state_io(), 3).unwrap();
+	repository
+		.initialize_new_shard(outdated_shard, &state_without_runtime_version())
+		.unwrap();
+	repository.initialize_new_shard(current_shard, &new_state()).unwrap();
+
+	let mut outcomes =
+		migrate_latest_snapshots(&repository, &TestStateMigrator::new(true)).unwrap();
+	outcomes.sort_by_key(|(shard, _)| *shard);
+
+	assert_eq!(
+		outcomes,
+		vec![
+			(
+				outdated_shard,
+				MigrationOutcome::DryRun {
+					from_spec_version: None,
+					to_spec_version: VERSION.spec_version
+				}
+			),
+			(current_shard, MigrationOutcome::UpToDate),
+		]
+	);
+	let mut stored_state = repository.load_latest(&outdated_shard).unwrap();
+	assert_eq!(TestStf::state_spec_version(&mut stored_state), None);
+}
+
+fn new_state() -> SgxExternalities {
+	TestStf::init_state(AccountId::new([5u8; 32]))
+}
+
+/// A state as written by enclaves from before runtime versions were recorded.
+fn state_without_runtime_version() -> SgxExternalities {
+	let mut state = new_state();
+	state.remove(&storage_value_key("System", "LastRuntimeUpgrade"));
+	state.prune_state_diff();
+	state
+}
+
+fn shard() -> ShardIdentifier {
+	ShardIdentifier::default()
+}
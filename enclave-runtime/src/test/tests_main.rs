@@ -15,7 +15,7 @@
 */
 
 #[cfg(feature = "evm")]
-use crate::test::evm_pallet_tests;
+use crate::test::{evm_pallet_tests, evm_precompiles_tests, evm_state_access_tests};
 
 use crate::{
 	rpc,
@@ -27,7 +27,8 @@ use crate::{
 			enclave_call_signer, test_setup, TestStf, TestStfExecutor, TestTopPoolAuthor,
 		},
 		mocks::types::TestStateKeyRepo,
-		sidechain_aura_tests, sidechain_event_tests, state_getter_tests, top_pool_tests,
+		sidechain_aura_tests, sidechain_event_tests, state_getter_tests, state_migration_tests,
+		top_pool_tests,
 	},
 	tls_ra,
 };
@@ -81,6 +82,13 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		stf_sgx_tests::enclave_account_initialization_works,
 		stf_sgx_tests::shield_funds_increments_signer_account_nonce,
 		stf_sgx_tests::test_root_account_exists_after_initialization,
+		stf_sgx_tests::executed_call_stores_receipt_for_sender,
+		stf_sgx_tests::failed_call_stores_failure_receipt,
+		stf_sgx_tests::receipts_are_paged_newest_first,
+		stf_sgx_tests::signed_account_events_subscription_is_verified,
+		stf_sgx_tests::account_events_subscription_is_accepted_once_until_it_expires,
+		stf_sgx_tests::transfer_events_concern_sender_and_receiver_only,
+		stf_sgx_tests::fee_payment_events_concern_the_payer,
 		itp_stf_state_handler::test::sgx_tests::test_write_and_load_state_works,
 		itp_stf_state_handler::test::sgx_tests::test_sgx_state_decode_encode_works,
 		itp_stf_state_handler::test::sgx_tests::test_encrypt_decrypt_state_type_works,
@@ -137,6 +145,9 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		enclave_signer_tests::derive_key_is_deterministic,
 		enclave_signer_tests::nonce_is_computed_correctly,
 		state_getter_tests::state_getter_works,
+		state_migration_tests::new_state_does_not_need_migration,
+		state_migration_tests::state_without_runtime_version_is_migrated,
+		state_migration_tests::migration_dry_run_over_stored_snapshots_leaves_them_untouched,
 		// sidechain integration tests
 		sidechain_aura_tests::produce_sidechain_block_and_import_it,
 		sidechain_event_tests::ensure_events_get_reset_upon_block_proposal,
@@ -152,10 +163,28 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		tls_ra::seal_handler::test::seal_state_works,
 		tls_ra::seal_handler::test::seal_state_fails_for_invalid_state,
 		tls_ra::seal_handler::test::unseal_seal_state_works,
+		tls_ra::seal_handler::test::seal_state_fails_for_mismatching_last_block,
+		tls_ra::seal_handler::test::seal_state_works_for_confirmed_last_block,
+		tls_ra::seal_handler::test::seal_state_works_for_successor_of_confirmed_block,
+		tls_ra::seal_handler::test::seal_state_fails_for_block_behind_confirmation,
+		tls_ra::seal_handler::test::seal_state_fails_for_fork_of_confirmed_block,
+		tls_ra::seal_handler::test::seal_state_fails_without_block_if_a_block_is_confirmed,
+		tls_ra::state_transfer::test::state_transfer_is_reassembled_from_chunks,
+		tls_ra::state_transfer::test::state_transfer_resumes_same_state,
+		tls_ra::state_transfer::test::state_transfer_restarts_for_other_state,
+		tls_ra::state_transfer::test::state_transfer_fails_for_unexpected_chunk_offset,
+		tls_ra::state_transfer::test::state_transfer_fails_for_wrong_state_hash,
 		tls_ra::tests::test_tls_ra_server_client_networking,
 		tls_ra::tests::test_state_and_key_provisioning,
+		tls_ra::tests::test_shielding_key_only_provisioning,
 		// RPC tests
 		direct_rpc_tests::get_state_request_works,
+		direct_rpc_tests::get_runtime_version_request_works,
+		direct_rpc_tests::subscribe_account_events_request_registers_subscriber,
+		direct_rpc_tests::subscribe_account_events_request_with_invalid_signature_is_rejected,
+		direct_rpc_tests::subscribe_account_events_request_for_other_enclave_is_rejected,
+		direct_rpc_tests::expired_account_events_subscription_is_rejected,
+		direct_rpc_tests::replayed_account_events_subscription_is_rejected,
 
 		// EVM tests
 		run_evm_tests,
@@ -192,6 +221,23 @@ fn run_evm_tests() {
 	evm_pallet_tests::test_evm_counter();
 	evm_pallet_tests::test_evm_create();
 	evm_pallet_tests::test_evm_create2();
+	evm_pallet_tests::test_evm_fees_go_to_fee_recipient();
+	evm_pallet_tests::test_evm_base_fee_rises_after_full_block();
+	evm_pallet_tests::test_evm_receipts_are_pruned_with_the_receipt_log();
+	evm_state_access_tests::test_evm_state_is_only_readable_by_owner_by_default();
+	evm_state_access_tests::test_evm_state_is_readable_by_readers_on_allowlist();
+	evm_state_access_tests::test_evm_state_is_readable_by_anyone_if_public();
+	evm_state_access_tests::test_evm_calls_are_only_simulated_for_readers();
+	evm_precompiles_tests::test_enclave_precompiles_are_disabled_by_default();
+	evm_precompiles_tests::test_parentchain_precompile_returns_latest_block();
+	evm_precompiles_tests::test_exchange_rates_precompile_returns_rate_set_by_enclave();
+	evm_precompiles_tests::test_randomness_precompile_returns_fresh_randomness();
+	evm_precompiles_tests::test_private_data_is_only_revealed_to_recipient();
+	evm_precompiles_tests::test_oversized_private_data_is_not_stored();
+	crate::rpc::eth_api::tests::quantities_are_hex_encoded_without_leading_zeros();
+	crate::rpc::eth_api::tests::call_request_is_parsed_from_call_object();
+	crate::rpc::eth_api::tests::invalid_address_is_rejected();
+	crate::rpc::eth_api::tests::no_shard_is_served_without_a_handled_shard();
 }
 #[cfg(not(feature = "evm"))]
 fn run_evm_tests() {}
@@ -425,8 +471,8 @@ fn test_create_state_diff() {
 		get_from_state_diff(state_diff, &account_key_hash::<AccountId>(&receiver.into()));
 
 	// state diff should consist of the following updates:
-	// (last_hash, sidechain block_number, sender_funds, receiver_funds, fee_recipient account [no clear, after polkadot_v0.9.26 update], events)
-	assert_eq!(state_diff.len(), 7);
+	// (last_hash, sidechain block_number, sender_funds, receiver_funds, fee_recipient account [no clear, after polkadot_v0.9.26 update], events, sender receipts)
+	assert_eq!(state_diff.len(), 8);
 	assert_eq!(receiver_acc_info.data.free, TX_AMOUNT);
 	assert_eq!(
 		sender_acc_info.data.free,
@@ -136,6 +136,7 @@ pub fn submit_shielding_call_to_top_pool() {
 		_,
 		_,
 		_,
+		_,
 		integritee::ExtrinsicFilter,
 		TestEventCreator,
 		integritee::ParentchainEventHandler,
@@ -146,6 +147,7 @@ pub fn submit_shielding_call_to_top_pool() {
 		enclave_signer,
 		top_pool_author.clone(),
 		node_meta_data_repository,
+		Arc::new(MetricsOCallMock::default()),
 		ParentchainId::Integritee,
 	);
 
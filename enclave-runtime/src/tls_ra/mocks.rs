@@ -17,7 +17,8 @@
 
 use super::seal_handler::{SealStateAndKeys, UnsealStateAndKeys};
 use crate::error::Result as EnclaveResult;
-use itp_types::ShardIdentifier;
+use itp_types::{parentchain::SidechainBlockConfirmation, ShardIdentifier};
+use its_sidechain::primitives::types::header::SidechainHeader;
 use std::{
 	sync::{Arc, SgxRwLock as RwLock},
 	vec::Vec,
@@ -53,7 +54,13 @@ impl SealStateAndKeys for SealHandlerMock {
 		Ok(())
 	}
 
-	fn seal_state(&self, bytes: &[u8], _shard: &ShardIdentifier) -> EnclaveResult<()> {
+	fn seal_state(
+		&self,
+		bytes: &[u8],
+		_shard: &ShardIdentifier,
+		_last_block_header: Option<&SidechainHeader>,
+		_latest_confirmation: Option<&SidechainBlockConfirmation>,
+	) -> EnclaveResult<()> {
 		*self.state.write().unwrap() = bytes.to_vec();
 		Ok(())
 	}
@@ -81,6 +88,13 @@ impl UnsealStateAndKeys for SealHandlerMock {
 		Ok(self.state.read().unwrap().clone())
 	}
 
+	fn unseal_last_block_header(
+		&self,
+		_shard: &ShardIdentifier,
+	) -> EnclaveResult<Option<SidechainHeader>> {
+		Ok(None)
+	}
+
 	fn unseal_light_client_state(&self) -> EnclaveResult<Vec<u8>> {
 		Ok(self.light_client_state.read().unwrap().clone())
 	}
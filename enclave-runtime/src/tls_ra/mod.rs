@@ -19,10 +19,13 @@
 //! including the remote attestation and tls / tcp connection part.
 
 use codec::{Decode, Encode, MaxEncodedLen};
-use itp_types::{AccountId, ShardIdentifier};
+use itp_types::{AccountId, ShardIdentifier, H256};
+use its_sidechain::primitives::types::header::SidechainHeader;
+use std::vec::Vec;
 
 mod authentication;
 pub mod seal_handler;
+pub mod state_transfer;
 mod tls_ra_client;
 mod tls_ra_server;
 
@@ -51,8 +54,9 @@ impl TcpHeader {
 pub enum Opcode {
 	ShieldingKey,
 	StateKey,
-	State,
+	StateMetadata,
 	LightClient,
+	StateChunk,
 }
 
 impl From<u8> for Opcode {
@@ -60,8 +64,9 @@ impl From<u8> for Opcode {
 		match item {
 			0 => Opcode::ShieldingKey,
 			1 => Opcode::StateKey,
-			2 => Opcode::State,
+			2 => Opcode::StateMetadata,
 			3 => Opcode::LightClient,
+			4 => Opcode::StateChunk,
 			_ => unimplemented!("Unsupported/unknown Opcode for MU-RA exchange"),
 		}
 	}
@@ -73,9 +78,39 @@ impl Opcode {
 	}
 }
 
+/// Maximum size of a single state chunk sent by the provisioning server.
+pub const STATE_CHUNK_SIZE: usize = 1024 * 1024;
+
 /// The data structure to be sent by the client to request provisioning
 #[derive(Clone, Debug, Eq, PartialEq, Decode, Encode, MaxEncodedLen)]
 pub struct ClientProvisioningRequest {
 	pub shard: ShardIdentifier,
 	pub account: AccountId,
+	/// Hash of a state, which has been partially received in a previous attempt.
+	pub resume_state_hash: H256,
+	/// Number of bytes of the state with `resume_state_hash` the client already has.
+	/// Zero requests the whole state.
+	pub resume_offset: u64,
+	/// Only request the shielding key, e.g. to follow a key rotation of the server.
+	pub shielding_key_only: bool,
+}
+
+/// Sent by the server before the state chunks.
+#[derive(Clone, Debug, Eq, PartialEq, Decode, Encode)]
+pub struct StateMetadata {
+	/// `blake2_256` hash of the encoded state.
+	pub state_hash: H256,
+	/// Size of the encoded state in bytes.
+	pub state_size: u64,
+	/// Header of the last sidechain block, which has been applied to the state.
+	pub last_block_header: Option<SidechainHeader>,
+	/// Offset of the first chunk the server is going to send.
+	pub offset: u64,
+}
+
+/// Part of the encoded state, starting at `offset`.
+#[derive(Clone, Debug, Eq, PartialEq, Decode, Encode)]
+pub struct StateChunk {
+	pub offset: u64,
+	pub data: Vec<u8>,
 }
@@ -22,13 +22,21 @@ use crate::error::{Error as EnclaveError, Result as EnclaveResult};
 use codec::{Decode, Encode};
 use ita_stf::{State as StfState, StateType as StfStateType};
 use itc_parentchain::light_client::LightClientSealing;
+use itp_settings::worker::BLOCK_NUMBER_FINALIZATION_DIFF;
 use itp_sgx_crypto::{
 	key_repository::{AccessKey, MutateKey},
 	Aes,
 };
 use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_state_handler::handle_state::HandleState;
-use itp_types::ShardIdentifier;
+use itp_types::{parentchain::SidechainBlockConfirmation, ShardIdentifier};
+use its_sidechain::{
+	primitives::{
+		traits::{Block as BlockTrait, Header as HeaderTrait},
+		types::{block::Block as SidechainBlock, header::SidechainHeader},
+	},
+	state::{LastBlockExt, SidechainSystemExt},
+};
 use log::*;
 use sgx_crypto_helper::rsa3072::Rsa3072KeyPair;
 use std::{sync::Arc, vec::Vec};
@@ -58,7 +66,15 @@ impl<ShieldingKeyRepository, StateKeyRepository, StateHandler, LightClientSeal>
 pub trait SealStateAndKeys {
 	fn seal_shielding_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
 	fn seal_state_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
-	fn seal_state(&self, bytes: &[u8], shard: &ShardIdentifier) -> EnclaveResult<()>;
+	/// Seals the state, if it results from the sidechain block with `last_block_header`
+	/// and that block is not behind the `latest_confirmation` of the shard on the parentchain.
+	fn seal_state(
+		&self,
+		bytes: &[u8],
+		shard: &ShardIdentifier,
+		last_block_header: Option<&SidechainHeader>,
+		latest_confirmation: Option<&SidechainBlockConfirmation>,
+	) -> EnclaveResult<()>;
 	fn seal_new_empty_state(&self, shard: &ShardIdentifier) -> EnclaveResult<()>;
 	fn seal_light_client_state(&self, bytes: &[u8]) -> EnclaveResult<()>;
 }
@@ -67,6 +83,11 @@ pub trait UnsealStateAndKeys {
 	fn unseal_shielding_key(&self) -> EnclaveResult<Vec<u8>>;
 	fn unseal_state_key(&self) -> EnclaveResult<Vec<u8>>;
 	fn unseal_state(&self, shard: &ShardIdentifier) -> EnclaveResult<Vec<u8>>;
+	/// Header of the last sidechain block, which has been applied to the state.
+	fn unseal_last_block_header(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<Option<SidechainHeader>>;
 	fn unseal_light_client_state(&self) -> EnclaveResult<Vec<u8>>;
 }
 
@@ -96,9 +117,17 @@ where
 		Ok(())
 	}
 
-	fn seal_state(&self, mut bytes: &[u8], shard: &ShardIdentifier) -> EnclaveResult<()> {
+	fn seal_state(
+		&self,
+		mut bytes: &[u8],
+		shard: &ShardIdentifier,
+		last_block_header: Option<&SidechainHeader>,
+		latest_confirmation: Option<&SidechainBlockConfirmation>,
+	) -> EnclaveResult<()> {
 		let state = StfStateType::decode(&mut bytes)?;
 		let state_with_empty_diff = StfState::new(state);
+		ensure_state_results_from_block(&state_with_empty_diff, shard, last_block_header)?;
+		ensure_block_is_not_behind_confirmation(last_block_header, latest_confirmation)?;
 
 		self.state_handler.reset(state_with_empty_diff, shard)?;
 		info!("Successfully updated shard {:?} with provisioned state", shard);
@@ -154,11 +183,107 @@ where
 		Ok(self.state_handler.execute_on_current(shard, |state, _| state.state.encode())?)
 	}
 
+	fn unseal_last_block_header(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<Option<SidechainHeader>> {
+		Ok(self.state_handler.execute_on_current(shard, |state, _| {
+			LastBlockExt::<SidechainBlock>::get_last_block(state).map(|block| *block.header())
+		})?)
+	}
+
 	fn unseal_light_client_state(&self) -> EnclaveResult<Vec<u8>> {
 		Ok(self.light_client_seal.unseal()?.encode())
 	}
 }
 
+/// Ensures that `state` is the state of `shard` after applying the sidechain block
+/// with `last_block_header`.
+fn ensure_state_results_from_block(
+	state: &StfState,
+	shard: &ShardIdentifier,
+	last_block_header: Option<&SidechainHeader>,
+) -> EnclaveResult<()> {
+	if let Some(header) = last_block_header {
+		if header.shard_id != *shard {
+			return Err(EnclaveError::Other(
+				format!("Latest sidechain block belongs to shard {:?}", header.shard_id).into(),
+			))
+		}
+	}
+
+	let last_block_hash_of_state = state.get_last_block_hash();
+	let expected_last_block_hash = last_block_header.map(|h| h.hash());
+	if last_block_hash_of_state != expected_last_block_hash {
+		return Err(EnclaveError::Other(
+			format!(
+				"Last sidechain block of the state {:?} does not match the latest block header {:?}",
+				last_block_hash_of_state, expected_last_block_hash
+			)
+			.into(),
+		))
+	}
+	Ok(())
+}
+
+/// Ensures that the sidechain block with `last_block_header` is either the block confirmed on the
+/// parentchain or one of its successors.
+///
+/// The header is announced by the provisioning server, whereas the confirmation is read by the
+/// client through its own light client. Hence, this prevents a server from provisioning an
+/// outdated state. We cannot verify the full ancestry of blocks that have not been confirmed yet,
+/// but their finalization target and, for the direct successor, the parent hash must match.
+fn ensure_block_is_not_behind_confirmation(
+	last_block_header: Option<&SidechainHeader>,
+	latest_confirmation: Option<&SidechainBlockConfirmation>,
+) -> EnclaveResult<()> {
+	let confirmation = match latest_confirmation {
+		Some(c) => c,
+		// Nothing has been confirmed for this shard yet.
+		None => return Ok(()),
+	};
+	let header = last_block_header.ok_or_else(|| {
+		EnclaveError::Other(
+			format!(
+				"Provisioned state has no sidechain block, but block {:?} has been confirmed",
+				confirmation.block_header_hash
+			)
+			.into(),
+		)
+	})?;
+
+	// A confirmation is sent for the block that reached its finalization target, and
+	// announces the next target, see `BlockImportConfirmationHandler`.
+	let confirmed_block_number =
+		confirmation.block_number.saturating_sub(BLOCK_NUMBER_FINALIZATION_DIFF);
+	let block_number = header.block_number();
+
+	let matches_confirmation = if block_number == confirmed_block_number {
+		header.hash() == confirmation.block_header_hash
+	} else if block_number > confirmed_block_number {
+		header.next_finalization_block_number() >= confirmation.block_number
+			&& (block_number > confirmed_block_number + 1
+				|| header.parent_hash() == confirmation.block_header_hash)
+	} else {
+		false
+	};
+
+	if !matches_confirmation {
+		return Err(EnclaveError::Other(
+			format!(
+				"Last sidechain block {} ({:?}) of the provisioned state does not build on the \
+				 confirmed block {} ({:?})",
+				block_number,
+				header.hash(),
+				confirmed_block_number,
+				confirmation.block_header_hash
+			)
+			.into(),
+		))
+	}
+	Ok(())
+}
+
 #[cfg(feature = "test")]
 pub mod test {
 	use super::*;
@@ -235,7 +360,7 @@ pub mod test {
 		let shard = ShardIdentifier::default();
 		let _init_hash = seal_handler.state_handler.initialize_shard(shard).unwrap();
 
-		let result = seal_handler.seal_state(&state.encode(), &shard);
+		let result = seal_handler.seal_state(&state.encode(), &shard, None, None);
 
 		assert!(result.is_ok());
 	}
@@ -244,7 +369,7 @@ pub mod test {
 		let seal_handler = SealHandlerMock::default();
 		let shard = ShardIdentifier::default();
 
-		let result = seal_handler.seal_state(&[1, 0, 3], &shard);
+		let result = seal_handler.seal_state(&[1, 0, 3], &shard, None, None);
 
 		assert!(result.is_err());
 	}
@@ -260,9 +385,135 @@ pub mod test {
 		seal_handler.state_handler.write_after_mutation(state, lock, &shard).unwrap();
 
 		let state_in_bytes = seal_handler.unseal_state(&shard).unwrap();
+		let last_block_header = seal_handler.unseal_last_block_header(&shard).unwrap();
 
-		let result = seal_handler.seal_state(&state_in_bytes, &shard);
+		let result =
+			seal_handler.seal_state(&state_in_bytes, &shard, last_block_header.as_ref(), None);
 
 		assert!(result.is_ok());
 	}
+
+	pub fn seal_state_fails_for_mismatching_last_block() {
+		let seal_handler = SealHandlerMock::default();
+		let state = <HandleStateMock as HandleState>::StateT::default();
+		let shard = ShardIdentifier::default();
+		let _init_hash = seal_handler.state_handler.initialize_shard(shard).unwrap();
+		let header = SidechainHeader { shard_id: shard, block_number: 3, ..Default::default() };
+
+		let result = seal_handler.seal_state(&state.encode(), &shard, Some(&header), None);
+
+		assert!(result.is_err());
+	}
+
+	pub fn seal_state_works_for_confirmed_last_block() {
+		let (seal_handler, shard) = seal_handler_with_initialized_shard();
+		let header = SidechainHeader { shard_id: shard, block_number: 5, ..Default::default() };
+		let confirmation = confirmation_of(&header);
+
+		let result = seal_handler.seal_state(
+			&state_after_block(&header),
+			&shard,
+			Some(&header),
+			Some(&confirmation),
+		);
+
+		assert!(result.is_ok());
+	}
+
+	pub fn seal_state_works_for_successor_of_confirmed_block() {
+		let (seal_handler, shard) = seal_handler_with_initialized_shard();
+		let confirmed_header =
+			SidechainHeader { shard_id: shard, block_number: 5, ..Default::default() };
+		let confirmation = confirmation_of(&confirmed_header);
+		let header = SidechainHeader {
+			shard_id: shard,
+			block_number: 6,
+			parent_hash: confirmed_header.hash(),
+			next_finalization_block_number: confirmation.block_number,
+			..Default::default()
+		};
+
+		let result = seal_handler.seal_state(
+			&state_after_block(&header),
+			&shard,
+			Some(&header),
+			Some(&confirmation),
+		);
+
+		assert!(result.is_ok());
+	}
+
+	pub fn seal_state_fails_for_block_behind_confirmation() {
+		let (seal_handler, shard) = seal_handler_with_initialized_shard();
+		let header = SidechainHeader { shard_id: shard, block_number: 3, ..Default::default() };
+		let confirmed_header =
+			SidechainHeader { shard_id: shard, block_number: 5, ..Default::default() };
+
+		let result = seal_handler.seal_state(
+			&state_after_block(&header),
+			&shard,
+			Some(&header),
+			Some(&confirmation_of(&confirmed_header)),
+		);
+
+		assert!(result.is_err());
+	}
+
+	pub fn seal_state_fails_for_fork_of_confirmed_block() {
+		let (seal_handler, shard) = seal_handler_with_initialized_shard();
+		let confirmed_header =
+			SidechainHeader { shard_id: shard, block_number: 5, ..Default::default() };
+		let confirmation = confirmation_of(&confirmed_header);
+		let header = SidechainHeader {
+			shard_id: shard,
+			block_number: 6,
+			next_finalization_block_number: confirmation.block_number,
+			..Default::default()
+		};
+
+		let result = seal_handler.seal_state(
+			&state_after_block(&header),
+			&shard,
+			Some(&header),
+			Some(&confirmation),
+		);
+
+		assert!(result.is_err());
+	}
+
+	pub fn seal_state_fails_without_block_if_a_block_is_confirmed() {
+		let (seal_handler, shard) = seal_handler_with_initialized_shard();
+		let state = <HandleStateMock as HandleState>::StateT::default();
+		let confirmed_header =
+			SidechainHeader { shard_id: shard, block_number: 5, ..Default::default() };
+
+		let result = seal_handler.seal_state(
+			&state.encode(),
+			&shard,
+			None,
+			Some(&confirmation_of(&confirmed_header)),
+		);
+
+		assert!(result.is_err());
+	}
+
+	fn seal_handler_with_initialized_shard() -> (SealHandlerMock, ShardIdentifier) {
+		let seal_handler = SealHandlerMock::default();
+		let shard = ShardIdentifier::default();
+		seal_handler.state_handler.initialize_shard(shard).unwrap();
+		(seal_handler, shard)
+	}
+
+	fn state_after_block(header: &SidechainHeader) -> Vec<u8> {
+		let mut state = <HandleStateMock as HandleState>::StateT::default();
+		state.set_last_block_hash(&header.hash());
+		state.state.encode()
+	}
+
+	fn confirmation_of(header: &SidechainHeader) -> SidechainBlockConfirmation {
+		SidechainBlockConfirmation {
+			block_number: header.block_number + BLOCK_NUMBER_FINALIZATION_DIFF,
+			block_header_hash: header.hash(),
+		}
+	}
 }
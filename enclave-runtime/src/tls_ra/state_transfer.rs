@@ -0,0 +1,202 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Reassembly of the state, which the provisioning server sends in chunks.
+//!
+//! A partially received state is kept in enclave memory. If a provisioning attempt fails,
+//! the next attempt (possibly with another peer of the shard) resumes the transfer, as long
+//! as the peer serves the same state.
+
+use super::{StateChunk, StateMetadata};
+use crate::error::{Error as EnclaveError, Result as EnclaveResult};
+use itp_types::{ShardIdentifier, H256};
+use log::*;
+use sp_core::blake2_256;
+use std::{collections::HashMap, sync::SgxMutex, vec::Vec};
+
+lazy_static! {
+	static ref PARTIAL_STATE_TRANSFERS: SgxMutex<HashMap<ShardIdentifier, PartialStateTransfer>> =
+		Default::default();
+}
+
+/// State of the shard, which has been received up to `data.len()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PartialStateTransfer {
+	pub state_hash: H256,
+	pub data: Vec<u8>,
+}
+
+impl PartialStateTransfer {
+	/// Starts receiving the state described by `metadata`, continuing with the already received
+	/// bytes if the server resumes the same state.
+	pub fn start(previous: Option<Self>, metadata: &StateMetadata) -> EnclaveResult<Self> {
+		let mut transfer = match previous {
+			Some(p) if p.state_hash == metadata.state_hash => p,
+			_ => PartialStateTransfer { state_hash: metadata.state_hash, data: Vec::new() },
+		};
+
+		if metadata.offset == 0 {
+			transfer.data.clear();
+		} else if metadata.offset != transfer.data.len() as u64 {
+			return Err(EnclaveError::Other(
+				format!(
+					"Server resumes the state transfer at {}, but we have {} bytes",
+					metadata.offset,
+					transfer.data.len()
+				)
+				.into(),
+			))
+		}
+		Ok(transfer)
+	}
+
+	pub fn append(&mut self, chunk: StateChunk, metadata: &StateMetadata) -> EnclaveResult<()> {
+		if chunk.offset != self.data.len() as u64 {
+			return Err(EnclaveError::Other(
+				format!(
+					"Received state chunk at offset {}, expected {}",
+					chunk.offset,
+					self.data.len()
+				)
+				.into(),
+			))
+		}
+		if self.data.len() as u64 + chunk.data.len() as u64 > metadata.state_size {
+			return Err(EnclaveError::Other("Received more state than announced".into()))
+		}
+		self.data.extend(chunk.data);
+		Ok(())
+	}
+
+	pub fn is_complete(&self, metadata: &StateMetadata) -> bool {
+		self.data.len() as u64 == metadata.state_size
+	}
+
+	/// Returns the encoded state, if it matches the announced hash.
+	///
+	/// This only detects a corrupted or mixed-up transfer, since the hash is announced by the
+	/// server. Whether the state is actually the current state of the shard is checked against
+	/// the parentchain, when the state is sealed.
+	pub fn into_received_state(self) -> EnclaveResult<Vec<u8>> {
+		let received_hash: H256 = blake2_256(&self.data).into();
+		if received_hash != self.state_hash {
+			return Err(EnclaveError::Other(
+				format!(
+					"Hash of the received state {:?} does not match the announced hash {:?}",
+					received_hash, self.state_hash
+				)
+				.into(),
+			))
+		}
+		Ok(self.data)
+	}
+}
+
+/// Takes the partially received state of `shard` from a previous provisioning attempt.
+pub(crate) fn take_partial_state_transfer(shard: &ShardIdentifier) -> Option<PartialStateTransfer> {
+	PARTIAL_STATE_TRANSFERS.lock().ok()?.remove(shard)
+}
+
+/// Keeps the partially received state of `shard` for the next provisioning attempt.
+pub(crate) fn store_partial_state_transfer(shard: ShardIdentifier, transfer: PartialStateTransfer) {
+	match PARTIAL_STATE_TRANSFERS.lock() {
+		Ok(mut transfers) => {
+			info!(
+				"Keeping {} bytes of the state of shard {:?} to resume the transfer",
+				transfer.data.len(),
+				shard
+			);
+			transfers.insert(shard, transfer);
+		},
+		Err(e) => error!("Failed to keep partial state transfer: {:?}", e),
+	}
+}
+
+#[cfg(feature = "test")]
+pub mod test {
+	use super::*;
+
+	fn metadata_for(state: &[u8], offset: u64) -> StateMetadata {
+		StateMetadata {
+			state_hash: blake2_256(state).into(),
+			state_size: state.len() as u64,
+			last_block_header: None,
+			offset,
+		}
+	}
+
+	pub fn state_transfer_is_reassembled_from_chunks() {
+		let state = vec![1u8, 2, 3, 4, 5];
+		let metadata = metadata_for(&state, 0);
+
+		let mut transfer = PartialStateTransfer::start(None, &metadata).unwrap();
+		transfer
+			.append(StateChunk { offset: 0, data: state[..2].to_vec() }, &metadata)
+			.unwrap();
+		assert!(!transfer.is_complete(&metadata));
+		transfer
+			.append(StateChunk { offset: 2, data: state[2..].to_vec() }, &metadata)
+			.unwrap();
+
+		assert!(transfer.is_complete(&metadata));
+		assert_eq!(transfer.into_received_state().unwrap(), state);
+	}
+
+	pub fn state_transfer_resumes_same_state() {
+		let state = vec![1u8, 2, 3, 4, 5];
+		let previous = PartialStateTransfer {
+			state_hash: blake2_256(&state).into(),
+			data: state[..3].to_vec(),
+		};
+		let metadata = metadata_for(&state, 3);
+
+		let mut transfer = PartialStateTransfer::start(Some(previous), &metadata).unwrap();
+		transfer
+			.append(StateChunk { offset: 3, data: state[3..].to_vec() }, &metadata)
+			.unwrap();
+
+		assert_eq!(transfer.into_received_state().unwrap(), state);
+	}
+
+	pub fn state_transfer_restarts_for_other_state() {
+		let previous = PartialStateTransfer { state_hash: H256::repeat_byte(1), data: vec![9, 9] };
+		let state = vec![1u8, 2, 3];
+
+		let transfer =
+			PartialStateTransfer::start(Some(previous), &metadata_for(&state, 0)).unwrap();
+
+		assert!(transfer.data.is_empty());
+	}
+
+	pub fn state_transfer_fails_for_unexpected_chunk_offset() {
+		let state = vec![1u8, 2, 3];
+		let metadata = metadata_for(&state, 0);
+		let mut transfer = PartialStateTransfer::start(None, &metadata).unwrap();
+
+		assert!(transfer.append(StateChunk { offset: 1, data: vec![2, 3] }, &metadata).is_err());
+	}
+
+	pub fn state_transfer_fails_for_wrong_state_hash() {
+		let state = vec![1u8, 2, 3];
+		let mut metadata = metadata_for(&state, 0);
+		metadata.state_hash = H256::repeat_byte(1);
+		let mut transfer = PartialStateTransfer::start(None, &metadata).unwrap();
+		transfer.append(StateChunk { offset: 0, data: state }, &metadata).unwrap();
+
+		assert!(transfer.into_received_state().is_err());
+	}
+}
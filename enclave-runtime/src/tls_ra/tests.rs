@@ -119,6 +119,8 @@ pub fn test_tls_ra_server_client_networking() {
 		SKIP_RA,
 		client_seal_handler,
 		client_account,
+		false,
+		None,
 	);
 
 	// Ensure server thread has finished.
@@ -139,6 +141,67 @@ pub fn test_tls_ra_server_client_networking() {
 	}
 }
 
+pub fn test_shielding_key_only_provisioning() {
+	let shard = ShardIdentifier::default();
+	let client_account = AccountId::from([42; 32]);
+	let shielding_key_encoded = vec![1, 2, 3];
+
+	let server_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(shielding_key_encoded.clone())),
+		Arc::new(RwLock::new(vec![5, 2, 3, 7])),
+		Arc::new(RwLock::new(vec![1u8; 100])),
+		Arc::new(RwLock::new(vec![1u8; 100])),
+	);
+	let initial_client_state = vec![0, 0, 1];
+	let initial_client_state_key = vec![0, 0, 2];
+	let initial_client_light_client_state = vec![0, 0, 3];
+	let client_shielding_key = Arc::new(RwLock::new(Vec::new()));
+	let client_state_key = Arc::new(RwLock::new(initial_client_state_key.clone()));
+	let client_state = Arc::new(RwLock::new(initial_client_state.clone()));
+	let client_light_client_state =
+		Arc::new(RwLock::new(initial_client_light_client_state.clone()));
+
+	let client_seal_handler = SealHandlerMock::new(
+		client_shielding_key.clone(),
+		client_state_key.clone(),
+		client_state.clone(),
+		client_light_client_state.clone(),
+	);
+
+	let port: u16 = 3151;
+
+	// Start server.
+	let server_thread_handle = thread::spawn(move || {
+		run_state_provisioning_server(server_seal_handler, port);
+	});
+	thread::sleep(Duration::from_secs(1));
+
+	// Start client.
+	let socket = TcpStream::connect(server_addr(port)).unwrap();
+	let sgx_target_info: sgx_target_info_t = sgx_target_info_t::default();
+	let result = request_state_provisioning_internal(
+		socket.as_raw_fd(),
+		SIGN_TYPE,
+		Some(&sgx_target_info),
+		Some(&QUOTE_SIZE),
+		shard,
+		SKIP_RA,
+		client_seal_handler,
+		client_account,
+		true,
+		None,
+	);
+
+	// Ensure server thread has finished.
+	server_thread_handle.join().unwrap();
+
+	assert!(result.is_ok());
+	assert_eq!(*client_shielding_key.read().unwrap(), shielding_key_encoded);
+	assert_eq!(*client_state_key.read().unwrap(), initial_client_state_key);
+	assert_eq!(*client_state.read().unwrap(), initial_client_state);
+	assert_eq!(*client_light_client_state.read().unwrap(), initial_client_light_client_state);
+}
+
 // Test state and key provisioning with 'real' data structures.
 pub fn test_state_and_key_provisioning() {
 	let client_account = AccountId::from([42; 32]);
@@ -172,6 +235,8 @@ pub fn test_state_and_key_provisioning() {
 		SKIP_RA,
 		client_seal_handler,
 		client_account,
+		false,
+		None,
 	);
 
 	// Ensure server thread has finished.
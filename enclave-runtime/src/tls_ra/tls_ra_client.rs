@@ -17,7 +17,13 @@
 
 //! Implementation of the client part of the state provisioning.
 
-use super::{authentication::ServerAuth, Opcode, TcpHeader};
+use super::{
+	authentication::ServerAuth,
+	state_transfer::{
+		store_partial_state_transfer, take_partial_state_transfer, PartialStateTransfer,
+	},
+	Opcode, StateChunk, StateMetadata, TcpHeader,
+};
 use crate::{
 	attestation::create_ra_report_and_signature,
 	error::{Error as EnclaveError, Result as EnclaveResult},
@@ -28,16 +34,22 @@ use crate::{
 	ocall::OcallApi,
 	shard_config::init_shard_config,
 	tls_ra::{seal_handler::SealStateAndKeys, ClientProvisioningRequest},
+	utils::get_validator_accessor_from_integritee_solo_or_parachain,
 	GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
 };
-use codec::Encode;
+use codec::{Decode, Encode};
+use itc_parentchain::light_client::{concurrent_access::ValidatorAccess, LightClientState};
 
 use itp_attestation_handler::{RemoteAttestationType, DEV_HOSTNAME};
 use itp_component_container::ComponentGetter;
 
-use itp_ocall_api::EnclaveAttestationOCallApi;
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
+use itp_pallet_storage::{SidechainPalletStorage, SidechainPalletStorageKeys};
 use itp_sgx_crypto::key_repository::AccessPubkey;
-use itp_types::{AccountId, ShardIdentifier};
+use itp_types::{
+	parentchain::{ParentchainId, SidechainBlockConfirmation},
+	AccountId, ShardIdentifier,
+};
 
 use log::*;
 use rustls::{ClientConfig, ClientSession, Stream};
@@ -60,6 +72,15 @@ where
 	tls_stream: Stream<'a, ClientSession, TcpStream>,
 	seal_handler: StateAndKeySealer,
 	shard: ShardIdentifier,
+	/// Only request the shielding key, not the whole provisioning.
+	shielding_key_only: bool,
+	/// State received in a previous attempt, which we ask the server to resume.
+	resumable_state: Option<PartialStateTransfer>,
+	/// State that is currently being received.
+	state_transfer: Option<(StateMetadata, PartialStateTransfer)>,
+	/// Latest sidechain block of the shard confirmed on the parentchain, as seen by our own
+	/// light client. The received state must not be behind it.
+	latest_confirmation: Option<SidechainBlockConfirmation>,
 }
 
 impl<'a, StateAndKeySealer> TlsClient<'a, StateAndKeySealer>
@@ -70,14 +91,25 @@ where
 		tls_stream: Stream<'a, ClientSession, TcpStream>,
 		seal_handler: StateAndKeySealer,
 		shard: ShardIdentifier,
+		shielding_key_only: bool,
+		latest_confirmation: Option<SidechainBlockConfirmation>,
 	) -> TlsClient<StateAndKeySealer> {
-		TlsClient { tls_stream, seal_handler, shard }
+		TlsClient {
+			tls_stream,
+			seal_handler,
+			shard,
+			shielding_key_only,
+			resumable_state: None,
+			state_transfer: None,
+			latest_confirmation,
+		}
 	}
 
 	/// Read all data sent by the server of the specific shard.
 	///
-	/// We trust here that the server sends us the correct data, as
-	/// we do not have any way to test it.
+	/// We trust here that the server sends us the correct keys, as we do not have any way
+	/// to test them. The state however must match the announced hash and the latest
+	/// sidechain block header, which must not be behind the block confirmed on the parentchain.
 	fn obtain_provisioning_for_shard(&mut self, account: AccountId) -> EnclaveResult<()> {
 		debug!(
 			"obtain_provisioning_for_shard called, about to call self.send_provisioning_request()."
@@ -90,31 +122,56 @@ where
 	/// Send the shard of the state we want to receive to the provisioning server.
 	fn send_provisioning_request(&mut self, account: AccountId) -> EnclaveResult<()> {
 		debug!("self.send_provisioning_request() called.");
-		self.tls_stream
-			.write_all(&ClientProvisioningRequest { shard: self.shard, account }.encode())?;
+		if !self.shielding_key_only {
+			self.resumable_state = take_partial_state_transfer(&self.shard);
+		}
+		let (resume_state_hash, resume_offset) = self
+			.resumable_state
+			.as_ref()
+			.map(|t| (t.state_hash, t.data.len() as u64))
+			.unwrap_or_default();
+		if resume_offset > 0 {
+			info!(
+				"Asking to resume the transfer of state {:?} at {}",
+				resume_state_hash, resume_offset
+			);
+		}
+		self.tls_stream.write_all(
+			&ClientProvisioningRequest {
+				shard: self.shard,
+				account,
+				resume_state_hash,
+				resume_offset,
+				shielding_key_only: self.shielding_key_only,
+			}
+			.encode(),
+		)?;
 		debug!("write_all succeeded.");
 		Ok(())
 	}
 
 	/// Read and seal all relevant data sent by the server.
+	///
+	/// An incompletely received state is kept, such that the next attempt can resume it.
 	fn read_and_seal_all(&mut self) -> EnclaveResult<()> {
-		let mut received_payloads: Vec<Opcode> = Vec::new();
-
-		loop {
-			let maybe_opcode = self.read_and_seal()?;
-			match maybe_opcode {
-				None => break,
-				Some(o) => {
-					received_payloads.push(o);
-				},
-			}
+		let result = self.read_and_seal_until_eof();
+
+		if let Some((_, transfer)) = self.state_transfer.take() {
+			store_partial_state_transfer(self.shard, transfer);
+			result?;
+			return Err(EnclaveError::Other(
+				"Connection closed before the whole state has been received".into(),
+			))
 		}
+		let received_payloads = result?;
 		info!("Successfully read and sealed all data sent by the state provisioning server.");
 
 		// In case we receive a shielding key, but no state, we need to reset our state
-		// to update the enclave account.
-		if received_payloads.contains(&Opcode::ShieldingKey)
-			&& !received_payloads.contains(&Opcode::State)
+		// to update the enclave account. Not so if we only follow a key rotation of the server,
+		// our state is up to date.
+		if !self.shielding_key_only
+			&& received_payloads.contains(&Opcode::ShieldingKey)
+			&& !received_payloads.contains(&Opcode::StateMetadata)
 		{
 			self.seal_handler.seal_new_empty_state(&self.shard)?;
 		}
@@ -122,6 +179,14 @@ where
 		Ok(())
 	}
 
+	fn read_and_seal_until_eof(&mut self) -> EnclaveResult<Vec<Opcode>> {
+		let mut received_payloads: Vec<Opcode> = Vec::new();
+		while let Some(opcode) = self.read_and_seal()? {
+			received_payloads.push(opcode);
+		}
+		Ok(received_payloads)
+	}
+
 	/// Read a server header / payload pair and directly seal the received data.
 	fn read_and_seal(&mut self) -> EnclaveResult<Option<Opcode>> {
 		let mut start_byte = [0u8; 1];
@@ -135,12 +200,48 @@ where
 		match header.opcode {
 			Opcode::ShieldingKey => self.seal_handler.seal_shielding_key(&bytes)?,
 			Opcode::StateKey => self.seal_handler.seal_state_key(&bytes)?,
-			Opcode::State => self.seal_handler.seal_state(&bytes, &self.shard)?,
+			Opcode::StateMetadata => self.start_state_transfer(&bytes)?,
+			Opcode::StateChunk => self.receive_state_chunk(&bytes)?,
 			Opcode::LightClient => self.seal_handler.seal_light_client_state(&bytes)?,
 		};
 		Ok(Some(header.opcode))
 	}
 
+	fn start_state_transfer(&mut self, mut bytes: &[u8]) -> EnclaveResult<()> {
+		let metadata = StateMetadata::decode(&mut bytes)?;
+		debug!("Receiving state: {:?}", metadata);
+		let transfer = PartialStateTransfer::start(self.resumable_state.take(), &metadata)?;
+		self.state_transfer = Some((metadata, transfer));
+		self.seal_state_if_complete()
+	}
+
+	fn receive_state_chunk(&mut self, mut bytes: &[u8]) -> EnclaveResult<()> {
+		let chunk = StateChunk::decode(&mut bytes)?;
+		let (metadata, transfer) = self.state_transfer.as_mut().ok_or_else(|| {
+			EnclaveError::Other("Received a state chunk before the state metadata".into())
+		})?;
+		transfer.append(chunk, metadata)?;
+		self.seal_state_if_complete()
+	}
+
+	fn seal_state_if_complete(&mut self) -> EnclaveResult<()> {
+		match self.state_transfer.take() {
+			Some((metadata, transfer)) if transfer.is_complete(&metadata) => {
+				let state = transfer.into_received_state()?;
+				self.seal_handler.seal_state(
+					&state,
+					&self.shard,
+					metadata.last_block_header.as_ref(),
+					self.latest_confirmation.as_ref(),
+				)
+			},
+			still_receiving => {
+				self.state_transfer = still_receiving;
+				Ok(())
+			},
+		}
+	}
+
 	/// Reads the payload header, indicating the sent payload length and type.
 	fn read_header(&mut self, start_byte: u8) -> EnclaveResult<TcpHeader> {
 		debug!("Read first byte: {:?}", start_byte);
@@ -173,9 +274,11 @@ pub unsafe extern "C" fn request_state_provisioning(
 	shard: *const u8,
 	shard_size: u32,
 	skip_ra: c_int,
+	shielding_key_only: c_int,
 ) -> sgx_status_t {
 	let _ = backtrace::enable_backtrace("enclave.signed.so", PrintFormat::Short);
 	let shard = ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+	let shielding_key_only = shielding_key_only == 1;
 
 	let state_handler = match GLOBAL_STATE_HANDLER_COMPONENT.get() {
 		Ok(s) => s,
@@ -229,6 +332,18 @@ pub unsafe extern "C" fn request_state_provisioning(
 		Err(e) => return e.into(),
 	};
 
+	let latest_confirmation = if shielding_key_only {
+		None
+	} else {
+		match latest_sidechain_block_confirmation(&shard) {
+			Ok(c) => c,
+			Err(e) => {
+				error!("Failed to fetch the latest sidechain block confirmation: {:?}", e);
+				return e.into()
+			},
+		}
+	};
+
 	if let Err(e) = request_state_provisioning_internal(
 		socket_fd,
 		sign_type,
@@ -238,11 +353,17 @@ pub unsafe extern "C" fn request_state_provisioning(
 		skip_ra,
 		seal_handler,
 		client_account,
+		shielding_key_only,
+		latest_confirmation,
 	) {
 		error!("Failed to sync state due to: {:?}", e);
 		return e.into()
 	};
 
+	if shielding_key_only {
+		return sgx_status_t::SGX_SUCCESS
+	}
+
 	// fixme: this needs only be called in sidechain mode. no harm though
 	if let Err(e) = init_shard_config(shard) {
 		error!("touch shard error: {:?}", e);
@@ -251,6 +372,29 @@ pub unsafe extern "C" fn request_state_provisioning(
 	sgx_status_t::SGX_SUCCESS
 }
 
+/// Reads the latest sidechain block confirmation of `shard` from the integritee parentchain,
+/// verified against the latest finalized header of our own light client.
+///
+/// Requires the integritee parentchain light client to be initialized.
+fn latest_sidechain_block_confirmation(
+	shard: &ShardIdentifier,
+) -> EnclaveResult<Option<SidechainBlockConfirmation>> {
+	let latest_parentchain_header = get_validator_accessor_from_integritee_solo_or_parachain()?
+		.execute_on_validator(|v| v.latest_finalized_header())?;
+	let confirmation = OcallApi
+		.get_storage_verified(
+			SidechainPalletStorage::latest_sidechain_block_confirmation(*shard),
+			&latest_parentchain_header,
+			&ParentchainId::Integritee,
+		)?
+		.value;
+	debug!(
+		"Latest sidechain block confirmation of shard {:?} at parentchain block {}: {:?}",
+		shard, latest_parentchain_header.number, confirmation
+	);
+	Ok(confirmation)
+}
+
 /// Internal [`request_state_provisioning`] function to be able to use the handy `?` operator.
 // allowing clippy rant because this fn will be refactored with MU RA deprecation
 #[allow(clippy::too_many_arguments)]
@@ -263,6 +407,8 @@ pub(crate) fn request_state_provisioning_internal<StateAndKeySealer: SealStateAn
 	skip_ra: c_int,
 	seal_handler: StateAndKeySealer,
 	client_account: AccountId,
+	shielding_key_only: bool,
+	latest_confirmation: Option<SidechainBlockConfirmation>,
 ) -> EnclaveResult<()> {
 	debug!("Client config generate...");
 	let client_config = tls_client_config(
@@ -280,9 +426,15 @@ pub(crate) fn request_state_provisioning_internal<StateAndKeySealer: SealStateAn
 		rustls::Stream::new(&mut client_session, &mut tcp_stream),
 		seal_handler,
 		shard,
+		shielding_key_only,
+		latest_confirmation,
 	);
 
-	info!("Requesting keys and state from mu-ra server of fellow validateer");
+	if shielding_key_only {
+		info!("Requesting the shielding key from mu-ra server of fellow validateer");
+	} else {
+		info!("Requesting keys and state from mu-ra server of fellow validateer");
+	}
 	client.obtain_provisioning_for_shard(client_account)
 }
 
@@ -17,7 +17,10 @@
 
 //! Implementation of the server part of the state provisioning.
 
-use super::{authentication::ClientAuth, ClientProvisioningRequest, Opcode, TcpHeader};
+use super::{
+	authentication::ClientAuth, ClientProvisioningRequest, Opcode, StateChunk, StateMetadata,
+	TcpHeader, STATE_CHUNK_SIZE,
+};
 use crate::{
 	attestation::create_ra_report_and_signature,
 	error::{Error as EnclaveError, Result as EnclaveResult},
@@ -30,15 +33,16 @@ use crate::{
 	tls_ra::seal_handler::UnsealStateAndKeys,
 	GLOBAL_STATE_HANDLER_COMPONENT,
 };
-use codec::Decode;
+use codec::{Decode, Encode, MaxEncodedLen};
 use itp_attestation_handler::RemoteAttestationType;
 use itp_component_container::ComponentGetter;
 use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider};
-use itp_types::ShardIdentifier;
+use itp_types::H256;
 use log::*;
 use rustls::{ServerConfig, ServerSession, StreamOwned};
 use sgx_types::*;
+use sp_core::blake2_256;
 use std::{
 	backtrace::{self, PrintFormat},
 	io::{Read, Write},
@@ -91,7 +95,12 @@ where
 		let request = self.await_shard_request_from_client()?;
 		println!("    [Enclave] (MU-RA-Server) handle_shard_request_from_client, await_shard_request_from_client() OK");
 		println!("    [Enclave] (MU-RA-Server) handle_shard_request_from_client, write_all()");
-		self.write_provisioning_payloads(&request.shard)?;
+		self.write_provisioning_payloads(&request)?;
+
+		if request.shielding_key_only {
+			// The client is already set up, it only follows a shielding key rotation.
+			return Ok(())
+		}
 
 		info!(
 			"will make client account 0x{} a proxy of vault for shard {:?}",
@@ -109,7 +118,7 @@ where
 
 	/// Read the shard of the state the client wants to receive.
 	fn await_shard_request_from_client(&mut self) -> EnclaveResult<ClientProvisioningRequest> {
-		let mut request = [0u8; std::mem::size_of::<ClientProvisioningRequest>()];
+		let mut request = vec![0u8; ClientProvisioningRequest::max_encoded_len()];
 		println!(
 			"    [Enclave] (MU-RA-Server) await_shard_request_from_client, calling read_exact()"
 		);
@@ -119,13 +128,21 @@ where
 	}
 
 	/// Sends all relevant data to the client.
-	fn write_provisioning_payloads(&mut self, shard: &ShardIdentifier) -> EnclaveResult<()> {
+	fn write_provisioning_payloads(
+		&mut self,
+		request: &ClientProvisioningRequest,
+	) -> EnclaveResult<()> {
 		debug!("Provisioning is set to: {:?}", self.provisioning_payload);
+		if request.shielding_key_only {
+			debug!("Client only requested the shielding key");
+			self.write_shielding_key()?;
+			return Ok(())
+		}
 		match self.provisioning_payload {
 			ProvisioningPayload::Everything => {
 				self.write_shielding_key()?;
 				self.write_state_key()?;
-				self.write_state(shard)?;
+				self.write_state(request)?;
 				self.write_light_client_state()?;
 			},
 			ProvisioningPayload::ShieldingKeyAndLightClient => {
@@ -150,9 +167,31 @@ where
 		Ok(())
 	}
 
-	fn write_state(&mut self, shard: &ShardIdentifier) -> EnclaveResult<()> {
-		let state = self.seal_handler.unseal_state(shard)?;
-		self.write(Opcode::State, &state)?;
+	/// Sends the state metadata, followed by the state in chunks of [`STATE_CHUNK_SIZE`].
+	///
+	/// Resumes at the offset requested by the client, if we still have the same state.
+	fn write_state(&mut self, request: &ClientProvisioningRequest) -> EnclaveResult<()> {
+		let state = self.seal_handler.unseal_state(&request.shard)?;
+		let last_block_header = self.seal_handler.unseal_last_block_header(&request.shard)?;
+		let state_hash: H256 = blake2_256(&state).into();
+
+		let offset = if request.resume_state_hash == state_hash
+			&& request.resume_offset <= state.len() as u64
+		{
+			request.resume_offset
+		} else {
+			0
+		};
+		let metadata =
+			StateMetadata { state_hash, state_size: state.len() as u64, last_block_header, offset };
+		debug!("Sending state: {:?}", metadata);
+		self.write(Opcode::StateMetadata, &metadata.encode())?;
+
+		for (index, data) in state[offset as usize..].chunks(STATE_CHUNK_SIZE).enumerate() {
+			let chunk_offset = offset + (index * STATE_CHUNK_SIZE) as u64;
+			let chunk = StateChunk { offset: chunk_offset, data: data.to_vec() };
+			self.write(Opcode::StateChunk, &chunk.encode())?;
+		}
 		Ok(())
 	}
 
@@ -18,7 +18,8 @@
 use crate::{
 	error::{Error, Result},
 	initialization::global_components::{
-		GLOBAL_OCALL_API_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
+		EnclaveSidechainBlockImportNotifier, EnclaveStfExecutor, GLOBAL_OCALL_API_COMPONENT,
+		GLOBAL_RPC_SUBSCRIPTION_NOTIFIER_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
 		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
 		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
 	},
@@ -40,6 +41,7 @@ use crate::{
 	},
 };
 use codec::Encode;
+use core::ffi::c_int;
 use itc_parentchain::{
 	block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport,
 	light_client::{
@@ -48,16 +50,17 @@ use itc_parentchain::{
 	},
 };
 use itp_component_container::ComponentGetter;
+use itp_enclave_metrics::EnclaveMetric;
 use itp_extrinsics_factory::CreateExtrinsics;
-use itp_ocall_api::{EnclaveOnChainOCallApi, EnclaveSidechainOCallApi};
+use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveOnChainOCallApi, EnclaveSidechainOCallApi};
 use itp_pallet_storage::{SidechainPalletStorage, SidechainPalletStorageKeys};
 use itp_settings::sidechain::SLOT_DURATION;
 use itp_sgx_crypto::key_repository::AccessKey;
 use itp_stf_state_handler::query_shard_state::QueryShardState;
-use itp_time_utils::duration_now;
+use itp_time_utils::{host_time_drift_millis, trusted_duration_now};
 use itp_types::{
 	parentchain::{ParentchainCall, ParentchainId, SidechainBlockConfirmation},
-	Block, OpaqueCall, H256,
+	Block, OpaqueCall, ShardIdentifier, H256,
 };
 use its_primitives::{
 	traits::{
@@ -77,11 +80,11 @@ use sp_core::{crypto::UncheckedFrom, Pair};
 use sp_runtime::{
 	generic::SignedBlock as SignedParentchainBlock, traits::Block as BlockTrait, MultiSignature,
 };
-use std::{sync::Arc, time::Instant, vec::Vec};
+use std::{collections::HashMap, sync::Arc, time::Instant, vec::Vec};
 
 #[no_mangle]
-pub unsafe extern "C" fn execute_trusted_calls() -> sgx_status_t {
-	if let Err(e) = execute_top_pool_trusted_calls_internal() {
+pub unsafe extern "C" fn execute_trusted_calls(produce_blocks: c_int) -> sgx_status_t {
+	if let Err(e) = execute_top_pool_trusted_calls_internal(produce_blocks == 1) {
 		return e.into()
 	}
 
@@ -97,14 +100,26 @@ pub unsafe extern "C" fn execute_trusted_calls() -> sgx_status_t {
 /// *   Import all pending parentchain blocks.
 /// *   Sends sidechain `confirm_block` xt's with the produced sidechain blocks.
 /// *   Broadcast produced sidechain blocks to peer validateers.
-fn execute_top_pool_trusted_calls_internal() -> Result<()> {
+///
+/// If `produce_blocks` is not set, only the sidechain block import queue is processed.
+fn execute_top_pool_trusted_calls_internal(produce_blocks: bool) -> Result<()> {
 	let start_time = Instant::now();
 
 	// We acquire lock explicitly (variable binding), since '_' will drop the lock after the statement.
 	// See https://medium.com/codechain/rust-underscore-does-not-bind-fec6a18115a8
 	let _enclave_write_lock = EnclaveLock::write_all()?;
 
-	let slot_beginning_timestamp = duration_now();
+	let slot_beginning_timestamp = trusted_duration_now();
+	if let Err(e) = GLOBAL_OCALL_API_COMPONENT
+		.get()?
+		.update_metric(EnclaveMetric::HostTimeDrift(host_time_drift_millis()))
+	{
+		warn!("Failed to update host time drift metric: {:?}", e);
+	}
+	// The parentchain import observers only export the buffered metrics in offchain-worker mode.
+	GLOBAL_OCALL_API_COMPONENT.get()?.export_buffered_metrics();
+	let slot_beginning_timestamp =
+		slot_beginning_timestamp.map_err(|e| Error::Other(Box::new(e)))?;
 
 	let integritee_parentchain_import_dispatcher =
 		get_triggered_dispatcher_from_integritee_solo_or_parachain()?;
@@ -148,26 +163,32 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
 	let shards = state_handler.list_shards()?;
-	let shard = *shards.get(0).ok_or(Error::NoShardAssigned)?;
-	if shards.len() > 1 {
-		return Err(Error::TooManyShardsAssigned)
-	};
+	if shards.is_empty() {
+		return Err(Error::NoShardAssigned)
+	}
 
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
-	// get latest finalized sidechain block
-	let maybe_latest_sidechain_block_confirmation: Option<SidechainBlockConfirmation> = ocall_api
-		.get_storage_verified(
-			SidechainPalletStorage::latest_sidechain_block_confirmation(shard),
-			&current_integritee_parentchain_header,
-			&ParentchainId::Integritee,
-		)?
-		.value;
-	trace!(
-		"fetched latest finalized sidechain block for shard {:?}: {:?}",
-		shard,
-		maybe_latest_sidechain_block_confirmation
-	);
+	// get latest finalized sidechain block of every shard
+	let mut latest_sidechain_block_confirmations = HashMap::new();
+	for shard in shards.iter() {
+		let maybe_latest_sidechain_block_confirmation: Option<SidechainBlockConfirmation> =
+			ocall_api
+				.get_storage_verified(
+					SidechainPalletStorage::latest_sidechain_block_confirmation(*shard),
+					&current_integritee_parentchain_header,
+					&ParentchainId::Integritee,
+				)?
+				.value;
+		trace!(
+			"fetched latest finalized sidechain block for shard {:?}: {:?}",
+			shard,
+			maybe_latest_sidechain_block_confirmation
+		);
+		if let Some(confirmation) = maybe_latest_sidechain_block_confirmation {
+			latest_sidechain_block_confirmations.insert(*shard, confirmation);
+		}
+	}
 
 	// Import any sidechain blocks that are in the import queue. In case we are missing blocks,
 	// a peer sync will happen. If that happens, the slot time might already be used up just by this import.
@@ -176,7 +197,7 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 	let latest_integritee_parentchain_header = sidechain_block_import_queue_worker.process_queue(
 		&current_integritee_parentchain_header,
-		maybe_latest_sidechain_block_confirmation,
+		&latest_sidechain_block_confirmations,
 	)?;
 
 	trace!(
@@ -184,13 +205,10 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 		start_time.elapsed().as_millis()
 	);
 
-	let (_, vault_target) = get_shard_vault_internal(shard)?;
-	trace!("using StfExecutor from {:?} parentchain", vault_target);
-	let stf_executor = match vault_target {
-		ParentchainId::Integritee => get_stf_executor_from_integritee_solo_or_parachain()?,
-		ParentchainId::TargetA => get_stf_executor_from_target_a_solo_or_parachain()?,
-		ParentchainId::TargetB => get_stf_executor_from_target_b_solo_or_parachain()?,
-	};
+	if !produce_blocks {
+		debug!("Block production is paused, skipping AURA execution");
+		return Ok(())
+	}
 
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
 
@@ -198,6 +216,10 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 	let authority = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
 
+	let block_import_notifier = Arc::new(EnclaveSidechainBlockImportNotifier::new(
+		GLOBAL_RPC_SUBSCRIPTION_NOTIFIER_COMPONENT.get()?,
+	));
+
 	match yield_next_slot(
 		slot_beginning_timestamp,
 		SLOT_DURATION,
@@ -214,23 +236,52 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 			log_remaining_slot_duration(&slot, "Before AURA");
 
-			let env = ProposerFactory::<Block, _, _, _>::new(
-				top_pool_author,
-				stf_executor,
-				block_composer,
-			);
-
-			let (blocks, parentchain_calls) =
-				exec_aura_on_slot::<_, _, SignedSidechainBlock, _, _, _, _, _>(
-					slot.clone(),
-					authority,
-					ocall_api.clone(),
-					integritee_parentchain_import_dispatcher,
-					maybe_target_a_parentchain_import_dispatcher,
-					maybe_target_b_parentchain_import_dispatcher,
-					env,
-					shards,
-				)?;
+			let mut blocks = Vec::with_capacity(shards.len());
+			let mut parentchain_calls = Vec::new();
+			let mut remaining_shards = shards.len() as u32;
+			for shard in shards {
+				// Each shard gets an equal share of what is left of the slot.
+				let now = trusted_duration_now().map_err(|e| Error::Other(Box::new(e)))?;
+				let mut shard_slot = slot.clone();
+				shard_slot.ends_at = now + slot.ends_at.saturating_sub(now) / remaining_shards;
+				remaining_shards -= 1;
+
+				let stf_executor = match get_stf_executor_for_shard(shard) {
+					Ok(stf_executor) => stf_executor,
+					Err(e) => {
+						warn!("Skipping block production for shard {:?}: {:?}", shard, e);
+						continue
+					},
+				};
+
+				let env = ProposerFactory::<Block, _, _, _, _>::new(
+					top_pool_author.clone(),
+					stf_executor,
+					block_composer.clone(),
+					block_import_notifier.clone(),
+				);
+
+				// A failing shard must not keep the blocks of the other shards from being sent.
+				let (shard_blocks, shard_parentchain_calls) =
+					match exec_aura_on_slot::<_, _, SignedSidechainBlock, _, _, _, _, _>(
+						shard_slot,
+						authority.clone(),
+						ocall_api.clone(),
+						integritee_parentchain_import_dispatcher.clone(),
+						maybe_target_a_parentchain_import_dispatcher.clone(),
+						maybe_target_b_parentchain_import_dispatcher.clone(),
+						env,
+						vec![shard],
+					) {
+						Ok(result) => result,
+						Err(e) => {
+							error!("Block production failed for shard {:?}: {:?}", shard, e);
+							continue
+						},
+					};
+				blocks.extend(shard_blocks);
+				parentchain_calls.extend(shard_parentchain_calls);
+			}
 
 			debug!("Aura executed successfully");
 
@@ -253,6 +304,17 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 	Ok(())
 }
 
+/// Returns the STF executor of the parentchain the vault of the shard is on.
+fn get_stf_executor_for_shard(shard: ShardIdentifier) -> Result<Arc<EnclaveStfExecutor>> {
+	let (_, vault_target) = get_shard_vault_internal(shard)?;
+	trace!("using StfExecutor from {:?} parentchain for shard {:?}", vault_target, shard);
+	match vault_target {
+		ParentchainId::Integritee => get_stf_executor_from_integritee_solo_or_parachain(),
+		ParentchainId::TargetA => get_stf_executor_from_target_a_solo_or_parachain(),
+		ParentchainId::TargetB => get_stf_executor_from_target_b_solo_or_parachain(),
+	}
+}
+
 /// Executes aura for the given `slot`.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn exec_aura_on_slot<
@@ -0,0 +1,497 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Admin JSON-RPC server to operate a running worker, hosted on a http server that is only
+//! reachable from localhost.
+//!
+//! Every request has to carry the token from the admin rpc token file in the data dir as
+//! `Authorization: Bearer <token>` header.
+
+use crate::{
+	error::{Error, ServiceResult},
+	shard_manager::ManageShards,
+	worker_admin::AdministrateWorker,
+};
+use base58::FromBase58;
+use itp_settings::files::ADMIN_RPC_TOKEN_FILE;
+use itp_types::ShardIdentifier;
+use itp_utils::hex::hex_encode;
+use log::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+	fs::{self, OpenOptions},
+	io::Write,
+	net::SocketAddr,
+	os::unix::fs::OpenOptionsExt,
+	path::Path,
+	sync::Arc,
+};
+use warp::{http::StatusCode, Filter};
+
+const INVALID_PARAMS_CODE: i64 = -32602;
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+const INTERNAL_ERROR_CODE: i64 = -32603;
+const UNAUTHORIZED_CODE: i64 = -32001;
+
+pub async fn start_admin_rpc_server<ShardManager, WorkerAdmin>(
+	shard_manager: Arc<ShardManager>,
+	worker_admin: Arc<WorkerAdmin>,
+	token: String,
+	port: u16,
+) -> ServiceResult<()>
+where
+	ShardManager: ManageShards + Send + Sync + 'static,
+	WorkerAdmin: AdministrateWorker + Send + Sync + 'static,
+{
+	let expected_authorization = format!("Bearer {}", token);
+	let admin_rpc_route = warp::post()
+		.and(warp::header::optional::<String>("authorization"))
+		.and(warp::body::json())
+		.and_then(move |authorization: Option<String>, request: AdminRpcRequest| {
+			let shard_manager_clone = shard_manager.clone();
+			let worker_admin_clone = worker_admin.clone();
+			let is_authorized = authorization.as_deref().map_or(false, |authorization| {
+				constant_time_eq(authorization.as_bytes(), expected_authorization.as_bytes())
+			});
+			async move {
+				if !is_authorized {
+					warn!("Rejected unauthorized admin rpc request: {}", request.method);
+					let response =
+						AdminRpcResponse::error(request.id, UNAUTHORIZED_CODE, "Unauthorized");
+					return Ok::<_, warp::Rejection>(warp::reply::with_status(
+						warp::reply::json(&response),
+						StatusCode::UNAUTHORIZED,
+					))
+				}
+
+				// The operations block on the parentchain and the enclave.
+				let response = tokio::task::spawn_blocking(move || {
+					handle_admin_request(
+						shard_manager_clone.as_ref(),
+						worker_admin_clone.as_ref(),
+						request,
+					)
+				})
+				.await
+				.unwrap_or_else(|e| {
+					error!("Admin rpc request panicked: {:?}", e);
+					AdminRpcResponse::error(Value::Null, INTERNAL_ERROR_CODE, "Request failed")
+				});
+				Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+			}
+		});
+
+	let socket_addr: SocketAddr = ([127, 0, 0, 1], port).into();
+
+	info!("Running admin rpc server on: {:?}", socket_addr);
+	warp::serve(admin_rpc_route).run(socket_addr).await;
+
+	info!("Admin rpc server shut down");
+	Ok(())
+}
+
+/// Compare two byte strings in time that only depends on their length, so the admin rpc token
+/// can not be guessed byte by byte from the response times.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false
+	}
+	a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Read the admin rpc token from the data dir, or create a new random one if there is none.
+///
+/// The token file is only readable by the user running the worker.
+pub fn load_or_create_admin_rpc_token(data_dir: &Path) -> ServiceResult<String> {
+	let token_file = data_dir.join(ADMIN_RPC_TOKEN_FILE);
+	if token_file.exists() {
+		let token = fs::read_to_string(&token_file).map_err(|e| Error::Custom(e.into()))?;
+		return Ok(token.trim().to_string())
+	}
+
+	let mut token_bytes = [0u8; 32];
+	rand::thread_rng().fill_bytes(&mut token_bytes);
+	let token = hex::encode(token_bytes);
+
+	OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.mode(0o600)
+		.open(&token_file)
+		.and_then(|mut file| file.write_all(token.as_bytes()))
+		.map_err(|e| Error::Custom(e.into()))?;
+	info!("Created admin rpc token file: {}", token_file.display());
+	Ok(token)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdminRpcRequest {
+	pub jsonrpc: String,
+	pub method: String,
+	#[serde(default)]
+	pub params: Vec<String>,
+	pub id: Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdminRpcError {
+	pub code: i64,
+	pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdminRpcResponse {
+	pub jsonrpc: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub result: Option<Value>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<AdminRpcError>,
+	pub id: Value,
+}
+
+impl AdminRpcResponse {
+	fn result(id: Value, result: Value) -> Self {
+		AdminRpcResponse { jsonrpc: "2.0".to_string(), result: Some(result), error: None, id }
+	}
+
+	fn error(id: Value, code: i64, message: &str) -> Self {
+		AdminRpcResponse {
+			jsonrpc: "2.0".to_string(),
+			result: None,
+			error: Some(AdminRpcError { code, message: message.to_string() }),
+			id,
+		}
+	}
+}
+
+/// Dispatch an admin request to the shard manager or the worker admin.
+///
+/// Supported methods are `admin_listShards`, `admin_addShard`, `admin_removeShard`,
+/// `admin_pauseBlockProduction`, `admin_resumeBlockProduction`,
+/// `admin_isBlockProductionPaused`, `admin_resyncPeers`, `admin_pendingTrustedOperations`,
+/// `admin_createStateSnapshot` and `admin_rotateShieldingKey`. Methods operating on a shard
+/// take the base58 encoded shard as only parameter.
+pub fn handle_admin_request<ShardManager, WorkerAdmin>(
+	shard_manager: &ShardManager,
+	worker_admin: &WorkerAdmin,
+	request: AdminRpcRequest,
+) -> AdminRpcResponse
+where
+	ShardManager: ManageShards,
+	WorkerAdmin: AdministrateWorker,
+{
+	let id = request.id.clone();
+	let params = &request.params;
+	// The outer result holds parameter errors, the inner one the outcome of the operation.
+	let result = match request.method.as_str() {
+		"admin_listShards" => Ok(shard_manager.list_shards().map(|shards| json!(shards))),
+		"admin_addShard" => parse_shard_param(params)
+			.map(|shard| shard_manager.add_shard(shard).map(|status| json!(status))),
+		"admin_removeShard" => parse_shard_param(params)
+			.map(|shard| shard_manager.remove_shard(&shard).map(|_| Value::Null)),
+		"admin_pauseBlockProduction" =>
+			Ok(worker_admin.pause_block_production().map(|_| Value::Null)),
+		"admin_resumeBlockProduction" =>
+			Ok(worker_admin.resume_block_production().map(|_| Value::Null)),
+		"admin_isBlockProductionPaused" =>
+			Ok(worker_admin.is_block_production_paused().map(|paused| json!(paused))),
+		"admin_resyncPeers" => parse_shard_param(params)
+			.map(|shard| worker_admin.resync_peers(shard).map(|_| Value::Null)),
+		"admin_pendingTrustedOperations" => parse_shard_param(params).map(|shard| {
+			worker_admin.pending_trusted_operations(&shard).map(|operations| {
+				json!(operations.iter().map(|op| hex_encode(op)).collect::<Vec<_>>())
+			})
+		}),
+		"admin_createStateSnapshot" => parse_shard_param(params)
+			.map(|shard| worker_admin.create_state_snapshot(&shard).map(|hash| json!(hash))),
+		"admin_rotateShieldingKey" => Ok(rotate_shielding_key(shard_manager, worker_admin)),
+		_ => return AdminRpcResponse::error(id, METHOD_NOT_FOUND_CODE, "Method not found"),
+	};
+
+	match result {
+		Ok(Ok(value)) => AdminRpcResponse::result(id, value),
+		Ok(Err(e)) => AdminRpcResponse::error(id, INTERNAL_ERROR_CODE, &format!("{}", e)),
+		Err(e) => AdminRpcResponse::error(id, INVALID_PARAMS_CODE, e),
+	}
+}
+
+/// The shielding key is shared by all workers of a shard, they follow the key of the primary
+/// worker. Hence, it can only be rotated on a worker that is the primary of all its shards.
+fn rotate_shielding_key<ShardManager, WorkerAdmin>(
+	shard_manager: &ShardManager,
+	worker_admin: &WorkerAdmin,
+) -> ServiceResult<Value>
+where
+	ShardManager: ManageShards,
+	WorkerAdmin: AdministrateWorker,
+{
+	let shards = shard_manager.list_shards()?;
+	if let Some(status) = shards.iter().find(|s| !s.is_primary && s.primary_worker.is_some()) {
+		return Err(Error::Custom(
+			format!(
+				"Not the primary worker of shard {}, rotate the shielding key on {} instead",
+				status.shard,
+				status.primary_worker.as_deref().unwrap_or_default()
+			)
+			.into(),
+		))
+	}
+	worker_admin.rotate_shielding_key().map(|pubkey| json!(pubkey))
+}
+
+fn parse_shard_param(params: &[String]) -> Result<ShardIdentifier, &'static str> {
+	let shard_str = params.first().ok_or("Missing shard parameter")?;
+	let shard_vec = shard_str.from_base58().map_err(|_| "Shard must be base58 encoded")?;
+	if shard_vec.len() != 32 {
+		return Err("Shard must be 32 bytes long")
+	}
+	Ok(ShardIdentifier::from_slice(&shard_vec))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		error::{Error, ServiceResult},
+		shard_manager::ShardStatus,
+		worker_admin::MockAdministrateWorker,
+	};
+	use base58::ToBase58;
+	use codec::Encode;
+	use itp_sgx_temp_dir::TempDir;
+	use parking_lot::RwLock;
+	use std::os::unix::fs::PermissionsExt;
+
+	#[derive(Default)]
+	struct ManageShardsMock {
+		shards: RwLock<Vec<ShardIdentifier>>,
+		primary_worker: Option<String>,
+	}
+
+	impl ManageShardsMock {
+		fn status(&self, shard: &ShardIdentifier) -> ShardStatus {
+			ShardStatus {
+				shard: shard.encode().to_base58(),
+				vault: None,
+				creation_block: None,
+				primary_worker: self.primary_worker.clone(),
+				is_primary: false,
+			}
+		}
+	}
+
+	impl ManageShards for ManageShardsMock {
+		fn add_shard(&self, shard: ShardIdentifier) -> ServiceResult<ShardStatus> {
+			self.shards.write().push(shard);
+			Ok(self.status(&shard))
+		}
+
+		fn remove_shard(&self, shard: &ShardIdentifier) -> ServiceResult<()> {
+			let mut shards = self.shards.write();
+			let position = shards
+				.iter()
+				.position(|s| s == shard)
+				.ok_or_else(|| Error::Custom("Shard is not handled".into()))?;
+			shards.remove(position);
+			Ok(())
+		}
+
+		fn list_shards(&self) -> ServiceResult<Vec<ShardStatus>> {
+			Ok(self.shards.read().iter().map(|shard| self.status(shard)).collect())
+		}
+	}
+
+	fn request(method: &str, params: Vec<String>) -> AdminRpcRequest {
+		AdminRpcRequest {
+			jsonrpc: "2.0".to_string(),
+			method: method.to_string(),
+			params,
+			id: 1.into(),
+		}
+	}
+
+	#[test]
+	fn add_and_remove_shard_works() {
+		let shard_manager = ManageShardsMock::default();
+		let shard = ShardIdentifier::from_slice(&[1u8; 32]);
+		let shard_param = vec![shard.encode().to_base58()];
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_addShard", shard_param.clone()),
+		);
+		assert_eq!(response.result, Some(json!(shard_manager.status(&shard))));
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_listShards", vec![]),
+		);
+		assert_eq!(response.result, Some(json!(vec![shard_manager.status(&shard)])));
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_removeShard", shard_param.clone()),
+		);
+		assert_eq!(response.result, Some(Value::Null));
+		assert!(shard_manager.shards.read().is_empty());
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_removeShard", shard_param),
+		);
+		assert_eq!(response.error.unwrap().code, INTERNAL_ERROR_CODE);
+	}
+
+	#[test]
+	fn invalid_shard_param_is_rejected() {
+		let shard_manager = ManageShardsMock::default();
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_addShard", vec![]),
+		);
+		assert_eq!(response.error.unwrap().code, INVALID_PARAMS_CODE);
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_addShard", vec![[1u8; 4].to_base58()]),
+		);
+		assert_eq!(response.error.unwrap().code, INVALID_PARAMS_CODE);
+		assert!(shard_manager.shards.read().is_empty());
+	}
+
+	#[test]
+	fn unknown_method_is_rejected() {
+		let shard_manager = ManageShardsMock::default();
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&MockAdministrateWorker::new(),
+			request("admin_unknown", vec![]),
+		);
+		assert_eq!(response.error.unwrap().code, METHOD_NOT_FOUND_CODE);
+	}
+
+	#[test]
+	fn block_production_can_be_paused_and_resumed() {
+		let shard_manager = ManageShardsMock::default();
+		let mut worker_admin = MockAdministrateWorker::new();
+		worker_admin.expect_pause_block_production().times(1).returning(|| Ok(()));
+		worker_admin.expect_resume_block_production().times(1).returning(|| Ok(()));
+		worker_admin.expect_is_block_production_paused().times(1).returning(|| Ok(true));
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&worker_admin,
+			request("admin_pauseBlockProduction", vec![]),
+		);
+		assert_eq!(response.result, Some(Value::Null));
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&worker_admin,
+			request("admin_isBlockProductionPaused", vec![]),
+		);
+		assert_eq!(response.result, Some(json!(true)));
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&worker_admin,
+			request("admin_resumeBlockProduction", vec![]),
+		);
+		assert_eq!(response.result, Some(Value::Null));
+	}
+
+	#[test]
+	fn pending_trusted_operations_are_hex_encoded() {
+		let shard_manager = ManageShardsMock::default();
+		let shard = ShardIdentifier::from_slice(&[2u8; 32]);
+		let mut worker_admin = MockAdministrateWorker::new();
+		worker_admin
+			.expect_pending_trusted_operations()
+			.withf(move |s| *s == shard)
+			.times(1)
+			.returning(|_| Ok(vec![vec![1u8, 2u8], vec![3u8]]));
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&worker_admin,
+			request("admin_pendingTrustedOperations", vec![shard.encode().to_base58()]),
+		);
+		assert_eq!(response.result, Some(json!(vec!["0x0102", "0x03"])));
+	}
+
+	#[test]
+	fn shard_operations_require_shard_param() {
+		let shard_manager = ManageShardsMock::default();
+		let worker_admin = MockAdministrateWorker::new();
+
+		for method in
+			["admin_resyncPeers", "admin_pendingTrustedOperations", "admin_createStateSnapshot"]
+		{
+			let response =
+				handle_admin_request(&shard_manager, &worker_admin, request(method, vec![]));
+			assert_eq!(response.error.unwrap().code, INVALID_PARAMS_CODE);
+		}
+	}
+
+	#[test]
+	fn shielding_key_rotation_is_refused_if_not_primary() {
+		let shard_manager = ManageShardsMock {
+			shards: RwLock::new(vec![ShardIdentifier::from_slice(&[1u8; 32])]),
+			primary_worker: Some("primary".to_string()),
+		};
+		let mut worker_admin = MockAdministrateWorker::new();
+		worker_admin.expect_rotate_shielding_key().times(0);
+
+		let response = handle_admin_request(
+			&shard_manager,
+			&worker_admin,
+			request("admin_rotateShieldingKey", vec![]),
+		);
+		assert_eq!(response.error.unwrap().code, INTERNAL_ERROR_CODE);
+	}
+
+	#[test]
+	fn admin_rpc_token_is_created_once() {
+		let temp_dir = TempDir::with_prefix("admin_rpc_token_is_created_once").unwrap();
+
+		let token = load_or_create_admin_rpc_token(temp_dir.path()).unwrap();
+		assert_eq!(token.len(), 64);
+		assert_eq!(load_or_create_admin_rpc_token(temp_dir.path()).unwrap(), token);
+
+		let permissions =
+			fs::metadata(temp_dir.path().join(ADMIN_RPC_TOKEN_FILE)).unwrap().permissions();
+		assert_eq!(permissions.mode() & 0o777, 0o600);
+	}
+
+	#[test]
+	fn constant_time_eq_works() {
+		assert!(constant_time_eq(b"Bearer token", b"Bearer token"));
+		assert!(!constant_time_eq(b"Bearer token", b"Bearer tokeN"));
+		assert!(!constant_time_eq(b"Bearer token", b"Bearer tok"));
+		assert!(!constant_time_eq(b"", b"Bearer token"));
+	}
+}
@@ -0,0 +1,238 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Periodic re-attestation of the enclave and tracking of its registration on the teerex pallet.
+//!
+//! The teerex pallet deregisters enclaves whose attestation is older than
+//! `MaxAttestationRenewalPeriod`. A worker therefore re-attests periodically and refuses to
+//! produce sidechain blocks once its registration has lapsed. Failed attempts are retried
+//! with an exponential backoff.
+
+use crate::{
+	error::{Error, ServiceResult},
+	initialized_service::TrackHealth,
+	prometheus_metrics::set_enclave_attestation,
+};
+use itp_node_api::api_client::{PalletTeerexApi, ParentchainApi};
+use itp_time_utils::now_as_millis;
+use itp_types::{parentchain::Hash, AccountId, Moment};
+use log::*;
+use parking_lot::RwLock;
+use std::{sync::Arc, thread, time::Duration};
+use substrate_api_client::GetStorage;
+
+/// Delay before the first retry of a failed re-attestation. It doubles with every further
+/// failed attempt, up to the re-attestation period.
+pub(crate) const REATTESTATION_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Registration of our enclave, as read from the teerex pallet after an attestation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct EnclaveRegistration {
+	/// Unix timestamp in milliseconds of the attestation.
+	pub attestation_timestamp: u64,
+	/// Unix timestamp in milliseconds, after which the pallet considers the attestation outdated.
+	pub expires_at: Option<u64>,
+}
+
+impl EnclaveRegistration {
+	pub fn has_lapsed(&self, now: u64) -> bool {
+		self.expires_at.map_or(false, |expires_at| now >= expires_at)
+	}
+}
+
+/// Keeps track of our registration on the teerex pallet.
+#[derive(Default)]
+pub(crate) struct RegistrationTracker {
+	registration: RwLock<Option<EnclaveRegistration>>,
+}
+
+impl RegistrationTracker {
+	/// Records a new registration and returns the previous one.
+	pub fn update(&self, registration: EnclaveRegistration) -> Option<EnclaveRegistration> {
+		self.registration.write().replace(registration)
+	}
+
+	pub fn registration(&self) -> Option<EnclaveRegistration> {
+		*self.registration.read()
+	}
+
+	/// We are registered and our attestation has not lapsed yet.
+	pub fn is_registration_valid(&self) -> bool {
+		self.registration
+			.read()
+			.map_or(false, |registration| !registration.has_lapsed(now_as_millis()))
+	}
+}
+
+/// Reads the registration of the enclave with `account` from the teerex pallet.
+pub(crate) fn fetch_enclave_registration(
+	api: &ParentchainApi,
+	account: &AccountId,
+) -> ServiceResult<EnclaveRegistration> {
+	let enclave = api.enclave(account, None)?.ok_or(Error::EnclaveNotRegistered)?;
+	let attestation_timestamp = enclave.attestation_timestamp();
+	let expires_at = api
+		.get_constant::<Moment>("Teerex", "MaxAttestationRenewalPeriod")
+		.ok()
+		.map(|renewal_period| attestation_timestamp.saturating_add(renewal_period));
+	Ok(EnclaveRegistration { attestation_timestamp, expires_at })
+}
+
+/// Records the registration read after an attestation.
+pub(crate) fn record_registration<HealthTracker: TrackHealth>(
+	registration_tracker: &RegistrationTracker,
+	health_tracker: &HealthTracker,
+	registration: EnclaveRegistration,
+) {
+	registration_tracker.update(registration);
+	health_tracker
+		.attestation_registered(registration.attestation_timestamp, registration.expires_at);
+	set_enclave_attestation(registration.attestation_timestamp);
+}
+
+/// Delay until the next re-attestation, after `failed_attempts` consecutive failures.
+pub(crate) fn reattestation_delay(period: Duration, failed_attempts: u32) -> Duration {
+	if failed_attempts == 0 {
+		return period
+	}
+	REATTESTATION_RETRY_DELAY
+		.saturating_mul(2u32.saturating_pow(failed_attempts - 1))
+		.min(period)
+}
+
+/// Schedule periodic re-attestation of the enclave.
+///
+/// The `send_register_xt` needs to create a fresh registration extrinsic every time it is called
+/// (updated nonce, fresh IAS-RA or DCAP-Quote). After each registration, the registration is
+/// read back with `fetch_registration` and recorded. A failed attempt is retried after
+/// [`reattestation_delay`].
+pub(crate) fn schedule_periodic_reattestation_thread<HealthTracker>(
+	send_register_xt: impl Fn() -> ServiceResult<Hash> + Send + 'static,
+	fetch_registration: impl Fn() -> ServiceResult<EnclaveRegistration> + Send + 'static,
+	registration_tracker: Arc<RegistrationTracker>,
+	health_tracker: Arc<HealthTracker>,
+	period: Duration,
+) where
+	HealthTracker: TrackHealth + Send + Sync + 'static,
+{
+	println!("Schedule periodic enclave reattestation every: {:?}", period);
+
+	thread::Builder::new()
+		.name("enclave_reattestation_thread".to_owned())
+		.spawn(move || {
+			let mut failed_attempts = 0u32;
+			loop {
+				thread::sleep(reattestation_delay(period, failed_attempts));
+
+				trace!("Reattesting the enclave.");
+				match send_register_xt() {
+					Ok(block_hash) => {
+						println!(
+							"✅ Successfully reattested the enclave. Block hash: {}.",
+							block_hash
+						);
+						failed_attempts = 0;
+					},
+					Err(e) => {
+						failed_attempts = failed_attempts.saturating_add(1);
+						error!(
+							"❌ Could not reattest the enclave, retrying in {:?}: {:?}",
+							reattestation_delay(period, failed_attempts),
+							e
+						);
+					},
+				}
+				match fetch_registration() {
+					Ok(registration) => record_registration(
+						registration_tracker.as_ref(),
+						health_tracker.as_ref(),
+						registration,
+					),
+					Err(e) => error!("Could not read the registration of the enclave: {:?}", e),
+				}
+				if !registration_tracker.is_registration_valid() {
+					error!(
+						"The registration of the enclave has lapsed, no sidechain blocks will be \
+						produced until the next successful reattestation"
+					);
+				}
+			}
+		})
+		.unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn registration(expires_at: Option<u64>) -> EnclaveRegistration {
+		EnclaveRegistration { attestation_timestamp: 0, expires_at }
+	}
+
+	#[test]
+	fn registration_without_expiry_does_not_lapse() {
+		assert!(!registration(None).has_lapsed(u64::MAX));
+	}
+
+	#[test]
+	fn registration_lapses_at_expiry() {
+		let registration = registration(Some(1_000));
+
+		assert!(!registration.has_lapsed(999));
+		assert!(registration.has_lapsed(1_000));
+	}
+
+	#[test]
+	fn tracker_is_invalid_until_registered() {
+		let tracker = RegistrationTracker::default();
+		assert!(!tracker.is_registration_valid());
+
+		tracker.update(registration(Some(now_as_millis() + 60_000)));
+		assert!(tracker.is_registration_valid());
+	}
+
+	#[test]
+	fn tracker_is_invalid_after_registration_lapsed() {
+		let tracker = RegistrationTracker::default();
+		tracker.update(registration(Some(now_as_millis() - 1)));
+
+		assert!(!tracker.is_registration_valid());
+	}
+
+	#[test]
+	fn update_returns_previous_registration() {
+		let tracker = RegistrationTracker::default();
+		let first = registration(Some(1));
+		let second = registration(Some(2));
+
+		assert_eq!(tracker.update(first), None);
+		assert_eq!(tracker.update(second), Some(first));
+		assert_eq!(tracker.registration(), Some(second));
+	}
+
+	#[test]
+	fn reattestation_delay_backs_off_up_to_the_period() {
+		let period = Duration::from_secs(600);
+
+		assert_eq!(reattestation_delay(period, 0), period);
+		assert_eq!(reattestation_delay(period, 1), REATTESTATION_RETRY_DELAY);
+		assert_eq!(reattestation_delay(period, 2), REATTESTATION_RETRY_DELAY * 2);
+		assert_eq!(reattestation_delay(period, 3), REATTESTATION_RETRY_DELAY * 4);
+		assert_eq!(reattestation_delay(period, 10), period);
+		assert_eq!(reattestation_delay(period, u32::MAX), period);
+	}
+}
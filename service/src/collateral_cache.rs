@@ -0,0 +1,291 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Local cache of the DCAP collateral (TCB info, QE identity, CRLs), per FMSPC.
+//!
+//! Collateral fetched from the PCCS is stored in the data dir, and used until it expires whenever
+//! the PCCS is unreachable. Air-gapped deployments can import collateral, which has been exported on a
+//! worker with access to a PCCS. The collateral is not trusted, its signatures are checked when
+//! verifying quotes and by the teerex pallet.
+
+use crate::{
+	error::{Error, ServiceResult},
+	prometheus_metrics::set_dcap_collateral_next_update,
+};
+use itp_attestation_handler::SgxQlQveCollateral;
+use itp_time_utils::now_as_secs;
+use log::*;
+use sgx_types::sgx_quote3_error_t;
+use sgx_verify::extract_tcb_info_from_raw_dcap_quote;
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+use teerex_primitives::Fmspc;
+
+#[derive(Clone, Debug)]
+pub(crate) struct CollateralCache {
+	dir: PathBuf,
+}
+
+impl CollateralCache {
+	pub fn new(dir: PathBuf) -> Self {
+		CollateralCache { dir }
+	}
+
+	/// Stores the collateral of `fmspc`, replacing any previously cached collateral.
+	pub fn store(&self, fmspc: &Fmspc, collateral: &SgxQlQveCollateral) -> ServiceResult<()> {
+		let next_update = collateral.next_update().ok_or_else(|| {
+			Error::Custom(format!("Collateral of FMSPC {} is malformed", hex::encode(fmspc)).into())
+		})?;
+		collateral
+			.write_to_dir(&self.fmspc_dir(fmspc))
+			.map_err(|e| Error::Custom(e.into()))?;
+		set_dcap_collateral_next_update(fmspc, next_update);
+		debug!("Cached collateral of FMSPC {}", hex::encode(fmspc));
+		Ok(())
+	}
+
+	/// Loads the cached collateral of `fmspc`. Fails if it has expired.
+	pub fn load(&self, fmspc: &Fmspc) -> ServiceResult<SgxQlQveCollateral> {
+		let collateral = SgxQlQveCollateral::read_from_dir(&self.fmspc_dir(fmspc))
+			.map_err(|e| Error::Custom(e.into()))?;
+		let next_update = collateral.next_update().ok_or_else(|| {
+			Error::Custom(
+				format!("Cached collateral of FMSPC {} is malformed", hex::encode(fmspc)).into(),
+			)
+		})?;
+		set_dcap_collateral_next_update(fmspc, next_update);
+		if next_update <= now_as_secs() as i64 {
+			return Err(Error::Custom(
+				format!(
+					"Cached collateral of FMSPC {} expired at {}",
+					hex::encode(fmspc),
+					next_update
+				)
+				.into(),
+			))
+		}
+		Ok(collateral)
+	}
+
+	/// Loads the cached collateral of the platform, which generated the DCAP `quote`.
+	pub fn load_for_quote(&self, quote: &[u8]) -> ServiceResult<SgxQlQveCollateral> {
+		let (fmspc, _tcb_info) = extract_tcb_info_from_raw_dcap_quote(quote)
+			.map_err(|e| Error::Custom(format!("{:?}", e).into()))?;
+		self.load(&fmspc)
+	}
+
+	/// All FMSPCs, for which collateral is cached.
+	pub fn fmspcs(&self) -> ServiceResult<Vec<Fmspc>> {
+		fmspcs_in_dir(&self.dir)
+	}
+
+	/// Imports all collateral from `source`, which has the layout of the cache (see [`Self::export_to`]).
+	///
+	/// Returns the number of imported FMSPCs.
+	pub fn import_from(&self, source: &Path) -> ServiceResult<usize> {
+		let fmspcs = fmspcs_in_dir(source)?;
+		for fmspc in fmspcs.iter() {
+			let collateral = SgxQlQveCollateral::read_from_dir(&source.join(hex::encode(fmspc)))
+				.map_err(|e| Error::Custom(e.into()))?;
+			self.store(fmspc, &collateral)?;
+		}
+		Ok(fmspcs.len())
+	}
+
+	/// Exports all cached collateral to `target`, in a directory per hex encoded FMSPC.
+	///
+	/// Returns the number of exported FMSPCs.
+	pub fn export_to(&self, target: &Path) -> ServiceResult<usize> {
+		let fmspcs = self.fmspcs()?;
+		for fmspc in fmspcs.iter() {
+			let fmspc_dir = hex::encode(fmspc);
+			SgxQlQveCollateral::read_from_dir(&self.dir.join(&fmspc_dir))
+				.and_then(|collateral| collateral.write_to_dir(&target.join(&fmspc_dir)))
+				.map_err(|e| Error::Custom(e.into()))?;
+		}
+		Ok(fmspcs.len())
+	}
+
+	fn fmspc_dir(&self, fmspc: &Fmspc) -> PathBuf {
+		self.dir.join(hex::encode(fmspc))
+	}
+}
+
+/// Fetches the collateral of `fmspc` from the PCCS and caches it. Falls back to the cached
+/// collateral, if the PCCS is unreachable and the cached collateral has not expired.
+#[cfg(feature = "dcap")]
+pub(crate) fn fetch_collateral_or_use_cached<E>(
+	enclave: &E,
+	cache: &CollateralCache,
+	fmspc: &Fmspc,
+) -> ServiceResult<SgxQlQveCollateral>
+where
+	E: itp_enclave_api::remote_attestation::RemoteAttestation + ?Sized,
+{
+	match enclave.fetch_dcap_collateral(*fmspc) {
+		Ok(collateral) => {
+			if let Err(e) = cache.store(fmspc, &collateral) {
+				warn!("Could not cache the collateral of FMSPC {}: {:?}", hex::encode(fmspc), e);
+			}
+			Ok(collateral)
+		},
+		Err(e) if is_collateral_unavailable(&e) => {
+			warn!(
+				"Could not fetch the collateral of FMSPC {} from the PCCS, using the cached collateral: {:?}",
+				hex::encode(fmspc),
+				e
+			);
+			cache.load(fmspc)
+		},
+		Err(e) => Err(e.into()),
+	}
+}
+
+/// Whether the quote library failed, because it could not fetch the collateral from the PCCS.
+///
+/// Only then the cached collateral is used, any other error is not caused by the PCCS.
+pub(crate) fn is_collateral_unavailable(error: &itp_enclave_api::error::Error) -> bool {
+	matches!(
+		error,
+		itp_enclave_api::error::Error::SgxQuote(
+			sgx_quote3_error_t::SGX_QL_NETWORK_ERROR
+				| sgx_quote3_error_t::SGX_QL_MESSAGE_ERROR
+				| sgx_quote3_error_t::SGX_QL_NO_QUOTE_COLLATERAL_DATA
+				| sgx_quote3_error_t::SGX_QL_PLATFORM_LIB_UNAVAILABLE
+		)
+	)
+}
+
+fn fmspcs_in_dir(dir: &Path) -> ServiceResult<Vec<Fmspc>> {
+	if !dir.exists() {
+		return Ok(Vec::new())
+	}
+	let entries = fs::read_dir(dir).map_err(|e| Error::Custom(e.into()))?;
+	Ok(entries
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.path().is_dir())
+		.filter_map(|entry| {
+			let fmspc = hex::decode(entry.file_name().to_str()?).ok()?;
+			fmspc.try_into().ok()
+		})
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_attestation_handler::collateral::{COLLATERAL_VERSION, TEE_TYPE_SGX};
+	use itp_sgx_temp_dir::TempDir;
+
+	const FMSPC: Fmspc = [0, 0x90, 0x6e, 0xa1, 0, 0];
+
+	fn collateral(next_update: &str) -> SgxQlQveCollateral {
+		SgxQlQveCollateral {
+			version: COLLATERAL_VERSION,
+			tee_type: TEE_TYPE_SGX,
+			pck_crl_issuer_chain: b"pck_crl_issuer_chain\0".to_vec(),
+			root_ca_crl: b"root_ca_crl\0".to_vec(),
+			pck_crl: b"pck_crl\0".to_vec(),
+			tcb_info_issuer_chain: b"tcb_info_issuer_chain\0".to_vec(),
+			tcb_info: format!(
+				r#"{{"tcbInfo":{{"nextUpdate":"{}"}},"signature":"00"}}"#,
+				next_update
+			)
+			.into_bytes(),
+			qe_identity_issuer_chain: b"qe_identity_issuer_chain\0".to_vec(),
+			qe_identity: format!(
+				r#"{{"enclaveIdentity":{{"nextUpdate":"{}"}},"signature":"00"}}"#,
+				next_update
+			)
+			.into_bytes(),
+		}
+	}
+
+	#[test]
+	fn stored_collateral_can_be_loaded() {
+		let temp_dir = TempDir::with_prefix("stored_collateral_can_be_loaded").unwrap();
+		let cache = CollateralCache::new(temp_dir.path().join("collateral"));
+
+		cache.store(&FMSPC, &collateral("2030-01-01T00:00:00Z")).unwrap();
+		let loaded = cache.load(&FMSPC).unwrap();
+
+		assert_eq!(loaded.next_update(), collateral("2030-01-01T00:00:00Z").next_update());
+		assert_eq!(cache.fmspcs().unwrap(), vec![FMSPC]);
+	}
+
+	#[test]
+	fn loading_uncached_fmspc_fails() {
+		let temp_dir = TempDir::with_prefix("loading_uncached_fmspc_fails").unwrap();
+		let cache = CollateralCache::new(temp_dir.path().join("collateral"));
+
+		assert!(cache.load(&FMSPC).is_err());
+		assert!(cache.fmspcs().unwrap().is_empty());
+	}
+
+	#[test]
+	fn expired_collateral_is_not_loaded() {
+		let temp_dir = TempDir::with_prefix("expired_collateral_is_not_loaded").unwrap();
+		let cache = CollateralCache::new(temp_dir.path().join("collateral"));
+
+		cache.store(&FMSPC, &collateral("2020-01-01T00:00:00Z")).unwrap();
+
+		assert!(cache.load(&FMSPC).is_err());
+	}
+
+	#[test]
+	fn only_fetch_failures_make_the_collateral_unavailable() {
+		use itp_enclave_api::error::Error as EnclaveApiError;
+
+		assert!(is_collateral_unavailable(&EnclaveApiError::SgxQuote(
+			sgx_quote3_error_t::SGX_QL_NETWORK_ERROR
+		)));
+		assert!(!is_collateral_unavailable(&EnclaveApiError::SgxQuote(
+			sgx_quote3_error_t::SGX_QL_ERROR_UNEXPECTED
+		)));
+		assert!(!is_collateral_unavailable(&EnclaveApiError::Sgx(
+			sgx_types::sgx_status_t::SGX_ERROR_UNEXPECTED
+		)));
+	}
+
+	#[test]
+	fn malformed_collateral_is_not_stored() {
+		let temp_dir = TempDir::with_prefix("malformed_collateral_is_not_stored").unwrap();
+		let cache = CollateralCache::new(temp_dir.path().join("collateral"));
+		let mut malformed = collateral("2030-01-01T00:00:00Z");
+		malformed.tcb_info = b"not json".to_vec();
+
+		assert!(cache.store(&FMSPC, &malformed).is_err());
+		assert!(cache.fmspcs().unwrap().is_empty());
+	}
+
+	#[test]
+	fn exported_collateral_can_be_imported() {
+		let temp_dir = TempDir::with_prefix("exported_collateral_can_be_imported").unwrap();
+		let cache = CollateralCache::new(temp_dir.path().join("collateral"));
+		let other_cache = CollateralCache::new(temp_dir.path().join("other_collateral"));
+		let export_dir = temp_dir.path().join("export");
+		cache.store(&FMSPC, &collateral("2030-01-01T00:00:00Z")).unwrap();
+
+		assert_eq!(cache.export_to(&export_dir).unwrap(), 1);
+		assert_eq!(other_cache.import_from(&export_dir).unwrap(), 1);
+
+		assert_eq!(other_cache.fmspcs().unwrap(), vec![FMSPC]);
+		assert!(other_cache.load(&FMSPC).is_ok());
+	}
+}
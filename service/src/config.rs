@@ -17,8 +17,12 @@
 
 use clap::ArgMatches;
 use itc_rest_client::rest_client::Url;
-use itp_settings::teeracle::{DEFAULT_MARKET_DATA_UPDATE_INTERVAL, ONE_DAY, THIRTY_MINUTES};
-use itp_types::parentchain::ParentchainId;
+use itp_settings::{
+	files::SIDECHAIN_PURGE_LIMIT,
+	teeracle::{DEFAULT_MARKET_DATA_UPDATE_INTERVAL, ONE_DAY, THIRTY_MINUTES},
+};
+use itp_types::{parentchain::ParentchainId, TrustedRpcServerLimits};
+use its_storage::PruningPolicy;
 use parse_duration::parse;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -34,6 +38,7 @@ static DEFAULT_UNTRUSTED_PORT: &str = "2001";
 static DEFAULT_MU_RA_PORT: &str = "3443";
 static DEFAULT_METRICS_PORT: &str = "8787";
 static DEFAULT_UNTRUSTED_HTTP_PORT: &str = "4545";
+static DEFAULT_ADMIN_RPC_PORT: &str = "4646";
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Config {
@@ -62,8 +67,16 @@ pub struct Config {
 	metrics_server_port: String,
 	/// Port for the untrusted HTTP server (e.g. for `is_initialized`)
 	untrusted_http_port: String,
+	/// Enable the admin rpc server (only reachable from localhost)
+	enable_admin_rpc: bool,
+	/// Port for the admin rpc server
+	admin_rpc_port: String,
+	/// OTLP endpoint of the OpenTelemetry collector to export tracing spans to.
+	otlp_endpoint: Option<String>,
 	/// Data directory used by all the services.
 	data_dir: PathBuf,
+	/// Only report the state migrations the enclave would run, then exit.
+	state_migration_dry_run: bool,
 	/// Config of the 'run' subcommand
 	run_config: Option<RunConfig>,
 }
@@ -87,7 +100,11 @@ impl Config {
 		enable_metrics_server: bool,
 		metrics_server_port: String,
 		untrusted_http_port: String,
+		enable_admin_rpc: bool,
+		admin_rpc_port: String,
+		otlp_endpoint: Option<String>,
 		data_dir: PathBuf,
+		state_migration_dry_run: bool,
 		run_config: Option<RunConfig>,
 	) -> Self {
 		Self {
@@ -107,7 +124,11 @@ impl Config {
 			enable_metrics_server,
 			metrics_server_port,
 			untrusted_http_port,
+			enable_admin_rpc,
+			admin_rpc_port,
+			otlp_endpoint,
 			data_dir,
+			state_migration_dry_run,
 			run_config,
 		}
 	}
@@ -187,6 +208,10 @@ impl Config {
 		self.data_dir.as_path()
 	}
 
+	pub fn state_migration_dry_run(&self) -> bool {
+		self.state_migration_dry_run
+	}
+
 	pub fn run_config(&self) -> &Option<RunConfig> {
 		&self.run_config
 	}
@@ -202,6 +227,18 @@ impl Config {
 	pub fn try_parse_untrusted_http_server_port(&self) -> Option<u16> {
 		self.untrusted_http_port.parse::<u16>().ok()
 	}
+
+	pub fn enable_admin_rpc(&self) -> bool {
+		self.enable_admin_rpc
+	}
+
+	pub fn otlp_endpoint(&self) -> Option<&str> {
+		self.otlp_endpoint.as_deref()
+	}
+
+	pub fn try_parse_admin_rpc_port(&self) -> Option<u16> {
+		self.admin_rpc_port.parse::<u16>().ok()
+	}
 }
 
 impl From<&ArgMatches<'_>> for Config {
@@ -213,6 +250,8 @@ impl From<&ArgMatches<'_>> for Config {
 		let metrics_server_port = m.value_of("metrics-port").unwrap_or(DEFAULT_METRICS_PORT);
 		let untrusted_http_port =
 			m.value_of("untrusted-http-port").unwrap_or(DEFAULT_UNTRUSTED_HTTP_PORT);
+		let is_admin_rpc_enabled = m.is_present("enable-admin-rpc");
+		let admin_rpc_port = m.value_of("admin-rpc-port").unwrap_or(DEFAULT_ADMIN_RPC_PORT);
 
 		let data_dir = match m.value_of("data-dir") {
 			Some(d) => {
@@ -254,7 +293,11 @@ impl From<&ArgMatches<'_>> for Config {
 			is_metrics_server_enabled,
 			metrics_server_port.to_string(),
 			untrusted_http_port.to_string(),
+			is_admin_rpc_enabled,
+			admin_rpc_port.to_string(),
+			m.value_of("otlp-endpoint").map(Into::into),
 			data_dir,
+			m.is_present("state-migration-dry-run"),
 			run_config,
 		)
 	}
@@ -266,16 +309,35 @@ pub struct RunConfig {
 	skip_ra: bool,
 	/// Set this flag if running in development mode to bootstrap enclave account on parentchain via //Alice.
 	dev: bool,
-	/// Shard identifier base58 encoded. Defines the shard that this worker operates on. Default is mrenclave.
-	shard: Option<String>,
+	/// Shard identifiers base58 encoded. Defines the shards that this worker operates on.
+	/// Default is a single shard with the mrenclave as identifier.
+	shards: Vec<String>,
 	/// Optional teeracle update interval
 	teeracle_update_interval: Option<Duration>,
-	/// Optional teeracle reregistration interval
+	/// Optional teeracle reregistration interval, superseded by `reattestation_interval`
 	reregister_teeracle_interval: Option<Duration>,
+	/// Optional interval at which the enclave is re-attested and re-registered on the parentchain
+	reattestation_interval: Option<Duration>,
 	/// Marblerun's Prometheus endpoint base URL
 	marblerun_base_url: Option<String>,
 	/// parentchain which should be used for shielding/unshielding the stf's native token
 	pub shielding_target: Option<ParentchainId>,
+	/// Optional policy deciding which sidechain blocks are kept when pruning the block storage
+	sidechain_pruning_policy: Option<PruningPolicy>,
+	/// Optional maximum number of open connections to the trusted RPC server
+	rpc_max_connections: Option<u32>,
+	/// Optional maximum number of requests per second to the trusted RPC server per IP address
+	rpc_max_requests_per_ip: Option<u32>,
+	/// Optional maximum number of requests per second to the trusted RPC server per connection
+	rpc_max_requests_per_connection: Option<u32>,
+	/// Optional maximum size of a message to the trusted RPC server in bytes
+	rpc_max_message_size: Option<u32>,
+	/// Optional time after which idle connections to the trusted RPC server are closed
+	rpc_idle_timeout: Option<Duration>,
+	/// Optional maximum number of messages queued for a client of the trusted RPC server
+	rpc_max_outbound_queue: Option<u32>,
+	/// Optional maximum number of requests in a JSON-RPC batch to the trusted RPC server
+	rpc_max_batch_size: Option<u32>,
 }
 
 impl RunConfig {
@@ -287,21 +349,24 @@ impl RunConfig {
 		self.dev
 	}
 
-	pub fn shard(&self) -> Option<&str> {
-		self.shard.as_deref()
+	pub fn shards(&self) -> &[String] {
+		&self.shards
 	}
 
 	pub fn teeracle_update_interval(&self) -> Duration {
 		self.teeracle_update_interval.unwrap_or(DEFAULT_MARKET_DATA_UPDATE_INTERVAL)
 	}
 
-	/// The periodic registration period of the teeracle.
+	/// The period at which the enclave is re-attested, in all worker modes.
 	///
-	/// Defaults to 23h30m, as this is slightly below the currently configured automatic
-	/// deregistration period on the Integritee chains.
-	pub fn reregister_teeracle_interval(&self) -> Duration {
+	/// Falls back to the teeracle reregistration interval, which has been used before. Defaults
+	/// to 23h30m, as this is slightly below the currently configured automatic deregistration
+	/// period on the Integritee chains.
+	pub fn reattestation_interval(&self) -> Duration {
 		// Todo: Derive this from chain https://github.com/integritee-network/worker/issues/1351
-		self.reregister_teeracle_interval.unwrap_or(ONE_DAY - THIRTY_MINUTES)
+		self.reattestation_interval
+			.or(self.reregister_teeracle_interval)
+			.unwrap_or(ONE_DAY - THIRTY_MINUTES)
 	}
 
 	pub fn marblerun_base_url(&self) -> &str {
@@ -310,19 +375,44 @@ impl RunConfig {
 		// https://github.com/edgelesssys/marblerun/blob/master/docs/docs/workflows/monitoring.md?plain=1#L26
 		self.marblerun_base_url.as_deref().unwrap_or("http://localhost:9944")
 	}
+
+	/// Defaults to keeping the last `SIDECHAIN_PURGE_LIMIT` blocks per shard.
+	pub fn sidechain_pruning_policy(&self) -> PruningPolicy {
+		self.sidechain_pruning_policy
+			.unwrap_or(PruningPolicy::KeepLastBlocks(SIDECHAIN_PURGE_LIMIT))
+	}
+
+	/// The limits of the trusted RPC server, unset limits are left to the enclave's defaults.
+	pub fn trusted_rpc_server_limits(&self) -> TrustedRpcServerLimits {
+		TrustedRpcServerLimits {
+			max_connections: self.rpc_max_connections,
+			max_requests_per_second_per_ip: self.rpc_max_requests_per_ip,
+			max_requests_per_second_per_connection: self.rpc_max_requests_per_connection,
+			max_message_size: self.rpc_max_message_size,
+			idle_timeout: self.rpc_idle_timeout.map(|timeout| timeout.as_secs()),
+			max_outbound_queue: self.rpc_max_outbound_queue,
+			max_batch_size: self.rpc_max_batch_size,
+		}
+	}
 }
 
 impl From<&ArgMatches<'_>> for RunConfig {
 	fn from(m: &ArgMatches<'_>) -> Self {
 		let skip_ra = m.is_present("skip-ra");
 		let dev = m.is_present("dev");
-		let shard = m.value_of("shard").map(|s| s.to_string());
+		let shards = m
+			.values_of("shard")
+			.map(|values| values.map(|s| s.to_string()).collect())
+			.unwrap_or_default();
 		let teeracle_update_interval = m.value_of("teeracle-interval").map(|i| {
 			parse(i).unwrap_or_else(|e| panic!("teeracle-interval parsing error {:?}", e))
 		});
 		let reregister_teeracle_interval = m.value_of("reregister-teeracle-interval").map(|i| {
 			parse(i).unwrap_or_else(|e| panic!("teeracle-interval parsing error {:?}", e))
 		});
+		let reattestation_interval = m.value_of("reattestation-interval").map(|i| {
+			parse(i).unwrap_or_else(|e| panic!("reattestation-interval parsing error {:?}", e))
+		});
 
 		let marblerun_base_url = m.value_of("marblerun-url").map(|i| {
 			Url::parse(i)
@@ -339,18 +429,61 @@ impl From<&ArgMatches<'_>> for RunConfig {
 				i
 			),
 		});
+		let sidechain_pruning_policy =
+			m.value_of("sidechain-pruning").map(parse_sidechain_pruning_policy);
+		let rpc_max_connections = parse_rpc_limit(m, "rpc-max-connections");
+		let rpc_max_requests_per_ip = parse_rpc_limit(m, "rpc-max-requests-per-ip");
+		let rpc_max_requests_per_connection = parse_rpc_limit(m, "rpc-max-requests-per-connection");
+		let rpc_max_message_size = parse_rpc_limit(m, "rpc-max-message-size");
+		let rpc_idle_timeout = m
+			.value_of("rpc-idle-timeout")
+			.map(|i| parse(i).unwrap_or_else(|e| panic!("rpc-idle-timeout parsing error {:?}", e)));
+		let rpc_max_outbound_queue = parse_rpc_limit(m, "rpc-max-outbound-queue");
+		let rpc_max_batch_size = parse_rpc_limit(m, "rpc-max-batch-size");
 		Self {
 			skip_ra,
 			dev,
-			shard,
+			shards,
 			teeracle_update_interval,
 			reregister_teeracle_interval,
+			reattestation_interval,
 			marblerun_base_url,
 			shielding_target,
+			sidechain_pruning_policy,
+			rpc_max_connections,
+			rpc_max_requests_per_ip,
+			rpc_max_requests_per_connection,
+			rpc_max_message_size,
+			rpc_idle_timeout,
+			rpc_max_outbound_queue,
+			rpc_max_batch_size,
 		}
 	}
 }
 
+fn parse_rpc_limit(m: &ArgMatches<'_>, name: &str) -> Option<u32> {
+	m.value_of(name)
+		.map(|limit| limit.parse().unwrap_or_else(|e| panic!("{} parsing error {:?}", name, e)))
+}
+
+fn parse_sidechain_pruning_policy(policy: &str) -> PruningPolicy {
+	match policy.split_once(':') {
+		None if policy == "archive" => PruningPolicy::Archive,
+		Some(("blocks", number_of_blocks)) => PruningPolicy::KeepLastBlocks(
+			number_of_blocks
+				.parse()
+				.unwrap_or_else(|e| panic!("sidechain-pruning block count parsing error {:?}", e)),
+		),
+		Some(("age", age)) => PruningPolicy::KeepBlocksNewerThan(
+			parse(age).unwrap_or_else(|e| panic!("sidechain-pruning age parsing error {:?}", e)),
+		),
+		_ => panic!(
+			"failed to parse sidechain-pruning: {} must be one of archive|blocks:N|age:DURATION",
+			policy
+		),
+	}
+}
+
 fn add_port_if_necessary(url: &str, port: &str) -> String {
 	// [Option("ws(s)"), ip, Option(port)]
 	match url.split(':').count() {
@@ -399,7 +532,11 @@ mod test {
 		assert!(config.mu_ra_external_address.is_none());
 		assert!(!config.enable_metrics_server);
 		assert_eq!(config.untrusted_http_port, DEFAULT_UNTRUSTED_HTTP_PORT);
+		assert!(!config.enable_admin_rpc);
+		assert_eq!(config.admin_rpc_port, DEFAULT_ADMIN_RPC_PORT);
+		assert!(config.otlp_endpoint.is_none());
 		assert_eq!(config.data_dir, pwd());
+		assert!(!config.state_migration_dry_run);
 		assert!(config.run_config.is_none());
 	}
 
@@ -414,6 +551,27 @@ mod test {
 		assert_eq!(config.worker_ip, expected_worker_ip);
 	}
 
+	#[test]
+	fn state_migration_dry_run_is_set_for_flag() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("state-migration-dry-run", Default::default())]);
+		let config = Config::from(&args);
+
+		assert!(config.state_migration_dry_run());
+	}
+
+	#[test]
+	fn otlp_endpoint_is_set_correctly() {
+		let endpoint = "http://localhost:4317";
+
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("otlp-endpoint", Default::default())]);
+		args.args.get_mut("otlp-endpoint").unwrap().vals = vec![endpoint.into()];
+		let config = Config::from(&args);
+
+		assert_eq!(config.otlp_endpoint(), Some(endpoint));
+	}
+
 	#[test]
 	fn check_correct_config_assignment_for_given_input() {
 		let node_ip = "ws://12.1.58.1";
@@ -471,8 +629,12 @@ mod test {
 
 		assert_eq!(run_config.dev, false);
 		assert_eq!(run_config.skip_ra, false);
-		assert!(run_config.shard.is_none());
+		assert!(run_config.shards.is_empty());
 		assert!(run_config.teeracle_update_interval.is_none());
+		assert_eq!(
+			run_config.sidechain_pruning_policy(),
+			PruningPolicy::KeepLastBlocks(SIDECHAIN_PURGE_LIMIT)
+		);
 	}
 
 	#[test]
@@ -494,10 +656,76 @@ mod test {
 
 		assert_eq!(run_config.dev, true);
 		assert_eq!(run_config.skip_ra, true);
-		assert_eq!(run_config.shard.unwrap(), shard_identifier.to_string());
+		assert_eq!(run_config.shards, vec![shard_identifier.to_string()]);
 		assert_eq!(run_config.teeracle_update_interval.unwrap(), Duration::from_secs(42));
 	}
 
+	#[test]
+	fn reattestation_interval_falls_back_to_teeracle_reregistration_interval() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("reregister-teeracle-interval", Default::default())]);
+		// Workaround because MatchedArg is private.
+		args.args.get_mut("reregister-teeracle-interval").unwrap().vals = vec!["2h".into()];
+
+		let run_config = RunConfig::from(&args);
+		assert_eq!(run_config.reattestation_interval(), Duration::from_secs(2 * 60 * 60));
+
+		args.args.insert("reattestation-interval", Default::default());
+		args.args.get_mut("reattestation-interval").unwrap().vals = vec!["1h".into()];
+
+		let run_config = RunConfig::from(&args);
+		assert_eq!(run_config.reattestation_interval(), Duration::from_secs(60 * 60));
+	}
+
+	#[test]
+	fn sidechain_pruning_policy_parsing_works() {
+		assert_eq!(parse_sidechain_pruning_policy("archive"), PruningPolicy::Archive);
+		assert_eq!(parse_sidechain_pruning_policy("blocks:42"), PruningPolicy::KeepLastBlocks(42));
+		assert_eq!(
+			parse_sidechain_pruning_policy("age:2h"),
+			PruningPolicy::KeepBlocksNewerThan(Duration::from_secs(2 * 60 * 60))
+		);
+	}
+
+	#[test]
+	#[should_panic]
+	fn sidechain_pruning_policy_parsing_fails_for_unknown_policy() {
+		parse_sidechain_pruning_policy("blocks");
+	}
+
+	#[test]
+	fn trusted_rpc_server_limits_parsing_works() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([
+			("rpc-max-connections", Default::default()),
+			("rpc-idle-timeout", Default::default()),
+			("rpc-max-batch-size", Default::default()),
+		]);
+		// Workaround because MatchedArg is private.
+		args.args.get_mut("rpc-max-connections").unwrap().vals = vec!["10".into()];
+		args.args.get_mut("rpc-idle-timeout").unwrap().vals = vec!["1m".into()];
+		args.args.get_mut("rpc-max-batch-size").unwrap().vals = vec!["5".into()];
+
+		let limits = RunConfig::from(&args).trusted_rpc_server_limits();
+
+		assert_eq!(limits.max_connections, Some(10));
+		assert_eq!(limits.idle_timeout, Some(60));
+		assert_eq!(limits.max_batch_size, Some(5));
+		assert_eq!(limits.max_requests_per_second_per_ip, None);
+	}
+
+	#[test]
+	fn run_config_parses_multiple_shards() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("shard", Default::default())]);
+		// Workaround because MatchedArg is private.
+		args.args.get_mut("shard").unwrap().vals = vec!["shard-1".into(), "shard-2".into()];
+
+		let run_config = RunConfig::from(&args);
+
+		assert_eq!(run_config.shards(), &["shard-1".to_string(), "shard-2".to_string()]);
+	}
+
 	#[test]
 	fn external_addresses_are_returned_correctly_if_not_set() {
 		let trusted_port = "7119";
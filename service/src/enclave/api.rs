@@ -108,6 +108,7 @@ pub fn enclave_init(config: &Config) -> EnclaveResult<Enclave> {
 		&config.mu_ra_url_external(),
 		&config.untrusted_worker_url_external(),
 		&config.data_dir().display().to_string(),
+		config.state_migration_dry_run(),
 	)?;
 
 	Ok(enclave_api)
@@ -0,0 +1,66 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{config::Config, ocall_bridge::bridge_api::Bridge};
+use codec::Encode;
+use itp_enclave_api::{enclave_base::EnclaveBase, error::Error, EnclaveResult};
+use itp_enclave_api_mock::{MockEnclave, SendToParentchain};
+use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider};
+use itp_types::parentchain::ParentchainId;
+use sp_runtime::OpaqueExtrinsic;
+use std::sync::Arc;
+
+/// Hands the extrinsics of the mock enclave to the same OCALL bridge the enclave uses.
+struct OCallBridgeParentchainSender;
+
+impl SendToParentchain for OCallBridgeParentchainSender {
+	fn send_to_parentchain(
+		&self,
+		extrinsics: Vec<OpaqueExtrinsic>,
+		parentchain_id: &ParentchainId,
+		await_each_inclusion: bool,
+	) -> EnclaveResult<()> {
+		Bridge::get_oc_api()
+			.send_to_parentchain(extrinsics.encode(), parentchain_id.encode(), await_each_inclusion)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))
+	}
+}
+
+/// Creates the in-process mock enclave, which stores its keys in the data directory.
+///
+/// The mock enclave only supports the offchain-worker mode.
+pub fn enclave_init(config: &Config) -> EnclaveResult<MockEnclave> {
+	if WorkerModeProvider::worker_mode() != WorkerMode::OffChainWorker {
+		return Err(Error::Other(
+			format!(
+				"the mock enclave does not support the {:?} mode, build with the `offchain-worker` feature",
+				WorkerModeProvider::worker_mode()
+			)
+			.into(),
+		))
+	}
+
+	let enclave_api = MockEnclave::new(config.data_dir(), Arc::new(OCallBridgeParentchainSender))?;
+	enclave_api.init(
+		&config.mu_ra_url_external(),
+		&config.untrusted_worker_url_external(),
+		&config.data_dir().display().to_string(),
+		config.state_migration_dry_run(),
+	)?;
+
+	Ok(enclave_api)
+}
@@ -17,4 +17,16 @@
 
 #[cfg(feature = "link-binary")]
 pub mod api;
+#[cfg(all(feature = "mock-enclave", not(feature = "link-binary")))]
+pub mod mock_api;
 pub mod tls_ra;
+
+#[cfg(feature = "link-binary")]
+pub use api::enclave_init;
+#[cfg(feature = "link-binary")]
+pub use itp_enclave_api::Enclave;
+
+#[cfg(all(feature = "mock-enclave", not(feature = "link-binary")))]
+pub use itp_enclave_api_mock::MockEnclave as Enclave;
+#[cfg(all(feature = "mock-enclave", not(feature = "link-binary")))]
+pub use mock_api::enclave_init;
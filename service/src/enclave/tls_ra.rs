@@ -76,6 +76,7 @@ pub fn enclave_request_state_provisioning<E: TlsRemoteAttestation + RemoteAttest
 	addr: &str,
 	shard: &ShardIdentifier,
 	skip_ra: bool,
+	shielding_key_only: bool,
 ) -> EnclaveResult<()> {
 	info!("[MU-RA-Client] Requesting key provisioning from {}", addr);
 
@@ -106,5 +107,6 @@ pub fn enclave_request_state_provisioning<E: TlsRemoteAttestation + RemoteAttest
 		quote_size.as_ref(),
 		shard,
 		skip_ra,
+		shielding_key_only,
 	)
 }
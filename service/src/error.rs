@@ -42,6 +42,8 @@ pub enum Error {
 	ApplicationSetup,
 	#[error("Failed to find any peer worker")]
 	NoPeerWorkerFound,
+	#[error("Our enclave is not registered on the parentchain")]
+	EnclaveNotRegistered,
 	#[error("No worker for shard {0} found on parentchain")]
 	NoWorkerForShardFound(ShardIdentifier),
 	#[error("Returned empty parentchain block vec after sync, even though there have been blocks given as input")]
@@ -17,23 +17,34 @@
 
 //! Service to determine if the integritee services is initialized and registered on the node,
 //! hosted on a http server.
+//!
+//! Besides the initialization, the same server reports the health of the running worker:
+//! `/health` answers the liveness and `/ready` the readiness probe of an orchestrator.
 
-use crate::error::ServiceResult;
+use crate::{error::ServiceResult, prometheus_metrics::top_pool_size};
 use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode};
+use itp_time_utils::now_as_millis;
+use itp_types::parentchain::{BlockNumber as ParentchainBlockNumber, ParentchainId};
+use its_primitives::types::BlockNumber as SidechainBlockNumber;
 use log::*;
 use parking_lot::RwLock;
+use serde::Serialize;
 use std::{default::Default, marker::PhantomData, net::SocketAddr, sync::Arc};
-use warp::Filter;
+use warp::{http::StatusCode, Filter};
+
+/// Maximum number of finalized parentchain blocks a worker may lag behind to be considered ready.
+pub const MAX_READY_PARENTCHAIN_SYNC_LAG: ParentchainBlockNumber = 10;
 
 pub async fn start_is_initialized_server<Handler>(
 	initialization_handler: Arc<Handler>,
 	port: u16,
 ) -> ServiceResult<()>
 where
-	Handler: IsInitialized + Send + Sync + 'static,
+	Handler: IsInitialized + ReportHealth + Send + Sync + 'static,
 {
+	let is_initialized_handler = initialization_handler.clone();
 	let is_initialized_route = warp::path!("is_initialized").and_then(move || {
-		let handler_clone = initialization_handler.clone();
+		let handler_clone = is_initialized_handler.clone();
 		async move {
 			if handler_clone.is_initialized() {
 				Ok("I am initialized.")
@@ -43,10 +54,26 @@ where
 		}
 	});
 
+	let liveness_handler = initialization_handler.clone();
+	let health_route = warp::path!("health").map(move || {
+		let report = liveness_handler.health_report();
+		let status = if report.live { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+		warp::reply::with_status(warp::reply::json(&report), status)
+	});
+
+	let readiness_handler = initialization_handler;
+	let ready_route = warp::path!("ready").map(move || {
+		let report = readiness_handler.health_report();
+		let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+		warp::reply::with_status(warp::reply::json(&report), status)
+	});
+
 	let socket_addr: SocketAddr = ([0, 0, 0, 0], port).into();
 
 	info!("Running initialized server on: {:?}", socket_addr);
-	warp::serve(is_initialized_route).run(socket_addr).await;
+	warp::serve(is_initialized_route.or(health_route).or(ready_route))
+		.run(socket_addr)
+		.await;
 
 	info!("Initialized server shut down");
 	Ok(())
@@ -66,10 +93,138 @@ pub trait TrackInitialization {
 	fn worker_for_shard_registered(&self);
 }
 
+/// Tracker for the health of a running worker. Used by the components that sync the
+/// parentchains, produce and import sidechain blocks, update the peers and register the enclave.
+pub trait TrackHealth {
+	fn parentchain_head_finalized(
+		&self,
+		parentchain_id: ParentchainId,
+		block_number: ParentchainBlockNumber,
+	);
+
+	fn parentchain_blocks_imported(
+		&self,
+		parentchain_id: ParentchainId,
+		block_number: ParentchainBlockNumber,
+	);
+
+	fn parentchain_sync_terminated(&self, parentchain_id: ParentchainId);
+
+	fn sidechain_block_proposed(&self, block_number: SidechainBlockNumber);
+
+	fn sidechain_block_imported(&self, block_number: SidechainBlockNumber);
+
+	fn peers_updated(&self, peer_count: usize);
+
+	/// Timestamps are unix timestamps in milliseconds, as stored by the teerex pallet.
+	fn attestation_registered(&self, attestation_timestamp: u64, expires_at: Option<u64>);
+}
+
+/// Trait to query the health report of a worker.
+pub trait ReportHealth {
+	fn health_report(&self) -> HealthReport;
+}
+
+/// Health report of a worker, served as JSON.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HealthReport {
+	/// The worker is initialized, see [`IsInitialized`].
+	pub initialized: bool,
+	/// None of the parentchain sync loops has terminated.
+	pub live: bool,
+	/// The worker is live, initialized, its attestation has not expired and it does not lag behind
+	/// any parentchain by more than [`MAX_READY_PARENTCHAIN_SYNC_LAG`] blocks.
+	pub ready: bool,
+	pub parentchains: Vec<ParentchainHealth>,
+	pub last_produced_sidechain_block: Option<SidechainBlockHealth>,
+	pub last_imported_sidechain_block: Option<SidechainBlockHealth>,
+	pub peer_count: usize,
+	pub top_pool_size: u64,
+	pub attestation: Option<AttestationHealth>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ParentchainHealth {
+	pub id: ParentchainId,
+	pub latest_finalized: Option<ParentchainBlockNumber>,
+	pub last_imported: Option<ParentchainBlockNumber>,
+	pub sync_lag: Option<ParentchainBlockNumber>,
+	pub sync_terminated: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SidechainBlockHealth {
+	pub number: SidechainBlockNumber,
+	/// Seconds since the block was produced or imported by this worker.
+	pub age_secs: u64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AttestationHealth {
+	pub registered_at: u64,
+	pub expires_at: Option<u64>,
+	pub expires_in_secs: Option<i64>,
+}
+
+#[derive(Clone, Debug)]
+struct ParentchainSyncStatus {
+	id: ParentchainId,
+	latest_finalized: Option<ParentchainBlockNumber>,
+	last_imported: Option<ParentchainBlockNumber>,
+	sync_terminated: bool,
+}
+
+impl ParentchainSyncStatus {
+	fn new(id: ParentchainId) -> Self {
+		Self { id, latest_finalized: None, last_imported: None, sync_terminated: false }
+	}
+
+	fn sync_lag(&self) -> Option<ParentchainBlockNumber> {
+		let latest_finalized = self.latest_finalized?;
+		Some(latest_finalized.saturating_sub(self.last_imported.unwrap_or_default()))
+	}
+}
+
+/// Block number and the time (unix timestamp in milliseconds) it was recorded at.
+type RecordedSidechainBlock = (SidechainBlockNumber, u64);
+
+#[derive(Default)]
+struct HealthStatus {
+	parentchains: Vec<ParentchainSyncStatus>,
+	last_produced_sidechain_block: Option<RecordedSidechainBlock>,
+	last_imported_sidechain_block: Option<RecordedSidechainBlock>,
+	peer_count: usize,
+	attestation: Option<(u64, Option<u64>)>,
+}
+
+impl HealthStatus {
+	fn parentchain_mut(&mut self, parentchain_id: ParentchainId) -> &mut ParentchainSyncStatus {
+		let index = match self.parentchains.iter().position(|p| p.id == parentchain_id) {
+			Some(index) => index,
+			None => {
+				self.parentchains.push(ParentchainSyncStatus::new(parentchain_id));
+				self.parentchains.len() - 1
+			},
+		};
+		&mut self.parentchains[index]
+	}
+}
+
+fn sidechain_block_health(
+	recorded_block: Option<RecordedSidechainBlock>,
+	now: u64,
+) -> Option<SidechainBlockHealth> {
+	recorded_block.map(|(number, recorded_at)| SidechainBlockHealth {
+		number,
+		age_secs: now.saturating_sub(recorded_at) / 1000,
+	})
+}
+
 pub struct InitializationHandler<WorkerModeProvider> {
 	registered_on_parentchain: RwLock<bool>,
 	sidechain_block_produced: RwLock<bool>,
 	worker_for_shard_registered: RwLock<bool>,
+	health: RwLock<HealthStatus>,
 	_phantom: PhantomData<WorkerModeProvider>,
 }
 
@@ -83,6 +238,7 @@ impl<WorkerModeProvider> Default for InitializationHandler<WorkerModeProvider> {
 			registered_on_parentchain: Default::default(),
 			sidechain_block_produced: Default::default(),
 			worker_for_shard_registered: Default::default(),
+			health: Default::default(),
 			_phantom: Default::default(),
 		}
 	}
@@ -120,6 +276,99 @@ where
 	}
 }
 
+impl<WorkerModeProvider> TrackHealth for InitializationHandler<WorkerModeProvider> {
+	fn parentchain_head_finalized(
+		&self,
+		parentchain_id: ParentchainId,
+		block_number: ParentchainBlockNumber,
+	) {
+		self.health.write().parentchain_mut(parentchain_id).latest_finalized = Some(block_number);
+	}
+
+	fn parentchain_blocks_imported(
+		&self,
+		parentchain_id: ParentchainId,
+		block_number: ParentchainBlockNumber,
+	) {
+		self.health.write().parentchain_mut(parentchain_id).last_imported = Some(block_number);
+	}
+
+	fn parentchain_sync_terminated(&self, parentchain_id: ParentchainId) {
+		self.health.write().parentchain_mut(parentchain_id).sync_terminated = true;
+	}
+
+	fn sidechain_block_proposed(&self, block_number: SidechainBlockNumber) {
+		self.health.write().last_produced_sidechain_block = Some((block_number, now_as_millis()));
+	}
+
+	fn sidechain_block_imported(&self, block_number: SidechainBlockNumber) {
+		self.health.write().last_imported_sidechain_block = Some((block_number, now_as_millis()));
+	}
+
+	fn peers_updated(&self, peer_count: usize) {
+		self.health.write().peer_count = peer_count;
+	}
+
+	fn attestation_registered(&self, attestation_timestamp: u64, expires_at: Option<u64>) {
+		self.health.write().attestation = Some((attestation_timestamp, expires_at));
+	}
+}
+
+impl<WorkerModeProvider> ReportHealth for InitializationHandler<WorkerModeProvider>
+where
+	WorkerModeProvider: ProvideWorkerMode,
+{
+	fn health_report(&self) -> HealthReport {
+		let initialized = self.is_initialized();
+		let now = now_as_millis();
+		let health = self.health.read();
+
+		let parentchains: Vec<ParentchainHealth> = health
+			.parentchains
+			.iter()
+			.map(|p| ParentchainHealth {
+				id: p.id,
+				latest_finalized: p.latest_finalized,
+				last_imported: p.last_imported,
+				sync_lag: p.sync_lag(),
+				sync_terminated: p.sync_terminated,
+			})
+			.collect();
+
+		let attestation = health.attestation.map(|(registered_at, expires_at)| AttestationHealth {
+			registered_at,
+			expires_at,
+			expires_in_secs: expires_at.map(|e| (e as i64 - now as i64) / 1000),
+		});
+
+		let live = !parentchains.iter().any(|p| p.sync_terminated);
+		let parentchains_synced = parentchains
+			.iter()
+			.all(|p| p.sync_lag.map_or(false, |lag| lag <= MAX_READY_PARENTCHAIN_SYNC_LAG));
+		let attestation_valid = attestation
+			.as_ref()
+			.map_or(true, |a| a.expires_in_secs.map_or(true, |secs| secs > 0));
+
+		HealthReport {
+			initialized,
+			live,
+			ready: live && initialized && parentchains_synced && attestation_valid,
+			parentchains,
+			last_produced_sidechain_block: sidechain_block_health(
+				health.last_produced_sidechain_block,
+				now,
+			),
+			last_imported_sidechain_block: sidechain_block_health(
+				health.last_imported_sidechain_block,
+				now,
+			),
+			peer_count: health.peer_count,
+			top_pool_size: top_pool_size(),
+			attestation,
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -169,4 +418,92 @@ mod tests {
 		sidechain_handler.sidechain_block_produced();
 		assert!(sidechain_handler.is_initialized());
 	}
+
+	#[test]
+	fn default_handler_is_live_but_not_ready() {
+		let handler = InitializationHandler::<OffchainWorkerMode>::default();
+
+		let report = handler.health_report();
+
+		assert!(report.live);
+		assert!(!report.ready);
+		assert!(report.parentchains.is_empty());
+		assert_eq!(report.last_produced_sidechain_block, None);
+	}
+
+	#[test]
+	fn initialized_and_synced_handler_is_ready() {
+		let handler = InitializationHandler::<OffchainWorkerMode>::default();
+		handler.registered_on_parentchain();
+		handler.parentchain_head_finalized(ParentchainId::Integritee, 100);
+		handler.parentchain_blocks_imported(
+			ParentchainId::Integritee,
+			100 - MAX_READY_PARENTCHAIN_SYNC_LAG,
+		);
+
+		let report = handler.health_report();
+
+		assert!(report.ready);
+		assert_eq!(report.parentchains[0].sync_lag, Some(MAX_READY_PARENTCHAIN_SYNC_LAG));
+	}
+
+	#[test]
+	fn lagging_parentchain_sync_is_not_ready() {
+		let handler = InitializationHandler::<OffchainWorkerMode>::default();
+		handler.registered_on_parentchain();
+		handler.parentchain_head_finalized(ParentchainId::Integritee, 100);
+		handler.parentchain_blocks_imported(ParentchainId::Integritee, 100);
+		handler.parentchain_head_finalized(ParentchainId::TargetA, 100);
+		handler.parentchain_blocks_imported(ParentchainId::TargetA, 50);
+
+		let report = handler.health_report();
+
+		assert!(report.live);
+		assert!(!report.ready);
+		assert_eq!(report.parentchains.len(), 2);
+		assert_eq!(report.parentchains[1].sync_lag, Some(50));
+	}
+
+	#[test]
+	fn terminated_parentchain_sync_is_not_live() {
+		let handler = InitializationHandler::<OffchainWorkerMode>::default();
+		handler.registered_on_parentchain();
+		handler.parentchain_head_finalized(ParentchainId::Integritee, 100);
+		handler.parentchain_blocks_imported(ParentchainId::Integritee, 100);
+		handler.parentchain_sync_terminated(ParentchainId::Integritee);
+
+		let report = handler.health_report();
+
+		assert!(!report.live);
+		assert!(!report.ready);
+	}
+
+	#[test]
+	fn expired_attestation_is_not_ready() {
+		let handler = InitializationHandler::<OffchainWorkerMode>::default();
+		handler.registered_on_parentchain();
+		handler.parentchain_head_finalized(ParentchainId::Integritee, 100);
+		handler.parentchain_blocks_imported(ParentchainId::Integritee, 100);
+		let now = now_as_millis();
+		handler.attestation_registered(now - 20_000, Some(now - 10_000));
+
+		let report = handler.health_report();
+
+		assert!(!report.ready);
+		assert!(report.attestation.unwrap().expires_in_secs.unwrap() <= -10);
+	}
+
+	#[test]
+	fn sidechain_blocks_and_peers_are_reported() {
+		let handler = InitializationHandler::<SidechainWorkerMode>::default();
+		handler.sidechain_block_proposed(5);
+		handler.sidechain_block_imported(4);
+		handler.peers_updated(3);
+
+		let report = handler.health_report();
+
+		assert_eq!(report.last_produced_sidechain_block.unwrap().number, 5);
+		assert_eq!(report.last_imported_sidechain_block.unwrap().number, 4);
+		assert_eq!(report.peer_count, 3);
+	}
 }
@@ -19,6 +19,9 @@
 #![allow(unused)]
 
 mod account_funding;
+mod admin_rpc;
+mod attestation_renewal;
+mod collateral_cache;
 mod config;
 mod enclave;
 mod error;
@@ -28,25 +31,30 @@ mod ocall_bridge;
 mod parentchain_handler;
 mod prometheus_metrics;
 mod setup;
+mod shard_manager;
 mod sidechain_setup;
 mod sync_block_broadcaster;
 mod sync_state;
+#[cfg(feature = "dcap")]
+mod tcb_status;
 #[cfg(feature = "teeracle")]
 mod teeracle;
+mod telemetry;
 mod tests;
 mod utils;
 mod worker;
+mod worker_admin;
 mod worker_peers_updater;
 
-#[cfg(feature = "link-binary")]
+#[cfg(any(feature = "link-binary", feature = "mock-enclave"))]
 mod main_impl;
 
-#[cfg(feature = "link-binary")]
+#[cfg(any(feature = "link-binary", feature = "mock-enclave"))]
 fn main() {
 	main_impl::main();
 }
 
-#[cfg(not(feature = "link-binary"))]
+#[cfg(not(any(feature = "link-binary", feature = "mock-enclave")))]
 fn main() {
-	panic!("tried to run the binary without linking. Make sure to pass `--feature link-binary`")
+	panic!("tried to run the binary without linking. Make sure to pass `--feature link-binary`, or `--feature mock-enclave` to run without SGX")
 }
@@ -1,38 +1,51 @@
 #[cfg(feature = "teeracle")]
-use crate::teeracle::{schedule_periodic_reregistration_thread, start_periodic_market_update};
+use crate::teeracle::start_periodic_market_update;
 
 #[cfg(not(feature = "dcap"))]
 use crate::utils::check_files;
 use crate::{
 	account_funding::{setup_reasonable_account_funding, EnclaveAccountInfoProvider},
+	admin_rpc::{load_or_create_admin_rpc_token, start_admin_rpc_server},
+	attestation_renewal::{
+		fetch_enclave_registration, record_registration, schedule_periodic_reattestation_thread,
+		RegistrationTracker,
+	},
+	collateral_cache::CollateralCache,
 	config::Config,
 	enclave::{
-		api::enclave_init,
+		enclave_init,
 		tls_ra::{enclave_request_state_provisioning, enclave_run_state_provisioning_server},
+		Enclave,
 	},
 	error::Error,
 	globals::tokio_handle::{GetTokioHandle, GlobalTokioHandle},
 	initialized_service::{
-		start_is_initialized_server, InitializationHandler, IsInitialized, TrackInitialization,
+		start_is_initialized_server, InitializationHandler, IsInitialized, ReportHealth,
+		TrackHealth, TrackInitialization,
 	},
 	ocall_bridge::{
 		bridge_api::Bridge as OCallBridge, component_factory::OCallBridgeComponentFactory,
 	},
 	parentchain_handler::{HandleParentchain, ParentchainHandler},
-	prometheus_metrics::{start_metrics_server, EnclaveMetricsReceiver, MetricsHandler},
+	prometheus_metrics::{
+		set_parentchain_import_lag, start_metrics_server, EnclaveMetricsReceiver, MetricsHandler,
+	},
 	setup,
+	shard_manager::{init_provided_shard_vault, init_shard_for_worker, ShardManager, ShardSet},
 	sidechain_setup::{sidechain_init_block_production, sidechain_start_untrusted_rpc_server},
 	sync_block_broadcaster::SyncBlockBroadcaster,
-	sync_state, tests,
-	utils::extract_shard,
+	sync_state, telemetry,
+	utils::{extract_shard, extract_shards},
 	worker::Worker,
-	worker_peers_updater::WorkerPeersUpdater,
+	worker_admin::WorkerAdmin,
+	worker_peers_updater::{UpdateWorkerPeers, WorkerPeersUpdater},
 };
 use base58::ToBase58;
 use clap::{load_yaml, App, ArgMatches};
 use codec::{Decode, Encode};
 use ita_parentchain_interface::integritee::{Hash, Header};
 use itp_enclave_api::{
+	admin_api::AdminApi,
 	direct_request::DirectRequest,
 	enclave_base::EnclaveBase,
 	remote_attestation::{RemoteAttestation, TlsRemoteAttestation},
@@ -44,41 +57,49 @@ use itp_node_api::{
 	metadata::NodeMetadata,
 	node_api_factory::{CreateNodeApi, NodeApiFactory},
 };
-use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider};
+use itp_settings::{
+	files::{DCAP_COLLATERAL_CACHE_PATH, DCAP_COLLATERAL_PATH},
+	worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider},
+};
+use its_consensus_common::block_production_suspension::BlockProductionSuspender;
 use its_peer_fetch::{
 	block_fetch_client::BlockFetcher, untrusted_peer_fetch::UntrustedPeerFetcher,
+	FetchBlocksFromPeer,
 };
-use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+use its_primitives::types::{block::SignedBlock as SignedSidechainBlock, BlockNumber};
 use its_storage::{interface::FetchBlocks, BlockPruner, SidechainStorageLock};
 use log::*;
 use regex::Regex;
 use sgx_types::*;
 use sp_runtime::traits::Header as HeaderT;
 use substrate_api_client::{
-	api::XtStatus, rpc::HandleSubscription, GetAccountInformation, GetBalance, GetChainInfo,
-	SubmitAndWatch, SubscribeChain, SubscribeEvents,
+	api::XtStatus, rpc::HandleSubscription, GetBalance, GetChainInfo, GetStorage, SubmitAndWatch,
+	SubscribeChain, SubscribeEvents,
 };
 
-use teerex_primitives::{AnySigner, MultiEnclave};
+use teerex_primitives::MultiEnclave;
 
+#[cfg(feature = "dcap")]
+use crate::{
+	collateral_cache::fetch_collateral_or_use_cached,
+	tcb_status::{evaluate_tcb_status, TcbStatusTracker},
+};
 #[cfg(feature = "dcap")]
 use sgx_verify::extract_tcb_info_from_raw_dcap_quote;
 
-use itp_enclave_api::Enclave;
-
-use crate::{account_funding::shard_vault_initial_funds, error::ServiceResult};
+use crate::error::ServiceResult;
 use enclave_bridge_primitives::ShardIdentifier;
 use itc_parentchain::primitives::ParentchainId;
-use itp_types::parentchain::{AccountId, Balance};
-use sp_core::crypto::{AccountId32, Ss58Codec};
+use sp_core::{
+	crypto::{AccountId32, Ss58Codec},
+	ed25519,
+};
 use sp_keyring::AccountKeyring;
-use sp_runtime::MultiSigner;
 use std::{fmt::Debug, path::PathBuf, str, str::Utf8Error, sync::Arc, thread, time::Duration};
 use substrate_api_client::ac_node_api::{EventRecord, Phase::ApplyExtrinsic};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[cfg(feature = "link-binary")]
 pub type EnclaveWorker =
 	Worker<Config, NodeApiFactory, Enclave, InitializationHandler<WorkerModeProvider>>;
 
@@ -95,6 +116,11 @@ pub(crate) fn main() {
 
 	GlobalTokioHandle::initialize();
 
+	if let Some(otlp_endpoint) = config.otlp_endpoint() {
+		telemetry::init_otlp_export(otlp_endpoint, &GlobalTokioHandle {}.get_handle())
+			.expect("Failed to set up the OTLP export of tracing spans");
+	}
+
 	// log this information, don't println because some python scripts for GA rely on the
 	// stdout from the service
 	#[cfg(feature = "production")]
@@ -129,6 +155,13 @@ pub(crate) fn main() {
 		AccountKeyring::Alice.pair(),
 	));
 	let enclave = Arc::new(enclave_init(&config).unwrap());
+	if config.state_migration_dry_run() {
+		info!("State migration dry-run finished, see the enclave log for the outcome per shard");
+		return
+	}
+	if config.otlp_endpoint().is_some() {
+		enclave.enable_tracing().expect("Failed to enable tracing in the enclave");
+	}
 	let initialization_handler = Arc::new(InitializationHandler::default());
 	let worker = Arc::new(EnclaveWorker::new(
 		config.clone(),
@@ -161,10 +194,13 @@ pub(crate) fn main() {
 		sync_block_broadcaster,
 		enclave.clone(),
 		sidechain_blockstorage.clone(),
-		peer_updater,
-		peer_sidechain_block_fetcher,
+		peer_updater.clone(),
+		peer_sidechain_block_fetcher.clone(),
 		tokio_handle.clone(),
 		enclave_metrics_receiver,
+		initialization_handler.clone(),
+		config.data_dir().join(DCAP_COLLATERAL_PATH),
+		config.data_dir().join(DCAP_COLLATERAL_CACHE_PATH),
 	)));
 
 	let quoting_enclave_target_info = match enclave.qe_get_target_info() {
@@ -183,22 +219,26 @@ pub(crate) fn main() {
 	};
 
 	if let Some(run_config) = config.run_config() {
-		let shard = extract_shard(run_config.shard(), enclave.as_ref());
+		let shard_set = load_shard_set(&config, run_config.shards(), enclave.as_ref());
 
 		println!("Worker Config: {:?}", config);
 
 		if clean_reset {
-			setup::initialize_shard_and_keys(enclave.as_ref(), &shard).unwrap();
+			for shard in shard_set.handled.iter() {
+				setup::initialize_shard_and_keys(enclave.as_ref(), shard).unwrap();
+			}
 		}
 
 		let node_api =
 			node_api_factory.create_api().expect("Failed to create parentchain node API");
 
-		start_worker::<_, _, _, _, WorkerModeProvider>(
+		start_worker::<_, _, _, _, _, _, WorkerModeProvider>(
 			config,
-			&shard,
+			&shard_set,
 			enclave,
 			sidechain_blockstorage,
+			peer_updater,
+			peer_sidechain_block_fetcher,
 			node_api,
 			tokio_handle,
 			initialization_handler,
@@ -209,12 +249,23 @@ pub(crate) fn main() {
 		println!("*** Requesting state from a registered worker \n");
 		let node_api =
 			node_api_factory.create_api().expect("Failed to create parentchain node API");
+		let shard = extract_shard(smatches.value_of("shard"), enclave.as_ref());
+		// The provisioned state is verified against the sidechain block confirmed on the
+		// parentchain, which the enclave reads through its light client.
+		init_parentchain(
+			&enclave,
+			&node_api,
+			&enclave_account(enclave.as_ref()),
+			ParentchainId::Integritee,
+			&shard,
+		);
 		sync_state::sync_state::<_, _, WorkerModeProvider>(
 			&node_api,
-			&extract_shard(smatches.value_of("shard"), enclave.as_ref()),
+			&shard,
 			enclave.as_ref(),
 			smatches.is_present("skip-ra"),
-		);
+		)
+		.expect("State provisioning failed");
 	} else if matches.is_present("shielding-key") {
 		setup::generate_shielding_key_file(enclave.as_ref());
 	} else if matches.is_present("signing-key") {
@@ -231,6 +282,48 @@ pub(crate) fn main() {
 			enclave.dump_dcap_collateral_to_disk(fmspc).unwrap();
 			enclave.dump_dcap_ra_cert_to_disk().unwrap();
 		}
+	} else if let Some(sub_matches) = matches.subcommand_matches("import-collateral") {
+		let source = PathBuf::from(sub_matches.value_of("dir").expect("dir is required; qed"));
+		let collateral_cache =
+			CollateralCache::new(config.data_dir().join(DCAP_COLLATERAL_CACHE_PATH));
+		match collateral_cache.import_from(&source) {
+			Ok(count) =>
+				println!("[+] Imported DCAP collateral of {} FMSPCs from {:?}", count, source),
+			Err(e) => error!("Failed to import DCAP collateral from {:?}: {:?}", source, e),
+		}
+	} else if let Some(sub_matches) = matches.subcommand_matches("export-collateral") {
+		let target = PathBuf::from(sub_matches.value_of("dir").expect("dir is required; qed"));
+		let collateral_cache =
+			CollateralCache::new(config.data_dir().join(DCAP_COLLATERAL_CACHE_PATH));
+		match collateral_cache.export_to(&target) {
+			Ok(count) =>
+				println!("[+] Exported DCAP collateral of {} FMSPCs to {:?}", count, target),
+			Err(e) => error!("Failed to export DCAP collateral to {:?}: {:?}", target, e),
+		}
+	} else if let Some(sub_matches) = matches.subcommand_matches("export-sidechain-blocks") {
+		let file = PathBuf::from(sub_matches.value_of("file").expect("file is required; qed"));
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let from = sub_matches
+			.value_of("from")
+			.map_or(0, |n| n.parse().expect("from must be a block number"));
+		let until = sub_matches
+			.value_of("until")
+			.map_or(BlockNumber::MAX, |n| n.parse().expect("until must be a block number"));
+		match sidechain_blockstorage.export_blocks(&shard, from, until, &file) {
+			Ok(count) => println!("[+] Exported {} sidechain blocks to {:?}", count, file),
+			Err(e) => error!("Failed to export sidechain blocks to {:?}: {:?}", file, e),
+		}
+	} else if let Some(sub_matches) = matches.subcommand_matches("import-sidechain-blocks") {
+		let file = PathBuf::from(sub_matches.value_of("file").expect("file is required; qed"));
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let authorities = sidechain_authorities(
+			&node_api_factory.create_api().expect("Failed to create parentchain node API"),
+			&shard,
+		);
+		match sidechain_blockstorage.import_blocks(&file, &shard, &authorities) {
+			Ok(count) => println!("[+] Imported {} sidechain blocks from {:?}", count, file),
+			Err(e) => error!("Failed to import sidechain blocks from {:?}: {:?}", file, e),
+		}
 	} else if matches.is_present("mrenclave") {
 		println!("{}", enclave.get_fingerprint().unwrap().encode().to_base58());
 	} else if let Some(sub_matches) = matches.subcommand_matches("init-shard") {
@@ -259,11 +352,15 @@ pub(crate) fn main() {
 				&config.mu_ra_url_external(),
 				&shard,
 				sub_matches.is_present("skip-ra"),
+				false,
 			)
 			.unwrap();
 			println!("[+] Done!");
 		} else {
-			tests::run_enclave_tests(sub_matches);
+			#[cfg(feature = "link-binary")]
+			crate::tests::run_enclave_tests(sub_matches);
+			#[cfg(not(feature = "link-binary"))]
+			println!("Enclave tests can only be run with the linked enclave");
 		}
 	} else {
 		println!("For options: use --help");
@@ -272,31 +369,47 @@ pub(crate) fn main() {
 
 /// FIXME: needs some discussion (restructuring?)
 #[allow(clippy::too_many_arguments)]
-fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
+fn start_worker<
+	E,
+	T,
+	D,
+	PeersUpdater,
+	PeerBlockFetcher,
+	InitializationHandler,
+	WorkerModeProvider,
+>(
 	config: Config,
-	shard: &ShardIdentifier,
+	shard_set: &ShardSet,
 	enclave: Arc<E>,
 	sidechain_storage: Arc<D>,
+	peers_updater: Arc<PeersUpdater>,
+	peer_block_fetcher: Arc<PeerBlockFetcher>,
 	integritee_rpc_api: ParentchainApi,
 	tokio_handle_getter: Arc<T>,
 	initialization_handler: Arc<InitializationHandler>,
 	quoting_enclave_target_info: Option<sgx_target_info_t>,
 	quote_size: Option<u32>,
 ) where
-	T: GetTokioHandle,
+	T: GetTokioHandle + Send + Sync + 'static,
 	E: EnclaveBase
 		+ DirectRequest
 		+ Sidechain
 		+ RemoteAttestation
 		+ TlsRemoteAttestation
 		+ TeeracleApi
+		+ AdminApi
 		+ Clone,
 	D: BlockPruner + FetchBlocks<SignedSidechainBlock> + Sync + Send + 'static,
-	InitializationHandler: TrackInitialization + IsInitialized + Sync + Send + 'static,
+	PeersUpdater: UpdateWorkerPeers + Send + Sync + 'static,
+	PeerBlockFetcher:
+		FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock> + Send + Sync + 'static,
+	InitializationHandler:
+		TrackInitialization + TrackHealth + IsInitialized + ReportHealth + Sync + Send + 'static,
 	WorkerModeProvider: ProvideWorkerMode,
 {
 	let run_config = config.run_config().clone().expect("Run config missing");
 	let skip_ra = run_config.skip_ra();
+	let shards = shard_set.handled.as_slice();
 
 	#[cfg(feature = "teeracle")]
 	let flavor_str = "teeracle";
@@ -322,7 +435,10 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	#[cfg(not(feature = "evm"))]
 	println!("  EVM is disabled");
 
-	info!("starting worker on shard {}", shard.encode().to_base58());
+	info!(
+		"starting worker on shards {:?}",
+		shards.iter().map(|shard| shard.encode().to_base58()).collect::<Vec<_>>()
+	);
 	// ------------------------------------------------------------------------
 	// check for required files
 	if !skip_ra {
@@ -399,6 +515,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		|| WorkerModeProvider::worker_mode() == WorkerMode::OffChainWorker
 	{
 		let direct_invocation_server_addr = config.trusted_worker_url_internal();
+		let trusted_rpc_server_limits = run_config.trusted_rpc_server_limits();
 		let enclave_for_direct_invocation = enclave.clone();
 		thread::spawn(move || {
 			println!(
@@ -406,7 +523,10 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 				direct_invocation_server_addr
 			);
 			enclave_for_direct_invocation
-				.init_direct_invocation_server(direct_invocation_server_addr)
+				.init_direct_invocation_server(
+					direct_invocation_server_addr,
+					trusted_rpc_server_limits,
+				)
 				.unwrap();
 			println!("[+] RPC direct invocation server shut down");
 		});
@@ -432,17 +552,25 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			&integritee_rpc_api,
 			&tee_accountid,
 			ParentchainId::Integritee,
-			shard,
+			&sync_anchor_shard(enclave.as_ref(), shards, ParentchainId::Integritee),
 		);
 
 	#[cfg(feature = "dcap")]
-	register_collateral(
+	let collateral_cache = CollateralCache::new(config.data_dir().join(DCAP_COLLATERAL_CACHE_PATH));
+	#[cfg(feature = "dcap")]
+	let tcb_status_tracker = TcbStatusTracker::default();
+	#[cfg(feature = "dcap")]
+	if let Err(e) = register_collateral(
 		&integritee_rpc_api,
 		&*enclave,
+		&collateral_cache,
+		&tcb_status_tracker,
 		&tee_accountid,
 		is_development_mode,
 		skip_ra,
-	);
+	) {
+		error!("[-] DCAP setup: could not register the collateral: {:?}", e);
+	}
 
 	let trusted_url = config.trusted_worker_url_external();
 
@@ -472,9 +600,11 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 
 	let enclave2 = enclave.clone();
 	#[cfg(not(feature = "dcap"))]
-	let register_xt = move || enclave2.generate_ias_ra_extrinsic(&trusted_url, skip_ra).unwrap();
+	let register_xt =
+		move || Ok::<_, Error>(enclave2.generate_ias_ra_extrinsic(&trusted_url, skip_ra)?);
 	#[cfg(feature = "dcap")]
-	let register_xt = move || enclave2.generate_dcap_ra_extrinsic(&trusted_url, skip_ra).unwrap();
+	let register_xt =
+		move || Ok::<_, Error>(enclave2.generate_dcap_ra_extrinsic(&trusted_url, skip_ra)?);
 
 	// clones because of the move
 	let node_api2 = integritee_rpc_api.clone();
@@ -482,7 +612,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	let send_register_xt = move || {
 		println!("[+] Send register enclave extrinsic");
 		send_integritee_extrinsic(
-			register_xt(),
+			register_xt()?,
 			&node_api2,
 			&tee_accountid_clone,
 			is_development_mode,
@@ -508,68 +638,63 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		register_enclave_xt_header.hash()
 	);
 	// double-check
-	let my_enclave = integritee_rpc_api
-		.enclave(&tee_accountid, None)
-		.unwrap()
+	let my_registration = fetch_enclave_registration(&integritee_rpc_api, &tee_accountid)
 		.expect("our enclave should be registered at this point");
-	trace!("verified that our enclave is registered: {:?}", my_enclave);
-
-	let (we_are_primary_validateer, re_init_parentchain_needed) =
-		match integritee_rpc_api.primary_worker_for_shard(shard, None).unwrap() {
-			Some(primary_enclave) => match primary_enclave.instance_signer() {
-				AnySigner::Known(MultiSigner::Ed25519(primary)) =>
-					if primary.encode() == tee_accountid.encode() {
-						println!("We are primary worker on this shard and we have been previously running.");
-						(true, false)
-					} else {
-						println!(
-							"We are NOT primary worker. The primary worker is {}.",
-							primary.to_ss58check(),
-						);
-						info!("The primary worker enclave is {:?}", primary_enclave);
-						if enclave
-							.get_shard_creation_info(shard)
-							.unwrap()
-							.for_parentchain(ParentchainId::Integritee)
-							.is_none()
-						{
-							//obtain provisioning from last active worker as this hasn't been done before
-							info!("my state doesn't know the creation header of the shard. will request provisioning");
-							sync_state::sync_state::<_, _, WorkerModeProvider>(
-								&integritee_rpc_api,
-								&shard,
-								enclave.as_ref(),
-								skip_ra,
-							);
-						}
-						(false, true)
-					},
-				_ => {
-					panic!(
-						"the primary worker for shard {:?} has unknown signer type: {:?}",
-						shard, primary_enclave
-					);
-				},
-			},
-			None => {
-				println!("We are the primary worker on this shard and the shard is untouched. Will initialize it");
-				enclave.init_shard(shard.encode()).unwrap();
-				if WorkerModeProvider::worker_mode() != WorkerMode::Teeracle {
-					enclave
-						.init_shard_creation_parentchain_header(
-							shard,
-							&ParentchainId::Integritee,
-							&register_enclave_xt_header,
-						)
-						.unwrap();
-					debug!("shard config should be initialized on integritee network now");
-					(true, true)
-				} else {
-					(true, false)
-				}
-			},
-		};
-	debug!("getting shard creation: {:?}", enclave.get_shard_creation_info(shard));
+	trace!("verified that our enclave is registered: {:?}", my_registration);
+	let registration_tracker = Arc::new(RegistrationTracker::default());
+	record_registration(&registration_tracker, initialization_handler.as_ref(), my_registration);
+
+	// ------------------------------------------------------------------------
+	// Re-attest periodically, before the registration on the parentchain lapses.
+	let node_api3 = integritee_rpc_api.clone();
+	let tee_accountid_clone = tee_accountid.clone();
+	#[cfg(feature = "dcap")]
+	let enclave3 = enclave.clone();
+	let reattest = move || {
+		#[cfg(feature = "dcap")]
+		register_collateral(
+			&node_api3,
+			&*enclave3,
+			&collateral_cache,
+			&tcb_status_tracker,
+			&tee_accountid_clone,
+			is_development_mode,
+			skip_ra,
+		)?;
+		send_register_xt()
+	};
+	let node_api4 = integritee_rpc_api.clone();
+	let tee_accountid_clone = tee_accountid.clone();
+	schedule_periodic_reattestation_thread(
+		reattest,
+		move || fetch_enclave_registration(&node_api4, &tee_accountid_clone),
+		registration_tracker.clone(),
+		initialization_handler.clone(),
+		run_config.reattestation_interval(),
+	);
+
+	let mut primary_validateer_for_shards = Vec::with_capacity(shards.len());
+	let mut re_init_parentchain_needed = false;
+	for shard in shards {
+		let (we_are_primary, re_init_needed) = init_shard_for_worker::<_, WorkerModeProvider>(
+			enclave.as_ref(),
+			&integritee_rpc_api,
+			&tee_accountid,
+			shard,
+			&register_enclave_xt_header,
+			skip_ra,
+		)
+		.expect("Failed to initialize shard");
+		debug!("getting shard creation: {:?}", enclave.get_shard_creation_info(shard));
+		primary_validateer_for_shards.push((*shard, we_are_primary));
+		re_init_parentchain_needed |= re_init_needed;
+	}
+	let we_are_primary_validateer =
+		primary_validateer_for_shards.iter().any(|(_, we_are_primary)| *we_are_primary);
+
+	// The parentchain is synced once for all shards, fast-syncing up to the oldest shard's creation.
+	let integritee_sync_shard =
+		sync_anchor_shard(enclave.as_ref(), shards, ParentchainId::Integritee);
 	initialization_handler.registered_on_parentchain();
 
 	let (integritee_parentchain_handler, integritee_last_synced_header_at_last_run) =
@@ -580,7 +705,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 				&integritee_rpc_api,
 				&tee_accountid,
 				ParentchainId::Integritee,
-				shard,
+				&integritee_sync_shard,
 			)
 		} else {
 			(integritee_parentchain_handler, integritee_last_synced_header_at_last_run)
@@ -590,12 +715,6 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		WorkerMode::Teeracle => {
 			// ------------------------------------------------------------------------
 			// initialize teeracle interval
-			#[cfg(feature = "teeracle")]
-			schedule_periodic_reregistration_thread(
-				send_register_xt,
-				run_config.reregister_teeracle_interval(),
-			);
-
 			#[cfg(feature = "teeracle")]
 			start_periodic_market_update(
 				&integritee_rpc_api,
@@ -611,7 +730,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			let last_synced_header = integritee_parentchain_handler
 				.sync_parentchain_until_latest_finalized(
 					integritee_last_synced_header_at_last_run,
-					*shard,
+					integritee_sync_shard,
 					true,
 				)
 				.unwrap();
@@ -619,7 +738,8 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			start_parentchain_header_subscription_thread(
 				integritee_parentchain_handler,
 				last_synced_header,
-				*shard,
+				integritee_sync_shard,
+				initialization_handler.clone(),
 			);
 
 			info!("skipping shard vault check because not yet supported for offchain worker");
@@ -633,7 +753,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 					.await_sync_and_import_parentchain_until_at_least(
 						&integritee_last_synced_header_at_last_run,
 						&register_enclave_xt_header,
-						*shard,
+						integritee_sync_shard,
 					)
 					.unwrap()
 			} else {
@@ -643,14 +763,17 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			start_parentchain_header_subscription_thread(
 				integritee_parentchain_handler,
 				last_synced_header,
-				*shard,
+				integritee_sync_shard,
+				initialization_handler.clone(),
 			);
 
-			spawn_worker_for_shard_polling(
-				shard,
-				integritee_rpc_api.clone(),
-				initialization_handler,
-			);
+			for shard in shards {
+				spawn_worker_for_shard_polling(
+					shard,
+					integritee_rpc_api.clone(),
+					initialization_handler.clone(),
+				);
+			}
 		},
 	}
 
@@ -659,9 +782,10 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			&enclave,
 			&tee_accountid,
 			url,
-			shard,
+			shards,
 			ParentchainId::TargetA,
 			is_development_mode,
+			initialization_handler.clone(),
 		))
 	} else {
 		None
@@ -672,28 +796,90 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			&enclave,
 			&tee_accountid,
 			url,
-			shard,
+			shards,
 			ParentchainId::TargetB,
 			is_development_mode,
+			initialization_handler.clone(),
 		))
 	} else {
 		None
 	};
 
-	init_provided_shard_vault(
-		shard,
-		&enclave,
-		integritee_rpc_api.clone(),
-		maybe_target_a_rpc_api,
-		maybe_target_b_rpc_api,
-		run_config.shielding_target,
-		we_are_primary_validateer,
-	);
+	for (shard, we_are_primary_for_shard) in primary_validateer_for_shards.iter() {
+		init_provided_shard_vault(
+			shard,
+			enclave.as_ref(),
+			integritee_rpc_api.clone(),
+			maybe_target_a_rpc_api.clone(),
+			maybe_target_b_rpc_api.clone(),
+			run_config.shielding_target,
+			*we_are_primary_for_shard,
+		)
+		.expect("Failed to initialize shard vault");
+	}
+
+	// ------------------------------------------------------------------------
+	// Start admin rpc server to operate the worker while running.
+	let block_production_suspender = Arc::new(BlockProductionSuspender::default());
+	if config.enable_admin_rpc() {
+		let shard_manager = Arc::new(ShardManager::<_, WorkerModeProvider>::new(
+			enclave.clone(),
+			integritee_rpc_api.clone(),
+			maybe_target_a_rpc_api,
+			maybe_target_b_rpc_api,
+			tee_accountid.clone(),
+			run_config.shielding_target,
+			skip_ra,
+			config.data_dir().to_path_buf(),
+			shard_set.clone(),
+		));
+		let worker_admin = Arc::new(WorkerAdmin::new(
+			enclave.clone(),
+			peers_updater,
+			block_production_suspender.clone(),
+			sidechain_storage.clone(),
+			peer_block_fetcher,
+			tokio_handle_getter.clone(),
+			config.data_dir().to_path_buf(),
+		));
+		let admin_rpc_port = config
+			.try_parse_admin_rpc_port()
+			.expect("admin rpc port to be a valid port number");
+		let admin_rpc_token = load_or_create_admin_rpc_token(config.data_dir())
+			.expect("admin rpc token to be readable or creatable in the data dir");
+		tokio_handle.spawn(async move {
+			if let Err(e) =
+				start_admin_rpc_server(shard_manager, worker_admin, admin_rpc_token, admin_rpc_port)
+					.await
+			{
+				error!("Unexpected error in admin rpc server: {:?}", e);
+			}
+		});
+	}
+
+	// ------------------------------------------------------------------------
+	// Follow shielding key rotations of the primary worker.
+	if WorkerModeProvider::worker_mode() != WorkerMode::Teeracle {
+		sync_state::start_shielding_key_sync_loop(
+			integritee_rpc_api.clone(),
+			enclave.clone(),
+			shards.to_vec(),
+			config.data_dir().to_path_buf(),
+			skip_ra,
+		);
+	}
 
 	if WorkerModeProvider::worker_mode() == WorkerMode::Sidechain {
 		println!("[Integritee:SCV] starting block production");
-		let last_synced_header =
-			sidechain_init_block_production(enclave.clone(), sidechain_storage).unwrap();
+		let last_synced_header = sidechain_init_block_production(
+			enclave.clone(),
+			sidechain_storage,
+			block_production_suspender,
+			registration_tracker,
+			integritee_rpc_api.clone(),
+			run_config.sidechain_pruning_policy(),
+		)
+		.unwrap();
 	}
 
 	ita_parentchain_interface::event_subscriber::subscribe_to_parentchain_events(
@@ -702,65 +888,18 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	);
 }
 
-fn init_provided_shard_vault<E: EnclaveBase>(
-	shard: &ShardIdentifier,
-	enclave: &Arc<E>,
-	integritee_rpc_api: ParentchainApi,
-	maybe_target_a_rpc_api: Option<ParentchainApi>,
-	maybe_target_b_rpc_api: Option<ParentchainApi>,
-	shielding_target: Option<ParentchainId>,
-	we_are_primary_validateer: bool,
-) {
-	let shielding_target = shielding_target.unwrap_or(ParentchainId::Integritee);
-	let rpc_api = match shielding_target {
-		ParentchainId::Integritee => integritee_rpc_api,
-		ParentchainId::TargetA => maybe_target_a_rpc_api
-			.expect("target A must be initialized to be used as shielding target"),
-		ParentchainId::TargetB => maybe_target_b_rpc_api
-			.expect("target B must be initialized to be used as shielding target"),
-	};
-	let funding_balance = shard_vault_initial_funds(&rpc_api).unwrap();
-	if let Ok(shard_vault) = enclave.get_ecc_vault_pubkey(shard) {
-		// verify if proxy is set up on chain
-		let nonce = rpc_api.get_account_nonce(&AccountId::from(shard_vault)).unwrap();
-		println!(
-			"[{:?}] shard vault account is already initialized in state: {} with nonce {}",
-			shielding_target,
-			shard_vault.to_ss58check(),
-			nonce
-		);
-		if nonce == 0 && we_are_primary_validateer {
-			println!(
-				"[{:?}] nonce = 0 means shard vault not properly set up on chain. will retry",
-				shielding_target
-			);
-			enclave.init_proxied_shard_vault(shard, &shielding_target, 0u128).unwrap();
-		}
-	} else if we_are_primary_validateer {
-		println!("[{:?}] initializing proxied shard vault account now", shielding_target);
-		enclave
-			.init_proxied_shard_vault(shard, &shielding_target, funding_balance)
-			.unwrap();
-		println!(
-			"[{:?}] initialized shard vault account: : {}",
-			shielding_target,
-			enclave.get_ecc_vault_pubkey(shard).unwrap().to_ss58check()
-		);
-	} else {
-		panic!("no vault account has been initialized and we are not the primary worker");
-	}
-}
-
-fn init_target_parentchain<E>(
+fn init_target_parentchain<E, HealthTracker>(
 	enclave: &Arc<E>,
 	tee_account_id: &AccountId32,
 	url: String,
-	shard: &ShardIdentifier,
+	shards: &[ShardIdentifier],
 	parentchain_id: ParentchainId,
 	is_development_mode: bool,
+	health_tracker: Arc<HealthTracker>,
 ) -> ParentchainApi
 where
 	E: EnclaveBase + Sidechain,
+	HealthTracker: TrackHealth + Send + Sync + 'static,
 {
 	println!("Initializing parentchain {:?} with url: {}", parentchain_id, url);
 	let node_api = NodeApiFactory::new(url, AccountKeyring::Alice.pair())
@@ -782,11 +921,14 @@ where
 	// TODO: #1451: Fix api-client type hacks
 	let head = Header::decode(&mut api_head.encode().as_slice())
 		.expect("Can decode previously encoded header; qed");
-	// we ignore failure
-	let _ = enclave.init_shard_creation_parentchain_header(shard, &parentchain_id, &head);
+	for shard in shards {
+		// we ignore failure
+		let _ = enclave.init_shard_creation_parentchain_header(shard, &parentchain_id, &head);
+	}
 
+	let sync_shard = sync_anchor_shard(enclave.as_ref(), shards, parentchain_id);
 	let (parentchain_handler, last_synched_header) =
-		init_parentchain(enclave, &node_api, tee_account_id, parentchain_id, shard);
+		init_parentchain(enclave, &node_api, tee_account_id, parentchain_id, &sync_shard);
 
 	if WorkerModeProvider::worker_mode() != WorkerMode::Teeracle {
 		println!(
@@ -796,13 +938,14 @@ where
 
 		// Syncing all parentchain blocks, this might take a while..
 		let last_synched_header = parentchain_handler
-			.sync_parentchain_until_latest_finalized(last_synched_header, *shard, true)
+			.sync_parentchain_until_latest_finalized(last_synched_header, sync_shard, true)
 			.unwrap();
 
 		start_parentchain_header_subscription_thread(
 			parentchain_handler.clone(),
 			last_synched_header,
-			*shard,
+			sync_shard,
+			health_tracker,
 		)
 	}
 
@@ -821,6 +964,51 @@ where
 	node_api
 }
 
+/// Loads the shard set persisted in the data dir, or creates it from the shards given on the
+/// command line. Removed shards are unloaded again, as the enclave loads all states on startup.
+fn load_shard_set<E: EnclaveBase>(config: &Config, shard_strs: &[String], enclave: &E) -> ShardSet {
+	let shard_set = match ShardSet::load(config.data_dir())
+		.expect("shard set in the data dir to be readable")
+	{
+		Some(shard_set) => {
+			if !shard_strs.is_empty() {
+				warn!("Using the shard set of the data dir, ignoring the shards given on the command line");
+			}
+			shard_set
+		},
+		None => ShardSet::new(extract_shards(shard_strs, enclave)),
+	};
+
+	for shard in shard_set.removed.iter() {
+		if let Err(e) = enclave.unload_shard(shard) {
+			debug!("Removed shard {:?} has not been loaded: {:?}", shard, e);
+		}
+	}
+	shard_set
+}
+
+/// Returns the shard, whose creation header is the oldest on the given parentchain.
+///
+/// A parentchain is synced once for all shards. Fast-sync must not skip any block after the
+/// creation of any of the shards, so the shard created first is used for syncing.
+fn sync_anchor_shard<E: EnclaveBase>(
+	enclave: &E,
+	shards: &[ShardIdentifier],
+	parentchain_id: ParentchainId,
+) -> ShardIdentifier {
+	*shards
+		.iter()
+		.min_by_key(|shard| {
+			// An unknown creation sorts first, which disables fast-sync.
+			enclave
+				.get_shard_creation_info(shard)
+				.ok()
+				.and_then(|info| info.for_parentchain(parentchain_id))
+				.map(|block| block.number)
+		})
+		.expect("worker operates on at least one shard")
+}
+
 fn init_parentchain<E>(
 	enclave: &Arc<E>,
 	node_api: &ParentchainApi,
@@ -957,26 +1145,42 @@ fn register_quotes_from_marblerun(
 		}
 	}
 }
+/// Registers the collateral of our platform and evaluates our TCB status against it.
 #[cfg(feature = "dcap")]
 fn register_collateral(
 	api: &ParentchainApi,
 	enclave: &dyn RemoteAttestation,
+	collateral_cache: &CollateralCache,
+	tcb_status_tracker: &TcbStatusTracker,
 	accountid: &AccountId32,
 	is_development_mode: bool,
 	skip_ra: bool,
-) {
+) -> ServiceResult<()> {
 	//TODO generate_dcap_ra_quote() does not really need skip_ra, rethink how many layers skip_ra should be passed along
-	if !skip_ra {
-		let dcap_quote = enclave.generate_dcap_ra_quote(skip_ra).unwrap();
-		let (fmspc, _tcb_info) = extract_tcb_info_from_raw_dcap_quote(&dcap_quote).unwrap();
-		println!("[>] DCAP setup: register QE collateral");
-		let uxt = enclave.generate_register_quoting_enclave_extrinsic(fmspc).unwrap();
-		send_integritee_extrinsic(uxt, api, accountid, is_development_mode);
-
-		println!("[>] DCAP setup: register TCB info");
-		let uxt = enclave.generate_register_tcb_info_extrinsic(fmspc).unwrap();
-		send_integritee_extrinsic(uxt, api, accountid, is_development_mode);
+	if skip_ra {
+		return Ok(())
+	}
+	let dcap_quote = enclave.generate_dcap_ra_quote(skip_ra)?;
+	let (fmspc, tcb) = extract_tcb_info_from_raw_dcap_quote(&dcap_quote).map_err(|e| {
+		Error::Custom(format!("Failed to extract the TCB info from our quote: {:?}", e).into())
+	})?;
+	let collateral = fetch_collateral_or_use_cached(enclave, collateral_cache, &fmspc)?;
+
+	match evaluate_tcb_status(&collateral, &tcb.cpusvn, tcb.pcesvn) {
+		Ok(tcb_status) => {
+			tcb_status_tracker.update(tcb_status);
+		},
+		Err(e) => warn!("[-] DCAP setup: could not evaluate our TCB status: {:?}", e),
 	}
+
+	println!("[>] DCAP setup: register QE collateral");
+	let uxt = enclave.generate_register_quoting_enclave_extrinsic(&collateral)?;
+	send_integritee_extrinsic(uxt, api, accountid, is_development_mode)?;
+
+	println!("[>] DCAP setup: register TCB info");
+	let uxt = enclave.generate_register_tcb_info_extrinsic(&collateral)?;
+	send_integritee_extrinsic(uxt, api, accountid, is_development_mode)?;
+	Ok(())
 }
 
 fn send_integritee_extrinsic(
@@ -1015,28 +1219,38 @@ fn send_integritee_extrinsic(
 			xt_report.block_hash.ok_or(Error::Custom("no extrinsic hash returned".into()))
 		},
 		Err(e) => {
-			panic!("Extrinsic failed {:?} parentchain genesis: {:?}", e, api.genesis_hash());
+			error!("Extrinsic failed {:?} parentchain genesis: {:?}", e, api.genesis_hash());
+			Err(e.into())
 		},
 	}
 }
 
-fn start_parentchain_header_subscription_thread<E: EnclaveBase + Sidechain>(
+fn start_parentchain_header_subscription_thread<E, HealthTracker>(
 	parentchain_handler: Arc<ParentchainHandler<ParentchainApi, E>>,
 	last_synced_header: Header,
 	shard: ShardIdentifier,
-) {
+	health_tracker: Arc<HealthTracker>,
+) where
+	E: EnclaveBase + Sidechain,
+	HealthTracker: TrackHealth + Send + Sync + 'static,
+{
 	let parentchain_id = *parentchain_handler.parentchain_id();
+	health_tracker.parentchain_blocks_imported(parentchain_id, last_synced_header.number);
 	thread::Builder::new()
 		.name(format!("{:?}_parentchain_sync_loop", parentchain_id))
 		.spawn(move || {
-			if let Err(e) =
-				subscribe_to_parentchain_new_headers(parentchain_handler, last_synced_header, shard)
-			{
+			if let Err(e) = subscribe_to_parentchain_new_headers(
+				parentchain_handler,
+				last_synced_header,
+				shard,
+				health_tracker.as_ref(),
+			) {
 				error!(
 					"[{:?}] parentchain block syncing terminated with a failure: {:?}",
 					parentchain_id, e
 				);
 			}
+			health_tracker.parentchain_sync_terminated(parentchain_id);
 			println!("[!] [{:?}] parentchain block syncing has terminated", parentchain_id);
 		})
 		.unwrap();
@@ -1044,10 +1258,11 @@ fn start_parentchain_header_subscription_thread<E: EnclaveBase + Sidechain>(
 
 /// Subscribe to the node API finalized heads stream and trigger a parent chain sync
 /// upon receiving a new header.
-fn subscribe_to_parentchain_new_headers<E: EnclaveBase + Sidechain>(
+fn subscribe_to_parentchain_new_headers<E: EnclaveBase + Sidechain, HealthTracker: TrackHealth>(
 	parentchain_handler: Arc<ParentchainHandler<ParentchainApi, E>>,
 	mut last_synced_header: Header,
 	shard: ShardIdentifier,
+	health_tracker: &HealthTracker,
 ) -> Result<(), Error> {
 	// TODO: this should be implemented by parentchain_handler directly, and not via
 	// exposed parentchain_api
@@ -1066,12 +1281,18 @@ fn subscribe_to_parentchain_new_headers<E: EnclaveBase + Sidechain>(
 			"[{:?}] Received finalized header update ({}), syncing parent chain...",
 			parentchain_id, new_header.number
 		);
+		health_tracker.parentchain_head_finalized(*parentchain_id, new_header.number);
 
 		last_synced_header = parentchain_handler.sync_parentchain_until_latest_finalized(
 			last_synced_header,
 			shard,
 			false,
 		)?;
+		health_tracker.parentchain_blocks_imported(*parentchain_id, last_synced_header.number);
+		set_parentchain_import_lag(
+			*parentchain_id,
+			new_header.number.saturating_sub(last_synced_header.number),
+		);
 	}
 }
 
@@ -1081,3 +1302,16 @@ fn enclave_account<E: EnclaveBase>(enclave_api: &E) -> AccountId32 {
 	trace!("[+] Got ed25519 account of TEE = {}", tee_public.to_ss58check());
 	AccountId32::from(*tee_public.as_array_ref())
 }
+
+/// Get the validateers of a shard, which are the authorities of its sidechain blocks.
+fn sidechain_authorities(api: &ParentchainApi, shard: &ShardIdentifier) -> Vec<ed25519::Public> {
+	api.shard_status(shard, None)
+		.expect("Failed to fetch the shard status from the parentchain")
+		.unwrap_or_default()
+		.into_iter()
+		.map(|signer_status| {
+			let signer: [u8; 32] = signer_status.signer.into();
+			ed25519::Public::from_raw(signer)
+		})
+		.collect()
+}
@@ -18,6 +18,7 @@
 
 use crate::{
 	globals::tokio_handle::GetTokioHandle,
+	initialized_service::TrackHealth,
 	ocall_bridge::{
 		bridge_api::{
 			GetOCallBridgeComponents, IpfsBridge, MetricsBridge, RemoteAttestationBridge,
@@ -38,7 +39,7 @@ use itp_node_api::node_api_factory::CreateNodeApi;
 use its_peer_fetch::FetchBlocksFromPeer;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
 use its_storage::BlockStorage;
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 /// Concrete implementation, should be moved out of the OCall Bridge, into the worker
 /// since the OCall bridge itself should not know any concrete types to ensure
@@ -52,6 +53,7 @@ pub struct OCallBridgeComponentFactory<
 	PeerBlockFetcher,
 	TokioHandle,
 	MetricsReceiver,
+	HealthTracker,
 > {
 	integritee_rpc_api_factory: Arc<NodeApi>,
 	target_a_parentchain_rpc_api_factory: Option<Arc<NodeApi>>,
@@ -63,6 +65,9 @@ pub struct OCallBridgeComponentFactory<
 	peer_block_fetcher: Arc<PeerBlockFetcher>,
 	tokio_handle: Arc<TokioHandle>,
 	metrics_receiver: Arc<MetricsReceiver>,
+	health_tracker: Arc<HealthTracker>,
+	dcap_collateral_dir: PathBuf,
+	dcap_collateral_cache_dir: PathBuf,
 }
 
 impl<
@@ -74,6 +79,7 @@ impl<
 		PeerBlockFetcher,
 		TokioHandle,
 		MetricsReceiver,
+		HealthTracker,
 	>
 	OCallBridgeComponentFactory<
 		NodeApi,
@@ -84,6 +90,7 @@ impl<
 		PeerBlockFetcher,
 		TokioHandle,
 		MetricsReceiver,
+		HealthTracker,
 	>
 {
 	#[allow(clippy::too_many_arguments)]
@@ -98,6 +105,9 @@ impl<
 		peer_block_fetcher: Arc<PeerBlockFetcher>,
 		tokio_handle: Arc<TokioHandle>,
 		metrics_receiver: Arc<MetricsReceiver>,
+		health_tracker: Arc<HealthTracker>,
+		dcap_collateral_dir: PathBuf,
+		dcap_collateral_cache_dir: PathBuf,
 	) -> Self {
 		OCallBridgeComponentFactory {
 			integritee_rpc_api_factory,
@@ -110,6 +120,9 @@ impl<
 			peer_block_fetcher,
 			tokio_handle,
 			metrics_receiver,
+			health_tracker,
+			dcap_collateral_dir,
+			dcap_collateral_cache_dir,
 		}
 	}
 }
@@ -123,6 +136,7 @@ impl<
 		PeerBlockFetcher,
 		TokioHandle,
 		MetricsReceiver,
+		HealthTracker,
 	> GetOCallBridgeComponents
 	for OCallBridgeComponentFactory<
 		NodeApi,
@@ -133,6 +147,7 @@ impl<
 		PeerBlockFetcher,
 		TokioHandle,
 		MetricsReceiver,
+		HealthTracker,
 	> where
 	NodeApi: CreateNodeApi + 'static,
 	Broadcaster: BroadcastBlocks + 'static,
@@ -142,9 +157,14 @@ impl<
 	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock> + 'static,
 	TokioHandle: GetTokioHandle + 'static,
 	MetricsReceiver: ReceiveEnclaveMetrics + 'static,
+	HealthTracker: TrackHealth + 'static,
 {
 	fn get_ra_api(&self) -> Arc<dyn RemoteAttestationBridge> {
-		Arc::new(RemoteAttestationOCall::new(self.enclave_api.clone()))
+		Arc::new(RemoteAttestationOCall::new(
+			self.enclave_api.clone(),
+			self.dcap_collateral_dir.clone(),
+			self.dcap_collateral_cache_dir.clone(),
+		))
 	}
 
 	fn get_sidechain_api(&self) -> Arc<dyn SidechainBridge> {
@@ -154,6 +174,7 @@ impl<
 			self.peer_updater.clone(),
 			self.peer_block_fetcher.clone(),
 			self.tokio_handle.clone(),
+			self.health_tracker.clone(),
 		))
 	}
 
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, SidechainBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, SidechainBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use itp_utils::write_slice_and_whitespace_pad;
 use log::*;
 use sgx_types::sgx_status_t;
@@ -36,6 +39,7 @@ pub unsafe extern "C" fn ocall_fetch_sidechain_blocks_from_peer(
 	sidechain_blocks_ptr: *mut u8,
 	sidechain_blocks_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("fetch_sidechain_blocks_from_peer");
 	fetch_sidechain_blocks_from_peer(
 		last_imported_block_hash_ptr,
 		last_imported_block_hash_size,
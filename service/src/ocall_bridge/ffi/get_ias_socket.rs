@@ -16,13 +16,17 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::{c_int, sgx_status_t};
 use std::sync::Arc;
 
 #[no_mangle]
 pub extern "C" fn ocall_get_ias_socket(ret_fd: *mut c_int) -> sgx_status_t {
+	let _timer = start_ocall_timer("get_ias_socket");
 	get_ias_socket(ret_fd, Bridge::get_ra_api()) // inject the RA API (global state)
 }
 
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::{sgx_quote_nonce_t, sgx_quote_sign_type_t, sgx_report_t, sgx_spid_t, sgx_status_t};
 use std::{slice, sync::Arc};
@@ -35,6 +38,7 @@ pub unsafe extern "C" fn ocall_get_quote(
 	maxlen: u32,
 	p_quote_len: *mut u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("get_quote");
 	get_quote(
 		p_sigrl,
 		sigrl_len,
@@ -105,6 +109,7 @@ pub unsafe extern "C" fn ocall_get_dcap_quote(
 	p_quote: *mut u8,
 	quote_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("get_dcap_quote");
 	get_dcap_quote(
 		p_report,
 		p_quote,
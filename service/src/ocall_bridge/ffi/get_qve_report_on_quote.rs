@@ -12,7 +12,10 @@
 	limitations under the License.
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::*;
 use std::{slice, sync::Arc};
@@ -29,6 +32,7 @@ pub unsafe extern "C" fn ocall_get_qve_report_on_quote(
 	p_supplemental_data: *mut u8,
 	supplemental_data_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("get_qve_report_on_quote");
 	get_qve_report_on_quote(
 		p_quote,
 		quote_len,
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::{sgx_platform_info_t, sgx_status_t, sgx_update_info_bit_t};
 use std::sync::Arc;
@@ -27,6 +30,7 @@ pub extern "C" fn ocall_get_update_info(
 	enclave_trusted: i32,
 	p_update_info: *mut sgx_update_info_bit_t,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("get_update_info");
 	get_update_info(
 		p_platform_blob,
 		enclave_trusted,
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, RemoteAttestationBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::{sgx_epid_group_id_t, sgx_status_t, sgx_target_info_t};
 use std::sync::Arc;
@@ -26,6 +29,7 @@ pub unsafe extern "C" fn ocall_sgx_init_quote(
 	ret_ti: *mut sgx_target_info_t,
 	ret_gid: *mut sgx_epid_group_id_t,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("sgx_init_quote");
 	sgx_init_quote(ret_ti, ret_gid, Bridge::get_ra_api()) // inject the RA API (global state)
 }
 
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, Cid, IpfsBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, Cid, IpfsBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::sgx_status_t;
 use std::{slice, sync::Arc};
@@ -29,12 +32,14 @@ pub unsafe extern "C" fn ocall_write_ipfs(
 	cid: *mut u8,
 	cid_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("write_ipfs");
 	write_ipfs(enc_state, enc_state_size, cid, cid_size, Bridge::get_ipfs_api())
 }
 
 /// C-API exposed for o-call from enclave
 #[no_mangle]
 pub unsafe extern "C" fn ocall_read_ipfs(cid: *const u8, cid_size: u32) -> sgx_status_t {
+	let _timer = start_ocall_timer("read_ipfs");
 	read_ipfs(cid, cid_size, Bridge::get_ipfs_api())
 }
 
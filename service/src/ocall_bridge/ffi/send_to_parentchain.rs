@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, WorkerOnChainBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, WorkerOnChainBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::{c_int, sgx_status_t};
 use std::{slice, sync::Arc, vec::Vec};
@@ -32,6 +35,7 @@ pub unsafe extern "C" fn ocall_send_to_parentchain(
 	parentchain_id_size: u32,
 	await_each_inclusion: c_int,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("send_to_parentchain");
 	send_to_parentchain(
 		extrinsics_encoded,
 		extrinsics_encoded_size,
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, SidechainBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, SidechainBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::sgx_status_t;
 use std::{slice, sync::Arc};
@@ -29,6 +32,7 @@ pub unsafe extern "C" fn ocall_store_sidechain_blocks(
 	signed_blocks_ptr: *const u8,
 	signed_blocks_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("store_sidechain_blocks");
 	store_sidechain_blocks(signed_blocks_ptr, signed_blocks_size, Bridge::get_sidechain_api())
 }
 
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, MetricsBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, MetricsBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use log::*;
 use sgx_types::sgx_status_t;
 use std::{slice, sync::Arc};
@@ -29,6 +32,7 @@ pub unsafe extern "C" fn ocall_update_metric(
 	metric_ptr: *const u8,
 	metric_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("update_metric");
 	update_metric(metric_ptr, metric_size, Bridge::get_metrics_api())
 }
 
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, WorkerOnChainBridge};
+use crate::{
+	ocall_bridge::bridge_api::{Bridge, WorkerOnChainBridge},
+	prometheus_metrics::start_ocall_timer,
+};
 use itp_utils::write_slice_and_whitespace_pad;
 use log::*;
 use sgx_types::sgx_status_t;
@@ -34,6 +37,7 @@ pub unsafe extern "C" fn ocall_worker_request(
 	response: *mut u8,
 	resp_size: u32,
 ) -> sgx_status_t {
+	let _timer = start_ocall_timer("worker_request");
 	worker_request(
 		request,
 		req_size,
@@ -16,24 +16,61 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{
-	OCallBridgeError, OCallBridgeResult, RemoteAttestationBridge,
+use crate::{
+	collateral_cache::{is_collateral_unavailable, CollateralCache},
+	ocall_bridge::bridge_api::{OCallBridgeError, OCallBridgeResult, RemoteAttestationBridge},
 };
+use itp_attestation_handler::SgxQlQveCollateral;
 use itp_enclave_api::remote_attestation::{QveReport, RemoteAttestationCallBacks};
+use log::*;
 use sgx_types::*;
 use std::{
 	net::{SocketAddr, TcpStream},
 	os::unix::io::IntoRawFd,
+	path::PathBuf,
 	sync::Arc,
 };
 
 pub struct RemoteAttestationOCall<E> {
 	enclave_api: Arc<E>,
+	dcap_collateral_dir: PathBuf,
+	collateral_cache: CollateralCache,
 }
 
 impl<E> RemoteAttestationOCall<E> {
-	pub fn new(enclave_api: Arc<E>) -> Self {
-		RemoteAttestationOCall { enclave_api }
+	pub fn new(
+		enclave_api: Arc<E>,
+		dcap_collateral_dir: PathBuf,
+		dcap_collateral_cache_dir: PathBuf,
+	) -> Self {
+		RemoteAttestationOCall {
+			enclave_api,
+			dcap_collateral_dir,
+			collateral_cache: CollateralCache::new(dcap_collateral_cache_dir),
+		}
+	}
+
+	/// Collateral supplied from local files, if the enclave has not passed any.
+	fn local_collateral(
+		&self,
+		quote_collateral: &sgx_ql_qve_collateral_t,
+	) -> Option<SgxQlQveCollateral> {
+		if quote_collateral.version != 0 || !self.dcap_collateral_dir.exists() {
+			return None
+		}
+		match SgxQlQveCollateral::read_from_dir(&self.dcap_collateral_dir) {
+			Ok(collateral) => {
+				debug!("Verifying quote with collateral from {:?}", self.dcap_collateral_dir);
+				Some(collateral)
+			},
+			Err(e) => {
+				warn!(
+					"Could not read collateral from {:?}, fetching it from the PCCS: {:?}",
+					self.dcap_collateral_dir, e
+				);
+				None
+			},
+		}
 	}
 }
 
@@ -98,18 +135,45 @@ where
 		qve_report_info: sgx_ql_qe_report_info_t,
 		supplemental_data_size: u32,
 	) -> OCallBridgeResult<QveReport> {
-		self.enclave_api
-			.get_qve_report_on_quote(
-				quote,
-				current_time,
-				quote_collateral,
-				qve_report_info,
-				supplemental_data_size,
-			)
-			.map_err(|e| match e {
-				itp_enclave_api::error::Error::Sgx(s) => OCallBridgeError::GetQuote(s),
-				_ => OCallBridgeError::GetQuote(sgx_status_t::SGX_ERROR_UNEXPECTED),
-			})
+		let local_collateral = self.local_collateral(quote_collateral);
+		let local_c_collateral = local_collateral.as_ref().map(|c| c.as_c_type());
+		let quote_collateral = local_c_collateral.as_ref().unwrap_or(quote_collateral);
+
+		let qve_report = self.enclave_api.get_qve_report_on_quote(
+			quote.clone(),
+			current_time,
+			quote_collateral,
+			qve_report_info,
+			supplemental_data_size,
+		);
+
+		// No collateral has been passed, so the QvE fetched it from the PCCS.
+		// If the PCCS was unreachable, retry with the cached collateral of the quoting platform.
+		let qve_report = match qve_report {
+			Err(e) if quote_collateral.version == 0 && is_collateral_unavailable(&e) =>
+				match self.collateral_cache.load_for_quote(&quote) {
+					Ok(collateral) => {
+						warn!("Could not fetch collateral: {:?}, verifying the quote with cached collateral", e);
+						self.enclave_api.get_qve_report_on_quote(
+							quote,
+							current_time,
+							&collateral.as_c_type(),
+							qve_report_info,
+							supplemental_data_size,
+						)
+					},
+					Err(cache_error) => {
+						debug!("No cached collateral to verify the quote with: {:?}", cache_error);
+						Err(e)
+					},
+				},
+			qve_report => qve_report,
+		};
+
+		qve_report.map_err(|e| match e {
+			itp_enclave_api::error::Error::Sgx(s) => OCallBridgeError::GetQuote(s),
+			_ => OCallBridgeError::GetQuote(sgx_status_t::SGX_ERROR_UNEXPECTED),
+		})
 	}
 
 	fn get_update_info(
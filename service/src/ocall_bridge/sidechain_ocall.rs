@@ -18,6 +18,7 @@
 
 use crate::{
 	globals::tokio_handle::GetTokioHandle,
+	initialized_service::TrackHealth,
 	ocall_bridge::bridge_api::{OCallBridgeError, OCallBridgeResult, SidechainBridge},
 	sync_block_broadcaster::BroadcastBlocks,
 	worker_peers_updater::UpdateWorkerPeers,
@@ -31,18 +32,26 @@ use its_primitives::{
 };
 use its_storage::BlockStorage;
 use log::*;
-use std::{collections::HashSet, sync::Arc};
-
-pub struct SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle> {
+use std::{collections::HashMap, sync::Arc};
+
+pub struct SidechainOCall<
+	BlockBroadcaster,
+	Storage,
+	PeerUpdater,
+	PeerBlockFetcher,
+	TokioHandle,
+	HealthTracker,
+> {
 	block_broadcaster: Arc<BlockBroadcaster>,
 	block_storage: Arc<Storage>,
 	peer_updater: Arc<PeerUpdater>,
 	peer_block_fetcher: Arc<PeerBlockFetcher>,
 	tokio_handle: Arc<TokioHandle>,
+	health_tracker: Arc<HealthTracker>,
 }
 
-impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
-	SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
+impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle, HealthTracker>
+	SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle, HealthTracker>
 {
 	pub fn new(
 		block_broadcaster: Arc<BlockBroadcaster>,
@@ -50,6 +59,7 @@ impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
 		peer_updater: Arc<PeerUpdater>,
 		peer_block_fetcher: Arc<PeerBlockFetcher>,
 		tokio_handle: Arc<TokioHandle>,
+		health_tracker: Arc<HealthTracker>,
 	) -> Self {
 		SidechainOCall {
 			block_broadcaster,
@@ -57,18 +67,27 @@ impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
 			peer_updater,
 			peer_block_fetcher,
 			tokio_handle,
+			health_tracker,
 		}
 	}
 }
 
-impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle> SidechainBridge
-	for SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
-where
+impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle, HealthTracker>
+	SidechainBridge
+	for SidechainOCall<
+		BlockBroadcaster,
+		Storage,
+		PeerUpdater,
+		PeerBlockFetcher,
+		TokioHandle,
+		HealthTracker,
+	> where
 	BlockBroadcaster: BroadcastBlocks,
 	Storage: BlockStorage<SignedSidechainBlock>,
 	PeerUpdater: UpdateWorkerPeers,
 	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock>,
 	TokioHandle: GetTokioHandle,
+	HealthTracker: TrackHealth,
 {
 	fn propose_sidechain_blocks(&self, signed_blocks_encoded: Vec<u8>) -> OCallBridgeResult<()> {
 		// TODO: improve error handling, using a mut status is not good design?
@@ -99,35 +118,35 @@ where
 				.collect::<Vec<u64>>()
 		);
 
-		let shards: Vec<ShardIdentifier> = signed_blocks
-			.iter()
-			.map(|b| b.block.header().shard_id())
-			.collect::<HashSet<_>>()
-			.into_iter()
-			.collect();
-
-		if shards.len() > 1 {
-			error!("operating multiple shards is not supported");
-		}
-		let shard = shards[0];
-
-		// FIXME: When & where should peers be updated?
-		trace!("Updating peers..");
-		if let Err(e) = self.peer_updater.update_peers(shard) {
-			error!("Error updating peers: {:?}", e);
-		// Fixme: returning an error here results in a `HeaderAncestryMismatch` error.
-		// status = sgx_status_t::SGX_ERROR_UNEXPECTED;
-		} else {
-			debug!("Successfully updated peers");
+		let mut blocks_per_shard: HashMap<ShardIdentifier, Vec<SignedSidechainBlock>> =
+			HashMap::new();
+		for signed_block in signed_blocks {
+			blocks_per_shard
+				.entry(signed_block.block.header().shard_id())
+				.or_default()
+				.push(signed_block);
 		}
 
-		trace!("Broadcasting sidechain blocks ...");
-		if let Err(e) = self.block_broadcaster.broadcast_blocks(signed_blocks) {
-			error!("Error broadcasting blocks: {:?}", e);
-		// Fixme: returning an error here results in a `HeaderAncestryMismatch` error.
-		// status = sgx_status_t::SGX_ERROR_UNEXPECTED;
-		} else {
-			debug!("Successfully broadcast blocks");
+		// The peers are updated per shard, so each shard's blocks are sent to the peers of that shard.
+		for (shard, shard_blocks) in blocks_per_shard {
+			// FIXME: When & where should peers be updated?
+			trace!("Updating peers of shard {:?}..", shard);
+			if let Err(e) = self.peer_updater.update_peers(shard) {
+				error!("Error updating peers: {:?}", e);
+			// Fixme: returning an error here results in a `HeaderAncestryMismatch` error.
+			// status = sgx_status_t::SGX_ERROR_UNEXPECTED;
+			} else {
+				debug!("Successfully updated peers");
+			}
+
+			trace!("Broadcasting sidechain blocks of shard {:?} ...", shard);
+			if let Err(e) = self.block_broadcaster.broadcast_blocks(shard_blocks) {
+				error!("Error broadcasting blocks: {:?}", e);
+			// Fixme: returning an error here results in a `HeaderAncestryMismatch` error.
+			// status = sgx_status_t::SGX_ERROR_UNEXPECTED;
+			} else {
+				debug!("Successfully broadcast blocks");
+			}
 		}
 
 		status
@@ -148,8 +167,14 @@ where
 				},
 			};
 
-		if let Err(e) = self.block_storage.store_blocks(signed_blocks) {
-			error!("Error storing blocks: {:?}", e);
+		let last_block_number = signed_blocks.iter().map(|b| b.block.header().block_number).max();
+
+		match self.block_storage.store_blocks(signed_blocks) {
+			Ok(()) =>
+				if let Some(block_number) = last_block_number {
+					self.health_tracker.sidechain_block_imported(block_number);
+				},
+			Err(e) => error!("Error storing blocks: {:?}", e),
 		}
 
 		status
@@ -212,6 +237,7 @@ mod tests {
 		globals::tokio_handle::ScopedTokioHandle,
 		tests::mocks::{
 			broadcast_blocks_mock::BroadcastBlocksMock,
+			initialization_handler_mock::TrackInitializationMock,
 			update_worker_peers_mock::UpdateWorkerPeersMock,
 		},
 	};
@@ -236,6 +262,7 @@ mod tests {
 		UpdateWorkerPeersMock,
 		FetchBlocksFromPeerMock<SignedSidechainBlock>,
 		ScopedTokioHandle,
+		TrackInitializationMock,
 	>;
 
 	#[test]
@@ -282,6 +309,7 @@ mod tests {
 			peer_updater_mock,
 			peer_block_fetcher_mock,
 			scoped_tokio_handle,
+			Arc::new(TrackInitializationMock {}),
 		)
 	}
 }
@@ -17,12 +17,15 @@
 
 //! Service for prometheus metrics, hosted on a http server.
 
+#[cfg(feature = "dcap")]
+use crate::tcb_status::TcbStatus;
 #[cfg(feature = "teeracle")]
 use crate::teeracle::teeracle_metrics::update_teeracle_metrics;
 
 use crate::{
 	account_funding::EnclaveAccountInfo,
 	error::{Error, ServiceResult},
+	telemetry::export_trace_batch,
 };
 use async_trait::async_trait;
 use codec::{Decode, Encode};
@@ -36,9 +39,15 @@ use itc_rest_client::{
 	RestGet, RestPath,
 };
 use itp_enclave_metrics::EnclaveMetric;
+use itp_time_utils::now_as_millis;
+use itp_types::parentchain::{BlockNumber, ParentchainId};
 use lazy_static::lazy_static;
 use log::*;
-use prometheus::{proto::MetricFamily, register_int_gauge, IntGauge};
+use prometheus::{
+	proto::MetricFamily, register_histogram, register_histogram_vec, register_int_counter,
+	register_int_counter_vec, register_int_gauge, register_int_gauge_vec, Histogram,
+	HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+};
 use serde::{Deserialize, Serialize};
 use std::{net::SocketAddr, sync::Arc};
 use warp::{Filter, Rejection, Reply};
@@ -55,6 +64,93 @@ lazy_static! {
 	static ref ENCLAVE_SIDECHAIN_TOP_POOL_SIZE: IntGauge =
 		register_int_gauge!("integritee_worker_enclave_sidechain_top_pool_size", "Enclave sidechain top pool size")
 			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_SLOTS_CLAIMED: IntCounter =
+		register_int_counter!("integritee_worker_enclave_sidechain_slots_claimed", "Number of sidechain slots claimed by this validateer")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_SLOTS_MISSED: IntCounter =
+		register_int_counter!("integritee_worker_enclave_sidechain_slots_missed", "Number of claimed sidechain slots that did not result in a block")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCK_PROPOSAL_DURATION: Histogram =
+		register_histogram!("integritee_worker_enclave_sidechain_block_proposal_duration_seconds", "Time it took to propose a sidechain block")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCK_IMPORT_DURATION: Histogram =
+		register_histogram!("integritee_worker_enclave_sidechain_block_import_duration_seconds", "Time it took to import a sidechain block")
+			.unwrap();
+	static ref ENCLAVE_SUCCESSFUL_TRUSTED_CALLS: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_successful_trusted_calls", "Number of successfully executed trusted calls per call type", &["call_type"])
+			.unwrap();
+	static ref ENCLAVE_FAILED_TRUSTED_CALLS: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_failed_trusted_calls", "Number of failed trusted calls per call type", &["call_type"])
+			.unwrap();
+	static ref ENCLAVE_STATE_SNAPSHOT_SIZE: IntGauge =
+		register_int_gauge!("integritee_worker_enclave_state_snapshot_size_bytes", "Size of the encoded state after the last sidechain block import")
+			.unwrap();
+	static ref ENCLAVE_HOST_TIME_DRIFT: IntGauge =
+		register_int_gauge!("integritee_worker_enclave_host_time_drift_ms", "Drift of the host time from the time bounded by verified parentchain timestamps, positive if the host clock is ahead")
+			.unwrap();
+	static ref ENCLAVE_RPC_REJECTIONS: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_rpc_rejections", "Number of connections and requests rejected by the trusted RPC server per reason", &["reason"])
+			.unwrap();
+	static ref ENCLAVE_INDIRECT_CALLS_PROCESSED: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_indirect_calls_processed", "Number of indirect calls processed per parentchain", &["parentchain"])
+			.unwrap();
+	static ref PARENTCHAIN_IMPORT_LAG: IntGaugeVec =
+		register_int_gauge_vec!("integritee_worker_parentchain_import_lag", "Number of finalized parentchain blocks not yet imported by the enclave", &["parentchain"])
+			.unwrap();
+	static ref OCALL_DURATION: HistogramVec =
+		register_histogram_vec!("integritee_worker_ocall_duration_seconds", "Time it took to handle an ocall", &["ocall"])
+			.unwrap();
+	static ref ENCLAVE_ATTESTATION_TIMESTAMP: IntGauge =
+		register_int_gauge!("integritee_worker_enclave_attestation_timestamp_ms", "Timestamp of the last attestation of the enclave registered on the parentchain")
+			.unwrap();
+	static ref ENCLAVE_ATTESTATION_AGE: IntGauge =
+		register_int_gauge!("integritee_worker_enclave_attestation_age_seconds", "Age of the last attestation of the enclave registered on the parentchain")
+			.unwrap();
+	static ref DCAP_COLLATERAL_NEXT_UPDATE: IntGaugeVec =
+		register_int_gauge_vec!("integritee_worker_dcap_collateral_next_update_seconds", "Unix timestamp at which the cached DCAP collateral expires, per FMSPC", &["fmspc"])
+			.unwrap();
+	#[cfg(feature = "dcap")]
+	static ref ENCLAVE_TCB_STATUS: IntGaugeVec =
+		register_int_gauge_vec!("integritee_worker_enclave_tcb_status", "TCB status of the platform as evaluated against the DCAP collateral (1 for the current status)", &["status"])
+			.unwrap();
+}
+
+/// Size of the TOP pool, as last reported by the enclave.
+pub(crate) fn top_pool_size() -> u64 {
+	ENCLAVE_SIDECHAIN_TOP_POOL_SIZE.get().max(0) as u64
+}
+
+/// Sets the number of finalized blocks of a parentchain that have not been imported yet.
+pub(crate) fn set_parentchain_import_lag(parentchain_id: ParentchainId, lag: BlockNumber) {
+	PARENTCHAIN_IMPORT_LAG
+		.with_label_values(&[format!("{:?}", parentchain_id).as_str()])
+		.set(lag as i64);
+}
+
+/// Sets the timestamp of the attestation registered on the parentchain.
+pub(crate) fn set_enclave_attestation(attestation_timestamp: u64) {
+	ENCLAVE_ATTESTATION_TIMESTAMP.set(attestation_timestamp as i64);
+}
+
+/// Sets the TCB status of the platform.
+#[cfg(feature = "dcap")]
+pub(crate) fn set_enclave_tcb_status(tcb_status: TcbStatus) {
+	ENCLAVE_TCB_STATUS.reset();
+	ENCLAVE_TCB_STATUS
+		.with_label_values(&[format!("{:?}", tcb_status).as_str()])
+		.set(1);
+}
+
+/// Sets the expiry of the cached DCAP collateral of a platform.
+pub(crate) fn set_dcap_collateral_next_update(fmspc: &[u8], next_update: i64) {
+	DCAP_COLLATERAL_NEXT_UPDATE
+		.with_label_values(&[hex::encode(fmspc).as_str()])
+		.set(next_update);
+}
+
+/// Starts a timer, which observes the duration of the given ocall when dropped.
+pub(crate) fn start_ocall_timer(ocall: &str) -> HistogramTimer {
+	OCALL_DURATION.with_label_values(&[ocall]).start_timer()
 }
 
 pub async fn start_metrics_server<MetricsHandler>(
@@ -120,6 +216,12 @@ where
 	}
 
 	async fn update_metrics(&self) {
+		let attestation_timestamp = ENCLAVE_ATTESTATION_TIMESTAMP.get();
+		if attestation_timestamp > 0 {
+			let age_ms = (now_as_millis() as i64).saturating_sub(attestation_timestamp);
+			ENCLAVE_ATTESTATION_AGE.set(age_ms.max(0) / 1000);
+		}
+
 		match self.enclave_wallet.free_balance() {
 			Ok(b) => {
 				ENCLAVE_ACCOUNT_FREE_BALANCE.set(b as i64);
@@ -171,6 +273,48 @@ impl ReceiveEnclaveMetrics for EnclaveMetricsReceiver {
 			EnclaveMetric::TopPoolSizeDecrement => {
 				ENCLAVE_SIDECHAIN_TOP_POOL_SIZE.dec();
 			},
+			EnclaveMetric::SidechainSlotClaimed => {
+				ENCLAVE_SIDECHAIN_SLOTS_CLAIMED.inc();
+			},
+			EnclaveMetric::SidechainSlotMissed => {
+				ENCLAVE_SIDECHAIN_SLOTS_MISSED.inc();
+			},
+			EnclaveMetric::SidechainBlockProposalDuration(duration) => {
+				ENCLAVE_SIDECHAIN_BLOCK_PROPOSAL_DURATION.observe(duration.as_secs_f64());
+			},
+			EnclaveMetric::SidechainBlockImportDuration(duration) => {
+				ENCLAVE_SIDECHAIN_BLOCK_IMPORT_DURATION.observe(duration.as_secs_f64());
+			},
+			EnclaveMetric::TrustedCallsExecuted(counts) =>
+				for (call_type, count) in counts {
+					ENCLAVE_SUCCESSFUL_TRUSTED_CALLS
+						.get_metric_with_label_values(&[call_type.as_str()])
+						.map(|m| m.inc_by(count.successful))
+						.map_err(|e| Error::Custom(e.into()))?;
+					ENCLAVE_FAILED_TRUSTED_CALLS
+						.get_metric_with_label_values(&[call_type.as_str()])
+						.map(|m| m.inc_by(count.failed))
+						.map_err(|e| Error::Custom(e.into()))?;
+				},
+			EnclaveMetric::StateSnapshotSize(size) => {
+				ENCLAVE_STATE_SNAPSHOT_SIZE.set(size as i64);
+			},
+			EnclaveMetric::IndirectCallsProcessed(parentchain, count) =>
+				ENCLAVE_INDIRECT_CALLS_PROCESSED
+					.get_metric_with_label_values(&[parentchain.as_str()])
+					.map(|m| m.inc_by(count))
+					.map_err(|e| Error::Custom(e.into()))?,
+			EnclaveMetric::TraceSpans(batch) => export_trace_batch(batch),
+			EnclaveMetric::HostTimeDrift(drift) => {
+				ENCLAVE_HOST_TIME_DRIFT.set(drift);
+			},
+			EnclaveMetric::RpcRequestsRejected(rejections) =>
+				for (reason, count) in rejections {
+					ENCLAVE_RPC_REJECTIONS
+						.get_metric_with_label_values(&[reason.as_str()])
+						.map(|m| m.inc_by(count))
+						.map_err(|e| Error::Custom(e.into()))?;
+				},
 			#[cfg(feature = "teeracle")]
 			EnclaveMetric::ExchangeRateOracle(m) => update_teeracle_metrics(m)?,
 			#[cfg(not(feature = "teeracle"))]
@@ -23,16 +23,19 @@ use itp_settings::files::{
 };
 use std::{fs, path::Path};
 
-#[cfg(feature = "link-binary")]
+#[cfg(any(feature = "link-binary", feature = "mock-enclave"))]
 pub(crate) use needs_enclave::{
 	generate_shielding_key_file, generate_signing_key_file, init_shard, initialize_shard_and_keys,
 };
 
-#[cfg(feature = "link-binary")]
+#[cfg(any(feature = "link-binary", feature = "mock-enclave"))]
 mod needs_enclave {
-	use crate::error::{Error, ServiceResult};
+	use crate::{
+		enclave::Enclave,
+		error::{Error, ServiceResult},
+	};
 	use codec::Encode;
-	use itp_enclave_api::{enclave_base::EnclaveBase, Enclave};
+	use itp_enclave_api::enclave_base::EnclaveBase;
 	use itp_settings::files::{
 		INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, SHARDS_PATH, SHIELDING_KEY_FILE,
 		SIDECHAIN_STORAGE_PATH, SIGNING_KEY_FILE, TARGET_A_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
@@ -0,0 +1,470 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Management of the shards a worker operates on, both at startup and while it is running.
+
+use crate::{
+	account_funding::shard_vault_initial_funds,
+	error::{Error, ServiceResult},
+	sync_state,
+};
+use base58::ToBase58;
+use codec::{Decode, Encode};
+use itc_parentchain::primitives::ParentchainId;
+use itp_enclave_api::{
+	enclave_base::EnclaveBase,
+	remote_attestation::{RemoteAttestation, TlsRemoteAttestation},
+};
+use itp_node_api::api_client::{PalletTeerexApi, ParentchainApi};
+use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode};
+use itp_types::{
+	parentchain::{AccountId, BlockNumber, Header},
+	ShardIdentifier,
+};
+use log::*;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::{AccountId32, Ss58Codec};
+use sp_runtime::MultiSigner;
+use std::{
+	fs,
+	marker::PhantomData,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use substrate_api_client::{GetAccountInformation, GetChainInfo};
+use teerex_primitives::AnySigner;
+
+/// File in the data dir holding the shard set, once it has been changed at runtime.
+pub const SHARD_SET_FILE: &str = "shard_set.bin";
+
+/// Status of a shard that the worker operates on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardStatus {
+	/// Shard identifier, base58 encoded.
+	pub shard: String,
+	/// Shard vault account (ss58), if it has been initialized.
+	pub vault: Option<String>,
+	/// Integritee parentchain block number at which the shard was created, if known.
+	pub creation_block: Option<BlockNumber>,
+	/// Primary worker of the shard registered on the integritee parentchain (ss58), if any.
+	pub primary_worker: Option<String>,
+	/// Whether this worker is the primary worker of the shard.
+	pub is_primary: bool,
+}
+
+/// Shards the worker operates on.
+///
+/// The enclave loads the state of every shard it has on startup, so the shards that
+/// have been removed are kept too, in order to unload them again after a restart.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ShardSet {
+	pub handled: Vec<ShardIdentifier>,
+	pub removed: Vec<ShardIdentifier>,
+}
+
+impl ShardSet {
+	pub fn new(handled: Vec<ShardIdentifier>) -> Self {
+		ShardSet { handled, removed: Vec::new() }
+	}
+
+	/// Loads the shard set from the data dir, returns `None` if it has never been persisted.
+	pub fn load(data_dir: &Path) -> ServiceResult<Option<Self>> {
+		let shard_set_file = data_dir.join(SHARD_SET_FILE);
+		if !shard_set_file.exists() {
+			return Ok(None)
+		}
+		let encoded = fs::read(&shard_set_file).map_err(|e| Error::Custom(e.into()))?;
+		Ok(Some(Self::decode(&mut encoded.as_slice())?))
+	}
+
+	pub fn save(&self, data_dir: &Path) -> ServiceResult<()> {
+		fs::write(data_dir.join(SHARD_SET_FILE), self.encode()).map_err(|e| Error::Custom(e.into()))
+	}
+
+	fn add(&mut self, shard: ShardIdentifier) {
+		self.removed.retain(|s| s != &shard);
+		self.handled.push(shard);
+	}
+
+	/// Returns false if the shard is not handled.
+	fn remove(&mut self, shard: &ShardIdentifier) -> bool {
+		let handled_len = self.handled.len();
+		self.handled.retain(|s| s != shard);
+		if self.handled.len() == handled_len {
+			return false
+		}
+		self.removed.push(*shard);
+		true
+	}
+}
+
+/// Add, remove and list the shards of a running worker.
+pub trait ManageShards {
+	/// Start operating on a shard, initializing it if necessary.
+	fn add_shard(&self, shard: ShardIdentifier) -> ServiceResult<ShardStatus>;
+
+	/// Stop operating on a shard. Its state is kept, so it can be added again later.
+	fn remove_shard(&self, shard: &ShardIdentifier) -> ServiceResult<()>;
+
+	fn list_shards(&self) -> ServiceResult<Vec<ShardStatus>>;
+}
+
+/// Shard manager implementation, holding the set of shards the worker operates on.
+///
+/// Every change of the shard set is persisted in the data dir, and used on the next startup.
+pub struct ShardManager<E, WorkerModeProvider> {
+	enclave: Arc<E>,
+	integritee_rpc_api: ParentchainApi,
+	maybe_target_a_rpc_api: Option<ParentchainApi>,
+	maybe_target_b_rpc_api: Option<ParentchainApi>,
+	tee_account_id: AccountId32,
+	shielding_target: Option<ParentchainId>,
+	skip_ra: bool,
+	data_dir: PathBuf,
+	shard_set: RwLock<ShardSet>,
+	_phantom: PhantomData<WorkerModeProvider>,
+}
+
+impl<E, WorkerModeProvider> ShardManager<E, WorkerModeProvider> {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		enclave: Arc<E>,
+		integritee_rpc_api: ParentchainApi,
+		maybe_target_a_rpc_api: Option<ParentchainApi>,
+		maybe_target_b_rpc_api: Option<ParentchainApi>,
+		tee_account_id: AccountId32,
+		shielding_target: Option<ParentchainId>,
+		skip_ra: bool,
+		data_dir: PathBuf,
+		shard_set: ShardSet,
+	) -> Self {
+		ShardManager {
+			enclave,
+			integritee_rpc_api,
+			maybe_target_a_rpc_api,
+			maybe_target_b_rpc_api,
+			tee_account_id,
+			shielding_target,
+			skip_ra,
+			data_dir,
+			shard_set: RwLock::new(shard_set),
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<E, WorkerModeProvider> ShardManager<E, WorkerModeProvider>
+where
+	E: EnclaveBase + RemoteAttestation + TlsRemoteAttestation,
+{
+	fn shard_status(&self, shard: &ShardIdentifier) -> ServiceResult<ShardStatus> {
+		let vault = self.enclave.get_ecc_vault_pubkey(shard).ok().map(|v| v.to_ss58check());
+		let creation_block = self
+			.enclave
+			.get_shard_creation_info(shard)?
+			.for_parentchain(ParentchainId::Integritee)
+			.map(|block| block.number);
+		let primary_worker = self
+			.integritee_rpc_api
+			.primary_worker_for_shard(shard, None)?
+			.and_then(|primary_enclave| match primary_enclave.instance_signer() {
+				AnySigner::Known(MultiSigner::Ed25519(primary)) => Some(primary),
+				_ => None,
+			});
+		let is_primary = primary_worker
+			.map_or(false, |primary| primary.encode() == self.tee_account_id.encode());
+
+		Ok(ShardStatus {
+			shard: shard.encode().to_base58(),
+			vault,
+			creation_block,
+			primary_worker: primary_worker.map(|primary| primary.to_ss58check()),
+			is_primary,
+		})
+	}
+}
+
+impl<E, WorkerModeProvider> ManageShards for ShardManager<E, WorkerModeProvider>
+where
+	E: EnclaveBase + RemoteAttestation + TlsRemoteAttestation,
+	WorkerModeProvider: ProvideWorkerMode,
+{
+	fn add_shard(&self, shard: ShardIdentifier) -> ServiceResult<ShardStatus> {
+		if self.shard_set.read().handled.contains(&shard) {
+			return Err(already_handled(&shard))
+		}
+
+		// A shard we have operated on before still has its state, which is then used again.
+		if let Err(e) = self.enclave.load_shard(&shard) {
+			debug!("No previous state of shard {:?} found: {:?}", shard, e);
+		}
+
+		// A new shard is created at the current head of each parentchain.
+		let creation_header = latest_header(&self.integritee_rpc_api)?;
+		let (we_are_primary_validateer, _) = init_shard_for_worker::<_, WorkerModeProvider>(
+			self.enclave.as_ref(),
+			&self.integritee_rpc_api,
+			&self.tee_account_id,
+			&shard,
+			&creation_header,
+			self.skip_ra,
+		)?;
+
+		for (parentchain_id, maybe_api) in [
+			(ParentchainId::TargetA, &self.maybe_target_a_rpc_api),
+			(ParentchainId::TargetB, &self.maybe_target_b_rpc_api),
+		] {
+			if let Some(api) = maybe_api {
+				// we ignore failure, the creation header might have been set before
+				let _ = self.enclave.init_shard_creation_parentchain_header(
+					&shard,
+					&parentchain_id,
+					&latest_header(api)?,
+				);
+			}
+		}
+
+		init_provided_shard_vault(
+			&shard,
+			self.enclave.as_ref(),
+			self.integritee_rpc_api.clone(),
+			self.maybe_target_a_rpc_api.clone(),
+			self.maybe_target_b_rpc_api.clone(),
+			self.shielding_target,
+			we_are_primary_validateer,
+		)?;
+
+		{
+			let mut shard_set_lock = self.shard_set.write();
+			// The shard might have been added concurrently, while we initialized it.
+			if shard_set_lock.handled.contains(&shard) {
+				return Err(already_handled(&shard))
+			}
+			shard_set_lock.add(shard);
+			shard_set_lock.save(&self.data_dir)?;
+		}
+		info!("Added shard {}", shard.encode().to_base58());
+		self.shard_status(&shard)
+	}
+
+	fn remove_shard(&self, shard: &ShardIdentifier) -> ServiceResult<()> {
+		let mut shard_set_lock = self.shard_set.write();
+		if !shard_set_lock.handled.contains(shard) {
+			return Err(Error::Custom(
+				format!("Shard {} is not handled", shard.encode().to_base58()).into(),
+			))
+		}
+
+		self.enclave.unload_shard(shard)?;
+		shard_set_lock.remove(shard);
+		shard_set_lock.save(&self.data_dir)?;
+		info!("Removed shard {}", shard.encode().to_base58());
+		Ok(())
+	}
+
+	fn list_shards(&self) -> ServiceResult<Vec<ShardStatus>> {
+		self.shard_set
+			.read()
+			.handled
+			.iter()
+			.map(|shard| self.shard_status(shard))
+			.collect()
+	}
+}
+
+fn already_handled(shard: &ShardIdentifier) -> Error {
+	Error::Custom(format!("Shard {} is already handled", shard.encode().to_base58()).into())
+}
+
+/// Checks who is the primary worker of the shard on the integritee parentchain and initializes
+/// the shard accordingly. Initializes an untouched shard with the given creation header, or
+/// requests state provisioning from the primary worker if we don't know the shard yet.
+///
+/// Returns whether we are the primary worker of the shard, and whether the integritee
+/// parentchain has to be re-initialized for the shard's creation header to be respected.
+pub(crate) fn init_shard_for_worker<E, WorkerModeProvider>(
+	enclave: &E,
+	integritee_rpc_api: &ParentchainApi,
+	tee_account_id: &AccountId32,
+	shard: &ShardIdentifier,
+	creation_header: &Header,
+	skip_ra: bool,
+) -> ServiceResult<(bool, bool)>
+where
+	E: EnclaveBase + RemoteAttestation + TlsRemoteAttestation,
+	WorkerModeProvider: ProvideWorkerMode,
+{
+	let shard_str = shard.encode().to_base58();
+	match integritee_rpc_api.primary_worker_for_shard(shard, None)? {
+		Some(primary_enclave) =>
+			match primary_enclave.instance_signer() {
+				AnySigner::Known(MultiSigner::Ed25519(primary)) =>
+					if primary.encode() == tee_account_id.encode() {
+						println!("We are primary worker on shard {} and we have been previously running.", shard_str);
+						Ok((true, false))
+					} else {
+						println!(
+							"We are NOT primary worker on shard {}. The primary worker is {}.",
+							shard_str,
+							primary.to_ss58check(),
+						);
+						info!("The primary worker enclave is {:?}", primary_enclave);
+						if enclave
+							.get_shard_creation_info(shard)?
+							.for_parentchain(ParentchainId::Integritee)
+							.is_none()
+						{
+							//obtain provisioning from last active worker as this hasn't been done before
+							info!("my state doesn't know the creation header of the shard. will request provisioning");
+							sync_state::sync_state::<_, _, WorkerModeProvider>(
+								integritee_rpc_api,
+								shard,
+								enclave,
+								skip_ra,
+							)?;
+						}
+						Ok((false, true))
+					},
+				_ => Err(Error::Custom(
+					format!(
+						"the primary worker for shard {:?} has unknown signer type: {:?}",
+						shard, primary_enclave
+					)
+					.into(),
+				)),
+			},
+		None => {
+			println!("We are the primary worker on shard {} and the shard is untouched. Will initialize it", shard_str);
+			enclave.init_shard(shard.encode())?;
+			if WorkerModeProvider::worker_mode() != WorkerMode::Teeracle {
+				enclave.init_shard_creation_parentchain_header(
+					shard,
+					&ParentchainId::Integritee,
+					creation_header,
+				)?;
+				debug!("shard config should be initialized on integritee network now");
+				Ok((true, true))
+			} else {
+				Ok((true, false))
+			}
+		},
+	}
+}
+
+pub(crate) fn init_provided_shard_vault<E: EnclaveBase>(
+	shard: &ShardIdentifier,
+	enclave: &E,
+	integritee_rpc_api: ParentchainApi,
+	maybe_target_a_rpc_api: Option<ParentchainApi>,
+	maybe_target_b_rpc_api: Option<ParentchainApi>,
+	shielding_target: Option<ParentchainId>,
+	we_are_primary_validateer: bool,
+) -> ServiceResult<()> {
+	let shielding_target = shielding_target.unwrap_or(ParentchainId::Integritee);
+	let rpc_api = match shielding_target {
+		ParentchainId::Integritee => Some(integritee_rpc_api),
+		ParentchainId::TargetA => maybe_target_a_rpc_api,
+		ParentchainId::TargetB => maybe_target_b_rpc_api,
+	}
+	.ok_or_else(|| {
+		Error::Custom(
+			format!("{:?} must be initialized to be used as shielding target", shielding_target)
+				.into(),
+		)
+	})?;
+	if let Ok(shard_vault) = enclave.get_ecc_vault_pubkey(shard) {
+		// verify if proxy is set up on chain
+		let nonce = rpc_api.get_account_nonce(&AccountId::from(shard_vault))?;
+		println!(
+			"[{:?}] shard vault account is already initialized in state: {} with nonce {}",
+			shielding_target,
+			shard_vault.to_ss58check(),
+			nonce
+		);
+		if nonce == 0 && we_are_primary_validateer {
+			println!(
+				"[{:?}] nonce = 0 means shard vault not properly set up on chain. will retry",
+				shielding_target
+			);
+			enclave.init_proxied_shard_vault(shard, &shielding_target, 0u128)?;
+		}
+	} else if we_are_primary_validateer {
+		let funding_balance = shard_vault_initial_funds(&rpc_api)?;
+		println!("[{:?}] initializing proxied shard vault account now", shielding_target);
+		enclave.init_proxied_shard_vault(shard, &shielding_target, funding_balance)?;
+		println!(
+			"[{:?}] initialized shard vault account: : {}",
+			shielding_target,
+			enclave.get_ecc_vault_pubkey(shard)?.to_ss58check()
+		);
+	} else {
+		return Err(Error::Custom(
+			format!(
+				"no vault account has been initialized for shard {} and we are not the primary worker",
+				shard.encode().to_base58()
+			)
+			.into(),
+		))
+	}
+	Ok(())
+}
+
+fn latest_header(api: &ParentchainApi) -> ServiceResult<Header> {
+	let api_head = api.get_header(None)?.ok_or(Error::MissingLastFinalizedBlock)?;
+	// TODO: #1451: Fix api-client type hacks
+	Ok(Header::decode(&mut api_head.encode().as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_temp_dir::TempDir;
+
+	#[test]
+	fn removed_shard_stays_removed_after_reload() {
+		let temp_dir = TempDir::with_prefix("removed_shard_stays_removed_after_reload").unwrap();
+		let shard_a = ShardIdentifier::from([1u8; 32]);
+		let shard_b = ShardIdentifier::from([2u8; 32]);
+
+		let mut shard_set = ShardSet::new(vec![shard_a, shard_b]);
+		assert!(shard_set.remove(&shard_a));
+		assert!(!shard_set.remove(&shard_a));
+		shard_set.save(temp_dir.path()).unwrap();
+
+		let loaded = ShardSet::load(temp_dir.path()).unwrap().unwrap();
+		assert_eq!(loaded.handled, vec![shard_b]);
+		assert_eq!(loaded.removed, vec![shard_a]);
+	}
+
+	#[test]
+	fn adding_a_removed_shard_handles_it_again() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let mut shard_set = ShardSet::new(vec![shard]);
+		shard_set.remove(&shard);
+
+		shard_set.add(shard);
+
+		assert_eq!(shard_set, ShardSet::new(vec![shard]));
+	}
+
+	#[test]
+	fn loading_unpersisted_shard_set_returns_none() {
+		let temp_dir = TempDir::with_prefix("loading_unpersisted_shard_set_returns_none").unwrap();
+		assert!(ShardSet::load(temp_dir.path()).unwrap().is_none());
+	}
+}
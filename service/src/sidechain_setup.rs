@@ -16,6 +16,7 @@
 */
 
 use crate::{
+	attestation_renewal::RegistrationTracker,
 	config::Config,
 	error::{Error, ServiceResult},
 	parentchain_handler::HandleParentchain,
@@ -24,14 +25,16 @@ use futures::executor::block_on;
 use itp_enclave_api::{
 	direct_request::DirectRequest, enclave_base::EnclaveBase, sidechain::Sidechain,
 };
-use itp_settings::{
-	files::{SIDECHAIN_PURGE_INTERVAL, SIDECHAIN_PURGE_LIMIT},
-	sidechain::SLOT_DURATION,
-};
+use itp_node_api::api_client::{PalletSidechainApi, ParentchainApi};
+use itp_settings::{files::SIDECHAIN_PURGE_INTERVAL, sidechain::SLOT_DURATION};
 use itp_types::{Header, ShardIdentifier};
+use its_consensus_common::block_production_suspension::IsBlockProductionSuspended;
 use its_consensus_slots::start_slot_worker;
-use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-use its_storage::{interface::FetchBlocks, start_sidechain_pruning_loop, BlockPruner};
+use its_primitives::types::{block::SignedBlock as SignedSidechainBlock, BlockNumber};
+use its_storage::{
+	confirmed_block_number, interface::FetchBlocks, start_sidechain_pruning_loop, BlockPruner,
+	FetchFinalizedBlockNumber, PruningPolicy,
+};
 use log::*;
 use std::{sync::Arc, thread};
 use tokio::runtime::Handle;
@@ -57,13 +60,18 @@ pub(crate) fn sidechain_start_untrusted_rpc_server<Enclave, SidechainStorage>(
 	});
 }
 
-pub(crate) fn sidechain_init_block_production<Enclave, SidechainStorage>(
+pub(crate) fn sidechain_init_block_production<Enclave, SidechainStorage, BlockProductionSuspender>(
 	enclave: Arc<Enclave>,
 	sidechain_storage: Arc<SidechainStorage>,
+	block_production_suspender: Arc<BlockProductionSuspender>,
+	registration_tracker: Arc<RegistrationTracker>,
+	integritee_rpc_api: ParentchainApi,
+	pruning_policy: PruningPolicy,
 ) -> ServiceResult<()>
 where
 	Enclave: EnclaveBase + Sidechain,
 	SidechainStorage: BlockPruner + FetchBlocks<SignedSidechainBlock> + Sync + Send + 'static,
+	BlockProductionSuspender: IsBlockProductionSuspended + Send + Sync + 'static,
 {
 	// ------------------------------------------------------------------------
 	// Initialize sidechain components (has to be AFTER init_parentchain_components()
@@ -77,7 +85,13 @@ where
 		.name("interval_block_production_timer".to_owned())
 		.spawn(move || {
 			let future = start_slot_worker(
-				|| execute_trusted_calls(sidechain_enclave_api.as_ref()),
+				|| {
+					execute_trusted_calls(
+						sidechain_enclave_api.as_ref(),
+						block_production_suspender.as_ref(),
+						registration_tracker.as_ref(),
+					)
+				},
 				SLOT_DURATION,
 			);
 			block_on(future);
@@ -87,13 +101,19 @@ where
 
 	// ------------------------------------------------------------------------
 	// start sidechain pruning loop
+	if pruning_policy.is_archive() {
+		println!("[+] Sidechain block storage runs in archive mode, blocks are never pruned");
+		return Ok(())
+	}
+	let confirmed_blocks = ConfirmedSidechainBlocks { api: integritee_rpc_api };
 	thread::Builder::new()
 		.name("sidechain_pruning_loop".to_owned())
 		.spawn(move || {
 			start_sidechain_pruning_loop(
 				&sidechain_storage,
+				&confirmed_blocks,
 				SIDECHAIN_PURGE_INTERVAL,
-				SIDECHAIN_PURGE_LIMIT,
+				pruning_policy,
 			);
 		})
 		.map_err(|e| Error::Custom(Box::new(e)))?;
@@ -101,9 +121,55 @@ where
 	Ok(())
 }
 
-/// Execute trusted operations in the enclave.
-fn execute_trusted_calls<E: Sidechain>(enclave_api: &E) {
-	if let Err(e) = enclave_api.execute_trusted_calls() {
+/// Sidechain blocks are finalized, once they are confirmed on the Integritee parentchain.
+struct ConfirmedSidechainBlocks<Api> {
+	api: Api,
+}
+
+impl<Api: PalletSidechainApi> FetchFinalizedBlockNumber for ConfirmedSidechainBlocks<Api> {
+	fn finalized_block_number(&self, shard: &ShardIdentifier) -> Option<BlockNumber> {
+		match self.api.latest_sidechain_block_confirmation(shard, None) {
+			Ok(confirmation) => confirmation.as_ref().map(confirmed_block_number),
+			Err(e) => {
+				warn!(
+					"Could not fetch the last confirmed sidechain block of shard {:?}: {:?}",
+					shard, e
+				);
+				None
+			},
+		}
+	}
+}
+
+/// Execute trusted operations in the enclave, unless block production is paused or the
+/// registration of our enclave has lapsed.
+///
+/// The sidechain blocks of our peers are imported in any case, so we are in sync once block
+/// production continues.
+fn execute_trusted_calls<E: Sidechain, S: IsBlockProductionSuspended>(
+	enclave_api: &E,
+	block_production_suspender: &S,
+	registration_tracker: &RegistrationTracker,
+) {
+	let produce_blocks = match block_production_suspender.is_suspended() {
+		Ok(false) => true,
+		Ok(true) => {
+			debug!("Block production is paused, only importing sidechain blocks");
+			false
+		},
+		Err(e) => {
+			error!("Could not check if block production is paused: {:?}", e);
+			false
+		},
+	};
+	let produce_blocks = produce_blocks && {
+		let is_registration_valid = registration_tracker.is_registration_valid();
+		if !is_registration_valid {
+			warn!("Registration of our enclave has lapsed, only importing sidechain blocks");
+		}
+		is_registration_valid
+	};
+	if let Err(e) = enclave_api.execute_trusted_calls(produce_blocks) {
 		error!("{:?}", e);
 	};
 }
@@ -19,6 +19,8 @@
 use crate::{
 	enclave::tls_ra::enclave_request_state_provisioning,
 	error::{Error, ServiceResult as Result},
+	shard_manager::ShardSet,
+	worker_admin::write_shielding_key_file,
 };
 use futures::executor;
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
@@ -27,14 +29,22 @@ use itp_enclave_api::{
 	remote_attestation::{RemoteAttestation, TlsRemoteAttestation},
 };
 use itp_node_api::api_client::PalletTeerexApi;
-use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode};
+use itp_settings::{
+	worker::SHIELDING_KEY_SYNC_INTERVAL,
+	worker_mode::{ProvideWorkerMode, WorkerMode},
+};
 use itp_types::{parentchain::AccountId, ShardIdentifier};
-use log::info;
+use log::{error, info, warn};
 use sgx_types::sgx_quote_sign_type_t;
 use sp_runtime::MultiSigner;
-use std::string::String;
+use std::{path::PathBuf, string::String, sync::Arc, thread};
 use teerex_primitives::AnySigner;
 
+/// How many times we try each peer before giving up.
+///
+/// A state transfer that has been interrupted is resumed by the next attempt.
+const PROVISIONING_ROUNDS: usize = 3;
+
 pub(crate) fn sync_state<
 	E: TlsRemoteAttestation + EnclaveBase + RemoteAttestation,
 	NodeApi: PalletTeerexApi,
@@ -44,27 +54,148 @@ pub(crate) fn sync_state<
 	shard: &ShardIdentifier,
 	enclave_api: &E,
 	skip_ra: bool,
-) {
-	let provider_url = match WorkerModeProvider::worker_mode() {
+) -> Result<()> {
+	let peer_urls = match WorkerModeProvider::worker_mode() {
 		WorkerMode::Sidechain | WorkerMode::OffChainWorker =>
-			executor::block_on(get_enclave_url_of_last_active(node_api, enclave_api, shard))
-				.expect("author of most recent shard update not found"),
+			executor::block_on(get_enclave_urls_of_active_peers(node_api, enclave_api, shard))?,
 		WorkerMode::Teeracle =>
-			executor::block_on(get_enclave_url_of_first_registered(node_api, enclave_api))
-				.expect("Author of last finalized sidechain block could not be found"),
+			executor::block_on(get_enclave_urls_of_registered_peers(node_api, enclave_api))?,
 	};
 
-	println!("Requesting state provisioning from worker at {}", &provider_url);
+	request_state_provisioning_from_any_peer(enclave_api, &peer_urls, shard, skip_ra)?;
+	println!("[+] State provisioning successfully performed.");
+	Ok(())
+}
+
+/// Requests the state provisioning from the peers in the given order, until one succeeds.
+fn request_state_provisioning_from_any_peer<E: TlsRemoteAttestation + RemoteAttestation>(
+	enclave_api: &E,
+	peer_urls: &[String],
+	shard: &ShardIdentifier,
+	skip_ra: bool,
+) -> Result<()> {
+	for round in 0..PROVISIONING_ROUNDS {
+		for peer_url in peer_urls {
+			let provider_url = match DirectWorkerApi::new(peer_url.clone()).get_mu_ra_url() {
+				Ok(url) => url,
+				Err(e) => {
+					warn!("Could not get the mu-ra url of peer {}: {:?}", peer_url, e);
+					continue
+				},
+			};
+			println!(
+				"Requesting state provisioning from worker at {} (round {})",
+				&provider_url,
+				round + 1
+			);
+			match enclave_request_state_provisioning(
+				enclave_api,
+				sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
+				&provider_url,
+				shard,
+				skip_ra,
+				false,
+			) {
+				Ok(()) => return Ok(()),
+				Err(e) => warn!("State provisioning from {} failed: {:?}", provider_url, e),
+			}
+		}
+	}
+	Err(Error::NoPeerWorkerFound)
+}
+
+/// Fetches the shielding key from the primary worker of the shard, if it differs from ours.
+///
+/// The shielding key is rotated on the primary worker, the other workers follow it with this.
+/// Returns whether our shielding key has been updated.
+pub(crate) fn sync_shielding_key<
+	E: TlsRemoteAttestation + EnclaveBase + RemoteAttestation,
+	NodeApi: PalletTeerexApi,
+>(
+	node_api: &NodeApi,
+	shard: &ShardIdentifier,
+	enclave_api: &E,
+	skip_ra: bool,
+) -> Result<bool> {
+	let primary_worker = node_api
+		.primary_worker_for_shard(shard, None)?
+		.ok_or_else(|| Error::NoWorkerForShardFound(*shard))?;
+	let self_account = enclave_api.get_ecc_signing_pubkey()?;
+	if primary_worker.instance_signer() == AnySigner::Known(MultiSigner::Ed25519(self_account)) {
+		return Ok(false)
+	}
+	let primary_worker_url = primary_worker
+		.instance_url()
+		.and_then(|url| String::from_utf8(url).ok())
+		.ok_or_else(|| Error::NoWorkerForShardFound(*shard))?;
+
+	let primary_worker_api = DirectWorkerApi::new(primary_worker_url);
+	let primary_shielding_key = serde_json::to_vec(&primary_worker_api.get_rsa_pubkey()?)?;
+	let shielding_key = serde_json::to_vec(&enclave_api.get_rsa_shielding_pubkey()?)?;
+	if primary_shielding_key == shielding_key {
+		return Ok(false)
+	}
 
+	let provider_url = primary_worker_api.get_mu_ra_url()?;
+	info!("Primary worker rotated the shielding key, fetching it from {}", provider_url);
 	enclave_request_state_provisioning(
 		enclave_api,
 		sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
 		&provider_url,
 		shard,
 		skip_ra,
-	)
-	.unwrap();
-	println!("[+] State provisioning successfully performed.");
+		true,
+	)?;
+	Ok(true)
+}
+
+/// Periodically follows the shielding key of the primary worker, see [`sync_shielding_key`].
+///
+/// The handled shards are re-read from the data dir in every round, so shards that are added
+/// while running are covered too. An updated public key is written to the data dir.
+pub(crate) fn start_shielding_key_sync_loop<E, NodeApi>(
+	node_api: NodeApi,
+	enclave_api: Arc<E>,
+	shards: Vec<ShardIdentifier>,
+	data_dir: PathBuf,
+	skip_ra: bool,
+) where
+	E: TlsRemoteAttestation + EnclaveBase + RemoteAttestation + Send + Sync + 'static,
+	NodeApi: PalletTeerexApi + Send + 'static,
+{
+	thread::Builder::new()
+		.name("shielding_key_sync".to_owned())
+		.spawn(move || loop {
+			thread::sleep(SHIELDING_KEY_SYNC_INTERVAL);
+
+			let handled_shards = match ShardSet::load(&data_dir) {
+				Ok(Some(shard_set)) => shard_set.handled,
+				Ok(None) => shards.clone(),
+				Err(e) => {
+					warn!("Failed to load the shard set, using the initial shards: {:?}", e);
+					shards.clone()
+				},
+			};
+
+			// There is a single shielding key per enclave, one update is enough.
+			for shard in handled_shards.iter() {
+				match sync_shielding_key(&node_api, shard, enclave_api.as_ref(), skip_ra) {
+					Ok(false) => continue,
+					Ok(true) => {
+						if let Err(e) = enclave_api
+							.get_rsa_shielding_pubkey()
+							.map_err(Error::from)
+							.and_then(|pubkey| write_shielding_key_file(&data_dir, &pubkey))
+						{
+							error!("Failed to export the updated shielding key: {:?}", e);
+						}
+						break
+					},
+					Err(e) => warn!("Shielding key sync for shard {:?} failed: {:?}", shard, e),
+				}
+			}
+		})
+		.expect("Failed to spawn the shielding key sync thread");
 }
 
 /// Returns the url of the last sidechain block author that has been stored
@@ -85,51 +216,54 @@ async fn get_author_url_of_last_finalized_sidechain_block<NodeApi: PalletTeerexA
 	Ok(worker_api_direct.get_mu_ra_url()?)
 }
 
-/// Returns the url of the first Enclave that matches our own MRENCLAVE which isn't ourself.
-/// this is not reliable because there may be other active peers although the very first one went offline
-async fn get_enclave_url_of_first_registered<NodeApi: PalletTeerexApi, EnclaveApi: EnclaveBase>(
+/// Returns the urls of all registered enclaves that match our own MRENCLAVE, except ourself.
+async fn get_enclave_urls_of_registered_peers<NodeApi: PalletTeerexApi, EnclaveApi: EnclaveBase>(
 	node_api: &NodeApi,
 	enclave_api: &EnclaveApi,
-) -> Result<String> {
+) -> Result<Vec<String>> {
 	let self_mr_enclave = enclave_api.get_fingerprint()?;
 	let self_account = enclave_api.get_ecc_signing_pubkey()?;
-	let first_enclave = node_api
+	let peer_urls: Vec<String> = node_api
 		.all_enclaves(None)?
 		.into_iter()
 		.filter(|e| e.instance_signer() != AnySigner::Known(MultiSigner::Ed25519(self_account)))
-		.find(|e| e.fingerprint() == self_mr_enclave)
-		.ok_or(Error::NoPeerWorkerFound)?;
-	let worker_api_direct =
-		DirectWorkerApi::new(String::from_utf8(first_enclave.instance_url().unwrap()).unwrap());
-	Ok(worker_api_direct.get_mu_ra_url()?)
+		.filter(|e| e.fingerprint() == self_mr_enclave)
+		.filter_map(|e| e.instance_url())
+		.filter_map(|url| String::from_utf8(url).ok())
+		.collect();
+	if peer_urls.is_empty() {
+		return Err(Error::NoPeerWorkerFound)
+	}
+	Ok(peer_urls)
 }
 
-/// Returns the url of the last active worker on our shard
-async fn get_enclave_url_of_last_active<NodeApi: PalletTeerexApi, EnclaveApi: EnclaveBase>(
+/// Returns the urls of the active peers on our shard, the most recently active first.
+async fn get_enclave_urls_of_active_peers<NodeApi: PalletTeerexApi, EnclaveApi: EnclaveBase>(
 	node_api: &NodeApi,
 	enclave_api: &EnclaveApi,
 	shard: &ShardIdentifier,
-) -> Result<String> {
+) -> Result<Vec<String>> {
 	let self_account = enclave_api.get_ecc_signing_pubkey()?;
-	let shard_status = node_api
-		.shard_status(shard, None)
-		.expect("must be able to fetch shard status")
-		.expect("can only sync state for active shards");
-	info!("fetching active peer. shard status: {:?}", shard_status);
-	let last_active_signer_status = shard_status
-		.iter()
-		.filter(|&s| s.signer != AccountId::from(self_account))
-		.max_by_key(|&signer_status| signer_status.last_activity)
-		.expect("there has to be a most recently active peer")
-		.clone();
-	info!("most recently active signer on this shard: {:?}", last_active_signer_status);
-	let provider_enclave = node_api
-		.enclave(&last_active_signer_status.signer, None)
-		.expect("must be able to fetch enclaves")
-		.expect("active peer must exist in registry");
-	let worker_api_direct = DirectWorkerApi::new(
-		String::from_utf8(provider_enclave.instance_url().expect("provider must specify url"))
-			.unwrap(),
-	);
-	Ok(worker_api_direct.get_mu_ra_url()?)
+	let mut shard_status = node_api
+		.shard_status(shard, None)?
+		.ok_or_else(|| Error::NoWorkerForShardFound(*shard))?;
+	info!("fetching active peers. shard status: {:?}", shard_status);
+	shard_status.retain(|s| s.signer != AccountId::from(self_account));
+	shard_status.sort_by_key(|signer_status| core::cmp::Reverse(signer_status.last_activity));
+
+	let mut peer_urls = Vec::new();
+	for signer_status in shard_status {
+		match node_api.enclave(&signer_status.signer, None)? {
+			Some(enclave) => match enclave.instance_url().map(String::from_utf8) {
+				Some(Ok(url)) => peer_urls.push(url),
+				_ => warn!("Active peer {:?} does not specify a valid url", signer_status.signer),
+			},
+			None => warn!("Active peer {:?} is not registered", signer_status.signer),
+		}
+	}
+	if peer_urls.is_empty() {
+		return Err(Error::NoPeerWorkerFound)
+	}
+	info!("active peers on this shard, most recent first: {:?}", peer_urls);
+	Ok(peer_urls)
 }
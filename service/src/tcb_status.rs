@@ -0,0 +1,256 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! TCB status of our platform, evaluated against the TCB info of the DCAP collateral.
+//!
+//! The collateral is the one we register along with each attestation, so the status is known
+//! independently of how the teerex pallet evaluates it.
+
+use crate::{
+	error::{Error, ServiceResult},
+	prometheus_metrics::set_enclave_tcb_status,
+};
+use itp_attestation_handler::SgxQlQveCollateral;
+use log::*;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+/// TCB status of a TCB level in the TCB info issued by Intel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TcbStatus {
+	UpToDate,
+	SWHardeningNeeded,
+	ConfigurationNeeded,
+	ConfigurationAndSWHardeningNeeded,
+	OutOfDate,
+	OutOfDateConfigurationNeeded,
+	Revoked,
+	/// Our TCB is below all TCB levels, or the status is unknown to us.
+	Unrecognized,
+}
+
+impl TcbStatus {
+	fn from_tcb_info(tcb_status: &str) -> Self {
+		match tcb_status {
+			"UpToDate" => TcbStatus::UpToDate,
+			"SWHardeningNeeded" => TcbStatus::SWHardeningNeeded,
+			"ConfigurationNeeded" => TcbStatus::ConfigurationNeeded,
+			"ConfigurationAndSWHardeningNeeded" => TcbStatus::ConfigurationAndSWHardeningNeeded,
+			"OutOfDate" => TcbStatus::OutOfDate,
+			"OutOfDateConfigurationNeeded" => TcbStatus::OutOfDateConfigurationNeeded,
+			"Revoked" => TcbStatus::Revoked,
+			_ => TcbStatus::Unrecognized,
+		}
+	}
+
+	/// The same policy as we apply to the quotes of peers.
+	pub fn is_up_to_date(&self) -> bool {
+		matches!(self, TcbStatus::UpToDate | TcbStatus::SWHardeningNeeded)
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TcbInfo {
+	tcb_levels: Vec<TcbLevel>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TcbLevel {
+	tcb: serde_json::Value,
+	tcb_status: String,
+}
+
+/// Evaluates the TCB status of our platform, given by the `cpusvn` and `pcesvn` of the PCK
+/// certificate in our quote, against the TCB levels in the TCB info of the `collateral`.
+///
+/// The TCB levels are sorted in descending order, the status of the first level which our TCB
+/// reaches in all components applies.
+pub(crate) fn evaluate_tcb_status(
+	collateral: &SgxQlQveCollateral,
+	cpusvn: &[u8; 16],
+	pcesvn: u16,
+) -> ServiceResult<TcbStatus> {
+	let (tcb_info, _signature) = collateral
+		.get_tcb_info_split()
+		.ok_or_else(|| Error::Custom("Collateral contains no valid TCB info".into()))?;
+	let tcb_info: TcbInfo = serde_json::from_str(&tcb_info)?;
+
+	for level in tcb_info.tcb_levels {
+		let (level_cpusvn, level_pcesvn) = tcb_level_components(&level.tcb)
+			.ok_or_else(|| Error::Custom("Malformed TCB level in the TCB info".into()))?;
+		if level_cpusvn.iter().zip(cpusvn.iter()).all(|(level_svn, svn)| level_svn <= svn)
+			&& level_pcesvn <= pcesvn
+		{
+			return Ok(TcbStatus::from_tcb_info(&level.tcb_status))
+		}
+	}
+	Ok(TcbStatus::Unrecognized)
+}
+
+/// The SVNs of a TCB level, in the layout of TCB info version 2 or 3.
+fn tcb_level_components(tcb: &serde_json::Value) -> Option<([u8; 16], u16)> {
+	let pcesvn = u16::try_from(tcb["pcesvn"].as_u64()?).ok()?;
+	let mut cpusvn = [0u8; 16];
+	for (i, svn) in cpusvn.iter_mut().enumerate() {
+		let component_svn = match tcb["sgxtcbcomponents"].as_array() {
+			Some(components) => components.get(i)?["svn"].as_u64()?,
+			None => tcb[format!("sgxtcbcomp{:02}svn", i + 1).as_str()].as_u64()?,
+		};
+		*svn = u8::try_from(component_svn).ok()?;
+	}
+	Some((cpusvn, pcesvn))
+}
+
+/// Keeps track of the TCB status of our platform, reporting changes.
+#[derive(Default)]
+pub(crate) struct TcbStatusTracker {
+	tcb_status: RwLock<Option<TcbStatus>>,
+}
+
+impl TcbStatusTracker {
+	/// Records the TCB status evaluated for a new attestation and returns the previous one.
+	pub fn update(&self, tcb_status: TcbStatus) -> Option<TcbStatus> {
+		let previous_tcb_status = self.tcb_status.write().replace(tcb_status);
+
+		if tcb_status.is_up_to_date() {
+			debug!("TCB status of the platform is {:?}", tcb_status);
+		} else if previous_tcb_status != Some(tcb_status) {
+			warn!(
+				"TCB status of the platform changed to {:?}, the platform needs an update",
+				tcb_status
+			);
+		} else {
+			debug!("TCB status of the platform is still {:?}", tcb_status);
+		}
+		if previous_tcb_status.map_or(false, |s| s.is_up_to_date()) && !tcb_status.is_up_to_date() {
+			error!("Our TCB is no longer up to date: {:?}", tcb_status);
+		}
+
+		set_enclave_tcb_status(tcb_status);
+		previous_tcb_status
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_attestation_handler::collateral::{COLLATERAL_VERSION, TEE_TYPE_SGX};
+
+	#[test]
+	fn tracker_update_returns_previous_status() {
+		let tracker = TcbStatusTracker::default();
+
+		assert_eq!(tracker.update(TcbStatus::UpToDate), None);
+		assert_eq!(tracker.update(TcbStatus::OutOfDate), Some(TcbStatus::UpToDate));
+	}
+
+	#[test]
+	fn tcb_status_is_evaluated_against_tcb_levels() {
+		let collateral = collateral_with_tcb_levels(&format!(
+			r#"[{},{}]"#,
+			tcb_level_v3(5, 11, "UpToDate"),
+			tcb_level_v3(4, 10, "OutOfDate")
+		));
+
+		assert_eq!(evaluate_tcb_status(&collateral, &cpusvn(6), 11).unwrap(), TcbStatus::UpToDate);
+		assert_eq!(evaluate_tcb_status(&collateral, &cpusvn(4), 11).unwrap(), TcbStatus::OutOfDate);
+		assert_eq!(evaluate_tcb_status(&collateral, &cpusvn(5), 10).unwrap(), TcbStatus::OutOfDate);
+		assert_eq!(
+			evaluate_tcb_status(&collateral, &cpusvn(3), 11).unwrap(),
+			TcbStatus::Unrecognized
+		);
+	}
+
+	#[test]
+	fn tcb_status_is_evaluated_for_tcb_info_version_2() {
+		let components: Vec<String> =
+			(1..=16).map(|i| format!(r#""sgxtcbcomp{:02}svn":2"#, i)).collect();
+		let collateral = collateral_with_tcb_levels(&format!(
+			r#"[{{"tcb":{{{},"pcesvn":7}},"tcbStatus":"SWHardeningNeeded"}}]"#,
+			components.join(",")
+		));
+
+		assert_eq!(
+			evaluate_tcb_status(&collateral, &[2u8; 16], 7).unwrap(),
+			TcbStatus::SWHardeningNeeded
+		);
+		assert_eq!(
+			evaluate_tcb_status(&collateral, &[1u8; 16], 7).unwrap(),
+			TcbStatus::Unrecognized
+		);
+	}
+
+	#[test]
+	fn unknown_tcb_status_is_unrecognized() {
+		let collateral =
+			collateral_with_tcb_levels(&format!(r#"[{}]"#, tcb_level_v3(1, 1, "SomethingNew")));
+
+		assert_eq!(
+			evaluate_tcb_status(&collateral, &cpusvn(1), 1).unwrap(),
+			TcbStatus::Unrecognized
+		);
+	}
+
+	#[test]
+	fn tcb_status_evaluation_fails_for_malformed_tcb_info() {
+		let collateral =
+			collateral_with_tcb_levels(r#"[{"tcb":{"pcesvn":7},"tcbStatus":"UpToDate"}]"#);
+
+		assert!(evaluate_tcb_status(&collateral, &[2u8; 16], 7).is_err());
+	}
+
+	/// CPU SVN with the given first component, the others are 0.
+	fn cpusvn(first_component: u8) -> [u8; 16] {
+		let mut cpusvn = [0u8; 16];
+		cpusvn[0] = first_component;
+		cpusvn
+	}
+
+	fn tcb_level_v3(first_component: u8, pcesvn: u16, tcb_status: &str) -> String {
+		let components: Vec<String> = cpusvn(first_component)
+			.iter()
+			.map(|svn| format!(r#"{{"svn":{}}}"#, svn))
+			.collect();
+		format!(
+			r#"{{"tcb":{{"sgxtcbcomponents":[{}],"pcesvn":{}}},"tcbStatus":"{}"}}"#,
+			components.join(","),
+			pcesvn,
+			tcb_status
+		)
+	}
+
+	fn collateral_with_tcb_levels(tcb_levels: &str) -> SgxQlQveCollateral {
+		SgxQlQveCollateral {
+			version: COLLATERAL_VERSION,
+			tee_type: TEE_TYPE_SGX,
+			pck_crl_issuer_chain: Vec::new(),
+			root_ca_crl: Vec::new(),
+			pck_crl: Vec::new(),
+			tcb_info_issuer_chain: Vec::new(),
+			tcb_info: format!(
+				r#"{{"tcbInfo":{{"version":3,"tcbLevels":{}}},"signature":"00"}}"#,
+				tcb_levels
+			)
+			.into_bytes(),
+			qe_identity_issuer_chain: Vec::new(),
+			qe_identity: Vec::new(),
+		}
+	}
+}
@@ -19,7 +19,6 @@ use crate::{error::ServiceResult, teeracle::schedule_periodic::schedule_periodic
 use codec::{Decode, Encode};
 use itp_enclave_api::teeracle_api::TeeracleApi;
 use itp_node_api::api_client::ParentchainApi;
-use itp_types::parentchain::Hash;
 use itp_utils::hex::hex_encode;
 use log::*;
 use sp_runtime::OpaqueExtrinsic;
@@ -31,40 +30,6 @@ use tokio::runtime::Handle;
 pub(crate) mod schedule_periodic;
 pub(crate) mod teeracle_metrics;
 
-/// Schedule periodic reregistration of the enclave.
-///
-/// The `send_register_xt` needs to create a fresh registration extrinsic every time it is called
-/// (updated nonce, fresh IAS-RA or DCAP-Quote).
-///
-/// Currently, this is only used for the teeracle, but could also be used for other flavors in the
-/// future.
-pub(crate) fn schedule_periodic_reregistration_thread(
-	send_register_xt: impl Fn() -> ServiceResult<Hash> + std::marker::Send + 'static,
-	period: Duration,
-) {
-	println!("Schedule periodic enclave reregistration every: {:?}", period);
-
-	std::thread::Builder::new()
-		.name("enclave_reregistration_thread".to_owned())
-		.spawn(move || {
-			schedule_periodic(
-				|| {
-					trace!("Reregistering the enclave.");
-					if let Ok(block_hash) = send_register_xt() {
-						println!(
-							"✅ Successfully reregistered the enclave. Block hash: {}.",
-							block_hash
-						)
-					} else {
-						error!("❌ Could not reregister the enclave.")
-					}
-				},
-				period,
-			);
-		})
-		.unwrap();
-}
-
 /// Executes a periodic teeracle data update and sends the new data to the parentchain.
 ///
 /// Note: Puts the current thread to sleep for `period`.
@@ -0,0 +1,205 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Export of tracing spans to an OpenTelemetry collector.
+//!
+//! The enclave sends the spans it records in batches with the metrics ocall (see
+//! [`itp_enclave_metrics::trace`]). They are exported together with the spans of the service.
+//! The enclave only records spans if an OTLP endpoint is configured.
+//!
+//! Operations submitted with a client trace context are moved into the client's trace: the
+//! trace id derived from the operation hash is replaced by the one of the client.
+
+use crate::error::{Error, ServiceResult};
+use itp_enclave_metrics::trace::{self, TraceBatch, TraceContext, TraceSpan};
+use itp_types::H256;
+use lazy_static::lazy_static;
+use log::*;
+use opentelemetry::{
+	global,
+	trace::{Span, SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, Tracer},
+	Context, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace as sdk_trace, Resource};
+use parking_lot::RwLock;
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime::Handle;
+
+const TRACER_NAME: &str = "integritee-worker";
+
+/// Maximum number of client trace contexts kept, the oldest are dropped first.
+const MAX_TRACE_CONTEXTS: usize = 10_000;
+
+lazy_static! {
+	static ref TRACE_CONTEXTS: RwLock<TraceContexts> = Default::default();
+}
+
+/// Client trace contexts of operations, by the trace id derived from the operation hash.
+#[derive(Default)]
+struct TraceContexts {
+	contexts: HashMap<trace::TraceId, TraceContext>,
+	insertion_order: VecDeque<trace::TraceId>,
+}
+
+impl TraceContexts {
+	fn insert(&mut self, trace_id: trace::TraceId, context: TraceContext) {
+		if self.contexts.insert(trace_id, context).is_none() {
+			self.insertion_order.push_back(trace_id);
+		}
+		while self.insertion_order.len() > MAX_TRACE_CONTEXTS {
+			if let Some(oldest) = self.insertion_order.pop_front() {
+				self.contexts.remove(&oldest);
+			}
+		}
+	}
+
+	/// Moves `span` into the client trace, if its operation has been submitted with one.
+	fn apply(&self, span: &mut TraceSpan) {
+		if let Some(context) = self.contexts.get(&span.trace_id) {
+			span.trace_id = context.trace_id;
+			if span.parent_span_id.is_none() {
+				span.parent_span_id = Some(context.parent_span_id);
+			}
+		}
+	}
+}
+
+/// Installs an OTLP exporter, which sends the spans in batches to `endpoint` (gRPC).
+pub(crate) fn init_otlp_export(endpoint: &str, tokio_handle: &Handle) -> ServiceResult<()> {
+	// The batch span processor spawns its export task on the current tokio runtime.
+	let _runtime_guard = tokio_handle.enter();
+
+	opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+		.with_trace_config(
+			sdk_trace::config()
+				.with_resource(Resource::new(vec![KeyValue::new("service.name", TRACER_NAME)])),
+		)
+		.install_batch(runtime::Tokio)
+		.map_err(|e| Error::Custom(e.into()))?;
+
+	info!("Exporting tracing spans to OTLP endpoint {}", endpoint);
+	Ok(())
+}
+
+/// Exports a batch of spans recorded in the enclave.
+pub(crate) fn export_trace_batch(batch: TraceBatch) {
+	if !batch.contexts.is_empty() {
+		let mut trace_contexts = TRACE_CONTEXTS.write();
+		for (trace_id, context) in batch.contexts {
+			trace_contexts.insert(trace_id, context);
+		}
+	}
+	batch.spans.into_iter().for_each(export_span);
+}
+
+/// Exports a span that has already ended.
+pub(crate) fn export_span(mut span: TraceSpan) {
+	TRACE_CONTEXTS.read().apply(&mut span);
+
+	let trace_id = TraceId::from_bytes(span.trace_id);
+	let parent_context = match span.parent_span_id {
+		Some(parent_span_id) => Context::new().with_remote_span_context(SpanContext::new(
+			trace_id,
+			SpanId::from_bytes(parent_span_id),
+			TraceFlags::SAMPLED,
+			true,
+			TraceState::default(),
+		)),
+		None => Context::new(),
+	};
+
+	let tracer = global::tracer(TRACER_NAME);
+	let mut builder = tracer
+		.span_builder(span.name)
+		.with_trace_id(trace_id)
+		.with_start_time(UNIX_EPOCH + span.start)
+		.with_attributes(span.attributes.into_iter().map(|(key, value)| KeyValue::new(key, value)));
+	if let Some(span_id) = span.span_id {
+		builder = builder.with_span_id(SpanId::from_bytes(span_id));
+	}
+
+	builder
+		.start_with_context(&tracer, &parent_context)
+		.end_with_timestamp(UNIX_EPOCH + span.end);
+}
+
+/// Exports a span of the service, which belongs to the trace of the operation with `operation_hash`.
+pub(crate) fn export_operation_span(
+	operation_hash: &H256,
+	name: &str,
+	start: SystemTime,
+	end: SystemTime,
+	attributes: Vec<(&str, String)>,
+) {
+	let span = attributes.into_iter().fold(
+		TraceSpan::child(
+			operation_hash.as_fixed_bytes(),
+			name,
+			since_unix_epoch(start),
+			since_unix_epoch(end),
+		),
+		|span, (key, value)| span.with_attribute(key, value),
+	);
+	export_span(span)
+}
+
+fn since_unix_epoch(time: SystemTime) -> Duration {
+	time.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn span_of_operation_is_moved_into_client_trace() {
+		let operation_hash = [1u8; 32];
+		let context = TraceContext { trace_id: [2u8; 16], parent_span_id: [3u8; 8] };
+		let mut trace_contexts = TraceContexts::default();
+		trace_contexts.insert(trace::trace_id_of(&operation_hash), context);
+
+		let mut root = TraceSpan::root(&operation_hash, "root", Duration::ZERO, Duration::ZERO);
+		let mut child = TraceSpan::child(&operation_hash, "child", Duration::ZERO, Duration::ZERO);
+		trace_contexts.apply(&mut root);
+		trace_contexts.apply(&mut child);
+
+		assert_eq!(root.trace_id, context.trace_id);
+		assert_eq!(root.parent_span_id, Some(context.parent_span_id));
+		assert_eq!(child.trace_id, context.trace_id);
+		assert_eq!(child.parent_span_id, Some(trace::root_span_id_of(&operation_hash)));
+	}
+
+	#[test]
+	fn oldest_trace_contexts_are_dropped() {
+		let context = TraceContext { trace_id: [2u8; 16], parent_span_id: [3u8; 8] };
+		let mut trace_contexts = TraceContexts::default();
+		for i in 0..=MAX_TRACE_CONTEXTS as u32 {
+			let mut trace_id = trace::TraceId::default();
+			trace_id[..4].copy_from_slice(&i.to_le_bytes());
+			trace_contexts.insert(trace_id, context);
+		}
+
+		assert_eq!(trace_contexts.contexts.len(), MAX_TRACE_CONTEXTS);
+		assert!(!trace_contexts.contexts.contains_key(&trace::TraceId::default()));
+	}
+}
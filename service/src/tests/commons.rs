@@ -54,7 +54,11 @@ pub fn local_worker_config(
 		false,
 		"8787".to_string(),
 		"4545".to_string(),
+		false,
+		"4646".to_string(),
+		None,
 		crate::config::pwd(),
+		false,
 		None,
 	)
 }
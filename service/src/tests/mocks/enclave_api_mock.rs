@@ -22,13 +22,16 @@ use itc_parentchain::primitives::{
 	ParentchainId, ParentchainInitParams,
 	ParentchainInitParams::{Parachain, Solochain},
 };
-use itp_enclave_api::{enclave_base::EnclaveBase, sidechain::Sidechain, EnclaveResult};
+use itp_enclave_api::{
+	admin_api::AdminApi, direct_request::DirectRequest, enclave_base::EnclaveBase,
+	sidechain::Sidechain, EnclaveResult,
+};
 use itp_settings::worker::MR_ENCLAVE_SIZE;
 use itp_stf_interface::ShardCreationInfo;
 use itp_storage::StorageProof;
 use itp_types::{
 	parentchain::{Balance, Header},
-	ShardIdentifier,
+	ShardIdentifier, TrustedRpcServerLimits, H256,
 };
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
@@ -37,7 +40,13 @@ use sp_core::ed25519;
 pub struct EnclaveMock;
 
 impl EnclaveBase for EnclaveMock {
-	fn init(&self, _mu_ra_url: &str, _untrusted_url: &str, _base_dir: &str) -> EnclaveResult<()> {
+	fn init(
+		&self,
+		_mu_ra_url: &str,
+		_untrusted_url: &str,
+		_base_dir: &str,
+		_state_migration_dry_run: bool,
+	) -> EnclaveResult<()> {
 		Ok(())
 	}
 
@@ -45,7 +54,15 @@ impl EnclaveBase for EnclaveMock {
 		Ok(())
 	}
 
-	fn init_direct_invocation_server(&self, _rpc_server_addr: String) -> EnclaveResult<()> {
+	fn enable_tracing(&self) -> EnclaveResult<()> {
+		Ok(())
+	}
+
+	fn init_direct_invocation_server(
+		&self,
+		_rpc_server_addr: String,
+		_limits: TrustedRpcServerLimits,
+	) -> EnclaveResult<()> {
 		unreachable!()
 	}
 
@@ -65,6 +82,14 @@ impl EnclaveBase for EnclaveMock {
 		unimplemented!()
 	}
 
+	fn load_shard(&self, _shard: &ShardIdentifier) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
+	fn unload_shard(&self, _shard: &ShardIdentifier) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
 	fn init_proxied_shard_vault(
 		&self,
 		_shard: &ShardIdentifier,
@@ -124,7 +149,30 @@ impl Sidechain for EnclaveMock {
 		Ok(())
 	}
 
-	fn execute_trusted_calls(&self) -> EnclaveResult<()> {
+	fn execute_trusted_calls(&self, _: bool) -> EnclaveResult<()> {
 		todo!()
 	}
 }
+
+impl AdminApi for EnclaveMock {
+	fn get_pending_trusted_operations(
+		&self,
+		_shard: &ShardIdentifier,
+	) -> EnclaveResult<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn create_state_snapshot(&self, _shard: &ShardIdentifier) -> EnclaveResult<H256> {
+		Ok(H256::default())
+	}
+
+	fn rotate_shielding_key(&self) -> EnclaveResult<Rsa3072PubKey> {
+		unreachable!()
+	}
+}
+
+impl DirectRequest for EnclaveMock {
+	fn rpc(&self, _request: Vec<u8>) -> EnclaveResult<Vec<u8>> {
+		Ok(Vec::new())
+	}
+}
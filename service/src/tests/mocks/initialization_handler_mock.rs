@@ -15,7 +15,9 @@
 
 */
 
-use crate::initialized_service::{IsInitialized, TrackInitialization};
+use crate::initialized_service::{IsInitialized, TrackHealth, TrackInitialization};
+use itp_types::parentchain::{BlockNumber as ParentchainBlockNumber, ParentchainId};
+use its_primitives::types::BlockNumber as SidechainBlockNumber;
 
 pub struct TrackInitializationMock;
 
@@ -27,6 +29,22 @@ impl TrackInitialization for TrackInitializationMock {
 	fn worker_for_shard_registered(&self) {}
 }
 
+impl TrackHealth for TrackInitializationMock {
+	fn parentchain_head_finalized(&self, _: ParentchainId, _: ParentchainBlockNumber) {}
+
+	fn parentchain_blocks_imported(&self, _: ParentchainId, _: ParentchainBlockNumber) {}
+
+	fn parentchain_sync_terminated(&self, _: ParentchainId) {}
+
+	fn sidechain_block_proposed(&self, _: SidechainBlockNumber) {}
+
+	fn sidechain_block_imported(&self, _: SidechainBlockNumber) {}
+
+	fn peers_updated(&self, _: usize) {}
+
+	fn attestation_registered(&self, _: u64, _: Option<u64>) {}
+}
+
 pub struct IsInitializedMock;
 
 impl IsInitialized for IsInitializedMock {
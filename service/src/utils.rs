@@ -40,6 +40,17 @@ pub fn extract_shard<E: EnclaveBase>(
 	}
 }
 
+/// Extracts all given shards, or the mrenclave as the only shard if none are given.
+pub fn extract_shards<E: EnclaveBase>(
+	shard_strs: &[String],
+	enclave_api: &E,
+) -> Vec<ShardIdentifier> {
+	if shard_strs.is_empty() {
+		return vec![extract_shard(None, enclave_api)]
+	}
+	shard_strs.iter().map(|s| extract_shard(Some(s), enclave_api)).collect()
+}
+
 #[cfg(not(feature = "dcap"))]
 pub fn check_files() {
 	use itp_settings::files::{ENCLAVE_FILE, RA_API_KEY_FILE, RA_SPID_FILE};
@@ -20,19 +20,30 @@
 /// This should serve as a proof of concept for a potential refactoring design. Ultimately, everything
 /// from the main.rs should be covered by the worker struct here - hidden and split across
 /// multiple traits.
-use crate::{config::Config, error::Error, initialized_service::TrackInitialization};
+use crate::{
+	config::Config,
+	error::Error,
+	initialized_service::{TrackHealth, TrackInitialization},
+	telemetry::export_operation_span,
+};
 use async_trait::async_trait;
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
 use itp_node_api::{api_client::PalletTeerexApi, node_api_factory::CreateNodeApi};
 use itp_types::ShardIdentifier;
-use its_primitives::types::SignedBlock as SignedSidechainBlock;
+use its_primitives::{
+	traits::{Block, BlockData},
+	types::SignedBlock as SignedSidechainBlock,
+};
 use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
 use jsonrpsee::{
 	types::{to_json_value, traits::Client},
 	ws_client::WsClientBuilder,
 };
 use log::*;
-use std::sync::{Arc, RwLock};
+use std::{
+	sync::{Arc, RwLock},
+	time::SystemTime,
+};
 use teerex_primitives::MultiEnclave;
 use url::Url as UrlType;
 
@@ -79,7 +90,7 @@ impl<NodeApiFactory, Enclave, InitializationHandler> AsyncBlockBroadcaster
 where
 	NodeApiFactory: CreateNodeApi + Send + Sync,
 	Enclave: Send + Sync,
-	InitializationHandler: TrackInitialization + Send + Sync,
+	InitializationHandler: TrackInitialization + TrackHealth + Send + Sync,
 {
 	async fn broadcast_blocks(&self, blocks: Vec<SignedSidechainBlock>) -> WorkerResult<()> {
 		if blocks.is_empty() {
@@ -87,6 +98,11 @@ where
 			return Ok(())
 		}
 		let nr_blocks = blocks.len();
+		let last_block_number = blocks.iter().map(|b| b.block.header().block_number).max();
+		let operation_hashes: Vec<_> = blocks
+			.iter()
+			.flat_map(|b| b.block.block_data().signed_top_hashes().to_vec())
+			.collect();
 
 		let blocks_json = vec![to_json_value(blocks)?];
 		let peers = self
@@ -98,13 +114,18 @@ where
 			.map(|l| l.clone())?;
 
 		self.initialization_handler.sidechain_block_produced();
+		if let Some(block_number) = last_block_number {
+			self.initialization_handler.sidechain_block_proposed(block_number);
+		}
 
 		let nr_peers = peers.len();
 
 		for url in peers {
 			let blocks = blocks_json.clone();
+			let operation_hashes = operation_hashes.clone();
 
 			tokio::spawn(async move {
+				let broadcast_start = SystemTime::now();
 				debug!("Broadcasting block to peer with address: {:?}", url);
 				// FIXME: Websocket connection to a worker should stay, once established.
 				let client = match WsClientBuilder::default().build(&url).await {
@@ -115,14 +136,25 @@ where
 					},
 				};
 
-				if let Err(e) =
-					client.request::<Vec<u8>>(RPC_METHOD_NAME_IMPORT_BLOCKS, blocks.into()).await
-				{
+				let response =
+					client.request::<Vec<u8>>(RPC_METHOD_NAME_IMPORT_BLOCKS, blocks.into()).await;
+				if let Err(e) = &response {
 					error!(
 						"Broadcast block request ({}) to {} failed: {:?}",
 						RPC_METHOD_NAME_IMPORT_BLOCKS, url, e
 					);
 				}
+
+				let broadcast_end = SystemTime::now();
+				for operation_hash in operation_hashes.iter() {
+					export_operation_span(
+						operation_hash,
+						"sidechain_block_broadcast",
+						broadcast_start,
+						broadcast_end,
+						vec![("peer", url.clone()), ("success", response.is_ok().to_string())],
+					);
+				}
 			});
 		}
 		info!("broadcast {} block(s) to {} peers", nr_blocks, nr_peers);
@@ -146,6 +178,7 @@ impl<NodeApiFactory, Enclave, InitializationHandler> UpdatePeers
 	for Worker<Config, NodeApiFactory, Enclave, InitializationHandler>
 where
 	NodeApiFactory: CreateNodeApi + Send + Sync,
+	InitializationHandler: TrackHealth,
 {
 	fn search_peers(&self, shard: ShardIdentifier) -> WorkerResult<Vec<String>> {
 		let node_api = self
@@ -190,6 +223,7 @@ where
 		let mut peers_lock = self.peers.write().map_err(|e| {
 			Error::Custom(format!("Encountered poisoned lock for peers: {:?}", e).into())
 		})?;
+		self.initialization_handler.peers_updated(peers.len());
 		*peers_lock = peers;
 		Ok(())
 	}
@@ -0,0 +1,326 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Operational control of a running worker, exposed by the admin rpc.
+
+#[cfg(test)]
+use mockall::predicate::*;
+#[cfg(test)]
+use mockall::*;
+
+use crate::{
+	error::{Error, ServiceResult},
+	globals::tokio_handle::GetTokioHandle,
+	worker_peers_updater::UpdateWorkerPeers,
+};
+use itp_enclave_api::{admin_api::AdminApi, direct_request::DirectRequest};
+use itp_rpc::RpcRequest;
+use itp_settings::files::SHIELDING_KEY_FILE;
+use itp_types::{ShardIdentifier, H256};
+use itp_utils::ToHexPrefixed;
+use its_consensus_common::block_production_suspension::{
+	IsBlockProductionSuspended, SuspendBlockProduction,
+};
+use its_peer_fetch::FetchBlocksFromPeer;
+use its_primitives::types::SignedBlock as SignedSidechainBlock;
+use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use its_storage::interface::FetchBlocks;
+use log::*;
+use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+use std::{
+	fs::File,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+#[cfg_attr(test, automock)]
+pub trait AdministrateWorker {
+	/// Suspend sidechain block production until it is resumed.
+	fn pause_block_production(&self) -> ServiceResult<()>;
+
+	fn resume_block_production(&self) -> ServiceResult<()>;
+
+	fn is_block_production_paused(&self) -> ServiceResult<bool>;
+
+	/// Fetch the peers of a shard from the parentchain again and import the sidechain blocks
+	/// we are missing from them.
+	fn resync_peers(&self, shard: ShardIdentifier) -> ServiceResult<()>;
+
+	/// Encoded trusted operations that are pending in the TOP pool of a shard.
+	fn pending_trusted_operations(&self, shard: &ShardIdentifier) -> ServiceResult<Vec<Vec<u8>>>;
+
+	/// Create a new snapshot of the current state of a shard, returns the state hash.
+	fn create_state_snapshot(&self, shard: &ShardIdentifier) -> ServiceResult<H256>;
+
+	/// Replace the enclave shielding key with a new one, returns the new public key.
+	///
+	/// The previous key remains valid for decryption during a grace period.
+	fn rotate_shielding_key(&self) -> ServiceResult<Rsa3072PubKey>;
+}
+
+pub struct WorkerAdmin<
+	Enclave,
+	PeersUpdater,
+	BlockProductionSuspender,
+	SidechainStorage,
+	PeerBlockFetcher,
+	TokioHandle,
+> {
+	enclave: Arc<Enclave>,
+	peers_updater: Arc<PeersUpdater>,
+	block_production_suspender: Arc<BlockProductionSuspender>,
+	sidechain_storage: Arc<SidechainStorage>,
+	peer_block_fetcher: Arc<PeerBlockFetcher>,
+	tokio_handle: Arc<TokioHandle>,
+	data_dir: PathBuf,
+}
+
+impl<
+		Enclave,
+		PeersUpdater,
+		BlockProductionSuspender,
+		SidechainStorage,
+		PeerBlockFetcher,
+		TokioHandle,
+	>
+	WorkerAdmin<
+		Enclave,
+		PeersUpdater,
+		BlockProductionSuspender,
+		SidechainStorage,
+		PeerBlockFetcher,
+		TokioHandle,
+	>
+{
+	pub fn new(
+		enclave: Arc<Enclave>,
+		peers_updater: Arc<PeersUpdater>,
+		block_production_suspender: Arc<BlockProductionSuspender>,
+		sidechain_storage: Arc<SidechainStorage>,
+		peer_block_fetcher: Arc<PeerBlockFetcher>,
+		tokio_handle: Arc<TokioHandle>,
+		data_dir: PathBuf,
+	) -> Self {
+		WorkerAdmin {
+			enclave,
+			peers_updater,
+			block_production_suspender,
+			sidechain_storage,
+			peer_block_fetcher,
+			tokio_handle,
+			data_dir,
+		}
+	}
+}
+
+impl<
+		Enclave,
+		PeersUpdater,
+		BlockProductionSuspender,
+		SidechainStorage,
+		PeerBlockFetcher,
+		TokioHandle,
+	> AdministrateWorker
+	for WorkerAdmin<
+		Enclave,
+		PeersUpdater,
+		BlockProductionSuspender,
+		SidechainStorage,
+		PeerBlockFetcher,
+		TokioHandle,
+	> where
+	Enclave: AdminApi + DirectRequest,
+	PeersUpdater: UpdateWorkerPeers,
+	BlockProductionSuspender: SuspendBlockProduction + IsBlockProductionSuspended,
+	SidechainStorage: FetchBlocks<SignedSidechainBlock>,
+	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock>,
+	TokioHandle: GetTokioHandle,
+{
+	fn pause_block_production(&self) -> ServiceResult<()> {
+		self.block_production_suspender.suspend().map_err(suspension_error)
+	}
+
+	fn resume_block_production(&self) -> ServiceResult<()> {
+		self.block_production_suspender.resume().map_err(suspension_error)
+	}
+
+	fn is_block_production_paused(&self) -> ServiceResult<bool> {
+		self.block_production_suspender.is_suspended().map_err(suspension_error)
+	}
+
+	fn resync_peers(&self, shard: ShardIdentifier) -> ServiceResult<()> {
+		self.peers_updater.update_peers(shard)?;
+
+		let last_block_hash = match self
+			.sidechain_storage
+			.last_block_hash_of_shard(&shard)
+			.map_err(|e| Error::Custom(Box::new(e)))?
+		{
+			Some(hash) => hash,
+			None => {
+				info!("No sidechain blocks of shard {:?} in storage, nothing to resync", shard);
+				return Ok(())
+			},
+		};
+
+		let blocks = self
+			.tokio_handle
+			.get_handle()
+			.block_on(self.peer_block_fetcher.fetch_blocks_from_peer(last_block_hash, None, shard))
+			.map_err(|e| {
+				Error::Custom(format!("Fetching blocks from peer failed: {:?}", e).into())
+			})?;
+
+		if blocks.is_empty() {
+			info!("Sidechain blocks of shard {:?} are up to date", shard);
+			return Ok(())
+		}
+
+		info!("Importing {} sidechain blocks of shard {:?} fetched from peer", blocks.len(), shard);
+		let request = RpcRequest::compose_jsonrpc_call(
+			RPC_METHOD_NAME_IMPORT_BLOCKS.into(),
+			vec![blocks.to_hex()],
+		)?;
+		self.enclave.rpc(request.into_bytes())?;
+		Ok(())
+	}
+
+	fn pending_trusted_operations(&self, shard: &ShardIdentifier) -> ServiceResult<Vec<Vec<u8>>> {
+		Ok(self.enclave.get_pending_trusted_operations(shard)?)
+	}
+
+	fn create_state_snapshot(&self, shard: &ShardIdentifier) -> ServiceResult<H256> {
+		Ok(self.enclave.create_state_snapshot(shard)?)
+	}
+
+	fn rotate_shielding_key(&self) -> ServiceResult<Rsa3072PubKey> {
+		let pubkey = self.enclave.rotate_shielding_key()?;
+
+		// Keep the exported public key in sync with the enclave.
+		write_shielding_key_file(&self.data_dir, &pubkey)?;
+		info!("Rotated shielding key");
+		Ok(pubkey)
+	}
+}
+
+/// Writes the shielding public key to the [`SHIELDING_KEY_FILE`] in the data dir.
+pub(crate) fn write_shielding_key_file(
+	data_dir: &Path,
+	pubkey: &Rsa3072PubKey,
+) -> ServiceResult<()> {
+	let path = data_dir.join(SHIELDING_KEY_FILE);
+	let file = File::create(&path).map_err(|e| Error::Custom(e.into()))?;
+	serde_json::to_writer(file, pubkey)?;
+	info!("Shielding public key written to '{}'", path.display());
+	Ok(())
+}
+
+fn suspension_error(e: its_consensus_common::Error) -> Error {
+	Error::Custom(format!("Block production suspension failed: {:?}", e).into())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		globals::tokio_handle::ScopedTokioHandle, tests::mocks::enclave_api_mock::EnclaveMock,
+		worker_peers_updater::MockUpdateWorkerPeers,
+	};
+	use its_consensus_common::block_production_suspension::BlockProductionSuspender;
+	use its_peer_fetch::mocks::fetch_blocks_from_peer_mock::FetchBlocksFromPeerMock;
+	use its_storage::fetch_blocks_mock::FetchBlocksMock;
+	use its_test::sidechain_block_builder::{SidechainBlockBuilder, SidechainBlockBuilderTrait};
+	use std::collections::HashMap;
+
+	type TestWorkerAdmin = WorkerAdmin<
+		EnclaveMock,
+		MockUpdateWorkerPeers,
+		BlockProductionSuspender,
+		FetchBlocksMock,
+		FetchBlocksFromPeerMock<SignedSidechainBlock>,
+		ScopedTokioHandle,
+	>;
+
+	fn worker_admin(peers_updater: MockUpdateWorkerPeers) -> TestWorkerAdmin {
+		worker_admin_with_blocks(peers_updater, FetchBlocksMock::default(), HashMap::new())
+	}
+
+	fn worker_admin_with_blocks(
+		peers_updater: MockUpdateWorkerPeers,
+		sidechain_storage: FetchBlocksMock,
+		peer_blocks: HashMap<ShardIdentifier, Vec<SignedSidechainBlock>>,
+	) -> TestWorkerAdmin {
+		WorkerAdmin::new(
+			Arc::new(EnclaveMock),
+			Arc::new(peers_updater),
+			Arc::new(BlockProductionSuspender::default()),
+			Arc::new(sidechain_storage),
+			Arc::new(FetchBlocksFromPeerMock::default().with_signed_blocks(peer_blocks)),
+			Arc::new(ScopedTokioHandle::default()),
+			PathBuf::new(),
+		)
+	}
+
+	#[test]
+	fn pausing_and_resuming_block_production_works() {
+		let worker_admin = worker_admin(MockUpdateWorkerPeers::new());
+		assert!(!worker_admin.is_block_production_paused().unwrap());
+
+		worker_admin.pause_block_production().unwrap();
+		assert!(worker_admin.is_block_production_paused().unwrap());
+
+		worker_admin.resume_block_production().unwrap();
+		assert!(!worker_admin.is_block_production_paused().unwrap());
+	}
+
+	#[test]
+	fn resync_peers_updates_peers_of_shard() {
+		let shard = ShardIdentifier::from_slice(&[1u8; 32]);
+		let mut peers_updater = MockUpdateWorkerPeers::new();
+		peers_updater
+			.expect_update_peers()
+			.with(eq(shard))
+			.times(1)
+			.returning(|_| Ok(()));
+
+		worker_admin(peers_updater).resync_peers(shard).unwrap();
+	}
+
+	#[test]
+	fn resync_peers_imports_blocks_fetched_from_peer() {
+		let shard = ShardIdentifier::from_slice(&[1u8; 32]);
+		let mut peers_updater = MockUpdateWorkerPeers::new();
+		peers_updater.expect_update_peers().times(1).returning(|_| Ok(()));
+
+		let last_block = SidechainBlockBuilder::random().build_signed();
+		let peer_blocks = HashMap::from([(
+			shard,
+			vec![
+				SidechainBlockBuilder::random().build_signed(),
+				SidechainBlockBuilder::random().build_signed(),
+			],
+		)]);
+
+		worker_admin_with_blocks(
+			peers_updater,
+			FetchBlocksMock::default().with_blocks(vec![last_block]),
+			peer_blocks,
+		)
+		.resync_peers(shard)
+		.unwrap();
+	}
+}
@@ -32,7 +32,7 @@ use itp_stf_state_handler::handle_state::HandleState;
 use itp_top_pool_author::traits::{AuthorApi, OnBlockImported};
 use itp_types::H256;
 pub use its_consensus_common::BlockImport;
-use its_consensus_common::Error as ConsensusError;
+use its_consensus_common::{Error as ConsensusError, NotifyBlockImported};
 use its_primitives::traits::{
 	BlockData, Header as HeaderTrait, ShardIdentifierFor, SignedBlock as SignedBlockTrait,
 };
@@ -56,6 +56,7 @@ pub struct BlockImporter<
 	StateKeyRepository,
 	TopPoolAuthor,
 	ParentchainBlockImporter,
+	BlockImportNotifier,
 	TCS,
 	G,
 > {
@@ -64,6 +65,7 @@ pub struct BlockImporter<
 	top_pool_author: Arc<TopPoolAuthor>,
 	parentchain_block_importer: Arc<ParentchainBlockImporter>,
 	ocall_api: Arc<OCallApi>,
+	block_import_notifier: Arc<BlockImportNotifier>,
 	_phantom: PhantomData<(Authority, ParentchainBlock, SignedSidechainBlock, TCS, G)>,
 }
 
@@ -76,6 +78,7 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		BlockImportNotifier,
 		TCS,
 		G,
 	>
@@ -88,6 +91,7 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		BlockImportNotifier,
 		TCS,
 		G,
 	> where
@@ -110,6 +114,7 @@ impl<
 	ParentchainBlockImporter: TriggerParentchainBlockImport<SignedBlockType = SignedParentchainBlock<ParentchainBlock>>
 		+ Send
 		+ Sync,
+	BlockImportNotifier: NotifyBlockImported<SignedSidechainBlock::Block, SgxExternalities>,
 	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
 	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
 {
@@ -119,6 +124,7 @@ impl<
 		top_pool_author: Arc<TopPoolAuthor>,
 		parentchain_block_importer: Arc<ParentchainBlockImporter>,
 		ocall_api: Arc<OCallApi>,
+		block_import_notifier: Arc<BlockImportNotifier>,
 	) -> Self {
 		Self {
 			state_handler,
@@ -126,6 +132,7 @@ impl<
 			top_pool_author,
 			parentchain_block_importer,
 			ocall_api,
+			block_import_notifier,
 			_phantom: Default::default(),
 		}
 	}
@@ -167,6 +174,7 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		BlockImportNotifier,
 		TCS,
 		G,
 	> BlockImport<ParentchainBlock, SignedSidechainBlock>
@@ -179,6 +187,7 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		BlockImportNotifier,
 		TCS,
 		G,
 	> where
@@ -201,6 +210,7 @@ impl<
 	ParentchainBlockImporter: TriggerParentchainBlockImport<SignedBlockType = SignedParentchainBlock<ParentchainBlock>>
 		+ Send
 		+ Sync,
+	BlockImportNotifier: NotifyBlockImported<SignedSidechainBlock::Block, SgxExternalities>,
 	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
 	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
 {
@@ -235,11 +245,16 @@ impl<
 		// We load a copy of the state and apply the update. In case the update fails, we don't write
 		// the state back to the state handler, and thus guaranteeing state integrity.
 		let updated_state = mutating_function(state)?;
+		let state_size = updated_state.encoded_size() as u64;
 
 		self.state_handler
 			.write_after_mutation(updated_state, write_lock, shard)
 			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
 
+		if let Err(e) = self.ocall_api.update_metric(EnclaveMetric::StateSnapshotSize(state_size)) {
+			warn!("Failed to update state snapshot size metric: {:?}", e);
+		}
+
 		Ok(())
 	}
 
@@ -346,6 +361,15 @@ impl<
 			warn!("Failed to update sidechain block height metric: {:?}", e);
 		}
 
+		// Notify with the state of the imported block, before the events are reset by the next one.
+		if let Err(e) = self
+			.state_handler
+			.execute_on_current(&sidechain_block.header().shard_id(), |state, _| {
+				self.block_import_notifier.notify_block_imported(sidechain_block, state)
+			}) {
+			warn!("Failed to notify about imported sidechain block: {:?}", e);
+		}
+
 		Ok(())
 	}
 }
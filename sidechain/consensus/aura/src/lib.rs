@@ -32,15 +32,22 @@ extern crate sgx_tstd as std;
 use codec::Encode;
 use core::marker::PhantomData;
 use itc_parentchain_block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport;
-use itp_ocall_api::EnclaveOnChainOCallApi;
-use itp_time_utils::duration_now;
+use itp_enclave_metrics::{
+	trace::{TraceSpan, GLOBAL_TRACE_SPAN_BUFFER},
+	EnclaveMetric,
+};
+use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveOnChainOCallApi};
+use itp_time_utils::{duration_difference, trusted_duration_now};
 
 use itp_utils::hex::hex_encode;
 use its_block_verification::slot::slot_author;
 use its_consensus_common::{Environment, Error as ConsensusError, Proposer};
 use its_consensus_slots::{SimpleSlotWorker, Slot, SlotInfo};
 use its_primitives::{
-	traits::{Block as SidechainBlockTrait, Header as HeaderTrait, SignedBlock},
+	traits::{
+		Block as SidechainBlockTrait, BlockData as BlockDataTrait, Header as HeaderTrait,
+		SignedBlock,
+	},
 	types::block::BlockHash,
 };
 use its_validateer_fetch::ValidateerFetch;
@@ -175,7 +182,7 @@ impl<
 	E: Environment<ParentchainBlock, SignedSidechainBlock, Error = ConsensusError>,
 	E::Proposer: Proposer<ParentchainBlock, SignedSidechainBlock>,
 	SignedSidechainBlock: SignedBlock + Send + 'static,
-	OcallApi: ValidateerFetch + EnclaveOnChainOCallApi + Send + 'static,
+	OcallApi: ValidateerFetch + EnclaveOnChainOCallApi + EnclaveMetricsOCallApi + Send + 'static,
 	IntegriteeImportTrigger:
 		TriggerParentchainBlockImport<SignedBlockType = SignedParentchainBlock<ParentchainBlock>>,
 	TargetAImportTrigger:
@@ -220,6 +227,7 @@ impl<
 
 		if expected_author == &self.authority_pair.public() {
 			log::info!(target: self.logging_target(), "Claiming slot ({})", *slot);
+			update_metric(&self.ocall_api, EnclaveMetric::SidechainSlotClaimed);
 			return Some(self.authority_pair.public())
 		}
 
@@ -228,6 +236,7 @@ impl<
 				target: self.logging_target(),
 				"Not our slot but we still claim it."
 			);
+			update_metric(&self.ocall_api, EnclaveMetric::SidechainSlotClaimed);
 			return Some(self.authority_pair.public())
 		}
 
@@ -243,7 +252,8 @@ impl<
 	}
 
 	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<ParentchainBlock>) -> Duration {
-		proposing_remaining_duration(slot_info, duration_now())
+		trusted_duration_now()
+			.map_or(Duration::default(), |now| proposing_remaining_duration(slot_info, now))
 	}
 
 	// Design remark: the following may seem too explicit and it certainly could be abstracted.
@@ -335,6 +345,50 @@ impl<
 
 		Ok(maybe_parentchain_block.map(|b| b.block.header().clone()))
 	}
+
+	fn on_slot_missed(&self, _slot: Slot) {
+		update_metric(&self.ocall_api, EnclaveMetric::SidechainSlotMissed);
+	}
+
+	fn on_block_proposed(
+		&self,
+		block: &Self::Output,
+		proposal_start: Duration,
+		proposal_end: Duration,
+	) {
+		if let Some(proposal_duration) = duration_difference(proposal_start, proposal_end) {
+			update_metric(
+				&self.ocall_api,
+				EnclaveMetric::SidechainBlockProposalDuration(proposal_duration),
+			);
+		}
+
+		if !GLOBAL_TRACE_SPAN_BUFFER.is_enabled() {
+			return
+		}
+		let block_number = block.block().header().block_number();
+		for operation_hash in block.block().block_data().signed_top_hashes() {
+			GLOBAL_TRACE_SPAN_BUFFER.record(
+				TraceSpan::child(
+					operation_hash.as_fixed_bytes(),
+					"sidechain_block_proposal",
+					proposal_start,
+					proposal_end,
+				)
+				.with_attribute("block_number", block_number.to_string()),
+			);
+		}
+		// Everything recorded for the operations of this block is exported with a single ocall.
+		if let Some(batch) = GLOBAL_TRACE_SPAN_BUFFER.take() {
+			update_metric(&self.ocall_api, EnclaveMetric::TraceSpans(batch));
+		}
+	}
+}
+
+fn update_metric<OcallApi: EnclaveMetricsOCallApi>(ocall_api: &OcallApi, metric: EnclaveMetric) {
+	if let Err(e) = ocall_api.update_metric(metric) {
+		log::warn!("Failed to update enclave metric: {:?}", e);
+	}
 }
 
 /// unit-testable remaining duration fn.
@@ -391,6 +445,7 @@ mod tests {
 	use itc_parentchain_block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
 	use itc_parentchain_test::{ParentchainBlockBuilder, ParentchainHeaderBuilder};
 	use itp_test::mock::onchain_mock::OnchainMock;
+	use itp_time_utils::duration_now;
 	use itp_types::{
 		AccountId, Block as ParentchainBlock, Header as ParentchainHeader, ShardIdentifier,
 		SignedBlock as SignedParentchainBlock,
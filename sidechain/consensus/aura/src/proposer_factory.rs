@@ -24,7 +24,7 @@ use itp_stf_executor::traits::StateUpdateProposer;
 use itp_top_pool_author::traits::AuthorApi;
 use itp_types::H256;
 use its_block_composer::ComposeBlock;
-use its_consensus_common::{Environment, Error as ConsensusError};
+use its_consensus_common::{Environment, Error as ConsensusError, NotifyBlockImported};
 use its_primitives::traits::{
 	Block as SidechainBlockTrait, Header as HeaderTrait, ShardIdentifierFor,
 	SignedBlock as SignedSidechainBlockTrait,
@@ -38,25 +38,34 @@ use std::{marker::PhantomData, sync::Arc};
 
 ///! `ProposerFactory` instance containing all the data to create the `SlotProposer` for the
 /// next `Slot`.
-pub struct ProposerFactory<ParentchainBlock: Block, TopPoolAuthor, StfExecutor, BlockComposer> {
+pub struct ProposerFactory<
+	ParentchainBlock: Block,
+	TopPoolAuthor,
+	StfExecutor,
+	BlockComposer,
+	BlockImportNotifier,
+> {
 	top_pool_author: Arc<TopPoolAuthor>,
 	stf_executor: Arc<StfExecutor>,
 	block_composer: Arc<BlockComposer>,
+	block_import_notifier: Arc<BlockImportNotifier>,
 	_phantom: PhantomData<ParentchainBlock>,
 }
 
-impl<ParentchainBlock: Block, TopPoolAuthor, StfExecutor, BlockComposer>
-	ProposerFactory<ParentchainBlock, TopPoolAuthor, StfExecutor, BlockComposer>
+impl<ParentchainBlock: Block, TopPoolAuthor, StfExecutor, BlockComposer, BlockImportNotifier>
+	ProposerFactory<ParentchainBlock, TopPoolAuthor, StfExecutor, BlockComposer, BlockImportNotifier>
 {
 	pub fn new(
 		top_pool_executor: Arc<TopPoolAuthor>,
 		stf_executor: Arc<StfExecutor>,
 		block_composer: Arc<BlockComposer>,
+		block_import_notifier: Arc<BlockImportNotifier>,
 	) -> Self {
 		Self {
 			top_pool_author: top_pool_executor,
 			stf_executor,
 			block_composer,
+			block_import_notifier,
 			_phantom: Default::default(),
 		}
 	}
@@ -68,9 +77,15 @@ impl<
 		TopPoolAuthor,
 		StfExecutor,
 		BlockComposer,
+		BlockImportNotifier,
 	> Environment<ParentchainBlock, SignedSidechainBlock>
-	for ProposerFactory<ParentchainBlock, TopPoolAuthor, StfExecutor, BlockComposer>
-where
+	for ProposerFactory<
+		ParentchainBlock,
+		TopPoolAuthor,
+		StfExecutor,
+		BlockComposer,
+		BlockImportNotifier,
+	> where
 	NumberFor<ParentchainBlock>: BlockNumberOps,
 	SignedSidechainBlock: SignedSidechainBlockTrait<Public = sp_core::ed25519::Public, Signature = MultiSignature>
 		+ 'static,
@@ -90,6 +105,8 @@ where
 		> + Send
 		+ Sync
 		+ 'static,
+	BlockImportNotifier:
+		NotifyBlockImported<SignedSidechainBlock::Block, ExternalitiesFor<StfExecutor>> + 'static,
 {
 	type Proposer = SlotProposer<
 		ParentchainBlock,
@@ -97,6 +114,7 @@ where
 		TopPoolAuthor,
 		StfExecutor,
 		BlockComposer,
+		BlockImportNotifier,
 	>;
 	type Error = ConsensusError;
 
@@ -109,6 +127,7 @@ where
 			top_pool_author: self.top_pool_author.clone(),
 			stf_executor: self.stf_executor.clone(),
 			block_composer: self.block_composer.clone(),
+			block_import_notifier: self.block_import_notifier.clone(),
 			parentchain_header: parent_header,
 			shard,
 			_phantom: PhantomData,
@@ -20,11 +20,11 @@ use finality_grandpa::BlockNumberOps;
 use ita_stf::{Getter, TrustedCallSigned};
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_executor::traits::StateUpdateProposer;
-use itp_time_utils::now_as_millis;
+use itp_time_utils::trusted_now_as_millis;
 use itp_top_pool_author::traits::AuthorApi;
 use itp_types::H256;
 use its_block_composer::ComposeBlock;
-use its_consensus_common::{Error as ConsensusError, Proposal, Proposer};
+use its_consensus_common::{Error as ConsensusError, NotifyBlockImported, Proposal, Proposer};
 use its_primitives::traits::{
 	Block as SidechainBlockTrait, Header as HeaderTrait, ShardIdentifierFor,
 	SignedBlock as SignedSidechainBlockTrait,
@@ -45,19 +45,33 @@ pub struct SlotProposer<
 	TopPoolAuthor,
 	StfExecutor,
 	BlockComposer,
+	BlockImportNotifier,
 > {
 	pub(crate) top_pool_author: Arc<TopPoolAuthor>,
 	pub(crate) stf_executor: Arc<StfExecutor>,
 	pub(crate) block_composer: Arc<BlockComposer>,
+	pub(crate) block_import_notifier: Arc<BlockImportNotifier>,
 	pub(crate) parentchain_header: ParentchainBlock::Header,
 	pub(crate) shard: ShardIdentifierFor<SignedSidechainBlock>,
 	pub(crate) _phantom: PhantomData<ParentchainBlock>,
 }
 
-impl<ParentchainBlock, SignedSidechainBlock, TopPoolAuthor, BlockComposer, StfExecutor>
-	Proposer<ParentchainBlock, SignedSidechainBlock>
-	for SlotProposer<ParentchainBlock, SignedSidechainBlock, TopPoolAuthor, StfExecutor, BlockComposer>
-where
+impl<
+		ParentchainBlock,
+		SignedSidechainBlock,
+		TopPoolAuthor,
+		BlockComposer,
+		StfExecutor,
+		BlockImportNotifier,
+	> Proposer<ParentchainBlock, SignedSidechainBlock>
+	for SlotProposer<
+		ParentchainBlock,
+		SignedSidechainBlock,
+		TopPoolAuthor,
+		StfExecutor,
+		BlockComposer,
+		BlockImportNotifier,
+	> where
 	ParentchainBlock: Block<Hash = H256>,
 	NumberFor<ParentchainBlock>: BlockNumberOps,
 	SignedSidechainBlock: SignedSidechainBlockTrait<Public = sp_core::ed25519::Public, Signature = MultiSignature>
@@ -78,6 +92,8 @@ where
 		> + Send
 		+ Sync
 		+ 'static,
+	BlockImportNotifier:
+		NotifyBlockImported<SignedSidechainBlock::Block, ExternalitiesFor<StfExecutor>>,
 {
 	/// Proposes a new sidechain block.
 	///
@@ -85,11 +101,14 @@ where
 	/// 1) Retrieve all trusted calls from the top pool.
 	/// 2) Calculate a new state that will be proposed in the sidechain block.
 	/// 3) Compose the sidechain block and the parentchain confirmation.
+	/// 4) Notify subscribers about the new block, the same way an imported block is notified.
 	fn propose(
 		&self,
 		max_duration: Duration,
 	) -> Result<Proposal<SignedSidechainBlock>, ConsensusError> {
 		let latest_parentchain_header = &self.parentchain_header;
+		let timestamp =
+			trusted_now_as_millis().map_err(|e| ConsensusError::Other(e.to_string().into()))?;
 
 		// 1) Retrieve trusted calls from top pool.
 		let trusted_calls = self.top_pool_author.get_pending_trusted_calls(self.shard);
@@ -110,7 +129,7 @@ where
 					sidechain_db.reset_events();
 					sidechain_db
 						.set_block_number(&sidechain_db.get_block_number().map_or(1, |n| n + 1));
-					sidechain_db.set_timestamp(&now_as_millis());
+					sidechain_db.set_timestamp(&timestamp);
 					sidechain_db
 				},
 			)
@@ -157,6 +176,12 @@ where
 			max_duration.as_millis(),
 		);
 
+		// 4) Notify with the state the block was composed from, it has already been persisted.
+		self.block_import_notifier.notify_block_imported(
+			sidechain_block.block(),
+			&batch_execution_result.state_after_execution,
+		);
+
 		Ok(Proposal { block: sidechain_block, parentchain_effects: parentchain_extrinsics })
 	}
 }
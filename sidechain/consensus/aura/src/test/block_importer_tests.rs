@@ -15,7 +15,10 @@
 
 */
 
-use crate::{block_importer::BlockImporter, ShardIdentifierFor};
+use crate::{
+	block_importer::BlockImporter,
+	test::mocks::block_import_notifier_mock::BlockImportNotifierMock, ShardIdentifierFor,
+};
 use codec::Encode;
 use core::assert_matches::assert_matches;
 use itc_parentchain_block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
@@ -60,6 +63,7 @@ type TestBlockImporter = BlockImporter<
 	TestStateKeyRepo,
 	TestTopPoolAuthor,
 	TestParentchainBlockImportTrigger,
+	BlockImportNotifierMock,
 	TrustedCallSignedMock,
 	GetterMock,
 >;
@@ -95,6 +99,7 @@ fn test_fixtures(
 		top_pool_author.clone(),
 		parentchain_block_import_trigger,
 		ocall_api,
+		Arc::new(BlockImportNotifierMock),
 	);
 
 	(block_importer, state_handler, top_pool_author)
@@ -15,5 +15,6 @@
 
 */
 
+pub mod block_import_notifier_mock;
 pub mod environment_mock;
 pub mod proposer_mock;
@@ -19,21 +19,23 @@ use crate::{Result, SyncBlockFromPeer};
 use core::marker::PhantomData;
 use itertools::Itertools;
 use itp_import_queue::{PeekQueue, PopFromQueue};
-use itp_types::parentchain::SidechainBlockConfirmation;
+use itp_types::{parentchain::SidechainBlockConfirmation, ShardIdentifier};
 use its_primitives::traits::{
 	Block as BlockTrait, Header, SignedBlock as SignedSidechainBlockTrait,
 };
 use log::{debug, trace};
-use sp_runtime::traits::Block as ParentchainBlockTrait;
-use std::{sync::Arc, time::Instant, vec::Vec};
+use sp_runtime::traits::{Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait};
+use std::{collections::HashMap, sync::Arc, time::Instant, vec::Vec};
 
 /// Trait to trigger working the sidechain block import queue.
 pub trait ProcessBlockImportQueue<ParentchainBlockHeader> {
 	/// Pop sidechain blocks from the import queue and import them until queue is empty.
+	///
+	/// The blocks of each shard are imported against the latest confirmation of that shard.
 	fn process_queue(
 		&self,
 		current_parentchain_header: &ParentchainBlockHeader,
-		maybe_latest_sidechain_block_confirmation: Option<SidechainBlockConfirmation>,
+		latest_sidechain_block_confirmations: &HashMap<ShardIdentifier, SidechainBlockConfirmation>,
 	) -> Result<ParentchainBlockHeader>;
 }
 
@@ -90,13 +92,15 @@ impl<ParentchainBlock, SignedSidechainBlock, BlockImportQueue, PeerBlockSyncer>
 	ParentchainBlock: ParentchainBlockTrait,
 	SignedSidechainBlock: SignedSidechainBlockTrait,
 	SignedSidechainBlock::Block: BlockTrait,
+	<SignedSidechainBlock::Block as BlockTrait>::HeaderType:
+		Header<ShardIdentifier = ShardIdentifier>,
 	BlockImportQueue: PopFromQueue<ItemType = SignedSidechainBlock> + PeekQueue,
 	PeerBlockSyncer: SyncBlockFromPeer<ParentchainBlock::Header, SignedSidechainBlock>,
 {
 	fn process_queue(
 		&self,
 		current_parentchain_header: &ParentchainBlock::Header,
-		maybe_latest_sidechain_block_confirmation: Option<SidechainBlockConfirmation>,
+		latest_sidechain_block_confirmations: &HashMap<ShardIdentifier, SidechainBlockConfirmation>,
 	) -> Result<ParentchainBlock::Header> {
 		let mut latest_imported_parentchain_header = current_parentchain_header.clone();
 		let start_time = Instant::now();
@@ -110,17 +114,24 @@ impl<ParentchainBlock, SignedSidechainBlock, BlockImportQueue, PeerBlockSyncer>
 			.block_import_queue
 			.pop_all()?
 			.iter()
-			.map(|b| (b.block().header().block_number(), b))
+			.map(|b| ((b.block().header().shard_id(), b.block().header().block_number()), b))
+			// Blocks only compete with blocks of the same shard.
 			.sorted_by_key(|a| a.0)
 			.group_by(|&a| a.0)
 			.into_iter()
-			.filter_map(|(block_number, competitors)| {
+			.filter_map(|((shard, block_number), competitors)| {
 				let mut competitors: Vec<&SignedSidechainBlock> =
 					competitors.map(|c| c.1).collect();
 				// deterministic import order decreases chances for forks
 				competitors.sort_by_key(|a| a.block().hash());
-				trace!("nr of competitors for block {}: {}", block_number, competitors.len());
-
+				trace!(
+					"nr of competitors for block {} of shard {:?}: {}",
+					block_number,
+					shard,
+					competitors.len()
+				);
+				let maybe_latest_sidechain_block_confirmation =
+					latest_sidechain_block_confirmations.get(&shard).cloned();
 				// returns the first block satisfying the predicate
 				competitors.into_iter().find_map(|block| {
 					self.peer_block_syncer
@@ -131,7 +142,12 @@ impl<ParentchainBlock, SignedSidechainBlock, BlockImportQueue, PeerBlockSyncer>
 						)
 						.ok()
 						.map(|parentchain_header| {
-							latest_imported_parentchain_header = parentchain_header;
+							// The blocks of another shard may have been imported on a later header.
+							if parentchain_header.number()
+								> latest_imported_parentchain_header.number()
+							{
+								latest_imported_parentchain_header = parentchain_header;
+							}
 							block
 						})
 				})
@@ -31,6 +31,9 @@ pub trait SuspendBlockProduction {
 	/// Suspend any sidechain block production.
 	fn suspend_for_sync(&self) -> Result<()>;
 
+	/// Suspend any sidechain block production, without a sync being ongoing.
+	fn suspend(&self) -> Result<()>;
+
 	/// Resume block sidechain block production.
 	fn resume(&self) -> Result<()>;
 }
@@ -71,6 +74,13 @@ impl SuspendBlockProduction for BlockProductionSuspender {
 		Ok(())
 	}
 
+	fn suspend(&self) -> Result<()> {
+		let mut suspended_lock = self.is_suspended.write().map_err(|_| Error::LockPoisoning)?;
+		*suspended_lock = true;
+		info!("Suspend sidechain block production");
+		Ok(())
+	}
+
 	fn resume(&self) -> Result<()> {
 		let mut suspended_lock = self.is_suspended.write().map_err(|_| Error::LockPoisoning)?;
 		*suspended_lock = false;
@@ -109,4 +119,16 @@ mod tests {
 		block_production_suspender.resume().unwrap();
 		assert!(!block_production_suspender.is_suspended().unwrap());
 	}
+
+	#[test]
+	fn suspending_without_sync_works() {
+		let block_production_suspender = BlockProductionSuspender::default();
+
+		block_production_suspender.suspend().unwrap();
+		assert!(block_production_suspender.is_suspended().unwrap());
+		assert!(!block_production_suspender.is_sync_ongoing().unwrap());
+
+		block_production_suspender.resume().unwrap();
+		assert!(!block_production_suspender.is_suspended().unwrap());
+	}
 }
@@ -46,6 +46,8 @@ mod is_descendant_of_builder;
 #[cfg(test)]
 mod test;
 
+pub mod block_production_suspension;
+
 pub use block_import::*;
 pub use block_import_confirmation_handler::*;
 pub use block_import_queue_worker::*;
@@ -33,7 +33,7 @@ extern crate sgx_tstd as std;
 
 use codec::Encode;
 use derive_more::From;
-use itp_time_utils::{duration_difference, duration_now};
+use itp_time_utils::{duration_difference, duration_now, trusted_duration_now};
 
 use its_consensus_common::{Error as ConsensusError, Proposer};
 use its_primitives::traits::{
@@ -197,6 +197,19 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 		&self,
 	) -> Result<Option<ParentchainBlock::Header>, ConsensusError>;
 
+	/// Called when a claimed slot did not result in a proposed block. Does nothing by default.
+	fn on_slot_missed(&self, _slot: Slot) {}
+
+	/// Called with a block proposed in a claimed slot and the time span (since unix epoch) it
+	/// took to propose it. Does nothing by default.
+	fn on_block_proposed(
+		&self,
+		_block: &Self::Output,
+		_proposal_start: Duration,
+		_proposal_end: Duration,
+	) {
+	}
+
 	/// Implements [`SlotWorker::on_slot`]. This is an adaption from
 	/// substrate's sc-consensus-slots implementation. There, the slot worker handles all the
 	/// scheduling itself. Unfortunately, we can't use the same principle in the enclave due to some
@@ -349,17 +362,21 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 			Ok(p) => p,
 			Err(e) => {
 				warn!("Could not create proposer: {:?}", e);
+				self.on_slot_missed(slot);
 				return None
 			},
 		};
 
+		let proposal_start = duration_now();
 		let proposing = match proposer.propose(remaining_duration) {
 			Ok(p) => p,
 			Err(e) => {
 				warn!("Could not propose: {:?}", e);
+				self.on_slot_missed(slot);
 				return None
 			},
 		};
+		self.on_block_proposed(&proposing.block, proposal_start, duration_now());
 
 		if !timestamp_within_slot(&slot_info, &proposing.block) {
 			warn!(
@@ -367,6 +384,7 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 				"⌛️ Discarding proposal for slot {}, block number {}; block production took too long",
 				*slot, proposing.block.block().header().block_number(),
 			);
+			self.on_slot_missed(slot);
 
 			return None
 		}
@@ -423,7 +441,14 @@ impl<ParentchainBlock: ParentchainBlockTrait, T: SimpleSlotWorker<ParentchainBlo
 		let mut slot_results = Vec::with_capacity(remaining_shards);
 
 		for shard in shards.into_iter() {
-			let now = duration_now(); // It's important we have a common `now` for all following computations.
+			// It's important we have a common `now` for all following computations.
+			let now = match trusted_duration_now() {
+				Ok(now) => now,
+				Err(e) => {
+					warn!("⌛️ Could not produce blocks for all shards: {}", e);
+					return slot_results
+				},
+			};
 			let shard_remaining_duration = duration_difference(now, slot_info.ends_at)
 				.and_then(|time| time.checked_div(remaining_shards as u32))
 				.unwrap_or_default();
@@ -19,7 +19,7 @@
 //!
 //! This is used instead of `futures_timer::Interval` because it was unreliable.
 
-use itp_time_utils::duration_now;
+use itp_time_utils::{duration_now, trusted_duration_now};
 use its_block_verification::slot::slot_from_timestamp_and_duration;
 use its_consensus_common::Error as ConsensusError;
 use its_primitives::traits::{
@@ -96,7 +96,7 @@ impl<ParentchainBlock: ParentchainBlockTrait> SlotInfo<ParentchainBlock> {
 	}
 
 	pub fn duration_remaining(&self) -> Option<Duration> {
-		let duration_now = duration_now();
+		let duration_now = trusted_duration_now().ok()?;
 		if self.ends_at <= duration_now {
 			return None
 		}
@@ -159,6 +159,8 @@ pub trait SidechainSystemExt {
 	fn set_timestamp(&mut self, timestamp: &Timestamp);
 
 	/// Resets the events.
+	///
+	/// The events of the previous block have been sent to the RPC subscribers upon its import.
 	fn reset_events(&mut self);
 }
 
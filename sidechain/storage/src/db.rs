@@ -59,6 +59,11 @@ impl SidechainDB {
 		batch.delete(key.encode())
 	}
 
+	/// compacts the whole DB, reclaiming the disk space of deleted entries
+	pub fn compact(&self) {
+		self.db.compact_range::<&[u8], &[u8]>(None, None)
+	}
+
 	/// add an entry to the DB
 	#[cfg(test)]
 	pub fn put<K: Encode, V: Encode>(&mut self, key: K, value: V) -> Result<()> {
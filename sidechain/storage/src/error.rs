@@ -15,6 +15,7 @@
 
 */
 
+use its_primitives::types::BlockHash;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -25,10 +26,22 @@ pub enum Error {
 	Operational(#[from] rocksdb::Error),
 	#[error("Last Block of shard {0} not found")]
 	LastBlockNotFound(String),
+	#[error("Block {0:?} not found")]
+	BlockNotFound(BlockHash),
 	#[error("Failed to find parent block")]
 	FailedToFindParentBlock,
 	#[error("Could not decode: {0:?}")]
 	Decode(#[from] codec::Error),
 	#[error("Given block is not a successor of the last known block")]
 	HeaderAncestryMismatch,
+	#[error("Could not access block export file: {0:?}")]
+	Io(#[from] std::io::Error),
+	#[error("Unsupported block export version {0}, expected {1}")]
+	UnsupportedExportVersion(u32, u32),
+	#[error("Imported block {0:?} belongs to another shard")]
+	ImportedBlockOfOtherShard(BlockHash),
+	#[error("Imported block {0:?} has an invalid signature")]
+	ImportedBlockSignatureInvalid(BlockHash),
+	#[error("Imported block {0:?} is not authored by an authority of the shard")]
+	ImportedBlockAuthorUnknown(BlockHash),
 }
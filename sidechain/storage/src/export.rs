@@ -0,0 +1,90 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Portable export of sidechain blocks to a file, e.g. to archive them before they are pruned,
+//! or to seed the storage of another worker.
+//!
+//! The file contains the SCALE encoded [`BlockExport`].
+
+use super::{Error, Result};
+use codec::{Decode, Encode};
+use std::{fs, path::Path};
+
+/// Version of the export format, increased on incompatible changes.
+pub const BLOCK_EXPORT_VERSION: u32 = 1;
+
+/// Sidechain blocks exported to a file, oldest first.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BlockExport<SignedBlock> {
+	pub version: u32,
+	pub blocks: Vec<SignedBlock>,
+}
+
+impl<SignedBlock: Encode + Decode> BlockExport<SignedBlock> {
+	pub fn new(blocks: Vec<SignedBlock>) -> Self {
+		BlockExport { version: BLOCK_EXPORT_VERSION, blocks }
+	}
+
+	pub fn write_to_file(&self, path: &Path) -> Result<()> {
+		fs::write(path, self.encode()).map_err(Error::Io)
+	}
+
+	pub fn read_from_file(path: &Path) -> Result<Self> {
+		let encoded = fs::read(path)?;
+		// Check the version first, the blocks of other versions might not decode.
+		let version = u32::decode(&mut encoded.as_slice())?;
+		if version != BLOCK_EXPORT_VERSION {
+			return Err(Error::UnsupportedExportVersion(version, BLOCK_EXPORT_VERSION))
+		}
+		Ok(Self::decode(&mut encoded.as_slice())?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::{create_signed_block_with_shard, create_temp_dir, default_shard};
+	use its_primitives::types::SignedBlock;
+
+	#[test]
+	fn written_export_can_be_read_again() {
+		let temp_dir = create_temp_dir();
+		let path = temp_dir.path().join("blocks.scale");
+		let export = BlockExport::new(vec![
+			create_signed_block_with_shard(1, default_shard()),
+			create_signed_block_with_shard(2, default_shard()),
+		]);
+
+		export.write_to_file(&path).unwrap();
+
+		assert_eq!(BlockExport::<SignedBlock>::read_from_file(&path).unwrap(), export);
+	}
+
+	#[test]
+	fn reading_export_of_other_version_fails() {
+		let temp_dir = create_temp_dir();
+		let path = temp_dir.path().join("blocks.scale");
+		BlockExport::<SignedBlock> { version: 2, blocks: vec![] }
+			.write_to_file(&path)
+			.unwrap();
+
+		assert!(matches!(
+			BlockExport::<SignedBlock>::read_from_file(&path),
+			Err(Error::UnsupportedExportVersion(2, BLOCK_EXPORT_VERSION))
+		));
+	}
+}
@@ -17,7 +17,7 @@
 
 use crate::{error::Result, interface::FetchBlocks};
 use its_primitives::{
-	traits::ShardIdentifierFor,
+	traits::{ShardIdentifierFor, SignedBlock as SignedBlockT},
 	types::{BlockHash, SignedBlock},
 };
 
@@ -50,4 +50,11 @@ impl FetchBlocks<SignedBlock> for FetchBlocksMock {
 	) -> Result<Vec<SignedBlock>> {
 		Ok(self.blocks_to_be_fetched.clone())
 	}
+
+	fn last_block_hash_of_shard(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<BlockHash>> {
+		Ok(self.blocks_to_be_fetched.last().map(|b| b.hash()))
+	}
 }
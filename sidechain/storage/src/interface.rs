@@ -20,13 +20,18 @@ use mockall::predicate::*;
 #[cfg(test)]
 use mockall::*;
 
-use super::{storage::SidechainStorage, Result};
+use super::{
+	export::BlockExport,
+	pruning::{FetchFinalizedBlockNumber, PruningPolicy},
+	storage::SidechainStorage,
+	Result,
+};
 use its_primitives::{
 	traits::{ShardIdentifierFor, SignedBlock as SignedBlockT},
-	types::{BlockHash, BlockNumber},
+	types::{BlockHash, BlockNumber, ShardIdentifier},
 };
 use parking_lot::RwLock;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Lock wrapper around sidechain storage
 pub struct SidechainStorageLock<SignedBlock: SignedBlockT> {
@@ -39,6 +44,36 @@ impl<SignedBlock: SignedBlockT> SidechainStorageLock<SignedBlock> {
 			storage: RwLock::new(SidechainStorage::<SignedBlock>::load_from_base_path(path)?),
 		})
 	}
+
+	/// Exports the blocks of a shard with a block number within `from..=until` to a file.
+	///
+	/// Returns the number of exported blocks.
+	pub fn export_blocks(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		until: BlockNumber,
+		path: &Path,
+	) -> Result<usize> {
+		let blocks = self.storage.read().get_blocks_by_number(shard, from, until)?;
+		let number_of_blocks = blocks.len();
+		BlockExport::new(blocks).write_to_file(path)?;
+		Ok(number_of_blocks)
+	}
+
+	/// Imports the blocks of a shard from a file written by [`Self::export_blocks`]. Every block
+	/// must be signed by one of the given authorities of the shard.
+	///
+	/// Returns the number of imported blocks, see [`SidechainStorage::import_blocks`].
+	pub fn import_blocks(
+		&self,
+		path: &Path,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		authorities: &[SignedBlock::Public],
+	) -> Result<usize> {
+		let export = BlockExport::<SignedBlock>::read_from_file(path)?;
+		self.storage.write().import_blocks(export.blocks, shard, authorities)
+	}
 }
 
 /// Storage interface Trait
@@ -53,6 +88,17 @@ pub trait BlockStorage<SignedBlock: SignedBlockT> {
 pub trait BlockPruner {
 	/// Prune all blocks except the newest n, where n = `number_of_blocks_to_keep`.
 	fn prune_blocks_except(&self, number_of_blocks_to_keep: u64);
+
+	/// Prune blocks according to the given policy, but never the last finalized block of a shard
+	/// or any block after it.
+	fn prune_blocks<F: FetchFinalizedBlockNumber>(
+		&self,
+		policy: &PruningPolicy,
+		finalized_blocks: &F,
+	);
+
+	/// Compact the storage, to reclaim the disk space of pruned blocks.
+	fn compact(&self);
 }
 
 #[cfg_attr(test, automock)]
@@ -77,6 +123,14 @@ pub trait FetchBlocks<SignedBlock: SignedBlockT> {
 		block_hash_until: &BlockHash,
 		shard_identifier: &ShardIdentifierFor<SignedBlock>,
 	) -> Result<Vec<SignedBlock>>;
+
+	/// Fetch the hash of the last block of a shard.
+	///
+	/// Returns `None` if no block of the shard is in storage.
+	fn last_block_hash_of_shard(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<BlockHash>>;
 }
 
 impl<SignedBlock: SignedBlockT> BlockStorage<SignedBlock> for SidechainStorageLock<SignedBlock> {
@@ -85,10 +139,28 @@ impl<SignedBlock: SignedBlockT> BlockStorage<SignedBlock> for SidechainStorageLo
 	}
 }
 
-impl<SignedBlock: SignedBlockT> BlockPruner for SidechainStorageLock<SignedBlock> {
+impl<SignedBlock> BlockPruner for SidechainStorageLock<SignedBlock>
+where
+	SignedBlock: SignedBlockT,
+	ShardIdentifierFor<SignedBlock>: Into<ShardIdentifier>,
+{
 	fn prune_blocks_except(&self, number_of_blocks_to_keep: BlockNumber) {
 		self.storage.write().prune_shards(number_of_blocks_to_keep);
 	}
+
+	fn prune_blocks<F: FetchFinalizedBlockNumber>(
+		&self,
+		policy: &PruningPolicy,
+		finalized_blocks: &F,
+	) {
+		self.storage
+			.write()
+			.prune(policy, |shard| finalized_blocks.finalized_block_number(&(*shard).into()));
+	}
+
+	fn compact(&self) {
+		self.storage.read().compact();
+	}
 }
 
 impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLock<SignedBlock> {
@@ -110,4 +182,11 @@ impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLoc
 			.read()
 			.get_blocks_in_range(block_hash_from, block_hash_until, shard_identifier)
 	}
+
+	fn last_block_hash_of_shard(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<BlockHash>> {
+		Ok(self.storage.read().last_block_of_shard(shard_identifier).map(|b| b.hash))
+	}
 }
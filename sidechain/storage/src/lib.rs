@@ -17,7 +17,6 @@
 
 #![cfg_attr(test, feature(assert_matches))]
 
-use its_primitives::types::BlockNumber;
 use std::{
 	sync::Arc,
 	thread,
@@ -26,7 +25,9 @@ use std::{
 
 mod db;
 mod error;
+pub mod export;
 pub mod interface;
+mod pruning;
 mod storage;
 
 #[cfg(test)]
@@ -43,14 +44,23 @@ pub mod fetch_blocks_mock;
 
 pub use error::{Error, Result};
 pub use interface::{BlockPruner, BlockStorage, SidechainStorageLock};
+pub use pruning::{confirmed_block_number, FetchFinalizedBlockNumber, PruningPolicy};
 
-pub fn start_sidechain_pruning_loop<D>(
+/// Prunes the storage according to the policy in the given interval (in seconds), and compacts it
+/// afterwards. Blocks from the last finalized one on are never pruned. Returns right away in
+/// archive mode.
+pub fn start_sidechain_pruning_loop<D, F>(
 	storage: &Arc<D>,
+	finalized_blocks: &F,
 	purge_interval: u64,
-	purge_limit: BlockNumber,
+	policy: PruningPolicy,
 ) where
 	D: BlockPruner,
+	F: FetchFinalizedBlockNumber,
 {
+	if policy.is_archive() {
+		return
+	}
 	let interval_time = Duration::from_secs(purge_interval);
 	let mut interval_start = SystemTime::now();
 	loop {
@@ -58,7 +68,8 @@ pub fn start_sidechain_pruning_loop<D>(
 			if elapsed >= interval_time {
 				// update interval time
 				interval_start = SystemTime::now();
-				storage.prune_blocks_except(purge_limit);
+				storage.prune_blocks(&policy, finalized_blocks);
+				storage.compact();
 			} else {
 				// sleep for the rest of the interval
 				let sleep_time = interval_time - elapsed;
@@ -0,0 +1,58 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Policies, which decide which sidechain blocks are kept when pruning the storage.
+
+use itp_settings::worker::BLOCK_NUMBER_FINALIZATION_DIFF;
+use itp_types::parentchain::SidechainBlockConfirmation;
+use its_primitives::types::{BlockNumber, ShardIdentifier};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Decides which sidechain blocks of a shard are pruned from the storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PruningPolicy {
+	/// Keep the newest n blocks of every shard.
+	KeepLastBlocks(BlockNumber),
+	/// Keep the blocks with a timestamp newer than the given age. The last block of a shard is
+	/// kept regardless of its age, as new blocks are verified against it.
+	KeepBlocksNewerThan(Duration),
+	/// Keep all blocks, i.e. never prune.
+	Archive,
+}
+
+impl PruningPolicy {
+	pub fn is_archive(&self) -> bool {
+		matches!(self, PruningPolicy::Archive)
+	}
+}
+
+/// Provides the last finalized sidechain block of a shard, i.e. the last one confirmed on the
+/// parentchain. Blocks from the finalized block on are never pruned.
+pub trait FetchFinalizedBlockNumber {
+	/// Returns `None` if the finalized block of the shard is unknown, in which case none of its
+	/// blocks are pruned.
+	fn finalized_block_number(&self, shard: &ShardIdentifier) -> Option<BlockNumber>;
+}
+
+/// Number of the sidechain block, which has been confirmed on the parentchain.
+///
+/// A confirmation is sent for the block that reached its finalization target, and announces the
+/// next target in its block number, see `BlockImportConfirmationHandler`.
+pub fn confirmed_block_number(confirmation: &SidechainBlockConfirmation) -> BlockNumber {
+	confirmation.block_number.saturating_sub(BLOCK_NUMBER_FINALIZATION_DIFF)
+}
@@ -15,22 +15,32 @@
 
 */
 
-use super::{db::SidechainDB, Error, Result};
+use super::{db::SidechainDB, pruning::PruningPolicy, Error, Result};
 use codec::{Decode, Encode};
 use itp_settings::files::SIDECHAIN_STORAGE_PATH;
 use its_primitives::{
-	traits::{Block as BlockTrait, Header as HeaderTrait, SignedBlock as SignedBlockT},
+	traits::{
+		Block as BlockTrait, BlockData as BlockDataTrait, Header as HeaderTrait,
+		SignedBlock as SignedBlockT,
+	},
 	types::{BlockHash, BlockNumber},
 };
 use log::*;
 use rocksdb::WriteBatch;
 use sp_core::H256;
-use std::{collections::HashMap, fmt::Debug, path::PathBuf};
+use std::{
+	collections::HashMap,
+	fmt::Debug,
+	path::PathBuf,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 /// key value of sidechain db of last block
 const LAST_BLOCK_KEY: &[u8] = b"last_sidechainblock";
 /// key value of the stored shards vector
 const STORED_SHARDS_KEY: &[u8] = b"stored_shards";
+/// key value of the number of the newest pruned block of a shard
+const PRUNED_UNTIL_KEY: &[u8] = b"pruned_until";
 
 /// ShardIdentifier type
 type ShardIdentifierFor<B> =
@@ -55,6 +65,8 @@ pub struct SidechainStorage<SignedBlock: SignedBlockT> {
 	shards: Vec<ShardIdentifierFor<SignedBlock>>,
 	/// map to last sidechain block of every shard
 	last_blocks: HashMap<ShardIdentifierFor<SignedBlock>, LastSidechainBlock>,
+	/// map to the number of the newest pruned block of every shard, the pruning cursor
+	pruned_until: HashMap<ShardIdentifierFor<SignedBlock>, BlockNumber>,
 }
 
 impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
@@ -64,10 +76,18 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 	pub fn load_from_base_path(base_path: PathBuf) -> Result<SidechainStorage<SignedBlock>> {
 		// load db
 		let db = SidechainDB::open_default(base_path.join(SIDECHAIN_STORAGE_PATH))?;
-		let mut storage = SidechainStorage { db, shards: vec![], last_blocks: HashMap::new() };
+		let mut storage = SidechainStorage {
+			db,
+			shards: vec![],
+			last_blocks: HashMap::new(),
+			pruned_until: HashMap::new(),
+		};
 		storage.shards = storage.load_shards_from_db()?;
-		// get last block of each shard
+		// get last block and pruning cursor of each shard
 		for shard in storage.shards.iter() {
+			if let Some(pruned_until) = storage.db.get((PRUNED_UNTIL_KEY, *shard))? {
+				storage.pruned_until.insert(*shard, pruned_until);
+			}
 			if let Some(last_block) = storage.load_last_block_from_db(shard)? {
 				storage.last_blocks.insert(*shard, last_block);
 			} else {
@@ -164,6 +184,73 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 			.collect())
 	}
 
+	/// Get the blocks of a shard with a block number within `from..=until`, oldest first.
+	///
+	/// Blocks, which are not in storage (e.g. because they have been pruned), are skipped.
+	pub fn get_blocks_by_number(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		until: BlockNumber,
+	) -> Result<Vec<SignedBlock>> {
+		let mut blocks = Vec::new();
+		let last_block_number = match self.last_block_of_shard(shard) {
+			Some(last_block) => last_block.number,
+			None => return Ok(blocks),
+		};
+		for block_number in from..=until.min(last_block_number) {
+			if let Some(block_hash) = self.get_block_hash(shard, block_number)? {
+				blocks.push(self.get_block(&block_hash)?.ok_or(Error::BlockNotFound(block_hash))?);
+			}
+		}
+		Ok(blocks)
+	}
+
+	/// Imports the blocks of a shard, e.g. of a block export, and returns the number of imported
+	/// blocks.
+	///
+	/// Nothing is imported, unless every block belongs to the given shard, has a valid signature
+	/// and is authored by one of the given authorities. Blocks, which are not newer than the last
+	/// block of the shard, are skipped, because they are either in storage already or have been
+	/// pruned. The remaining blocks must continue the chain of the shard, as with
+	/// [`Self::store_blocks`].
+	pub fn import_blocks(
+		&mut self,
+		blocks: Vec<SignedBlock>,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		authorities: &[SignedBlock::Public],
+	) -> Result<usize> {
+		for block in blocks.iter() {
+			if block.block().header().shard_id() != *shard {
+				return Err(Error::ImportedBlockOfOtherShard(block.hash()))
+			}
+			if !block.verify_signature() {
+				return Err(Error::ImportedBlockSignatureInvalid(block.hash()))
+			}
+			if !authorities.contains(block.block().block_data().block_author()) {
+				return Err(Error::ImportedBlockAuthorUnknown(block.hash()))
+			}
+		}
+		let last_block_number = self.last_block_of_shard(shard).map(|last_block| last_block.number);
+		let new_blocks: Vec<SignedBlock> = blocks
+			.into_iter()
+			.filter(|block| {
+				last_block_number
+					.map_or(true, |number| block.block().header().block_number() > number)
+			})
+			.collect();
+		let new_block_hashes: Vec<BlockHash> = new_blocks.iter().map(|b| b.hash()).collect();
+		self.store_blocks(new_blocks)?;
+
+		let mut imported_blocks = 0;
+		for block_hash in new_block_hashes.iter() {
+			if self.get_block(block_hash)?.is_some() {
+				imported_blocks += 1;
+			}
+		}
+		Ok(imported_blocks)
+	}
+
 	/// Update sidechain storage with blocks.
 	///
 	/// Blocks are iterated through one by one. In case more than one block per shard is included,
@@ -199,6 +286,9 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 			current_block_number = previous_block.number;
 			self.delete_block(&mut batch, &previous_block.hash, &current_block_number, shard);
 		}
+		// Remove pruning cursor.
+		SidechainDB::delete_to_batch(&mut batch, (PRUNED_UNTIL_KEY, *shard));
+		self.pruned_until.remove(shard);
 		// Remove shard from list.
 		// STORED_SHARDS_KEY -> Vec<(Shard)>
 		self.shards.retain(|&x| x != *shard);
@@ -208,8 +298,9 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		self.db.write(batch)
 	}
 
-	/// purges a shard and its block from the db storage
-	/// FIXME: Add delete functions?
+	/// prunes the blocks of a shard up to and including the given block number
+	///
+	/// Purges the whole shard, if the given block number is the one of the last block.
 	pub fn prune_shard_from_block_number(
 		&mut self,
 		shard: &ShardIdentifierFor<SignedBlock>,
@@ -233,16 +324,88 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 				self.delete_block(&mut batch, &block_hash, &current_block_number, shard);
 				current_block_number -= 1;
 			}
+			// Move the pruning cursor.
+			if block_number > self.pruned_until(shard) {
+				SidechainDB::add_to_batch(&mut batch, (PRUNED_UNTIL_KEY, *shard), block_number);
+				self.pruned_until.insert(*shard, block_number);
+			}
 			// Update DB
 			self.db.write(batch)
 		}
 	}
 
+	/// Prunes all shards according to the given policy, but never the last finalized block of a
+	/// shard or any block after it. Shards without a known finalized block are not pruned.
+	pub fn prune<F>(&mut self, policy: &PruningPolicy, finalized_block_number: F)
+	where
+		F: Fn(&ShardIdentifierFor<SignedBlock>) -> Option<BlockNumber>,
+	{
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+		self.prune_at(policy, now, finalized_block_number)
+	}
+
+	/// Prunes all shards according to the given policy at the given time since the unix epoch,
+	/// see [`Self::prune`].
+	fn prune_at<F>(&mut self, policy: &PruningPolicy, now: Duration, finalized_block_number: F)
+	where
+		F: Fn(&ShardIdentifierFor<SignedBlock>) -> Option<BlockNumber>,
+	{
+		for shard in self.shards().clone() {
+			let threshold_block = match self.pruning_threshold(&shard, policy, now) {
+				Ok(Some(threshold_block)) => threshold_block,
+				Ok(None) => continue,
+				Err(e) => {
+					error!("Could not find blocks to prune in shard {:?}: {:?}", shard, e);
+					continue
+				},
+			};
+			let newest_prunable_block = match finalized_block_number(&shard)
+				.and_then(|finalized| finalized.checked_sub(1))
+			{
+				Some(newest_prunable_block) if newest_prunable_block > 0 => newest_prunable_block,
+				_ => {
+					debug!("No finalized blocks to prune in shard {:?}", shard);
+					continue
+				},
+			};
+			if let Err(e) = self
+				.prune_shard_from_block_number(&shard, threshold_block.min(newest_prunable_block))
+			{
+				error!("Could not purge shard {:?} due to {:?}", shard, e);
+			}
+		}
+	}
+
+	/// Number of the newest block of a shard, which is pruned according to the given policy at the
+	/// given time since the unix epoch. The last block of a shard is never pruned.
+	fn pruning_threshold(
+		&mut self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		policy: &PruningPolicy,
+		now: Duration,
+	) -> Result<Option<BlockNumber>> {
+		match policy {
+			PruningPolicy::KeepLastBlocks(number_of_blocks_to_keep) => Ok(self
+				.last_block_of_shard(shard)
+				.and_then(|last_block| {
+					last_block.number.checked_sub((*number_of_blocks_to_keep).max(1))
+				})
+				.filter(|threshold_block| *threshold_block > 0)),
+			PruningPolicy::KeepBlocksNewerThan(max_age) =>
+				self.newest_block_older_than(shard, now.saturating_sub(*max_age).as_millis() as u64),
+			PruningPolicy::Archive => Ok(None),
+		}
+	}
+
 	/// Prunes all shards except for the newest blocks (according to blocknumber).
 	pub fn prune_shards(&mut self, number_of_blocks_to_keep: BlockNumber) {
 		for shard in self.shards().clone() {
 			// get last block:
 			if let Some(last_block) = self.last_block_of_shard(&shard) {
+				if last_block.number <= number_of_blocks_to_keep {
+					// nothing to prune yet
+					continue
+				}
 				let threshold_block = last_block.number - number_of_blocks_to_keep;
 				if let Err(e) = self.prune_shard_from_block_number(&shard, threshold_block) {
 					error!("Could not purge shard {:?} due to {:?}", shard, e);
@@ -253,6 +416,11 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		}
 	}
 
+	/// Compacts the database, to reclaim the disk space of pruned blocks.
+	pub fn compact(&self) {
+		self.db.compact()
+	}
+
 	fn add_block_to_batch(
 		&mut self,
 		signed_block: &SignedBlock,
@@ -268,6 +436,10 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		} else {
 			self.shards.push(*shard);
 			*new_shard = true;
+			// A shard may start with a later block, e.g. when importing blocks.
+			let pruned_until = signed_block.block().header().block_number().saturating_sub(1);
+			SidechainDB::add_to_batch(batch, (PRUNED_UNTIL_KEY, *shard), pruned_until);
+			self.pruned_until.insert(*shard, pruned_until);
 		}
 		// Add block to DB batch.
 		self.add_last_block(batch, signed_block);
@@ -293,6 +465,45 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		true
 	}
 
+	/// Number of the newest block of a shard, which is older than the given timestamp, excluding
+	/// the last block.
+	///
+	/// Walks forward from the pruning cursor, so only the blocks to prune and the first block to
+	/// keep are read.
+	fn newest_block_older_than(
+		&mut self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		timestamp: u64,
+	) -> Result<Option<BlockNumber>> {
+		let last_block = self.get_last_block_of_shard(shard)?;
+		let mut newest_older_block = None;
+		let mut current_block_number = self.pruned_until(shard) + 1;
+		while current_block_number < last_block.number {
+			match self.get_block_hash(shard, current_block_number)? {
+				Some(block_hash) => {
+					let block =
+						self.get_block(&block_hash)?.ok_or(Error::BlockNotFound(block_hash))?;
+					if block.block().block_data().timestamp() >= timestamp {
+						break
+					}
+					newest_older_block = Some(current_block_number);
+				},
+				// Pruned before the cursor was stored, skip it from now on.
+				None if newest_older_block.is_none() => {
+					self.pruned_until.insert(*shard, current_block_number);
+				},
+				None => {},
+			}
+			current_block_number += 1;
+		}
+		Ok(newest_older_block)
+	}
+
+	/// Number of the newest pruned block of a shard, 0 if none has been pruned.
+	fn pruned_until(&self, shard: &ShardIdentifierFor<SignedBlock>) -> BlockNumber {
+		self.pruned_until.get(shard).copied().unwrap_or_default()
+	}
+
 	/// Implementations of helper functions, not meant for pub use
 	/// gets the previous block of given shard and block number, if there is one.
 	fn get_previous_block(
@@ -392,12 +603,18 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 #[cfg(test)]
 mod test {
 	use super::*;
-	use crate::test_utils::{
-		create_signed_block_with_shard as create_signed_block, create_temp_dir, get_storage,
+	use crate::{
+		pruning::confirmed_block_number,
+		test_utils::{
+			block_author, create_signed_block_with_shard as create_signed_block,
+			create_signed_block_with_timestamp, create_temp_dir, default_shard, get_storage,
+		},
 	};
-	use itp_types::ShardIdentifier;
+	use itp_settings::worker::BLOCK_NUMBER_FINALIZATION_DIFF;
+	use itp_types::{parentchain::SidechainBlockConfirmation, ShardIdentifier};
 	use its_primitives::{traits::SignedBlock as SignedBlockT, types::SignedBlock};
-	use sp_core::H256;
+	use sp_core::{crypto::Pair, ed25519, H256};
+	use std::assert_matches::assert_matches;
 
 	#[test]
 	fn load_shards_from_db_works() {
@@ -1177,4 +1394,250 @@ mod test {
 			assert!(updated_sidechain_db.get_block(&block_two_s.hash()).unwrap().is_none());
 		}
 	}
+
+	#[test]
+	fn prune_shards_keeps_all_blocks_if_less_than_limit() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let block_one = create_signed_block(1, shard);
+		let block_two = create_signed_block(2, shard);
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(vec![block_one.clone(), block_two.clone()]).unwrap();
+
+		sidechain_db.prune_shards(5);
+
+		assert_eq!(sidechain_db.get_block(&block_one.hash()).unwrap().unwrap(), block_one);
+		assert_eq!(sidechain_db.get_block(&block_two.hash()).unwrap().unwrap(), block_two);
+	}
+
+	fn all_blocks_finalized(_shard: &ShardIdentifier) -> Option<BlockNumber> {
+		Some(BlockNumber::MAX)
+	}
+
+	fn keep_blocks_newer_than(millis: u64) -> PruningPolicy {
+		PruningPolicy::KeepBlocksNewerThan(Duration::from_millis(millis))
+	}
+
+	#[test]
+	fn prune_by_age_keeps_newer_blocks() {
+		let temp_dir = create_temp_dir();
+		let blocks: Vec<SignedBlock> =
+			(1..=4).map(|n| create_signed_block_with_timestamp(n, n * 1_000)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+
+		sidechain_db.prune_at(
+			&keep_blocks_newer_than(10_000),
+			Duration::from_millis(12_500),
+			all_blocks_finalized,
+		);
+
+		assert!(sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		assert!(sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert!(sidechain_db.get_block_hash(&default_shard(), 2).unwrap().is_none());
+		assert_eq!(sidechain_db.get_block(&blocks[2].hash()).unwrap().unwrap(), blocks[2]);
+		assert_eq!(sidechain_db.get_block(&blocks[3].hash()).unwrap().unwrap(), blocks[3]);
+	}
+
+	#[test]
+	fn prune_by_age_keeps_last_block() {
+		let temp_dir = create_temp_dir();
+		let blocks: Vec<SignedBlock> =
+			(1..=3).map(|n| create_signed_block_with_timestamp(n, n * 1_000)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+
+		sidechain_db.prune_at(
+			&keep_blocks_newer_than(10_000),
+			Duration::from_millis(20_000),
+			all_blocks_finalized,
+		);
+
+		assert!(sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert_eq!(sidechain_db.get_block(&blocks[2].hash()).unwrap().unwrap(), blocks[2]);
+		assert_eq!(sidechain_db.last_block_of_shard(&default_shard()).unwrap().number, 3);
+	}
+
+	#[test]
+	fn prune_in_archive_mode_keeps_all_blocks() {
+		let temp_dir = create_temp_dir();
+		let blocks: Vec<SignedBlock> =
+			(1..=3).map(|n| create_signed_block_with_timestamp(n, n * 1_000)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+
+		sidechain_db.prune(&PruningPolicy::Archive, all_blocks_finalized);
+		sidechain_db.compact();
+
+		for block in blocks {
+			assert_eq!(sidechain_db.get_block(&block.hash()).unwrap().unwrap(), block);
+		}
+	}
+
+	#[test]
+	fn get_blocks_by_number_skips_pruned_blocks() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<SignedBlock> = (1..=4).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+		sidechain_db.prune_shards(3);
+
+		let fetched_blocks = sidechain_db.get_blocks_by_number(&shard, 1, 3).unwrap();
+
+		assert_eq!(fetched_blocks, blocks[1..3].to_vec());
+		assert_eq!(
+			sidechain_db.get_blocks_by_number(&shard, 0, BlockNumber::MAX).unwrap(),
+			blocks[1..].to_vec()
+		);
+		assert!(sidechain_db
+			.get_blocks_by_number(&H256::from_low_u64_be(2), 0, BlockNumber::MAX)
+			.unwrap()
+			.is_empty());
+	}
+
+	#[test]
+	fn import_blocks_skips_known_blocks() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<SignedBlock> = (1..=4).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks[..2].to_vec()).unwrap();
+
+		let authorities = [block_author().public()];
+
+		assert_eq!(sidechain_db.import_blocks(blocks.clone(), &shard, &authorities).unwrap(), 2);
+		assert_eq!(sidechain_db.import_blocks(blocks.clone(), &shard, &authorities).unwrap(), 0);
+
+		assert_eq!(sidechain_db.get_blocks_by_number(&shard, 1, 4).unwrap(), blocks);
+	}
+
+	#[test]
+	fn import_blocks_rejects_blocks_of_other_shard() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks =
+			vec![create_signed_block(1, shard), create_signed_block(1, H256::from_low_u64_be(2))];
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+
+		assert_matches!(
+			sidechain_db.import_blocks(blocks, &shard, &[block_author().public()]),
+			Err(Error::ImportedBlockOfOtherShard(_))
+		);
+		assert!(sidechain_db.last_block_of_shard(&shard).is_none());
+	}
+
+	#[test]
+	fn import_blocks_rejects_invalid_signature() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let block = create_signed_block(1, shard);
+		let other_block = create_signed_block(2, shard);
+		let forged_block = SignedBlock::new(block.block().clone(), other_block.signature().clone());
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+
+		assert_matches!(
+			sidechain_db.import_blocks(vec![forged_block], &shard, &[block_author().public()]),
+			Err(Error::ImportedBlockSignatureInvalid(_))
+		);
+		assert!(sidechain_db.last_block_of_shard(&shard).is_none());
+	}
+
+	#[test]
+	fn import_blocks_rejects_blocks_of_unknown_author() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<SignedBlock> = (1..=2).map(|n| create_signed_block(n, shard)).collect();
+		let other_authority = ed25519::Pair::from_string("//Bob", None).unwrap().public();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+
+		assert_matches!(
+			sidechain_db.import_blocks(blocks, &shard, &[other_authority]),
+			Err(Error::ImportedBlockAuthorUnknown(_))
+		);
+		assert!(sidechain_db.last_block_of_shard(&shard).is_none());
+	}
+
+	#[test]
+	fn prune_never_prunes_finalized_blocks() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<SignedBlock> = (1..=6).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+
+		sidechain_db.prune(&PruningPolicy::KeepLastBlocks(1), |_| Some(3));
+
+		assert!(sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		assert!(sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert_eq!(sidechain_db.get_blocks_by_number(&shard, 1, 6).unwrap(), blocks[2..].to_vec());
+	}
+
+	#[test]
+	fn prune_keeps_blocks_from_the_confirmed_block_on() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<SignedBlock> = (1..=25).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+		// Confirmation of block 3, as sent by the `BlockImportConfirmationHandler`.
+		let confirmation = SidechainBlockConfirmation {
+			block_number: 3 + BLOCK_NUMBER_FINALIZATION_DIFF,
+			block_header_hash: blocks[2].block().header().hash(),
+		};
+
+		sidechain_db.prune(&PruningPolicy::KeepLastBlocks(1), |_| {
+			Some(confirmed_block_number(&confirmation))
+		});
+
+		assert_eq!(sidechain_db.get_blocks_by_number(&shard, 1, 25).unwrap(), blocks[2..].to_vec());
+	}
+
+	#[test]
+	fn prune_keeps_shards_without_finalized_block() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let blocks: Vec<SignedBlock> = (1..=6).map(|n| create_signed_block(n, shard)).collect();
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(blocks.clone()).unwrap();
+
+		sidechain_db.prune(&PruningPolicy::KeepLastBlocks(1), |_| None);
+		sidechain_db.prune(&PruningPolicy::KeepLastBlocks(1), |_| Some(1));
+
+		assert_eq!(sidechain_db.get_blocks_by_number(&shard, 1, 6).unwrap(), blocks);
+	}
+
+	#[test]
+	fn prune_by_age_continues_from_stored_cursor() {
+		let temp_dir = create_temp_dir();
+		let blocks: Vec<SignedBlock> =
+			(1..=5).map(|n| create_signed_block_with_timestamp(n, n * 1_000)).collect();
+		{
+			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			sidechain_db.store_blocks(blocks.clone()).unwrap();
+			assert_eq!(sidechain_db.pruned_until(&default_shard()), 0);
+
+			sidechain_db.prune_at(
+				&keep_blocks_newer_than(10_000),
+				Duration::from_millis(12_500),
+				all_blocks_finalized,
+			);
+			assert_eq!(sidechain_db.pruned_until(&default_shard()), 2);
+		}
+
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		assert_eq!(sidechain_db.pruned_until(&default_shard()), 2);
+
+		sidechain_db.prune_at(
+			&keep_blocks_newer_than(10_000),
+			Duration::from_millis(13_500),
+			all_blocks_finalized,
+		);
+
+		assert_eq!(sidechain_db.pruned_until(&default_shard()), 3);
+		assert_eq!(
+			sidechain_db.get_blocks_by_number(&default_shard(), 1, 5).unwrap(),
+			blocks[3..].to_vec()
+		);
+	}
 }
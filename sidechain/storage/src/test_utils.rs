@@ -43,6 +43,11 @@ pub fn get_storage(path: PathBuf) -> SidechainStorage<SignedSidechainBlock> {
 	SidechainStorage::<SignedSidechainBlock>::load_from_base_path(path).unwrap()
 }
 
+/// Author and signer of the created blocks.
+pub fn block_author() -> ed25519::Pair {
+	ed25519::Pair::from_string("//Alice", None).unwrap()
+}
+
 pub fn default_shard() -> ShardIdentifier {
 	ShardIdentifier::default()
 }
@@ -59,6 +64,7 @@ pub fn create_signed_block_with_parenthash(
 	let block_data = default_block_data_builder().build();
 
 	SidechainBlockBuilder::default()
+		.with_signer(block_author())
 		.with_header(header)
 		.with_block_data(block_data)
 		.build_signed()
@@ -76,6 +82,22 @@ pub fn create_signed_block_with_shard(
 	let block_data = default_block_data_builder().build();
 
 	SidechainBlockBuilder::default()
+		.with_signer(block_author())
+		.with_header(header)
+		.with_block_data(block_data)
+		.build_signed()
+}
+
+pub fn create_signed_block_with_timestamp(
+	block_number: u64,
+	timestamp: u64,
+) -> SignedSidechainBlock {
+	let header = default_header_builder().with_block_number(block_number).build();
+
+	let block_data = default_block_data_builder().with_timestamp(timestamp).build();
+
+	SidechainBlockBuilder::default()
+		.with_signer(block_author())
 		.with_header(header)
 		.with_block_data(block_data)
 		.build_signed()
@@ -92,5 +114,5 @@ fn default_block_data_builder() -> SidechainBlockDataBuilder {
 	SidechainBlockDataBuilder::default()
 		.with_timestamp(now_as_millis())
 		.with_layer_one_head(H256::random())
-		.with_signer(ed25519::Pair::from_string("//Alice", None).unwrap())
+		.with_signer(block_author())
 }